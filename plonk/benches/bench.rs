@@ -14,26 +14,24 @@ use ark_bn254::{Bn254, Fr as Fr254};
 use ark_bw6_761::{Fr as Fr761, BW6_761};
 use ark_ff::PrimeField;
 use jf_plonk::{
-    circuit::{Circuit, PlonkCircuit},
+    circuit::{Arithmetization, Circuit, PlonkCircuit},
     errors::PlonkError,
     proof_system::{PlonkKzgSnark, Snark},
     transcript::StandardTranscript,
     PlonkType,
 };
-
 const NUM_REPETITIONS: usize = 10;
 const NUM_GATES_LARGE: usize = 32768;
 const NUM_GATES_SMALL: usize = 8192;
+const BATCH_VERIFY_CHUNK_SIZE: usize = 100;
 
 fn gen_circuit_for_bench<F: PrimeField>(
     num_gates: usize,
     plonk_type: PlonkType,
 ) -> Result<PlonkCircuit<F>, PlonkError> {
     let range_bit_len = 8;
-    let mut cs: PlonkCircuit<F> = match plonk_type {
-        PlonkType::TurboPlonk => PlonkCircuit::new_turbo_plonk(),
-        PlonkType::UltraPlonk => PlonkCircuit::new_ultra_plonk(range_bit_len),
-    };
+    let mut cs: PlonkCircuit<F> =
+        PlonkCircuit::with_capacity(num_gates, num_gates, plonk_type, Some(range_bit_len))?;
     let mut a = cs.zero();
     for _ in 0..num_gates - 10 {
         a = cs.add(a, cs.one())?;
@@ -49,7 +47,7 @@ macro_rules! plonk_prove_bench {
         let rng = &mut ark_std::test_rng();
         let cs = gen_circuit_for_bench::<$bench_field>($num_gates, $bench_plonk_type).unwrap();
 
-        let max_degree = $num_gates + 2;
+        let max_degree = cs.srs_size().unwrap();
         let srs = PlonkKzgSnark::<$bench_curve>::universal_setup(max_degree, rng).unwrap();
 
         let (pk, _) = PlonkKzgSnark::<$bench_curve>::preprocess(&srs, &cs).unwrap();
@@ -69,6 +67,23 @@ macro_rules! plonk_prove_bench {
             stringify!($bench_plonk_type),
             start.elapsed().as_nanos() / NUM_REPETITIONS as u128 / $num_gates as u128
         );
+
+        #[cfg(feature = "std")]
+        {
+            let (_, profile) =
+                PlonkKzgSnark::<$bench_curve>::prove_with_profile::<_, _, StandardTranscript>(
+                    rng, &cs, &pk, None,
+                )
+                .unwrap();
+            println!(
+                "proving time breakdown for {}, {}: fft {:?}, msm {:?}, poly eval {:?}",
+                stringify!($bench_curve),
+                stringify!($bench_plonk_type),
+                profile.fft_time,
+                profile.msm_time,
+                profile.poly_eval_time
+            );
+        }
     };
 }
 
@@ -88,7 +103,7 @@ macro_rules! plonk_verify_bench {
         let rng = &mut ark_std::test_rng();
         let cs = gen_circuit_for_bench::<$bench_field>($num_gates, $bench_plonk_type).unwrap();
 
-        let max_degree = $num_gates + 2;
+        let max_degree = cs.srs_size().unwrap();
         let srs = PlonkKzgSnark::<$bench_curve>::universal_setup(max_degree, rng).unwrap();
 
         let (pk, vk) = PlonkKzgSnark::<$bench_curve>::preprocess(&srs, &cs).unwrap();
@@ -163,6 +178,28 @@ macro_rules! plonk_batch_verify_bench {
             stringify!($num_proofs),
             start.elapsed().as_nanos() / NUM_REPETITIONS as u128 / $num_proofs as u128
         );
+
+        let start = ark_std::time::Instant::now();
+
+        for _ in 0..NUM_REPETITIONS {
+            let _ = PlonkKzgSnark::<$bench_curve>::batch_verify_parallel::<StandardTranscript>(
+                &vks,
+                &public_inputs_ref[..],
+                &proofs_ref,
+                &vec![None; vks.len()],
+                BATCH_VERIFY_CHUNK_SIZE,
+            )
+            .unwrap();
+        }
+
+        println!(
+            "parallel batch verifying time for {}, {}, {} proofs, chunks of {}: {} ns/proof",
+            stringify!($bench_curve),
+            stringify!($bench_plonk_type),
+            stringify!($num_proofs),
+            BATCH_VERIFY_CHUNK_SIZE,
+            start.elapsed().as_nanos() / NUM_REPETITIONS as u128 / $num_proofs as u128
+        );
     };
 }
 
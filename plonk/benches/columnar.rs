@@ -0,0 +1,177 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+// Benchmarks of the verification APIs counting the allocations, kept apart
+// from the timings of `bench.rs` as the counting allocator slows down every
+// allocation. Run:
+//     RAYON_NUM_THREADS=N cargo bench --bench plonk-columnar-benches
+// where N is the number of threads you want to use (N = 1 for single-thread).
+
+use ark_bls12_381::{Bls12_381, Fr as Fr381};
+use ark_bn254::{Bn254, Fr as Fr254};
+use ark_ff::PrimeField;
+use jf_plonk::{
+    circuit::{Circuit, PlonkCircuit},
+    errors::PlonkError,
+    proof_system::{PlonkKzgSnark, Snark},
+    transcript::StandardTranscript,
+    PlonkType,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// Allocator counting the allocations, to compare the allocations of the
+// verification APIs.
+struct CountingAllocator;
+
+static NUM_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        NUM_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const NUM_REPETITIONS: usize = 10;
+
+// The number of allocations of `f`.
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let start_allocations = NUM_ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    NUM_ALLOCATIONS.load(Ordering::Relaxed) - start_allocations
+}
+
+fn gen_circuit_for_bench<F: PrimeField>(
+    num_gates: usize,
+    plonk_type: PlonkType,
+) -> Result<PlonkCircuit<F>, PlonkError> {
+    let range_bit_len = 8;
+    let mut cs: PlonkCircuit<F> =
+        PlonkCircuit::with_capacity(num_gates, num_gates, plonk_type, Some(range_bit_len))?;
+    let mut a = cs.zero();
+    for _ in 0..num_gates - 10 {
+        a = cs.add(a, cs.one())?;
+    }
+    // Finalize the circuit.
+    cs.finalize_for_arithmetization()?;
+
+    Ok(cs)
+}
+
+macro_rules! plonk_batch_verify_columnar_bench {
+    ($bench_curve:ty, $bench_field:ty, $bench_plonk_type:expr, $num_proofs:expr) => {
+        let rng = &mut ark_std::test_rng();
+        let cs = gen_circuit_for_bench::<$bench_field>(1024, $bench_plonk_type).unwrap();
+
+        let max_degree = 1026;
+        let srs = PlonkKzgSnark::<$bench_curve>::universal_setup(max_degree, rng).unwrap();
+
+        let (pk, vk) = PlonkKzgSnark::<$bench_curve>::preprocess(&srs, &cs).unwrap();
+
+        let proof =
+            PlonkKzgSnark::<$bench_curve>::prove::<_, _, StandardTranscript>(rng, &cs, &pk, None)
+                .unwrap();
+
+        // the columnar buffer
+        let vks = vec![vk; $num_proofs];
+        let public_inputs: Vec<$bench_field> = vec![];
+        let proofs = vec![proof; $num_proofs];
+        let extra_msgs = vec![None; $num_proofs];
+
+        // batch verification from the columnar buffer, converted to slices of
+        // references
+        let verify_from_slices = || {
+            let vks_ref: Vec<_> = vks.iter().collect();
+            // the bench circuit has no public input
+            let public_inputs_ref = vec![&public_inputs[..]; vks.len()];
+            let proofs_ref: Vec<_> = proofs.iter().collect();
+            PlonkKzgSnark::<$bench_curve>::batch_verify::<StandardTranscript>(
+                &vks_ref,
+                &public_inputs_ref,
+                &proofs_ref,
+                &extra_msgs,
+            )
+            .unwrap();
+        };
+        let start_allocations = NUM_ALLOCATIONS.load(Ordering::Relaxed);
+        let start = ark_std::time::Instant::now();
+        for _ in 0..NUM_REPETITIONS {
+            verify_from_slices();
+        }
+        let elapsed = start.elapsed();
+        let allocations = NUM_ALLOCATIONS.load(Ordering::Relaxed) - start_allocations;
+        println!(
+            "batch verifying time for {}, {}, {} proofs from slices: {} ns/proof, {} allocations/batch",
+            stringify!($bench_curve),
+            stringify!($bench_plonk_type),
+            stringify!($num_proofs),
+            elapsed.as_nanos() / NUM_REPETITIONS as u128 / $num_proofs as u128,
+            allocations / NUM_REPETITIONS
+        );
+
+        // batch verification directly from the columnar buffer
+        let verify_from_columns = || {
+            PlonkKzgSnark::<$bench_curve>::batch_verify_columnar::<StandardTranscript>(
+                &vks,
+                &public_inputs,
+                &proofs,
+                &extra_msgs,
+            )
+            .unwrap();
+        };
+        let start_allocations = NUM_ALLOCATIONS.load(Ordering::Relaxed);
+        let start = ark_std::time::Instant::now();
+        for _ in 0..NUM_REPETITIONS {
+            verify_from_columns();
+        }
+        let elapsed = start.elapsed();
+        let allocations = NUM_ALLOCATIONS.load(Ordering::Relaxed) - start_allocations;
+        println!(
+            "batch verifying time for {}, {}, {} proofs from columns: {} ns/proof, {} allocations/batch",
+            stringify!($bench_curve),
+            stringify!($bench_plonk_type),
+            stringify!($num_proofs),
+            elapsed.as_nanos() / NUM_REPETITIONS as u128 / $num_proofs as u128,
+            allocations / NUM_REPETITIONS
+        );
+
+        // the columnar verification does not build the slices of references,
+        // and neither copies the proofs. The allocations of the parallel
+        // iterators depend on how the work is split between the threads, so
+        // they are compared on a single thread.
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let slices_allocations = pool.install(|| count_allocations(verify_from_slices));
+        let columnar_allocations = pool.install(|| count_allocations(verify_from_columns));
+        assert!(
+            columnar_allocations < slices_allocations,
+            "{} allocations from columns, {} from slices",
+            columnar_allocations,
+            slices_allocations
+        );
+    };
+}
+
+fn bench_batch_verify_columnar() {
+    plonk_batch_verify_columnar_bench!(Bls12_381, Fr381, PlonkType::TurboPlonk, 1000);
+    plonk_batch_verify_columnar_bench!(Bn254, Fr254, PlonkType::UltraPlonk, 1000);
+}
+
+fn main() {
+    bench_batch_verify_columnar();
+}
@@ -9,13 +9,14 @@ use super::{Arithmetization, Circuit, GateId, Variable, WireId};
 use crate::{
     circuit::{gates::*, SortedLookupVecAndPolys},
     constants::{compute_coset_representatives, GATE_WIDTH, N_MUL_SELECTORS},
-    errors::{CircuitError::*, PlonkError},
+    errors::{CircuitError::*, GateError, PlonkError},
     MergeableCircuitType, PlonkType,
 };
 use ark_ff::{FftField, PrimeField};
 use ark_poly::{
     domain::Radix2EvaluationDomain, univariate::DensePolynomial, EvaluationDomain, UVPolynomial,
 };
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, Write};
 use ark_std::{
     boxed::Box,
     cmp::max,
@@ -61,6 +62,28 @@ impl PlonkParams {
     }
 }
 
+/// Size statistics of a circuit, to budget its proofs before preprocessing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// The number of gates, padded to the domain size once the circuit is
+    /// finalized.
+    pub num_gates: usize,
+    /// The number of variables.
+    pub num_vars: usize,
+    /// The number of public inputs.
+    pub num_inputs: usize,
+    /// The number of range-checked variables, zero if not support lookup,
+    /// and padded to the domain size once the circuit is finalized.
+    pub num_range_checks: usize,
+    /// The number of lookup gates, each holding a lookup and/or an entry of
+    /// the lookup table, zero if not support lookup.
+    pub num_lookup_gates: usize,
+    /// The size of the evaluation domain of the finalized circuit: the next
+    /// power of two of the number of gates, or with lookup, of the number of
+    /// slots needed by the range and lookup tables if larger.
+    pub domain_size: usize,
+}
+
 /// A specific Plonk circuit instantiation.
 #[derive(Debug, Clone)]
 pub struct PlonkCircuit<F>
@@ -113,6 +136,13 @@ where
     /// For each inserted table, the 1st value is the start id of the table,
     /// the 2nd values is the length of the table.
     table_gate_ids: Vec<(GateId, usize)>,
+
+    /// The variables shared by the constant variables of the same value, if
+    /// constants are deduplicated.
+    constant_vars: Option<HashMap<F, Variable>>,
+
+    /// The registered custom gates, each with its own selector column.
+    custom_gates: Vec<CustomGateSpec<F>>,
 }
 
 impl<F: FftField> Default for PlonkCircuit<F> {
@@ -147,6 +177,8 @@ impl<F: FftField> PlonkCircuit<F> {
             plonk_params,
             num_table_elems: 0,
             table_gate_ids: vec![],
+            constant_vars: None,
+            custom_gates: vec![],
         };
         // Constrain variables `0`/`1` to have value 0/1.
         circuit.constant_gate(0, zero).unwrap(); // safe unwrap
@@ -166,6 +198,101 @@ impl<F: FftField> PlonkCircuit<F> {
         Self::new(plonk_params)
     }
 
+    /// Construct a new circuit of type `plonk_type`, with room for
+    /// `num_gates` gates and `num_vars` variables before any reallocation.
+    /// `range_bit_len` is the range bit length of UltraPlonk circuits, as in
+    /// [`Self::new_ultra_plonk`], and is ignored for TurboPlonk circuits.
+    /// Return error if `range_bit_len` is missing for UltraPlonk.
+    pub fn with_capacity(
+        num_gates: usize,
+        num_vars: usize,
+        plonk_type: PlonkType,
+        range_bit_len: Option<usize>,
+    ) -> Result<Self, PlonkError> {
+        let plonk_params = PlonkParams::init(plonk_type, range_bit_len)?;
+        let mut circuit = Self::new(plonk_params);
+        circuit.reserve_gates(num_gates);
+        circuit.witness.reserve(num_vars);
+        Ok(circuit)
+    }
+
+    /// Deduplicate the constant variables created from now on: the constant
+    /// variables of the same value, including the zero and one variables,
+    /// are a single variable constrained by a single constant gate, which
+    /// reduces the numbers of variables and gates of circuits using many
+    /// constants. The constant variables created before are left untouched.
+    pub fn dedup_constants(&mut self) {
+        if self.constant_vars.is_none() {
+            let mut constant_vars = HashMap::new();
+            constant_vars.insert(F::zero(), self.zero());
+            constant_vars.insert(F::one(), self.one());
+            self.constant_vars = Some(constant_vars);
+        }
+    }
+
+    /// Register the custom gate `spec`, with its own selector column, and
+    /// return its index to be used with [`Self::custom_gate`]. Preprocessing
+    /// the circuit commits to the selector in the verifying key, which also
+    /// holds `spec`.
+    /// Return error if the circuit is not a TurboPlonk circuit or is
+    /// finalized.
+    pub fn register_custom_gate(&mut self, spec: CustomGateSpec<F>) -> Result<usize, PlonkError> {
+        self.check_plonk_type(PlonkType::TurboPlonk)?;
+        self.check_finalize_flag(false)?;
+        self.custom_gates.push(spec);
+        Ok(self.custom_gates.len() - 1)
+    }
+
+    /// Constrain the wires `wire_vars` of a new gate with the identity of the
+    /// `id`-th registered custom gate.
+    /// Return error if the custom gate is not registered or if variables are
+    /// invalid.
+    pub fn custom_gate(
+        &mut self,
+        id: usize,
+        wire_vars: &[Variable; GATE_WIDTH + 1],
+    ) -> Result<(), PlonkError> {
+        if id >= self.custom_gates.len() {
+            return Err(ParameterError(format!("the custom gate {} is not registered", id)).into());
+        }
+        for &var in wire_vars.iter() {
+            self.check_var_bound(var)?;
+        }
+        self.insert_gate(wire_vars, Box::new(CustomGate(id)))
+    }
+
+    /// Check the constraints of the circuit against its witness and the public
+    /// input `pub_input`, without generating any proof, and return the index
+    /// of the first unsatisfied constraint together with the kind of the
+    /// violation and the values involved. The index of a range gate is its
+    /// index among the range gates, and a mismatched public input length is
+    /// reported at index 0.
+    ///
+    /// This is a debugging tool for provers, only available with the
+    /// `test_apis` feature.
+    #[cfg(any(test, feature = "test_apis"))]
+    pub fn debug_circuit_satisfiability(
+        &self,
+        pub_input: &[F],
+    ) -> Result<(), (usize, GateError<F>)> {
+        if pub_input.len() != self.num_inputs() {
+            return Err((
+                0,
+                GateError::PubInputLenMismatch(pub_input.len(), self.num_inputs()),
+            ));
+        }
+        self.find_unsatisfied_gate(pub_input)
+    }
+
+    /// Reserve room for at least `additional` more gates, so that inserting
+    /// them does not reallocate the gate and wire vectors.
+    pub fn reserve_gates(&mut self, additional: usize) {
+        self.gates.reserve(additional);
+        for wire_variable in self.wire_variables.iter_mut().take(GATE_WIDTH + 1) {
+            wire_variable.reserve(additional);
+        }
+    }
+
     /// Insert a general (algebraic) gate
     /// * `wire_vars` - wire variables. Each of these variables must be in range
     /// * `gate` - specific gate to be inserted
@@ -231,6 +358,134 @@ impl<F: FftField> PlonkCircuit<F> {
         &mut self.witness[idx]
     }
 
+    /// Replace the values of all the variables with `assignment`, indexed by
+    /// variable, e.g. computed by an external solver, without changing the
+    /// gates. A circuit built (and finalized) once can thus be proven for many
+    /// witnesses with the same proving key. The assignment includes the
+    /// constant variables, and is not checked against the gates here.
+    /// Return error if the length of `assignment` is not the number of
+    /// variables.
+    pub fn load_witness(&mut self, assignment: &[F]) -> Result<(), PlonkError> {
+        if assignment.len() != self.num_vars {
+            return Err(ParameterError(format!(
+                "the assignment of {} values != the number of variables {}",
+                assignment.len(),
+                self.num_vars
+            ))
+            .into());
+        }
+        self.witness = assignment.to_vec();
+        Ok(())
+    }
+
+    /// Append the gates and variables of `other`, built independently, to the
+    /// circuit, and return the variables of the circuit that the variables of
+    /// `other` became, indexed by the variables of `other`.
+    ///
+    /// Each pair `(var, other_var)` of `wire_map` shares the variable `var`
+    /// of the circuit with the variable `other_var` of `other`, which is
+    /// wired to the gates of `other` in its place and keeps the witness of
+    /// `var`. The zero and one variables are shared as well. The public
+    /// inputs of `other` follow the ones of the circuit, and its custom gates
+    /// are registered after the ones of the circuit.
+    /// Return error if the circuits are finalized, if their Plonk types or
+    /// range bit lengths differ, if both have lookup tables, or if a variable
+    /// of `wire_map` is invalid.
+    pub fn merge(
+        &mut self,
+        other: PlonkCircuit<F>,
+        wire_map: &[(Variable, Variable)],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_finalize_flag(false)?;
+        other.check_finalize_flag(false)?;
+        if self.plonk_params.plonk_type != other.plonk_params.plonk_type
+            || self.plonk_params.range_bit_len != other.plonk_params.range_bit_len
+        {
+            return Err(ParameterError(format!(
+                "cannot merge circuits with different parameters: {:?}, {:?}",
+                self.plonk_params, other.plonk_params
+            ))
+            .into());
+        }
+        // the keys of the lookup tables are offset by the number of table
+        // elements in the constants of the gates
+        if self.num_table_elems != 0 && other.num_table_elems != 0 {
+            return Err(ParameterError(
+                "cannot merge circuits that both have lookup tables".to_string(),
+            )
+            .into());
+        }
+
+        let mut var_map: Vec<Option<Variable>> = vec![None; other.num_vars];
+        var_map[other.zero()] = Some(self.zero());
+        var_map[other.one()] = Some(self.one());
+        // the shared variables already mapped to another variable of the
+        // circuit, to be constrained equal to it
+        let mut equal_vars = vec![];
+        for &(var, other_var) in wire_map.iter() {
+            self.check_var_bound(var)?;
+            other.check_var_bound(other_var)?;
+            match var_map[other_var] {
+                Some(mapped_var) if mapped_var != var => equal_vars.push((mapped_var, var)),
+                Some(_) => (),
+                None => var_map[other_var] = Some(var),
+            }
+        }
+        let var_map: Vec<Variable> = var_map
+            .into_iter()
+            .zip(other.witness.iter())
+            .map(|(var, &val)| match var {
+                Some(var) => var,
+                None => {
+                    self.witness.push(val);
+                    self.num_vars += 1;
+                    self.num_vars - 1
+                },
+            })
+            .collect();
+
+        let gate_offset = self.num_gates();
+        let custom_gate_offset = self.custom_gates.len();
+        self.reserve_gates(other.num_gates());
+        for (j, gate) in other.gates.iter().enumerate() {
+            let mut wire_vars = [0; GATE_WIDTH + 1];
+            for (i, wire_var) in wire_vars.iter_mut().enumerate() {
+                *wire_var = var_map[other.wire_variable(i, j)];
+            }
+            let gate: Box<dyn Gate<F>> = match gate.q_custom() {
+                Some(id) => Box::new(CustomGate(custom_gate_offset + id)),
+                None => (*gate).clone(),
+            };
+            self.insert_gate(&wire_vars, gate)?;
+        }
+        if self.support_lookup() {
+            let range_vars: Vec<Variable> = other.wire_variables[RANGE_WIRE_ID]
+                .iter()
+                .map(|&var| var_map[var])
+                .collect();
+            self.wire_variables[RANGE_WIRE_ID].extend(range_vars);
+        }
+        self.pub_input_gate_ids.extend(
+            other
+                .pub_input_gate_ids
+                .iter()
+                .map(|&gate_id| gate_offset + gate_id),
+        );
+        self.table_gate_ids.extend(
+            other
+                .table_gate_ids
+                .iter()
+                .map(|&(gate_id, len)| (gate_offset + gate_id, len)),
+        );
+        self.num_table_elems += other.num_table_elems;
+        self.custom_gates.extend(other.custom_gates);
+
+        for (a, b) in equal_vars {
+            self.equal_gate(a, b)?;
+        }
+        Ok(var_map)
+    }
+
     /// Get the mutable reference of the inserted table ids.
     pub(crate) fn table_gate_ids_mut(&mut self) -> &mut Vec<(GateId, usize)> {
         &mut self.table_gate_ids
@@ -261,6 +516,49 @@ impl<F: FftField> PlonkCircuit<F> {
     pub fn range_size(&self) -> Result<usize, PlonkError> {
         Ok(1 << self.range_bit_len()?)
     }
+
+    /// The size statistics of the circuit, which can be computed before its
+    /// finalization. See [`CircuitStats`].
+    pub fn stats(&self) -> Result<CircuitStats, PlonkError> {
+        let (num_range_checks, num_lookup_gates) = match self.support_lookup() {
+            true => (
+                self.wire_variables[RANGE_WIRE_ID].len(),
+                self.gates
+                    .iter()
+                    .filter(|gate| !gate.q_lookup().is_zero())
+                    .count(),
+            ),
+            false => (0, 0),
+        };
+        let domain_size = match self.is_finalized() {
+            true => self.eval_domain_size()?,
+            false => Radix2EvaluationDomain::<F>::new(self.num_slots_needed()?)
+                .ok_or(PlonkError::DomainCreationError)?
+                .size(),
+        };
+        Ok(CircuitStats {
+            num_gates: self.num_gates(),
+            num_vars: self.num_vars(),
+            num_inputs: self.num_inputs(),
+            num_range_checks,
+            num_lookup_gates,
+            domain_size,
+        })
+    }
+
+    // The number of slots of the evaluation domain needed by the gates, and by
+    // the range gates and lookup gates, which need to have separate slots.
+    fn num_slots_needed(&self) -> Result<usize, PlonkError> {
+        Ok(match self.support_lookup() {
+            false => self.num_gates(),
+            true => max(
+                self.num_gates(),
+                max(self.range_size()?, self.wire_variables[RANGE_WIRE_ID].len())
+                    + self.num_table_elems()
+                    + 1,
+            ),
+        })
+    }
 }
 
 impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
@@ -294,62 +592,23 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
         if pub_input.len() != self.num_inputs() {
             return Err(PubInputLenMismatch(pub_input.len(), self.pub_input_gate_ids.len()).into());
         }
-        // Check public I/O gates
-        for (i, gate_id) in self.pub_input_gate_ids.iter().enumerate() {
-            let pi = pub_input[i];
-            self.check_gate(*gate_id, &pi)?;
-        }
-        // Check rest of the gates
-        for gate_id in 0..self.num_gates() {
-            if !self.is_io_gate(gate_id) {
-                let pi = F::zero();
-                self.check_gate(gate_id, &pi)?;
-            }
-        }
-        // Check range/lookup gates if the circuit supports lookup
-        if self.plonk_params.plonk_type == PlonkType::UltraPlonk {
-            // range gates
-            for idx in 0..self.wire_variables[RANGE_WIRE_ID].len() {
-                self.check_range_gate(idx)?
-            }
-            // key-value map lookup gates
-            let mut key_val_table = HashSet::new();
-            key_val_table.insert((F::zero(), F::zero(), F::zero()));
-            let mut num_table_elems: u32 = 0;
-            let q_lookup_vec = self.q_lookup();
-            for (gate_id, &q_lookup) in q_lookup_vec.iter().enumerate() {
-                if q_lookup != F::zero() {
-                    let key = F::from(num_table_elems);
-                    let val0 = self.witness(self.wire_variable(3, gate_id))?;
-                    let val1 = self.witness(self.wire_variable(4, gate_id))?;
-                    key_val_table.insert((key, val0, val1));
-                    num_table_elems += 1;
-                }
-            }
-            for (gate_id, &q_lookup) in q_lookup_vec.iter().enumerate() {
-                if q_lookup != F::zero() {
-                    let key = self.witness(self.wire_variable(0, gate_id))?;
-                    let val0 = self.witness(self.wire_variable(1, gate_id))?;
-                    let val1 = self.witness(self.wire_variable(2, gate_id))?;
-                    if !key_val_table.contains(&(key, val0, val1)) {
-                        return Err(GateCheckFailure(
-                            gate_id,
-                            format!(
-                                "Lookup gate failed: ({}, {}, {}) not in the table",
-                                key, val0, val1
-                            ),
-                        )
-                        .into());
-                    }
-                }
-            }
-        }
-        Ok(())
+        self.find_unsatisfied_gate(pub_input)
+            .map_err(|(gate_id, err)| GateCheckFailure(gate_id, err.to_string()).into())
     }
 
     fn create_constant_variable(&mut self, val: F) -> Result<Variable, PlonkError> {
+        if let Some(&var) = self
+            .constant_vars
+            .as_ref()
+            .and_then(|constant_vars| constant_vars.get(&val))
+        {
+            return Ok(var);
+        }
         let var = self.create_variable(val)?;
         self.constant_gate(var, val)?;
+        if let Some(constant_vars) = self.constant_vars.as_mut() {
+            constant_vars.insert(val, var);
+        }
         Ok(var)
     }
 
@@ -496,23 +755,68 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
 
 /// Private helper methods
 impl<F: FftField> PlonkCircuit<F> {
-    /// Check correctness of the idx-th range gate. Return an error if the
-    /// circuit does not support lookup.
-    fn check_range_gate(&self, idx: usize) -> Result<(), PlonkError> {
-        self.check_plonk_type(PlonkType::UltraPlonk)?;
-        if idx >= self.wire_variables[RANGE_WIRE_ID].len() {
-            return Err(IndexError.into());
+    /// Check correctness of the idx-th range gate of an UltraPlonk circuit,
+    /// whose range size is `range_size`. `idx` is guaranteed to be in the
+    /// range.
+    fn check_range_gate(&self, idx: usize, range_size: usize) -> Result<(), GateError<F>> {
+        let value = self.witness[self.wire_variables[RANGE_WIRE_ID][idx]];
+        if value >= F::from(range_size as u32) {
+            return Err(GateError::Range { value, range_size });
         }
-        let range_size = self.range_size()?;
-        if self.witness[self.wire_variables[RANGE_WIRE_ID][idx]] >= F::from(range_size as u32) {
-            return Err(GateCheckFailure(
-                idx,
-                format!(
-                    "Range gate failed: {} >= {}",
-                    self.witness[self.wire_variables[RANGE_WIRE_ID][idx]], range_size
-                ),
-            )
-            .into());
+        Ok(())
+    }
+
+    /// Check the constraints of the circuit against its witness and the public
+    /// input `pub_input`, whose length is guaranteed to match, and return the
+    /// index and the violation of the first unsatisfied constraint. The index
+    /// of a range gate is its index among the range gates.
+    fn find_unsatisfied_gate(&self, pub_input: &[F]) -> Result<(), (usize, GateError<F>)> {
+        // Check public I/O gates
+        for (i, gate_id) in self.pub_input_gate_ids.iter().enumerate() {
+            let pi = pub_input[i];
+            self.check_gate(*gate_id, &pi)
+                .map_err(|err| (*gate_id, err))?;
+        }
+        // Check rest of the gates
+        for gate_id in 0..self.num_gates() {
+            if !self.is_io_gate(gate_id) {
+                let pi = F::zero();
+                self.check_gate(gate_id, &pi)
+                    .map_err(|err| (gate_id, err))?;
+            }
+        }
+        // Check range/lookup gates if the circuit supports lookup
+        if self.plonk_params.plonk_type == PlonkType::UltraPlonk {
+            // range gates
+            let range_size = self.range_size().unwrap(); // safe unwrap
+            for idx in 0..self.wire_variables[RANGE_WIRE_ID].len() {
+                self.check_range_gate(idx, range_size)
+                    .map_err(|err| (idx, err))?;
+            }
+            // key-value map lookup gates
+            let mut key_val_table = HashSet::new();
+            key_val_table.insert((F::zero(), F::zero(), F::zero()));
+            let mut num_table_elems: u32 = 0;
+            let q_lookup_vec = self.q_lookup();
+            for (gate_id, &q_lookup) in q_lookup_vec.iter().enumerate() {
+                if q_lookup != F::zero() {
+                    let key = F::from(num_table_elems);
+                    let val0 = self.witness[self.wire_variable(3, gate_id)];
+                    let val1 = self.witness[self.wire_variable(4, gate_id)];
+                    key_val_table.insert((key, val0, val1));
+                    num_table_elems += 1;
+                }
+            }
+            for (gate_id, &q_lookup) in q_lookup_vec.iter().enumerate() {
+                if q_lookup != F::zero() {
+                    let key = self.witness[self.wire_variable(0, gate_id)];
+                    let val0 = self.witness[self.wire_variable(1, gate_id)];
+                    let val1 = self.witness[self.wire_variable(2, gate_id)];
+                    if !key_val_table.contains(&(key, val0, val1)) {
+                        return Err((gate_id, GateError::Lookup { key, val0, val1 }));
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -590,7 +894,7 @@ impl<F: FftField> PlonkCircuit<F> {
     ///           q_lc0 * w0 + q_lc1 * w1 + q_lc2 * w2 + q_lc3 * w3 +
     ///           q_hash0 * w0 + q_hash1 * w1 + q_hash2 * w2 + q_hash3 * w3 +
     ///           q_ecc * w0 * w1 * w2 * w3 * wo
-    fn check_gate(&self, gate_id: Variable, pub_input: &F) -> Result<(), PlonkError> {
+    fn check_gate(&self, gate_id: Variable, pub_input: &F) -> Result<(), GateError<F>> {
         // Compute wire values
 
         let w_vals: Vec<F> = (0..GATE_WIDTH + 1)
@@ -617,22 +921,19 @@ impl<F: FftField> PlonkCircuit<F> {
             + q_hash[1] * w_vals[1].pow(&[5])
             + q_hash[2] * w_vals[2].pow(&[5])
             + q_hash[3] * w_vals[3].pow(&[5])
-            + q_c;
+            + q_c
+            + self.gates[gate_id]
+                .q_custom()
+                .map_or(F::zero(), |i| self.custom_gates[i].evaluate(&w_vals));
         let gate_output = q_o * w_vals[4];
         if expected_gate_output != gate_output {
-            return Err(
-                GateCheckFailure(
-                    gate_id,
-                    format!(
-                        "gate: {:?}, wire values: {:?}, pub_input: {}, expected_gate_output: {}, gate_output: {}",
-                        self.gates[gate_id],
-                        w_vals,
-                        pub_input,
-                        expected_gate_output,
-                        gate_output
-                    )
-                )
-                .into());
+            return Err(GateError::Algebraic {
+                gate: self.gates[gate_id].name(),
+                wire_values: w_vals,
+                pub_input: *pub_input,
+                expected_gate_output,
+                gate_output,
+            });
         }
         Ok(())
     }
@@ -724,7 +1025,7 @@ impl<F: FftField> PlonkCircuit<F> {
     // j is the gate index. If gate `j` is a padded dummy gate, return zero
     // variable.
     #[inline]
-    fn wire_variable(&self, i: WireId, j: GateId) -> Variable {
+    pub(crate) fn wire_variable(&self, i: WireId, j: GateId) -> Variable {
         match j < self.wire_variables[i].len() {
             true => self.wire_variables[i][j],
             false => self.zero(),
@@ -788,9 +1089,24 @@ impl<F: FftField> PlonkCircuit<F> {
     fn q_lookup(&self) -> Vec<F> {
         self.gates.iter().map(|g| g.q_lookup()).collect()
     }
+    // getter for the selector of the `i`-th custom gate
+    #[inline]
+    fn q_custom(&self, i: usize) -> Vec<F> {
+        self.gates
+            .iter()
+            .map(|g| {
+                if g.q_custom() == Some(i) {
+                    F::one()
+                } else {
+                    F::zero()
+                }
+            })
+            .collect()
+    }
     // TODO: (alex) try return reference instead of expensive clone
     // getter for all selectors in the following order:
-    // q_lc, q_mul, q_hash, q_o, q_c, q_ecc, [q_lookup (if support lookup)]
+    // q_lc, q_mul, q_hash, q_o, q_c, q_ecc, [q_lookup (if support lookup)],
+    // [q_custom (for each custom gate)]
     #[inline]
     fn all_selectors(&self) -> Vec<Vec<F>> {
         let mut selectors = vec![];
@@ -806,6 +1122,9 @@ impl<F: FftField> PlonkCircuit<F> {
         if self.support_lookup() {
             selectors.push(self.q_lookup());
         }
+        for i in 0..self.custom_gates.len() {
+            selectors.push(self.q_custom(i));
+        }
         selectors
     }
 }
@@ -871,17 +1190,8 @@ impl<F: PrimeField> PlonkCircuit<F> {
         if self.is_finalized() {
             return Ok(());
         }
-        let num_slots_needed = match self.support_lookup() {
-            false => self.num_gates(),
-            true => max(
-                self.num_gates(),
-                max(self.range_size()?, self.wire_variables[RANGE_WIRE_ID].len())
-                    + self.num_table_elems()
-                    + 1,
-            ), // range gates and lookup gates need to have separate slots
-        };
-        self.eval_domain =
-            Radix2EvaluationDomain::new(num_slots_needed).ok_or(PlonkError::DomainCreationError)?;
+        self.eval_domain = Radix2EvaluationDomain::new(self.num_slots_needed()?)
+            .ok_or(PlonkError::DomainCreationError)?;
         self.pad()?;
         self.rearrange_gates()?;
         self.compute_wire_permutation();
@@ -950,7 +1260,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
     /// Both circuits should have been finalized before.
     /// The method only supports TurboPlonk circuits.
     #[allow(dead_code)]
-    pub(crate) fn merge(&self, other: &Self) -> Result<Self, PlonkError> {
+    pub(crate) fn merge_mergeable_circuits(&self, other: &Self) -> Result<Self, PlonkError> {
         self.check_finalize_flag(true)?;
         other.check_finalize_flag(true)?;
         if self.eval_domain_size()? != other.eval_domain_size()? {
@@ -969,6 +1279,12 @@ impl<F: PrimeField> PlonkCircuit<F> {
             )
             .into());
         }
+        if !self.custom_gates.is_empty() || !other.custom_gates.is_empty() {
+            return Err(ParameterError(
+                "do not support merging circuits with custom gates.".to_string(),
+            )
+            .into());
+        }
         if self.num_inputs() != other.num_inputs() {
             return Err(ParameterError(format!(
                 "self.num_inputs = {} different from other.num_inputs = {}",
@@ -1040,10 +1356,218 @@ impl<F: PrimeField> PlonkCircuit<F> {
             plonk_params: self.plonk_params,
             num_table_elems: 0,
             table_gate_ids: vec![],
+            constant_vars: None,
+            custom_gates: vec![],
         })
     }
 }
 
+/// The version of the intermediate representation of finalized circuits.
+const IR_VERSION: u8 = 1;
+
+/// The number of selectors of a gate in the intermediate representation.
+const N_IR_SELECTORS: usize = 2 * GATE_WIDTH + N_MUL_SELECTORS + 4;
+
+/// Methods for exporting and importing finalized circuits.
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Export the finalized circuit, namely its gate selectors, wiring,
+    /// permutation and witness, to a portable binary intermediate
+    /// representation. Return error if the circuit is not finalized or if
+    /// writing fails.
+    pub fn export_ir<W: Write>(&self, mut writer: W) -> Result<(), PlonkError> {
+        self.check_finalize_flag(true)?;
+        if !self.custom_gates.is_empty() {
+            return Err(ParameterError(
+                "circuits with custom gates have no intermediate representation".to_string(),
+            )
+            .into());
+        }
+        let plonk_type: u8 = match self.plonk_params.plonk_type {
+            PlonkType::TurboPlonk => 0,
+            PlonkType::UltraPlonk => 1,
+        };
+        let selectors: Vec<F> = self
+            .gates
+            .iter()
+            .flat_map(|gate| {
+                let mut selectors = Vec::with_capacity(N_IR_SELECTORS);
+                selectors.extend_from_slice(&gate.q_lc());
+                selectors.extend_from_slice(&gate.q_mul());
+                selectors.extend_from_slice(&gate.q_hash());
+                selectors.extend_from_slice(&[
+                    gate.q_o(),
+                    gate.q_c(),
+                    gate.q_ecc(),
+                    gate.q_lookup(),
+                ]);
+                selectors
+            })
+            .collect();
+        let (perm_wire_ids, perm_gate_ids): (Vec<WireId>, Vec<GateId>) =
+            self.wire_permutation.iter().cloned().unzip();
+        let (table_start_ids, table_lens): (Vec<GateId>, Vec<usize>) =
+            self.table_gate_ids.iter().cloned().unzip();
+
+        IR_VERSION.serialize(&mut writer)?;
+        plonk_type.serialize(&mut writer)?;
+        self.plonk_params
+            .range_bit_len
+            .unwrap_or(0)
+            .serialize(&mut writer)?;
+        self.eval_domain.size().serialize(&mut writer)?;
+        self.num_vars.serialize(&mut writer)?;
+        self.witness.serialize(&mut writer)?;
+        selectors.serialize(&mut writer)?;
+        for wire_vars in self.wire_variables.iter() {
+            wire_vars.serialize(&mut writer)?;
+        }
+        self.pub_input_gate_ids.serialize(&mut writer)?;
+        perm_wire_ids.serialize(&mut writer)?;
+        perm_gate_ids.serialize(&mut writer)?;
+        self.num_table_elems.serialize(&mut writer)?;
+        table_start_ids.serialize(&mut writer)?;
+        table_lens.serialize(&mut writer)?;
+        Ok(())
+    }
+
+    /// Import a finalized circuit from its intermediate representation
+    /// produced by [`Self::export_ir`]. The circuit is ready for
+    /// preprocessing and proving.
+    /// Return error if reading fails or if the representation is malformed.
+    pub fn import_ir<R: Read>(mut reader: R) -> Result<Self, PlonkError> {
+        let version = u8::deserialize(&mut reader)?;
+        if version != IR_VERSION {
+            return Err(ParameterError(format!(
+                "unsupported circuit IR version {}, expected {}",
+                version, IR_VERSION
+            ))
+            .into());
+        }
+        let plonk_type = u8::deserialize(&mut reader)?;
+        let range_bit_len = usize::deserialize(&mut reader)?;
+        let plonk_params = match plonk_type {
+            0 => PlonkParams::init(PlonkType::TurboPlonk, None)?,
+            1 => PlonkParams::init(PlonkType::UltraPlonk, Some(range_bit_len))?,
+            _ => {
+                return Err(ParameterError(format!(
+                    "unknown Plonk type {} in circuit IR",
+                    plonk_type
+                ))
+                .into())
+            },
+        };
+        let n = usize::deserialize(&mut reader)?;
+        let eval_domain = Radix2EvaluationDomain::new(n)
+            .filter(|domain| n > 1 && domain.size() == n)
+            .ok_or_else(|| ParameterError(format!("invalid domain size {} in circuit IR", n)))?;
+        let num_vars = usize::deserialize(&mut reader)?;
+        let witness = Vec::<F>::deserialize(&mut reader)?;
+        let selectors = Vec::<F>::deserialize(&mut reader)?;
+        let mut wire_variables: [Vec<Variable>; GATE_WIDTH + 2] =
+            [vec![], vec![], vec![], vec![], vec![], vec![]];
+        for wire_vars in wire_variables.iter_mut() {
+            *wire_vars = Vec::<Variable>::deserialize(&mut reader)?;
+        }
+        let pub_input_gate_ids = Vec::<GateId>::deserialize(&mut reader)?;
+        let perm_wire_ids = Vec::<WireId>::deserialize(&mut reader)?;
+        let perm_gate_ids = Vec::<GateId>::deserialize(&mut reader)?;
+        let num_table_elems = usize::deserialize(&mut reader)?;
+        let table_start_ids = Vec::<GateId>::deserialize(&mut reader)?;
+        let table_lens = Vec::<usize>::deserialize(&mut reader)?;
+
+        // check the consistency of the representation
+        let num_wire_types = GATE_WIDTH
+            + 1
+            + match plonk_params.plonk_type {
+                PlonkType::TurboPlonk => 0,
+                PlonkType::UltraPlonk => 1,
+            };
+        if num_vars < 2
+            || witness.len() != num_vars
+            || witness[0] != F::zero()
+            || witness[1] != F::one()
+        {
+            return Err(ParameterError("malformed witness in circuit IR".to_string()).into());
+        }
+        if selectors.len() != n * N_IR_SELECTORS {
+            return Err(ParameterError("malformed selectors in circuit IR".to_string()).into());
+        }
+        if wire_variables
+            .iter()
+            .take(num_wire_types)
+            .any(|vars| vars.len() != n)
+            || wire_variables.iter().flatten().any(|&var| var >= num_vars)
+        {
+            return Err(ParameterError("malformed wiring in circuit IR".to_string()).into());
+        }
+        if pub_input_gate_ids.iter().any(|&gate_id| gate_id >= n) {
+            return Err(ParameterError("malformed IO gates in circuit IR".to_string()).into());
+        }
+        if perm_wire_ids.len() != num_wire_types * n
+            || perm_gate_ids.len() != num_wire_types * n
+            || perm_wire_ids
+                .iter()
+                .any(|&wire_id| wire_id >= num_wire_types)
+            || perm_gate_ids.iter().any(|&gate_id| gate_id >= n)
+        {
+            return Err(ParameterError("malformed permutation in circuit IR".to_string()).into());
+        }
+        if table_start_ids.len() != table_lens.len()
+            || table_start_ids
+                .iter()
+                .zip(table_lens.iter())
+                .any(|(&start, &len)| start.checked_add(len).map_or(true, |end| end > n))
+        {
+            return Err(ParameterError("malformed lookup tables in circuit IR".to_string()).into());
+        }
+
+        let mut gates: Vec<Box<dyn Gate<F>>> = selectors
+            .chunks(N_IR_SELECTORS)
+            .map(|q| {
+                let mut gate = SelectorGate {
+                    q_lc: [F::zero(); GATE_WIDTH],
+                    q_mul: [F::zero(); N_MUL_SELECTORS],
+                    q_hash: [F::zero(); GATE_WIDTH],
+                    q_o: q[2 * GATE_WIDTH + N_MUL_SELECTORS],
+                    q_c: q[2 * GATE_WIDTH + N_MUL_SELECTORS + 1],
+                    q_ecc: q[2 * GATE_WIDTH + N_MUL_SELECTORS + 2],
+                    q_lookup: q[2 * GATE_WIDTH + N_MUL_SELECTORS + 3],
+                };
+                gate.q_lc.copy_from_slice(&q[..GATE_WIDTH]);
+                gate.q_mul
+                    .copy_from_slice(&q[GATE_WIDTH..GATE_WIDTH + N_MUL_SELECTORS]);
+                gate.q_hash.copy_from_slice(
+                    &q[GATE_WIDTH + N_MUL_SELECTORS..2 * GATE_WIDTH + N_MUL_SELECTORS],
+                );
+                Box::new(gate) as Box<dyn Gate<F>>
+            })
+            .collect();
+        // IO gates are recognized by their type when checking the circuit.
+        for &gate_id in pub_input_gate_ids.iter() {
+            gates[gate_id] = Box::new(IoGate);
+        }
+
+        let mut circuit = Self {
+            num_vars,
+            gates,
+            wire_variables,
+            pub_input_gate_ids,
+            witness,
+            wire_permutation: perm_wire_ids.into_iter().zip(perm_gate_ids).collect(),
+            extended_id_permutation: vec![],
+            num_wire_types,
+            eval_domain,
+            plonk_params,
+            num_table_elems,
+            table_gate_ids: table_start_ids.into_iter().zip(table_lens).collect(),
+            constant_vars: None,
+            custom_gates: vec![],
+        };
+        circuit.compute_extended_id_permutation();
+        Ok(circuit)
+    }
+}
+
 impl<F> Arithmetization<F> for PlonkCircuit<F>
 where
     F: PrimeField,
@@ -1123,6 +1647,20 @@ where
     }
 
     fn compute_wire_polynomials(&self) -> Result<Vec<DensePolynomial<F>>, PlonkError> {
+        let domain = &self.eval_domain;
+        let wire_polys: Vec<_> = self
+            .compute_wire_evaluations()?
+            .into_par_iter()
+            .map(|mut wire_vec| {
+                domain.ifft_in_place(&mut wire_vec);
+                DensePolynomial::from_coefficients_vec(wire_vec)
+            })
+            .collect();
+        assert_eq!(wire_polys.len(), self.num_wire_types());
+        Ok(wire_polys)
+    }
+
+    fn compute_wire_evaluations(&self) -> Result<Vec<Vec<F>>, PlonkError> {
         self.check_finalize_flag(true)?;
         let domain = &self.eval_domain;
         if domain.size() < self.num_gates() {
@@ -1134,18 +1672,12 @@ where
             .into());
         }
         let witness = &self.witness;
-        let wire_polys: Vec<_> = self
+        Ok(self
             .wire_variables
             .par_iter()
             .take(self.num_wire_types())
-            .map(|wire_vars| {
-                let mut wire_vec: Vec<F> = wire_vars.iter().map(|&var| witness[var]).collect();
-                domain.ifft_in_place(&mut wire_vec);
-                DensePolynomial::from_coefficients_vec(wire_vec)
-            })
-            .collect();
-        assert_eq!(wire_polys.len(), self.num_wire_types());
-        Ok(wire_polys)
+            .map(|wire_vars| wire_vars.iter().map(|&var| witness[var]).collect())
+            .collect())
     }
 
     fn compute_pub_input_polynomial(&self) -> Result<DensePolynomial<F>, PlonkError> {
@@ -1160,6 +1692,10 @@ where
         Ok(DensePolynomial::from_coefficients_vec(pub_input_vec))
     }
 
+    fn custom_gates(&self) -> &[CustomGateSpec<F>] {
+        &self.custom_gates
+    }
+
     // Plookup-related methods
     //
     fn compute_range_table_polynomial(&self) -> Result<DensePolynomial<F>, PlonkError> {
@@ -1378,9 +1914,11 @@ impl<F: PrimeField> PlonkCircuit<F> {
 #[cfg(test)]
 pub(crate) mod test {
     use crate::{
-        circuit::{Arithmetization, Circuit, PlonkCircuit},
-        constants::compute_coset_representatives,
-        errors::PlonkError,
+        circuit::{gates::CustomGateSpec, Arithmetization, Circuit, PlonkCircuit, Variable},
+        constants::{compute_coset_representatives, GATE_WIDTH},
+        errors::{GateError, PlonkError},
+        goldilocks::Fq as Goldilocks,
+        MergeableCircuitType, PlonkType,
     };
     use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
@@ -1395,7 +1933,8 @@ pub(crate) mod test {
         test_circuit_trait_helper::<FqEd254>()?;
         test_circuit_trait_helper::<FqEd377>()?;
         test_circuit_trait_helper::<FqEd381>()?;
-        test_circuit_trait_helper::<Fq377>()
+        test_circuit_trait_helper::<Fq377>()?;
+        test_circuit_trait_helper::<Goldilocks>()
     }
 
     fn test_circuit_trait_helper<F: PrimeField>() -> Result<(), PlonkError> {
@@ -1929,7 +2468,8 @@ pub(crate) mod test {
         test_arithmetization_helper::<FqEd254>()?;
         test_arithmetization_helper::<FqEd377>()?;
         test_arithmetization_helper::<FqEd381>()?;
-        test_arithmetization_helper::<Fq377>()
+        test_arithmetization_helper::<Fq377>()?;
+        test_arithmetization_helper::<Goldilocks>()
     }
 
     fn test_arithmetization_helper<F: PrimeField>() -> Result<(), PlonkError> {
@@ -1947,6 +2487,534 @@ pub(crate) mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_with_capacity() -> Result<(), PlonkError> {
+        test_with_capacity_helper::<FqEd254>()?;
+        test_with_capacity_helper::<FqEd377>()?;
+        test_with_capacity_helper::<FqEd381>()?;
+        test_with_capacity_helper::<Fq377>()
+    }
+
+    fn test_with_capacity_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // the same circuits as with `new_turbo_plonk` and `new_ultra_plonk`
+        for &(plonk_type, range_bit_len) in [
+            (PlonkType::TurboPlonk, None),
+            (PlonkType::UltraPlonk, Some(4)),
+        ]
+        .iter()
+        {
+            let mut circuit: PlonkCircuit<F> =
+                PlonkCircuit::with_capacity(16, 16, plonk_type, range_bit_len)?;
+            let a = circuit.create_variable(F::from(3u32))?;
+            let b = circuit.create_public_variable(F::from(1u32))?;
+            circuit.constant_gate(a, F::from(3u32))?;
+            circuit.bool_gate(b)?;
+            let c = circuit.add(a, b)?;
+            let d = circuit.sub(a, b)?;
+            let e = circuit.mul(c, d)?;
+            let f = circuit.create_public_variable(F::from(8u32))?;
+            circuit.equal_gate(e, f)?;
+            if plonk_type == PlonkType::UltraPlonk {
+                circuit.add_range_check_variable(c)?;
+            }
+            assert_eq!(circuit.plonk_params.plonk_type, plonk_type);
+            circuit.finalize_for_arithmetization()?;
+            if plonk_type == PlonkType::UltraPlonk {
+                test_arithmetization_for_lookup_circuit(&circuit)?;
+            }
+            test_arithmetization_for_circuit(circuit, vec![F::from(1u32), F::from(8u32)])?;
+        }
+        // UltraPlonk needs a range bit length
+        assert!(PlonkCircuit::<F>::with_capacity(16, 16, PlonkType::UltraPlonk, None).is_err());
+
+        // reserving more gates
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        circuit.reserve_gates(100);
+        assert!(circuit.gates.capacity() >= circuit.num_gates() + 100);
+        assert!(circuit
+            .wire_variables
+            .iter()
+            .take(GATE_WIDTH + 1)
+            .all(|wire_variable| wire_variable.capacity() >= circuit.num_gates() + 100));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_constants() -> Result<(), PlonkError> {
+        test_dedup_constants_helper::<FqEd254>()?;
+        test_dedup_constants_helper::<FqEd377>()?;
+        test_dedup_constants_helper::<FqEd381>()?;
+        test_dedup_constants_helper::<Fq377>()
+    }
+
+    fn test_dedup_constants_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let build = |dedup: bool| -> Result<PlonkCircuit<F>, PlonkError> {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            if dedup {
+                circuit.dedup_constants();
+            }
+            let mut acc = circuit.create_variable(F::from(7u32))?;
+            for i in 0..100u32 {
+                let constant = circuit.create_constant_variable(F::from(i % 5))?;
+                acc = circuit.add(acc, constant)?;
+            }
+            let expected = circuit.create_public_variable(F::from(7u32 + 200))?;
+            circuit.equal_gate(acc, expected)?;
+            Ok(circuit)
+        };
+        let circuit = build(false)?;
+        let dedup_circuit = build(true)?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(207u32)])
+            .is_ok());
+        assert!(dedup_circuit
+            .check_circuit_satisfiability(&[F::from(207u32)])
+            .is_ok());
+        // only the constants 2, 3 and 4 are created
+        assert_eq!(circuit.num_vars() - dedup_circuit.num_vars(), 100 - 3);
+        assert_eq!(circuit.num_gates() - dedup_circuit.num_gates(), 100 - 3);
+        assert!(dedup_circuit
+            .check_circuit_satisfiability(&[F::from(208u32)])
+            .is_err());
+
+        // the shared constants are still constrained
+        let mut bad_circuit = build(true)?;
+        let two = bad_circuit.create_constant_variable(F::from(2u32))?;
+        *bad_circuit.witness_mut(two) = F::from(3u32);
+        assert!(bad_circuit
+            .check_circuit_satisfiability(&[F::from(207u32)])
+            .is_err());
+
+        // enabling twice is harmless
+        let mut circuit_1 = build(true)?;
+        let one = circuit_1.create_constant_variable(F::one())?;
+        assert_eq!(one, circuit_1.one());
+        circuit_1.dedup_constants();
+        let two = circuit_1.create_constant_variable(F::from(2u32))?;
+        assert_eq!(two, circuit_1.create_constant_variable(F::from(2u32))?);
+        circuit_1.finalize_for_arithmetization()?;
+        test_arithmetization_for_circuit(circuit_1, vec![F::from(207u32)])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_gate() -> Result<(), PlonkError> {
+        test_custom_gate_helper::<FqEd254>()?;
+        test_custom_gate_helper::<FqEd377>()?;
+        test_custom_gate_helper::<FqEd381>()?;
+        test_custom_gate_helper::<Fq377>()
+    }
+
+    fn test_custom_gate_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // w_0^4 - w_4 = 0
+        let spec =
+            CustomGateSpec::new(&[(F::one(), [4, 0, 0, 0, 0]), (-F::one(), [0, 0, 0, 0, 1])])?;
+        assert!(CustomGateSpec::<F>::new(&[]).is_err());
+        assert!(CustomGateSpec::new(&[(F::one(), [4, 0, 0, 0, 2])]).is_err());
+        assert!(CustomGateSpec::new(&[(F::one(), [1, 1, 1, 1, 1])]).is_ok());
+
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let id = circuit.register_custom_gate(spec.clone())?;
+        let x = circuit.create_variable(F::from(3u32))?;
+        let y = circuit.create_public_variable(F::from(81u32))?;
+        let zero = circuit.zero();
+        circuit.custom_gate(id, &[x, zero, zero, zero, y])?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(81u32)])
+            .is_ok());
+        *circuit.witness_mut(y) = F::from(27u32);
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(27u32)])
+            .is_err());
+        *circuit.witness_mut(y) = F::from(81u32);
+
+        // Check variable out of bound error.
+        let out_of_bound = circuit.num_vars();
+        assert!(circuit
+            .custom_gate(id, &[out_of_bound, zero, zero, zero, y])
+            .is_err());
+        // Check unregistered custom gate error.
+        assert!(circuit
+            .custom_gate(id + 1, &[x, zero, zero, zero, y])
+            .is_err());
+        // Check UltraPlonk error.
+        let mut ultra_circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(8);
+        assert!(ultra_circuit.register_custom_gate(spec.clone()).is_err());
+
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.custom_gates(), &[spec.clone()]);
+        assert!(circuit.register_custom_gate(spec).is_err());
+        test_arithmetization_for_circuit(circuit, vec![F::from(81u32)])?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() -> Result<(), PlonkError> {
+        test_merge_helper::<FqEd254>()?;
+        test_merge_helper::<FqEd377>()?;
+        test_merge_helper::<FqEd381>()
+    }
+
+    fn test_merge_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // y = x^2 with a custom gate, x is public
+        let build_a = || -> Result<(PlonkCircuit<F>, Variable, Variable), PlonkError> {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let square = circuit.register_custom_gate(CustomGateSpec::new(&[
+                (F::one(), [2, 0, 0, 0, 0]),
+                (-F::one(), [0, 0, 0, 0, 1]),
+            ])?)?;
+            let x = circuit.create_public_variable(F::from(3u32))?;
+            let y = circuit.create_variable(F::from(9u32))?;
+            let zero = circuit.zero();
+            circuit.custom_gate(square, &[x, zero, zero, zero, y])?;
+            Ok((circuit, x, y))
+        };
+        // v = 2 * u with a custom gate, v is public
+        let build_b = |u_val: u32| -> Result<(PlonkCircuit<F>, Variable, Variable), PlonkError> {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let double = circuit.register_custom_gate(CustomGateSpec::new(&[
+                (F::from(2u32), [1, 0, 0, 0, 0]),
+                (-F::one(), [0, 0, 0, 0, 1]),
+            ])?)?;
+            let u = circuit.create_variable(F::from(u_val))?;
+            let v = circuit.create_public_variable(F::from(2 * u_val))?;
+            let zero = circuit.zero();
+            circuit.custom_gate(double, &[u, zero, zero, zero, v])?;
+            Ok((circuit, u, v))
+        };
+
+        let (mut circuit, x, y) = build_a()?;
+        let (other, u, v) = build_b(9)?;
+        let (num_gates, num_vars) = (circuit.num_gates(), circuit.num_vars());
+        let (other_num_gates, other_num_vars) = (other.num_gates(), other.num_vars());
+        let var_map = circuit.merge(other, &[(y, u)])?;
+        assert_eq!(var_map.len(), other_num_vars);
+        assert_eq!(var_map[0], circuit.zero());
+        assert_eq!(var_map[1], circuit.one());
+        assert_eq!(var_map[u], y);
+        // only the variables of `other` that are not shared are new
+        assert_eq!(circuit.num_vars(), num_vars + other_num_vars - 3);
+        assert_eq!(circuit.num_gates(), num_gates + other_num_gates);
+        assert_eq!(circuit.custom_gates.len(), 2);
+        let pub_input = vec![F::from(3u32), F::from(18u32)];
+        assert_eq!(circuit.public_input()?, pub_input);
+        assert_eq!(circuit.witness(var_map[v])?, F::from(18u32));
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(3u32), F::from(16u32)])
+            .is_err());
+        check_wire_permutation_and_extended_id_permutation(&mut circuit.clone())?;
+        circuit.finalize_for_arithmetization()?;
+        test_arithmetization_for_circuit(circuit, pub_input)?;
+
+        // the shared variable keeps the witness of the circuit
+        let (mut circuit, _, y) = build_a()?;
+        let (other, u, _) = build_b(8)?;
+        circuit.merge(other, &[(y, u)])?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(3u32), F::from(16u32)])
+            .is_err());
+        // a variable of `other` shared with two variables constrains them equal
+        let (mut circuit, x, y) = build_a()?;
+        let (other, u, _) = build_b(9)?;
+        circuit.merge(other, &[(y, u), (x, u)])?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(3u32), F::from(18u32)])
+            .is_err());
+
+        // UltraPlonk circuits with range gates and a lookup table
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let x = circuit.create_variable(F::from(12u32))?;
+        circuit.range_gate(x, 4)?;
+        let mut other: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let table = other.create_table(&[vec![F::from(5u32)], vec![F::from(12u32)]])?;
+        let u = other.create_variable(F::from(12u32))?;
+        other.range_gate(u, 8)?;
+        other.lookup_gate(&[u], table)?;
+        let mut other_with_table = other.clone();
+        other_with_table.create_table(&[vec![F::from(7u32)]])?;
+        circuit.merge(other, &[(x, u)])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(x) = F::from(7u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(x) = F::from(12u32);
+        circuit.finalize_for_arithmetization()?;
+        test_arithmetization_for_lookup_circuit(&circuit)?;
+        test_arithmetization_for_circuit(circuit, vec![])?;
+
+        // bad paths
+        let (mut circuit, _, y) = build_a()?;
+        let (other, u, _) = build_b(9)?;
+        let out_of_bound = circuit.num_vars();
+        assert!(circuit
+            .clone()
+            .merge(other.clone(), &[(out_of_bound, u)])
+            .is_err());
+        assert!(circuit
+            .clone()
+            .merge(other.clone(), &[(y, other.num_vars())])
+            .is_err());
+        let ultra_circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        assert!(circuit.clone().merge(ultra_circuit.clone(), &[]).is_err());
+        assert!(PlonkCircuit::<F>::new_ultra_plonk(8)
+            .merge(ultra_circuit, &[])
+            .is_err());
+        let mut finalized_other = other.clone();
+        finalized_other.finalize_for_arithmetization()?;
+        assert!(circuit.clone().merge(finalized_other, &[]).is_err());
+        circuit.finalize_for_arithmetization()?;
+        assert!(circuit.merge(other, &[]).is_err());
+        // both circuits have lookup tables
+        assert!(other_with_table
+            .clone()
+            .merge(other_with_table, &[])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats() -> Result<(), PlonkError> {
+        test_stats_helper::<FqEd254>()?;
+        test_stats_helper::<FqEd377>()?;
+        test_stats_helper::<FqEd381>()?;
+        test_stats_helper::<Fq377>()
+    }
+
+    fn test_stats_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // TurboPlonk: the domain covers the gates only
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_public_variable(F::from(3u32))?;
+        let mut b = circuit.create_variable(F::from(4u32))?;
+        for _ in 0..20 {
+            b = circuit.mul(a, b)?;
+        }
+        let stats = circuit.stats()?;
+        assert_eq!(stats.num_gates, circuit.num_gates());
+        assert_eq!(stats.num_vars, circuit.num_vars());
+        assert_eq!(stats.num_inputs, 1);
+        assert_eq!(stats.num_range_checks, 0);
+        assert_eq!(stats.num_lookup_gates, 0);
+        assert_eq!(stats.domain_size, circuit.num_gates().next_power_of_two());
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(stats.domain_size, circuit.eval_domain_size()?);
+        let finalized_stats = circuit.stats()?;
+        assert_eq!(finalized_stats.num_gates, stats.domain_size);
+        assert_eq!(finalized_stats.num_vars, stats.num_vars);
+        assert_eq!(finalized_stats.num_inputs, 1);
+        assert_eq!(finalized_stats.domain_size, stats.domain_size);
+
+        // the domain of a mergeable circuit is doubled
+        circuit = PlonkCircuit::new_turbo_plonk();
+        circuit.create_variable(F::from(3u32))?;
+        let stats = circuit.stats()?;
+        circuit.finalize_for_mergeable_circuit(MergeableCircuitType::TypeA)?;
+        assert_eq!(circuit.stats()?.domain_size, 2 * stats.domain_size);
+
+        // UltraPlonk: the domain covers the range and lookup tables
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(8);
+        let a = circuit.create_variable(F::from(1000u32))?;
+        circuit.range_gate(a, 16)?;
+        let zero = circuit.zero();
+        circuit
+            .create_table_and_lookup_variables(&[(zero, zero, zero)], &[(zero, zero), (a, a)])?;
+        let stats = circuit.stats()?;
+        assert_eq!(stats.num_gates, circuit.num_gates());
+        assert_eq!(stats.num_inputs, 0);
+        assert_eq!(stats.num_range_checks, 2);
+        assert_eq!(stats.num_lookup_gates, 2);
+        assert_eq!(stats.domain_size, 512);
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(stats.domain_size, circuit.eval_domain_size()?);
+        let finalized_stats = circuit.stats()?;
+        assert_eq!(finalized_stats.num_lookup_gates, 2);
+        assert_eq!(finalized_stats.domain_size, 512);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_circuit_satisfiability() -> Result<(), PlonkError> {
+        test_debug_circuit_satisfiability_helper::<FqEd254>()?;
+        test_debug_circuit_satisfiability_helper::<FqEd377>()?;
+        test_debug_circuit_satisfiability_helper::<FqEd381>()?;
+        test_debug_circuit_satisfiability_helper::<Fq377>()
+    }
+
+    fn test_debug_circuit_satisfiability_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(5u32))?;
+        // the 2-nd gate, after the gates fixing the zero and one variables
+        let c = circuit.mul(a, b)?;
+        // the 3-rd gate
+        let d = circuit.add(c, a)?;
+        let e = circuit.create_public_variable(F::from(18u32))?;
+        circuit.equal_gate(d, e)?;
+        assert!(circuit
+            .debug_circuit_satisfiability(&[F::from(18u32)])
+            .is_ok());
+        assert_eq!(
+            circuit.debug_circuit_satisfiability(&[]),
+            Err((0, GateError::PubInputLenMismatch(0, 1)))
+        );
+
+        // break the addition gate
+        *circuit.witness_mut(d) = F::from(17u32);
+        let (gate_id, err) = circuit
+            .debug_circuit_satisfiability(&[F::from(18u32)])
+            .unwrap_err();
+        assert_eq!(gate_id, 3);
+        assert_eq!(
+            err,
+            GateError::Algebraic {
+                gate: "Addition Gate",
+                wire_values: vec![
+                    F::from(15u32),
+                    F::from(3u32),
+                    F::zero(),
+                    F::zero(),
+                    F::from(17u32)
+                ],
+                pub_input: F::zero(),
+                expected_gate_output: F::from(18u32),
+                gate_output: F::from(17u32),
+            }
+        );
+        // break the multiplication gate as well, which comes first
+        *circuit.witness_mut(c) = F::from(14u32);
+        let (gate_id, err) = circuit
+            .debug_circuit_satisfiability(&[F::from(18u32)])
+            .unwrap_err();
+        assert_eq!(gate_id, 2);
+        assert!(matches!(
+            err,
+            GateError::Algebraic {
+                gate: "Multiplication Gate",
+                ..
+            }
+        ));
+        // the same violation is reported by the satisfiability check
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(18u32)])
+            .is_err());
+
+        // range and lookup gates
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(20u32))?;
+        circuit.add_range_check_variable(a)?;
+        circuit.add_range_check_variable(b)?;
+        assert_eq!(
+            circuit.debug_circuit_satisfiability(&[]),
+            Err((
+                1,
+                GateError::Range {
+                    value: F::from(20u32),
+                    range_size: 16
+                }
+            ))
+        );
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(7u32))?;
+        let key = circuit.zero();
+        let bad_value = circuit.create_variable(F::from(8u32))?;
+        circuit.create_table_and_lookup_variables(&[(key, a, bad_value)], &[(a, b)])?;
+        let (_, err) = circuit.debug_circuit_satisfiability(&[]).unwrap_err();
+        assert_eq!(
+            err,
+            GateError::Lookup {
+                key: F::zero(),
+                val0: F::from(3u32),
+                val1: F::from(8u32)
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_capacity_large_circuit() -> Result<(), PlonkError> {
+        // a circuit of 4096 gates, built without reallocation
+        let num_gates = 1 << 12;
+        let mut circuit: PlonkCircuit<FqEd254> =
+            PlonkCircuit::with_capacity(num_gates, num_gates, PlonkType::TurboPlonk, None)?;
+        let gates_capacity = circuit.gates.capacity();
+        let witness_capacity = circuit.witness.capacity();
+        let mut a = circuit.zero();
+        for _ in 0..num_gates - 10 {
+            a = circuit.add(a, circuit.one())?;
+        }
+        assert_eq!(circuit.num_gates(), num_gates - 10 + 2);
+        assert_eq!(circuit.gates.capacity(), gates_capacity);
+        assert_eq!(circuit.witness.capacity(), witness_capacity);
+        assert_eq!(circuit.witness(a)?, FqEd254::from((num_gates - 10) as u64));
+        circuit.check_circuit_satisfiability(&[])?;
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.eval_domain_size()?, num_gates);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ir_export_import() -> Result<(), PlonkError> {
+        test_ir_export_import_helper::<FqEd254>()?;
+        test_ir_export_import_helper::<FqEd377>()?;
+        test_ir_export_import_helper::<FqEd381>()?;
+        test_ir_export_import_helper::<Fq377>()
+    }
+
+    fn test_ir_export_import_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        for (mut circuit, pub_inputs) in vec![
+            create_turbo_plonk_instance::<F>()?,
+            create_ultra_plonk_instance::<F>()?,
+        ] {
+            // cannot export an unfinalized circuit
+            assert!(circuit.export_ir(&mut vec![]).is_err());
+            circuit.finalize_for_arithmetization()?;
+            let mut ir = vec![];
+            circuit.export_ir(&mut ir)?;
+            let imported = PlonkCircuit::<F>::import_ir(&ir[..])?;
+
+            assert_eq!(imported.num_vars(), circuit.num_vars());
+            assert_eq!(imported.num_gates(), circuit.num_gates());
+            assert_eq!(imported.eval_domain_size()?, circuit.eval_domain_size()?);
+            assert_eq!(imported.support_lookup(), circuit.support_lookup());
+            assert_eq!(imported.public_input()?, pub_inputs);
+            assert!(imported.check_circuit_satisfiability(&pub_inputs).is_ok());
+            assert_eq!(
+                imported.compute_selector_polynomials()?,
+                circuit.compute_selector_polynomials()?
+            );
+            assert_eq!(
+                imported.compute_extended_permutation_polynomials()?,
+                circuit.compute_extended_permutation_polynomials()?
+            );
+            assert_eq!(
+                imported.compute_wire_polynomials()?,
+                circuit.compute_wire_polynomials()?
+            );
+            if circuit.support_lookup() {
+                assert_eq!(
+                    imported.compute_key_table_polynomial()?,
+                    circuit.compute_key_table_polynomial()?
+                );
+                test_arithmetization_for_lookup_circuit(&imported)?;
+            }
+            test_arithmetization_for_circuit(imported, pub_inputs)?;
+
+            // malformed representations
+            assert!(PlonkCircuit::<F>::import_ir(&ir[..ir.len() - 1]).is_err());
+            let mut bad_ir = ir.clone();
+            bad_ir[0] += 1;
+            assert!(PlonkCircuit::<F>::import_ir(&bad_ir[..]).is_err());
+            let mut bad_ir = ir.clone();
+            bad_ir[1] = 2;
+            assert!(PlonkCircuit::<F>::import_ir(&bad_ir[..]).is_err());
+        }
+        Ok(())
+    }
+
     // Check that the polynomial `poly` is consistent with the evaluations `evals`
     // over the domain.
     fn check_polynomial<F: PrimeField>(poly: &DensePolynomial<F>, evals: &[F]) {
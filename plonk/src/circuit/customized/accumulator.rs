@@ -0,0 +1,92 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Rolling hash accumulator of inputs, for circuits that commit their inputs
+//! across a stream of proofs.
+//!
+//! The accumulator is updated as `acc' = Rescue(acc, inputs)`. A transition
+//! circuit creates `acc` and `acc'` as its only two public inputs (in that
+//! order), so that the proof can be verified with
+//! [`crate::proof_system::PlonkKzgSnark::verify_accumulator_transition`]
+//! against the accumulator states rather than the raw inputs.
+
+use crate::{
+    circuit::{customized::rescue::RescueGadget, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use ark_std::vec::Vec;
+use jf_rescue::{Permutation, RescueParameter};
+
+/// Compute the next accumulator state from `prev_acc` and the newly absorbed
+/// `inputs`.
+pub fn rolling_accumulate<F: RescueParameter>(prev_acc: F, inputs: &[F]) -> F {
+    let mut data = Vec::with_capacity(inputs.len() + 1);
+    data.push(prev_acc);
+    data.extend_from_slice(inputs);
+    Permutation::default().sponge_with_padding(&data, 1)[0]
+}
+
+impl<F: RescueParameter> PlonkCircuit<F> {
+    /// Obtain a variable representing the next accumulator state computed
+    /// from `prev_acc` and `input_vars`, matching [`rolling_accumulate`].
+    /// Return error if variables are invalid.
+    pub fn rolling_accumulate(
+        &mut self,
+        prev_acc: Variable,
+        input_vars: &[Variable],
+    ) -> Result<Variable, PlonkError> {
+        self.check_var_bound(prev_acc)?;
+        self.check_vars_bound(input_vars)?;
+        let mut data_vars = Vec::with_capacity(input_vars.len() + 1);
+        data_vars.push(prev_acc);
+        data_vars.extend_from_slice(input_vars);
+        Ok(self.rescue_sponge_with_padding(&data_vars, 1)?[0])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::Circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{vec, UniformRand};
+
+    #[test]
+    fn test_rolling_accumulate() -> Result<(), PlonkError> {
+        test_rolling_accumulate_helper::<FqEd254>()?;
+        test_rolling_accumulate_helper::<FqEd377>()?;
+        test_rolling_accumulate_helper::<FqEd381>()?;
+        test_rolling_accumulate_helper::<Fq377>()
+    }
+
+    fn test_rolling_accumulate_helper<F: RescueParameter>() -> Result<(), PlonkError> {
+        let rng = &mut ark_std::test_rng();
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let prev_acc = F::rand(rng);
+        let inputs = vec![F::rand(rng), F::rand(rng), F::rand(rng), F::rand(rng)];
+        let prev_acc_var = circuit.create_variable(prev_acc)?;
+        let input_vars = inputs
+            .iter()
+            .map(|&x| circuit.create_variable(x))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let next_acc_var = circuit.rolling_accumulate(prev_acc_var, &input_vars)?;
+        let next_acc = rolling_accumulate(prev_acc, &inputs);
+        assert_eq!(circuit.witness(next_acc_var)?, next_acc);
+        assert_ne!(rolling_accumulate(next_acc, &inputs), next_acc);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        *circuit.witness_mut(next_acc_var) = prev_acc;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .rolling_accumulate(circuit.num_vars(), &input_vars)
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Constant product market maker swap gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+
+/// The bit length of reserves.
+const RESERVE_BIT_LEN: usize = 64;
+
+/// The denominator of fees, in basis points.
+const BPS_SCALE: u32 = 10_000;
+
+/// The bit length of fee-adjusted reserves, which are below
+/// `BPS_SCALE * 2^RESERVE_BIT_LEN`.
+const ADJUSTED_BIT_LEN: usize = RESERVE_BIT_LEN + 14;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain a swap from reserves (`x_before`, `y_before`) to reserves
+    /// (`x_after`, `y_after`) to preserve the constant product invariant,
+    /// after charging a fee of `fee_bps` basis points on the amounts paid in:
+    /// `x_adj * y_adj >= x_before * y_before * 10000^2`, where `x_adj =
+    /// x_after * 10000 - fee_bps * max(x_after - x_before, 0)` and likewise
+    /// for `y_adj`.
+    ///
+    /// The reserves are constrained to be 64-bit integers, so that the
+    /// products of at most 156 bits do not wrap around the field.
+    /// Return error if variables are invalid, if `fee_bps` exceeds 10000, or
+    /// if the field is too small for the products.
+    pub fn enforce_constant_product(
+        &mut self,
+        x_before: Variable,
+        y_before: Variable,
+        x_after: Variable,
+        y_after: Variable,
+        fee_bps: u32,
+    ) -> Result<(), PlonkError> {
+        if fee_bps > BPS_SCALE {
+            return Err(CircuitError::ParameterError(format!(
+                "the fee of {} basis points exceeds {}",
+                fee_bps, BPS_SCALE
+            ))
+            .into());
+        }
+        if F::size_in_bits() <= 2 * ADJUSTED_BIT_LEN + 1 {
+            return Err(CircuitError::ParameterError(format!(
+                "the field of {} bits is too small for {}-bit products",
+                F::size_in_bits(),
+                2 * ADJUSTED_BIT_LEN
+            ))
+            .into());
+        }
+        for &var in [x_before, y_before, x_after, y_after].iter() {
+            self.check_var_bound(var)?;
+            self.range_gate(var, RESERVE_BIT_LEN)?;
+        }
+
+        let x_adjusted = self.fee_adjusted_reserve(x_before, x_after, fee_bps)?;
+        let y_adjusted = self.fee_adjusted_reserve(y_before, y_after, fee_bps)?;
+        let product_after = self.mul(x_adjusted, y_adjusted)?;
+        // the initial product, at the scale of the adjusted one, is below
+        // 2^128 * 10000^2 < 2^(2 * ADJUSTED_BIT_LEN)
+        let product_before = self.mul(x_before, y_before)?;
+        let bps_scale = F::from(BPS_SCALE);
+        let product_before = self.mul_constant(product_before, &(bps_scale * bps_scale))?;
+        let invariant_holds = self.is_leq(product_before, product_after, 2 * ADJUSTED_BIT_LEN)?;
+        self.enforce_true(invariant_holds)
+    }
+
+    // Obtain a variable representing `after * 10000 - fee_bps * max(after -
+    // before, 0)`, where both reserves are already constrained to be
+    // RESERVE_BIT_LEN-bit integers. The result is in [0, 2^ADJUSTED_BIT_LEN)
+    // since `fee_bps` is at most 10000.
+    fn fee_adjusted_reserve(
+        &mut self,
+        before: Variable,
+        after: Variable,
+        fee_bps: u32,
+    ) -> Result<Variable, PlonkError> {
+        let is_paid_in = self.is_leq(before, after, RESERVE_BIT_LEN)?;
+        let diff = self.sub(after, before)?;
+        let amount_in = self.mul(is_paid_in, diff)?;
+        let zero = self.zero();
+        self.lc(
+            &[after, amount_in, zero, zero],
+            &[F::from(BPS_SCALE), -F::from(fee_bps), F::zero(), F::zero()],
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{circuit::customized::test::test_variable_independence_for_circuit, goldilocks};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_enforce_constant_product() -> Result<(), PlonkError> {
+        test_enforce_constant_product_helper::<FqEd254>()?;
+        test_enforce_constant_product_helper::<FqEd377>()?;
+        test_enforce_constant_product_helper::<FqEd381>()?;
+        test_enforce_constant_product_helper::<Fq377>()
+    }
+
+    fn test_enforce_constant_product_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // paying 100 x in a 1000 x 1000 pool with a fee of 0.3% gives at most
+        // 90.66 y out, in both directions
+        for &(y_out, valid) in [(0, true), (90, true), (91, false), (1000, false)].iter() {
+            let circuit = build_swap_circuit::<F>([1000, 1000, 1100, 1000 - y_out], 30)?;
+            assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), valid);
+            let circuit = build_swap_circuit::<F>([1000, 1000, 1000 - y_out, 1100], 30)?;
+            assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), valid);
+        }
+        // without fee, the product can be kept exactly
+        let circuit = build_swap_circuit::<F>([4, 25, 5, 20], 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_swap_circuit::<F>([4, 25, 5, 20], 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_swap_circuit::<F>([4, 25, 5, 19], 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // with a fee of 100%, nothing can be taken out
+        let circuit = build_swap_circuit::<F>([1000, 1000, 2000, 1000], 10_000)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_swap_circuit::<F>([1000, 1000, 2000, 999], 10_000)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // the largest reserves do not overflow
+        let max = u64::MAX;
+        let circuit = build_swap_circuit::<F>([max, max, max, max], 30)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_swap_circuit::<F>([max, max, max, max - 1], 30)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_swap_circuit::<F>([1, 1, max, 1], 30)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_swap_circuit::<F>([1000, 1000, 1100, 910], 30)?;
+        // y_after
+        *circuit.witness_mut(5) = F::from(900u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let one = circuit.one();
+        assert!(circuit
+            .enforce_constant_product(one, one, one, one, 10_001)
+            .is_err());
+        let mut small_circuit: PlonkCircuit<goldilocks::Fq> = PlonkCircuit::new_turbo_plonk();
+        let small_one = small_circuit.one();
+        assert!(small_circuit
+            .enforce_constant_product(small_one, small_one, small_one, small_one, 30)
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_constant_product(circuit.num_vars(), one, one, one, 30)
+            .is_err());
+        assert!(circuit
+            .enforce_constant_product(one, one, one, circuit.num_vars(), 30)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_swap_circuit::<F>([1000, 1000, 1100, 910], 30)?;
+        let mut circuit_2 = build_swap_circuit::<F>([4, 25, 3, 50], 30)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The reserves are the variables from index 2, in the order x_before,
+    // y_before, x_after, y_after.
+    fn build_swap_circuit<F: PrimeField>(
+        reserves: [u64; 4],
+        fee_bps: u32,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let x_before = circuit.create_variable(F::from(reserves[0]))?;
+        let y_before = circuit.create_variable(F::from(reserves[1]))?;
+        let x_after = circuit.create_variable(F::from(reserves[2]))?;
+        let y_after = circuit.create_variable(F::from(reserves[3]))?;
+        circuit.enforce_constant_product(x_before, y_before, x_after, y_after, fee_bps)?;
+        Ok(circuit)
+    }
+}
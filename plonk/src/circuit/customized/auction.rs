@@ -0,0 +1,180 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Sealed-bid auction winner gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec, vec::Vec};
+
+/// The bit length of bids.
+const BID_BIT_LEN: usize = 64;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `winner_index` to be the index of the highest bid in `bids`.
+    /// Ties are broken by index: the winner is the first of the highest
+    /// bids, i.e. its bid is strictly higher than the bids before it and at
+    /// least the bids after it.
+    ///
+    /// The bids are constrained to be 64-bit integers, and `winner_index` to
+    /// be in [0, `bids.len()`).
+    /// Return error if variables are invalid, if `bids` is empty, or if the
+    /// field is too small for 64-bit comparisons.
+    pub fn enforce_auction_winner(
+        &mut self,
+        bids: &[Variable],
+        winner_index: Variable,
+    ) -> Result<(), PlonkError> {
+        if bids.is_empty() {
+            return Err(CircuitError::ParameterError("no bids".into()).into());
+        }
+        if F::size_in_bits() <= BID_BIT_LEN + 1 {
+            return Err(CircuitError::ParameterError(format!(
+                "the field of {} bits is too small for {}-bit comparisons",
+                F::size_in_bits(),
+                BID_BIT_LEN
+            ))
+            .into());
+        }
+        self.check_vars_bound(bids)?;
+        self.check_var_bound(winner_index)?;
+        for &bid in bids.iter() {
+            self.range_gate(bid, BID_BIT_LEN)?;
+        }
+
+        // one-hot encoding of the winner index, which has exactly one flag set
+        // iff the index is in range
+        let mut flags = Vec::with_capacity(bids.len());
+        for i in 0..bids.len() {
+            let index = self.create_constant_variable(F::from(i as u64))?;
+            flags.push(self.is_equal(winner_index, index)?);
+        }
+        let num_flags = self.sum(&flags)?;
+        self.constant_gate(num_flags, F::one())?;
+        let selected_bids = flags
+            .iter()
+            .zip(bids.iter())
+            .map(|(&flag, &bid)| self.mul(flag, bid))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let winner_bid = self.sum(&selected_bids)?;
+
+        // bids[j] + [j < winner_index] <= winner_bid: as both bids are below
+        // 2^64, the slack wraps around to a large value otherwise
+        let zero = self.zero();
+        let mut is_before_winner = vec![zero; bids.len()];
+        for j in (0..bids.len() - 1).rev() {
+            is_before_winner[j] = self.add(is_before_winner[j + 1], flags[j + 1])?;
+        }
+        for (&bid, &before) in bids.iter().zip(is_before_winner.iter()) {
+            let slack = self.sub(winner_bid, bid)?;
+            let slack = self.sub(slack, before)?;
+            self.range_gate(slack, BID_BIT_LEN)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{circuit::customized::test::test_variable_independence_for_circuit, goldilocks};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_enforce_auction_winner() -> Result<(), PlonkError> {
+        test_enforce_auction_winner_helper::<FqEd254>()?;
+        test_enforce_auction_winner_helper::<FqEd377>()?;
+        test_enforce_auction_winner_helper::<FqEd381>()?;
+        test_enforce_auction_winner_helper::<Fq377>()
+    }
+
+    fn test_enforce_auction_winner_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // a clear winner
+        let bids = [30, 70, 10, 50];
+        let circuit = build_auction_circuit::<F>(&bids, 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        for &winner in [0, 2, 3].iter() {
+            let circuit = build_auction_circuit::<F>(&bids, winner)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        let circuit = build_auction_circuit::<F>(&[0, u64::MAX, u64::MAX - 1], 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_auction_circuit::<F>(&[42], 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a tie is won by the lowest index
+        let bids = [30, 70, 10, 70, 70];
+        let circuit = build_auction_circuit::<F>(&bids, 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        for &winner in [3, 4].iter() {
+            let circuit = build_auction_circuit::<F>(&bids, winner)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        let circuit = build_auction_circuit::<F>(&[0, 0, 0], 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_auction_circuit::<F>(&[0, 0, 0], 2)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // out of range winner index
+        let circuit = build_auction_circuit::<F>(&[30, 70], 2)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_auction_circuit::<F>(&[30, 70, 10, 50], 1)?;
+        // the third bid
+        *circuit.witness_mut(4) = F::from(80u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit.enforce_auction_winner(&[], zero).is_err());
+        let mut small_circuit: PlonkCircuit<goldilocks::Fq> = PlonkCircuit::new_turbo_plonk();
+        let small_zero = small_circuit.zero();
+        assert!(small_circuit
+            .enforce_auction_winner(&[small_zero], small_zero)
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_auction_winner(&[zero, circuit.num_vars()], zero)
+            .is_err());
+        assert!(circuit
+            .enforce_auction_winner(&[zero], circuit.num_vars())
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_auction_circuit::<F>(&[30, 70, 10, 50], 1)?;
+        let mut circuit_2 = build_auction_circuit::<F>(&[5, 5, 8, 9], 3)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The bids are the variables of indices 2 and above.
+    fn build_auction_circuit<F: PrimeField>(
+        bids: &[u64],
+        winner_index: u64,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let bid_vars = bids
+            .iter()
+            .map(|&bid| circuit.create_variable(F::from(bid)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let winner_index = circuit.create_variable(F::from(winner_index))?;
+        circuit.enforce_auction_winner(&bid_vars, winner_index)?;
+        Ok(circuit)
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Quadratic Bezier curve gadget over fixed-point values.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `result` to be the point at parameter `t` of the quadratic
+    /// Bezier curve with control points `p0`, `p1` and `p2`, namely
+    /// `(1-t)^2 * p0 + 2(1-t)t * p1 + t^2 * p2`, rounded down coordinate-wise.
+    ///
+    /// `t` is a fixed-point value with `frac_bits` fractional bits, i.e. the
+    /// variable `t_var` holds `t * 2^frac_bits`, and is constrained to be in
+    /// [0, 1]. The coordinates of the points are non-negative values of
+    /// the same, arbitrary, fixed-point scale; the coordinates of the control
+    /// points are assumed to be below `2^(F::size_in_bits() - 2 - 2 *
+    /// frac_bits)`, and those of `result` are constrained to be so.
+    /// Return error if variables are invalid, if the points have different
+    /// dimensions, or if `frac_bits` is zero or too large for the field.
+    pub fn enforce_bezier_point(
+        &mut self,
+        p0: &[Variable],
+        p1: &[Variable],
+        p2: &[Variable],
+        t_var: Variable,
+        result: &[Variable],
+        frac_bits: usize,
+    ) -> Result<(), PlonkError> {
+        let dim = p0.len();
+        if p1.len() != dim || p2.len() != dim || result.len() != dim {
+            return Err(CircuitError::ParameterError(format!(
+                "points have different dimensions: {}, {}, {}, {}",
+                dim,
+                p1.len(),
+                p2.len(),
+                result.len()
+            ))
+            .into());
+        }
+        // the weighted sum of each coordinate is below 2^(F::size_in_bits() - 2)
+        // and does not overflow
+        if frac_bits == 0 || 2 * frac_bits + 2 >= F::size_in_bits() {
+            return Err(CircuitError::ParameterError(format!(
+                "the number of fractional bits {} should be in [1, {}]",
+                frac_bits,
+                (F::size_in_bits() - 3) / 2
+            ))
+            .into());
+        }
+        self.check_vars_bound(p0)?;
+        self.check_vars_bound(p1)?;
+        self.check_vars_bound(p2)?;
+        self.check_vars_bound(result)?;
+        self.check_var_bound(t_var)?;
+
+        // 0 <= t <= 1, i.e. both t and 1 - t are in [0, 2^frac_bits]
+        let one_var = self.create_constant_variable(F::from(2u8).pow(&[frac_bits as u64]))?;
+        let one_minus_t_var = self.sub(one_var, t_var)?;
+        self.range_gate(t_var, frac_bits + 1)?;
+        self.range_gate(one_minus_t_var, frac_bits + 1)?;
+
+        // weights of the control points, scaled by 2^(2 * frac_bits)
+        let w0 = self.mul(one_minus_t_var, one_minus_t_var)?;
+        let w1 = self.mul(one_minus_t_var, t_var)?;
+        let w2 = self.mul(t_var, t_var)?;
+
+        let scale = F::from(2u8).pow(&[2 * frac_bits as u64]);
+        let coord_bit_len = F::size_in_bits() - 2 - 2 * frac_bits;
+        for i in 0..dim {
+            let partial = self.mul_add(&[w0, p0[i], w1, p1[i]], &[F::one(), F::from(2u8)])?;
+            let one = self.one();
+            let weighted_sum = self.mul_add(&[w2, p2[i], one, partial], &[F::one(), F::one()])?;
+            // weighted_sum = result * 2^(2 * frac_bits) + remainder
+            let remainder = self
+                .create_variable(self.witness(weighted_sum)? - self.witness(result[i])? * scale)?;
+            self.range_gate(remainder, 2 * frac_bits)?;
+            self.range_gate(result[i], coord_bit_len)?;
+            let zero = self.zero();
+            self.lc_gate(
+                &[result[i], remainder, zero, zero, weighted_sum],
+                &[scale, F::one(), F::zero(), F::zero()],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec::Vec;
+
+    const FRAC_BITS: usize = 8;
+
+    #[test]
+    fn test_enforce_bezier_point() -> Result<(), PlonkError> {
+        test_enforce_bezier_point_helper::<FqEd254>()?;
+        test_enforce_bezier_point_helper::<FqEd377>()?;
+        test_enforce_bezier_point_helper::<FqEd381>()?;
+        test_enforce_bezier_point_helper::<Fq377>()
+    }
+
+    fn test_enforce_bezier_point_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let p0 = [0u64, 0];
+        let p1 = [100u64, 300];
+        let p2 = [200u64, 50];
+        let one = 1u64 << FRAC_BITS;
+
+        // t = 0 gives the first control point
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, 0, &p0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, 0, &p1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // t = 1 gives the last control point
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, one, &p2)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, one, &p0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // t = 1/2 gives (p0 + 2 * p1 + p2) / 4
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, one / 2, &[100, 162])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, one / 2, &[100, 163])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, one / 2, &[100, 161])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // t = 1/4 gives (9 * p0 + 6 * p1 + p2) / 16
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, one / 4, &[50, 115])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // t > 1
+        let (circuit, _) = build_bezier_circuit::<F>(&p0, &p1, &p1, one + 1, &p1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let (mut circuit, t_var) = build_bezier_circuit::<F>(&p0, &p1, &p2, one, &p2)?;
+        *circuit.witness_mut(t_var) = F::zero();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        let point = [zero, zero];
+        assert!(circuit
+            .enforce_bezier_point(&point, &point, &point[..1], zero, &point, FRAC_BITS)
+            .is_err());
+        assert!(circuit
+            .enforce_bezier_point(&point, &point, &point, zero, &point, 0)
+            .is_err());
+        assert!(circuit
+            .enforce_bezier_point(&point, &point, &point, zero, &point, F::size_in_bits() / 2)
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_bezier_point(
+                &point,
+                &point,
+                &point,
+                circuit.num_vars(),
+                &point,
+                FRAC_BITS
+            )
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let (mut circuit_1, _) = build_bezier_circuit::<F>(&p0, &p1, &p2, one / 2, &[100, 162])?;
+        let (mut circuit_2, _) = build_bezier_circuit::<F>(&p2, &p0, &p1, 3, &[7, 200])?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_bezier_circuit<F: PrimeField>(
+        p0: &[u64],
+        p1: &[u64],
+        p2: &[u64],
+        t: u64,
+        result: &[u64],
+    ) -> Result<(PlonkCircuit<F>, Variable), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let mut create_vars = |vals: &[u64]| {
+            vals.iter()
+                .map(|&val| circuit.create_variable(F::from(val)))
+                .collect::<Result<Vec<_>, PlonkError>>()
+        };
+        let p0_vars = create_vars(p0)?;
+        let p1_vars = create_vars(p1)?;
+        let p2_vars = create_vars(p2)?;
+        let result_vars = create_vars(result)?;
+        let t_var = circuit.create_variable(F::from(t))?;
+        circuit.enforce_bezier_point(
+            &p0_vars,
+            &p1_vars,
+            &p2_vars,
+            t_var,
+            &result_vars,
+            FRAC_BITS,
+        )?;
+        Ok((circuit, t_var))
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Bloom filter membership gadget.
+//!
+//! The i-th position of an element `x` in a filter of `num_bits` bits is given
+//! by the `log2(num_bits)` least significant bits of `Rescue(x, i)`.
+
+use crate::{
+    circuit::{customized::rescue::RescueGadget, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::{Field, PrimeField};
+use ark_std::{format, vec::Vec};
+use jf_rescue::{Permutation, RescueParameter};
+
+/// Compute the `k` positions of `element` in a bloom filter of `num_bits`
+/// bits, matching [`PlonkCircuit::enforce_bloom_contains`]. `num_bits` is
+/// assumed to be a power of two.
+pub fn bloom_positions<F: RescueParameter>(element: F, k: usize, num_bits: usize) -> Vec<usize> {
+    let perm = Permutation::default();
+    (0..k)
+        .map(|i| {
+            let hash = perm.sponge_with_padding(&[element, F::from(i as u64)], 1)[0];
+            (hash.into_repr().as_ref()[0] as usize) & (num_bits - 1)
+        })
+        .collect()
+}
+
+impl<F: RescueParameter> PlonkCircuit<F> {
+    /// Constrain the `k` positions of `element` (see [`bloom_positions`]) to
+    /// be set in the bloom filter `bit_array`, of `num_bits` bits. The bits of
+    /// the filter are constrained to be boolean.
+    /// Return error if variables are invalid, if `bit_array` does not have
+    /// `num_bits` bits, or if `num_bits` is not a power of two in [4, 2^32].
+    ///
+    /// Cost: O(`k` * (`num_bits` + `F::size_in_bits()`)) constraints.
+    pub fn enforce_bloom_contains(
+        &mut self,
+        element: Variable,
+        bit_array: &[Variable],
+        k: usize,
+        num_bits: usize,
+    ) -> Result<(), PlonkError> {
+        if !num_bits.is_power_of_two() || num_bits < 4 || num_bits as u64 > 1 << 32 {
+            return Err(CircuitError::ParameterError(format!(
+                "the number of bits {} should be a power of two in [4, 2^32]",
+                num_bits
+            ))
+            .into());
+        }
+        if bit_array.len() != num_bits {
+            return Err(CircuitError::ParameterError(format!(
+                "the bit array has {} bits, expected {}",
+                bit_array.len(),
+                num_bits
+            ))
+            .into());
+        }
+        self.check_var_bound(element)?;
+        self.check_vars_bound(bit_array)?;
+        for &bit in bit_array.iter() {
+            self.bool_gate(bit)?;
+        }
+
+        let log_num_bits = num_bits.trailing_zeros() as usize;
+        for i in 0..k {
+            let index = self.create_constant_variable(F::from(i as u64))?;
+            let hash = self.rescue_sponge_with_padding(&[element, index], 1)?[0];
+            let position_bits = self.low_bits_of_canonical(hash, log_num_bits)?;
+            // select the bit at the position, least significant bit first
+            let mut layer = bit_array.to_vec();
+            for &position_bit in position_bits.iter() {
+                layer = layer
+                    .chunks(2)
+                    .map(|pair| self.conditional_select(position_bit, pair[0], pair[1]))
+                    .collect::<Result<Vec<_>, PlonkError>>()?;
+            }
+            self.constant_gate(layer[0], F::one())?;
+        }
+        Ok(())
+    }
+
+    // Return the `len` least significant bits of the canonical representation
+    // of `a`, with 2 <= `len` <= 32.
+    fn low_bits_of_canonical(
+        &mut self,
+        a: Variable,
+        len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let a_val = self.witness(a)?;
+        let pow = F::from(1u64 << len);
+        let low_val = F::from(a_val.into_repr().as_ref()[0] & ((1u64 << len) - 1));
+        let low = self.create_variable(low_val)?;
+        let high = self.create_variable((a_val - low_val) * pow.inverse().unwrap())?;
+        let low_bits = self.unpack(low, len)?;
+        let high_bit_len = F::size_in_bits() - len;
+        self.range_gate(high, high_bit_len)?;
+        // a = high * 2^len + low
+        let zero = self.zero();
+        self.lc_gate(
+            &[high, low, zero, zero, a],
+            &[pow, F::one(), F::zero(), F::zero()],
+        )?;
+
+        // (high, low) <= (p - 1) in lexicographic order, where p - 1 =
+        // max_high * 2^len + max_low. As `len` is at least 2, if high >
+        // max_high, max_high - high wraps around to a value of at least
+        // 2^high_bit_len.
+        let p_minus_one = -F::one();
+        let max_low = F::from(p_minus_one.into_repr().as_ref()[0] & ((1u64 << len) - 1));
+        let max_high = (p_minus_one - max_low) * pow.inverse().unwrap();
+        let max_high_var = self.create_constant_variable(max_high)?;
+        let high_gap = self.sub(max_high_var, high)?;
+        self.range_gate(high_gap, high_bit_len)?;
+        // if high = max_high, low <= max_low
+        let max_low_var = self.create_constant_variable(max_low)?;
+        let low_gap = self.sub(max_low_var, low)?;
+        let is_max_high = self.is_equal(high, max_high_var)?;
+        let masked_low_gap = self.mul(is_max_high, low_gap)?;
+        self.range_gate(masked_low_gap, len)?;
+
+        Ok(low_bits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec;
+
+    const NUM_BITS: usize = 64;
+    const K: usize = 3;
+
+    #[test]
+    fn test_enforce_bloom_contains() -> Result<(), PlonkError> {
+        test_enforce_bloom_contains_helper::<FqEd254>()?;
+        test_enforce_bloom_contains_helper::<FqEd377>()?;
+        test_enforce_bloom_contains_helper::<FqEd381>()?;
+        test_enforce_bloom_contains_helper::<Fq377>()
+    }
+
+    fn test_enforce_bloom_contains_helper<F: RescueParameter>() -> Result<(), PlonkError> {
+        let set: Vec<F> = (1..=4u64).map(F::from).collect();
+        let mut bits = vec![false; NUM_BITS];
+        for &x in set.iter() {
+            for pos in bloom_positions(x, K, NUM_BITS) {
+                bits[pos] = true;
+            }
+        }
+
+        // members of the set
+        for &x in set.iter() {
+            let circuit = build_bloom_circuit(x, &bits)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+
+        // an element missing a bit
+        let x = F::from(5u64);
+        let positions = bloom_positions(x, K, NUM_BITS);
+        let mut bits_of_x = vec![false; NUM_BITS];
+        for &pos in positions.iter() {
+            bits_of_x[pos] = true;
+        }
+        let circuit = build_bloom_circuit(x, &bits_of_x)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        bits_of_x[positions[K - 1]] = false;
+        let circuit = build_bloom_circuit(x, &bits_of_x)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_bloom_circuit(x, &vec![false; NUM_BITS])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_bloom_circuit(set[0], &bits)?;
+        *circuit.witness_mut(2) = F::from(5u64);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // non-boolean bits
+        let mut circuit = build_bloom_circuit(set[0], &bits)?;
+        let pos = bloom_positions(set[0], K, NUM_BITS)[0];
+        *circuit.witness_mut(3 + pos) = F::from(2u64);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        let bit_array = vec![zero; NUM_BITS];
+        assert!(circuit
+            .enforce_bloom_contains(zero, &bit_array, K, NUM_BITS / 2)
+            .is_err());
+        assert!(circuit
+            .enforce_bloom_contains(zero, &bit_array[..2], K, 2)
+            .is_err());
+        assert!(circuit
+            .enforce_bloom_contains(zero, &bit_array[..48], K, 48)
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_bloom_contains(circuit.num_vars(), &bit_array, K, NUM_BITS)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_bloom_circuit(set[0], &bits)?;
+        let mut circuit_2 = build_bloom_circuit(x, &vec![true; NUM_BITS])?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The element is the variable of index 2, and the bits follow it.
+    fn build_bloom_circuit<F: RescueParameter>(
+        element: F,
+        bits: &[bool],
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let element = circuit.create_variable(element)?;
+        let bit_array = bits
+            .iter()
+            .map(|&bit| circuit.create_variable(F::from(bit as u64)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.enforce_bloom_contains(element, &bit_array, K, NUM_BITS)?;
+        Ok(circuit)
+    }
+}
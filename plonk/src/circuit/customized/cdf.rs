@@ -0,0 +1,188 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Cumulative distribution function sampling gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+/// The bit length of samples and CDF values.
+const CDF_BIT_LEN: usize = 64;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `outcome` to be the outcome obtained by sampling the uniform
+    /// value `u` against the cumulative distribution `cdf`, i.e.
+    /// `cdf[outcome - 1] <= u < cdf[outcome]`, where `cdf[-1]` is zero.
+    ///
+    /// The sample and the CDF values are constrained to be 64-bit integers,
+    /// and `outcome` to be in [0, `cdf.len()`). The CDF is not constrained
+    /// to be non-decreasing.
+    /// Return error if variables are invalid, if `cdf` is empty, or if the
+    /// field is too small for 64-bit comparisons.
+    pub fn enforce_cdf_sample(
+        &mut self,
+        u: Variable,
+        cdf: &[Variable],
+        outcome: Variable,
+    ) -> Result<(), PlonkError> {
+        if cdf.is_empty() {
+            return Err(CircuitError::ParameterError("empty CDF".into()).into());
+        }
+        if F::size_in_bits() <= CDF_BIT_LEN + 1 {
+            return Err(CircuitError::ParameterError(format!(
+                "the field of {} bits is too small for {}-bit comparisons",
+                F::size_in_bits(),
+                CDF_BIT_LEN
+            ))
+            .into());
+        }
+        self.check_var_bound(u)?;
+        self.check_vars_bound(cdf)?;
+        self.check_var_bound(outcome)?;
+        self.range_gate(u, CDF_BIT_LEN)?;
+        for &val in cdf.iter() {
+            self.range_gate(val, CDF_BIT_LEN)?;
+        }
+
+        // select the bounds of the bucket with a one-hot encoding of the
+        // outcome, which has exactly one flag set iff the outcome is in range
+        let mut flags = Vec::with_capacity(cdf.len());
+        let mut lower_terms = Vec::with_capacity(cdf.len());
+        let mut upper_terms = Vec::with_capacity(cdf.len());
+        for (i, &val) in cdf.iter().enumerate() {
+            let index = self.create_constant_variable(F::from(i as u64))?;
+            let flag = self.is_equal(outcome, index)?;
+            if i > 0 {
+                lower_terms.push(self.mul(flag, cdf[i - 1])?);
+            }
+            upper_terms.push(self.mul(flag, val)?);
+            flags.push(flag);
+        }
+        let num_flags = self.sum(&flags)?;
+        self.constant_gate(num_flags, F::one())?;
+        lower_terms.push(self.zero());
+        let lower = self.sum(&lower_terms)?;
+        let upper = self.sum(&upper_terms)?;
+
+        // lower <= u < upper
+        let lower_leq_u = self.is_leq(lower, u, CDF_BIT_LEN)?;
+        self.enforce_true(lower_leq_u)?;
+        let upper_leq_u = self.is_leq(upper, u, CDF_BIT_LEN)?;
+        self.enforce_false(upper_leq_u)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{circuit::customized::test::test_variable_independence_for_circuit, goldilocks};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    const CDF: [u64; 4] = [10, 25, 25, 100];
+
+    #[test]
+    fn test_enforce_cdf_sample() -> Result<(), PlonkError> {
+        test_enforce_cdf_sample_helper::<FqEd254>()?;
+        test_enforce_cdf_sample_helper::<FqEd377>()?;
+        test_enforce_cdf_sample_helper::<FqEd381>()?;
+        test_enforce_cdf_sample_helper::<Fq377>()
+    }
+
+    fn test_enforce_cdf_sample_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // the first bucket [0, 10), the second [10, 25), the empty third
+        // [25, 25) and the last [25, 100)
+        for &(u, outcome) in [
+            (0, 0),
+            (5, 0),
+            (9, 0),
+            (10, 1),
+            (24, 1),
+            (25, 3),
+            (60, 3),
+            (99, 3),
+        ]
+        .iter()
+        {
+            let circuit = build_cdf_circuit::<F>(u, &CDF, outcome)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            for wrong_outcome in (0..CDF.len() as u64 + 1).filter(|&o| o != outcome) {
+                let circuit = build_cdf_circuit::<F>(u, &CDF, wrong_outcome)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+        // beyond the last bucket
+        for outcome in 0..CDF.len() as u64 + 1 {
+            let circuit = build_cdf_circuit::<F>(100, &CDF, outcome)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        // a single bucket
+        let circuit = build_cdf_circuit::<F>(u64::MAX - 1, &[u64::MAX], 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_cdf_circuit::<F>(u64::MAX, &[u64::MAX], 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_cdf_circuit::<F>(60, &CDF, 3)?;
+        // u
+        *circuit.witness_mut(2) = F::from(20u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit.enforce_cdf_sample(zero, &[], zero).is_err());
+        let mut small_circuit: PlonkCircuit<goldilocks::Fq> = PlonkCircuit::new_turbo_plonk();
+        let small_zero = small_circuit.zero();
+        assert!(small_circuit
+            .enforce_cdf_sample(small_zero, &[small_zero], small_zero)
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_cdf_sample(circuit.num_vars(), &[zero], zero)
+            .is_err());
+        assert!(circuit
+            .enforce_cdf_sample(zero, &[zero, circuit.num_vars()], zero)
+            .is_err());
+        assert!(circuit
+            .enforce_cdf_sample(zero, &[zero], circuit.num_vars())
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_cdf_circuit::<F>(60, &CDF, 3)?;
+        let mut circuit_2 = build_cdf_circuit::<F>(1, &[2, 3, 4, 5], 0)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The sample is the variable of index 2.
+    fn build_cdf_circuit<F: PrimeField>(
+        u: u64,
+        cdf: &[u64],
+        outcome: u64,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let u = circuit.create_variable(F::from(u))?;
+        let cdf_vars = cdf
+            .iter()
+            .map(|&val| circuit.create_variable(F::from(val)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let outcome = circuit.create_variable(F::from(outcome))?;
+        circuit.enforce_cdf_sample(u, &cdf_vars, outcome)?;
+        Ok(circuit)
+    }
+}
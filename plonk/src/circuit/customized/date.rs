@@ -0,0 +1,211 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Gregorian calendar date gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// The bit length of years.
+const YEAR_BIT_LEN: usize = 16;
+
+/// The lengths of the months of a non-leap year.
+const MONTH_LENGTHS: [u64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain (`year`, `month`, `day`) to be a valid date of the
+    /// (proleptic) Gregorian calendar, i.e. `month` is in [1, 12] and `day` is
+    /// in [1, length of the month], where February has 29 days in leap years:
+    /// years divisible by 4, except those divisible by 100 but not by 400.
+    ///
+    /// The year is constrained to be in [0, 2^16).
+    /// Return error if variables are invalid.
+    pub fn enforce_valid_date(
+        &mut self,
+        year: Variable,
+        month: Variable,
+        day: Variable,
+    ) -> Result<(), PlonkError> {
+        self.check_var_bound(year)?;
+        self.check_var_bound(month)?;
+        self.check_var_bound(day)?;
+
+        // 1 <= month <= 12
+        let month_minus_one = self.add_constant(month, &-F::one())?;
+        self.range_gate(month_minus_one, 4)?;
+        let twelve = self.create_constant_variable(F::from(12u32))?;
+        let months_left = self.sub(twelve, month)?;
+        self.range_gate(months_left, 4)?;
+
+        // the length of the month, from a one-hot encoding of the month
+        let is_leap = self.is_leap_year(year)?;
+        let mut flags = Vec::with_capacity(MONTH_LENGTHS.len());
+        let mut length_terms = Vec::with_capacity(MONTH_LENGTHS.len() + 1);
+        for (i, &length) in MONTH_LENGTHS.iter().enumerate() {
+            let index = self.create_constant_variable(F::from(i as u64 + 1))?;
+            let flag = self.is_equal(month, index)?;
+            length_terms.push(self.mul_constant(flag, &F::from(length))?);
+            flags.push(flag);
+        }
+        // one more day in February of leap years
+        length_terms.push(self.mul(flags[1], is_leap)?);
+        let month_length = self.sum(&length_terms)?;
+
+        // 1 <= day <= month_length
+        let day_minus_one = self.add_constant(day, &-F::one())?;
+        self.range_gate(day_minus_one, 5)?;
+        let days_left = self.sub(month_length, day)?;
+        self.range_gate(days_left, 5)
+    }
+
+    // Obtain a bool variable representing whether `year` is a leap year, and
+    // constrain `year` to be in [0, 2^YEAR_BIT_LEN).
+    fn is_leap_year(&mut self, year: Variable) -> Result<Variable, PlonkError> {
+        let year_bits = self.range_gate_internal(year, YEAR_BIT_LEN)?;
+        // a year is divisible by 4 (resp. 16) iff its 2 (resp. 4) least
+        // significant bits are zero
+        let low_bits_2 = self.sum(&year_bits[..2])?;
+        let divisible_by_4 = self.is_zero(low_bits_2)?;
+        let low_bits_4 = self.sum(&year_bits[..4])?;
+        let divisible_by_16 = self.is_zero(low_bits_4)?;
+
+        // year = 100 * quotient + remainder, with 0 <= remainder < 100, where the
+        // quotient is below 2^10 as the year is below 2^16
+        let year_val = self.witness(year)?.into_repr().as_ref()[0];
+        let quotient = self.create_variable(F::from(year_val / 100))?;
+        let remainder = self.create_variable(F::from(year_val % 100))?;
+        self.range_gate(quotient, 10)?;
+        self.range_gate(remainder, 7)?;
+        let ninety_nine = self.create_constant_variable(F::from(99u32))?;
+        let remainder_gap = self.sub(ninety_nine, remainder)?;
+        self.range_gate(remainder_gap, 7)?;
+        let zero = self.zero();
+        self.lc_gate(
+            &[quotient, remainder, zero, zero, year],
+            &[F::from(100u32), F::one(), F::zero(), F::zero()],
+        )?;
+        let divisible_by_100 = self.is_zero(remainder)?;
+
+        // divisible by 400 iff divisible by 100 and by 16
+        let not_divisible_by_100 = self.logic_neg(divisible_by_100)?;
+        let century_rule = self.logic_or(not_divisible_by_100, divisible_by_16)?;
+        self.logic_and(divisible_by_4, century_rule)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_enforce_valid_date() -> Result<(), PlonkError> {
+        test_enforce_valid_date_helper::<FqEd254>()?;
+        test_enforce_valid_date_helper::<FqEd377>()?;
+        test_enforce_valid_date_helper::<FqEd381>()?;
+        test_enforce_valid_date_helper::<Fq377>()
+    }
+
+    fn test_enforce_valid_date_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // February 29 in leap and non-leap years
+        for &(year, is_leap) in [
+            (2024, true),
+            (2000, true),
+            (1600, true),
+            (0, true),
+            (2023, false),
+            (2022, false),
+            (1900, false),
+            (2100, false),
+            (65535, false),
+        ]
+        .iter()
+        {
+            let circuit = build_date_circuit::<F>(year, 2, 29)?;
+            assert_eq!(
+                circuit.check_circuit_satisfiability(&[]).is_ok(),
+                is_leap,
+                "year {}",
+                year
+            );
+            let circuit = build_date_circuit::<F>(year, 2, 28)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            let circuit = build_date_circuit::<F>(year, 2, 30)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // month boundaries
+        for (i, &length) in MONTH_LENGTHS.iter().enumerate() {
+            let month = i as u64 + 1;
+            for &day in [1, length].iter() {
+                let circuit = build_date_circuit::<F>(2023, month, day)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            }
+            for &day in [0, length + 1].iter() {
+                let circuit = build_date_circuit::<F>(2023, month, day)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+        for &month in [0, 13, 16].iter() {
+            let circuit = build_date_circuit::<F>(2024, month, 1)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        let circuit = build_date_circuit::<F>(2024, 12, 32)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // year out of range
+        let circuit = build_date_circuit::<F>(1 << 16, 1, 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_date_circuit::<F>(2024, 2, 29)?;
+        // year
+        *circuit.witness_mut(2) = F::from(2023u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // Check variable out of bound error.
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let one = circuit.one();
+        assert!(circuit
+            .enforce_valid_date(circuit.num_vars(), one, one)
+            .is_err());
+        assert!(circuit
+            .enforce_valid_date(one, one, circuit.num_vars())
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_date_circuit::<F>(2024, 2, 29)?;
+        let mut circuit_2 = build_date_circuit::<F>(1999, 12, 31)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The year is the variable of index 2.
+    fn build_date_circuit<F: PrimeField>(
+        year: u64,
+        month: u64,
+        day: u64,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let year = circuit.create_variable(F::from(year))?;
+        let month = circuit.create_variable(F::from(month))?;
+        let day = circuit.create_variable(F::from(day))?;
+        circuit.enforce_valid_date(year, month, day)?;
+        Ok(circuit)
+    }
+}
@@ -20,7 +20,7 @@ use ark_ec::{
     TEModelParameters as Parameters,
 };
 use ark_ff::{PrimeField, Zero};
-use ark_std::{borrow::ToOwned, boxed::Box, string::ToString, vec, vec::Vec};
+use ark_std::{borrow::ToOwned, boxed::Box, format, string::ToString, vec, vec::Vec};
 use core::marker::PhantomData;
 
 mod conversion;
@@ -431,38 +431,50 @@ where
         // `num_bits` needs to be an even number
         num_bits += num_bits & 1;
         let scalar_bits_le = self.unpack(scalar, num_bits)?;
-        let fixed_bases = compute_base_points(base, num_bits / 2)?;
-        let mut accum = self.neutral_point_variable();
-        for i in 0..num_bits / 2 {
-            let b0 = scalar_bits_le.get(2 * i).ok_or_else(|| {
-                CircuitError::InternalError(
-                    "scalar binary representation has the wrong length".to_string(),
-                )
-            })?;
-            let b1 = scalar_bits_le.get(2 * i + 1).ok_or_else(|| {
-                CircuitError::InternalError(
-                    "scalar binary representation has the wrong length".to_string(),
-                )
-            })?;
-            let p1 = fixed_bases[0].get(i).ok_or_else(|| {
-                CircuitError::InternalError("fixed_bases_1 has the wrong length".to_string())
-            })?;
-            let p2 = fixed_bases[1].get(i).ok_or_else(|| {
-                CircuitError::InternalError("fixed_bases_2 has the wrong length".to_string())
-            })?;
-            let p3 = fixed_bases[2].get(i).ok_or_else(|| {
-                CircuitError::InternalError("fixed_bases_3 has the wrong length".to_string())
-            })?;
-            let selected = self.quaternary_point_select::<P>(
-                *b0,
-                *b1,
-                &Point::from(*p1),
-                &Point::from(*p2),
-                &Point::from(*p3),
-            )?;
-            accum = self.ecc_add::<P>(&accum, &selected)?;
+        self.fixed_base_scalar_mul_with_windows(&scalar_bits_le, base)
+    }
+
+    /// Obtain the fixed-based scalar multiplication result of `scalar` *
+    /// `base`, where `scalar_bits_le` is the little-endian binary form of the
+    /// scalar. The multiples of `base` are precomputed for each window of two
+    /// bits and folded into the selection gates as constants, so that the
+    /// gadget costs 4 gates per pair of bits, without any range check.
+    ///
+    /// The bit variables should already be constrained to be boolean, e.g. by
+    /// [`PlonkCircuit::unpack`] or [`Circuit::bool_gate`]. At most
+    /// `P::ScalarField::size_in_bits()` bits are accepted, i.e. the bit
+    /// length of the order of the prime subgroup; shorter scalars are
+    /// implicitly padded with zeros.
+    /// Currently only supports GroupAffine::<P> scalar multiplication.
+    /// Return error if there are too many bits, or some bit variable is
+    /// invalid or not boolean.
+    pub fn fixed_base_binary_scalar_mul<P: Parameters<BaseField = F> + Clone>(
+        &mut self,
+        scalar_bits_le: &[Variable],
+        base: &GroupAffine<P>,
+    ) -> Result<PointVariable, PlonkError> {
+        let max_num_bits = <P as ModelParameters>::ScalarField::size_in_bits();
+        if scalar_bits_le.len() > max_num_bits {
+            return Err(CircuitError::ParameterError(format!(
+                "the scalar has {} bits, more than the {} bits of the subgroup order",
+                scalar_bits_le.len(),
+                max_num_bits
+            ))
+            .into());
         }
-        Ok(accum)
+        for &bit in scalar_bits_le {
+            self.check_var_bound(bit)?;
+            self.check_bool(bit)?;
+        }
+        if scalar_bits_le.is_empty() {
+            return Ok(self.neutral_point_variable());
+        }
+
+        let mut scalar_bits_le = scalar_bits_le.to_vec();
+        if scalar_bits_le.len() & 1 == 1 {
+            scalar_bits_le.push(self.zero());
+        }
+        self.fixed_base_scalar_mul_with_windows(&scalar_bits_le, base)
     }
 
     /// Obtain a variable of the result of a variable base scalar
@@ -517,6 +529,48 @@ where
     }
 }
 
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    /// The number of gates added by `variable_base_scalar_mul::<P>` on a
+    /// circuit that does not support lookup.
+    pub fn gate_cost_of_variable_base_scalar_mul<P: Parameters<BaseField = F> + Clone>() -> usize {
+        let num_bits = <P as ModelParameters>::ScalarField::size_in_bits();
+        // one point selection (2 gates) and two point additions (2 gates
+        // each) per scalar bit
+        Self::gate_cost_of_range_check(num_bits) + 6 * num_bits
+    }
+
+    /// The number of gates added by `fixed_base_scalar_mul::<P>`.
+    pub fn gate_cost_of_fixed_base_scalar_mul<P: Parameters<BaseField = F> + Clone>() -> usize {
+        let mut num_bits = <P as ModelParameters>::ScalarField::size_in_bits();
+        num_bits += num_bits & 1;
+        // one quaternary point selection (2 gates) and one point addition (2
+        // gates) per pair of scalar bits
+        Self::gate_cost_of_range_check(num_bits) + 2 * num_bits
+    }
+
+    /// Constrain `shared` to be the Diffie-Hellman shared secret
+    /// `priv_scalar` * `other_pub`, where `other_pub` is constrained to be on
+    /// the curve. Uses the variable base scalar multiplication gadget.
+    /// Return error if the input variables are invalid.
+    pub fn enforce_dh<P: Parameters<BaseField = F> + Clone>(
+        &mut self,
+        priv_scalar: Variable,
+        other_pub: &PointVariable,
+        shared: &PointVariable,
+    ) -> Result<(), PlonkError> {
+        self.check_var_bound(priv_scalar)?;
+        self.check_point_var_bound(other_pub)?;
+        self.check_point_var_bound(shared)?;
+
+        self.on_curve_gate::<P>(other_pub)?;
+        let expected_shared = self.variable_base_scalar_mul::<P>(priv_scalar, other_pub)?;
+        self.point_equal_gate(&expected_shared, shared)
+    }
+}
+
 // private helper functions
 impl<F> PlonkCircuit<F>
 where
@@ -527,6 +581,48 @@ where
         self.check_var_bound(point_var.1)?;
         Ok(())
     }
+
+    // Fixed-base scalar multiplication over windows of two bits, where
+    // `scalar_bits_le` has a positive even length.
+    fn fixed_base_scalar_mul_with_windows<P: Parameters<BaseField = F> + Clone>(
+        &mut self,
+        scalar_bits_le: &[Variable],
+        base: &GroupAffine<P>,
+    ) -> Result<PointVariable, PlonkError> {
+        let num_windows = scalar_bits_le.len() / 2;
+        let fixed_bases = compute_base_points(base, num_windows)?;
+        let mut accum = self.neutral_point_variable();
+        for i in 0..num_windows {
+            let b0 = scalar_bits_le.get(2 * i).ok_or_else(|| {
+                CircuitError::InternalError(
+                    "scalar binary representation has the wrong length".to_string(),
+                )
+            })?;
+            let b1 = scalar_bits_le.get(2 * i + 1).ok_or_else(|| {
+                CircuitError::InternalError(
+                    "scalar binary representation has the wrong length".to_string(),
+                )
+            })?;
+            let p1 = fixed_bases[0].get(i).ok_or_else(|| {
+                CircuitError::InternalError("fixed_bases_1 has the wrong length".to_string())
+            })?;
+            let p2 = fixed_bases[1].get(i).ok_or_else(|| {
+                CircuitError::InternalError("fixed_bases_2 has the wrong length".to_string())
+            })?;
+            let p3 = fixed_bases[2].get(i).ok_or_else(|| {
+                CircuitError::InternalError("fixed_bases_3 has the wrong length".to_string())
+            })?;
+            let selected = self.quaternary_point_select::<P>(
+                *b0,
+                *b1,
+                &Point::from(*p1),
+                &Point::from(*p2),
+                &Point::from(*p3),
+            )?;
+            accum = self.ecc_add::<P>(&accum, &selected)?;
+        }
+        Ok(accum)
+    }
 }
 
 // Given a base point [G] and a scalar s of length 2*n, denote as s[G] the
@@ -1167,6 +1263,78 @@ mod test {
         Ok(circuit)
     }
 
+    #[test]
+    fn test_fixed_base_binary_scalar_mul() -> Result<(), PlonkError> {
+        test_fixed_base_binary_scalar_mul_helper::<FqEd354, Param254>()?;
+        test_fixed_base_binary_scalar_mul_helper::<FqEd377, Param377>()?;
+        test_fixed_base_binary_scalar_mul_helper::<FqEd381, Param381>()?;
+        test_fixed_base_binary_scalar_mul_helper::<FqEd381b, Param381b>()?;
+        test_fixed_base_binary_scalar_mul_helper::<Fq377, Param761>()
+    }
+
+    fn test_fixed_base_binary_scalar_mul_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: PrimeField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let num_bits = P::ScalarField::size_in_bits();
+
+        for _ in 0..6 {
+            let base = GroupAffine::<P>::rand(&mut rng);
+            let s = P::ScalarField::rand(&mut rng);
+            let scalar = circuit.create_variable(fr_to_fq::<F, P>(&s))?;
+            let scalar_bits_le = circuit.unpack(scalar, num_bits)?;
+            let num_gates = circuit.num_gates();
+            let result = circuit.fixed_base_binary_scalar_mul(&scalar_bits_le, &base)?;
+            assert_eq!(Point::from(base.mul(s)), circuit.point_witness(&result)?);
+            // 4 gates per window of two bits
+            assert_eq!(
+                circuit.num_gates() - num_gates,
+                2 * (num_bits + (num_bits & 1))
+            );
+        }
+
+        // short scalars, with an odd number of bits
+        let base = GroupAffine::<P>::prime_subgroup_generator();
+        for &s in [0u64, 1, 2, 3, 87, (1 << 63) - 1].iter() {
+            let scalar = circuit.create_variable(F::from(s))?;
+            let scalar_bits_le = circuit.unpack(scalar, 63)?;
+            let result = circuit.fixed_base_binary_scalar_mul(&scalar_bits_le, &base)?;
+            assert_eq!(
+                Point::from(base.mul(P::ScalarField::from(s))),
+                circuit.point_witness(&result)?
+            );
+        }
+        let result = circuit.fixed_base_binary_scalar_mul(&[], &base)?;
+        assert_eq!(
+            Point::from(GroupAffine::<P>::zero()),
+            circuit.point_witness(&result)?
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong witness should fail
+        *circuit.witness_mut(2) = F::rand(&mut rng);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // more bits than the subgroup order
+        let scalar_bits_le = vec![circuit.zero(); num_bits + 1];
+        assert!(circuit
+            .fixed_base_binary_scalar_mul(&scalar_bits_le, &base)
+            .is_err());
+        // non-boolean bit
+        let non_bit_var = circuit.create_variable(F::from(2u32))?;
+        assert!(circuit
+            .fixed_base_binary_scalar_mul(&[circuit.one(), non_bit_var], &base)
+            .is_err());
+        // variable out of bound
+        assert!(circuit
+            .fixed_base_binary_scalar_mul(&[circuit.num_vars()], &base)
+            .is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_binary_point_vars_select() -> Result<(), PlonkError> {
         test_binary_point_vars_select_helper::<FqEd354, Param254>()?;
@@ -1346,6 +1514,127 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_gate_cost_of_scalar_mul() -> Result<(), PlonkError> {
+        test_gate_cost_of_scalar_mul_helper::<FqEd354, Param254>()?;
+        test_gate_cost_of_scalar_mul_helper::<FqEd377, Param377>()?;
+        test_gate_cost_of_scalar_mul_helper::<FqEd381, Param381>()?;
+        test_gate_cost_of_scalar_mul_helper::<FqEd381b, Param381b>()?;
+        test_gate_cost_of_scalar_mul_helper::<Fq377, Param761>()
+    }
+
+    fn test_gate_cost_of_scalar_mul_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: PrimeField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let scalar = P::ScalarField::rand(&mut rng);
+        let base = GroupAffine::<P>::rand(&mut rng);
+        let scalar_var = circuit.create_variable(F::from(fr_to_fq::<F, P>(&scalar)))?;
+        let base_var = circuit.create_point_variable(Point::from(base))?;
+
+        let num_gates = circuit.num_gates();
+        circuit.variable_base_scalar_mul::<P>(scalar_var, &base_var)?;
+        assert_eq!(
+            circuit.num_gates() - num_gates,
+            PlonkCircuit::<F>::gate_cost_of_variable_base_scalar_mul::<P>()
+        );
+
+        let num_gates = circuit.num_gates();
+        circuit.fixed_base_scalar_mul(scalar_var, &base)?;
+        assert_eq!(
+            circuit.num_gates() - num_gates,
+            PlonkCircuit::<F>::gate_cost_of_fixed_base_scalar_mul::<P>()
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_enforce_dh() -> Result<(), PlonkError> {
+        test_enforce_dh_helper::<FqEd354, Param254>()?;
+        test_enforce_dh_helper::<FqEd377, Param377>()?;
+        test_enforce_dh_helper::<FqEd381, Param381>()?;
+        test_enforce_dh_helper::<FqEd381b, Param381b>()?;
+        test_enforce_dh_helper::<Fq377, Param761>()
+    }
+
+    fn test_enforce_dh_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: PrimeField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let generator = GroupAffine::<P>::prime_subgroup_generator();
+        let alice_priv = P::ScalarField::rand(&mut rng);
+        let bob_priv = P::ScalarField::rand(&mut rng);
+        let alice_pub = generator.mul(alice_priv).into_affine();
+        let bob_pub = generator.mul(bob_priv).into_affine();
+        let shared = bob_pub.mul(alice_priv).into_affine();
+        assert_eq!(shared, alice_pub.mul(bob_priv).into_affine());
+
+        // correct shared secret
+        let circuit = build_dh_circuit::<F, P>(&alice_priv, &bob_pub, &shared)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_dh_circuit::<F, P>(&bob_priv, &alice_pub, &shared)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong shared secret
+        let circuit = build_dh_circuit::<F, P>(&alice_priv, &bob_pub, &bob_pub)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let wrong_shared = GroupAffine::<P>::rand(&mut rng);
+        let circuit = build_dh_circuit::<F, P>(&alice_priv, &bob_pub, &wrong_shared)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_dh_circuit::<F, P>(&bob_priv, &bob_pub, &shared)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // Check variable out of bound error.
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let s_var = circuit.create_variable(F::from(fr_to_fq::<F, P>(&alice_priv)))?;
+        let pub_var = circuit.create_point_variable(Point::from(bob_pub))?;
+        let shared_var = circuit.create_point_variable(Point::from(shared))?;
+        let bad_point_var = PointVariable(circuit.num_vars(), circuit.num_vars());
+        assert!(circuit
+            .enforce_dh::<P>(circuit.num_vars(), &pub_var, &shared_var)
+            .is_err());
+        assert!(circuit
+            .enforce_dh::<P>(s_var, &bad_point_var, &shared_var)
+            .is_err());
+        assert!(circuit
+            .enforce_dh::<P>(s_var, &pub_var, &bad_point_var)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_dh_circuit::<F, P>(&alice_priv, &bob_pub, &shared)?;
+        let mut circuit_2 = build_dh_circuit::<F, P>(&bob_priv, &bob_pub, &alice_pub)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        customized::test::test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_dh_circuit<F, P>(
+        priv_scalar: &P::ScalarField,
+        other_pub: &GroupAffine<P>,
+        shared: &GroupAffine<P>,
+    ) -> Result<PlonkCircuit<F>, PlonkError>
+    where
+        F: PrimeField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let priv_var = circuit.create_variable(F::from(fr_to_fq::<F, P>(priv_scalar)))?;
+        let other_pub_var = circuit.create_point_variable(Point::from(*other_pub))?;
+        let shared_var = circuit.create_point_variable(Point::from(*shared))?;
+        circuit.enforce_dh::<P>(priv_var, &other_pub_var, &shared_var)?;
+        Ok(circuit)
+    }
+
     fn build_variable_base_scalar_mul_circuit<F, P>(
         scalar: F,
         base: Point<F>,
@@ -0,0 +1,210 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Elo rating update gadget over fixed-point values.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{cmp::max, format};
+
+/// The bit length of the integer part of ratings.
+const RATING_INT_BIT_LEN: usize = 32;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `new` to be the Elo rating updated from `old`, i.e.
+    /// `old + k * (score - expected)` rounded down, clamped to be
+    /// non-negative.
+    ///
+    /// All the values are fixed-point values with `frac_bits` fractional bits,
+    /// i.e. a variable holds its value times `2^frac_bits`, and so is the
+    /// constant `k`. `score` and `expected` are constrained to be in [0, 1],
+    /// `old` to be non-negative with an integer part of at most 32 bits.
+    /// Return error if variables are invalid, or if `frac_bits` is zero or too
+    /// large for the field.
+    pub fn enforce_elo_update(
+        &mut self,
+        old: Variable,
+        score: Variable,
+        expected: Variable,
+        k: u64,
+        new: Variable,
+        frac_bits: usize,
+    ) -> Result<(), PlonkError> {
+        // |old * 2^frac_bits + k * (score - expected)| < 2^diff_bit_len
+        let diff_bit_len = max(RATING_INT_BIT_LEN + 2 * frac_bits, 64 + frac_bits) + 1;
+        if frac_bits == 0 || diff_bit_len + 1 >= F::size_in_bits() {
+            return Err(CircuitError::ParameterError(format!(
+                "the number of fractional bits {} is zero or too large for the field",
+                frac_bits
+            ))
+            .into());
+        }
+        self.check_var_bound(old)?;
+        self.check_var_bound(score)?;
+        self.check_var_bound(expected)?;
+        self.check_var_bound(new)?;
+
+        // 0 <= score, expected <= 1
+        let one_var = self.create_constant_variable(F::from(2u8).pow(&[frac_bits as u64]))?;
+        for &var in [score, expected].iter() {
+            let one_minus_var = self.sub(one_var, var)?;
+            self.range_gate(var, frac_bits + 1)?;
+            self.range_gate(one_minus_var, frac_bits + 1)?;
+        }
+        self.range_gate(old, RATING_INT_BIT_LEN + frac_bits)?;
+
+        // the update scaled by 2^(2 * frac_bits):
+        // diff = old * 2^frac_bits + k * score - k * expected
+        let scale = F::from(2u8).pow(&[frac_bits as u64]);
+        let k = F::from(k);
+        let zero = self.zero();
+        let diff = self.lc(&[old, score, expected, zero], &[scale, k, -k, F::zero()])?;
+
+        // `is_negative` is one iff diff < 0, in which case the rating is clamped to
+        // zero, and abs = diff if diff >= 0, -diff - 1 otherwise, is non-negative.
+        let diff_val = self.witness(diff)?;
+        let is_negative_val =
+            diff_val.into_repr() > F::from(2u8).pow(&[diff_bit_len as u64]).into_repr();
+        let is_negative = self.create_bool_variable(is_negative_val)?;
+        let negative_diff = self.mul(is_negative, diff)?;
+        let abs = self.lc(
+            &[diff, negative_diff, is_negative, zero],
+            &[F::one(), -F::from(2u8), -F::one(), F::zero()],
+        )?;
+        self.range_gate(abs, diff_bit_len)?;
+
+        // clamped_diff = new * 2^frac_bits + remainder
+        let clamped_diff = self.sub(diff, negative_diff)?;
+        let clamped_diff_val = self.witness(clamped_diff)?;
+        let remainder_val = clamped_diff_val - self.witness(new)? * scale;
+        let remainder = self.create_variable(remainder_val)?;
+        self.range_gate(remainder, frac_bits)?;
+        self.range_gate(new, diff_bit_len - frac_bits)?;
+        self.lc_gate(
+            &[new, remainder, zero, zero, clamped_diff],
+            &[scale, F::one(), F::zero(), F::zero()],
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    const FRAC_BITS: usize = 16;
+    const ONE: u64 = 1 << FRAC_BITS;
+
+    // Native Elo update over fixed-point values.
+    fn elo_update(old: u64, score: u64, expected: u64, k: u64) -> u64 {
+        let diff = ((old as i128) << FRAC_BITS) + k as i128 * (score as i128 - expected as i128);
+        max(0, diff.div_euclid(ONE as i128)) as u64
+    }
+
+    #[test]
+    fn test_enforce_elo_update() -> Result<(), PlonkError> {
+        test_enforce_elo_update_helper::<FqEd254>()?;
+        test_enforce_elo_update_helper::<FqEd377>()?;
+        test_enforce_elo_update_helper::<FqEd381>()?;
+        test_enforce_elo_update_helper::<Fq377>()
+    }
+
+    fn test_enforce_elo_update_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let k = 32 * ONE;
+        let expected = ONE * 64 / 100;
+        // updates matching the native computation
+        for &(old, score, expected, k) in [
+            (1500 * ONE, ONE, expected, k),
+            (1500 * ONE, ONE / 2, expected, k),
+            (1500 * ONE, 0, expected, k),
+            (1500 * ONE + 12345, ONE, 0, k),
+            (2000 * ONE, 0, ONE, 10 * ONE + 1),
+            (0, ONE, expected, k),
+            // clamped to zero
+            (5 * ONE, 0, ONE * 9 / 10, k),
+            (0, 0, 1, k),
+        ]
+        .iter()
+        {
+            let new = elo_update(old, score, expected, k);
+            let (circuit, _) = build_elo_circuit::<F>(old, score, expected, k, new)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            let (circuit, _) = build_elo_circuit::<F>(old, score, expected, k, new + 1)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            if new > 0 {
+                let (circuit, _) = build_elo_circuit::<F>(old, score, expected, k, new - 1)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+        assert_eq!(elo_update(5 * ONE, 0, ONE * 9 / 10, k), 0);
+
+        // score or expected score out of range
+        let (circuit, _) = build_elo_circuit::<F>(1500 * ONE, ONE + 1, 0, k, 1532 * ONE)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, _) =
+            build_elo_circuit::<F>(1500 * ONE, ONE, ONE + ONE, k, 1500 * ONE - 32 * ONE)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let (mut circuit, old_var) = build_elo_circuit::<F>(1500 * ONE, ONE, 0, k, 1532 * ONE)?;
+        *circuit.witness_mut(old_var) = F::from(1501 * ONE);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_elo_update(zero, zero, zero, k, zero, 0)
+            .is_err());
+        assert!(circuit
+            .enforce_elo_update(zero, zero, zero, k, zero, F::size_in_bits())
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_elo_update(zero, zero, zero, k, circuit.num_vars(), FRAC_BITS)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let (mut circuit_1, _) = build_elo_circuit::<F>(
+            1500 * ONE,
+            ONE,
+            expected,
+            k,
+            elo_update(1500 * ONE, ONE, expected, k),
+        )?;
+        let (mut circuit_2, _) = build_elo_circuit::<F>(5 * ONE, 0, ONE, k, 0)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_elo_circuit<F: PrimeField>(
+        old: u64,
+        score: u64,
+        expected: u64,
+        k: u64,
+        new: u64,
+    ) -> Result<(PlonkCircuit<F>, Variable), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let old_var = circuit.create_variable(F::from(old))?;
+        let score_var = circuit.create_variable(F::from(score))?;
+        let expected_var = circuit.create_variable(F::from(expected))?;
+        let new_var = circuit.create_variable(F::from(new))?;
+        circuit.enforce_elo_update(old_var, score_var, expected_var, k, new_var, FRAC_BITS)?;
+        Ok((circuit, old_var))
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Geohash gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+
+/// The number of bits of a base-32 geohash symbol.
+const GEOHASH_SYMBOL_BIT_LEN: usize = 5;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain the first `coarse_len` base-32 symbols of the geohashes
+    /// `coarse_bits` and `fine_bits` to match, i.e. the region of the coarse
+    /// geohash truncated to `coarse_len` symbols contains the region of the
+    /// fine one.
+    ///
+    /// The geohashes are given as bits, most significant bit of the first
+    /// symbol first, and all their bits are constrained to be boolean.
+    /// Return error if variables are invalid, if a geohash has a number of
+    /// bits that is not a multiple of 5, or if either geohash has fewer than
+    /// `coarse_len` symbols.
+    pub fn enforce_geohash_prefix(
+        &mut self,
+        coarse_bits: &[Variable],
+        fine_bits: &[Variable],
+        coarse_len: usize,
+    ) -> Result<(), PlonkError> {
+        for bits in [coarse_bits, fine_bits].iter() {
+            if bits.len() % GEOHASH_SYMBOL_BIT_LEN != 0 {
+                return Err(CircuitError::ParameterError(format!(
+                    "the number of geohash bits {} is not a multiple of {}",
+                    bits.len(),
+                    GEOHASH_SYMBOL_BIT_LEN
+                ))
+                .into());
+            }
+        }
+        let prefix_bit_len = coarse_len * GEOHASH_SYMBOL_BIT_LEN;
+        if prefix_bit_len > coarse_bits.len() || prefix_bit_len > fine_bits.len() {
+            return Err(CircuitError::ParameterError(format!(
+                "the prefix of {} symbols is longer than a geohash of {} or {} symbols",
+                coarse_len,
+                coarse_bits.len() / GEOHASH_SYMBOL_BIT_LEN,
+                fine_bits.len() / GEOHASH_SYMBOL_BIT_LEN
+            ))
+            .into());
+        }
+        self.check_vars_bound(coarse_bits)?;
+        self.check_vars_bound(fine_bits)?;
+
+        for &bit in coarse_bits.iter().chain(fine_bits.iter()) {
+            self.bool_gate(bit)?;
+        }
+        for (&coarse_bit, &fine_bit) in coarse_bits
+            .iter()
+            .zip(fine_bits.iter())
+            .take(prefix_bit_len)
+        {
+            self.equal_gate(coarse_bit, fine_bit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec::Vec;
+
+    const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+    #[test]
+    fn test_enforce_geohash_prefix() -> Result<(), PlonkError> {
+        test_enforce_geohash_prefix_helper::<FqEd254>()?;
+        test_enforce_geohash_prefix_helper::<FqEd377>()?;
+        test_enforce_geohash_prefix_helper::<FqEd381>()?;
+        test_enforce_geohash_prefix_helper::<Fq377>()
+    }
+
+    fn test_enforce_geohash_prefix_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // containment
+        let (circuit, ..) = build_geohash_circuit::<F>("u4pru", "u4pruydqqvj", 5)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, ..) = build_geohash_circuit::<F>("u4pru", "u4pruydqqvj", 3)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, ..) = build_geohash_circuit::<F>("u4pzz", "u4pruydqqvj", 3)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, ..) = build_geohash_circuit::<F>("ezs42", "gbsuv", 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // non-containment
+        let (circuit, ..) = build_geohash_circuit::<F>("u4prv", "u4pruydqqvj", 5)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, ..) = build_geohash_circuit::<F>("ezs42", "gbsuv", 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // neighboring regions
+        let (circuit, ..) = build_geohash_circuit::<F>("u4pr", "u4pxydqq", 4)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let (mut circuit, coarse, _) = build_geohash_circuit::<F>("u4pru", "u4pruydqqvj", 5)?;
+        *circuit.witness_mut(coarse[0]) = F::zero();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (mut circuit, _, fine) = build_geohash_circuit::<F>("u4pru", "u4pruydqqvj", 5)?;
+        *circuit.witness_mut(fine[54]) = F::from(2u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let bits: Vec<Variable> = (0..10).map(|_| circuit.zero()).collect();
+        assert!(circuit
+            .enforce_geohash_prefix(&bits[..5], &bits, 2)
+            .is_err());
+        assert!(circuit
+            .enforce_geohash_prefix(&bits, &bits[..5], 2)
+            .is_err());
+        assert!(circuit
+            .enforce_geohash_prefix(&bits[..4], &bits, 0)
+            .is_err());
+        // Check variable out of bound error.
+        let mut bad_bits = bits.clone();
+        bad_bits[9] = circuit.num_vars();
+        assert!(circuit.enforce_geohash_prefix(&bits, &bad_bits, 1).is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let (mut circuit_1, ..) = build_geohash_circuit::<F>("u4pru", "u4pruydqqvj", 3)?;
+        let (mut circuit_2, ..) = build_geohash_circuit::<F>("ezs42", "ezs42bcdefg", 3)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn geohash_bits<F: PrimeField>(
+        circuit: &mut PlonkCircuit<F>,
+        geohash: &str,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let mut bits = Vec::new();
+        for symbol in geohash.bytes() {
+            let val = GEOHASH_ALPHABET.iter().position(|&c| c == symbol).unwrap();
+            for i in (0..GEOHASH_SYMBOL_BIT_LEN).rev() {
+                bits.push(circuit.create_variable(F::from(((val >> i) & 1) as u64))?);
+            }
+        }
+        Ok(bits)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn build_geohash_circuit<F: PrimeField>(
+        coarse: &str,
+        fine: &str,
+        coarse_len: usize,
+    ) -> Result<(PlonkCircuit<F>, Vec<Variable>, Vec<Variable>), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let coarse_bits = geohash_bits(&mut circuit, coarse)?;
+        let fine_bits = geohash_bits(&mut circuit, fine)?;
+        circuit.enforce_geohash_prefix(&coarse_bits, &fine_bits, coarse_len)?;
+        Ok((circuit, coarse_bits, fine_bits))
+    }
+}
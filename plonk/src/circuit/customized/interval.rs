@@ -0,0 +1,180 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Interval containment gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain the query interval [`q_start`, `q_end`] to be contained in at
+    /// least one of the `intervals`, each given as a pair (start, end) of
+    /// inclusive bounds.
+    ///
+    /// The query bounds and the interval bounds are constrained to be in the
+    /// range [0, 2^`bit_len`).
+    /// Return error if variables are invalid, if `intervals` is empty, or if
+    /// `bit_len` is zero or too large for the field.
+    pub fn enforce_interval_contained(
+        &mut self,
+        q_start: Variable,
+        q_end: Variable,
+        intervals: &[(Variable, Variable)],
+        bit_len: usize,
+    ) -> Result<(), PlonkError> {
+        if intervals.is_empty() {
+            return Err(CircuitError::ParameterError(
+                "the set of intervals should not be empty".to_string(),
+            )
+            .into());
+        }
+        if bit_len == 0 || bit_len + 1 >= F::size_in_bits() {
+            return Err(CircuitError::ParameterError(format!(
+                "the bit length {} should be in [1, {})",
+                bit_len,
+                F::size_in_bits() - 1
+            ))
+            .into());
+        }
+        self.check_var_bound(q_start)?;
+        self.check_var_bound(q_end)?;
+        for &(start, end) in intervals.iter() {
+            self.check_var_bound(start)?;
+            self.check_var_bound(end)?;
+        }
+
+        self.range_gate_internal(q_start, bit_len)?;
+        self.range_gate_internal(q_end, bit_len)?;
+        let mut contained = self.zero();
+        for &(start, end) in intervals.iter() {
+            self.range_gate_internal(start, bit_len)?;
+            self.range_gate_internal(end, bit_len)?;
+            let start_leq = self.is_leq(start, q_start, bit_len)?;
+            let end_geq = self.is_leq(q_end, end, bit_len)?;
+            let contained_in_interval = self.logic_and(start_leq, end_geq)?;
+            contained = self.logic_or(contained, contained_in_interval)?;
+        }
+        self.enforce_true(contained)
+    }
+
+    // Obtain a bool variable representing whether `a <= b`, where both are
+    // already constrained to be in [0, 2^`bit_len`).
+    pub(crate) fn is_leq(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<Variable, PlonkError> {
+        // b - a + 2^bit_len lies in (0, 2^(bit_len+1)) and its top bit is one
+        // iff a <= b.
+        let two_to_bit_len = F::from(2u8).pow([bit_len as u64]);
+        let diff = self.sub(b, a)?;
+        let shifted_diff = self.add_constant(diff, &two_to_bit_len)?;
+        let diff_bits = self.range_gate_internal(shifted_diff, bit_len + 1)?;
+        Ok(diff_bits[bit_len])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec::Vec;
+
+    const BIT_LEN: usize = 16;
+    const INTERVALS: [(u64, u64); 3] = [(10, 20), (15, 30), (100, 100)];
+
+    #[test]
+    fn test_enforce_interval_contained() -> Result<(), PlonkError> {
+        test_enforce_interval_contained_helper::<FqEd254>()?;
+        test_enforce_interval_contained_helper::<FqEd377>()?;
+        test_enforce_interval_contained_helper::<FqEd381>()?;
+        test_enforce_interval_contained_helper::<Fq377>()
+    }
+
+    fn test_enforce_interval_contained_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // contained queries
+        for &(q_start, q_end) in [(10, 20), (12, 18), (16, 30), (18, 25), (100, 100)].iter() {
+            let (circuit, _) = build_interval_circuit::<F>(q_start, q_end, &INTERVALS)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+
+        // non-contained queries
+        for &(q_start, q_end) in [(9, 12), (10, 31), (5, 8), (31, 99), (99, 100), (0, 65535)].iter()
+        {
+            let (circuit, _) = build_interval_circuit::<F>(q_start, q_end, &INTERVALS)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        // out of range bounds
+        let (circuit, _) = build_interval_circuit::<F>(12, 18, &[(0, 1 << BIT_LEN)])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let (mut circuit, q_start) = build_interval_circuit::<F>(12, 18, &INTERVALS)?;
+        *circuit.witness_mut(q_start) = F::from(9u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_interval_contained(zero, zero, &[], BIT_LEN)
+            .is_err());
+        assert!(circuit
+            .enforce_interval_contained(zero, zero, &[(zero, zero)], 0)
+            .is_err());
+        assert!(circuit
+            .enforce_interval_contained(zero, zero, &[(zero, zero)], F::size_in_bits())
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_interval_contained(zero, circuit.num_vars(), &[(zero, zero)], BIT_LEN)
+            .is_err());
+        assert!(circuit
+            .enforce_interval_contained(zero, zero, &[(zero, circuit.num_vars())], BIT_LEN)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let (mut circuit_1, _) = build_interval_circuit::<F>(12, 18, &INTERVALS)?;
+        let (mut circuit_2, _) = build_interval_circuit::<F>(5, 8, &[(1, 2), (3, 4), (5, 6)])?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_interval_circuit<F: PrimeField>(
+        q_start: u64,
+        q_end: u64,
+        intervals: &[(u64, u64)],
+    ) -> Result<(PlonkCircuit<F>, Variable), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let q_start_var = circuit.create_variable(F::from(q_start))?;
+        let q_end_var = circuit.create_variable(F::from(q_end))?;
+        let interval_vars = intervals
+            .iter()
+            .map(|&(start, end)| {
+                Ok((
+                    circuit.create_variable(F::from(start))?,
+                    circuit.create_variable(F::from(end))?,
+                ))
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.enforce_interval_contained(q_start_var, q_end_var, &interval_vars, BIT_LEN)?;
+        Ok((circuit, q_start_var))
+    }
+}
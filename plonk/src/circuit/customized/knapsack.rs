@@ -0,0 +1,183 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Knapsack gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+/// The bit length of weights, values, capacities and totals.
+const AMOUNT_BIT_LEN: usize = 64;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain the items flagged by `selected` to fit in the knapsack, i.e.
+    /// their total weight is at most `capacity`, and to have a total value of
+    /// `claimed_value`. The i-th item has weight `weights[i]` and value
+    /// `values[i]`.
+    ///
+    /// The flags are constrained to be boolean. The weights, the values, the
+    /// capacity and the total value are constrained to be 64-bit integers.
+    /// Return error if variables are invalid, or if `selected`, `weights` and
+    /// `values` have different lengths.
+    pub fn enforce_knapsack(
+        &mut self,
+        selected: &[Variable],
+        weights: &[Variable],
+        values: &[Variable],
+        capacity: Variable,
+        claimed_value: Variable,
+    ) -> Result<(), PlonkError> {
+        if weights.len() != selected.len() || values.len() != selected.len() {
+            return Err(CircuitError::ParameterError(format!(
+                "{} flags, {} weights and {} values",
+                selected.len(),
+                weights.len(),
+                values.len()
+            ))
+            .into());
+        }
+        self.check_vars_bound(selected)?;
+        self.check_vars_bound(weights)?;
+        self.check_vars_bound(values)?;
+        self.check_var_bound(capacity)?;
+        self.check_var_bound(claimed_value)?;
+
+        let mut selected_weights = Vec::with_capacity(selected.len() + 1);
+        let mut selected_values = Vec::with_capacity(selected.len() + 1);
+        selected_weights.push(self.zero());
+        selected_values.push(self.zero());
+        for ((&flag, &weight), &value) in selected.iter().zip(weights.iter()).zip(values.iter()) {
+            self.bool_gate(flag)?;
+            self.range_gate(weight, AMOUNT_BIT_LEN)?;
+            self.range_gate(value, AMOUNT_BIT_LEN)?;
+            selected_weights.push(self.mul(flag, weight)?);
+            selected_values.push(self.mul(flag, value)?);
+        }
+        let total_weight = self.sum(&selected_weights)?;
+        let total_value = self.sum(&selected_values)?;
+
+        // total_weight <= capacity: as the total weight is far below the field
+        // modulus, capacity - total_weight wraps around to a large value
+        // otherwise
+        self.range_gate(capacity, AMOUNT_BIT_LEN)?;
+        let slack = self.sub(capacity, total_weight)?;
+        self.range_gate(slack, AMOUNT_BIT_LEN)?;
+
+        self.range_gate(total_value, AMOUNT_BIT_LEN)?;
+        self.equal_gate(total_value, claimed_value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    const WEIGHTS: [u64; 4] = [10, 20, 30, 40];
+    const VALUES: [u64; 4] = [60, 100, 120, 5];
+
+    #[test]
+    fn test_enforce_knapsack() -> Result<(), PlonkError> {
+        test_enforce_knapsack_helper::<FqEd254>()?;
+        test_enforce_knapsack_helper::<FqEd377>()?;
+        test_enforce_knapsack_helper::<FqEd381>()?;
+        test_enforce_knapsack_helper::<Fq377>()
+    }
+
+    fn test_enforce_knapsack_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // feasible selections
+        let circuit = build_knapsack_circuit::<F>(&[0, 1, 1, 0], &WEIGHTS, 50, 220)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_knapsack_circuit::<F>(&[1, 1, 0, 0], &WEIGHTS, 50, 160)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_knapsack_circuit::<F>(&[0, 0, 0, 0], &WEIGHTS, 0, 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_knapsack_circuit::<F>(&[1, 1, 1, 1], &WEIGHTS, u64::MAX, 285)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // infeasible selections
+        let circuit = build_knapsack_circuit::<F>(&[1, 1, 1, 0], &WEIGHTS, 50, 280)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_knapsack_circuit::<F>(&[0, 0, 0, 1], &WEIGHTS, 39, 5)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // wrong claimed value
+        let circuit = build_knapsack_circuit::<F>(&[0, 1, 1, 0], &WEIGHTS, 50, 221)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // non-boolean flag
+        let circuit = build_knapsack_circuit::<F>(&[2, 0, 0, 0], &WEIGHTS, 50, 120)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_knapsack_circuit::<F>(&[0, 1, 1, 0], &WEIGHTS, 50, 220)?;
+        // the first flag
+        *circuit.witness_mut(2) = F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_knapsack(&[zero, zero], &[zero], &[zero, zero], zero, zero)
+            .is_err());
+        assert!(circuit
+            .enforce_knapsack(&[zero], &[zero], &[zero, zero], zero, zero)
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_knapsack(&[zero], &[zero], &[zero], circuit.num_vars(), zero)
+            .is_err());
+        assert!(circuit
+            .enforce_knapsack(&[circuit.num_vars()], &[zero], &[zero], zero, zero)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_knapsack_circuit::<F>(&[0, 1, 1, 0], &WEIGHTS, 50, 220)?;
+        let mut circuit_2 = build_knapsack_circuit::<F>(&[1, 0, 0, 1], &[1, 2, 3, 4], 7, 65)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The items have values `VALUES`.
+    fn build_knapsack_circuit<F: PrimeField>(
+        selected: &[u64],
+        weights: &[u64],
+        capacity: u64,
+        claimed_value: u64,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let mut create_vars = |vals: &[u64]| {
+            vals.iter()
+                .map(|&val| circuit.create_variable(F::from(val)))
+                .collect::<Result<Vec<_>, PlonkError>>()
+        };
+        let selected_vars = create_vars(selected)?;
+        let weight_vars = create_vars(weights)?;
+        let value_vars = create_vars(&VALUES)?;
+        let capacity_var = circuit.create_variable(F::from(capacity))?;
+        let claimed_value_var = circuit.create_variable(F::from(claimed_value))?;
+        circuit.enforce_knapsack(
+            &selected_vars,
+            &weight_vars,
+            &value_vars,
+            capacity_var,
+            claimed_value_var,
+        )?;
+        Ok(circuit)
+    }
+}
@@ -18,11 +18,35 @@ use ark_ff::{BigInteger, PrimeField};
 use ark_std::{borrow::ToOwned, boxed::Box, cmp::Ordering, format, string::ToString, vec::Vec};
 use num_bigint::BigUint;
 
+pub mod accumulator;
+mod amm;
+mod auction;
+mod bezier;
+mod bloom;
+mod cdf;
+mod date;
 pub mod ecc;
+mod elo;
 mod gates;
+mod geohash;
+mod interval;
+mod knapsack;
+mod moving_average;
+mod palette;
+pub mod poseidon;
+mod prefix;
+mod quorum;
+mod radix;
+mod reputation;
 pub mod rescue;
+mod rle;
+mod sha256;
+pub mod signed;
+mod staking;
 pub mod transcript;
 pub mod ultraplonk;
+pub mod variant;
+mod vesting;
 
 impl<F> PlonkCircuit<F>
 where
@@ -259,6 +283,34 @@ where
         Ok(y)
     }
 
+    /// Obtain variables that equal `a[i]` if `cond` is one, or `b[i]` if
+    /// `cond` is zero, for every `i`. `cond` is constrained to be a boolean
+    /// once, followed by one selection gate per element.
+    /// Return error if variables are invalid, or if `a` and `b` have different
+    /// lengths.
+    pub fn conditional_select_vec(
+        &mut self,
+        cond: Variable,
+        a: &[Variable],
+        b: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        if a.len() != b.len() {
+            return Err(CircuitError::ParameterError(format!(
+                "the selected vectors have different lengths: {} != {}",
+                a.len(),
+                b.len()
+            ))
+            .into());
+        }
+        self.check_var_bound(cond)?;
+        self.check_bool(cond)?;
+        self.bool_gate(cond)?;
+        a.iter()
+            .zip(b.iter())
+            .map(|(&a_i, &b_i)| self.conditional_select(cond, b_i, a_i))
+            .collect()
+    }
+
     /// Constrain variable `y` to the addition of `a` and `c`, where `c` is a
     /// constant value Return error if the input variables are invalid.
     fn add_constant_gate(&mut self, x: Variable, c: F, y: Variable) -> Result<(), PlonkError> {
@@ -370,6 +422,23 @@ where
         self.mul_gate(var, inv_var, one_var)
     }
 
+    /// Constrain a variable to be one of the (constant) elements of `set`,
+    /// i.e. the product of `var - elem` over the elements of the set is zero.
+    /// Return error if the variable is invalid or if `set` is empty.
+    /// Cost: 2 * `set.len()` constraints.
+    pub fn enforce_in_set(&mut self, var: Variable, set: &[F]) -> Result<(), PlonkError> {
+        self.check_var_bound(var)?;
+        if set.is_empty() {
+            return Err(CircuitError::ParameterError("empty set".to_string()).into());
+        }
+        let mut prod = self.add_constant(var, &-set[0])?;
+        for elem in set.iter().skip(1) {
+            let diff = self.add_constant(var, &-*elem)?;
+            prod = self.mul(prod, diff)?;
+        }
+        self.constant_gate(prod, F::zero())
+    }
+
     /// Assuming value represented by `a` is boolean, obtain a
     /// variable representing the result of a logic negation gate. Return the
     /// index of the variable. Return error if the input variable is invalid.
@@ -651,6 +720,17 @@ impl<F: PrimeField> PlonkCircuit<F> {
         Ok(())
     }
 
+    /// The number of gates added by `range_gate` (or `unpack`) with a positive
+    /// `bit_len` on a circuit that does not support lookup, so that the size
+    /// of a circuit can be planned before building it.
+    pub fn gate_cost_of_range_check(bit_len: usize) -> usize {
+        let rate = GATE_WIDTH - 1;
+        // one bool gate per bit, plus one lc gate per `rate` bits of the
+        // decomposition
+        let padded_len = next_multiple(bit_len.saturating_sub(1), rate).unwrap_or(0) + 1;
+        bit_len + padded_len / rate
+    }
+
     /// Return a boolean variable indicating whether variable `a` is in the
     /// range [0, 2^`bit_len`). Return error if the variable is invalid.
     /// TODO: optimize the gate for UltraPlonk.
@@ -676,6 +756,62 @@ impl<F: PrimeField> PlonkCircuit<F> {
         self.range_gate_internal(a, bit_len)
     }
 
+    /// Obtain the canonical `bit_len`-long little-endian binary representation
+    /// of variable `a`: the returned variables are constrained to be boolean
+    /// and to sum to `a` with the powers of two, as with [`Self::unpack`].
+    /// When `bit_len` is at least the bit length of the modulus, the bits are
+    /// further constrained to represent an integer smaller than the modulus,
+    /// which is the only representation of `a`.
+    ///
+    /// The bits are checked by boolean gates on UltraPlonk circuits as well,
+    /// since a lookup into the range table cannot constrain a value to be a
+    /// bit.
+    /// Return error if `a` is invalid or not in the range [0, 2^`bit_len`).
+    pub fn unpack_le(&mut self, a: Variable, bit_len: usize) -> Result<Vec<Variable>, PlonkError> {
+        let bits_le = self.unpack(a, bit_len)?;
+        if bit_len >= F::size_in_bits() {
+            self.enforce_bits_le_below_modulus(&bits_le)?;
+        }
+        Ok(bits_le)
+    }
+
+    /// Obtain a variable equal to the integer with little-endian binary
+    /// representation `bits_le`, reduced modulo the field modulus, i.e. the
+    /// inverse of [`Self::unpack_le`]. The bit variables should already be
+    /// constrained to be boolean.
+    /// Return error if some bit variable is invalid or not boolean.
+    pub fn pack_le(&mut self, bits_le: &[Variable]) -> Result<Variable, PlonkError> {
+        let mut val = F::zero();
+        for &bit in bits_le.iter().rev() {
+            self.check_var_bound(bit)?;
+            self.check_bool(bit)?;
+            val = val.double() + self.witness(bit)?;
+        }
+        if bits_le.is_empty() {
+            return Ok(self.zero());
+        }
+        let packed = self.create_variable(val)?;
+        self.decompose_vars_gate(bits_le.to_vec(), packed, F::from(2u8))?;
+        Ok(packed)
+    }
+
+    // Constrain the boolean variables `bits_le` to represent an integer
+    // smaller than the field modulus, with one gate per bit: from the most
+    // significant bit, `run` is 1 as long as the bits equal those of the
+    // modulus minus one, in which case a bit cannot exceed its counterpart.
+    fn enforce_bits_le_below_modulus(&mut self, bits_le: &[Variable]) -> Result<(), PlonkError> {
+        let max_bits_le = (-F::one()).into_repr().to_bits_le();
+        let mut run = self.one();
+        for (i, &bit) in bits_le.iter().enumerate().rev() {
+            if max_bits_le.get(i) == Some(&true) {
+                run = self.mul(run, bit)?;
+            } else {
+                self.mul_gate(run, bit, self.zero())?;
+            }
+        }
+        Ok(())
+    }
+
     // internal of a range check gate
     fn range_gate_internal(
         &mut self,
@@ -772,7 +908,7 @@ pub(crate) mod test {
     use ark_ed_on_bls12_377::Fq as FqEd377;
     use ark_ed_on_bls12_381::Fq as FqEd381;
     use ark_ed_on_bn254::Fq as FqEd254;
-    use ark_std::{convert::TryInto, test_rng, vec};
+    use ark_std::{convert::TryInto, rand::Rng, test_rng, vec};
 
     // two circuit with the same statement should have the same extended permutation
     // polynomials even with different variable assignment
@@ -1225,6 +1361,88 @@ pub(crate) mod test {
         Ok(circuit)
     }
 
+    #[test]
+    fn test_conditional_select_vec() -> Result<(), PlonkError> {
+        test_conditional_select_vec_helper::<FqEd254>()?;
+        test_conditional_select_vec_helper::<FqEd377>()?;
+        test_conditional_select_vec_helper::<FqEd381>()?;
+        test_conditional_select_vec_helper::<Fq377>()
+    }
+
+    fn test_conditional_select_vec_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let cond_true = circuit.create_variable(F::one())?;
+        let cond_false = circuit.create_variable(F::zero())?;
+        let a = (0..5)
+            .map(|i| circuit.create_variable(F::from(i as u32)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let b = (0..5)
+            .map(|i| circuit.create_variable(F::from(100 + i as u32)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let num_gates = circuit.num_gates();
+        let select_true = circuit.conditional_select_vec(cond_true, &a, &b)?;
+        // one boolean gate and one selection gate per element
+        assert_eq!(circuit.num_gates(), num_gates + 1 + a.len());
+        let select_false = circuit.conditional_select_vec(cond_false, &a, &b)?;
+        for i in 0..a.len() {
+            assert_eq!(circuit.witness(select_true[i])?, circuit.witness(a[i])?);
+            assert_eq!(circuit.witness(select_false[i])?, circuit.witness(b[i])?);
+        }
+        assert!(circuit
+            .conditional_select_vec(cond_true, &[], &[])?
+            .is_empty());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // if cond is NOT a boolean variable, should fail
+        let non_bool = circuit.create_variable(F::from(2u32))?;
+        assert!(circuit.conditional_select_vec(non_bool, &a, &b).is_err());
+        assert!(circuit.conditional_select_vec(non_bool, &[], &[]).is_err());
+        // mismatched lengths
+        assert!(circuit
+            .conditional_select_vec(cond_true, &a, &b[..4])
+            .is_err());
+        // if mess up the wire value, should fail
+        *circuit.witness_mut(cond_false) = F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(cond_false) = F::zero();
+        *circuit.witness_mut(select_false[2]) = F::from(2u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .conditional_select_vec(circuit.num_vars(), &a, &b)
+            .is_err());
+        assert!(circuit
+            .conditional_select_vec(cond_true, &[circuit.num_vars()], &[a[0]])
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let circuit_1 = build_conditional_select_vec_circuit(F::one(), &[F::from(23u32); 3])?;
+        let circuit_2 = build_conditional_select_vec_circuit(F::zero(), &[F::from(99u32); 3])?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+        Ok(())
+    }
+
+    fn build_conditional_select_vec_circuit<F: PrimeField>(
+        cond: F,
+        a: &[F],
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let cond_var = circuit.create_variable(cond)?;
+        let a_vars = a
+            .iter()
+            .map(|&a_i| circuit.create_variable(a_i))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let b_vars = a
+            .iter()
+            .map(|&a_i| circuit.create_variable(a_i + F::one()))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.conditional_select_vec(cond_var, &a_vars, &b_vars)?;
+        circuit.finalize_for_arithmetization()?;
+        Ok(circuit)
+    }
+
     #[test]
     fn test_sum() -> Result<(), PlonkError> {
         test_sum_helper::<FqEd254>()?;
@@ -1308,6 +1526,110 @@ pub(crate) mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_unpack_le() -> Result<(), PlonkError> {
+        test_unpack_le_helper::<FqEd254>()?;
+        test_unpack_le_helper::<FqEd377>()?;
+        test_unpack_le_helper::<FqEd381>()?;
+        test_unpack_le_helper::<Fq377>()
+    }
+
+    fn test_unpack_le_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let num_bits = F::size_in_bits();
+        for mut circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            for _ in 0..5 {
+                // random elements, and random 64-bit values
+                let val = F::rand(rng);
+                let a = circuit.create_variable(val)?;
+                let bits_le = circuit.unpack_le(a, num_bits)?;
+                assert_eq!(bits_le.len(), num_bits);
+                let packed = circuit.pack_le(&bits_le)?;
+                assert_eq!(circuit.witness(packed)?, val);
+                circuit.equal_gate(a, packed)?;
+
+                let val = F::from(rng.gen::<u64>());
+                let a = circuit.create_variable(val)?;
+                let bits_le = circuit.unpack_le(a, 64)?;
+                let packed = circuit.pack_le(&bits_le)?;
+                assert_eq!(circuit.witness(packed)?, val);
+            }
+            let packed = circuit.pack_le(&[])?;
+            assert_eq!(circuit.witness(packed)?, F::zero());
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a value that doesn't fit in the bits
+            let a = circuit.create_variable(F::from(200u32))?;
+            assert!(circuit.unpack_le(a, 7).is_err());
+            circuit.unpack_le(a, 8)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            *circuit.witness_mut(a) = F::from(300u32);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // bad bit variables
+            let non_bit = circuit.create_variable(F::from(2u32))?;
+            assert!(circuit.pack_le(&[circuit.one(), non_bit]).is_err());
+            assert!(circuit.pack_le(&[circuit.num_vars()]).is_err());
+            assert!(circuit.unpack_le(circuit.num_vars(), 8).is_err());
+        }
+
+        // the bits of `p + 1` also sum to 1, which `unpack` accepts but not
+        // `unpack_le`
+        let mut non_canonical = F::Params::MODULUS;
+        non_canonical.add_nocarry(&F::BigInt::from(1));
+        let non_canonical_bits_le = non_canonical.to_bits_le();
+        for canonical in [false, true] {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let a = circuit.create_variable(F::one())?;
+            let bits_le = if canonical {
+                circuit.unpack_le(a, num_bits)?
+            } else {
+                circuit.unpack(a, num_bits)?
+            };
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            for (&bit, &b) in bits_le.iter().zip(non_canonical_bits_le.iter()) {
+                *circuit.witness_mut(bit) = F::from(b as u32);
+            }
+            assert_eq!(
+                circuit.check_circuit_satisfiability(&[]).is_ok(),
+                !canonical
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_gate_cost_of_range_check() -> Result<(), PlonkError> {
+        test_gate_cost_of_range_check_helper::<FqEd254>()?;
+        test_gate_cost_of_range_check_helper::<FqEd377>()?;
+        test_gate_cost_of_range_check_helper::<FqEd381>()?;
+        test_gate_cost_of_range_check_helper::<Fq377>()
+    }
+
+    fn test_gate_cost_of_range_check_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(F::one())?;
+        for bit_len in (1..20).chain([64, 128, F::size_in_bits()]) {
+            let num_gates = circuit.num_gates();
+            circuit.range_gate(a, bit_len)?;
+            assert_eq!(
+                circuit.num_gates() - num_gates,
+                PlonkCircuit::<F>::gate_cost_of_range_check(bit_len)
+            );
+            let num_gates = circuit.num_gates();
+            circuit.unpack(a, bit_len)?;
+            assert_eq!(
+                circuit.num_gates() - num_gates,
+                PlonkCircuit::<F>::gate_cost_of_range_check(bit_len)
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
     #[test]
     fn test_range_gate() -> Result<(), PlonkError> {
         test_range_gate_helper::<FqEd254>()?;
@@ -1475,6 +1797,46 @@ pub(crate) mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_enforce_in_set() -> Result<(), PlonkError> {
+        test_enforce_in_set_helper::<FqEd254>()?;
+        test_enforce_in_set_helper::<FqEd377>()?;
+        test_enforce_in_set_helper::<FqEd381>()?;
+        test_enforce_in_set_helper::<Fq377>()
+    }
+
+    fn test_enforce_in_set_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let set = [F::from(3u32), -F::one(), F::from(100u32)];
+        for elem in set.iter() {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let var = circuit.create_variable(*elem)?;
+            circuit.enforce_in_set(var, &set)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            assert_eq!(circuit.num_gates(), 2 * set.len() + 2);
+        }
+
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let var = circuit.create_variable(F::one())?;
+        circuit.enforce_in_set(var, &set)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        circuit.enforce_in_set(var, &[F::one()])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let var = circuit.create_variable(F::from(100u32))?;
+        circuit.enforce_in_set(var, &set)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(var) = F::from(3u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        assert!(circuit.enforce_in_set(var, &[]).is_err());
+        // Check variable out of bound error.
+        assert!(circuit.enforce_in_set(circuit.num_vars(), &set).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_power_11_gen_gate() -> Result<(), PlonkError> {
         test_power_11_gen_gate_helper::<FqEd254>()?;
@@ -0,0 +1,205 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Moving average gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{cmp::min, format};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `avgs[i]` to be the average of the window of the (at most)
+    /// `w` last values up to `values[i]`, rounded down. The first `w - 1`
+    /// windows are partial: `avgs[i]` is the average of `values[0..=i]`.
+    ///
+    /// The values are constrained to be below `2^bit_len`. Each average is
+    /// checked by a cross-multiplied equality `sum = k * avg + remainder`,
+    /// with `k` the window length and `0 <= remainder < k`.
+    /// Return error if variables are invalid, if `values` and `avgs` have
+    /// different lengths, if `w` is zero, or if the window sums may overflow
+    /// the field.
+    pub fn enforce_moving_average(
+        &mut self,
+        values: &[Variable],
+        w: usize,
+        avgs: &[Variable],
+        bit_len: usize,
+    ) -> Result<(), PlonkError> {
+        if values.len() != avgs.len() {
+            return Err(CircuitError::ParameterError(format!(
+                "{} values and {} averages",
+                values.len(),
+                avgs.len()
+            ))
+            .into());
+        }
+        // a window sum is below 2^(bit_len + sum_extra_bit_len)
+        let max_len = min(w, values.len()) as u64;
+        let sum_extra_bit_len = (64 - max_len.leading_zeros()) as usize;
+        if w == 0 || bit_len == 0 || bit_len + sum_extra_bit_len >= F::size_in_bits() {
+            return Err(CircuitError::ParameterError(format!(
+                "invalid window length {} or bit length {}",
+                w, bit_len
+            ))
+            .into());
+        }
+        self.check_vars_bound(values)?;
+        self.check_vars_bound(avgs)?;
+
+        for &value in values.iter() {
+            self.range_gate(value, bit_len)?;
+        }
+        let zero = self.zero();
+        let mut sum = zero;
+        for (i, (&value, &avg)) in values.iter().zip(avgs.iter()).enumerate() {
+            // slide the window: add the new value and drop the oldest one
+            sum = if i >= w {
+                self.lc(
+                    &[sum, value, values[i - w], zero],
+                    &[F::one(), F::one(), -F::one(), F::zero()],
+                )?
+            } else {
+                self.add(sum, value)?
+            };
+
+            // sum = k * avg + remainder, with 0 <= remainder < k
+            let k = min(i + 1, w) as u64;
+            let remainder =
+                self.create_variable(self.witness(sum)? - self.witness(avg)? * F::from(k))?;
+            if k == 1 {
+                self.constant_gate(remainder, F::zero())?;
+            } else {
+                let remainder_bit_len = (64 - (k - 1).leading_zeros()) as usize;
+                let max_remainder = self.create_constant_variable(F::from(k - 1))?;
+                let gap = self.sub(max_remainder, remainder)?;
+                self.range_gate(remainder, remainder_bit_len)?;
+                self.range_gate(gap, remainder_bit_len)?;
+            }
+            self.range_gate(avg, bit_len)?;
+            self.lc_gate(
+                &[avg, remainder, zero, zero, sum],
+                &[F::from(k), F::one(), F::zero(), F::zero()],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec::Vec;
+
+    const BIT_LEN: usize = 16;
+
+    // Native moving average, rounded down, with partial initial windows.
+    fn moving_average(values: &[u64], w: usize) -> Vec<u64> {
+        (0..values.len())
+            .map(|i| {
+                let window = &values[(i + 1).saturating_sub(w)..=i];
+                window.iter().sum::<u64>() / window.len() as u64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_enforce_moving_average() -> Result<(), PlonkError> {
+        test_enforce_moving_average_helper::<FqEd254>()?;
+        test_enforce_moving_average_helper::<FqEd377>()?;
+        test_enforce_moving_average_helper::<FqEd381>()?;
+        test_enforce_moving_average_helper::<Fq377>()
+    }
+
+    fn test_enforce_moving_average_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let values = [10u64, 20, 31, 7, 65535, 0, 0, 42, 13, 13];
+        assert_eq!(moving_average(&values[..4], 3), [10, 15, 20, 19]);
+
+        // averages matching the native computation, including windows longer
+        // than the sequence
+        for &w in [1, 2, 3, 4, 7, 10, 12].iter() {
+            let avgs = moving_average(&values, w);
+            let (circuit, _) = build_moving_average_circuit::<F>(&values, w, &avgs)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            for i in 0..values.len() {
+                let mut wrong_avgs = avgs.clone();
+                wrong_avgs[i] += 1;
+                let (circuit, _) = build_moving_average_circuit::<F>(&values, w, &wrong_avgs)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                if avgs[i] > 0 {
+                    wrong_avgs[i] -= 2;
+                    let (circuit, _) = build_moving_average_circuit::<F>(&values, w, &wrong_avgs)?;
+                    assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                }
+            }
+        }
+        // out of range values
+        let (circuit, _) =
+            build_moving_average_circuit::<F>(&[1 << BIT_LEN, 0], 2, &[65536, 32768])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let avgs = moving_average(&values, 3);
+        let (mut circuit, value_vars) = build_moving_average_circuit::<F>(&values, 3, &avgs)?;
+        *circuit.witness_mut(value_vars[5]) = F::from(3u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_moving_average(&[zero, zero], 2, &[zero], BIT_LEN)
+            .is_err());
+        assert!(circuit
+            .enforce_moving_average(&[zero], 0, &[zero], BIT_LEN)
+            .is_err());
+        assert!(circuit
+            .enforce_moving_average(&[zero], 1, &[zero], F::size_in_bits())
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_moving_average(&[zero], 1, &[circuit.num_vars()], BIT_LEN)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let other_values = [1u64, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let (mut circuit_1, _) =
+            build_moving_average_circuit::<F>(&values, 4, &moving_average(&values, 4))?;
+        let (mut circuit_2, _) =
+            build_moving_average_circuit::<F>(&other_values, 4, &moving_average(&other_values, 4))?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_moving_average_circuit<F: PrimeField>(
+        values: &[u64],
+        w: usize,
+        avgs: &[u64],
+    ) -> Result<(PlonkCircuit<F>, Vec<Variable>), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let mut create_vars = |vals: &[u64]| {
+            vals.iter()
+                .map(|&val| circuit.create_variable(F::from(val)))
+                .collect::<Result<Vec<_>, PlonkError>>()
+        };
+        let value_vars = create_vars(values)?;
+        let avg_vars = create_vars(avgs)?;
+        circuit.enforce_moving_average(&value_vars, w, &avg_vars, BIT_LEN)?;
+        Ok((circuit, value_vars))
+    }
+}
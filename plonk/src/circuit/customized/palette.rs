@@ -0,0 +1,128 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Color palette membership gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// The bit length of a color channel.
+const CHANNEL_BIT_LEN: usize = 8;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain the RGB color (`r`, `g`, `b`) to be one of the colors of
+    /// `palette`. Each channel is constrained to be a byte, and the color is
+    /// packed into a single variable before checking its membership.
+    /// Return error if variables are invalid or if `palette` is empty.
+    pub fn enforce_in_palette(
+        &mut self,
+        r: Variable,
+        g: Variable,
+        b: Variable,
+        palette: &[[u8; 3]],
+    ) -> Result<(), PlonkError> {
+        let channels = [r, g, b];
+        self.check_vars_bound(&channels)?;
+        for &channel in channels.iter() {
+            self.range_gate(channel, CHANNEL_BIT_LEN)?;
+        }
+        let packed = self.lc(&[r, g, b, self.zero()], &pack_coeffs())?;
+        let packed_palette: Vec<F> = palette.iter().map(pack_color).collect();
+        self.enforce_in_set(packed, &packed_palette)
+    }
+}
+
+// Coefficients of the channels in a packed color.
+fn pack_coeffs<F: PrimeField>() -> [F; 4] {
+    [
+        F::from(1u32 << (2 * CHANNEL_BIT_LEN)),
+        F::from(1u32 << CHANNEL_BIT_LEN),
+        F::one(),
+        F::zero(),
+    ]
+}
+
+fn pack_color<F: PrimeField>(color: &[u8; 3]) -> F {
+    let coeffs = pack_coeffs::<F>();
+    color
+        .iter()
+        .zip(coeffs.iter())
+        .map(|(&channel, &coeff)| F::from(channel) * coeff)
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    const PALETTE: [[u8; 3]; 4] = [[0, 0, 0], [255, 255, 255], [255, 0, 0], [12, 34, 56]];
+
+    #[test]
+    fn test_enforce_in_palette() -> Result<(), PlonkError> {
+        test_enforce_in_palette_helper::<FqEd254>()?;
+        test_enforce_in_palette_helper::<FqEd377>()?;
+        test_enforce_in_palette_helper::<FqEd381>()?;
+        test_enforce_in_palette_helper::<Fq377>()
+    }
+
+    fn test_enforce_in_palette_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // palette members
+        for color in PALETTE.iter() {
+            let color = [color[0] as u32, color[1] as u32, color[2] as u32];
+            let circuit = build_palette_circuit::<F>(&color)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+
+        // non-member colors
+        let circuit = build_palette_circuit::<F>(&[12, 34, 57])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_palette_circuit::<F>(&[0, 255, 0])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // a non-byte channel with the same packed value as a member
+        let circuit = build_palette_circuit::<F>(&[254, 256, 0])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit.enforce_in_palette(zero, zero, zero, &[]).is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_in_palette(zero, circuit.num_vars(), zero, &PALETTE)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_palette_circuit::<F>(&[255, 0, 0])?;
+        let mut circuit_2 = build_palette_circuit::<F>(&[1, 2, 3])?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_palette_circuit<F: PrimeField>(
+        color: &[u32; 3],
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let r = circuit.create_variable(F::from(color[0]))?;
+        let g = circuit.create_variable(F::from(color[1]))?;
+        let b = circuit.create_variable(F::from(color[2]))?;
+        circuit.enforce_in_palette(r, g, b, &PALETTE)?;
+        Ok(circuit)
+    }
+}
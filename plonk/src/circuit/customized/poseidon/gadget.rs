@@ -0,0 +1,245 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+use super::params::{PoseidonParams, POSEIDON_ALPHA};
+use crate::{
+    circuit::{gates::Gate, Circuit, PlonkCircuit, Variable},
+    constants::GATE_WIDTH,
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{boxed::Box, format, vec::Vec};
+
+/// Gate for one element of the output of a Poseidon round:
+/// `var_output = <q_lc, vars> + <q_hash, vars^5> + constant`.
+#[derive(Debug, Clone)]
+pub(crate) struct PoseidonRoundGate<F> {
+    pub(crate) q_lc: [F; GATE_WIDTH],
+    pub(crate) q_hash: [F; GATE_WIDTH],
+    pub(crate) constant: F,
+}
+
+impl<F: PrimeField> Gate<F> for PoseidonRoundGate<F> {
+    fn name(&self) -> &'static str {
+        "Poseidon round gate"
+    }
+
+    fn q_lc(&self) -> [F; GATE_WIDTH] {
+        self.q_lc
+    }
+
+    fn q_hash(&self) -> [F; GATE_WIDTH] {
+        self.q_hash
+    }
+
+    fn q_c(&self) -> F {
+        self.constant
+    }
+
+    fn q_o(&self) -> F {
+        F::one()
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    /// Poseidon hash of `params.arity()` variables, i.e. the first element of
+    /// the Poseidon permutation of `[0, inputs...]`. It matches
+    /// [`PoseidonParams::hash`].
+    /// Return error if the number of inputs is not the arity of `params` or
+    /// if some input variable is invalid.
+    pub fn poseidon_hash(
+        &mut self,
+        inputs: &[Variable],
+        params: &PoseidonParams<F>,
+    ) -> Result<Variable, PlonkError> {
+        if inputs.len() != params.arity() {
+            return Err(ParameterError(format!(
+                "Poseidon hash of arity {} takes {} inputs, got {}",
+                params.arity(),
+                params.arity(),
+                inputs.len()
+            ))
+            .into());
+        }
+        self.check_vars_bound(inputs)?;
+
+        let state = [&[self.zero()][..], inputs].concat();
+        Ok(self.poseidon_permutation(&state, params)?[0])
+    }
+
+    /// The Poseidon permutation of `state`, which has `params.width()`
+    /// variables.
+    ///
+    /// The round constants of a round are added by the gates of the
+    /// previous one, so that each output element of a round is a single
+    /// gate when the state fits in `GATE_WIDTH` wires.
+    fn poseidon_permutation(
+        &mut self,
+        state: &[Variable],
+        params: &PoseidonParams<F>,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let width = params.width();
+        let mut state = state
+            .iter()
+            .zip(params.round_constants[0].iter())
+            .map(|(&var, c)| self.add_constant(var, c))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for round in 0..params.round_constants.len() {
+            let num_sbox = if params.is_full_round(round) {
+                width
+            } else {
+                1
+            };
+            let next_constants = params.round_constants.get(round + 1);
+            let mut next_state = Vec::with_capacity(width);
+            for (i, row) in params.mds.iter().enumerate() {
+                let constant = next_constants.map_or_else(F::zero, |rc| rc[i]);
+                next_state.push(self.poseidon_round_output(&state, row, num_sbox, constant)?);
+            }
+            state = next_state;
+        }
+        Ok(state)
+    }
+
+    /// Return `sum_{j < num_sbox} row[j] * state[j]^5 + sum_{j >= num_sbox}
+    /// row[j] * state[j] + constant`, chaining gates over the state elements
+    /// that do not fit in a single gate.
+    fn poseidon_round_output(
+        &mut self,
+        state: &[Variable],
+        row: &[F],
+        num_sbox: usize,
+        constant: F,
+    ) -> Result<Variable, PlonkError> {
+        let zero = self.zero();
+        // (variable, linear coefficient, power 5 coefficient)
+        let terms: Vec<(Variable, F, F)> = state
+            .iter()
+            .zip(row.iter())
+            .enumerate()
+            .map(|(j, (&var, &coeff))| {
+                if j < num_sbox {
+                    (var, F::zero(), coeff)
+                } else {
+                    (var, coeff, F::zero())
+                }
+            })
+            .collect();
+
+        let mut acc: Option<Variable> = None;
+        let mut remaining = &terms[..];
+        loop {
+            let mut wire_vars = [zero; GATE_WIDTH + 1];
+            let mut q_lc = [F::zero(); GATE_WIDTH];
+            let mut q_hash = [F::zero(); GATE_WIDTH];
+            // the partial sum of the previous gate takes the first wire
+            let offset = if let Some(acc) = acc {
+                wire_vars[0] = acc;
+                q_lc[0] = F::one();
+                1
+            } else {
+                0
+            };
+            let (chunk, rest) = remaining.split_at(remaining.len().min(GATE_WIDTH - offset));
+            for (k, &(var, lc_coeff, hash_coeff)) in chunk.iter().enumerate() {
+                wire_vars[offset + k] = var;
+                q_lc[offset + k] = lc_coeff;
+                q_hash[offset + k] = hash_coeff;
+            }
+            let constant = if rest.is_empty() { constant } else { F::zero() };
+
+            let mut output_val = constant;
+            for ((&var, lc_coeff), hash_coeff) in
+                wire_vars.iter().zip(q_lc.iter()).zip(q_hash.iter())
+            {
+                let val = self.witness(var)?;
+                output_val += *lc_coeff * val + *hash_coeff * val.pow([POSEIDON_ALPHA]);
+            }
+            let output_var = self.create_variable(output_val)?;
+            wire_vars[GATE_WIDTH] = output_var;
+            self.insert_gate(
+                &wire_vars,
+                Box::new(PoseidonRoundGate {
+                    q_lc,
+                    q_hash,
+                    constant,
+                }),
+            )?;
+
+            if rest.is_empty() {
+                return Ok(output_var);
+            }
+            acc = Some(output_var);
+            remaining = rest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PoseidonParams;
+    use crate::{
+        circuit::{Circuit, PlonkCircuit},
+        constants::GATE_WIDTH,
+        errors::PlonkError,
+    };
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::PrimeField;
+    use ark_std::vec::Vec;
+
+    #[test]
+    fn test_poseidon_hash() -> Result<(), PlonkError> {
+        test_poseidon_hash_helper::<FqEd254>()?;
+        test_poseidon_hash_helper::<FqEd381>()?;
+        test_poseidon_hash_helper::<Fq377>()
+    }
+
+    fn test_poseidon_hash_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        for &(width, full_rounds, partial_rounds) in [(3, 8, 57), (5, 8, 60)].iter() {
+            let params = PoseidonParams::<F>::new(width, full_rounds, partial_rounds)?;
+            let inputs: Vec<F> = (1..width as u64).map(F::from).collect();
+            let expected = params.hash(&inputs)?;
+
+            let mut circuit = PlonkCircuit::<F>::new();
+            let input_vars = inputs
+                .iter()
+                .map(|&input| circuit.create_variable(input))
+                .collect::<Result<Vec<_>, _>>()?;
+            let num_gates = circuit.num_gates();
+            let output_var = circuit.poseidon_hash(&input_vars, &params)?;
+            assert_eq!(circuit.witness(output_var)?, expected);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // initial round constants, then one gate per output element of a
+            // round, chained over the state elements beyond the gate width
+            let gates_per_output = 1 + (width.max(GATE_WIDTH) - GATE_WIDTH + 2) / 3;
+            assert_eq!(
+                circuit.num_gates(),
+                num_gates + width + (full_rounds + partial_rounds) * width * gates_per_output
+            );
+
+            // wrong output
+            *circuit.witness_mut(output_var) = expected + F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // wrong number of inputs
+            assert!(circuit
+                .poseidon_hash(&input_vars[..width - 2], &params)
+                .is_err());
+            // out of bound variable
+            let mut bad_vars = input_vars.clone();
+            bad_vars[0] = circuit.num_vars();
+            assert!(circuit.poseidon_hash(&bad_vars, &params).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Poseidon hash related parameters and gadgets over the native field.
+//!
+//! The permutation uses the `x^5` S-box, hence it is only defined over
+//! fields where `gcd(5, p - 1) = 1`. The default round constants and MDS
+//! matrix are generated with the Grain LFSR of the Poseidon reference
+//! implementation, so that hashes agree with trees built outside of the
+//! circuit (e.g. with circomlib).
+
+mod gadget;
+mod params;
+
+pub use params::PoseidonParams;
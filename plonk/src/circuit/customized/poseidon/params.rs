@@ -0,0 +1,395 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+use crate::errors::{CircuitError::ParameterError, PlonkError};
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_std::{format, string::ToString, vec::Vec};
+use num_bigint::BigUint;
+
+/// The exponent of the Poseidon S-box.
+pub(crate) const POSEIDON_ALPHA: u64 = 5;
+
+/// Parameters of a Poseidon permutation over `F` with the `x^5` S-box.
+///
+/// The state has `width` elements: a single capacity element and
+/// `width - 1` rate elements, so that [`PoseidonParams::hash`] compresses
+/// `width - 1` elements (e.g. `width = 3` for a binary Merkle tree).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoseidonParams<F: PrimeField> {
+    pub(crate) full_rounds: usize,
+    pub(crate) partial_rounds: usize,
+    pub(crate) mds: Vec<Vec<F>>,
+    pub(crate) round_constants: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> PoseidonParams<F> {
+    /// Generate the standard parameters for a state of `width` elements with
+    /// `full_rounds` full rounds and `partial_rounds` partial rounds, e.g.
+    /// `(3, 8, 57)` and `(5, 8, 60)` for arity 2 and 4 over the BN254 scalar
+    /// field.
+    ///
+    /// The round constants and the Cauchy MDS matrix are sampled from the
+    /// Grain LFSR exactly as the Poseidon reference script does. The script
+    /// additionally resamples the MDS matrix when it fails its invariant
+    /// subspace checks; this never happens for the commonly used instances,
+    /// the other ones should be passed to [`PoseidonParams::from_constants`].
+    pub fn new(
+        width: usize,
+        full_rounds: usize,
+        partial_rounds: usize,
+    ) -> Result<Self, PlonkError> {
+        check_shape::<F>(width, full_rounds)?;
+        if width >= 1 << 12 || full_rounds >= 1 << 10 || partial_rounds >= 1 << 10 {
+            return Err(ParameterError(format!(
+                "Poseidon instance (width {}, {} full rounds, {} partial rounds) is out of the \
+                 range of the Grain LFSR",
+                width, full_rounds, partial_rounds
+            ))
+            .into());
+        }
+
+        let mut grain = GrainLfsr::new::<F>(width, full_rounds, partial_rounds);
+        let round_constants = (0..full_rounds + partial_rounds)
+            .map(|_| (0..width).map(|_| grain.next_field_element()).collect())
+            .collect();
+        let mds = grain.next_cauchy_matrix(width);
+
+        Ok(Self {
+            full_rounds,
+            partial_rounds,
+            mds,
+            round_constants,
+        })
+    }
+
+    /// Build the parameters from externally generated constants: `mds` is a
+    /// `width x width` matrix and `round_constants` holds `width` constants
+    /// per round.
+    pub fn from_constants(
+        full_rounds: usize,
+        partial_rounds: usize,
+        mds: Vec<Vec<F>>,
+        round_constants: Vec<Vec<F>>,
+    ) -> Result<Self, PlonkError> {
+        let width = mds.len();
+        check_shape::<F>(width, full_rounds)?;
+        if mds.iter().any(|row| row.len() != width) {
+            return Err(ParameterError("Poseidon MDS matrix is not square".to_string()).into());
+        }
+        if round_constants.len() != full_rounds + partial_rounds
+            || round_constants.iter().any(|rc| rc.len() != width)
+        {
+            return Err(ParameterError(format!(
+                "Poseidon round constants should be {} vectors of {} elements",
+                full_rounds + partial_rounds,
+                width
+            ))
+            .into());
+        }
+
+        Ok(Self {
+            full_rounds,
+            partial_rounds,
+            mds,
+            round_constants,
+        })
+    }
+
+    /// The number of elements of the state.
+    pub fn width(&self) -> usize {
+        self.mds.len()
+    }
+
+    /// The number of elements compressed by [`PoseidonParams::hash`].
+    pub fn arity(&self) -> usize {
+        self.width() - 1
+    }
+
+    /// The number of full rounds.
+    pub fn full_rounds(&self) -> usize {
+        self.full_rounds
+    }
+
+    /// The number of partial rounds.
+    pub fn partial_rounds(&self) -> usize {
+        self.partial_rounds
+    }
+
+    /// Whether the `round`-th round applies the S-box to the whole state.
+    pub(crate) fn is_full_round(&self, round: usize) -> bool {
+        let half = self.full_rounds / 2;
+        round < half || round >= half + self.partial_rounds
+    }
+
+    /// The Poseidon permutation of `state`, which should have `width`
+    /// elements.
+    pub fn permute(&self, state: &[F]) -> Result<Vec<F>, PlonkError> {
+        if state.len() != self.width() {
+            return Err(ParameterError(format!(
+                "Poseidon state has {} elements, expected {}",
+                state.len(),
+                self.width()
+            ))
+            .into());
+        }
+
+        let mut state = state.to_vec();
+        for (round, rc) in self.round_constants.iter().enumerate() {
+            state.iter_mut().zip(rc).for_each(|(x, c)| *x += c);
+            if self.is_full_round(round) {
+                state.iter_mut().for_each(|x| *x = x.pow([POSEIDON_ALPHA]));
+            } else {
+                state[0] = state[0].pow([POSEIDON_ALPHA]);
+            }
+            state = self
+                .mds
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(state.iter())
+                        .fold(F::zero(), |acc, (m, x)| acc + *m * x)
+                })
+                .collect();
+        }
+        Ok(state)
+    }
+
+    /// Hash `arity` elements: the permutation is applied to the state
+    /// `[0, inputs...]` and its first element is returned.
+    pub fn hash(&self, inputs: &[F]) -> Result<F, PlonkError> {
+        if inputs.len() != self.arity() {
+            return Err(ParameterError(format!(
+                "Poseidon hash of arity {} takes {} inputs, got {}",
+                self.arity(),
+                self.arity(),
+                inputs.len()
+            ))
+            .into());
+        }
+        let state = [&[F::zero()][..], inputs].concat();
+        Ok(self.permute(&state)?[0])
+    }
+}
+
+fn check_shape<F: PrimeField>(width: usize, full_rounds: usize) -> Result<(), PlonkError> {
+    if width < 2 {
+        return Err(ParameterError(format!(
+            "Poseidon state should have at least 2 elements, got {}",
+            width
+        ))
+        .into());
+    }
+    if full_rounds % 2 != 0 {
+        return Err(ParameterError(format!(
+            "Poseidon full rounds should be even, got {}",
+            full_rounds
+        ))
+        .into());
+    }
+    if modulus::<F>() % (POSEIDON_ALPHA as u32) == BigUint::from(1u32) {
+        return Err(ParameterError(
+            "x^5 is not a permutation of the field, Poseidon is undefined".to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn modulus<F: PrimeField>() -> BigUint {
+    BigUint::from_bytes_le(&F::Params::MODULUS.to_bytes_le())
+}
+
+/// The Grain LFSR used by the Poseidon reference script to sample the round
+/// constants and the MDS matrix of an instance.
+struct GrainLfsr {
+    state: [bool; 80],
+    head: usize,
+    modulus: BigUint,
+    num_bits: usize,
+}
+
+impl GrainLfsr {
+    fn new<F: PrimeField>(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let num_bits = F::Params::MODULUS_BITS as usize;
+        let mut state = [true; 80];
+        // (value, bit length): prime field, x^alpha S-box, field size, width,
+        // rounds; the remaining 30 bits are ones
+        let header = [
+            (1, 2),
+            (0, 4),
+            (num_bits, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+        ];
+        let mut pos = 0;
+        for (value, len) in header.iter() {
+            for i in (0..*len).rev() {
+                state[pos] = (value >> i) & 1 == 1;
+                pos += 1;
+            }
+        }
+
+        let mut grain = Self {
+            state,
+            head: 0,
+            modulus: modulus::<F>(),
+            num_bits,
+        };
+        for _ in 0..160 {
+            grain.clock();
+        }
+        grain
+    }
+
+    fn clock(&mut self) -> bool {
+        let bit = |i: usize| self.state[(self.head + i) % 80];
+        let new_bit = bit(62) ^ bit(51) ^ bit(38) ^ bit(23) ^ bit(13) ^ bit(0);
+        self.state[self.head] = new_bit;
+        self.head = (self.head + 1) % 80;
+        new_bit
+    }
+
+    // Bits are produced in pairs, the second one is kept iff the first one is
+    // set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.clock();
+            let bit = self.clock();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    // A `num_bits`-bit integer, most significant bit first.
+    fn next_integer(&mut self) -> BigUint {
+        let digits: Vec<u8> = (0..self.num_bits).map(|_| self.next_bit() as u8).collect();
+        BigUint::from_radix_be(&digits, 2).unwrap()
+    }
+
+    fn to_field<F: PrimeField>(n: &BigUint) -> F {
+        F::from_le_bytes_mod_order(&n.to_bytes_le())
+    }
+
+    // Round constants are sampled by rejection.
+    fn next_field_element<F: PrimeField>(&mut self) -> F {
+        loop {
+            let n = self.next_integer();
+            if n < self.modulus {
+                return Self::to_field(&n);
+            }
+        }
+    }
+
+    // The Cauchy matrix `1 / (x_i + y_j)` for `2 * width` distinct elements
+    // reduced modulo p.
+    fn next_cauchy_matrix<F: PrimeField>(&mut self, width: usize) -> Vec<Vec<F>> {
+        let modulus = self.modulus.clone();
+        loop {
+            let ints: Vec<BigUint> = (0..2 * width)
+                .map(|_| self.next_integer() % &modulus)
+                .collect();
+            let mut sorted = ints.clone();
+            sorted.sort();
+            sorted.dedup();
+            if sorted.len() != ints.len() {
+                continue;
+            }
+            let elems: Vec<F> = ints.iter().map(Self::to_field).collect();
+            let (xs, ys) = elems.split_at(width);
+            let matrix: Option<Vec<Vec<F>>> = xs
+                .iter()
+                .map(|x| ys.iter().map(|y| (*x + y).inverse()).collect())
+                .collect();
+            if let Some(matrix) = matrix {
+                return matrix;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PoseidonParams;
+    use crate::errors::PlonkError;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::PrimeField;
+    use ark_std::{vec, vec::Vec};
+
+    fn from_hex<F: PrimeField>(s: &str) -> F {
+        F::from_be_bytes_mod_order(&hex::decode(s).unwrap())
+    }
+
+    #[test]
+    fn test_poseidon_reference_vectors() -> Result<(), PlonkError> {
+        // test vectors of the Poseidon reference implementation over the
+        // BN254 scalar field
+        let params = PoseidonParams::<FqEd254>::new(3, 8, 57)?;
+        let state: Vec<FqEd254> = (0..3u64).map(FqEd254::from).collect();
+        assert_eq!(
+            params.permute(&state)?,
+            vec![
+                from_hex("115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"),
+                from_hex("0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29"),
+                from_hex("0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c"),
+            ]
+        );
+        assert_eq!(
+            params.hash(&[FqEd254::from(1u64), FqEd254::from(2u64)])?,
+            from_hex("115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a")
+        );
+
+        let params = PoseidonParams::<FqEd254>::new(5, 8, 60)?;
+        let state: Vec<FqEd254> = (0..5u64).map(FqEd254::from).collect();
+        assert_eq!(
+            params.permute(&state)?,
+            vec![
+                from_hex("299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465"),
+                from_hex("1148aaef609aa338b27dafd89bb98862d8bb2b429aceac47d86206154ffe053d"),
+                from_hex("24febb87fed7462e23f6665ff9a0111f4044c38ee1672c1ac6b0637d34f24907"),
+                from_hex("0eb08f6d809668a981c186beaf6110060707059576406b248e5d9cf6e78b3d3e"),
+                from_hex("07748bc6877c9b82c8b98666ee9d0626ec7f5be4205f79ee8528ef1c4a376fc7"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_poseidon_params_errors() -> Result<(), PlonkError> {
+        // x^5 is not a permutation of the field
+        assert!(PoseidonParams::<FqEd377>::new(3, 8, 57).is_err());
+        assert!(PoseidonParams::<Fq377>::new(1, 8, 57).is_err());
+        assert!(PoseidonParams::<Fq377>::new(3, 7, 57).is_err());
+
+        let params = PoseidonParams::<Fq377>::new(3, 8, 57)?;
+        assert!(PoseidonParams::from_constants(
+            8,
+            57,
+            params.mds.clone(),
+            params.round_constants.clone()
+        )
+        .is_ok());
+        assert!(PoseidonParams::from_constants(
+            8,
+            56,
+            params.mds.clone(),
+            params.round_constants.clone()
+        )
+        .is_err());
+        let mut mds = params.mds.clone();
+        mds[1].pop();
+        assert!(
+            PoseidonParams::from_constants(8, 57, mds, params.round_constants.clone()).is_err()
+        );
+
+        assert!(params.hash(&[Fq377::from(1u64)]).is_err());
+        assert!(params.permute(&[Fq377::from(1u64); 2]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Prefix code gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain the codewords to form a prefix code, i.e. no codeword is a
+    /// prefix of another one (hence no two codewords are equal).
+    ///
+    /// Each codeword is given as a pair (`bits`, `length`), where `bits` are
+    /// the bits of the codeword, first bit first, and only the first `length`
+    /// of them are significant. All the `bits` slices have the same length,
+    /// the maximal codeword length. The bits are constrained to be boolean,
+    /// and the lengths to be at most the maximal codeword length.
+    /// Return error if variables are invalid, or if the `bits` slices have
+    /// different lengths.
+    ///
+    /// Cost: O(`codewords.len()`^2 * maximal codeword length) constraints.
+    pub fn enforce_prefix_free(
+        &mut self,
+        codewords: &[(&[Variable], Variable)],
+    ) -> Result<(), PlonkError> {
+        let max_len = codewords.first().map_or(0, |(bits, _)| bits.len());
+        for (bits, length) in codewords.iter() {
+            if bits.len() != max_len {
+                return Err(CircuitError::ParameterError(format!(
+                    "codewords have {} and {} bits",
+                    max_len,
+                    bits.len()
+                ))
+                .into());
+            }
+            self.check_vars_bound(bits)?;
+            self.check_var_bound(*length)?;
+        }
+
+        // `masks[i][k]` is one iff the k-th bit of the i-th codeword is
+        // significant, i.e. k < length.
+        let mut masks = Vec::with_capacity(codewords.len());
+        for (bits, length) in codewords.iter() {
+            for &bit in bits.iter() {
+                self.bool_gate(bit)?;
+            }
+            let length_val = self.witness(*length)?.into_repr();
+            let mask = (0..max_len)
+                .map(|k| self.create_bool_variable(F::from(k as u64).into_repr() < length_val))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            // The mask is a run of ones followed by zeros, of `length` ones.
+            for k in 1..max_len {
+                // mask[k] * mask[k-1] = mask[k]
+                self.mul_gate(mask[k], mask[k - 1], mask[k])?;
+            }
+            if mask.is_empty() {
+                self.constant_gate(*length, F::zero())?;
+            } else {
+                let num_ones = self.sum(&mask)?;
+                self.equal_gate(num_ones, *length)?;
+            }
+            masks.push(mask);
+        }
+
+        // Any two codewords differ at some position significant for both.
+        for i in 0..codewords.len() {
+            for j in i + 1..codewords.len() {
+                let mut differences = Vec::with_capacity(max_len);
+                for k in 0..max_len {
+                    let significant = self.mul(masks[i][k], masks[j][k])?;
+                    let diff = self.sub(codewords[i].0[k], codewords[j].0[k])?;
+                    // diff^2 is the xor of the two bits
+                    let differ = self.mul(diff, diff)?;
+                    differences.push(self.mul(significant, differ)?);
+                }
+                let num_differences = if differences.is_empty() {
+                    self.zero()
+                } else {
+                    self.sum(&differences)?
+                };
+                self.non_zero_gate(num_differences)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec;
+
+    #[test]
+    fn test_enforce_prefix_free() -> Result<(), PlonkError> {
+        test_enforce_prefix_free_helper::<FqEd254>()?;
+        test_enforce_prefix_free_helper::<FqEd377>()?;
+        test_enforce_prefix_free_helper::<FqEd381>()?;
+        test_enforce_prefix_free_helper::<Fq377>()
+    }
+
+    fn test_enforce_prefix_free_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // valid prefix codes
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["0", "10", "110", "111"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["00", "01", "1"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, _) = build_prefix_free_circuit::<F>(&[""])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a codeword is a prefix of another one
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["0", "10", "01"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["110", "0", "11"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["", "1"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // equal codewords
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["10", "0", "10"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // insignificant bits are ignored
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["0|1", "10", "11"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["0|11", "10|0", "11|1"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, _) = build_prefix_free_circuit::<F>(&["1|0", "10", "0"])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let (mut circuit, codewords) = build_prefix_free_circuit::<F>(&["0", "10", "11"])?;
+        *circuit.witness_mut(codewords[0].0[0]) = F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (mut circuit, codewords) = build_prefix_free_circuit::<F>(&["0", "10", "11"])?;
+        *circuit.witness_mut(codewords[1].1) = F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (mut circuit, codewords) = build_prefix_free_circuit::<F>(&["0", "10", "11"])?;
+        *circuit.witness_mut(codewords[1].0[0]) = F::from(2u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // length larger than the number of bits
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let bits = vec![circuit.zero(), circuit.one()];
+        let length = circuit.create_variable(F::from(3u32))?;
+        circuit.enforce_prefix_free(&[(&bits[..], length)])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let bits = vec![circuit.zero(), circuit.one()];
+        let length = circuit.one();
+        assert!(circuit
+            .enforce_prefix_free(&[(&bits[..], length), (&bits[..1], length)])
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_prefix_free(&[(&bits[..], circuit.num_vars())])
+            .is_err());
+        assert!(circuit
+            .enforce_prefix_free(&[(&[circuit.num_vars()][..], length)])
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let (mut circuit_1, _) = build_prefix_free_circuit::<F>(&["0", "10", "110", "111"])?;
+        let (mut circuit_2, _) = build_prefix_free_circuit::<F>(&["111", "", "01", "101"])?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // Codewords are given as strings of '0' and '1', optionally followed by '|'
+    // and insignificant bits, and padded with zeros to the maximal length.
+    #[allow(clippy::type_complexity)]
+    fn build_prefix_free_circuit<F: PrimeField>(
+        codewords: &[&str],
+    ) -> Result<(PlonkCircuit<F>, Vec<(Vec<Variable>, Variable)>), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let max_len = codewords
+            .iter()
+            .map(|c| c.replace('|', "").len())
+            .max()
+            .unwrap_or(0);
+        let codeword_vars = codewords
+            .iter()
+            .map(|c| {
+                let length = c.find('|').unwrap_or(c.len());
+                let c = c.replace('|', "");
+                let bits = (0..max_len)
+                    .map(|k| {
+                        let bit = c.as_bytes().get(k) == Some(&b'1');
+                        circuit.create_variable(F::from(bit as u64))
+                    })
+                    .collect::<Result<Vec<_>, PlonkError>>()?;
+                let length = circuit.create_variable(F::from(length as u64))?;
+                Ok((bits, length))
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let codeword_refs: Vec<(&[Variable], Variable)> = codeword_vars
+            .iter()
+            .map(|(bits, length)| (bits.as_slice(), *length))
+            .collect();
+        circuit.enforce_prefix_free(&codeword_refs)?;
+        Ok((circuit, codeword_vars))
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Quorum threshold gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain at least `threshold` of the `valid_flags` to be set, e.g. the
+    /// validity flags of the signatures of a quorum.
+    ///
+    /// The flags are constrained to be boolean.
+    /// Return error if variables are invalid, if `valid_flags` is empty, or if
+    /// `threshold` exceeds the number of flags.
+    pub fn enforce_quorum(
+        &mut self,
+        valid_flags: &[Variable],
+        threshold: usize,
+    ) -> Result<(), PlonkError> {
+        if valid_flags.is_empty() {
+            return Err(CircuitError::ParameterError(
+                "the set of flags should not be empty".to_string(),
+            )
+            .into());
+        }
+        if threshold > valid_flags.len() {
+            return Err(CircuitError::ParameterError(format!(
+                "the threshold {} exceeds the number of flags {}",
+                threshold,
+                valid_flags.len()
+            ))
+            .into());
+        }
+        self.check_vars_bound(valid_flags)?;
+        for &flag in valid_flags.iter() {
+            self.bool_gate(flag)?;
+        }
+
+        // both the number of set flags and the threshold are at most the
+        // number of flags, which is below 2^bit_len
+        let bit_len = (0usize.leading_zeros() - valid_flags.len().leading_zeros()) as usize;
+        let num_set = self.sum(valid_flags)?;
+        let threshold = self.create_constant_variable(F::from(threshold as u64))?;
+        let quorum_reached = self.is_leq(threshold, num_set, bit_len)?;
+        self.enforce_true(quorum_reached)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec::Vec;
+
+    #[test]
+    fn test_enforce_quorum() -> Result<(), PlonkError> {
+        test_enforce_quorum_helper::<FqEd254>()?;
+        test_enforce_quorum_helper::<FqEd377>()?;
+        test_enforce_quorum_helper::<FqEd381>()?;
+        test_enforce_quorum_helper::<Fq377>()
+    }
+
+    fn test_enforce_quorum_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // 3 of 5 flags set
+        let flags = [true, false, true, false, true];
+        for threshold in 0..=flags.len() {
+            let circuit = build_quorum_circuit::<F>(&flags, threshold)?;
+            // exactly at or above the threshold, or below it
+            assert_eq!(
+                circuit.check_circuit_satisfiability(&[]).is_ok(),
+                threshold <= 3,
+                "threshold {}",
+                threshold
+            );
+        }
+        // the boundaries of the number of flags
+        let circuit = build_quorum_circuit::<F>(&[true; 8], 8)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_quorum_circuit::<F>(&[false; 8], 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_quorum_circuit::<F>(&[false; 8], 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_quorum_circuit::<F>(&[true], 1)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_quorum_circuit::<F>(&flags, 3)?;
+        // the second flag
+        *circuit.witness_mut(3) = F::from(2u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = build_quorum_circuit::<F>(&flags, 3)?;
+        // the first flag, making up for the second one
+        *circuit.witness_mut(2) = F::zero();
+        *circuit.witness_mut(3) = F::from(2u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let one = circuit.one();
+        assert!(circuit.enforce_quorum(&[], 0).is_err());
+        assert!(circuit.enforce_quorum(&[one, one], 3).is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_quorum(&[one, circuit.num_vars()], 1)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_quorum_circuit::<F>(&flags, 3)?;
+        let mut circuit_2 = build_quorum_circuit::<F>(&[true; 5], 3)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The flags are the variables from index 2.
+    fn build_quorum_circuit<F: PrimeField>(
+        flags: &[bool],
+        threshold: usize,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let flag_vars = flags
+            .iter()
+            .map(|&flag| circuit.create_variable(F::from(flag as u32)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.enforce_quorum(&flag_vars, threshold)?;
+        Ok(circuit)
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Base conversion gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain the digits `digits_b1` in base `b1` and the digits
+    /// `digits_b2` in base `b2`, both most significant digit first, to
+    /// represent the same number. Every digit is constrained to be smaller
+    /// than its base.
+    /// Return error if variables are invalid, if a base is smaller than 2, or
+    /// if a number has too many digits to be represented in the field.
+    pub fn enforce_base_equal(
+        &mut self,
+        digits_b1: &[Variable],
+        b1: u64,
+        digits_b2: &[Variable],
+        b2: u64,
+    ) -> Result<(), PlonkError> {
+        let value_b1 = self.recompose_digits(digits_b1, b1)?;
+        let value_b2 = self.recompose_digits(digits_b2, b2)?;
+        self.equal_gate(value_b1, value_b2)
+    }
+
+    // Range-check the digits in base `base`, most significant digit first, and
+    // return the number they represent.
+    fn recompose_digits(&mut self, digits: &[Variable], base: u64) -> Result<Variable, PlonkError> {
+        if base < 2 {
+            return Err(
+                CircuitError::ParameterError(format!("base {} is smaller than 2", base)).into(),
+            );
+        }
+        // base - 1 < 2^digit_bit_len, and the number is below 2^(F::size_in_bits() - 1)
+        let digit_bit_len = (64 - (base - 1).leading_zeros()) as usize;
+        if digits.len() * digit_bit_len >= F::size_in_bits() {
+            return Err(CircuitError::ParameterError(format!(
+                "{} digits in base {} may overflow the field",
+                digits.len(),
+                base
+            ))
+            .into());
+        }
+        self.check_vars_bound(digits)?;
+
+        let max_digit = self.create_constant_variable(F::from(base - 1))?;
+        for &digit in digits.iter() {
+            self.range_gate(digit, digit_bit_len)?;
+            if !base.is_power_of_two() {
+                let gap = self.sub(max_digit, digit)?;
+                self.range_gate(gap, digit_bit_len)?;
+            }
+        }
+
+        // Horner's rule
+        let zero = self.zero();
+        let mut acc = zero;
+        for &digit in digits.iter() {
+            acc = self.lc(
+                &[acc, digit, zero, zero],
+                &[F::from(base), F::one(), F::zero(), F::zero()],
+            )?;
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{vec, vec::Vec};
+
+    // The `len` digits of `value` in base `base`, most significant digit first.
+    fn to_digits(mut value: u64, base: u64, len: usize) -> Vec<u64> {
+        let mut digits = vec![0; len];
+        for digit in digits.iter_mut().rev() {
+            *digit = value % base;
+            value /= base;
+        }
+        digits
+    }
+
+    // A number of digits in base `base` covering 50-bit values.
+    fn num_digits(base: u64) -> usize {
+        64 / (64 - (base - 1).leading_zeros()) as usize
+    }
+
+    #[test]
+    fn test_enforce_base_equal() -> Result<(), PlonkError> {
+        test_enforce_base_equal_helper::<FqEd254>()?;
+        test_enforce_base_equal_helper::<FqEd377>()?;
+        test_enforce_base_equal_helper::<FqEd381>()?;
+        test_enforce_base_equal_helper::<Fq377>()
+    }
+
+    fn test_enforce_base_equal_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // conversions between several base pairs
+        for &(b1, b2) in [(2, 10), (10, 16), (3, 7), (16, 256), (60, 2), (7, 7)].iter() {
+            for &value in [0u64, 1, 42, 59, 1_000_003, 123_456_789].iter() {
+                let digits_b1 = to_digits(value, b1, num_digits(b1));
+                let digits_b2 = to_digits(value, b2, num_digits(b2));
+                let circuit = build_base_equal_circuit::<F>(&digits_b1, b1, &digits_b2, b2)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+                let digits_b2 = to_digits(value + 1, b2, num_digits(b2));
+                let circuit = build_base_equal_circuit::<F>(&digits_b1, b1, &digits_b2, b2)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+        // different numbers of digits, and no digits
+        let circuit = build_base_equal_circuit::<F>(&[1, 0, 1, 0], 2, &[1, 0], 10)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_base_equal_circuit::<F>(&[], 2, &[0, 0], 10)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // digits out of range with the same recomposition
+        // 10 * 12 + 3 = 123
+        let circuit = build_base_equal_circuit::<F>(&[12, 3], 10, &[1, 2, 3], 10)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // 2 * 2 + 1 = 5
+        let circuit = build_base_equal_circuit::<F>(&[2, 1], 2, &[5], 10)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // 3 * 3 - 4 = 5
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let digits_b1 = [
+            circuit.create_variable(F::from(3u32))?,
+            circuit.create_variable(-F::from(4u32))?,
+        ];
+        let digits_b2 = [circuit.create_variable(F::from(5u32))?];
+        circuit.enforce_base_equal(&digits_b1, 3, &digits_b2, 10)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let digits_b1 = [
+            circuit.create_variable(F::from(4u32))?,
+            circuit.create_variable(F::from(2u32))?,
+        ];
+        let digits_b2 = [
+            circuit.create_variable(F::from(2u32))?,
+            circuit.create_variable(F::from(10u32))?,
+        ];
+        circuit.enforce_base_equal(&digits_b1, 10, &digits_b2, 16)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(digits_b1[1]) = F::from(3u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit.enforce_base_equal(&[zero], 1, &[zero], 10).is_err());
+        assert!(circuit.enforce_base_equal(&[zero], 10, &[zero], 0).is_err());
+        let too_many_digits = vec![zero; F::size_in_bits()];
+        assert!(circuit
+            .enforce_base_equal(&too_many_digits, 2, &[zero], 10)
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_base_equal(&[zero], 10, &[circuit.num_vars()], 10)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 =
+            build_base_equal_circuit::<F>(&to_digits(42, 3, 8), 3, &to_digits(42, 10, 4), 10)?;
+        let mut circuit_2 =
+            build_base_equal_circuit::<F>(&to_digits(999, 3, 8), 3, &to_digits(999, 10, 4), 10)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_base_equal_circuit<F: PrimeField>(
+        digits_b1: &[u64],
+        b1: u64,
+        digits_b2: &[u64],
+        b2: u64,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let mut create_vars = |vals: &[u64]| {
+            vals.iter()
+                .map(|&val| circuit.create_variable(F::from(val)))
+                .collect::<Result<Vec<_>, PlonkError>>()
+        };
+        let digit_vars_b1 = create_vars(digits_b1)?;
+        let digit_vars_b2 = create_vars(digits_b2)?;
+        circuit.enforce_base_equal(&digit_vars_b1, b1, &digit_vars_b2, b2)?;
+        Ok(circuit)
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Reputation decay gadget over fixed-point values.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+use num_bigint::BigUint;
+
+/// The bit length of the integer part of reputations.
+const REPUTATION_INT_BIT_LEN: usize = 64;
+
+/// The bit length of elapsed times.
+const ELAPSED_BIT_LEN: usize = 32;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `new_rep` to be the reputation `old_rep * decay^elapsed`.
+    ///
+    /// The reputations and the decay factor are fixed-point values with
+    /// `frac_bits` fractional bits, i.e. a variable holds its value times
+    /// `2^frac_bits`. The power is computed by square-and-multiply over the
+    /// bits of `elapsed`, and every fixed-point product is rounded down, so
+    /// that `new_rep` is unchanged when `elapsed` is zero and reaches zero as
+    /// `elapsed` grows.
+    ///
+    /// `decay` is constrained to be in [0, 1), `elapsed` to be a 32-bit
+    /// integer, and the reputations to be non-negative with an integer part of
+    /// at most 64 bits.
+    /// Return error if variables are invalid, or if `frac_bits` is zero or too
+    /// large for the field.
+    pub fn enforce_reputation_decay(
+        &mut self,
+        old_rep: Variable,
+        decay: Variable,
+        elapsed: Variable,
+        new_rep: Variable,
+        frac_bits: usize,
+    ) -> Result<(), PlonkError> {
+        // old_rep * decay^elapsed * 2^frac_bits < 2^(64 + 2 * frac_bits + 1)
+        if frac_bits == 0 || REPUTATION_INT_BIT_LEN + 2 * frac_bits + 2 >= F::size_in_bits() {
+            return Err(CircuitError::ParameterError(format!(
+                "the number of fractional bits {} is zero or too large for the field",
+                frac_bits
+            ))
+            .into());
+        }
+        self.check_var_bound(old_rep)?;
+        self.check_var_bound(decay)?;
+        self.check_var_bound(elapsed)?;
+        self.check_var_bound(new_rep)?;
+
+        self.range_gate(old_rep, REPUTATION_INT_BIT_LEN + frac_bits)?;
+        self.range_gate(decay, frac_bits)?;
+        let elapsed_bits = self.range_gate_internal(elapsed, ELAPSED_BIT_LEN)?;
+
+        let power = self.fixed_point_pow(decay, &elapsed_bits, frac_bits)?;
+        let product = self.fixed_point_mul(
+            old_rep,
+            power,
+            frac_bits,
+            REPUTATION_INT_BIT_LEN + frac_bits,
+        )?;
+        self.equal_gate(product, new_rep)
+    }
+
+    // Obtain a variable representing the fixed-point power `base^exponent`,
+    // rounded down at every product, where the exponent is given by its
+    // little-endian bits and the base is assumed to be in [0, 1].
+    fn fixed_point_pow(
+        &mut self,
+        base: Variable,
+        exponent_bits: &[Variable],
+        frac_bits: usize,
+    ) -> Result<Variable, PlonkError> {
+        let one = self.create_constant_variable(F::from(2u8).pow(&[frac_bits as u64]))?;
+        let mut power = one;
+        let mut square = base;
+        for (i, &bit) in exponent_bits.iter().enumerate() {
+            let factor = self.conditional_select(bit, one, square)?;
+            power = self.fixed_point_mul(power, factor, frac_bits, frac_bits + 1)?;
+            if i + 1 < exponent_bits.len() {
+                square = self.fixed_point_mul(square, square, frac_bits, frac_bits + 1)?;
+            }
+        }
+        Ok(power)
+    }
+
+    // Obtain a variable representing the fixed-point product `a * b`, rounded
+    // down, i.e. `a * b = product * 2^frac_bits + remainder` with `0 <=
+    // remainder < 2^frac_bits`, and constrain the product to be below
+    // 2^bit_len.
+    fn fixed_point_mul(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        frac_bits: usize,
+        bit_len: usize,
+    ) -> Result<Variable, PlonkError> {
+        let full = self.mul(a, b)?;
+        let full_val: BigUint = self.witness(full)?.into_repr().into();
+        let product = self.create_variable(F::from(&full_val >> frac_bits))?;
+        let remainder_val = full_val % (BigUint::from(1u8) << frac_bits);
+        let remainder = self.create_variable(F::from(remainder_val))?;
+        self.range_gate(product, bit_len)?;
+        self.range_gate(remainder, frac_bits)?;
+        let zero = self.zero();
+        self.lc_gate(
+            &[product, remainder, zero, zero, full],
+            &[
+                F::from(2u8).pow(&[frac_bits as u64]),
+                F::one(),
+                F::zero(),
+                F::zero(),
+            ],
+        )?;
+        Ok(product)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{circuit::customized::test::test_variable_independence_for_circuit, goldilocks};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    const FRAC_BITS: usize = 32;
+    const ONE: u128 = 1 << FRAC_BITS;
+
+    // Native reputation decay over fixed-point values, rounded down as in the
+    // circuit.
+    fn reputation_decay(old_rep: u128, decay: u128, elapsed: u32) -> u128 {
+        let (mut power, mut square) = (ONE, decay);
+        for i in 0..ELAPSED_BIT_LEN {
+            if (elapsed >> i) & 1 == 1 {
+                power = (power * square) >> FRAC_BITS;
+            }
+            square = (square * square) >> FRAC_BITS;
+        }
+        (old_rep * power) >> FRAC_BITS
+    }
+
+    #[test]
+    fn test_enforce_reputation_decay() -> Result<(), PlonkError> {
+        test_enforce_reputation_decay_helper::<FqEd254>()?;
+        test_enforce_reputation_decay_helper::<FqEd377>()?;
+        test_enforce_reputation_decay_helper::<FqEd381>()?;
+        test_enforce_reputation_decay_helper::<Fq377>()
+    }
+
+    fn test_enforce_reputation_decay_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // compare to the native computation, and to the real decay within a
+        // relative tolerance
+        for &old_rep in [0, 123_456 * ONE + 789, (1 << 64) * ONE - 1].iter() {
+            for &decay in [0, ONE / 2, ONE * 99 / 100, ONE - 1].iter() {
+                for &elapsed in [0, 1, 7, 365, 100_000, u32::MAX].iter() {
+                    let new_rep = reputation_decay(old_rep, decay, elapsed);
+                    let real = old_rep as f64 * (decay as f64 / ONE as f64).powf(elapsed as f64);
+                    assert!((new_rep as f64 - real).abs() <= old_rep as f64 * 1e-5 + 1.0);
+                    let circuit = build_reputation_circuit::<F>(old_rep, decay, elapsed, new_rep)?;
+                    assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+                    let circuit =
+                        build_reputation_circuit::<F>(old_rep, decay, elapsed, new_rep + 1)?;
+                    assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                    if new_rep > 0 {
+                        let circuit =
+                            build_reputation_circuit::<F>(old_rep, decay, elapsed, new_rep - 1)?;
+                        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                    }
+                }
+            }
+        }
+        // unchanged when no time elapsed
+        assert_eq!(
+            reputation_decay(123_456 * ONE + 789, ONE / 2, 0),
+            123_456 * ONE + 789
+        );
+        // down to zero after a long time
+        assert_eq!(reputation_decay((1 << 64) * ONE - 1, ONE / 2, 1000), 0);
+        assert_eq!(reputation_decay(1000 * ONE, ONE * 99 / 100, 100_000), 0);
+
+        // a decay factor of one, or a reputation out of range
+        let circuit = build_reputation_circuit::<F>(1000 * ONE, ONE, 7, 1000 * ONE)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_reputation_circuit::<F>((1 << 64) * ONE, ONE / 2, 0, (1 << 64) * ONE)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let new_rep = reputation_decay(1000 * ONE, ONE / 2, 3);
+        let mut circuit = build_reputation_circuit::<F>(1000 * ONE, ONE / 2, 3, new_rep)?;
+        // elapsed
+        *circuit.witness_mut(4) = F::from(4u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_reputation_decay(zero, zero, zero, zero, 0)
+            .is_err());
+        assert!(circuit
+            .enforce_reputation_decay(zero, zero, zero, zero, F::size_in_bits())
+            .is_err());
+        let mut small_circuit: PlonkCircuit<goldilocks::Fq> = PlonkCircuit::new_turbo_plonk();
+        let zero = small_circuit.zero();
+        assert!(small_circuit
+            .enforce_reputation_decay(zero, zero, zero, zero, 1)
+            .is_err());
+        // Check variable out of bound error.
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_reputation_decay(zero, zero, zero, circuit.num_vars(), FRAC_BITS)
+            .is_err());
+        assert!(circuit
+            .enforce_reputation_decay(circuit.num_vars(), zero, zero, zero, FRAC_BITS)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_reputation_circuit::<F>(
+            1000 * ONE,
+            ONE * 99 / 100,
+            365,
+            reputation_decay(1000 * ONE, ONE * 99 / 100, 365),
+        )?;
+        let mut circuit_2 = build_reputation_circuit::<F>(7, 0, 0, 7)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The old reputation is the variable of index 2, followed by the decay
+    // factor and the elapsed time.
+    fn build_reputation_circuit<F: PrimeField>(
+        old_rep: u128,
+        decay: u128,
+        elapsed: u32,
+        new_rep: u128,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let old_rep = circuit.create_variable(F::from(old_rep))?;
+        let decay = circuit.create_variable(F::from(decay))?;
+        let elapsed = circuit.create_variable(F::from(elapsed))?;
+        let new_rep = circuit.create_variable(F::from(new_rep))?;
+        circuit.enforce_reputation_decay(old_rep, decay, elapsed, new_rep, FRAC_BITS)?;
+        Ok(circuit)
+    }
+}
@@ -144,6 +144,14 @@ pub trait RescueGadget<F: RescueParameter> {
         num_output: usize,
     ) -> Result<Vec<Variable>, PlonkError>;
 
+    /// Sponge-based hash of a vector of any length into a single output,
+    /// matching the native [`jf_rescue::rescue_sponge`]: the input is padded
+    /// with a One then with zeros up to a multiple of the rate (i.e. 3)
+    /// before being absorbed.
+    /// * `data_vars` - sponge input variables, possibly empty
+    /// * `returns` - a variable that refers to the hash output
+    fn rescue_sponge_hash(&mut self, data_vars: &[Variable]) -> Result<Variable, PlonkError>;
+
     /// Full-State-Keyed-Sponge with a single output
     /// * `key` - key variable
     /// * `input` - input variables,
@@ -280,6 +288,16 @@ where
         self.rescue_sponge_no_padding(&data_vars, num_output)
     }
 
+    fn rescue_sponge_hash(&mut self, data_vars: &[Variable]) -> Result<Variable, PlonkError> {
+        let rate = STATE_SIZE - 1;
+        let data_len = compute_len_to_next_multiple(data_vars.len() + 1, rate);
+        let mut padded_vars = data_vars.to_vec();
+        padded_vars.push(self.one());
+        padded_vars.resize(data_len, self.zero());
+
+        Ok(self.rescue_sponge_no_padding(&padded_vars, 1)?[0])
+    }
+
     fn rescue_full_state_keyed_sponge_no_padding(
         &mut self,
         key: Variable,
@@ -376,6 +394,39 @@ where
     }
 }
 
+impl<F> PlonkCircuit<F>
+where
+    F: RescueParameter,
+{
+    /// The number of gates added by `rescue_permutation`.
+    pub fn gate_cost_of_rescue_permutation() -> usize {
+        // gates of `pow_alpha_inv` and of `non_linear_transform` per state
+        // element
+        let (pow_alpha_inv_cost, non_linear_cost) = if F::A == 5 { (1, 1) } else { (3, 4) };
+        // initial key addition, then `ROUNDS` pairs of rounds, each made of an
+        // inversion followed by an affine transform and a non linear transform
+        STATE_SIZE + ROUNDS * STATE_SIZE * (pow_alpha_inv_cost + 1 + non_linear_cost)
+    }
+
+    /// The number of gates added by `rescue_sponge_no_padding` on
+    /// `data_len` inputs (a positive multiple of the rate) with `num_output`
+    /// outputs.
+    pub fn gate_cost_of_rescue_sponge_no_padding(data_len: usize, num_output: usize) -> usize {
+        let rate = STATE_SIZE - 1;
+        let num_blocks = data_len / rate;
+        let num_squeezes = compute_len_to_next_multiple(num_output.max(1), rate) / rate - 1;
+        (num_blocks + num_squeezes) * Self::gate_cost_of_rescue_permutation()
+            + num_blocks.saturating_sub(1) * STATE_SIZE
+    }
+
+    /// The number of gates added by `rescue_sponge_with_padding` on
+    /// `data_len` inputs with `num_output` outputs.
+    pub fn gate_cost_of_rescue_sponge_with_padding(data_len: usize, num_output: usize) -> usize {
+        let padded_len = compute_len_to_next_multiple(data_len + 1, STATE_SIZE - 1);
+        Self::gate_cost_of_rescue_sponge_no_padding(padded_len, num_output)
+    }
+}
+
 pub(crate) trait RescueHelperGadget<F: PrimeField>: Circuit<F> {
     fn check_var_bound_rescue_state(&self, rescue_state: &RescueStateVar)
         -> Result<(), PlonkError>;
@@ -657,6 +708,7 @@ mod tests {
 
     use super::{RescueGadget, RescueHelperGadget, RescueStateVar};
     use crate::circuit::{Circuit, PlonkCircuit, Variable};
+    use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
     use ark_ed_on_bls12_381::Fq as FqEd381;
     use ark_ed_on_bn254::Fq as FqEd254;
@@ -852,6 +904,55 @@ mod tests {
         check_circuit_satisfiability(&mut circuit, state_out.elems(), out_var);
     }
 
+    #[test]
+    fn test_gate_cost_of_rescue() {
+        test_gate_cost_of_rescue_helper::<FqEd254>();
+        test_gate_cost_of_rescue_helper::<FqEd377>();
+        test_gate_cost_of_rescue_helper::<FqEd381>();
+    }
+
+    fn test_gate_cost_of_rescue_helper<F: RescueParameter>() {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let state_in =
+            RescueVector::from(&[F::from(1u32), F::from(2u32), F::from(3u32), F::from(4u32)]);
+        let state_in_var = circuit.create_rescue_state_variable(&state_in).unwrap();
+        let num_gates = circuit.num_gates();
+        circuit.rescue_permutation(state_in_var).unwrap();
+        assert_eq!(
+            circuit.num_gates() - num_gates,
+            PlonkCircuit::<F>::gate_cost_of_rescue_permutation()
+        );
+
+        let data_vars = (0..10)
+            .map(|i| circuit.create_variable(F::from(i as u32)).unwrap())
+            .collect_vec();
+        for num_output in 1..8 {
+            for data_len in [RATE, 2 * RATE, 3 * RATE] {
+                let num_gates = circuit.num_gates();
+                circuit
+                    .rescue_sponge_no_padding(&data_vars[..data_len], num_output)
+                    .unwrap();
+                assert_eq!(
+                    circuit.num_gates() - num_gates,
+                    PlonkCircuit::<F>::gate_cost_of_rescue_sponge_no_padding(data_len, num_output)
+                );
+            }
+            for data_len in 1..data_vars.len() {
+                let num_gates = circuit.num_gates();
+                circuit
+                    .rescue_sponge_with_padding(&data_vars[..data_len], num_output)
+                    .unwrap();
+                assert_eq!(
+                    circuit.num_gates() - num_gates,
+                    PlonkCircuit::<F>::gate_cost_of_rescue_sponge_with_padding(
+                        data_len, num_output
+                    )
+                );
+            }
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+    }
+
     #[test]
     fn test_add_state() {
         test_add_state_helper::<FqEd254>();
@@ -1118,6 +1219,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rescue_sponge_hash() {
+        test_rescue_sponge_hash_helper::<FqEd254>();
+        test_rescue_sponge_hash_helper::<FqEd377>();
+        test_rescue_sponge_hash_helper::<FqEd381>();
+        test_rescue_sponge_hash_helper::<Fq377>();
+    }
+
+    fn test_rescue_sponge_hash_helper<F: RescueParameter>() {
+        let rate = RATE;
+        let mut prng = ark_std::test_rng();
+        // lengths around the rate, where the padding takes a block of its own
+        for &input_len in [0, 1, rate - 1, rate, rate + 1, 2 * rate].iter() {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let input_vec: Vec<F> = (0..input_len).map(|_| F::rand(&mut prng)).collect();
+            let input_vars: Vec<Variable> = input_vec
+                .iter()
+                .map(|&x| circuit.create_variable(x).unwrap())
+                .collect();
+
+            let hash_var = circuit.rescue_sponge_hash(&input_vars).unwrap();
+            let expected_hash = jf_rescue::rescue_sponge(&input_vec);
+            assert_eq!(expected_hash, circuit.witness(hash_var).unwrap());
+            assert_eq!(
+                expected_hash,
+                Permutation::default().sponge_with_padding(&input_vec, 1)[0]
+            );
+            assert_eq!(
+                circuit.num_gates(),
+                PlonkCircuit::<F>::gate_cost_of_rescue_sponge_with_padding(input_len, 1)
+            );
+
+            // good path
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            // bad path: incorrect output
+            *circuit.witness_mut(hash_var) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // padding is not ambiguous: a trailing zero changes the hash
+        let input_vec = vec![F::one(); rate];
+        let mut padded_vec = input_vec.clone();
+        padded_vec.push(F::zero());
+        assert_ne!(
+            jf_rescue::rescue_sponge(&input_vec),
+            jf_rescue::rescue_sponge(&padded_vec)
+        );
+    }
+
     #[test]
     fn test_fsks() {
         test_fsks_helper::<FqEd254>();
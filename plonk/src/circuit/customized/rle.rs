@@ -0,0 +1,228 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Run-length encoding gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{format, vec, vec::Vec};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain that the (`value_vars[j]`, `count_vars[j]`) pairs are the
+    /// run-length encoding of `data_vars`, i.e. expanding each value
+    /// `count_vars[j]` times, in order, gives back `data_vars`.
+    /// Every count is constrained to be nonzero and at most
+    /// `data_vars.len()`.
+    /// Return error if variables are invalid, or if the number of runs is
+    /// zero or larger than the length of the data.
+    ///
+    /// Cost: O(`data_vars.len()` * `value_vars.len()`) constraints.
+    pub fn enforce_rle(
+        &mut self,
+        data_vars: &[Variable],
+        value_vars: &[Variable],
+        count_vars: &[Variable],
+    ) -> Result<(), PlonkError> {
+        let n = data_vars.len();
+        let k = value_vars.len();
+        if count_vars.len() != k {
+            return Err(CircuitError::ParameterError(format!(
+                "the number of values {} != the number of counts {}",
+                k,
+                count_vars.len()
+            ))
+            .into());
+        }
+        if k == 0 || k > n {
+            return Err(CircuitError::ParameterError(format!(
+                "the number of runs {} should be in [1, {}]",
+                k, n
+            ))
+            .into());
+        }
+        self.check_vars_bound(data_vars)?;
+        self.check_vars_bound(value_vars)?;
+        self.check_vars_bound(count_vars)?;
+
+        // Counts are nonzero and fit in the bit length of `n`.
+        let count_bit_len = ark_std::log2(n + 1) as usize;
+        for &count in count_vars.iter() {
+            self.non_zero_gate(count)?;
+            self.range_gate(count, count_bit_len)?;
+        }
+
+        // Assign each data position to a run according to the claimed counts.
+        // Counts that do not sum to `n` yield an unsatisfiable circuit.
+        let max_count = F::from(n as u64).into_repr();
+        let counts = count_vars
+            .iter()
+            .map(|&var| {
+                let repr = self.witness(var)?.into_repr();
+                Ok(if repr > max_count {
+                    n
+                } else {
+                    repr.as_ref()[0] as usize
+                })
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let mut run_of_position = vec![0; n];
+        let mut run = 0;
+        let mut remaining = counts[0];
+        for run_of_pos in run_of_position.iter_mut() {
+            while remaining == 0 && run < k - 1 {
+                run += 1;
+                remaining = counts[run];
+            }
+            *run_of_pos = run;
+            remaining = remaining.saturating_sub(1);
+        }
+
+        // `selectors[i][j]` is one iff the i-th data position belongs to the j-th
+        // run.
+        let selectors = run_of_position
+            .iter()
+            .map(|&run| {
+                (0..k)
+                    .map(|j| self.create_bool_variable(j == run))
+                    .collect::<Result<Vec<_>, PlonkError>>()
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+
+        for (i, row) in selectors.iter().enumerate() {
+            // Each position belongs to exactly one run.
+            let row_sum = self.sum(row)?;
+            self.constant_gate(row_sum, F::one())?;
+            // The data value equals the value of its run.
+            let terms = row
+                .iter()
+                .zip(value_vars.iter())
+                .map(|(&sel, &value)| self.mul(sel, value))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let selected = self.sum(&terms)?;
+            self.equal_gate(data_vars[i], selected)?;
+        }
+
+        // Each run covers exactly `count` positions.
+        for (j, &count) in count_vars.iter().enumerate() {
+            let column: Vec<Variable> = selectors.iter().map(|row| row[j]).collect();
+            let column_sum = self.sum(&column)?;
+            self.equal_gate(column_sum, count)?;
+        }
+
+        // Runs are contiguous and in order: the data starts with the first run,
+        // ends with the last run, and position i belongs either to the run of
+        // position i-1 or to the next one.
+        self.enforce_true(selectors[0][0])?;
+        self.enforce_true(selectors[n - 1][k - 1])?;
+        for i in 1..n {
+            for j in 0..k {
+                let allowed = if j == 0 {
+                    selectors[i - 1][0]
+                } else {
+                    self.add(selectors[i - 1][j], selectors[i - 1][j - 1])?
+                };
+                // selectors[i][j] * allowed = selectors[i][j]
+                self.mul_gate(selectors[i][j], allowed, selectors[i][j])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_enforce_rle() -> Result<(), PlonkError> {
+        test_enforce_rle_helper::<FqEd254>()?;
+        test_enforce_rle_helper::<FqEd377>()?;
+        test_enforce_rle_helper::<FqEd381>()?;
+        test_enforce_rle_helper::<Fq377>()
+    }
+
+    fn test_enforce_rle_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let data = [3u32, 3, 5, 7, 7, 7];
+        let values = [3u32, 5, 7];
+        let counts = [2u32, 1, 3];
+
+        // correct encoding
+        let (mut circuit, _, _, count_vars) = build_rle_circuit::<F>(&data, &values, &counts)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // incorrect count
+        *circuit.witness_mut(count_vars[0]) = F::from(1u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, ..) = build_rle_circuit::<F>(&data, &values, &[1, 2, 3])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, ..) = build_rle_circuit::<F>(&data, &values, &[2, 1, 2])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // zero count
+        let (circuit, ..) = build_rle_circuit::<F>(&data, &[3, 4, 5, 7], &[2, 0, 1, 3])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // incorrect value
+        let (circuit, ..) = build_rle_circuit::<F>(&data, &[3, 5, 8], &counts)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // incorrect data
+        let (mut circuit, data_vars, ..) = build_rle_circuit::<F>(&data, &values, &counts)?;
+        *circuit.witness_mut(data_vars[2]) = F::from(3u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let vars = (0..3)
+            .map(|i| circuit.create_variable(F::from(i as u32)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        assert!(circuit.enforce_rle(&vars, &vars[..2], &vars[..1]).is_err());
+        assert!(circuit.enforce_rle(&vars, &[], &[]).is_err());
+        assert!(circuit
+            .enforce_rle(&vars[..1], &vars[..2], &vars[..2])
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_rle(&vars, &[circuit.num_vars()], &[vars[0]])
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let (mut circuit_1, ..) = build_rle_circuit::<F>(&data, &values, &counts)?;
+        let (mut circuit_2, ..) =
+            build_rle_circuit::<F>(&[1u32, 2, 2, 2, 2, 4], &[1u32, 2, 4], &[1u32, 4, 1])?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn build_rle_circuit<F: PrimeField>(
+        data: &[u32],
+        values: &[u32],
+        counts: &[u32],
+    ) -> Result<(PlonkCircuit<F>, Vec<Variable>, Vec<Variable>, Vec<Variable>), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let mut create_vars = |vals: &[u32]| {
+            vals.iter()
+                .map(|&val| circuit.create_variable(F::from(val)))
+                .collect::<Result<Vec<_>, PlonkError>>()
+        };
+        let data_vars = create_vars(data)?;
+        let value_vars = create_vars(values)?;
+        let count_vars = create_vars(counts)?;
+        circuit.enforce_rle(&data_vars, &value_vars, &count_vars)?;
+        Ok((circuit, data_vars, value_vars, count_vars))
+    }
+}
@@ -0,0 +1,385 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! SHA-256 gadget.
+//!
+//! The words of the hash are represented by their bits, so that rotations and
+//! shifts are free and each bitwise operation costs a gate per bit, along with
+//! their packed values for the modular additions. A modular addition splits
+//! the sum into the bits of a new word and a carry, which is range checked,
+//! with lookups on UltraPlonk circuits when the carry length allows it.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    constants::GATE_WIDTH,
+    errors::PlonkError,
+};
+use ark_ff::PrimeField;
+use ark_std::{convert::TryInto, vec, vec::Vec};
+
+/// The number of bits of a SHA-256 word.
+const WORD_BITS: usize = 32;
+
+/// The number of bits of a SHA-256 block.
+const BLOCK_BITS: usize = 512;
+
+/// The initial hash value of SHA-256.
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants of SHA-256.
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The little-endian bits of a SHA-256 word.
+type WordBits = [Variable; WORD_BITS];
+
+/// A SHA-256 word of the state or of the message schedule, with its packed
+/// value for the modular additions.
+#[derive(Debug, Clone, Copy)]
+struct Sha256Word {
+    bits_le: WordBits,
+    packed: Variable,
+}
+
+/// Rotate the bits of a word to the right by `n`.
+fn rotr(x: &WordBits, n: usize) -> WordBits {
+    let mut bits = *x;
+    bits.rotate_left(n);
+    bits
+}
+
+/// Shift the bits of a word to the right by `n`, filling with `zero`.
+fn shr(x: &WordBits, n: usize, zero: Variable) -> WordBits {
+    let mut bits = [zero; WORD_BITS];
+    bits[..WORD_BITS - n].copy_from_slice(&x[n..]);
+    bits
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// SHA-256 hash of the message with the bits `input_bits`, in the order of
+    /// the SHA-256 bit string, i.e. the most significant bit of each byte
+    /// first. The message is padded in the circuit, its length being fixed by
+    /// the circuit. Return the bits of the digest in the same order, which
+    /// match the output of the reference implementation on the message bytes.
+    /// The input variables should already be constrained to be boolean.
+    /// Return error if some input variable is invalid or not boolean.
+    ///
+    /// Cost: about 33000 constraints per 512-bit block of the padded message.
+    pub fn sha256(&mut self, input_bits: &[Variable]) -> Result<[Variable; 256], PlonkError> {
+        for &bit in input_bits.iter() {
+            self.check_var_bound(bit)?;
+            self.check_bool(bit)?;
+        }
+
+        // padding: a one bit, zero bits up to 64 bits before the end of a
+        // block, and the bit length of the message in 64 bits
+        let (zero, one) = (self.zero(), self.one());
+        let len = input_bits.len() as u64;
+        let mut padded = input_bits.to_vec();
+        padded.push(one);
+        while padded.len() % BLOCK_BITS != BLOCK_BITS - 64 {
+            padded.push(zero);
+        }
+        padded.extend((0..64).rev().map(|i| match (len >> i) & 1 {
+            1 => one,
+            _ => zero,
+        }));
+
+        let mut state = SHA256_IV
+            .iter()
+            .map(|&h| self.sha256_constant_word(h))
+            .collect::<Result<Vec<_>, _>>()?;
+        for block in padded.chunks(BLOCK_BITS) {
+            state = self.sha256_compress(&state, block)?;
+        }
+
+        let digest: Vec<Variable> = state
+            .iter()
+            .flat_map(|word| word.bits_le.iter().rev().copied())
+            .collect();
+        Ok(digest.try_into().unwrap()) // safe unwrap: 8 words of 32 bits
+    }
+
+    /// The SHA-256 compression function of the 8 words of `state` with the
+    /// 512 bits of `block`.
+    fn sha256_compress(
+        &mut self,
+        state: &[Sha256Word],
+        block: &[Variable],
+    ) -> Result<Vec<Sha256Word>, PlonkError> {
+        let zero = self.zero();
+        // message schedule
+        let mut w = Vec::with_capacity(SHA256_K.len());
+        for chunk in block.chunks(WORD_BITS) {
+            let mut bits_le: WordBits = [zero; WORD_BITS];
+            for (bit, &var) in bits_le.iter_mut().zip(chunk.iter().rev()) {
+                *bit = var;
+            }
+            let packed = self.pack_le(&bits_le)?;
+            w.push(Sha256Word { bits_le, packed });
+        }
+        for t in 16..SHA256_K.len() {
+            let x = &w[t - 15].bits_le;
+            let s0 = self.sha256_xor3(&rotr(x, 7), &rotr(x, 18), &shr(x, 3, zero))?;
+            let x = &w[t - 2].bits_le;
+            let s1 = self.sha256_xor3(&rotr(x, 17), &rotr(x, 19), &shr(x, 10, zero))?;
+            let s0 = self.pack_le(&s0)?;
+            let s1 = self.pack_le(&s1)?;
+            let word = self.sha256_add(&[s1, w[t - 7].packed, s0, w[t - 16].packed], 0)?;
+            w.push(word);
+        }
+
+        // rounds
+        let mut v = state.to_vec();
+        for (t, &k) in SHA256_K.iter().enumerate() {
+            let (a, b, c, d) = (v[0], v[1], v[2], v[3]);
+            let (e, f, g, h) = (v[4], v[5], v[6], v[7]);
+            let sigma1 = self.sha256_xor3(
+                &rotr(&e.bits_le, 6),
+                &rotr(&e.bits_le, 11),
+                &rotr(&e.bits_le, 25),
+            )?;
+            let sigma1 = self.pack_le(&sigma1)?;
+            let ch = self.sha256_ch(&e.bits_le, &f.bits_le, &g.bits_le)?;
+            let ch = self.pack_le(&ch)?;
+            let sigma0 = self.sha256_xor3(
+                &rotr(&a.bits_le, 2),
+                &rotr(&a.bits_le, 13),
+                &rotr(&a.bits_le, 22),
+            )?;
+            let sigma0 = self.pack_le(&sigma0)?;
+            let maj = self.sha256_maj(&a.bits_le, &b.bits_le, &c.bits_le)?;
+            let maj = self.pack_le(&maj)?;
+
+            // e' = d + T1 and a' = T1 + T2, with T1 = h + sigma1 + ch + k + w
+            // and T2 = sigma0 + maj
+            let new_e = self.sha256_add(&[d.packed, h.packed, sigma1, ch, w[t].packed], k)?;
+            let new_a = self.sha256_add(&[h.packed, sigma1, ch, w[t].packed, sigma0, maj], k)?;
+            v = vec![new_a, a, b, c, new_e, e, f, g];
+        }
+
+        state
+            .iter()
+            .zip(v.iter())
+            .map(|(h, x)| self.sha256_add(&[h.packed, x.packed], 0))
+            .collect()
+    }
+
+    /// The word of the constant `val`, whose bits are the zero and one
+    /// variables.
+    fn sha256_constant_word(&mut self, val: u32) -> Result<Sha256Word, PlonkError> {
+        let (zero, one) = (self.zero(), self.one());
+        let mut bits_le = [zero; WORD_BITS];
+        for (i, bit) in bits_le.iter_mut().enumerate() {
+            if (val >> i) & 1 == 1 {
+                *bit = one;
+            }
+        }
+        let packed = self.create_constant_variable(F::from(val))?;
+        Ok(Sha256Word { bits_le, packed })
+    }
+
+    /// The word of the sum of the packed words `words` and of `constant`
+    /// modulo 2^32: the sum is split into the bits of the word and a carry
+    /// smaller than the number of summands.
+    fn sha256_add(&mut self, words: &[Variable], constant: u32) -> Result<Sha256Word, PlonkError> {
+        let zero = self.zero();
+        let one = F::one();
+        // sum of up to 4 words and the constant, then 3 more words per gate
+        let mut terms = words.iter().copied();
+        let mut wires = [zero; GATE_WIDTH];
+        for wire in wires.iter_mut() {
+            *wire = terms.next().unwrap_or(zero);
+        }
+        let mut sum = self.gen_quad_poly(
+            &wires,
+            &[one; GATE_WIDTH],
+            &[F::zero(); 2],
+            F::from(constant),
+        )?;
+        let rest: Vec<Variable> = terms.collect();
+        for chunk in rest.chunks(GATE_WIDTH - 1) {
+            let mut wires = [sum, zero, zero, zero];
+            wires[1..1 + chunk.len()].copy_from_slice(chunk);
+            sum = self.lc(&wires, &[one; GATE_WIDTH])?;
+        }
+
+        let sum_val = self.witness(sum)?.into_repr().as_ref()[0];
+        let mut bits_le = [zero; WORD_BITS];
+        for (i, bit) in bits_le.iter_mut().enumerate() {
+            *bit = self.create_bool_variable((sum_val >> i) & 1 == 1)?;
+        }
+        let packed = self.pack_le(&bits_le)?;
+        let num_summands = words.len() + (constant != 0) as usize;
+        let carry_bit_len = (usize::BITS - (num_summands - 1).leading_zeros()) as usize;
+        let carry = self.create_variable(F::from(sum_val >> WORD_BITS))?;
+        if carry_bit_len > 0 {
+            self.range_gate(carry, carry_bit_len)?;
+        } else {
+            self.constant_gate(carry, F::zero())?;
+        }
+        self.lc_gate(
+            &[packed, carry, zero, zero, sum],
+            &[one, F::from(1u64 << WORD_BITS), F::zero(), F::zero()],
+        )?;
+        Ok(Sha256Word { bits_le, packed })
+    }
+
+    /// The bitwise xor of 3 words.
+    fn sha256_xor3(
+        &mut self,
+        x: &WordBits,
+        y: &WordBits,
+        z: &WordBits,
+    ) -> Result<WordBits, PlonkError> {
+        let mut bits = [self.zero(); WORD_BITS];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            let xy = self.sha256_xor_bit(x[i], y[i])?;
+            *bit = self.sha256_xor_bit(xy, z[i])?;
+        }
+        Ok(bits)
+    }
+
+    /// The bitwise choice `(e & f) ^ (!e & g)`, i.e. `e * (f - g) + g` on
+    /// each bit.
+    fn sha256_ch(
+        &mut self,
+        e: &WordBits,
+        f: &WordBits,
+        g: &WordBits,
+    ) -> Result<WordBits, PlonkError> {
+        let one = F::one();
+        let mut bits = [self.zero(); WORD_BITS];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            *bit = self.gen_quad_poly(
+                &[e[i], f[i], e[i], g[i]],
+                &[F::zero(), F::zero(), F::zero(), one],
+                &[one, -one],
+                F::zero(),
+            )?;
+        }
+        Ok(bits)
+    }
+
+    /// The bitwise majority `(a & b) ^ (a & c) ^ (b & c)`, i.e. `(a ^ b) * (c
+    /// - a) + a` on each bit.
+    fn sha256_maj(
+        &mut self,
+        a: &WordBits,
+        b: &WordBits,
+        c: &WordBits,
+    ) -> Result<WordBits, PlonkError> {
+        let one = F::one();
+        let mut bits = [self.zero(); WORD_BITS];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            let ab = self.sha256_xor_bit(a[i], b[i])?;
+            *bit = self.gen_quad_poly(
+                &[ab, c[i], ab, a[i]],
+                &[F::zero(), F::zero(), F::zero(), one],
+                &[one, -one],
+                F::zero(),
+            )?;
+        }
+        Ok(bits)
+    }
+
+    /// The xor `a + b - 2 * a * b` of the bits `a` and `b`, without a gate if
+    /// one of them is the zero variable.
+    fn sha256_xor_bit(&mut self, a: Variable, b: Variable) -> Result<Variable, PlonkError> {
+        let zero = self.zero();
+        if a == zero {
+            return Ok(b);
+        }
+        if b == zero {
+            return Ok(a);
+        }
+        let one = F::one();
+        self.gen_quad_poly(
+            &[a, b, zero, zero],
+            &[one, one, F::zero(), F::zero()],
+            &[-one.double(), F::zero()],
+            F::zero(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        circuit::{Circuit, PlonkCircuit, Variable},
+        errors::PlonkError,
+    };
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::PrimeField;
+    use ark_std::{rand::RngCore, test_rng, vec, vec::Vec};
+    use sha2::{Digest, Sha256};
+
+    fn bytes_to_bits(bytes: &[u8]) -> Vec<bool> {
+        bytes
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_sha256() -> Result<(), PlonkError> {
+        test_sha256_helper::<FqEd254>()?;
+        test_sha256_helper::<FqEd381>()?;
+        test_sha256_helper::<Fq377>()
+    }
+
+    fn test_sha256_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        // one block, one block with the length in the next one, two blocks
+        for &len in [0usize, 3, 55, 56, 64, 100].iter() {
+            let mut message = vec![0u8; len];
+            rng.fill_bytes(&mut message);
+            let expected = bytes_to_bits(&Sha256::digest(&message));
+
+            let mut circuit: PlonkCircuit<F> = match len % 2 {
+                0 => PlonkCircuit::new_turbo_plonk(),
+                _ => PlonkCircuit::new_ultra_plonk(8),
+            };
+            let input_bits = bytes_to_bits(&message)
+                .into_iter()
+                .map(|bit| circuit.create_bool_variable(bit))
+                .collect::<Result<Vec<_>, _>>()?;
+            let digest = circuit.sha256(&input_bits)?;
+            let digest_bits = digest
+                .iter()
+                .map(|&bit| Ok(circuit.witness(bit)? == F::one()))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            assert_eq!(digest_bits, expected);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong digest bit
+            let flipped: Variable = *digest.iter().find(|&&bit| bit > 1).unwrap();
+            let val = circuit.witness(flipped)?;
+            *circuit.witness_mut(flipped) = F::one() - val;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // non-boolean or out of bound inputs
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let two = circuit.create_variable(F::from(2u32))?;
+        assert!(circuit.sha256(&[circuit.one(), two]).is_err());
+        let out_of_bound = circuit.num_vars();
+        assert!(circuit.sha256(&[out_of_bound]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Gadgets for signed integers in two's complement representation.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// Represent a `w`-bit signed integer in two's complement.
+/// * `bits` - the little-endian bit decomposition, the last bit being the
+///   sign bit.
+/// * `value` - the integer value as a field element, i.e. negative integers
+///   `-x` are represented by `p - x`.
+pub struct SignedVariable {
+    bits: Vec<Variable>,
+    value: Variable,
+}
+
+impl SignedVariable {
+    /// Get the little-endian bits.
+    pub fn bits(&self) -> &[Variable] {
+        &self.bits
+    }
+
+    /// Get the variable holding the signed value.
+    pub fn value(&self) -> Variable {
+        self.value
+    }
+
+    /// Get the sign bit, which is one iff the integer is negative.
+    pub fn sign(&self) -> Variable {
+        self.bits[self.bits.len() - 1]
+    }
+
+    /// Get the bit width.
+    pub fn width(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Interpret the little-endian `bit_vars` as a two's complement signed
+    /// integer. The bits are constrained to be boolean.
+    /// Return error if variables are invalid, or if the bit width is zero or
+    /// too large for the field.
+    pub fn signed_from_bits(
+        &mut self,
+        bit_vars: &[Variable],
+    ) -> Result<SignedVariable, PlonkError> {
+        self.check_vars_bound(bit_vars)?;
+        for &bit in bit_vars.iter() {
+            self.bool_gate(bit)?;
+        }
+        self.signed_from_bool_bits(bit_vars)
+    }
+
+    /// Obtain the two's complement sum of `a` and `b`, wrapped around to their
+    /// common bit width, along with a bool variable indicating whether the
+    /// addition overflowed (as in `i64::overflowing_add`).
+    /// Return error if variables are invalid or have different widths.
+    pub fn signed_add(
+        &mut self,
+        a: &SignedVariable,
+        b: &SignedVariable,
+    ) -> Result<(SignedVariable, Variable), PlonkError> {
+        let width = Self::check_signed_widths(a, b)?;
+        self.check_var_bound(a.value)?;
+        self.check_var_bound(b.value)?;
+
+        // unsigned_a + unsigned_b = a + 2^w * sign_a + b + 2^w * sign_b, which
+        // fits in w + 1 bits.
+        let two_to_width = F::from(2u8).pow([width as u64]);
+        let unsigned_sum = self.lc(
+            &[a.value, a.sign(), b.value, b.sign()],
+            &[F::one(), two_to_width, F::one(), two_to_width],
+        )?;
+        let sum_bits = self.unpack(unsigned_sum, width + 1)?;
+        let sum = self.signed_from_bool_bits(&sum_bits[..width])?;
+
+        // Overflow iff the operands have the same sign and the result has the
+        // opposite one.
+        let same_sign = self.is_equal(a.sign(), b.sign())?;
+        let sign_unchanged = self.is_equal(a.sign(), sum.sign())?;
+        let sign_changed = self.logic_neg(sign_unchanged)?;
+        let overflow = self.logic_and(same_sign, sign_changed)?;
+        Ok((sum, overflow))
+    }
+
+    /// Obtain a bool variable representing whether `a < b` as signed integers.
+    /// Return error if variables are invalid or have different widths.
+    pub fn signed_lt(
+        &mut self,
+        a: &SignedVariable,
+        b: &SignedVariable,
+    ) -> Result<Variable, PlonkError> {
+        let width = Self::check_signed_widths(a, b)?;
+        self.check_var_bound(a.value)?;
+        self.check_var_bound(b.value)?;
+
+        // a - b lies in (-2^w, 2^w), so a - b + 2^w lies in (0, 2^(w+1)) and its
+        // top bit is zero iff a < b.
+        let two_to_width = F::from(2u8).pow([width as u64]);
+        let diff = self.sub(a.value, b.value)?;
+        let shifted_diff = self.add_constant(diff, &two_to_width)?;
+        let diff_bits = self.unpack(shifted_diff, width + 1)?;
+        self.logic_neg(diff_bits[width])
+    }
+
+    // Build a signed variable from bits that are already constrained to be
+    // boolean.
+    fn signed_from_bool_bits(
+        &mut self,
+        bit_vars: &[Variable],
+    ) -> Result<SignedVariable, PlonkError> {
+        let width = bit_vars.len();
+        if width == 0 || width + 1 >= F::size_in_bits() {
+            return Err(CircuitError::ParameterError(format!(
+                "signed integer bit width {} should be in [1, {})",
+                width,
+                F::size_in_bits() - 1
+            ))
+            .into());
+        }
+
+        // unsigned = \sum_i bits[i] * 2^i
+        let mut unsigned_val = F::zero();
+        for &bit in bit_vars.iter().rev() {
+            unsigned_val = unsigned_val.double() + self.witness(bit)?;
+        }
+        let unsigned = self.create_variable(unsigned_val)?;
+        self.decompose_vars_gate(bit_vars.to_vec(), unsigned, F::from(2u8))?;
+
+        // value = unsigned - 2^w * sign
+        let sign = bit_vars[width - 1];
+        let two_to_width = F::from(2u8).pow([width as u64]);
+        let zero = self.zero();
+        let value = self.lc(
+            &[unsigned, sign, zero, zero],
+            &[F::one(), -two_to_width, F::zero(), F::zero()],
+        )?;
+        Ok(SignedVariable {
+            bits: bit_vars.to_vec(),
+            value,
+        })
+    }
+
+    fn check_signed_widths(a: &SignedVariable, b: &SignedVariable) -> Result<usize, PlonkError> {
+        if a.width() != b.width() {
+            return Err(CircuitError::ParameterError(format!(
+                "mismatched signed integer widths: {} != {}",
+                a.width(),
+                b.width()
+            ))
+            .into());
+        }
+        Ok(a.width())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec;
+
+    const WIDTH: usize = 8;
+
+    fn to_field<F: PrimeField>(x: i64) -> F {
+        if x >= 0 {
+            F::from(x as u64)
+        } else {
+            -F::from(x.unsigned_abs())
+        }
+    }
+
+    fn create_signed<F: PrimeField>(
+        circuit: &mut PlonkCircuit<F>,
+        x: i8,
+    ) -> Result<SignedVariable, PlonkError> {
+        let bits = (0..WIDTH)
+            .map(|i| circuit.create_variable(F::from(((x as u8) >> i) & 1)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.signed_from_bits(&bits)
+    }
+
+    #[test]
+    fn test_signed_from_bits() -> Result<(), PlonkError> {
+        test_signed_from_bits_helper::<FqEd254>()?;
+        test_signed_from_bits_helper::<FqEd377>()?;
+        test_signed_from_bits_helper::<FqEd381>()?;
+        test_signed_from_bits_helper::<Fq377>()
+    }
+
+    fn test_signed_from_bits_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        for &x in [0i8, 1, 42, 127, -1, -42, -127, i8::MIN].iter() {
+            let signed = create_signed(&mut circuit, x)?;
+            assert_eq!(signed.width(), WIDTH);
+            assert_eq!(circuit.witness(signed.value())?, to_field(x as i64));
+            let expected_sign = if x < 0 { F::one() } else { F::zero() };
+            assert_eq!(circuit.witness(signed.sign())?, expected_sign);
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // non-boolean bits should fail
+        let signed = create_signed(&mut circuit, 3)?;
+        *circuit.witness_mut(signed.bits()[0]) = F::from(2u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // a wrong value should fail
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let signed = create_signed(&mut circuit, i8::MIN)?;
+        *circuit.witness_mut(signed.value()) = F::from(128u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        assert!(circuit.signed_from_bits(&[]).is_err());
+        let bits = vec![circuit.zero(); F::size_in_bits()];
+        assert!(circuit.signed_from_bits(&bits).is_err());
+        // Check variable out of bound error.
+        assert!(circuit.signed_from_bits(&[circuit.num_vars()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_add() -> Result<(), PlonkError> {
+        test_signed_add_helper::<FqEd254>()?;
+        test_signed_add_helper::<FqEd377>()?;
+        test_signed_add_helper::<FqEd381>()?;
+        test_signed_add_helper::<Fq377>()
+    }
+
+    fn test_signed_add_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let cases = [
+            (3i8, 4i8),
+            (100, 50),
+            (-5, 3),
+            (-100, -50),
+            (-1, 1),
+            (127, 1),
+            (i8::MIN, -1),
+            (i8::MIN, 127),
+            (i8::MIN, i8::MIN),
+            (i8::MIN, 0),
+        ];
+        for &(x, y) in cases.iter() {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let a = create_signed(&mut circuit, x)?;
+            let b = create_signed(&mut circuit, y)?;
+            let (sum, overflow) = circuit.signed_add(&a, &b)?;
+            let (expected_sum, expected_overflow) = x.overflowing_add(y);
+            assert_eq!(sum.width(), WIDTH);
+            assert_eq!(circuit.witness(sum.value())?, to_field(expected_sum as i64));
+            assert_eq!(circuit.witness(overflow)?, F::from(expected_overflow as u8));
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            *circuit.witness_mut(sum.value()) = to_field(x as i64 + y as i64 + 1);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // mismatched widths
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = create_signed(&mut circuit, 1)?;
+        let zero = circuit.zero();
+        let b = circuit.signed_from_bits(&[zero; WIDTH - 1])?;
+        assert!(circuit.signed_add(&a, &b).is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let circuit_1 = build_signed_add_circuit::<F>(-3, 100)?;
+        let circuit_2 = build_signed_add_circuit::<F>(i8::MIN, -1)?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+        Ok(())
+    }
+
+    fn build_signed_add_circuit<F: PrimeField>(
+        x: i8,
+        y: i8,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = create_signed(&mut circuit, x)?;
+        let b = create_signed(&mut circuit, y)?;
+        circuit.signed_add(&a, &b)?;
+        circuit.finalize_for_arithmetization()?;
+        Ok(circuit)
+    }
+
+    #[test]
+    fn test_signed_lt() -> Result<(), PlonkError> {
+        test_signed_lt_helper::<FqEd254>()?;
+        test_signed_lt_helper::<FqEd377>()?;
+        test_signed_lt_helper::<FqEd381>()?;
+        test_signed_lt_helper::<Fq377>()
+    }
+
+    fn test_signed_lt_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let cases = [
+            (3i8, 4i8),
+            (4, 3),
+            (5, 5),
+            (-5, 3),
+            (3, -5),
+            (-7, -2),
+            (-2, -7),
+            (-1, 0),
+            (0, -1),
+            (i8::MIN, 127),
+            (127, i8::MIN),
+            (i8::MIN, i8::MIN),
+            (i8::MIN, -1),
+        ];
+        for &(x, y) in cases.iter() {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let a = create_signed(&mut circuit, x)?;
+            let b = create_signed(&mut circuit, y)?;
+            let lt = circuit.signed_lt(&a, &b)?;
+            assert_eq!(circuit.witness(lt)?, F::from((x < y) as u8));
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            *circuit.witness_mut(lt) = F::from((x >= y) as u8);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // mismatched widths
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = create_signed(&mut circuit, 1)?;
+        let zero = circuit.zero();
+        let b = circuit.signed_from_bits(&[zero; WIDTH + 1])?;
+        assert!(circuit.signed_lt(&a, &b).is_err());
+        Ok(())
+    }
+}
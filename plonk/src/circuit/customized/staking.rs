@@ -0,0 +1,176 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Fixed-point staking reward gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+use num_bigint::BigUint;
+
+/// The bit length of stakes, rates, times and scales.
+const STAKING_BIT_LEN: usize = 64;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `reward` to be the fixed-point staking reward `stake * rate *
+    /// time / scale`, rounded down, which is checked by cross-multiplication:
+    /// `stake * rate * time = reward * scale + remainder` with `0 <= remainder
+    /// < scale`.
+    ///
+    /// The stake, the rate, the time and the scale are constrained to be
+    /// 64-bit integers, the scale to be positive, and the reward to be a
+    /// 128-bit integer.
+    /// Return error if variables are invalid, or if the field is too small to
+    /// hold the product of three 64-bit integers.
+    pub fn enforce_staking_reward(
+        &mut self,
+        stake: Variable,
+        rate: Variable,
+        time: Variable,
+        scale: Variable,
+        reward: Variable,
+    ) -> Result<(), PlonkError> {
+        if F::size_in_bits() <= 3 * STAKING_BIT_LEN + 1 {
+            return Err(CircuitError::ParameterError(format!(
+                "the field of {} bits is too small for {}-bit products",
+                F::size_in_bits(),
+                3 * STAKING_BIT_LEN
+            ))
+            .into());
+        }
+        for &var in [stake, rate, time, scale].iter() {
+            self.check_var_bound(var)?;
+            self.range_gate(var, STAKING_BIT_LEN)?;
+        }
+        self.check_var_bound(reward)?;
+        self.range_gate(reward, 2 * STAKING_BIT_LEN)?;
+        self.non_zero_gate(scale)?;
+
+        let stake_rate = self.mul(stake, rate)?;
+        let product = self.mul(stake_rate, time)?;
+
+        // product = reward * scale + remainder, with 0 <= remainder < scale
+        let product_val: BigUint = self.witness(product)?.into_repr().into();
+        let scale_val: BigUint = self.witness(scale)?.into_repr().into();
+        let remainder_val = match scale_val == BigUint::from(0u8) {
+            true => BigUint::from(0u8),
+            false => product_val % scale_val,
+        };
+        let remainder = self.create_variable(F::from(remainder_val))?;
+        self.range_gate(remainder, STAKING_BIT_LEN)?;
+        let one = self.one();
+        self.mul_add_gate(
+            &[reward, scale, remainder, one, product],
+            &[F::one(), F::one()],
+        )?;
+        let gap = self.sub(scale, remainder)?;
+        let gap_minus_one = self.add_constant(gap, &-F::one())?;
+        self.range_gate(gap_minus_one, STAKING_BIT_LEN)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{circuit::customized::test::test_variable_independence_for_circuit, goldilocks};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_enforce_staking_reward() -> Result<(), PlonkError> {
+        test_enforce_staking_reward_helper::<FqEd254>()?;
+        test_enforce_staking_reward_helper::<FqEd377>()?;
+        test_enforce_staking_reward_helper::<FqEd381>()?;
+        test_enforce_staking_reward_helper::<Fq377>()
+    }
+
+    fn test_enforce_staking_reward_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // compare to the native computation, with a rate of 5% per time unit in
+        // basis points
+        let (rate, scale) = (500, 10_000);
+        for &stake in [0, 1, 999, 10_000, 123_456_789, 1 << 40].iter() {
+            for &time in [0, 1, 7, 365, 1 << 20].iter() {
+                let expected = (stake as u128 * rate as u128 * time as u128) / scale as u128;
+                let circuit = build_staking_circuit::<F>(stake, rate, time, scale, expected)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+                let circuit = build_staking_circuit::<F>(stake, rate, time, scale, expected + 1)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                if expected > 0 {
+                    let circuit =
+                        build_staking_circuit::<F>(stake, rate, time, scale, expected - 1)?;
+                    assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                }
+            }
+        }
+        // the largest values
+        let max = u64::MAX as u128;
+        let circuit =
+            build_staking_circuit::<F>(u64::MAX, u64::MAX, u64::MAX, u64::MAX, max * max)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // a reward beyond 128 bits
+        let circuit = build_staking_circuit::<F>(u64::MAX, u64::MAX, u64::MAX, 1, 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // a zero scale
+        let circuit = build_staking_circuit::<F>(10, 10, 10, 0, 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_staking_circuit::<F>(10_000, rate, 365, scale, 182_500)?;
+        // time
+        *circuit.witness_mut(4) = F::from(366u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut small_circuit: PlonkCircuit<goldilocks::Fq> = PlonkCircuit::new_turbo_plonk();
+        let zero = small_circuit.zero();
+        assert!(small_circuit
+            .enforce_staking_reward(zero, zero, zero, zero, zero)
+            .is_err());
+        // Check variable out of bound error.
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_staking_reward(zero, zero, zero, zero, circuit.num_vars())
+            .is_err());
+        assert!(circuit
+            .enforce_staking_reward(circuit.num_vars(), zero, zero, zero, zero)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_staking_circuit::<F>(10_000, rate, 365, scale, 182_500)?;
+        let mut circuit_2 = build_staking_circuit::<F>(7, 3, 2, 5, 8)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    // The stake is the variable of index 2, followed by the rate and the time.
+    fn build_staking_circuit<F: PrimeField>(
+        stake: u64,
+        rate: u64,
+        time: u64,
+        scale: u64,
+        reward: u128,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let stake = circuit.create_variable(F::from(stake))?;
+        let rate = circuit.create_variable(F::from(rate))?;
+        let time = circuit.create_variable(F::from(time))?;
+        let scale = circuit.create_variable(F::from(scale))?;
+        let reward = circuit.create_variable(F::from(reward))?;
+        circuit.enforce_staking_reward(stake, rate, time, scale, reward)?;
+        Ok(circuit)
+    }
+}
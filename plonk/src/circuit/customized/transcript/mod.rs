@@ -325,6 +325,8 @@ mod tests {
             open_key: open_key.clone(),
             is_merged: false,
             plookup_vk: None,
+            custom_gates: vec![],
+            num_quot_chunks: 0,
         };
 
         let dummy_vk_var = VerifyingKeyVar::new(&mut circuit, &dummy_vk).unwrap();
@@ -375,6 +377,7 @@ mod tests {
             // k
             let k: Vec<E::Fr> = (0..5).map(|_| E::Fr::rand(&mut rng)).collect();
 
+            let num_quot_chunks = sigma_comms.len();
             let vk = VerifyingKey {
                 domain_size: 512,
                 num_inputs: input.len(),
@@ -384,6 +387,8 @@ mod tests {
                 open_key: open_key.clone(),
                 is_merged: false,
                 plookup_vk: None,
+                custom_gates: vec![],
+                num_quot_chunks,
             };
             let vk_var = VerifyingKeyVar::new(&mut circuit, &vk).unwrap();
 
@@ -4,14 +4,31 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
-//! Lookup gates over variable tables.
+//! Lookup gates over variable and static tables.
 
 use crate::{
-    circuit::{customized::gates::LookupGate, Circuit, PlonkCircuit, Variable},
-    errors::PlonkError,
+    circuit::{
+        customized::gates::LookupGate, Arithmetization, Circuit, GateId, PlonkCircuit, Variable,
+    },
+    errors::{CircuitError, PlonkError},
 };
 use ark_ff::PrimeField;
-use ark_std::{boxed::Box, cmp::max, vec::Vec};
+use ark_std::{boxed::Box, cmp::max, format, vec::Vec};
+
+/// The maximum width of the rows of static tables.
+const MAX_TABLE_WIDTH: usize = 2;
+
+/// A static table created by [`PlonkCircuit::create_table`], whose rows are
+/// looked up by [`PlonkCircuit::lookup_gate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TableId {
+    // the first gate of the table
+    gate_id: GateId,
+    // the key of the first row of the table
+    key_offset: usize,
+    len: usize,
+    width: usize,
+}
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Create a table with keys/values
@@ -58,11 +75,121 @@ impl<F: PrimeField> PlonkCircuit<F> {
         *self.num_table_elems_mut() += n;
         Ok(())
     }
+
+    /// Create a static table with the rows `rows`, of the same width of at
+    /// most 2, to be looked up with [`Self::lookup_gate`].
+    ///
+    /// The values of the table are constant variables, so that the table is
+    /// fixed by the selectors of the circuit, and committed to by its
+    /// verifying key. Tables of different widths can be created in the same
+    /// circuit.
+    /// Return error if the circuit does not support lookup, or if the rows are
+    /// empty or of different or invalid widths.
+    pub fn create_table(&mut self, rows: &[Vec<F>]) -> Result<TableId, PlonkError> {
+        if !self.support_lookup() {
+            return Err(CircuitError::LookupUnsupported.into());
+        }
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 || width > MAX_TABLE_WIDTH || rows.iter().any(|row| row.len() != width) {
+            return Err(CircuitError::ParameterError(format!(
+                "a static table has a positive number of rows of the same width between 1 and {}",
+                MAX_TABLE_WIDTH
+            ))
+            .into());
+        }
+        // the tuple with a zero key and zero values is always in the lookup
+        // table, so no row of a static table can have a zero key
+        if self.num_table_elems() == 0 {
+            let zero = self.zero();
+            self.create_table_and_lookup_variables(&[], &[(zero, zero)])?;
+        }
+
+        let mut table_vars = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
+            let val0 = self.create_constant_variable(row[0])?;
+            let val1 = match width {
+                1 => self.zero(),
+                _ => self.create_constant_variable(row[1])?,
+            };
+            table_vars.push((val0, val1));
+        }
+        let table = TableId {
+            gate_id: self.num_gates(),
+            key_offset: self.num_table_elems(),
+            len: rows.len(),
+            width,
+        };
+        self.create_table_and_lookup_variables(&[], &table_vars)?;
+        Ok(table)
+    }
+
+    /// Constrain the values of `inputs` to be a row of the static table
+    /// `table`. The key of the row is a witness, constrained to be one of the
+    /// keys of `table`.
+    /// Return error if the circuit does not support lookup, if the variables
+    /// are invalid, or if the number of inputs is not the width of the table.
+    pub fn lookup_gate(&mut self, inputs: &[Variable], table: TableId) -> Result<(), PlonkError> {
+        if !self.support_lookup() {
+            return Err(CircuitError::LookupUnsupported.into());
+        }
+        if inputs.len() != table.width {
+            return Err(CircuitError::ParameterError(format!(
+                "{} inputs are looked up in a table of width {}",
+                inputs.len(),
+                table.width
+            ))
+            .into());
+        }
+        for &var in inputs.iter() {
+            self.check_var_bound(var)?;
+        }
+
+        // the index of the row holding the inputs, or zero if there is none
+        let input_vals = inputs
+            .iter()
+            .map(|&var| self.witness(var))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let mut index_val = 0;
+        for i in 0..table.len {
+            let row_vals = [
+                self.witness(self.wire_variable(3, table.gate_id + i))?,
+                self.witness(self.wire_variable(4, table.gate_id + i))?,
+            ];
+            if row_vals[..table.width] == input_vals[..] {
+                index_val = i;
+                break;
+            }
+        }
+
+        // 0 <= index <= len - 1
+        let range_bit_len = self.range_bit_len()?;
+        let index_bit_len = max(1, (usize::BITS - (table.len - 1).leading_zeros()) as usize);
+        let bit_len = (index_bit_len + range_bit_len - 1) / range_bit_len * range_bit_len;
+        let index = self.create_variable(F::from(index_val as u64))?;
+        self.range_gate(index, bit_len)?;
+        let last_index = self.create_constant_variable(F::from(table.len as u64 - 1))?;
+        let index_gap = self.sub(last_index, index)?;
+        self.range_gate(index_gap, bit_len)?;
+
+        // the current number of table elements is added to the key
+        let key = self.add_constant(
+            index,
+            &(F::from(table.key_offset as u64) - F::from(self.num_table_elems() as u64)),
+        )?;
+        let val1 = match table.width {
+            1 => self.zero(),
+            _ => inputs[1],
+        };
+        self.create_table_and_lookup_variables(&[(key, inputs[0], val1)], &[])
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::circuit::basic::test::{
+        test_arithmetization_for_circuit, test_arithmetization_for_lookup_circuit,
+    };
     use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
     use ark_ed_on_bls12_381::Fq as FqEd381;
@@ -132,4 +259,124 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_static_table() -> Result<(), PlonkError> {
+        test_static_table_helper::<FqEd254>()?;
+        test_static_table_helper::<FqEd377>()?;
+        test_static_table_helper::<FqEd381>()?;
+        test_static_table_helper::<Fq377>()
+    }
+
+    // A 4-bit S-box.
+    fn sbox(x: u64) -> u64 {
+        (7 * x + 3) % 16
+    }
+
+    fn test_static_table_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // an S-box table and a table of width 1, next to a variable table
+        let sbox_rows: Vec<Vec<F>> = (0..16)
+            .map(|x| vec![F::from(x), F::from(sbox(x))])
+            .collect();
+        let set_rows: Vec<Vec<F>> = [5u64, 9, 200].iter().map(|&x| vec![F::from(x)]).collect();
+        let build = |lookups: &[(u64, u64)], members: &[u64]| -> Result<_, PlonkError> {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+            let sbox_table = circuit.create_table(&sbox_rows)?;
+            let val0 = circuit.create_variable(F::from(3u32))?;
+            let val1 = circuit.create_variable(F::from(4u32))?;
+            let key = circuit.zero();
+            circuit.create_table_and_lookup_variables(&[(key, val0, val1)], &[(val0, val1)])?;
+            let set_table = circuit.create_table(&set_rows)?;
+            for &(x, y) in lookups.iter() {
+                let x = circuit.create_variable(F::from(x))?;
+                let y = circuit.create_variable(F::from(y))?;
+                circuit.lookup_gate(&[x, y], sbox_table)?;
+            }
+            for &member in members.iter() {
+                let member = circuit.create_variable(F::from(member))?;
+                circuit.lookup_gate(&[member], set_table)?;
+            }
+            Ok(circuit)
+        };
+
+        // rows of the tables
+        let lookups: Vec<(u64, u64)> = (0..16).map(|x| (x, sbox(x))).collect();
+        let circuit = build(&lookups, &[5, 9, 200, 9])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build(&[], &[])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // absent rows, including zero rows and rows of another table
+        for &lookup in [(3, 3), (0, 0), (16, sbox(16)), (3, 4)].iter() {
+            let circuit = build(&[(1, sbox(1)), lookup], &[5])?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        for &member in [0, 6, 3, 201].iter() {
+            let circuit = build(&[(1, sbox(1))], &[5, member])?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // if mess up the wire value, should fail
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let table = circuit.create_table(&sbox_rows)?;
+        let x = circuit.create_variable(F::from(1u32))?;
+        let y = circuit.create_variable(F::from(sbox(1)))?;
+        circuit.lookup_gate(&[x, y], table)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(y) = F::from(sbox(2));
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the tables are fixed by the selectors of the circuit
+        let mut circuit_1 = build(&lookups, &[5, 9])?;
+        let mut circuit_2 = build(&lookups, &[5, 9])?;
+        let mut other_rows = sbox_rows.clone();
+        other_rows[0][1] = F::from(4u32);
+        let mut circuit_3: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        circuit_3.create_table(&other_rows)?;
+        let mut circuit_4: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        circuit_4.create_table(&sbox_rows)?;
+        for circuit in [
+            &mut circuit_1,
+            &mut circuit_2,
+            &mut circuit_3,
+            &mut circuit_4,
+        ]
+        .iter_mut()
+        {
+            circuit.finalize_for_arithmetization()?;
+        }
+        assert_eq!(
+            circuit_1.compute_selector_polynomials()?,
+            circuit_2.compute_selector_polynomials()?
+        );
+        assert_ne!(
+            circuit_3.compute_selector_polynomials()?,
+            circuit_4.compute_selector_polynomials()?
+        );
+        test_arithmetization_for_lookup_circuit(&circuit_1)?;
+        test_arithmetization_for_circuit(circuit_1, vec![])?;
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        assert!(circuit.create_table(&sbox_rows).is_err());
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        assert!(circuit.create_table(&[]).is_err());
+        assert!(circuit.create_table(&[vec![]]).is_err());
+        assert!(circuit
+            .create_table(&[vec![F::one(), F::one(), F::one()]])
+            .is_err());
+        assert!(circuit
+            .create_table(&[vec![F::one(), F::one()], vec![F::one()]])
+            .is_err());
+        let table = circuit.create_table(&sbox_rows)?;
+        let zero = circuit.zero();
+        assert!(circuit.lookup_gate(&[zero], table).is_err());
+        assert!(circuit.lookup_gate(&[zero, zero, zero], table).is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .lookup_gate(&[zero, circuit.num_vars()], table)
+            .is_err());
+
+        Ok(())
+    }
 }
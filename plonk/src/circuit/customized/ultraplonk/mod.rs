@@ -7,7 +7,9 @@
 //! Implements ultra-plonk related circuits.
 
 mod lookup_table;
+pub use lookup_table::TableId;
 pub mod mod_arith;
+pub mod non_native_field;
 mod non_native_gates;
 pub mod plonk_verifier;
 mod range;
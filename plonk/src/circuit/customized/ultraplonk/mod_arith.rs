@@ -880,6 +880,20 @@ impl<F: PrimeField> PlonkCircuit<F> {
 
         FpElemVar::new_unchecked(self, x_neg_var, x.m, Some(x.two_power_m))
     }
+
+    /// Modular inverse gate:
+    /// Given Fp elements a, a_inv and modulus p, enforce a * a_inv = 1 mod p.
+    /// The constraints are unsatisfiable if a is not invertible mod p.
+    /// Return error if the splitting parameters of a, a_inv and p do not match.
+    pub fn enforce_mod_inverse(
+        &mut self,
+        a: &FpElemVar<F>,
+        a_inv: &FpElemVar<F>,
+        p: &FpElem<F>,
+    ) -> Result<(), PlonkError> {
+        let product = self.mod_mul(a, a_inv, p)?;
+        product.enforce_equal(self, &FpElemVar::one(self, p.m, Some(p.two_power_m)))
+    }
 }
 
 #[inline]
@@ -908,6 +922,7 @@ mod test {
     use ark_ff::{BigInteger, FpParameters};
     use ark_std::{rand::Rng, test_rng, vec::Vec};
     use jf_utils::field_switching;
+    use num_bigint::BigInt;
 
     const RANGE_BIT_LEN_FOR_TEST: usize = 16;
     const RANGE_SIZE_FOR_TEST: usize = 65536;
@@ -1594,4 +1609,123 @@ mod test {
 
         Ok(())
     }
+
+    // ========================================
+    //  mod inverse
+    // ========================================
+    #[test]
+    fn test_enforce_mod_inverse() -> Result<(), PlonkError> {
+        test_enforce_mod_inverse_helper::<FqEd254>()?;
+        test_enforce_mod_inverse_helper::<FqEd377>()?;
+        test_enforce_mod_inverse_helper::<FqEd381>()?;
+        test_enforce_mod_inverse_helper::<Fq377>()
+    }
+
+    fn test_enforce_mod_inverse_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // a non-prime modulus, so that some non-zero elements are not invertible
+        let p = F::from(3u8).pow(&[100u64]);
+        let m = 80;
+        let p_split = FpElem::new(&p, m, None)?;
+        let p_int: BigUint = to_big_int!(p);
+        let mut rng = test_rng();
+
+        let mut num_invertible = 0;
+        while num_invertible < 10 {
+            let a = p - F::from(rng.gen_range(1..u128::MAX));
+            let a_int: BigUint = to_big_int!(a);
+            let a_inv_int = match mod_inverse(&a_int, &p_int) {
+                Some(a_inv_int) => a_inv_int,
+                None => continue,
+            };
+            num_invertible += 1;
+            let a_inv = F::from(a_inv_int);
+            let (circuit, _) = build_mod_inverse_circuit(&a, &a_inv, &p_split)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // wrong inverses
+            let (circuit, _) = build_mod_inverse_circuit(&a, &(a_inv + F::one()), &p_split)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            let (circuit, _) = build_mod_inverse_circuit(&a, &a, &p_split)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // bad witnesses
+            let (mut circuit, a_inv_var) = build_mod_inverse_circuit(&a, &a_inv, &p_split)?;
+            *circuit.witness_mut(a_inv_var.vars.0) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // a non-coprime to the modulus has no inverse
+        let a = F::from(3u8) * F::from(rng.gen_range(1..u128::MAX));
+        let a_int: BigUint = to_big_int!(a);
+        assert!(mod_inverse(&a_int, &p_int).is_none());
+        for a_inv in [
+            F::zero(),
+            F::one(),
+            int_div(&p, &F::from(3u8)),
+            F::from(rng.gen_range(1..u128::MAX)),
+            p - F::one(),
+        ]
+        .iter()
+        {
+            let (circuit, _) = build_mod_inverse_circuit(&a, a_inv, &p_split)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        let (circuit, _) = build_mod_inverse_circuit(&F::zero(), &F::one(), &p_split)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the splitting parameters do not match
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let zero_var = circuit.zero();
+        let a_split_vars =
+            FpElemVar::new_unchecked(&mut circuit, zero_var, m + 1, Some(p_split.two_power_m))?;
+        let a_inv_split_vars =
+            FpElemVar::new_unchecked(&mut circuit, zero_var, m, Some(p_split.two_power_m))?;
+        assert!(circuit
+            .enforce_mod_inverse(&a_split_vars, &a_inv_split_vars, &p_split)
+            .is_err());
+
+        // check that circuit config is independent of witness values
+        let a1 = F::from(2u8);
+        let a1_inv = F::from(mod_inverse(&BigUint::from(2u8), &p_int).unwrap());
+        let a2 = F::from(4u8);
+        let a2_inv = F::from(mod_inverse(&BigUint::from(4u8), &p_int).unwrap());
+        let (mut circuit_1, _) = build_mod_inverse_circuit(&a1, &a1_inv, &p_split)?;
+        let (mut circuit_2, _) = build_mod_inverse_circuit(&a2, &a2_inv, &p_split)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit::<F>(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+    // Inverse of `a` modulo `p` by the extended Euclidean algorithm.
+    fn mod_inverse(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+        let (mut old_r, mut r) = (BigInt::from(a.clone()), BigInt::from(p.clone()));
+        let (mut old_s, mut s) = (BigInt::from(1u8), BigInt::from(0u8));
+        while r != BigInt::from(0u8) {
+            let q = &old_r / &r;
+            let new_r = &old_r - &q * &r;
+            old_r = ark_std::mem::replace(&mut r, new_r);
+            let new_s = &old_s - &q * &s;
+            old_s = ark_std::mem::replace(&mut s, new_s);
+        }
+        if old_r != BigInt::from(1u8) {
+            return None;
+        }
+        let p = BigInt::from(p.clone());
+        (((old_s % &p) + &p) % &p).to_biguint()
+    }
+
+    fn build_mod_inverse_circuit<F: PrimeField>(
+        a: &F,
+        a_inv: &F,
+        p: &FpElem<F>,
+    ) -> Result<(PlonkCircuit<F>, FpElemVar<F>), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let a_split_vars =
+            FpElemVar::new_from_field_element(&mut circuit, a, p.m, Some(p.two_power_m))?;
+        let a_inv_split_vars =
+            FpElemVar::new_from_field_element(&mut circuit, a_inv, p.m, Some(p.two_power_m))?;
+        circuit.enforce_mod_inverse(&a_split_vars, &a_inv_split_vars, p)?;
+        Ok((circuit, a_inv_split_vars))
+    }
 }
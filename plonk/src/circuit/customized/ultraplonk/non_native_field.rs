@@ -0,0 +1,678 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Emulated arithmetic modulo a foreign prime `p`, which can be larger than
+//! the native field, e.g. the secp256k1 base field in a Bn254 circuit.
+//!
+//! An element is represented by `num_limbs` little-endian limbs of
+//! `limb_bits` bits. Additions are lazy: the limbs of a sum are not carried.
+//! A product `x * y` is the remainder `r` of `x * y = q * p + r`, where the
+//! relation is enforced limb-wise over the integers with range-checked
+//! carries, so that `r` is only guaranteed to be smaller than
+//! `2^(num_limbs * limb_bits)`. [`NonNativeFieldVar::reduce`] further
+//! enforces the value to be smaller than `p`, and is applied by
+//! [`NonNativeFieldVar::enforce_equal`] before comparing the limbs.
+//!
+//! The range checks use UltraPlonk range gates, and `limb_bits` should be a
+//! multiple of the range bit length of the circuit.
+
+use crate::{
+    circuit::{customized::next_multiple, Circuit, PlonkCircuit, Variable},
+    constants::{GATE_WIDTH, N_MUL_SELECTORS},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, marker::PhantomData, string::ToString, vec, vec::Vec};
+use num_bigint::{BigInt, BigUint, Sign};
+
+/// The parameters of an emulated field: the foreign modulus and its limb
+/// decomposition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonNativeFieldParams<F: PrimeField> {
+    modulus: BigUint,
+    limb_bits: usize,
+    num_limbs: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: PrimeField> NonNativeFieldParams<F> {
+    /// Parameters to emulate arithmetic modulo `modulus` with limbs of
+    /// `limb_bits` bits.
+    /// Return error if the modulus is smaller than 2, or if the product of two
+    /// limbs doesn't fit in the native field with enough room for the carries.
+    pub fn new(modulus: &BigUint, limb_bits: usize) -> Result<Self, PlonkError> {
+        if *modulus < BigUint::from(2u8) {
+            return Err(ParameterError("the modulus should be at least 2".to_string()).into());
+        }
+        if limb_bits == 0 {
+            return Err(ParameterError("the limb size should be positive".to_string()).into());
+        }
+        let num_limbs = (modulus.bits() as usize + limb_bits - 1) / limb_bits;
+        if 2 * limb_bits + ceil_log2(2 * num_limbs + 1) + 4 >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "limbs of {} bits are too large for a native field of {} bits",
+                limb_bits,
+                F::size_in_bits()
+            ))
+            .into());
+        }
+        Ok(Self {
+            modulus: modulus.clone(),
+            limb_bits,
+            num_limbs,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// The foreign modulus.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// The number of bits of a limb.
+    pub fn limb_bits(&self) -> usize {
+        self.limb_bits
+    }
+
+    /// The number of limbs of an element.
+    pub fn num_limbs(&self) -> usize {
+        self.num_limbs
+    }
+
+    fn modulus_limbs(&self, num_limbs: usize) -> Vec<BigUint> {
+        to_limbs(&self.modulus, self.limb_bits, num_limbs)
+    }
+}
+
+/// Represent variable of an element of an emulated field, as
+/// `sum_i witness[limbs[i]] * 2^(i * limb_bits)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonNativeFieldVar {
+    limbs: Vec<Variable>,
+    /// Every limb is smaller than `2^limb_max_bits`.
+    limb_max_bits: usize,
+    /// Whether the value is enforced to be smaller than the modulus.
+    is_reduced: bool,
+}
+
+impl NonNativeFieldVar {
+    /// Create a variable for `value` modulo `params.modulus()`, with
+    /// range-checked limbs. The value is not enforced to be smaller than the
+    /// modulus, see [`Self::reduce`].
+    pub fn new<F: PrimeField>(
+        circuit: &mut PlonkCircuit<F>,
+        value: &BigUint,
+        params: &NonNativeFieldParams<F>,
+    ) -> Result<Self, PlonkError> {
+        let limbs = to_limbs(
+            &(value % &params.modulus),
+            params.limb_bits,
+            params.num_limbs,
+        );
+        Ok(Self {
+            limbs: circuit.create_limb_variables(&limbs, params.limb_bits)?,
+            limb_max_bits: params.limb_bits,
+            is_reduced: false,
+        })
+    }
+
+    /// The limb variables, from the least significant one.
+    pub fn limbs(&self) -> &[Variable] {
+        &self.limbs
+    }
+
+    /// Whether the value is enforced to be smaller than the modulus.
+    pub fn is_reduced(&self) -> bool {
+        self.is_reduced
+    }
+
+    /// The integer represented by the limbs, which is not necessarily smaller
+    /// than the modulus.
+    pub fn witness<F: PrimeField>(
+        &self,
+        circuit: &PlonkCircuit<F>,
+        params: &NonNativeFieldParams<F>,
+    ) -> Result<BigUint, PlonkError> {
+        let limbs = self
+            .limbs
+            .iter()
+            .map(|&var| Ok(field_to_biguint(circuit.witness(var)?)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        Ok(from_limbs(&limbs, params.limb_bits))
+    }
+
+    /// Emulated addition. The limbs of the sum are not carried.
+    /// Return error if the variables are invalid.
+    pub fn add<F: PrimeField>(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        other: &Self,
+        params: &NonNativeFieldParams<F>,
+    ) -> Result<Self, PlonkError> {
+        self.check_num_limbs(params)?;
+        other.check_num_limbs(params)?;
+        // keep the limbs small enough to be multiplied
+        let mut x = self.clone();
+        let mut y = other.clone();
+        while x.limb_max_bits.max(y.limb_max_bits) >= 2 * params.limb_bits {
+            if x.limb_max_bits >= y.limb_max_bits {
+                x = x.reduce(circuit, params)?;
+            } else {
+                y = y.reduce(circuit, params)?;
+            }
+        }
+        let limbs = x
+            .limbs
+            .iter()
+            .zip(y.limbs.iter())
+            .map(|(&a, &b)| circuit.add(a, b))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        Ok(Self {
+            limbs,
+            limb_max_bits: x.limb_max_bits.max(y.limb_max_bits) + 1,
+            is_reduced: false,
+        })
+    }
+
+    /// Emulated multiplication. The product is smaller than
+    /// `2^(num_limbs * limb_bits)` but not necessarily than the modulus.
+    /// Return error if the variables are invalid.
+    pub fn mul<F: PrimeField>(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        other: &Self,
+        params: &NonNativeFieldParams<F>,
+    ) -> Result<Self, PlonkError> {
+        self.check_num_limbs(params)?;
+        other.check_num_limbs(params)?;
+        let b = params.limb_bits;
+        let n = params.num_limbs;
+        let col_bits = |x: &Self, y: &Self| {
+            (x.limb_max_bits + y.limb_max_bits).max(2 * b) + ceil_log2(2 * n + 1) + 1
+        };
+        // reduce the operands whose limbs are too large for the products
+        let mut x = self.clone();
+        let mut y = other.clone();
+        while !circuit.limb_relation_fits(col_bits(&x, &y), b)?
+            && x.limb_max_bits.max(y.limb_max_bits) > b
+        {
+            if x.limb_max_bits >= y.limb_max_bits {
+                x = x.reduce(circuit, params)?;
+            } else {
+                y = y.reduce(circuit, params)?;
+            }
+        }
+
+        // x * y = q * p + r
+        let product = x.witness(circuit, params)? * y.witness(circuit, params)?;
+        let q = &product / &params.modulus;
+        let r = &product % &params.modulus;
+        let q_bits = (x.max_bits(params) + y.max_bits(params))
+            .saturating_sub(params.modulus.bits() as usize - 1);
+        let num_q_limbs = ((q_bits + b - 1) / b).max(1);
+        let q_vars = circuit.create_limb_variables(&to_limbs(&q, b, num_q_limbs), b)?;
+        let r_vars = circuit.create_limb_variables(&to_limbs(&r, b, n), b)?;
+        let p_limbs = params.modulus_limbs(n);
+
+        let num_columns = (2 * n - 1).max(num_q_limbs + n - 1);
+        let columns: Vec<LimbColumn> = (0..num_columns)
+            .map(|k| {
+                let mut column = LimbColumn::default();
+                for (i, &x_var) in x.limbs.iter().enumerate().take(k + 1) {
+                    if k - i < n {
+                        column.products.push((x_var, y.limbs[k - i]));
+                    }
+                }
+                for (i, &q_var) in q_vars.iter().enumerate().take(k + 1) {
+                    if k - i < n {
+                        column
+                            .terms
+                            .push((q_var, -BigInt::from(p_limbs[k - i].clone())));
+                    }
+                }
+                if k < n {
+                    column.terms.push((r_vars[k], -BigInt::from(1u8)));
+                }
+                column
+            })
+            .collect();
+        circuit.enforce_limb_relation(&columns, col_bits(&x, &y), b)?;
+
+        Ok(Self {
+            limbs: r_vars,
+            limb_max_bits: b,
+            is_reduced: false,
+        })
+    }
+
+    /// Return the variable for the value modulo `params.modulus()`, which is
+    /// enforced to be smaller than the modulus.
+    /// Return error if the variables are invalid.
+    pub fn reduce<F: PrimeField>(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        params: &NonNativeFieldParams<F>,
+    ) -> Result<Self, PlonkError> {
+        self.check_num_limbs(params)?;
+        if self.is_reduced {
+            return Ok(self.clone());
+        }
+        let b = params.limb_bits;
+        let n = params.num_limbs;
+
+        // x = q * p + r
+        let value = self.witness(circuit, params)?;
+        let q = &value / &params.modulus;
+        let r = &value % &params.modulus;
+        let q_bits = self
+            .max_bits(params)
+            .saturating_sub(params.modulus.bits() as usize - 1);
+        let num_q_limbs = ((q_bits + b - 1) / b).max(1);
+        let q_vars = circuit.create_limb_variables(&to_limbs(&q, b, num_q_limbs), b)?;
+        let r_vars = circuit.create_limb_variables(&to_limbs(&r, b, n), b)?;
+        let p_limbs = params.modulus_limbs(n);
+
+        let num_columns = n.max(num_q_limbs + n - 1);
+        let columns: Vec<LimbColumn> = (0..num_columns)
+            .map(|k| {
+                let mut column = LimbColumn::default();
+                if k < n {
+                    column.terms.push((self.limbs[k], BigInt::from(1u8)));
+                    column.terms.push((r_vars[k], -BigInt::from(1u8)));
+                }
+                for (i, &q_var) in q_vars.iter().enumerate().take(k + 1) {
+                    if k - i < n {
+                        column
+                            .terms
+                            .push((q_var, -BigInt::from(p_limbs[k - i].clone())));
+                    }
+                }
+                column
+            })
+            .collect();
+        let col_bits = self.limb_max_bits.max(2 * b) + ceil_log2(n + 2) + 1;
+        circuit.enforce_limb_relation(&columns, col_bits, b)?;
+
+        // r + d = p - 1 for some non-negative d, i.e. r < p
+        let p_minus_one = &params.modulus - 1u8;
+        let d = &p_minus_one - &r;
+        let d_vars = circuit.create_limb_variables(&to_limbs(&d, b, n), b)?;
+        let columns: Vec<LimbColumn> = to_limbs(&p_minus_one, b, n)
+            .into_iter()
+            .enumerate()
+            .map(|(k, p_minus_one_k)| LimbColumn {
+                products: vec![],
+                terms: vec![
+                    (r_vars[k], BigInt::from(1u8)),
+                    (d_vars[k], BigInt::from(1u8)),
+                ],
+                constant: -BigInt::from(p_minus_one_k),
+            })
+            .collect();
+        circuit.enforce_limb_relation(&columns, b + 3, b)?;
+
+        Ok(Self {
+            limbs: r_vars,
+            limb_max_bits: b,
+            is_reduced: true,
+        })
+    }
+
+    /// Enforce that `self` and `other` are equal modulo `params.modulus()`.
+    /// Both are reduced before their limbs are compared.
+    /// Return error if the variables are invalid.
+    pub fn enforce_equal<F: PrimeField>(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        other: &Self,
+        params: &NonNativeFieldParams<F>,
+    ) -> Result<(), PlonkError> {
+        let x = self.reduce(circuit, params)?;
+        let y = other.reduce(circuit, params)?;
+        for (&a, &b) in x.limbs.iter().zip(y.limbs.iter()) {
+            circuit.equal_gate(a, b)?;
+        }
+        Ok(())
+    }
+
+    /// Upper bound on the number of bits of the value.
+    fn max_bits<F: PrimeField>(&self, params: &NonNativeFieldParams<F>) -> usize {
+        self.limb_max_bits + params.limb_bits * (params.num_limbs - 1)
+    }
+
+    fn check_num_limbs<F: PrimeField>(
+        &self,
+        params: &NonNativeFieldParams<F>,
+    ) -> Result<(), PlonkError> {
+        if self.limbs.len() != params.num_limbs {
+            return Err(ParameterError(format!(
+                "expect {} limbs, got {}",
+                params.num_limbs,
+                self.limbs.len()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// A column of a limb-wise integer relation:
+/// `sum products[i].0 * products[i].1 + sum terms[i].1 * terms[i].0 +
+/// constant`.
+#[derive(Default)]
+struct LimbColumn {
+    products: Vec<(Variable, Variable)>,
+    terms: Vec<(Variable, BigInt)>,
+    constant: BigInt,
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create variables for `limbs`, range-checked to `limb_bits` bits.
+    fn create_limb_variables(
+        &mut self,
+        limbs: &[BigUint],
+        limb_bits: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        limbs
+            .iter()
+            .map(|limb| {
+                let var = self.create_variable(F::from(limb.clone()))?;
+                self.range_gate_with_lookup(var, limb_bits)?;
+                Ok(var)
+            })
+            .collect()
+    }
+
+    /// The bit length of the range checks of the carries of a limb-wise
+    /// relation whose columns are smaller than `2^col_bits` in absolute
+    /// value.
+    fn carry_range_bits(&self, col_bits: usize, limb_bits: usize) -> Result<usize, PlonkError> {
+        next_multiple(
+            col_bits.saturating_sub(limb_bits) + 2,
+            self.range_bit_len()?,
+        )
+    }
+
+    /// Whether the constraints of a limb-wise relation with columns smaller
+    /// than `2^col_bits` can't wrap around the native modulus.
+    fn limb_relation_fits(&self, col_bits: usize, limb_bits: usize) -> Result<bool, PlonkError> {
+        let carry_range_bits = self.carry_range_bits(col_bits, limb_bits)?;
+        Ok(
+            col_bits + 2 < F::size_in_bits()
+                && limb_bits + carry_range_bits + 2 < F::size_in_bits(),
+        )
+    }
+
+    /// Enforce `sum_k 2^(k * limb_bits) * columns[k] = 0` over the integers,
+    /// given that every column is smaller than `2^col_bits` in absolute value.
+    ///
+    /// The carry `c_k` of the k-th column is defined by `columns[k] + c_{k-1}
+    /// = c_k * 2^limb_bits`, with the last carry being zero; it is shifted by
+    /// `2^(col_bits - limb_bits + 1)` to be range-checked, which bounds all
+    /// the constraints far below the native modulus.
+    fn enforce_limb_relation(
+        &mut self,
+        columns: &[LimbColumn],
+        col_bits: usize,
+        limb_bits: usize,
+    ) -> Result<(), PlonkError> {
+        if !self.limb_relation_fits(col_bits, limb_bits)? {
+            return Err(ParameterError(format!(
+                "limb relation with {}-bit columns doesn't fit in the native field",
+                col_bits
+            ))
+            .into());
+        }
+        let carry_range_bits = self.carry_range_bits(col_bits, limb_bits)?;
+        let offset = BigInt::from(1u8) << (col_bits.saturating_sub(limb_bits) + 1);
+        let offset_in_f: F = bigint_to_field(&offset);
+        let two_power_b = BigInt::from(1u8) << limb_bits;
+        let two_power_b_in_f: F = bigint_to_field(&two_power_b);
+
+        let mut carry_in: Option<(Variable, BigInt)> = None;
+        for (k, column) in columns.iter().enumerate() {
+            let mut value = column.constant.clone();
+            let mut terms: Vec<(Variable, F)> = vec![];
+            let mut constant: F = bigint_to_field(&column.constant);
+
+            for pair in column.products.chunks(N_MUL_SELECTORS) {
+                let (wires, q_muls) = match pair {
+                    &[(a, b), (c, d)] => ([a, b, c, d], [F::one(), F::one()]),
+                    _ => (
+                        [pair[0].0, pair[0].1, self.zero(), self.zero()],
+                        [F::one(), F::zero()],
+                    ),
+                };
+                for &(a, b) in pair.iter() {
+                    value += BigInt::from(field_to_biguint(self.witness(a)?))
+                        * BigInt::from(field_to_biguint(self.witness(b)?));
+                }
+                terms.push((self.mul_add(&wires, &q_muls)?, F::one()));
+            }
+            for (var, coeff) in column.terms.iter() {
+                value += coeff * BigInt::from(field_to_biguint(self.witness(*var)?));
+                terms.push((*var, bigint_to_field(coeff)));
+            }
+            if let Some((carry_var, carry)) = carry_in.take() {
+                value += carry;
+                terms.push((carry_var, F::one()));
+                constant -= offset_in_f;
+            }
+            if k + 1 < columns.len() {
+                let carry = &value / &two_power_b;
+                let carry_var = self.create_variable(bigint_to_field(&(&carry + &offset)))?;
+                self.range_gate_with_lookup(carry_var, carry_range_bits)?;
+                terms.push((carry_var, -two_power_b_in_f));
+                constant += two_power_b_in_f * offset_in_f;
+                carry_in = Some((carry_var, carry));
+            }
+            self.enforce_zero_sum(&terms, constant)?;
+        }
+        Ok(())
+    }
+
+    /// Enforce `sum_i terms[i].1 * terms[i].0 + constant = 0`.
+    fn enforce_zero_sum(&mut self, terms: &[(Variable, F)], constant: F) -> Result<(), PlonkError> {
+        let mut terms = terms.to_vec();
+        // accumulate the terms that do not fit in the last gate
+        while terms.len() > GATE_WIDTH {
+            let rest = terms.split_off(GATE_WIDTH);
+            let wires = [terms[0].0, terms[1].0, terms[2].0, terms[3].0];
+            let coeffs = [terms[0].1, terms[1].1, terms[2].1, terms[3].1];
+            let acc = self.lc(&wires, &coeffs)?;
+            terms = [vec![(acc, F::one())], rest].concat();
+        }
+        terms.resize(GATE_WIDTH, (self.zero(), F::zero()));
+        let wires = [terms[0].0, terms[1].0, terms[2].0, terms[3].0, self.zero()];
+        let q_lc = [terms[0].1, terms[1].1, terms[2].1, terms[3].1];
+        self.quad_poly_gate(
+            &wires,
+            &q_lc,
+            &[F::zero(); N_MUL_SELECTORS],
+            F::zero(),
+            constant,
+        )
+    }
+}
+
+fn ceil_log2(n: usize) -> usize {
+    (usize::BITS - n.saturating_sub(1).leading_zeros()) as usize
+}
+
+fn to_limbs(x: &BigUint, limb_bits: usize, num_limbs: usize) -> Vec<BigUint> {
+    let mask = (BigUint::from(1u8) << limb_bits) - 1u8;
+    (0..num_limbs)
+        .map(|i| (x >> (i * limb_bits)) & &mask)
+        .collect()
+}
+
+fn from_limbs(limbs: &[BigUint], limb_bits: usize) -> BigUint {
+    limbs
+        .iter()
+        .rev()
+        .fold(BigUint::from(0u8), |acc, limb| (acc << limb_bits) + limb)
+}
+
+fn field_to_biguint<F: PrimeField>(x: F) -> BigUint {
+    x.into_repr().into()
+}
+
+fn bigint_to_field<F: PrimeField>(x: &BigInt) -> F {
+    let magnitude = F::from(x.magnitude().clone());
+    match x.sign() {
+        Sign::Minus => -magnitude,
+        _ => magnitude,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::Rng, test_rng};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    // the base field of secp256k1: 2^256 - 2^32 - 977
+    fn secp256k1_modulus() -> BigUint {
+        (BigUint::from(1u8) << 256) - (BigUint::from(1u8) << 32) - 977u32
+    }
+
+    fn random_element<R: Rng>(rng: &mut R, modulus: &BigUint) -> BigUint {
+        let bytes: Vec<u8> = (0..modulus.bits() / 8 + 8).map(|_| rng.gen()).collect();
+        BigUint::from_bytes_le(&bytes) % modulus
+    }
+
+    #[test]
+    fn test_non_native_field_mul() -> Result<(), PlonkError> {
+        test_non_native_field_mul_helper::<FqEd254>(&secp256k1_modulus(), 64)?;
+        test_non_native_field_mul_helper::<FqEd254>(&secp256k1_modulus(), 48)?;
+        test_non_native_field_mul_helper::<Fq377>(&secp256k1_modulus(), 96)?;
+        // a modulus smaller than the native one
+        test_non_native_field_mul_helper::<Fq377>(&BigUint::from(0xffff_fffbu32), 16)
+    }
+
+    fn test_non_native_field_mul_helper<F: PrimeField>(
+        modulus: &BigUint,
+        limb_bits: usize,
+    ) -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let params = NonNativeFieldParams::<F>::new(modulus, limb_bits)?;
+        for _ in 0..5 {
+            let x = random_element(rng, modulus);
+            let y = random_element(rng, modulus);
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let x_var = NonNativeFieldVar::new(&mut circuit, &x, &params)?;
+            let y_var = NonNativeFieldVar::new(&mut circuit, &y, &params)?;
+            assert_eq!(x_var.witness(&circuit, &params)?, x);
+
+            let z_var = x_var.mul(&mut circuit, &y_var, &params)?;
+            assert_eq!(z_var.witness(&circuit, &params)?, &x * &y % modulus);
+            let z_var = z_var.reduce(&mut circuit, &params)?;
+            assert!(z_var.is_reduced());
+            assert_eq!(z_var.witness(&circuit, &params)?, &x * &y % modulus);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // (x + y) * y = x * y + y * y, with lazy additions
+            let lhs = x_var.add(&mut circuit, &y_var, &params)?;
+            let lhs = lhs.mul(&mut circuit, &y_var, &params)?;
+            let y_square = y_var.mul(&mut circuit, &y_var, &params)?;
+            let rhs = z_var.add(&mut circuit, &y_square, &params)?;
+            lhs.enforce_equal(&mut circuit, &rhs, &params)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // wrong product
+            let tmp = circuit.witness(z_var.limbs()[0])?;
+            *circuit.witness_mut(z_var.limbs()[0]) = tmp + F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(z_var.limbs()[0]) = tmp;
+
+            // products are not equal to a different value
+            let w = random_element(rng, modulus);
+            let w_var = NonNativeFieldVar::new(&mut circuit, &w, &params)?;
+            z_var.enforce_equal(&mut circuit, &w_var, &params)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_native_field_reduce() -> Result<(), PlonkError> {
+        test_non_native_field_reduce_helper::<FqEd254>()?;
+        test_non_native_field_reduce_helper::<Fq377>()
+    }
+
+    fn test_non_native_field_reduce_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let modulus = secp256k1_modulus();
+        let params = NonNativeFieldParams::<F>::new(&modulus, 64)?;
+        let x = random_element(rng, &modulus);
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let x_var = NonNativeFieldVar::new(&mut circuit, &x, &params)?;
+        let neg_x_var = NonNativeFieldVar::new(&mut circuit, &(&modulus - &x), &params)?;
+        let zero_var = NonNativeFieldVar::new(&mut circuit, &BigUint::from(0u8), &params)?;
+
+        // x + (p - x) is represented by the integer p, which equals 0 only
+        // after the reduction
+        let sum_var = x_var.add(&mut circuit, &neg_x_var, &params)?;
+        assert_eq!(sum_var.witness(&circuit, &params)?, modulus);
+        assert!(!sum_var.is_reduced());
+        let reduced_var = sum_var.reduce(&mut circuit, &params)?;
+        assert_eq!(reduced_var.witness(&circuit, &params)?, BigUint::from(0u8));
+        sum_var.enforce_equal(&mut circuit, &zero_var, &params)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // many lazy additions
+        let mut acc = x_var.clone();
+        for _ in 0..200 {
+            acc = acc.add(&mut circuit, &x_var, &params)?;
+        }
+        assert_eq!(
+            acc.reduce(&mut circuit, &params)?
+                .witness(&circuit, &params)?,
+            &x * 201u32 % &modulus
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a non-reduced remainder, i.e. r + p instead of r, is rejected
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let small = BigUint::from(5u8);
+        let small_var = NonNativeFieldVar::new(&mut circuit, &small, &params)?;
+        let reduced_var = small_var.reduce(&mut circuit, &params)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let wrong = to_limbs(&(&small + &modulus), 64, params.num_limbs());
+        for (&var, limb) in reduced_var.limbs().iter().zip(wrong.iter()) {
+            *circuit.witness_mut(var) = F::from(limb.clone());
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_native_field_params() -> Result<(), PlonkError> {
+        let modulus = secp256k1_modulus();
+        let params = NonNativeFieldParams::<FqEd254>::new(&modulus, 64)?;
+        assert_eq!(params.num_limbs(), 4);
+        assert_eq!(params.limb_bits(), 64);
+        assert_eq!(params.modulus(), &modulus);
+        // limbs too large for the native field
+        assert!(NonNativeFieldParams::<FqEd254>::new(&modulus, 128).is_err());
+        assert!(NonNativeFieldParams::<FqEd254>::new(&modulus, 0).is_err());
+        assert!(NonNativeFieldParams::<FqEd254>::new(&BigUint::from(1u8), 16).is_err());
+
+        // mismatched number of limbs
+        let mut circuit = PlonkCircuit::<FqEd254>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let x_var = NonNativeFieldVar::new(&mut circuit, &BigUint::from(3u8), &params)?;
+        let other_params = NonNativeFieldParams::<FqEd254>::new(&modulus, 32)?;
+        assert!(x_var.mul(&mut circuit, &x_var, &other_params).is_err());
+        // limbs that are not a multiple of the range bit length
+        let other_params = NonNativeFieldParams::<FqEd254>::new(&modulus, 40)?;
+        assert!(NonNativeFieldVar::new(&mut circuit, &BigUint::from(3u8), &other_params).is_err());
+        Ok(())
+    }
+}
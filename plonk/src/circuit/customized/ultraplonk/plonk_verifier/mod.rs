@@ -15,7 +15,7 @@ use crate::{
     },
     constants::*,
     errors::{PlonkError, SnarkError::ParameterError},
-    proof_system::{structs::VerifyingKey, verifier::Verifier},
+    proof_system::{pcs::KzgPcs, structs::VerifyingKey, verifier::Verifier},
 };
 use ark_ec::{
     short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters as SWParam,
@@ -64,6 +64,19 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
         F: PrimeField + SWToTEConParam,
         P: SWParam<BaseField = F> + Clone,
     {
+        if !verify_key.custom_gates.is_empty() {
+            return Err(ParameterError(
+                "verifying keys with custom gates are not supported".to_string(),
+            )
+            .into());
+        }
+        if verify_key.num_quot_chunks != verify_key.sigma_comms.len() {
+            return Err(ParameterError(
+                "verifying keys with a non-default quotient polynomial split are not supported"
+                    .to_string(),
+            )
+            .into());
+        }
         let sigma_comms = verify_key
             .sigma_comms
             .iter()
@@ -166,17 +179,52 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
         if merged_vks.is_empty() {
             return Err(ParameterError("empty merged verification keys".to_string()).into());
         }
-        if merged_vks.len() != batch_proof.len() {
+
+        // we need to copy the public input once after merging the circuit
+        let shared_public_input_vars =
+            [shared_public_input_vars, shared_public_input_vars].concat();
+        let public_inputs = vec![&shared_public_input_vars[..]; merged_vks.len()];
+        let merged_vks_ref: Vec<&VerifyingKeyVar<E>> = merged_vks.iter().collect();
+        Self::partial_verify_instances_circuit(
+            circuit,
+            beta_g,
+            generator_g,
+            &merged_vks_ref,
+            &public_inputs,
+            batch_proof,
+            blinding_factor,
+        )
+    }
+
+    /// Circuit for partially verifying the instances of a batched proof, each
+    /// one w.r.t. its verifying key and public inputs, without performing the
+    /// pairing. Return the variables for the two group elements used in the
+    /// final pairing.
+    fn partial_verify_instances_circuit<F, P>(
+        circuit: &mut PlonkCircuit<F>,
+        beta_g: &Point<F>,
+        generator_g: &Point<F>,
+        verify_keys: &[&Self],
+        public_inputs: &[&[FpElemVar<F>]],
+        batch_proof: &BatchProofVar<F>,
+        blinding_factor: Variable,
+    ) -> Result<(PointVariable, PointVariable), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + Clone + TEParam,
+    {
+        if verify_keys.len() != batch_proof.len() {
             return Err(ParameterError(format!(
                 "the number of verification keys {} is different from the number of instances {}.",
-                merged_vks.len(),
+                verify_keys.len(),
                 batch_proof.len()
             ))
             .into());
         }
 
-        let domain_size = merged_vks[0].domain_size;
-        for (i, vk) in merged_vks.iter().skip(1).enumerate() {
+        let domain_size = verify_keys[0].domain_size;
+        for (i, vk) in verify_keys.iter().skip(1).enumerate() {
             if vk.domain_size != domain_size {
                 return Err(ParameterError(format!(
                     "the {}-th verification key's domain size {} is different from {}.",
@@ -205,20 +253,14 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
             modulus_fp_elem,
         };
 
-        let verifier = Verifier::<E>::new(domain_size)?;
+        let verifier = Verifier::<KzgPcs<E>>::new(domain_size)?;
         let domain = verifier.domain;
 
-        // we need to copy the public input once after merging the circuit
-        let shared_public_input_vars =
-            [shared_public_input_vars, shared_public_input_vars].concat();
-        let public_inputs = vec![&shared_public_input_vars[..]; merged_vks.len()];
-        let merged_vks_ref: Vec<&VerifyingKeyVar<E>> = merged_vks.iter().collect();
-
         // generate the PCS info
         let pcs_info_var = prepare_pcs_info_var(
             circuit,
-            &merged_vks_ref,
-            &public_inputs,
+            verify_keys,
+            public_inputs,
             batch_proof,
             &None,
             domain,
@@ -280,6 +322,73 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
     }
 }
 
+impl<F> PlonkCircuit<F>
+where
+    F: RescueParameter + SWToTEConParam,
+{
+    /// Circuit for partially verifying a single TurboPlonk proof w.r.t. the
+    /// verifying key `vk_var` and the public inputs `public_input_vars`,
+    /// without performing the pairing. The shape of the circuit only depends
+    /// on the inner verifying key.
+    ///
+    /// Return the variables for the two group elements `inner1` and `inner2`
+    /// of the final pairing check `e(inner1, [beta]2) = e(inner2, [1]2)`,
+    /// which is left to the verifier of the outer circuit. The inner proof
+    /// should be generated with a `RescueTranscript` and no extra transcript
+    /// message.
+    pub fn verify_proof_in_circuit<E, P>(
+        &mut self,
+        vk_var: &VerifyingKeyVar<E>,
+        public_input_vars: &[FpElemVar<F>],
+        proof_var: &BatchProofVar<F>,
+        beta_g: &Point<F>,
+        generator_g: &Point<F>,
+        blinding_factor: Variable,
+    ) -> Result<(PointVariable, PointVariable), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        P: SWParam<BaseField = F> + Clone + TEParam,
+    {
+        if vk_var.is_merged {
+            return Err(ParameterError(
+                "merged verifying keys should use `partial_verify_circuit`".to_string(),
+            )
+            .into());
+        }
+        if vk_var.selector_comms.len() != N_TURBO_PLONK_SELECTORS {
+            return Err(ParameterError(
+                "only TurboPlonk proofs can be verified in circuit".to_string(),
+            )
+            .into());
+        }
+        if proof_var.len() != 1 {
+            return Err(ParameterError(format!(
+                "expect a single proof, got a batch of {} proofs",
+                proof_var.len()
+            ))
+            .into());
+        }
+        if vk_var.num_inputs != public_input_vars.len() {
+            return Err(ParameterError(format!(
+                "the verifying key expects {} public inputs, got {}",
+                vk_var.num_inputs,
+                public_input_vars.len()
+            ))
+            .into());
+        }
+
+        VerifyingKeyVar::partial_verify_instances_circuit(
+            self,
+            beta_g,
+            generator_g,
+            &[vk_var],
+            &[public_input_vars],
+            proof_var,
+            blinding_factor,
+        )
+    }
+}
+
 // Instances batching scheme related gates
 impl<F> PlonkCircuit<F>
 where
@@ -316,17 +425,21 @@ mod test {
     use crate::{
         circuit::{
             customized::{ecc::Point, test::test_variable_independence_for_circuit},
-            Circuit,
+            Arithmetization, Circuit,
         },
         proof_system::{
             batch_arg::{new_mergeable_circuit_for_test, BatchArgument},
-            structs::BatchProof,
-            PlonkKzgSnark,
+            snark::test::gen_circuit_for_test,
+            structs::{BatchProof, Proof, ScalarsAndBases},
+            PlonkKzgSnark, Snark,
         },
         transcript::{PlonkTranscript, RescueTranscript},
+        PlonkType,
     };
     use ark_bls12_377::{g1::Parameters as Param377, Bls12_377, Fq as Fq377};
+    use ark_bw6_761::BW6_761;
     use ark_ec::{ProjectiveCurve, SWModelParameters, TEModelParameters};
+    use ark_ff::One;
     use ark_std::{test_rng, vec, UniformRand};
     use jf_rescue::RescueParameter;
     use jf_utils::field_switching;
@@ -829,4 +942,348 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_verify_proof_in_circuit() -> Result<(), PlonkError> {
+        test_verify_proof_in_circuit_helper::<Bls12_377, _, _>()
+    }
+
+    fn test_verify_proof_in_circuit_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone + TEModelParameters,
+    {
+        let rng = &mut test_rng();
+
+        // inner proof
+        let inner_circuit = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let public_input = inner_circuit.public_input()?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(inner_circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &inner_circuit)?;
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(rng, &inner_circuit, &pk, None)?;
+        PlonkKzgSnark::<E>::verify::<RescueTranscript<F>>(&vk, &public_input, &proof, None)?;
+
+        let beta_g_ref = &srs.0.powers_of_g[1];
+        let blinding_factor = E::Fr::rand(rng);
+        let (inner1, inner2) = native_partial_verify::<E, F, P, RescueTranscript<F>>(
+            beta_g_ref,
+            &vk.open_key.g,
+            &vk,
+            &public_input,
+            &proof,
+            blinding_factor,
+        )?;
+        assert!(BatchArgument::decide(&vk.open_key, inner1, inner2)?);
+
+        // good path
+        let (circuit, points) = build_verifier_circuit::<E, F, P>(
+            &vk,
+            &public_input,
+            &proof,
+            beta_g_ref,
+            &vk.open_key.g,
+            &blinding_factor,
+        )?;
+        let public_input_in_f: Vec<F> = public_input.iter().map(field_switching).collect();
+        assert!(circuit
+            .check_circuit_satisfiability(&public_input_in_f)
+            .is_ok());
+        assert_eq!(
+            circuit.point_witness(&points.0)?,
+            Point::<F>::from(&inner1.into_affine())
+        );
+        assert_eq!(
+            circuit.point_witness(&points.1)?,
+            Point::<F>::from(&inner2.into_affine())
+        );
+
+        // the circuit shape only depends on the inner verifying key
+        let other_circuit = gen_circuit_for_test::<E::Fr>(3, 5, PlonkType::TurboPlonk)?;
+        let other_public_input = other_circuit.public_input()?;
+        let other_proof =
+            PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(rng, &other_circuit, &pk, None)?;
+        let (other_verifier_circuit, _) = build_verifier_circuit::<E, F, P>(
+            &vk,
+            &other_public_input,
+            &other_proof,
+            beta_g_ref,
+            &vk.open_key.g,
+            &blinding_factor,
+        )?;
+        assert_eq!(other_verifier_circuit.num_gates(), circuit.num_gates());
+        assert_eq!(other_verifier_circuit.num_vars(), circuit.num_vars());
+
+        // bad path: wrong public inputs
+        let mut wrong_public_input = public_input.clone();
+        wrong_public_input[0] = E::Fr::rand(rng);
+        let (circuit, points) = build_verifier_circuit::<E, F, P>(
+            &vk,
+            &wrong_public_input,
+            &proof,
+            beta_g_ref,
+            &vk.open_key.g,
+            &blinding_factor,
+        )?;
+        assert!(circuit
+            .check_circuit_satisfiability(&public_input_in_f)
+            .is_err());
+        // the circuit is satisfied with the wrong inputs, but the pairing check
+        // doesn't pass
+        let wrong_public_input_in_f: Vec<F> =
+            wrong_public_input.iter().map(field_switching).collect();
+        assert!(circuit
+            .check_circuit_satisfiability(&wrong_public_input_in_f)
+            .is_ok());
+        assert_ne!(
+            circuit.point_witness(&points.0)?,
+            Point::<F>::from(&inner1.into_affine())
+        );
+        assert_ne!(
+            circuit.point_witness(&points.1)?,
+            Point::<F>::from(&inner2.into_affine())
+        );
+
+        // bad path: wrong parameters
+        let m = 128;
+        let two_power_m = Some(F::from(2u8).pow(&[m as u64]));
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let public_input_vars = public_input
+            .iter()
+            .map(|x| {
+                let var = circuit.create_public_variable(field_switching(x))?;
+                FpElemVar::new_unchecked(&mut circuit, var, m, two_power_m)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let vk_var = VerifyingKeyVar::new(&mut circuit, &vk)?;
+        let proof_var =
+            BatchProof::from(proof.clone()).create_variables(&mut circuit, m, two_power_m)?;
+        let beta_g: Point<F> = beta_g_ref.into();
+        let generator_g: Point<F> = (&vk.open_key.g).into();
+        let blinding_factor_var = circuit.create_variable(field_switching(&blinding_factor))?;
+        // wrong number of public inputs
+        assert!(circuit
+            .verify_proof_in_circuit::<E, P>(
+                &vk_var,
+                &public_input_vars[1..],
+                &proof_var,
+                &beta_g,
+                &generator_g,
+                blinding_factor_var,
+            )
+            .is_err());
+        // merged verifying key
+        let mut bad_vk_var = vk_var.clone();
+        bad_vk_var.is_merged = true;
+        assert!(circuit
+            .verify_proof_in_circuit::<E, P>(
+                &bad_vk_var,
+                &public_input_vars,
+                &proof_var,
+                &beta_g,
+                &generator_g,
+                blinding_factor_var,
+            )
+            .is_err());
+        // UltraPlonk verifying key
+        let mut bad_vk_var = vk_var;
+        bad_vk_var.selector_comms.pop();
+        assert!(circuit
+            .verify_proof_in_circuit::<E, P>(
+                &bad_vk_var,
+                &public_input_vars,
+                &proof_var,
+                &beta_g,
+                &generator_g,
+                blinding_factor_var,
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    // A slow test that proves and verifies the outer circuit.
+    #[test]
+    #[ignore]
+    fn test_recursive_proof() -> Result<(), PlonkError> {
+        test_recursive_proof_helper::<Bls12_377, BW6_761, _, _, _, _>()
+    }
+
+    fn test_recursive_proof_helper<E, E2, F, F2, P, P2>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+        F: RescueParameter + SWToTEConParam,
+        F2: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone + TEModelParameters,
+        P2: SWModelParameters<BaseField = F2> + Clone,
+    {
+        let rng = &mut test_rng();
+
+        // inner proof
+        let inner_circuit = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let public_input = inner_circuit.public_input()?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(inner_circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &inner_circuit)?;
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(rng, &inner_circuit, &pk, None)?;
+
+        // outer circuit, with the points of the final pairing check as public
+        // inputs
+        let beta_g_ref = &srs.0.powers_of_g[1];
+        let blinding_factor = E::Fr::rand(rng);
+        let (mut outer_circuit, (inner1, inner2)) = build_verifier_circuit::<E, F, P>(
+            &vk,
+            &public_input,
+            &proof,
+            beta_g_ref,
+            &vk.open_key.g,
+            &blinding_factor,
+        )?;
+        for &var in [
+            inner1.get_x(),
+            inner1.get_y(),
+            inner2.get_x(),
+            inner2.get_y(),
+        ]
+        .iter()
+        {
+            outer_circuit.set_variable_public(var)?;
+        }
+        outer_circuit.finalize_for_arithmetization()?;
+        let outer_public_input = outer_circuit.public_input()?;
+
+        // outer proof
+        let outer_srs = PlonkKzgSnark::<E2>::universal_setup(outer_circuit.srs_size()?, rng)?;
+        let (outer_pk, outer_vk) = PlonkKzgSnark::<E2>::preprocess(&outer_srs, &outer_circuit)?;
+        let outer_proof = PlonkKzgSnark::<E2>::prove::<_, _, RescueTranscript<F2>>(
+            rng,
+            &outer_circuit,
+            &outer_pk,
+            None,
+        )?;
+        PlonkKzgSnark::<E2>::verify::<RescueTranscript<F2>>(
+            &outer_vk,
+            &outer_public_input,
+            &outer_proof,
+            None,
+        )?;
+
+        // the final pairing check of the inner proof
+        let (expected_inner1, expected_inner2) =
+            native_partial_verify::<E, F, P, RescueTranscript<F>>(
+                beta_g_ref,
+                &vk.open_key.g,
+                &vk,
+                &public_input,
+                &proof,
+                blinding_factor,
+            )?;
+        assert!(BatchArgument::decide(
+            &vk.open_key,
+            expected_inner1,
+            expected_inner2
+        )?);
+        let expected_inner1: Point<F> = (&expected_inner1.into_affine()).into();
+        let expected_inner2: Point<F> = (&expected_inner2.into_affine()).into();
+        let n = outer_public_input.len();
+        assert_eq!(
+            outer_public_input[n - 4..],
+            [
+                expected_inner1.get_x(),
+                expected_inner1.get_y(),
+                expected_inner2.get_x(),
+                expected_inner2.get_y()
+            ]
+        );
+
+        Ok(())
+    }
+
+    fn build_verifier_circuit<E, F, P>(
+        vk: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        proof: &Proof<E>,
+        beta_g_ref: &GroupAffine<P>,
+        generator_g: &GroupAffine<P>,
+        blinding_factor: &E::Fr,
+    ) -> Result<(PlonkCircuit<F>, (PointVariable, PointVariable)), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone + TEModelParameters,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+
+        // constants
+        let m = 128;
+        let two_power_m = Some(F::from(2u8).pow(&[m as u64]));
+
+        // public inputs
+        let public_input_vars = public_input
+            .iter()
+            .map(|x| {
+                let var = circuit.create_public_variable(field_switching(x))?;
+                FpElemVar::new_unchecked(&mut circuit, var, m, two_power_m)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+
+        // vk and proof
+        let vk_var = VerifyingKeyVar::new(&mut circuit, vk)?;
+        let proof_var =
+            BatchProof::from(proof.clone()).create_variables(&mut circuit, m, two_power_m)?;
+
+        let beta_g: Point<F> = beta_g_ref.into();
+        let generator_g: Point<F> = generator_g.into();
+        let blinding_factor_var = circuit.create_variable(field_switching(blinding_factor))?;
+
+        let points = circuit.verify_proof_in_circuit::<E, P>(
+            &vk_var,
+            &public_input_vars,
+            &proof_var,
+            &beta_g,
+            &generator_g,
+            blinding_factor_var,
+        )?;
+        Ok((circuit, points))
+    }
+
+    // The native counterpart of `verify_proof_in_circuit`.
+    fn native_partial_verify<E, F, P, T>(
+        beta_g: &GroupAffine<P>,
+        generator_g: &GroupAffine<P>,
+        vk: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        proof: &Proof<E>,
+        blinding_factor: E::Fr,
+    ) -> Result<(E::G1Projective, E::G1Projective), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let verifier = Verifier::<KzgPcs<E>>::new(vk.domain_size)?;
+        let pcs_info =
+            verifier.prepare_pcs_info::<T>(&[vk], &[public_input], &proof.into(), &None)?;
+
+        let mut scalars_and_bases = ScalarsAndBases::<KzgPcs<E>>::new();
+        scalars_and_bases.push(E::Fr::one(), pcs_info.opening_proof.0);
+        scalars_and_bases.push(pcs_info.u, pcs_info.shifted_opening_proof.0);
+        scalars_and_bases.push(blinding_factor, *generator_g);
+        let inner1 = scalars_and_bases.g1_multi_scalar_mul();
+
+        let mut scalars_and_bases = pcs_info.comm_scalars_and_bases;
+        scalars_and_bases.push(pcs_info.eval_point, pcs_info.opening_proof.0);
+        scalars_and_bases.push(
+            pcs_info.next_eval_point * pcs_info.u,
+            pcs_info.shifted_opening_proof.0,
+        );
+        scalars_and_bases.push(-pcs_info.eval, *generator_g);
+        scalars_and_bases.push(blinding_factor, *beta_g);
+        let inner2 = scalars_and_bases.g1_multi_scalar_mul();
+
+        Ok((inner1, inner2))
+    }
 }
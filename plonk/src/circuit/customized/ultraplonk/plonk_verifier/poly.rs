@@ -177,7 +177,11 @@ where
 /// - v_i: A sequence of values, where v_i = g^i / n
 ///
 /// We then compute L_{i,H}(z) as `L_{i,H}(z) = Z_H(z) * v_i / (z - g^i)`
-/// The public input polynomial evaluation for the merged circuit is:
+/// The public input polynomial evaluation is:
+///
+/// \sum_{i=0..l} L_{i,H}(z) * pub_input[i].
+///
+/// For merged circuits, the evaluation is:
 ///
 /// \sum_{i=0..l/2} L_{i,H}(z) * pub_input[i] +
 /// \sum_{i=0..l/2} L_{n-i,H}(z) * pub_input[l/2+i]
@@ -194,13 +198,13 @@ where
     E: PairingEngine<Fq = F>,
     F: PrimeField,
 {
-    // the circuit is already merged
-    if !circuit_is_merged {
-        return Err(PlonkError::InvalidParameters(
-            "Circuit should already been merged".to_string(),
+    if pub_inputs_fp_elem_var.is_empty() {
+        return Ok(FpElemVar::zero(
+            circuit,
+            non_native_field_info.m,
+            non_native_field_info.two_power_m,
         ));
     }
-    let len = pub_inputs_fp_elem_var.len() >> 1;
 
     // constants
     let zeta = field_switching::<_, E::Fr>(&zeta_fp_elem_var.witness(circuit)?);
@@ -212,15 +216,21 @@ where
         .map(|x| domain.element(x) / E::Fr::from(domain_size as u64))
         .collect();
 
+    // the domain positions of the public inputs: the second half of the inputs
+    // of a merged circuit is at positions n-1, n-2, ...
+    let positions: Vec<usize> = if circuit_is_merged {
+        let len = pub_inputs_fp_elem_var.len() >> 1;
+        (0..len)
+            .chain((0..pub_inputs_fp_elem_var.len() - len).map(|i| domain_size - i - 1))
+            .collect()
+    } else {
+        (0..pub_inputs_fp_elem_var.len()).collect()
+    };
+
     // compute L_{i,H}(zeta) = Z_H(zeta) * v_i / (zeta - g^i)
     // where Z_H(z) is the vanishing evaluation
-    // compute for both i in [0, len) and [domain_size-len, domain_size)
     let mut lagrange_eval_fp_elem_var: Vec<FpElemVar<F>> = Vec::new();
-    let range = (0..len)
-        .into_iter()
-        .chain((domain_size - len..domain_size).into_iter());
-
-    for i in range {
+    for i in positions {
         // compute L_{i,H}(zeta) and related values in the clear
         let v_i_fp_elem = FpElem::<F>::new(
             &field_switching(&v_i[i]),
@@ -272,23 +282,18 @@ where
         lagrange_eval_fp_elem_var.push(eval_i_fp_elem_var);
     }
 
-    // \sum_{i=0..l/2} L_{i,H}(z) * pub_input[i] + \sum_{i=0..l/2} L_{n-i,H}(z)
-    // * pub_input[l/2+i]
-    let mut res_i_fp_elem_var = Vec::new();
-    for i in 0..len {
-        let first_term = circuit.mod_mul(
-            &lagrange_eval_fp_elem_var[i],
-            &pub_inputs_fp_elem_var[i],
-            &non_native_field_info.modulus_fp_elem,
-        )?;
-        let second_term = circuit.mod_mul(
-            &lagrange_eval_fp_elem_var[(len << 1) - i - 1],
-            &pub_inputs_fp_elem_var[len + i],
-            &non_native_field_info.modulus_fp_elem,
-        )?;
-        res_i_fp_elem_var.push(first_term);
-        res_i_fp_elem_var.push(second_term);
-    }
+    // \sum_i L_{position_i,H}(z) * pub_input[i]
+    let res_i_fp_elem_var = lagrange_eval_fp_elem_var
+        .iter()
+        .zip(pub_inputs_fp_elem_var.iter())
+        .map(|(lagrange_eval, pub_input)| {
+            circuit.mod_mul(
+                lagrange_eval,
+                pub_input,
+                &non_native_field_info.modulus_fp_elem,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let res = circuit.mod_add_vec(&res_i_fp_elem_var, &non_native_field_info.modulus_fp_elem)?;
 
     Ok(res)
@@ -340,13 +345,21 @@ where
     let mut alpha_bases_elem_var = alpha_bases.iter();
     let mut r_0_components = Vec::new();
 
-    // making sure the public inputs are the same for all instances
+    // making sure the public inputs and their layout are the same for all
+    // instances
     let pi = public_inputs[0];
     for &pi_i in public_inputs.iter().skip(1) {
         if pi != pi_i {
             return Err(PlonkError::PublicInputsDoNotMatch);
         }
     }
+    let is_merged = verify_keys[0].is_merged;
+    if verify_keys.iter().any(|vk| vk.is_merged != is_merged) {
+        return Err(ParameterError(
+            "the verification keys should be all merged or all unmerged".to_string(),
+        )
+        .into());
+    }
 
     // compute public inputs
     let pi_fp_elem_var = evaluate_pi_poly_circuit::<E, F>(
@@ -355,7 +368,7 @@ where
         pi,
         &zeta_fp_elem_var,
         &evals[1],
-        true,
+        is_merged,
         non_native_field_info,
     )?;
     let pi_fr = field_switching::<_, E::Fr>(&pi_fp_elem_var.witness(circuit)?);
@@ -10,7 +10,7 @@ use crate::{
     errors::{PlonkError, SnarkError::ParameterError},
 };
 use ark_ff::{BigInteger, PrimeField};
-use ark_std::{string::ToString, vec::Vec};
+use ark_std::{format, string::ToString, vec::Vec};
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Constrain a variable to be within the [0, 2^{bit_len}) range
@@ -55,6 +55,57 @@ impl<F: PrimeField> PlonkCircuit<F> {
         Ok(())
     }
 
+    /// Constrain a variable to be within the [0, 2^`num_bits`) range, for any
+    /// positive `num_bits`.
+    ///
+    /// With lookup, the variable is decomposed into `range_bit_len`-bit limbs
+    /// checked against the range table, and a last limb of `r` bits when
+    /// `range_bit_len` doesn't divide `num_bits`: both that limb and its
+    /// product with 2^(`range_bit_len` - `r`) are looked up, which holds only
+    /// if the limb is smaller than 2^`r`. Without lookup, the variable is
+    /// decomposed into bits.
+    /// Return error if the variable is invalid, or if `num_bits` is zero or
+    /// larger than the field bit size.
+    pub fn enforce_in_range(&mut self, a: Variable, num_bits: usize) -> Result<(), PlonkError> {
+        self.check_var_bound(a)?;
+        if num_bits == 0 {
+            return Err(ParameterError("num_bits cannot be zero".to_string()).into());
+        }
+        if num_bits > F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "Maximum field bit size: {}, requested range upper bound bit len: {}",
+                F::size_in_bits(),
+                num_bits
+            ))
+            .into());
+        }
+        if !self.support_lookup() {
+            self.range_gate_internal(a, num_bits)?;
+            return Ok(());
+        }
+        let range_bit_len = self.range_bit_len()?;
+        let partial_bit_len = num_bits % range_bit_len;
+        if partial_bit_len == 0 {
+            return self.range_gate_with_lookup(a, num_bits);
+        }
+
+        let len = num_bits / range_bit_len + 1;
+        let reprs_le = decompose_le(self.witness(a)?, len, range_bit_len);
+        let reprs_le_vars: Vec<Variable> = reprs_le
+            .iter()
+            .map(|&val| self.create_variable(val))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        for &var in reprs_le_vars.iter() {
+            self.add_range_check_variable(var)?;
+        }
+        // the partial limb shifted to the top of the range
+        let shift = F::from(1u64 << (range_bit_len - partial_bit_len));
+        let shifted_var = self.mul_constant(reprs_le_vars[len - 1], &shift)?;
+        self.add_range_check_variable(shifted_var)?;
+
+        self.decompose_vars_gate(reprs_le_vars, a, F::from(self.range_size()? as u64))
+    }
+
     /// The number of range blocks, i.e., the minimal integer such that
     /// RANGE_SIZE^NUM_RANGES >= p,
     #[inline]
@@ -172,4 +223,71 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_enforce_in_range() -> Result<(), PlonkError> {
+        test_enforce_in_range_helper::<FqEd254>()?;
+        test_enforce_in_range_helper::<FqEd377>()?;
+        test_enforce_in_range_helper::<FqEd381>()?;
+        test_enforce_in_range_helper::<Fq377>()
+    }
+
+    fn test_enforce_in_range_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        for &num_bits in [1, 3, 8, 12, 20, 31, 64, 100].iter() {
+            let max = F::from(2u64).pow([num_bits as u64]) - F::one();
+            for lookup in [false, true] {
+                let new_circuit = || -> PlonkCircuit<F> {
+                    if lookup {
+                        PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST)
+                    } else {
+                        PlonkCircuit::new_turbo_plonk()
+                    }
+                };
+
+                // Good path: 0 and 2^num_bits - 1
+                let mut circuit = new_circuit();
+                for val in [F::zero(), max] {
+                    let a = circuit.create_variable(val)?;
+                    circuit.enforce_in_range(a, num_bits)?;
+                }
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+                // Error path: 2^num_bits. Without lookup, the bit decomposition
+                // rejects the witness early.
+                let mut circuit = new_circuit();
+                let a = circuit.create_variable(max + F::one())?;
+                let res = circuit.enforce_in_range(a, num_bits);
+                assert!(res.is_err() || circuit.check_circuit_satisfiability(&[]).is_err());
+
+                // if mess up the witness value, should fail
+                let mut circuit = new_circuit();
+                let a = circuit.create_variable(max)?;
+                circuit.enforce_in_range(a, num_bits)?;
+                *circuit.witness_mut(a) = max + F::one();
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+
+        // the partial limb costs a single extra gate
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let a = circuit.create_variable(F::from(1u64 << 19))?;
+        let num_gates = circuit.num_gates();
+        circuit.enforce_in_range(a, 20)?;
+        let cost_20 = circuit.num_gates() - num_gates;
+        let num_gates = circuit.num_gates();
+        circuit.range_gate_with_lookup(a, 24)?;
+        assert_eq!(circuit.num_gates() - num_gates + 1, cost_20);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Error paths
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let zero_var = circuit.zero();
+        assert!(circuit.enforce_in_range(zero_var, 0).is_err());
+        assert!(circuit
+            .enforce_in_range(zero_var, F::size_in_bits() + 1)
+            .is_err());
+        assert!(circuit.enforce_in_range(circuit.num_vars(), 20).is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,146 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Gadget for tagged unions, i.e. a discriminant selecting which payload
+//! constraints apply.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{string::ToString, vec::Vec};
+
+/// A constraint on the payload of a variant. It returns a bool variable that
+/// is one iff the payload satisfies the constraint.
+pub type VariantConstraint<'a, F> =
+    &'a dyn Fn(&mut PlonkCircuit<F>) -> Result<Variable, PlonkError>;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `disc_var` to be one of the discriminant values in
+    /// `variants`, and enforce the payload constraint paired with the
+    /// matching discriminant. Constraints of the other variants are built
+    /// but not enforced.
+    /// Return error if variables are invalid, or if `variants` is empty or
+    /// contains duplicated discriminants.
+    pub fn match_variant(
+        &mut self,
+        disc_var: Variable,
+        variants: &[(F, VariantConstraint<F>)],
+    ) -> Result<(), PlonkError> {
+        self.check_var_bound(disc_var)?;
+        if variants.is_empty() {
+            return Err(CircuitError::ParameterError("empty variants".to_string()).into());
+        }
+        for (i, (value, _)) in variants.iter().enumerate() {
+            if variants[..i].iter().any(|(other, _)| other == value) {
+                return Err(
+                    CircuitError::ParameterError("duplicated discriminants".to_string()).into(),
+                );
+            }
+        }
+
+        let mut selectors = Vec::with_capacity(variants.len());
+        for (value, constraint) in variants.iter() {
+            let value_var = self.create_constant_variable(*value)?;
+            let selected = self.is_equal(disc_var, value_var)?;
+            let satisfied = constraint(self)?;
+            // selected => satisfied, i.e. selected * satisfied = selected
+            self.mul_gate(selected, satisfied, selected)?;
+            selectors.push(selected);
+        }
+        // The discriminant matches one of the variants.
+        let num_selected = self.sum(&selectors)?;
+        self.constant_gate(num_selected, F::one())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::test::test_variable_independence_for_circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_match_variant() -> Result<(), PlonkError> {
+        test_match_variant_helper::<FqEd254>()?;
+        test_match_variant_helper::<FqEd377>()?;
+        test_match_variant_helper::<FqEd381>()?;
+        test_match_variant_helper::<Fq377>()
+    }
+
+    fn test_match_variant_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // variant 0: payload in [0, 2^4)
+        // variant 1: payload in [0, 2^8)
+        // variant 5: payload is zero
+        let (circuit, ..) = build_match_variant_circuit(F::zero(), F::from(15u32))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, ..) = build_match_variant_circuit(F::zero(), F::from(16u32))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, ..) = build_match_variant_circuit(F::one(), F::from(255u32))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, ..) = build_match_variant_circuit(F::one(), F::from(256u32))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, ..) = build_match_variant_circuit(F::from(5u32), F::zero())?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, ..) = build_match_variant_circuit(F::from(5u32), F::one())?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // disallowed discriminant
+        let (circuit, ..) = build_match_variant_circuit(F::from(2u32), F::zero())?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let (mut circuit, disc_var, _) = build_match_variant_circuit(F::one(), F::from(100u32))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(disc_var) = F::zero();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let disc_var = circuit.create_variable(F::zero())?;
+        assert!(circuit.match_variant(disc_var, &[]).is_err());
+        let always: VariantConstraint<F> = &|cs| Ok(cs.one());
+        assert!(circuit
+            .match_variant(disc_var, &[(F::zero(), always), (F::zero(), always)])
+            .is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .match_variant(circuit.num_vars(), &[(F::zero(), always)])
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let (mut circuit_1, ..) = build_match_variant_circuit(F::zero(), F::from(3u32))?;
+        let (mut circuit_2, ..) = build_match_variant_circuit(F::one(), F::from(200u32))?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_match_variant_circuit<F: PrimeField>(
+        disc: F,
+        payload: F,
+    ) -> Result<(PlonkCircuit<F>, Variable, Variable), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let disc_var = circuit.create_variable(disc)?;
+        let payload_var = circuit.create_variable(payload)?;
+        let small: VariantConstraint<F> = &|cs| cs.is_in_range(payload_var, 4);
+        let byte: VariantConstraint<F> = &|cs| cs.is_in_range(payload_var, 8);
+        let zero: VariantConstraint<F> = &|cs| cs.is_zero(payload_var);
+        circuit.match_variant(
+            disc_var,
+            &[(F::zero(), small), (F::one(), byte), (F::from(5u32), zero)],
+        )?;
+        Ok((circuit, disc_var, payload_var))
+    }
+}
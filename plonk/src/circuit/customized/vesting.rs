@@ -0,0 +1,197 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Linear vesting schedule gadget.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+
+/// The bit length of amounts and times.
+const VESTING_BIT_LEN: usize = 64;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `unlocked` to be the amount of `total` vested after `elapsed`
+    /// units of time of a linear schedule over `duration`, with a `cliff`:
+    /// zero if `elapsed < cliff`, and `total * min(elapsed, duration) /
+    /// duration`, rounded down, otherwise.
+    ///
+    /// All values are constrained to be 64-bit integers, and `duration` to be
+    /// positive.
+    /// Return error if variables are invalid, or if the field is too small to
+    /// hold the product of two such integers.
+    pub fn enforce_vesting(
+        &mut self,
+        total: Variable,
+        elapsed: Variable,
+        duration: Variable,
+        cliff: Variable,
+        unlocked: Variable,
+    ) -> Result<(), PlonkError> {
+        if F::size_in_bits() <= 2 * VESTING_BIT_LEN + 1 {
+            return Err(CircuitError::ParameterError(format!(
+                "the field of {} bits is too small for {}-bit products",
+                F::size_in_bits(),
+                2 * VESTING_BIT_LEN
+            ))
+            .into());
+        }
+        for &var in [total, elapsed, duration, cliff, unlocked].iter() {
+            self.check_var_bound(var)?;
+            self.range_gate(var, VESTING_BIT_LEN)?;
+        }
+        self.non_zero_gate(duration)?;
+
+        // clamped = min(elapsed, duration)
+        let before_end = self.is_leq(elapsed, duration, VESTING_BIT_LEN)?;
+        let clamped = self.conditional_select(before_end, duration, elapsed)?;
+        let product = self.mul(total, clamped)?;
+
+        // product = vested * duration + remainder, with 0 <= remainder < duration
+        let product_val = to_u128(self.witness(product)?);
+        let duration_val = to_u128(self.witness(duration)?);
+        let (vested_val, remainder_val) = match duration_val {
+            0 => (0, 0),
+            _ => (product_val / duration_val, product_val % duration_val),
+        };
+        let vested = self.create_variable(F::from(vested_val))?;
+        let remainder = self.create_variable(F::from(remainder_val))?;
+        self.range_gate(vested, VESTING_BIT_LEN)?;
+        self.range_gate(remainder, VESTING_BIT_LEN)?;
+        let one = self.one();
+        self.mul_add_gate(
+            &[vested, duration, remainder, one, product],
+            &[F::one(), F::one()],
+        )?;
+        let gap = self.sub(duration, remainder)?;
+        let gap_minus_one = self.add_constant(gap, &-F::one())?;
+        self.range_gate(gap_minus_one, VESTING_BIT_LEN)?;
+
+        // nothing is unlocked before the cliff
+        let after_cliff = self.is_leq(cliff, elapsed, VESTING_BIT_LEN)?;
+        let zero = self.zero();
+        let expected = self.conditional_select(after_cliff, zero, vested)?;
+        self.equal_gate(unlocked, expected)
+    }
+}
+
+// The value of a field element below 2^128, truncated otherwise.
+fn to_u128<F: PrimeField>(val: F) -> u128 {
+    let limbs = val.into_repr();
+    let limbs = limbs.as_ref();
+    limbs[0] as u128 + ((limbs.get(1).copied().unwrap_or(0) as u128) << 64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{circuit::customized::test::test_variable_independence_for_circuit, goldilocks};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_enforce_vesting() -> Result<(), PlonkError> {
+        test_enforce_vesting_helper::<FqEd254>()?;
+        test_enforce_vesting_helper::<FqEd377>()?;
+        test_enforce_vesting_helper::<FqEd381>()?;
+        test_enforce_vesting_helper::<Fq377>()
+    }
+
+    fn test_enforce_vesting_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // before the cliff
+        for &elapsed in [0, 10, 24].iter() {
+            let circuit = build_vesting_circuit::<F>(1000, elapsed, 100, 25, 0)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+        let circuit = build_vesting_circuit::<F>(1000, 24, 100, 25, 240)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // mid-vesting
+        let circuit = build_vesting_circuit::<F>(1000, 25, 100, 25, 250)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_vesting_circuit::<F>(1000, 33, 100, 25, 330)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // rounded down: 1001 * 37 / 100 = 370.37
+        let circuit = build_vesting_circuit::<F>(1001, 37, 100, 25, 370)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let circuit = build_vesting_circuit::<F>(1001, 37, 100, 25, 371)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_vesting_circuit::<F>(1001, 37, 100, 25, 369)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_vesting_circuit::<F>(1000, 33, 100, 25, 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // large amounts
+        let circuit = build_vesting_circuit::<F>(u64::MAX, 1 << 40, 1 << 41, 0, u64::MAX / 2)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // fully vested
+        for &elapsed in [100, 101, 1 << 40].iter() {
+            let circuit = build_vesting_circuit::<F>(1000, elapsed, 100, 25, 1000)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+        let circuit = build_vesting_circuit::<F>(1000, 150, 100, 25, 1500)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // cliff after the end of the schedule
+        let circuit = build_vesting_circuit::<F>(1000, 150, 100, 200, 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // zero duration
+        let circuit = build_vesting_circuit::<F>(1000, 0, 0, 0, 0)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // if mess up the wire value, should fail
+        let mut circuit = build_vesting_circuit::<F>(1000, 33, 100, 25, 330)?;
+        // elapsed
+        *circuit.witness_mut(3) = F::from(34u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong parameters
+        let mut circuit: PlonkCircuit<goldilocks::Fq> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_vesting(zero, zero, zero, zero, zero)
+            .is_err());
+        // Check variable out of bound error.
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let zero = circuit.zero();
+        assert!(circuit
+            .enforce_vesting(zero, zero, circuit.num_vars(), zero, zero)
+            .is_err());
+
+        // build two fixed circuits with different variable assignments, checking that
+        // the arithmetized extended permutation polynomial is variable
+        // independent
+        let mut circuit_1 = build_vesting_circuit::<F>(1000, 33, 100, 25, 330)?;
+        let mut circuit_2 = build_vesting_circuit::<F>(7, 1, 3, 5, 0)?;
+        circuit_1.finalize_for_arithmetization()?;
+        circuit_2.finalize_for_arithmetization()?;
+        test_variable_independence_for_circuit(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+
+    fn build_vesting_circuit<F: PrimeField>(
+        total: u64,
+        elapsed: u64,
+        duration: u64,
+        cliff: u64,
+        unlocked: u64,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let total = circuit.create_variable(F::from(total))?;
+        let elapsed = circuit.create_variable(F::from(elapsed))?;
+        let duration = circuit.create_variable(F::from(duration))?;
+        let cliff = circuit.create_variable(F::from(cliff))?;
+        let unlocked = circuit.create_variable(F::from(unlocked))?;
+        circuit.enforce_vesting(total, elapsed, duration, cliff, unlocked)?;
+        Ok(circuit)
+    }
+}
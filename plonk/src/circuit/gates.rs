@@ -6,14 +6,20 @@
 
 //! Implementations of various Gates.
 use ark_ff::Field;
-use ark_std::boxed::Box;
+use ark_serialize::*;
+use ark_std::{boxed::Box, format, string::ToString, vec::Vec};
 use core::fmt;
 use downcast_rs::Downcast;
 
-use crate::constants::{GATE_WIDTH, N_MUL_SELECTORS};
+use crate::{
+    constants::{CUSTOM_GATE_MAX_DEGREE, GATE_WIDTH, N_MUL_SELECTORS},
+    errors::{CircuitError, PlonkError},
+};
 
 /// Describes a gate with getter for all selectors configuration
-pub trait Gate<F: Field>: Downcast + GateClone<F> {
+///
+/// Gates are `Send + Sync` so that circuits can be shared across threads.
+pub trait Gate<F: Field>: Downcast + GateClone<F> + Send + Sync {
     /// Get the name of a gate.
     fn name(&self) -> &'static str;
     /// Selectors for linear combination.
@@ -44,6 +50,10 @@ pub trait Gate<F: Field>: Downcast + GateClone<F> {
     fn q_lookup(&self) -> F {
         F::zero()
     }
+    /// The index of the custom gate whose selector is enabled, if any.
+    fn q_custom(&self) -> Option<usize> {
+        None
+    }
 }
 impl_downcast!(Gate<F> where F: Field);
 
@@ -279,3 +289,116 @@ impl<F: Field> Gate<F> for FifthRootGate {
         F::one()
     }
 }
+
+/// A gate given by the values of its selectors, e.g. a gate of a circuit
+/// imported from its intermediate representation.
+#[derive(Debug, Clone)]
+pub struct SelectorGate<F: Field> {
+    pub(crate) q_lc: [F; GATE_WIDTH],
+    pub(crate) q_mul: [F; N_MUL_SELECTORS],
+    pub(crate) q_hash: [F; GATE_WIDTH],
+    pub(crate) q_o: F,
+    pub(crate) q_c: F,
+    pub(crate) q_ecc: F,
+    pub(crate) q_lookup: F,
+}
+
+impl<F: Field> Gate<F> for SelectorGate<F> {
+    fn name(&self) -> &'static str {
+        "Selector Gate"
+    }
+    fn q_lc(&self) -> [F; GATE_WIDTH] {
+        self.q_lc
+    }
+    fn q_mul(&self) -> [F; N_MUL_SELECTORS] {
+        self.q_mul
+    }
+    fn q_hash(&self) -> [F; GATE_WIDTH] {
+        self.q_hash
+    }
+    fn q_o(&self) -> F {
+        self.q_o
+    }
+    fn q_c(&self) -> F {
+        self.q_c
+    }
+    fn q_ecc(&self) -> F {
+        self.q_ecc
+    }
+    fn q_lookup(&self) -> F {
+        self.q_lookup
+    }
+}
+
+/// The specification of a user-defined gate of TurboPlonk circuits: a
+/// polynomial identity `sum_j c_j * w_0^e_j0 * ... * w_4^e_j4 = 0` over the
+/// wires of the gate, enabled by its own selector column.
+///
+/// Every term has a degree of at most [`CUSTOM_GATE_MAX_DEGREE`], the largest
+/// wire degree of the built-in gates, so that a custom gate does not increase
+/// the degree of the quotient polynomial nor the SRS size.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CustomGateSpec<F: Field> {
+    // the coefficients of the terms
+    pub(crate) coeffs: Vec<F>,
+    // the wire exponents of the terms
+    pub(crate) exponents: Vec<Vec<u8>>,
+}
+
+impl<F: Field> CustomGateSpec<F> {
+    /// Create the specification of the identity made of `terms`, each given
+    /// by its coefficient and the exponents of the wires.
+    /// Return error if there is no term, or if a term has a degree larger
+    /// than [`CUSTOM_GATE_MAX_DEGREE`].
+    pub fn new(terms: &[(F, [u8; GATE_WIDTH + 1])]) -> Result<Self, PlonkError> {
+        if terms.is_empty() {
+            return Err(
+                CircuitError::ParameterError("a custom gate needs a term".to_string()).into(),
+            );
+        }
+        for (_, exponents) in terms.iter() {
+            let degree: usize = exponents.iter().map(|&e| e as usize).sum();
+            if degree > CUSTOM_GATE_MAX_DEGREE {
+                return Err(CircuitError::ParameterError(format!(
+                    "the custom gate term degree {} exceeds the maximum degree {}",
+                    degree, CUSTOM_GATE_MAX_DEGREE
+                ))
+                .into());
+            }
+        }
+        Ok(Self {
+            coeffs: terms.iter().map(|(coeff, _)| *coeff).collect(),
+            exponents: terms
+                .iter()
+                .map(|(_, exponents)| exponents.to_vec())
+                .collect(),
+        })
+    }
+
+    /// Evaluate the identity on the wire values `wires`.
+    pub fn evaluate(&self, wires: &[F]) -> F {
+        self.coeffs
+            .iter()
+            .zip(self.exponents.iter())
+            .fold(F::zero(), |sum, (coeff, exponents)| {
+                sum + wires
+                    .iter()
+                    .zip(exponents.iter())
+                    .fold(*coeff, |acc, (w, &e)| acc * w.pow(&[e as u64]))
+            })
+    }
+}
+
+/// A gate enforcing the identity of the `i`-th custom gate registered in the
+/// circuit.
+#[derive(Debug, Clone)]
+pub struct CustomGate(pub(crate) usize);
+
+impl<F: Field> Gate<F> for CustomGate {
+    fn name(&self) -> &'static str {
+        "Custom Gate"
+    }
+    fn q_custom(&self) -> Option<usize> {
+        Some(self.0)
+    }
+}
@@ -14,7 +14,8 @@ pub mod basic;
 pub mod customized;
 pub mod gates;
 
-pub use basic::PlonkCircuit;
+pub use basic::{CircuitStats, PlonkCircuit};
+use gates::CustomGateSpec;
 
 /// An index to one of the witness values.
 pub type Variable = usize;
@@ -42,7 +43,8 @@ pub trait Circuit<F: Field> {
     /// wires, and 1 type of lookup wires.
     fn num_wire_types(&self) -> usize;
 
-    /// The list of public input values.
+    /// The list of public input values, in the order in which the variables
+    /// were made public.
     fn public_input(&self) -> Result<Vec<F>, PlonkError>;
 
     /// Check circuit satisfiability against a public input.
@@ -66,7 +68,13 @@ pub trait Circuit<F: Field> {
     /// Add a public input variable; return the index of the variable.
     fn create_public_variable(&mut self, val: F) -> Result<Variable, PlonkError>;
 
-    /// Set a variable to a public variable
+    /// Set a variable to a public variable.
+    ///
+    /// Any variable can be made public, at any point of the construction of
+    /// the circuit: its public input gate is moved to the first slots of the
+    /// evaluation domain when the circuit is finalized, preserving the order
+    /// of the calls, which is the order of [`Self::public_input`] absorbed
+    /// by the transcripts.
     fn set_variable_public(&mut self, var: Variable) -> Result<(), PlonkError>;
 
     /// Return a default variable with value zero.
@@ -165,11 +173,22 @@ pub trait Arithmetization<F: FftField>: Circuit<F> {
     /// Return an error if the circuit has not been finalized yet.
     fn compute_wire_polynomials(&self) -> Result<Vec<DensePolynomial<F>>, PlonkError>;
 
+    /// Compute and return the list of wiring witness values over the
+    /// evaluation domain, i.e. the evaluations of the wiring witness
+    /// polynomials. Return an error if the circuit has not been finalized yet.
+    fn compute_wire_evaluations(&self) -> Result<Vec<Vec<F>>, PlonkError>;
+
     /// Compute and return the public input polynomial.
     /// Return an error if the circuit has not been finalized yet.
     /// The IO gates of the circuit are guaranteed to be in the front.
     fn compute_pub_input_polynomial(&self) -> Result<DensePolynomial<F>, PlonkError>;
 
+    /// The registered custom gates, in the order of their selectors which
+    /// follow the other selectors.
+    fn custom_gates(&self) -> &[CustomGateSpec<F>] {
+        &[]
+    }
+
     /// Plookup-related methods
     /// Return default errors if the constraint system does not support lookup
     /// gates.
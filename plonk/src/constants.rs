@@ -20,6 +20,8 @@ pub const GATE_WIDTH: usize = 4;
 pub(crate) const N_MUL_SELECTORS: usize = 2;
 /// The number of TurboPlonk selectors.
 pub(crate) const N_TURBO_PLONK_SELECTORS: usize = 13;
+/// The maximum degree of the terms of custom gates.
+pub const CUSTOM_GATE_MAX_DEGREE: usize = 5;
 
 /// Proof-system-related constants.
 ///
@@ -27,6 +29,32 @@ pub(crate) const N_TURBO_PLONK_SELECTORS: usize = 13;
 /// initialization
 pub(crate) const EXTRA_TRANSCRIPT_MSG_LABEL: &[u8] = b"extra info";
 
+/// label prefixing a randomness beacon value in the extra data field
+pub(crate) const BEACON_TRANSCRIPT_MSG_LABEL: &[u8] = b"randomness beacon";
+
+/// magic bytes starting the SRS files written by
+/// `PlonkKzgSnark::universal_setup_streaming`
+pub(crate) const SRS_FILE_MAGIC: &[u8] = b"JFSRS";
+
+/// version of the format of SRS files
+pub(crate) const SRS_FILE_VERSION: u8 = 1;
+
+/// endianness flag of SRS files, whose points are encoded in little-endian
+pub(crate) const SRS_FILE_LITTLE_ENDIAN: u8 = 0;
+
+/// number of powers computed at once when streaming the SRS
+pub(crate) const SRS_CHUNK_SIZE: usize = 1 << 16;
+
+/// magic bytes starting the proofs serialized with a header
+pub(crate) const PROOF_HEADER_MAGIC: &[u8] = b"JFPRF";
+
+/// version of the format of proofs serialized with a header
+pub(crate) const PROOF_HEADER_VERSION: u8 = 1;
+
+/// domain separator of the fingerprints of verifying keys, with the version
+/// of their format
+pub(crate) const VK_FINGERPRINT_DOMAIN_SEP: &[u8] = b"JFVKFP1";
+
 /// Compute the ratio between the quotient polynomial domain size and
 /// the vanishing polynomial domain size
 #[inline]
@@ -6,7 +6,8 @@
 
 //! Error module.
 
-use ark_std::string::String;
+use ark_ff::Field;
+use ark_std::{fmt, string::String, vec::Vec};
 use displaydoc::Display;
 
 /// A `enum` specifying the possible failure modes of the Plonk.
@@ -14,6 +15,13 @@ use displaydoc::Display;
 pub enum PlonkError {
     /// The index is too large for the universal public parameters
     IndexTooLarge,
+    /// The SRS supports degree {provided}, but the circuit requires degree {required}
+    SrsTooSmall {
+        /// The degree required by the circuit
+        required: usize,
+        /// The maximal degree supported by the SRS
+        provided: usize,
+    },
     /// Failed to create domain
     DomainCreationError,
     /// Failed to get array value by index
@@ -81,6 +89,8 @@ pub enum SnarkError {
     ParameterError(String),
     /// The SNARK does not support lookup
     SnarkLookupUnsupported,
+    /// The number of public inputs {0} exceeds the verification budget {1}
+    TooManyPublicInputs(usize, usize),
 }
 
 #[cfg(feature = "std")]
@@ -133,3 +143,71 @@ impl From<CircuitError> for PlonkError {
         Self::CircuitError(e)
     }
 }
+
+/// A constraint of a circuit violated by its witness, with the values
+/// involved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GateError<F: Field> {
+    /// The public input length does not match the number of inputs.
+    PubInputLenMismatch(usize, usize),
+    /// The equation of an algebraic gate does not hold.
+    Algebraic {
+        /// The name of the gate.
+        gate: &'static str,
+        /// The values of the wires of the gate.
+        wire_values: Vec<F>,
+        /// The public input value of the gate.
+        pub_input: F,
+        /// The output expected from the input wires and the selectors.
+        expected_gate_output: F,
+        /// The output wire value weighted by its selector.
+        gate_output: F,
+    },
+    /// The value of a range gate is out of the range.
+    Range {
+        /// The value of the range gate.
+        value: F,
+        /// The range size.
+        range_size: usize,
+    },
+    /// The key-value tuple of a lookup gate is not in the lookup table.
+    Lookup {
+        /// The key of the lookup gate.
+        key: F,
+        /// The first value of the lookup gate.
+        val0: F,
+        /// The second value of the lookup gate.
+        val1: F,
+    },
+}
+
+impl<F: Field> fmt::Display for GateError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PubInputLenMismatch(len, num_inputs) => write!(
+                f,
+                "Public input length {} doesn't match num_inputs = {}",
+                len, num_inputs
+            ),
+            Self::Algebraic {
+                gate,
+                wire_values,
+                pub_input,
+                expected_gate_output,
+                gate_output,
+            } => write!(
+                f,
+                "gate: {}, wire values: {:?}, pub_input: {}, expected_gate_output: {}, gate_output: {}",
+                gate, wire_values, pub_input, expected_gate_output, gate_output
+            ),
+            Self::Range { value, range_size } => {
+                write!(f, "Range gate failed: {} >= {}", value, range_size)
+            },
+            Self::Lookup { key, val0, val1 } => write!(
+                f,
+                "Lookup gate failed: ({}, {}, {}) not in the table",
+                key, val0, val1
+            ),
+        }
+    }
+}
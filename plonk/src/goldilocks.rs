@@ -0,0 +1,152 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! The 64-bit Goldilocks prime field, of modulus `p = 2^64 - 2^32 + 1`.
+//!
+//! Its multiplicative group has 2-adicity 32, hence circuits over it can be
+//! built, checked and arithmetized as over any other FFT-friendly field.
+//! As no pairing-friendly curve has a scalar field of this size, the field
+//! cannot be used with the KZG-based [`crate::proof_system::PlonkKzgSnark`].
+
+use ark_ff::{
+    biginteger::BigInteger64 as BigInteger,
+    fields::{FftParameters, Fp64, Fp64Parameters, FpParameters},
+};
+
+/// The Goldilocks field.
+pub type Fq = Fp64<FqParameters>;
+
+/// Parameters of the Goldilocks field.
+pub struct FqParameters;
+
+impl Fp64Parameters for FqParameters {}
+
+impl FftParameters for FqParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 32;
+
+    /// 7^((p - 1) / 2^32), in Montgomery form.
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([0xda58878b0d514e98]);
+}
+
+impl FpParameters for FqParameters {
+    /// p = 2^64 - 2^32 + 1
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([0xffffffff00000001]);
+
+    const MODULUS_BITS: u32 = 64;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 0;
+
+    /// R = 2^64 mod p
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([0xffffffff]);
+
+    /// R2 = 2^128 mod p
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([0xfffffffe00000001]);
+
+    /// INV = -p^(-1) mod 2^64
+    const INV: u64 = 0xfffffffeffffffff;
+
+    /// GENERATOR = 7, in Montgomery form.
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([0x6fffffff9]);
+
+    /// (p - 1) / 2
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([0x7fffffff80000000]);
+
+    /// T = (p - 1) / 2^32
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([0xffffffff]);
+
+    /// (T - 1) / 2
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([0x7fffffff]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::{basic::test::test_arithmetization_for_circuit, Circuit, PlonkCircuit},
+        errors::PlonkError,
+    };
+    use ark_ff::{FftField, Field, One, PrimeField, SquareRootField};
+
+    #[test]
+    fn test_goldilocks_field() {
+        // p - 1 = -1
+        assert_eq!(Fq::from(0xffffffff00000000u64), -Fq::one());
+        assert_eq!(
+            Fq::from(1u64 << 32) * Fq::from(1u64 << 32),
+            Fq::from(0xffffffffu64)
+        );
+        assert_eq!(
+            Fq::from(7u64).inverse().unwrap() * Fq::from(7u64),
+            Fq::one()
+        );
+        assert_eq!(Fq::multiplicative_generator(), Fq::from(7u64));
+
+        let root = Fq::two_adic_root_of_unity();
+        assert_eq!(
+            root.pow([1u64 << 31]),
+            -Fq::one(),
+            "the root of unity should have order 2^32"
+        );
+        assert_eq!(
+            Fq::multiplicative_generator().pow([0xffffffffu64]),
+            root,
+            "the root of unity should be 7^((p - 1) / 2^32)"
+        );
+        assert_eq!(
+            Fq::from(4u64).sqrt().map(|x| x.square()),
+            Some(Fq::from(4u64))
+        );
+        assert_eq!(Fq::from(123u64).into_repr(), BigInteger::from(123u64));
+    }
+
+    #[test]
+    fn test_goldilocks_circuit() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<Fq> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(Fq::from(0xfffffffeu64))?;
+        let b = circuit.create_variable(Fq::from(0xffffffffu64))?;
+        let c = circuit.mul(a, b)?;
+        let d = circuit.add_constant(c, &Fq::from(0x1fffffffdu64))?;
+        // (2^32 - 2)(2^32 - 1) + 2^33 - 3 = 2^64 - 2^32 - 1 = -2 mod p
+        let e = circuit.create_public_variable(-Fq::from(2u64))?;
+        circuit.equal_gate(d, e)?;
+        circuit.range_gate(a, 32)?;
+        circuit.range_gate(c, 64)?;
+        let a_bits = circuit.unpack(a, 32)?;
+        let is_zero = circuit.is_zero(a_bits[0])?;
+        circuit.enforce_true(is_zero)?;
+
+        let pub_input = [Fq::from(0xfffffffeffffffffu64)];
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+        assert!(circuit
+            .check_circuit_satisfiability(&[Fq::from(0xffffffffu64)])
+            .is_err());
+        // values beyond the range
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(a) = Fq::from(1u64 << 32);
+        assert!(bad_circuit
+            .check_circuit_satisfiability(&pub_input)
+            .is_err());
+        assert!(circuit.clone().range_gate(a, 65).is_err());
+
+        // arithmetization over the Goldilocks FFT domain
+        circuit.finalize_for_arithmetization()?;
+        test_arithmetization_for_circuit(circuit, pub_input.to_vec())?;
+
+        Ok(())
+    }
+}
@@ -21,7 +21,12 @@ extern crate derivative;
 pub mod circuit;
 pub mod constants;
 pub mod errors;
+pub mod goldilocks;
+pub mod pasta;
+pub mod profiling;
 pub mod proof_system;
+#[cfg(feature = "test-rng")]
+pub mod rng;
 pub mod transcript;
 
 #[cfg(feature = "test_apis")]
@@ -0,0 +1,127 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! The fields of the Pasta cycle of curves: Pallas is defined over [`Fq`]
+//! and has a group of order `|Fr|`, Vesta is defined over [`Fr`] and has a
+//! group of order `|Fq|`, so that each field is the scalar field of one curve
+//! and the base field of the other.
+//!
+//! Both multiplicative groups have 2-adicity 32, hence circuits over either
+//! field can be built, checked and arithmetized. As the Pasta curves are not
+//! pairing-friendly, proofs over them cannot be produced by the KZG-based
+//! [`crate::proof_system::PlonkKzgSnark`], but by
+//! [`crate::proof_system::PlonkPcsSnark`] with the inner-product argument
+//! [`crate::proof_system::pcs::IpaPcs`] over [`Pallas`] or [`Vesta`]. Such
+//! proofs are not zero-knowledge, and are only computed by
+//! [`crate::proof_system::PlonkPcsSnark::prove_without_zero_knowledge`].
+
+/// The base field of Pallas, i.e. the scalar field of Vesta.
+pub use ark_pallas::Fq;
+
+/// The scalar field of Pallas, i.e. the base field of Vesta.
+pub use ark_pallas::Fr;
+
+/// The Pallas curve, in affine coordinates.
+pub use ark_pallas::Affine as Pallas;
+
+/// The Vesta curve, in affine coordinates.
+pub use ark_vesta::Affine as Vesta;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::{basic::test::test_arithmetization_for_circuit, Circuit, PlonkCircuit},
+        errors::PlonkError,
+        proof_system::{pcs::IpaPcs, snark::test::test_pcs_snark_helper},
+        transcript::StandardTranscript,
+    };
+    use ark_ff::{BigInteger as _, FftField, Field, One, PrimeField, SquareRootField};
+
+    #[test]
+    fn test_pasta_fields() {
+        test_pasta_field_helper::<Fq>();
+        test_pasta_field_helper::<Fr>();
+        // Fq and Fr only differ in their low 128 bits
+        assert_ne!(
+            <Fq as PrimeField>::Params::MODULUS.0[..2],
+            <Fr as PrimeField>::Params::MODULUS.0[..2]
+        );
+        assert_eq!(
+            <Fq as PrimeField>::Params::MODULUS.0[2..],
+            <Fr as PrimeField>::Params::MODULUS.0[2..]
+        );
+    }
+
+    fn test_pasta_field_helper<F: PrimeField + SquareRootField>() {
+        let five = F::from(5u64);
+        assert_eq!(F::multiplicative_generator(), five);
+        assert_eq!(five.inverse().unwrap() * five, F::one());
+        // 5 is not a square
+        assert_eq!(five.pow(F::Params::MODULUS_MINUS_ONE_DIV_TWO), -F::one());
+        assert!(five.sqrt().is_none());
+
+        let root = F::two_adic_root_of_unity();
+        assert_eq!(
+            root.pow([1u64 << 31]),
+            -F::one(),
+            "the root of unity should have order 2^32"
+        );
+        assert_eq!(
+            five.pow(F::Params::T),
+            root,
+            "the root of unity should be 5^((p - 1) / 2^32)"
+        );
+        // p - 1 = -1
+        let mut p_minus_one = F::Params::MODULUS;
+        p_minus_one.sub_noborrow(&F::BigInt::from(1u64));
+        assert_eq!(F::from_repr(p_minus_one), Some(-F::one()));
+        assert_eq!(
+            F::from(4u64).sqrt().map(|x| x.square()),
+            Some(F::from(4u64))
+        );
+        assert_eq!(F::from(123u64).into_repr(), F::BigInt::from(123u64));
+    }
+
+    #[test]
+    fn test_pasta_circuit() -> Result<(), PlonkError> {
+        test_pasta_circuit_helper::<Fq>()?;
+        test_pasta_circuit_helper::<Fr>()
+    }
+
+    fn test_pasta_circuit_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let circuit = pasta_circuit::<F>()?;
+        let pub_input = [F::from(17u64)];
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(16u64)])
+            .is_err());
+
+        // arithmetization over the 2^32 FFT domain of the field
+        test_arithmetization_for_circuit(circuit, pub_input.to_vec())
+    }
+
+    #[test]
+    fn test_pasta_snark() -> Result<(), PlonkError> {
+        // circuits over the scalar field of each curve, proven with the
+        // inner-product argument over the curve
+        test_pcs_snark_helper::<IpaPcs<Pallas>, _, _, StandardTranscript>(&pasta_circuit()?)?;
+        test_pcs_snark_helper::<IpaPcs<Vesta>, _, _, StandardTranscript>(&pasta_circuit()?)
+    }
+
+    fn pasta_circuit<F: PrimeField>() -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(F::from(3u64))?;
+        let b = circuit.create_variable(F::from(5u64))?;
+        let c = circuit.mul(a, b)?;
+        let d = circuit.add_constant(c, &F::from(2u64))?;
+        let e = circuit.create_public_variable(F::from(17u64))?;
+        circuit.equal_gate(d, e)?;
+        circuit.range_gate(a, 2)?;
+        circuit.finalize_for_arithmetization()?;
+        Ok(circuit)
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Profiling of the proving phases.
+//!
+//! With the `profiling` feature enabled, [`record_trace`] captures a
+//! begin/end event with a timestamp for every proving phase executed by the
+//! closure on the current thread, and [`ChromeTrace::to_json`] exports them in
+//! the Chrome trace event format, to be loaded in `chrome://tracing`.
+//! Without the feature, phase markers compile to no-ops.
+//!
+//! Independently of the feature, [`ProveProfile`] reports the time spent in
+//! FFTs, MSMs and polynomial evaluations by a single proving call.
+
+#[cfg(feature = "profiling")]
+pub use self::trace::*;
+
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// The durations of the operations of a single proving call.
+///
+/// The FFTs cover the interpolation of the wire and public input polynomials
+/// and the (coset) FFTs of the quotient polynomial, the MSMs cover the
+/// polynomial commitments (including the opening proofs), and the polynomial
+/// evaluations cover the evaluations at the challenge points. The durations
+/// of operations run in parallel with each other, such as the coset FFTs, are
+/// summed up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ProveProfile {
+    /// Time spent in FFTs.
+    pub fft_time: Duration,
+    /// Time spent in multi-scalar multiplications.
+    pub msm_time: Duration,
+    /// Time spent in polynomial evaluations.
+    pub poly_eval_time: Duration,
+}
+
+/// A profiled operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Fft,
+    Msm,
+    PolyEval,
+}
+
+/// Accumulator of the durations of the operations of a proving call, in
+/// nanoseconds. It is disabled by default, in which case the operations are
+/// not timed; clones share the same counters.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProfileRecorder {
+    #[cfg(feature = "std")]
+    counters: Option<Arc<[AtomicU64; 3]>>,
+}
+
+impl ProfileRecorder {
+    /// An enabled recorder, with zero durations.
+    #[cfg(feature = "std")]
+    pub(crate) fn enabled() -> Self {
+        Self {
+            counters: Some(Arc::new(Default::default())),
+        }
+    }
+
+    /// Run `f`, adding its duration to the operation `op` if enabled.
+    #[inline]
+    pub(crate) fn record<R>(&self, op: Operation, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "std")]
+        if let Some(counters) = self.counters.as_ref() {
+            let start = Instant::now();
+            let output = f();
+            counters[op as usize].fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+            return output;
+        }
+        let _ = op;
+        f()
+    }
+
+    /// The durations recorded so far, zero if disabled.
+    #[cfg(feature = "std")]
+    pub(crate) fn profile(&self) -> ProveProfile {
+        let duration = |op: Operation| {
+            self.counters.as_ref().map_or(Duration::ZERO, |counters| {
+                Duration::from_nanos(counters[op as usize].load(Ordering::Relaxed))
+            })
+        };
+        ProveProfile {
+            fft_time: duration(Operation::Fft),
+            msm_time: duration(Operation::Msm),
+            poly_eval_time: duration(Operation::PolyEval),
+        }
+    }
+}
+
+/// Run `f` on `pool`, keeping the phases it executes in the trace recorded on
+/// the current thread, if any.
+pub(crate) fn install_in_pool<R: Send>(
+    pool: &rayon::ThreadPool,
+    f: impl FnOnce() -> R + Send,
+) -> R {
+    #[cfg(feature = "profiling")]
+    {
+        trace::install_in_pool(pool, f)
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        pool.install(f)
+    }
+}
+
+/// Marker of a profiled phase: a begin event is recorded on creation and an
+/// end event when it is ended (or dropped).
+pub(crate) struct Phase {
+    #[cfg(feature = "profiling")]
+    name: &'static str,
+}
+
+impl Phase {
+    /// Begin the phase `name`.
+    #[inline]
+    pub(crate) fn begin(name: &'static str) -> Self {
+        #[cfg(feature = "profiling")]
+        {
+            trace::record(name, trace::EventPhase::Begin);
+            Self { name }
+        }
+        #[cfg(not(feature = "profiling"))]
+        {
+            let _ = name;
+            Self {}
+        }
+    }
+
+    /// End the phase.
+    #[inline]
+    pub(crate) fn end(self) {}
+}
+
+#[cfg(feature = "profiling")]
+impl Drop for Phase {
+    fn drop(&mut self) {
+        trace::record(self.name, trace::EventPhase::End);
+    }
+}
+
+#[cfg(feature = "profiling")]
+mod trace {
+    use std::{
+        cell::RefCell,
+        fmt::Write,
+        string::String,
+        sync::atomic::{AtomicU64, Ordering},
+        time::Instant,
+        vec::Vec,
+    };
+
+    static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+    std::thread_local! {
+        static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+        static RECORDER: RefCell<Option<(Instant, Vec<TraceEvent>)>> = RefCell::new(None);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    /// Whether an event begins or ends a phase.
+    pub enum EventPhase {
+        /// Beginning of a phase.
+        Begin,
+        /// End of a phase.
+        End,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    /// A timestamped trace event.
+    pub struct TraceEvent {
+        /// Name of the phase.
+        pub name: &'static str,
+        /// Whether the event begins or ends the phase.
+        pub phase: EventPhase,
+        /// Microseconds elapsed since the recording started.
+        pub timestamp_us: u64,
+        /// Identifier of the thread that emitted the event.
+        pub thread_id: u64,
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    /// A sequence of trace events.
+    pub struct ChromeTrace {
+        /// The recorded events, in chronological order.
+        pub events: Vec<TraceEvent>,
+    }
+
+    impl ChromeTrace {
+        /// Serialize the trace in the Chrome trace event JSON format.
+        pub fn to_json(&self) -> String {
+            let pid = std::process::id();
+            let mut json = String::from("{\"traceEvents\":[");
+            for (i, event) in self.events.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                let ph = match event.phase {
+                    EventPhase::Begin => 'B',
+                    EventPhase::End => 'E',
+                };
+                let _ = write!(
+                    json,
+                    "{{\"name\":\"{}\",\"cat\":\"plonk\",\"ph\":\"{}\",\"ts\":{},\"pid\":{},\"tid\":{}}}",
+                    escape(event.name),
+                    ph,
+                    event.timestamp_us,
+                    pid,
+                    event.thread_id
+                );
+            }
+            json.push_str("],\"displayTimeUnit\":\"ms\"}");
+            json
+        }
+    }
+
+    /// Run `f` and return its output along with a trace of the phases it
+    /// executed on the current thread, or on the thread pool of
+    /// [`crate::proof_system::PlonkKzgSnark::prove_in_pool`].
+    pub fn record_trace<R>(f: impl FnOnce() -> R) -> (R, ChromeTrace) {
+        let previous =
+            RECORDER.with(|recorder| recorder.replace(Some((Instant::now(), Vec::new()))));
+        let output = f();
+        let events = RECORDER
+            .with(|recorder| recorder.replace(previous))
+            .map(|(_, events)| events)
+            .unwrap_or_default();
+        (output, ChromeTrace { events })
+    }
+
+    pub(crate) fn install_in_pool<R: Send>(
+        pool: &rayon::ThreadPool,
+        f: impl FnOnce() -> R + Send,
+    ) -> R {
+        let start = RECORDER.with(|recorder| recorder.borrow().as_ref().map(|(start, _)| *start));
+        let start = match start {
+            Some(start) => start,
+            None => return pool.install(f),
+        };
+        // record on the worker thread against the same start, then move the
+        // events to the recorder of the current thread
+        let (output, events) = pool.install(|| {
+            let previous = RECORDER.with(|recorder| recorder.replace(Some((start, Vec::new()))));
+            let output = f();
+            let events = RECORDER
+                .with(|recorder| recorder.replace(previous))
+                .map(|(_, events)| events)
+                .unwrap_or_default();
+            (output, events)
+        });
+        RECORDER.with(|recorder| {
+            if let Some((_, recorded)) = recorder.borrow_mut().as_mut() {
+                recorded.extend(events);
+            }
+        });
+        output
+    }
+
+    pub(crate) fn record(name: &'static str, phase: EventPhase) {
+        RECORDER.with(|recorder| {
+            if let Some((start, events)) = recorder.borrow_mut().as_mut() {
+                events.push(TraceEvent {
+                    name,
+                    phase,
+                    timestamp_us: start.elapsed().as_micros() as u64,
+                    thread_id: THREAD_ID.with(|id| *id),
+                });
+            }
+        })
+    }
+
+    fn escape(s: &str) -> String {
+        let mut escaped = String::with_capacity(s.len());
+        for c in s.chars() {
+            match c {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+}
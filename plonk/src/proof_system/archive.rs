@@ -0,0 +1,362 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Archives of Plonk proofs appended to a single stream.
+//!
+//! An archive is a sequence of entries, each of which is made of the
+//! identifier of the verifying key of the proof as a little-endian `u64`, the
+//! byte length of the payload as a little-endian `u64`, and the payload: the
+//! canonical serialization of the public input followed by that of the proof.
+//! Entries are appended with [`append_proof`] and read back, and verified, by
+//! a [`ProofArchiveReader`].
+
+use super::{
+    structs::{Proof, VerifyingKey},
+    PlonkKzgSnark,
+};
+use crate::{
+    circuit::customized::ecc::SWToTEConParam,
+    errors::{PlonkError, SnarkError::ParameterError},
+    transcript::PlonkTranscript,
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{format, marker::PhantomData, vec, vec::Vec};
+use jf_rescue::RescueParameter;
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Read, Write},
+};
+
+/// The maximum number of consecutive entries verified at once.
+const ARCHIVE_MAX_BATCH_SIZE: usize = 32;
+
+/// Append an entry holding `proof` for `public_input`, to be verified against
+/// the verifying key of identifier `vk_id`, to the archive `writer`.
+pub fn append_proof<E: PairingEngine, W: Write>(
+    writer: &mut W,
+    vk_id: u64,
+    public_input: &[E::Fr],
+    proof: &Proof<E>,
+) -> Result<(), PlonkError> {
+    let mut payload = Vec::new();
+    public_input.to_vec().serialize(&mut payload)?;
+    proof.serialize(&mut payload)?;
+    writer.write_all(&vk_id.to_le_bytes())?;
+    writer.write_all(&(payload.len() as u64).to_le_bytes())?;
+    writer.write_all(&payload)?;
+    Ok(())
+}
+
+/// A streaming reader of a proof archive, yielding the identifier of the
+/// verifying key of each entry together with the verdict of its proof.
+///
+/// Consecutive entries sharing a verifying key are verified at once with
+/// [`PlonkKzgSnark::batch_verify`], falling back to verifying them one by one
+/// if the batch is rejected. A corrupted entry, or one referring to an unknown
+/// verifying key, is reported as an invalid proof without aborting the rest of
+/// the archive. An unreadable or truncated archive is reported as an error,
+/// after which the reader stops.
+pub struct ProofArchiveReader<'a, E: PairingEngine, R, T> {
+    reader: R,
+    verify_keys: &'a HashMap<u64, VerifyingKey<E>>,
+    // the verdicts of the entries read but not yielded yet
+    verdicts: VecDeque<(u64, Result<(), PlonkError>)>,
+    // the first entry of the next batch
+    lookahead: Option<ArchiveEntry<E>>,
+    error: Option<PlonkError>,
+    done: bool,
+    _transcript: PhantomData<fn() -> T>,
+}
+
+// A well-formed entry of an archive.
+struct ArchiveEntry<E: PairingEngine> {
+    vk_id: u64,
+    public_input: Vec<E::Fr>,
+    proof: Proof<E>,
+}
+
+impl<'a, E, F, P, R, T> ProofArchiveReader<'a, E, R, T>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + Clone,
+    R: Read,
+    T: PlonkTranscript<F>,
+{
+    /// Read the archive `reader`, verifying its entries against the keys of
+    /// `verify_keys` with the transcript `T`.
+    pub fn new(reader: R, verify_keys: &'a HashMap<u64, VerifyingKey<E>>) -> Self {
+        Self {
+            reader,
+            verify_keys,
+            verdicts: VecDeque::new(),
+            lookahead: None,
+            error: None,
+            done: false,
+            _transcript: PhantomData,
+        }
+    }
+
+    // Read the next batch of entries sharing a verifying key and verify it.
+    fn read_batch(&mut self) {
+        let mut batch: Vec<ArchiveEntry<E>> = self.lookahead.take().into_iter().collect();
+        while batch.len() < ARCHIVE_MAX_BATCH_SIZE {
+            match self.read_entry() {
+                Ok(Some((_, Ok(entry)))) => {
+                    if batch.is_empty() || batch[0].vk_id == entry.vk_id {
+                        batch.push(entry);
+                    } else {
+                        self.lookahead = Some(entry);
+                        break;
+                    }
+                },
+                Ok(Some((vk_id, Err(e)))) => {
+                    // keep the entries in order
+                    self.verify_batch(&batch);
+                    self.verdicts.push_back((vk_id, Err(e)));
+                    return;
+                },
+                Ok(None) => {
+                    self.done = true;
+                    break;
+                },
+                Err(e) => {
+                    self.error = Some(e);
+                    self.done = true;
+                    break;
+                },
+            }
+        }
+        self.verify_batch(&batch);
+    }
+
+    // Read the next entry of the archive, if any. The inner result is an error
+    // if the payload of the entry is malformed.
+    #[allow(clippy::type_complexity)]
+    fn read_entry(
+        &mut self,
+    ) -> Result<Option<(u64, Result<ArchiveEntry<E>, PlonkError>)>, PlonkError> {
+        let mut header = [0u8; 16];
+        if self.reader.read(&mut header[..1])? == 0 {
+            return Ok(None);
+        }
+        self.reader.read_exact(&mut header[1..])?;
+        let (vk_id, len) = header.split_at(8);
+        let mut vk_id_bytes = [0u8; 8];
+        vk_id_bytes.copy_from_slice(vk_id);
+        let vk_id = u64::from_le_bytes(vk_id_bytes);
+        let mut len_bytes = [0u8; 8];
+        len_bytes.copy_from_slice(len);
+        let len = u64::from_le_bytes(len_bytes);
+
+        let mut payload = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut payload)?;
+        if payload.len() as u64 != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated proof archive entry",
+            )
+            .into());
+        }
+        Ok(Some((vk_id, parse_payload(vk_id, &payload))))
+    }
+
+    // Verify a batch of entries sharing a verifying key, falling back to
+    // verifying them one by one if it is rejected.
+    fn verify_batch(&mut self, batch: &[ArchiveEntry<E>]) {
+        let vk_id = match batch.first() {
+            Some(entry) => entry.vk_id,
+            None => return,
+        };
+        let verify_key = match self.verify_keys.get(&vk_id) {
+            Some(verify_key) => verify_key,
+            None => {
+                for _ in batch.iter() {
+                    self.verdicts.push_back((
+                        vk_id,
+                        Err(ParameterError(format!("unknown verifying key {}", vk_id)).into()),
+                    ));
+                }
+                return;
+            },
+        };
+        let verify_keys = vec![verify_key; batch.len()];
+        let public_inputs: Vec<&[E::Fr]> =
+            batch.iter().map(|e| e.public_input.as_slice()).collect();
+        let proofs: Vec<&Proof<E>> = batch.iter().map(|e| &e.proof).collect();
+        let extra_msgs = vec![None; batch.len()];
+        if PlonkKzgSnark::<E>::batch_verify::<T>(&verify_keys, &public_inputs, &proofs, &extra_msgs)
+            .is_ok()
+        {
+            self.verdicts
+                .extend(batch.iter().map(|entry| (entry.vk_id, Ok(()))));
+            return;
+        }
+        for entry in batch.iter() {
+            let verdict = PlonkKzgSnark::<E>::batch_verify::<T>(
+                &[verify_key],
+                &[&entry.public_input],
+                &[&entry.proof],
+                &[None],
+            );
+            self.verdicts.push_back((entry.vk_id, verdict));
+        }
+    }
+}
+
+impl<'a, E, F, P, R, T> Iterator for ProofArchiveReader<'a, E, R, T>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + Clone,
+    R: Read,
+    T: PlonkTranscript<F>,
+{
+    type Item = Result<(u64, Result<(), PlonkError>), PlonkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(verdict) = self.verdicts.pop_front() {
+                return Some(Ok(verdict));
+            }
+            if let Some(e) = self.error.take() {
+                return Some(Err(e));
+            }
+            if self.done {
+                return None;
+            }
+            self.read_batch();
+        }
+    }
+}
+
+// Deserialize the public input and the proof of an entry.
+fn parse_payload<E: PairingEngine>(
+    vk_id: u64,
+    mut payload: &[u8],
+) -> Result<ArchiveEntry<E>, PlonkError> {
+    let public_input = Vec::<E::Fr>::deserialize(&mut payload)?;
+    let proof = Proof::<E>::deserialize(&mut payload)?;
+    if !payload.is_empty() {
+        return Err(SerializationError::InvalidData.into());
+    }
+    Ok(ArchiveEntry {
+        vk_id,
+        public_input,
+        proof,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::{Arithmetization, Circuit},
+        proof_system::{snark::test::gen_circuit_for_test, Snark},
+        transcript::StandardTranscript,
+        PlonkType,
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_ff::One;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_proof_archive() -> Result<(), PlonkError> {
+        test_proof_archive_helper::<Bn254, Fq254, _>()?;
+        test_proof_archive_helper::<Bls12_377, Fq377, _>()
+    }
+
+    fn test_proof_archive_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut test_rng();
+        let circuits = [
+            gen_circuit_for_test(3, 4, PlonkType::TurboPlonk)?,
+            gen_circuit_for_test(5, 6, PlonkType::UltraPlonk)?,
+        ];
+        let srs_size = circuits
+            .iter()
+            .map(|circuit| circuit.srs_size())
+            .collect::<Result<Vec<_>, PlonkError>>()?
+            .into_iter()
+            .max()
+            .unwrap();
+        let srs = PlonkKzgSnark::<E>::universal_setup(srs_size, rng)?;
+        let mut verify_keys = HashMap::new();
+        let mut instances = Vec::new();
+        for (vk_id, circuit) in circuits.iter().enumerate() {
+            let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, circuit)?;
+            verify_keys.insert(vk_id as u64, vk);
+            let proofs = (0..3)
+                .map(|_| {
+                    PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(rng, circuit, &pk, None)
+                })
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            instances.push((circuit.public_input()?, proofs));
+        }
+
+        // runs of entries sharing a verifying key, a wrong public input, a
+        // corrupted entry and an unknown verifying key
+        let mut archive = Vec::new();
+        let mut expected = Vec::new();
+        for &(vk_id, i) in [(0, 0), (0, 1), (1, 0), (1, 1), (1, 2), (0, 2)].iter() {
+            let (public_input, proofs) = &instances[vk_id as usize];
+            append_proof(&mut archive, vk_id, public_input, &proofs[i])?;
+            expected.push((vk_id, true));
+        }
+        let (public_input, proofs) = &instances[1];
+        let mut bad_public_input = public_input.clone();
+        bad_public_input[0] += E::Fr::one();
+        append_proof(&mut archive, 1, &bad_public_input, &proofs[0])?;
+        expected.push((1, false));
+        let mut corrupted = Vec::new();
+        append_proof(&mut corrupted, 1, public_input, &proofs[1])?;
+        // the most significant byte of the first public input, after the entry
+        // header and the length of the public input
+        corrupted[16 + 8 + 31] = 0xff;
+        archive.extend(corrupted);
+        expected.push((1, false));
+        append_proof(&mut archive, 1, public_input, &proofs[2])?;
+        expected.push((1, true));
+        append_proof(&mut archive, 7, public_input, &proofs[2])?;
+        expected.push((7, false));
+        let (public_input, proofs) = &instances[0];
+        append_proof(&mut archive, 0, public_input, &proofs[0])?;
+        expected.push((0, true));
+
+        let verdicts =
+            ProofArchiveReader::<_, _, StandardTranscript>::new(&archive[..], &verify_keys)
+                .map(|item| item.map(|(vk_id, verdict)| (vk_id, verdict.is_ok())))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+        assert_eq!(verdicts, expected);
+
+        // an empty archive
+        assert!(
+            ProofArchiveReader::<E, _, StandardTranscript>::new(&[][..], &verify_keys)
+                .next()
+                .is_none()
+        );
+
+        // a truncated archive, reported after the entries read
+        for &cut in [archive.len() - 1, archive.len() - 20].iter() {
+            let mut reader =
+                ProofArchiveReader::<_, _, StandardTranscript>::new(&archive[..cut], &verify_keys);
+            for &(vk_id, valid) in expected[..expected.len() - 1].iter() {
+                let (id, verdict) = reader.next().unwrap()?;
+                assert_eq!((id, verdict.is_ok()), (vk_id, valid));
+            }
+            assert!(reader.next().unwrap().is_err());
+            assert!(reader.next().is_none());
+        }
+
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
     circuit::{customized::ecc::SWToTEConParam, Circuit, PlonkCircuit},
     errors::{PlonkError, SnarkError::ParameterError},
     proof_system::{
+        pcs::KzgPcs,
         structs::{BatchProof, OpenKey, ProvingKey, ScalarsAndBases, UniversalSrs, VerifyingKey},
         verifier::Verifier,
         PlonkKzgSnark,
@@ -67,7 +68,7 @@ where
         circuit_type: MergeableCircuitType,
     ) -> Result<Instance<'a, E>, PlonkError> {
         circuit.finalize_for_mergeable_circuit(circuit_type)?;
-        let (prove_key, _) = PlonkKzgSnark::preprocess(srs, &circuit)?;
+        let (prove_key, _) = PlonkKzgSnark::<E>::preprocess(srs, &circuit)?;
         Ok(Instance {
             prove_key,
             circuit,
@@ -101,7 +102,7 @@ where
         let circuits = instances_type_a
             .iter()
             .zip(instances_type_b.iter())
-            .map(|(pred_a, pred_b)| pred_a.circuit.merge(&pred_b.circuit))
+            .map(|(pred_a, pred_b)| pred_a.circuit.merge_mergeable_circuits(&pred_b.circuit))
             .collect::<Result<Vec<_>, PlonkError>>()?;
         let pks_ref: Vec<&ProvingKey<E>> = pks.iter().collect();
         let circuits_ref: Vec<&PlonkCircuit<E::Fr>> = circuits.iter().collect();
@@ -143,20 +144,24 @@ where
                 .into());
             }
         }
-        let verifier = Verifier::new(domain_size)?;
+        let verifier = Verifier::<KzgPcs<E>>::new(domain_size)?;
         // we need to copy the public input once after merging the circuit
         let shared_public_input = [shared_public_input, shared_public_input].concat();
         let public_inputs = vec![&shared_public_input[..]; merged_vks.len()];
         let merged_vks_ref: Vec<&VerifyingKey<E>> = merged_vks.iter().collect();
-        let pcs_info =
-            verifier.prepare_pcs_info::<T>(&merged_vks_ref, &public_inputs, batch_proof, &None)?;
+        let pcs_info = verifier.prepare_pcs_info::<T>(
+            &merged_vks_ref,
+            &public_inputs,
+            &batch_proof.into(),
+            &None,
+        )?;
 
         // inner1 = [open_proof] + u * [shifted_open_proof] + blinding_factor * [1]1
-        let mut scalars_and_bases = ScalarsAndBases::<E>::new();
+        let mut scalars_and_bases = ScalarsAndBases::<KzgPcs<E>>::new();
         scalars_and_bases.push(E::Fr::one(), pcs_info.opening_proof.0);
         scalars_and_bases.push(pcs_info.u, pcs_info.shifted_opening_proof.0);
         scalars_and_bases.push(blinding_factor, *generator_g);
-        let inner1 = scalars_and_bases.multi_scalar_mul();
+        let inner1 = scalars_and_bases.g1_multi_scalar_mul();
 
         // inner2 = eval_point * [open_proof] + next_eval_point * u *
         // [shifted_open_proof] + [aggregated_comm] - aggregated_eval * [1]1 +
@@ -169,7 +174,7 @@ where
         );
         scalars_and_bases.push(-pcs_info.eval, *generator_g);
         scalars_and_bases.push(blinding_factor, *beta_g);
-        let inner2 = scalars_and_bases.multi_scalar_mul();
+        let inner2 = scalars_and_bases.g1_multi_scalar_mul();
 
         Ok((inner1, inner2))
     }
@@ -11,13 +11,20 @@ use ark_std::{
     rand::{CryptoRng, RngCore},
     vec::Vec,
 };
+#[cfg(feature = "std")]
+pub mod archive;
 pub mod batch_arg;
+pub mod msm;
+pub mod pcs;
 pub(crate) mod prover;
 pub(crate) mod snark;
 pub mod structs;
 pub(crate) mod verifier;
+pub mod verifier_ir;
+#[cfg(feature = "std")]
+pub mod verifier_pool;
 use crate::transcript::PlonkTranscript;
-pub use snark::PlonkKzgSnark;
+pub use snark::{PlonkKzgSnark, PlonkPcsSnark};
 
 /// An interface for SNARKs.
 pub trait Snark<E: PairingEngine> {
@@ -0,0 +1,35 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Pluggable multi-scalar multiplications of the prover.
+//!
+//! The MSMs of the polynomial commitments, including the opening proofs,
+//! dominate the proving time. [`MsmBackend`] lets the caller of
+//! [`crate::proof_system::PlonkKzgSnark::prove_with_msm_backend`] compute them
+//! elsewhere, e.g. on a GPU; [`ArkworksMsm`] is the default CPU backend.
+
+use ark_ec::{msm::VariableBaseMSM, AffineCurve};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// A backend for the multi-scalar multiplications of the prover.
+pub trait MsmBackend<G: AffineCurve>: Sync {
+    /// Compute `sum_i scalars[i] * bases[i]`. `bases` and `scalars` are
+    /// guaranteed to have the same length.
+    ///
+    /// The default implementation is the variable base MSM of arkworks.
+    fn msm(&self, bases: &[G], scalars: &[G::ScalarField]) -> G::Projective {
+        let scalars: Vec<_> = scalars.iter().map(|scalar| scalar.into_repr()).collect();
+        VariableBaseMSM::multi_scalar_mul(bases, &scalars)
+    }
+}
+
+/// The default MSM backend: the variable base MSM of arkworks, run on the
+/// CPU with the rayon thread pool.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArkworksMsm;
+
+impl<G: AffineCurve> MsmBackend<G> for ArkworksMsm {}
@@ -0,0 +1,1025 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! The polynomial commitment schemes of the Plonk prover, exposed for other
+//! protocols.
+//!
+//! The [`PolynomialCommitmentScheme`] trait, along with its prover side
+//! [`PcsCommitter`], abstracts over the scheme and the curve: the Plonk
+//! prover and verifier of [`crate::proof_system::PlonkPcsSnark`] are generic
+//! over it. It is implemented by [`KzgPcs`], which instantiates
+//! [`crate::proof_system::PlonkKzgSnark`], and by [`IpaPcs`], an inner-product
+//! argument without trusted setup nor pairing, whose proofs have a logarithmic
+//! size but take a linear time to check.
+//!
+//! Besides committing and opening single polynomials, [`KzgPcs::open_batch`]
+//! opens several polynomials at the same point with a single proof, checked
+//! by [`verify_batch_open`]. The polynomials are combined with the powers of
+//! a challenge derived from a transcript supplied by the caller, which should
+//! already bind the commitments to the polynomials.
+
+use crate::{
+    errors::{PlonkError, SnarkError::ParameterError},
+    proof_system::{
+        msm::MsmBackend,
+        structs::{check_srs_size, trim, CommitKey, OpenKey, UniversalSrs},
+    },
+    transcript::PlonkTranscript,
+};
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use ark_poly_commit::kzg10::{Commitment, Proof as KzgProof, Randomness, KZG10};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    fmt::Debug,
+    format,
+    hash::Hash,
+    rand::{CryptoRng, RngCore, SeedableRng},
+    string::ToString,
+    vec,
+    vec::Vec,
+};
+use rand_chacha::ChaChaRng;
+use sha3::{Digest, Keccak256};
+
+const IPA_GENERATORS_LABEL: &[u8] = b"jf-plonk ipa generators";
+
+/// A polynomial commitment scheme for the polynomials over the prime field
+/// `Fr`, committed in the curve `Curve`, whose openings are made
+/// non-interactive with a transcript over the base field of `Curve`.
+///
+/// A commitment is a point of `Curve`, and the commitments are additively
+/// homomorphic: the commitment to a linear combination of polynomials is the
+/// same combination of their commitments. The commitments are not hiding.
+pub trait PolynomialCommitmentScheme: Sized {
+    /// The field of the coefficients of the committed polynomials.
+    type Fr: PrimeField;
+    /// The curve of the commitments.
+    type Curve: AffineCurve<ScalarField = Self::Fr>;
+    /// The public parameters, from which the keys are derived for any smaller
+    /// degree.
+    type Params;
+    /// The key to verify the opening proofs.
+    type OpenKey: Clone
+        + Debug
+        + PartialEq
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + Send
+        + Sync;
+    /// A commitment to a polynomial.
+    type Commitment: Copy
+        + Debug
+        + Default
+        + PartialEq
+        + Eq
+        + Hash
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + Send
+        + Sync;
+    /// The proof of the evaluation of a committed polynomial at a point.
+    type Proof: Clone
+        + Debug
+        + PartialEq
+        + Eq
+        + Hash
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + Send
+        + Sync;
+
+    /// Generate the public parameters for polynomials of degree at most
+    /// `max_degree`. `rng` is only used by schemes with a trusted setup.
+    fn setup<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::Params, PlonkError>;
+
+    /// The point of `Curve` of the commitment `comm`.
+    fn commitment_to_point(comm: &Self::Commitment) -> Self::Curve;
+
+    /// The commitment of the point `point` of `Curve`.
+    fn commitment_from_point(point: Self::Curve) -> Self::Commitment;
+
+    /// The points of `Curve` of the proof `proof`, which are appended to the
+    /// Plonk transcript before its scalars.
+    fn proof_points(proof: &Self::Proof) -> Vec<Self::Curve>;
+
+    /// The scalars of the proof `proof`.
+    fn proof_scalars(proof: &Self::Proof) -> Vec<Self::Fr>;
+
+    /// Check the proof `proof` from [`PcsCommitter::open`] that the polynomial
+    /// committed in `comm` evaluates to `value` at `point`. `transcript`
+    /// should be in the same state as the one given to the prover.
+    fn verify<T: PlonkTranscript<<Self::Curve as AffineCurve>::BaseField>>(
+        open_key: &Self::OpenKey,
+        transcript: &mut T,
+        comm: &Self::Commitment,
+        point: &Self::Fr,
+        value: &Self::Fr,
+        proof: &Self::Proof,
+    ) -> Result<bool, PlonkError>;
+}
+
+/// The prover side of a [`PolynomialCommitmentScheme`], whose committing key
+/// may borrow the public parameters for the lifetime `'a`.
+pub trait PcsCommitter<'a>: PolynomialCommitmentScheme {
+    /// The key to commit to and open the polynomials.
+    type CommitKey: Clone
+        + Debug
+        + PartialEq
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + Send
+        + Sync;
+
+    /// Derive from `params` the keys for polynomials of degree at most
+    /// `max_degree`.
+    /// Return error if `params` doesn't support `max_degree`.
+    fn trim(
+        params: &'a Self::Params,
+        max_degree: usize,
+    ) -> Result<(Self::CommitKey, Self::OpenKey), PlonkError>;
+
+    /// The bases of the commitments: the commitment to a polynomial is the
+    /// multi-scalar multiplication of its coefficients with a prefix of them.
+    fn commit_bases(commit_key: &Self::CommitKey) -> &[Self::Curve];
+
+    /// Commit to `poly`.
+    /// Return error if the degree of `poly` is too large.
+    fn commit(
+        commit_key: &Self::CommitKey,
+        poly: &DensePolynomial<Self::Fr>,
+    ) -> Result<Self::Commitment, PlonkError>;
+
+    /// Open `poly` at `point`: return its evaluation along with a proof, to be
+    /// checked with [`PolynomialCommitmentScheme::verify`]. The multi-scalar
+    /// multiplications of the proof are computed by `msm`.
+    ///
+    /// Schemes with an interactive opening append the point and the
+    /// evaluation to `transcript`, whose state should already bind the
+    /// commitment to `poly`.
+    /// Return error if the degree of `poly` is too large.
+    fn open<T: PlonkTranscript<<Self::Curve as AffineCurve>::BaseField>>(
+        commit_key: &Self::CommitKey,
+        transcript: &mut T,
+        poly: &DensePolynomial<Self::Fr>,
+        point: &Self::Fr,
+        msm: &dyn MsmBackend<Self::Curve>,
+    ) -> Result<(Self::Fr, Self::Proof), PlonkError>;
+}
+
+/// Commit to `poly` with the bases of `commit_key` and the multi-scalar
+/// multiplication of `msm`.
+/// Return error if `poly` has more coefficients than there are bases.
+pub(crate) fn commit_with_msm<'a, PCS: PcsCommitter<'a>>(
+    commit_key: &PCS::CommitKey,
+    poly: &DensePolynomial<PCS::Fr>,
+    msm: &dyn MsmBackend<PCS::Curve>,
+) -> Result<PCS::Commitment, PlonkError> {
+    let bases = PCS::commit_bases(commit_key);
+    let num_coeffs = poly.coeffs.len();
+    if num_coeffs > bases.len() {
+        return Err(PlonkError::PcsError(
+            ark_poly_commit::Error::TooManyCoefficients {
+                num_coefficients: num_coeffs,
+                num_powers: bases.len(),
+            },
+        ));
+    }
+    let comm = msm.msm(&bases[..num_coeffs], &poly.coeffs);
+    Ok(PCS::commitment_from_point(comm.into_affine()))
+}
+
+/// The KZG polynomial commitment scheme over the keys trimmed from a
+/// universal SRS to a maximal degree.
+#[derive(Debug, Clone)]
+pub struct KzgPcs<E: PairingEngine> {
+    commit_key: CommitKey<'static, E>,
+    open_key: OpenKey<E>,
+}
+
+impl<E: PairingEngine> KzgPcs<E> {
+    /// Trim the keys of `srs` to commit to polynomials of degree at most
+    /// `max_degree`.
+    /// Return error if `srs` doesn't support `max_degree`.
+    pub fn new(srs: &UniversalSrs<E>, max_degree: usize) -> Result<Self, PlonkError> {
+        check_srs_size(srs, max_degree)?;
+        let (commit_key, open_key) = trim(&srs.0, max_degree);
+        Ok(Self {
+            commit_key,
+            open_key,
+        })
+    }
+
+    /// The key to verify the opening proofs.
+    pub fn open_key(&self) -> &OpenKey<E> {
+        &self.open_key
+    }
+
+    /// Commit to `poly`. The commitment is not hiding.
+    /// Return error if the degree of `poly` is too large.
+    pub fn commit(&self, poly: &DensePolynomial<E::Fr>) -> Result<Commitment<E>, PlonkError> {
+        <Self as PcsCommitter<'static>>::commit(&self.commit_key, poly)
+    }
+
+    /// Open `polys` at `point`: return their evaluations along with a single
+    /// opening proof for all of them, to be checked with
+    /// [`verify_batch_open`].
+    ///
+    /// The point and the evaluations are appended to `transcript`, and the
+    /// polynomials are combined with the powers of a challenge derived from
+    /// it. The caller should have appended the commitments to `polys` before.
+    /// Return error if `polys` is empty or if the degree of some polynomial is
+    /// too large.
+    pub fn open_batch<F, T>(
+        &self,
+        transcript: &mut T,
+        polys: &[DensePolynomial<E::Fr>],
+        point: &E::Fr,
+    ) -> Result<(Vec<E::Fr>, Commitment<E>), PlonkError>
+    where
+        E: PairingEngine<Fq = F>,
+        T: PlonkTranscript<F>,
+    {
+        if polys.is_empty() {
+            return Err(ParameterError("no polynomial to open".to_string()).into());
+        }
+        let evals: Vec<E::Fr> = polys.iter().map(|poly| poly.evaluate(point)).collect();
+        let v = batch_open_challenge::<E, F, T>(transcript, point, &evals)?;
+
+        // batch_poly = \sum_i v^i * polys[i]
+        let batch_poly = polys
+            .iter()
+            .rev()
+            .fold(DensePolynomial::zero(), |acc, poly| {
+                let coeffs = acc.coeffs.iter().map(|c| v * c).collect();
+                DensePolynomial::from_coefficients_vec(coeffs) + poly.clone()
+            });
+        let empty_rand = Randomness::<E::Fr, DensePolynomial<E::Fr>>::empty();
+        let (witness_poly, _) = KZG10::<E, DensePolynomial<E::Fr>>::compute_witness_polynomial(
+            &batch_poly,
+            *point,
+            &empty_rand,
+        )?;
+        let (opening_proof, _) = KZG10::commit(&self.commit_key, &witness_poly, None, None)?;
+        Ok((evals, opening_proof))
+    }
+}
+
+impl<E: PairingEngine> PolynomialCommitmentScheme for KzgPcs<E> {
+    type Fr = E::Fr;
+    type Curve = E::G1Affine;
+    type Params = UniversalSrs<E>;
+    type OpenKey = OpenKey<E>;
+    type Commitment = Commitment<E>;
+    type Proof = Commitment<E>;
+
+    fn setup<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<UniversalSrs<E>, PlonkError> {
+        let srs = KZG10::<E, DensePolynomial<E::Fr>>::setup(max_degree, false, rng)?;
+        Ok(UniversalSrs(srs))
+    }
+
+    fn commitment_to_point(comm: &Commitment<E>) -> E::G1Affine {
+        comm.0
+    }
+
+    fn commitment_from_point(point: E::G1Affine) -> Commitment<E> {
+        Commitment(point)
+    }
+
+    fn proof_points(proof: &Commitment<E>) -> Vec<E::G1Affine> {
+        vec![proof.0]
+    }
+
+    fn proof_scalars(_proof: &Commitment<E>) -> Vec<E::Fr> {
+        vec![]
+    }
+
+    // The opening needs no challenge, hence `transcript` is left untouched.
+    fn verify<T: PlonkTranscript<E::Fq>>(
+        open_key: &OpenKey<E>,
+        _transcript: &mut T,
+        comm: &Commitment<E>,
+        point: &E::Fr,
+        value: &E::Fr,
+        proof: &Commitment<E>,
+    ) -> Result<bool, PlonkError> {
+        let proof = KzgProof {
+            w: proof.0,
+            random_v: None,
+        };
+        Ok(KZG10::<E, DensePolynomial<E::Fr>>::check(
+            open_key, comm, *point, *value, &proof,
+        )?)
+    }
+}
+
+impl<'a, E: PairingEngine> PcsCommitter<'a> for KzgPcs<E> {
+    type CommitKey = CommitKey<'a, E>;
+
+    fn trim(
+        params: &'a UniversalSrs<E>,
+        max_degree: usize,
+    ) -> Result<(CommitKey<'a, E>, OpenKey<E>), PlonkError> {
+        check_srs_size(params, max_degree)?;
+        Ok(trim(&params.0, max_degree))
+    }
+
+    fn commit_bases(commit_key: &CommitKey<'a, E>) -> &[E::G1Affine] {
+        &commit_key.powers_of_g
+    }
+
+    fn commit(
+        commit_key: &CommitKey<'a, E>,
+        poly: &DensePolynomial<E::Fr>,
+    ) -> Result<Commitment<E>, PlonkError> {
+        Ok(KZG10::commit(commit_key, poly, None, None)?.0)
+    }
+
+    // The opening needs no challenge, hence `transcript` is left untouched.
+    fn open<T: PlonkTranscript<E::Fq>>(
+        commit_key: &CommitKey<'a, E>,
+        _transcript: &mut T,
+        poly: &DensePolynomial<E::Fr>,
+        point: &E::Fr,
+        msm: &dyn MsmBackend<E::G1Affine>,
+    ) -> Result<(E::Fr, Commitment<E>), PlonkError> {
+        let empty_rand = Randomness::<E::Fr, DensePolynomial<E::Fr>>::empty();
+        let (witness_poly, _) = KZG10::<E, DensePolynomial<E::Fr>>::compute_witness_polynomial(
+            poly,
+            *point,
+            &empty_rand,
+        )?;
+        let proof = commit_with_msm::<Self>(commit_key, &witness_poly, msm)?;
+        Ok((poly.evaluate(point), proof))
+    }
+}
+
+/// Check the opening proof `opening_proof` from [`KzgPcs::open_batch`] of the
+/// polynomials committed in `comms` at `point`, against the claimed
+/// evaluations `evals`. `transcript` should be in the same state as the one
+/// given to the prover.
+/// Return error if `comms` is empty or if `comms` and `evals` have different
+/// lengths.
+pub fn verify_batch_open<E, F, T>(
+    open_key: &OpenKey<E>,
+    transcript: &mut T,
+    comms: &[Commitment<E>],
+    point: &E::Fr,
+    evals: &[E::Fr],
+    opening_proof: &Commitment<E>,
+) -> Result<bool, PlonkError>
+where
+    E: PairingEngine<Fq = F>,
+    T: PlonkTranscript<F>,
+{
+    if comms.is_empty() {
+        return Err(ParameterError("no commitment to check".to_string()).into());
+    }
+    if comms.len() != evals.len() {
+        return Err(ParameterError(format!(
+            "the number of commitments {} is different from the number of evaluations {}",
+            comms.len(),
+            evals.len()
+        ))
+        .into());
+    }
+    let v = batch_open_challenge::<E, F, T>(transcript, point, evals)?;
+
+    let mut scalars = Vec::with_capacity(comms.len());
+    let mut coeff = E::Fr::one();
+    for _ in 0..comms.len() {
+        scalars.push(coeff);
+        coeff *= v;
+    }
+    let bases: Vec<E::G1Affine> = comms.iter().map(|comm| comm.0).collect();
+    let scalars_repr: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+    let batch_comm = VariableBaseMSM::multi_scalar_mul(&bases, &scalars_repr).into_affine();
+    let batch_eval = scalars
+        .iter()
+        .zip(evals.iter())
+        .fold(E::Fr::zero(), |acc, (s, eval)| acc + *s * eval);
+
+    let proof = KzgProof {
+        w: opening_proof.0,
+        random_v: None,
+    };
+    Ok(KZG10::<E, DensePolynomial<E::Fr>>::check(
+        open_key,
+        &Commitment(batch_comm),
+        *point,
+        batch_eval,
+        &proof,
+    )?)
+}
+
+/// The public parameters of [`IpaPcs`]: generators of the group `G` with
+/// unknown discrete logarithms, derived from a public seed.
+#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct IpaParams<G: AffineCurve> {
+    u: G,
+    generators: Vec<G>,
+}
+
+/// The polynomial commitment scheme committing to the coefficients with a
+/// Pedersen vector commitment, and opening with an inner-product argument.
+/// It needs no trusted setup nor pairing, hence works over any curve `G`,
+/// e.g. the Pasta curves. It serves both as the committing and the opening
+/// key.
+///
+/// The opening proofs reveal the folded coefficients of the opened
+/// polynomial, hence the Plonk proofs over it are not zero-knowledge, see
+/// [`crate::proof_system::PlonkPcsSnark::prove_without_zero_knowledge`].
+#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct IpaPcs<G: AffineCurve> {
+    max_degree: usize,
+    u: G,
+    // a power of two of them
+    generators: Vec<G>,
+}
+
+/// An opening proof of [`IpaPcs`]: the cross terms of each halving round of
+/// the argument, and the last folded coefficient.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct IpaProof<G: AffineCurve> {
+    l: Vec<G>,
+    r: Vec<G>,
+    a: G::ScalarField,
+}
+
+impl<G: AffineCurve> IpaPcs<G> {
+    fn check_degree(&self, poly: &DensePolynomial<G::ScalarField>) -> Result<(), PlonkError> {
+        if poly.degree() > self.max_degree {
+            return Err(ParameterError(format!(
+                "the degree {} of the polynomial exceeds the maximal degree {}",
+                poly.degree(),
+                self.max_degree
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Append the opening point and the evaluation to `transcript`, and
+    /// return the generator binding the inner product, rescaled by a
+    /// challenge.
+    fn evaluation_generator<T: PlonkTranscript<G::BaseField>>(
+        &self,
+        transcript: &mut T,
+        point: &G::ScalarField,
+        value: &G::ScalarField,
+    ) -> Result<G::Projective, PlonkError> {
+        transcript.append_field_challenge(b"ipa_point", point)?;
+        transcript.append_field_challenge(b"ipa_value", value)?;
+        let xi = transcript.get_and_append_field_challenge::<G::ScalarField>(b"ipa_xi")?;
+        Ok(self.u.mul(xi.into_repr()))
+    }
+}
+
+/// Append the cross terms of a round to `transcript`, and derive the
+/// challenge folding the round.
+fn ipa_round_challenge<G, T>(transcript: &mut T, l: &G, r: &G) -> Result<G::ScalarField, PlonkError>
+where
+    G: AffineCurve,
+    T: PlonkTranscript<G::BaseField>,
+{
+    let mut bytes = Vec::new();
+    l.serialize(&mut bytes)?;
+    transcript.append_message(b"ipa_l", &bytes)?;
+    bytes.clear();
+    r.serialize(&mut bytes)?;
+    transcript.append_message(b"ipa_r", &bytes)?;
+    transcript.get_and_append_field_challenge(b"ipa_x")
+}
+
+/// The powers `1, point, ..., point^(n-1)`.
+fn ipa_powers<F: Field>(point: &F, n: usize) -> Vec<F> {
+    let mut powers = Vec::with_capacity(n);
+    let mut power = F::one();
+    for _ in 0..n {
+        powers.push(power);
+        power *= point;
+    }
+    powers
+}
+
+fn ipa_inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter()
+        .zip(b.iter())
+        .fold(F::zero(), |acc, (a_i, b_i)| acc + *a_i * b_i)
+}
+
+fn ipa_msm<G: AffineCurve>(bases: &[G], scalars: &[G::ScalarField]) -> G::Projective {
+    let scalars_repr: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+    VariableBaseMSM::multi_scalar_mul(bases, &scalars_repr)
+}
+
+impl<G: AffineCurve> PolynomialCommitmentScheme for IpaPcs<G> {
+    type Fr = G::ScalarField;
+    type Curve = G;
+    type Params = IpaParams<G>;
+    type OpenKey = IpaPcs<G>;
+    type Commitment = G;
+    type Proof = IpaProof<G>;
+
+    fn setup<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        _rng: &mut R,
+    ) -> Result<IpaParams<G>, PlonkError> {
+        // The generators are hashed to the curve from a public seed, so that
+        // the parameters for a smaller degree are a prefix of the ones for a
+        // larger degree.
+        let mut seed_rng = ChaChaRng::from_seed(Keccak256::digest(IPA_GENERATORS_LABEL).into());
+        let mut sample = || loop {
+            let mut bytes = [0u8; 128];
+            seed_rng.fill_bytes(&mut bytes);
+            if let Some(point) = G::from_random_bytes(&bytes) {
+                let point = point.mul_by_cofactor();
+                if !point.is_zero() {
+                    break point;
+                }
+            }
+        };
+        let u = sample();
+        let generators = (0..(max_degree + 1).next_power_of_two())
+            .map(|_| sample())
+            .collect();
+        Ok(IpaParams { u, generators })
+    }
+
+    fn commitment_to_point(comm: &G) -> G {
+        *comm
+    }
+
+    fn commitment_from_point(point: G) -> G {
+        point
+    }
+
+    fn proof_points(proof: &IpaProof<G>) -> Vec<G> {
+        proof.l.iter().chain(proof.r.iter()).copied().collect()
+    }
+
+    fn proof_scalars(proof: &IpaProof<G>) -> Vec<G::ScalarField> {
+        vec![proof.a]
+    }
+
+    fn verify<T: PlonkTranscript<G::BaseField>>(
+        open_key: &IpaPcs<G>,
+        transcript: &mut T,
+        comm: &G,
+        point: &G::ScalarField,
+        value: &G::ScalarField,
+        proof: &IpaProof<G>,
+    ) -> Result<bool, PlonkError> {
+        let n = open_key.generators.len();
+        let num_rounds = n.trailing_zeros() as usize;
+        if proof.l.len() != num_rounds || proof.r.len() != num_rounds {
+            return Err(ParameterError(format!(
+                "the proof has {} and {} cross terms instead of {}",
+                proof.l.len(),
+                proof.r.len(),
+                num_rounds
+            ))
+            .into());
+        }
+        let u = open_key.evaluation_generator(transcript, point, value)?;
+        let challenges = proof
+            .l
+            .iter()
+            .zip(proof.r.iter())
+            .map(|(l_j, r_j)| ipa_round_challenge(transcript, l_j, r_j))
+            .collect::<Result<Vec<_>, _>>()?;
+        let challenge_invs = challenges
+            .iter()
+            .map(|x| x.inverse().ok_or(PlonkError::DivisionError))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The folded generator is \sum_i s_i g_i, where s_i is the product of
+        // x_j or x_j^-1 depending on whether g_i was in the higher or the
+        // lower half at round j.
+        let mut s = vec![G::ScalarField::one()];
+        for (x, x_inv) in challenges.iter().zip(challenge_invs.iter()).rev() {
+            s = s
+                .iter()
+                .map(|s_i| *s_i * x_inv)
+                .chain(s.iter().map(|s_i| *s_i * x))
+                .collect();
+        }
+        let b = ipa_inner_product(&s, &ipa_powers(point, n));
+
+        // comm + (value - a * b) * u + \sum_j (x_j^2 l_j + x_j^-2 r_j)
+        //   - a * \sum_i s_i g_i == 0
+        let mut bases: Vec<G> = Vec::with_capacity(1 + 2 * num_rounds + n);
+        let mut scalars = Vec::with_capacity(1 + 2 * num_rounds + n);
+        bases.push(*comm);
+        scalars.push(G::ScalarField::one());
+        for (j, (l_j, r_j)) in proof.l.iter().zip(proof.r.iter()).enumerate() {
+            bases.push(*l_j);
+            scalars.push(challenges[j].square());
+            bases.push(*r_j);
+            scalars.push(challenge_invs[j].square());
+        }
+        bases.extend_from_slice(&open_key.generators);
+        scalars.extend(s.iter().map(|s_i| -proof.a * s_i));
+        let check = ipa_msm(&bases, &scalars) + u.mul((*value - proof.a * b).into_repr());
+        Ok(check.is_zero())
+    }
+}
+
+impl<'a, G: AffineCurve> PcsCommitter<'a> for IpaPcs<G> {
+    type CommitKey = IpaPcs<G>;
+
+    fn trim(
+        params: &'a IpaParams<G>,
+        max_degree: usize,
+    ) -> Result<(IpaPcs<G>, IpaPcs<G>), PlonkError> {
+        let n = (max_degree + 1).next_power_of_two();
+        if params.generators.len() < n {
+            return Err(PlonkError::SrsTooSmall {
+                required: max_degree,
+                provided: params.generators.len() - 1,
+            });
+        }
+        let pcs = Self {
+            max_degree,
+            u: params.u,
+            generators: params.generators[..n].to_vec(),
+        };
+        Ok((pcs.clone(), pcs))
+    }
+
+    fn commit_bases(commit_key: &IpaPcs<G>) -> &[G] {
+        &commit_key.generators
+    }
+
+    fn commit(
+        commit_key: &IpaPcs<G>,
+        poly: &DensePolynomial<G::ScalarField>,
+    ) -> Result<G, PlonkError> {
+        commit_key.check_degree(poly)?;
+        let comm = ipa_msm(&commit_key.generators[..poly.coeffs.len()], &poly.coeffs);
+        Ok(comm.into_affine())
+    }
+
+    fn open<T: PlonkTranscript<G::BaseField>>(
+        commit_key: &IpaPcs<G>,
+        transcript: &mut T,
+        poly: &DensePolynomial<G::ScalarField>,
+        point: &G::ScalarField,
+        msm: &dyn MsmBackend<G>,
+    ) -> Result<(G::ScalarField, IpaProof<G>), PlonkError> {
+        commit_key.check_degree(poly)?;
+        let n = commit_key.generators.len();
+        let value = poly.evaluate(point);
+        let u = commit_key
+            .evaluation_generator(transcript, point, &value)?
+            .into_affine();
+
+        // Fold a = coeffs, b = powers of the point and the generators in
+        // halves until a single coefficient is left, keeping the invariant
+        // <a, g> + <a, b> * u = comm + value * u + \sum_j (x_j^2 l_j + x_j^-2
+        // r_j).
+        let mut a = poly.coeffs.clone();
+        a.resize(n, G::ScalarField::zero());
+        let mut b = ipa_powers(point, n);
+        let mut g = commit_key.generators.clone();
+        let mut l = Vec::new();
+        let mut r = Vec::new();
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let (a_lo, a_hi) = a.split_at(half);
+            let (b_lo, b_hi) = b.split_at(half);
+            let (g_lo, g_hi) = g.split_at(half);
+
+            let l_j = (msm.msm(g_hi, a_lo) + u.mul(ipa_inner_product(a_lo, b_hi).into_repr()))
+                .into_affine();
+            let r_j = (msm.msm(g_lo, a_hi) + u.mul(ipa_inner_product(a_hi, b_lo).into_repr()))
+                .into_affine();
+            let x = ipa_round_challenge(transcript, &l_j, &r_j)?;
+            let x_inv = x.inverse().ok_or(PlonkError::DivisionError)?;
+
+            let new_a = a_lo
+                .iter()
+                .zip(a_hi.iter())
+                .map(|(lo, hi)| *lo * x + *hi * x_inv)
+                .collect();
+            let new_b = b_lo
+                .iter()
+                .zip(b_hi.iter())
+                .map(|(lo, hi)| *lo * x_inv + *hi * x)
+                .collect();
+            let new_g: Vec<_> = g_lo
+                .iter()
+                .zip(g_hi.iter())
+                .map(|(lo, hi)| lo.mul(x_inv.into_repr()) + hi.mul(x.into_repr()))
+                .collect();
+            a = new_a;
+            b = new_b;
+            g = G::Projective::batch_normalization_into_affine(&new_g);
+            l.push(l_j);
+            r.push(r_j);
+        }
+        Ok((value, IpaProof { l, r, a: a[0] }))
+    }
+}
+
+/// Append the opening point and the evaluations to `transcript`, and derive
+/// the challenge combining the opened polynomials.
+fn batch_open_challenge<E, F, T>(
+    transcript: &mut T,
+    point: &E::Fr,
+    evals: &[E::Fr],
+) -> Result<E::Fr, PlonkError>
+where
+    E: PairingEngine<Fq = F>,
+    T: PlonkTranscript<F>,
+{
+    transcript.append_challenge::<E>(b"batch_open_point", point)?;
+    for eval in evals.iter() {
+        transcript.append_challenge::<E>(b"batch_open_eval", eval)?;
+    }
+    transcript.get_and_append_challenge::<E>(b"batch_open_v")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        proof_system::{msm::ArkworksMsm, PlonkKzgSnark},
+        transcript::{RescueTranscript, StandardTranscript},
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::{Bn254, G1Affine};
+    use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
+    use ark_std::{test_rng, UniformRand};
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_batch_open() -> Result<(), PlonkError> {
+        test_batch_open_helper::<Bn254, _, _, StandardTranscript>()?;
+        test_batch_open_helper::<Bls12_381, _, _, StandardTranscript>()?;
+        test_batch_open_helper::<Bls12_377, _, _, RescueTranscript<_>>()
+    }
+
+    #[test]
+    fn test_pcs_trait() -> Result<(), PlonkError> {
+        test_pcs_helper::<_, _, StandardTranscript, KzgPcs<Bn254>>()?;
+        test_pcs_helper::<_, _, StandardTranscript, KzgPcs<Bls12_381>>()?;
+        test_pcs_helper::<_, _, RescueTranscript<_>, KzgPcs<Bls12_377>>()?;
+        test_pcs_helper::<_, _, StandardTranscript, IpaPcs<G1Affine>>()?;
+        test_pcs_helper::<_, _, StandardTranscript, IpaPcs<ark_bls12_381::G1Affine>>()?;
+        test_pcs_helper::<_, _, RescueTranscript<_>, IpaPcs<ark_bls12_377::G1Affine>>()
+    }
+
+    fn test_pcs_helper<F, P, T, PCS>() -> Result<(), PlonkError>
+    where
+        F: RescueParameter,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>> + for<'a> PcsCommitter<'a>,
+    {
+        let rng = &mut test_rng();
+        let max_degree = 20;
+        let params = PCS::setup(max_degree, rng)?;
+        let (commit_key, open_key) = PCS::trim(&params, max_degree)?;
+
+        for degree in [1, 7, max_degree] {
+            let poly = DensePolynomial::<PCS::Fr>::rand(degree, rng);
+            let comm = PCS::commit(&commit_key, &poly)?;
+            let point = PCS::Fr::rand(rng);
+            let new_transcript = || -> Result<T, PlonkError> {
+                let mut transcript = T::new(b"pcs test");
+                transcript.append_pcs_commitment::<PCS, _>(b"comm", &comm)?;
+                Ok(transcript)
+            };
+
+            let (value, proof) = PCS::open(
+                &commit_key,
+                &mut new_transcript()?,
+                &poly,
+                &point,
+                &ArkworksMsm,
+            )?;
+            assert_eq!(poly.evaluate(&point), value);
+            assert!(PCS::verify(
+                &open_key,
+                &mut new_transcript()?,
+                &comm,
+                &point,
+                &value,
+                &proof
+            )?);
+
+            // serialization roundtrip
+            let mut bytes = Vec::new();
+            proof.serialize(&mut bytes)?;
+            assert_eq!(PCS::Proof::deserialize(&bytes[..])?, proof);
+
+            // bad path: wrong evaluation, point and commitment
+            assert!(!PCS::verify(
+                &open_key,
+                &mut new_transcript()?,
+                &comm,
+                &point,
+                &(value + PCS::Fr::one()),
+                &proof
+            )?);
+            assert!(!PCS::verify(
+                &open_key,
+                &mut new_transcript()?,
+                &comm,
+                &(point + PCS::Fr::one()),
+                &value,
+                &proof
+            )?);
+            let other_comm = PCS::commit(&commit_key, &DensePolynomial::rand(degree, rng))?;
+            assert!(!PCS::verify(
+                &open_key,
+                &mut new_transcript()?,
+                &other_comm,
+                &point,
+                &value,
+                &proof
+            )?);
+        }
+
+        // bad path: degree too large
+        let large_poly = DensePolynomial::<PCS::Fr>::rand(max_degree + 1, rng);
+        assert!(PCS::commit(&commit_key, &large_poly).is_err());
+        assert!(PCS::open(
+            &commit_key,
+            &mut T::new(b"pcs test"),
+            &large_poly,
+            &PCS::Fr::rand(rng),
+            &ArkworksMsm
+        )
+        .is_err());
+        assert!(PCS::trim(&params, 2 * max_degree + 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ipa_proof_size_and_params() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let max_degree = 100;
+        let params = IpaPcs::<G1Affine>::setup(max_degree, rng)?;
+        let (commit_key, open_key) = IpaPcs::trim(&params, max_degree)?;
+        let poly = DensePolynomial::rand(max_degree, rng);
+        let comm = IpaPcs::commit(&commit_key, &poly)?;
+        let point = UniformRand::rand(rng);
+        let (value, proof) = IpaPcs::open(
+            &commit_key,
+            &mut StandardTranscript::new(b"ipa test"),
+            &poly,
+            &point,
+            &ArkworksMsm,
+        )?;
+        // 128 generators, hence 7 rounds.
+        assert_eq!(proof.l.len(), 7);
+        assert_eq!(proof.r.len(), 7);
+        assert_eq!(IpaPcs::proof_points(&proof).len(), 14);
+        assert!(IpaPcs::verify(
+            &open_key,
+            &mut StandardTranscript::new(b"ipa test"),
+            &comm,
+            &point,
+            &value,
+            &proof
+        )?);
+
+        // bad path: the challenges are derived from the transcript
+        assert!(!IpaPcs::verify(
+            &open_key,
+            &mut StandardTranscript::new(b"other ipa test"),
+            &comm,
+            &point,
+            &value,
+            &proof
+        )?);
+
+        // The parameters are transparent: they don't depend on the randomness,
+        // and trimming the larger ones agrees with a smaller setup.
+        let small_params = IpaPcs::<G1Affine>::setup(10, rng)?;
+        assert_eq!(small_params.u, params.u);
+        assert_eq!(small_params.generators[..], params.generators[..16]);
+        let poly = DensePolynomial::rand(10, rng);
+        assert_eq!(
+            IpaPcs::commit(&IpaPcs::trim(&small_params, 10)?.0, &poly)?,
+            IpaPcs::commit(&IpaPcs::trim(&params, 10)?.0, &poly)?
+        );
+        Ok(())
+    }
+
+    fn test_batch_open_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let max_degree = 32;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let pcs = KzgPcs::new(&srs, max_degree)?;
+
+        let polys: Vec<_> = (0..4)
+            .map(|_| DensePolynomial::<E::Fr>::rand(max_degree, rng))
+            .collect();
+        let comms = polys
+            .iter()
+            .map(|poly| pcs.commit(poly))
+            .collect::<Result<Vec<_>, _>>()?;
+        let point = E::Fr::rand(rng);
+        let new_transcript = || -> Result<T, PlonkError> {
+            let mut transcript = T::new(b"batch open test");
+            transcript.append_commitments(b"comms", &comms)?;
+            Ok(transcript)
+        };
+
+        let (evals, proof) = pcs.open_batch(&mut new_transcript()?, &polys, &point)?;
+        for (poly, eval) in polys.iter().zip(evals.iter()) {
+            assert_eq!(poly.evaluate(&point), *eval);
+        }
+        assert!(verify_batch_open(
+            pcs.open_key(),
+            &mut new_transcript()?,
+            &comms,
+            &point,
+            &evals,
+            &proof
+        )?);
+
+        // bad path: wrong evaluation
+        let mut bad_evals = evals.clone();
+        bad_evals[2] += E::Fr::one();
+        assert!(!verify_batch_open(
+            pcs.open_key(),
+            &mut new_transcript()?,
+            &comms,
+            &point,
+            &bad_evals,
+            &proof
+        )?);
+
+        // bad path: wrong point
+        assert!(!verify_batch_open(
+            pcs.open_key(),
+            &mut new_transcript()?,
+            &comms,
+            &(point + E::Fr::one()),
+            &evals,
+            &proof
+        )?);
+
+        // bad path: swapped commitments
+        let mut bad_comms = comms.clone();
+        bad_comms.swap(0, 1);
+        assert!(!verify_batch_open(
+            pcs.open_key(),
+            &mut new_transcript()?,
+            &bad_comms,
+            &point,
+            &evals,
+            &proof
+        )?);
+
+        // bad path: different transcript
+        let mut other_transcript = T::new(b"other batch open test");
+        assert!(!verify_batch_open(
+            pcs.open_key(),
+            &mut other_transcript,
+            &comms,
+            &point,
+            &evals,
+            &proof
+        )?);
+
+        // bad path: inconsistent lengths, no polynomial, degree too large
+        assert!(verify_batch_open(
+            pcs.open_key(),
+            &mut new_transcript()?,
+            &comms,
+            &point,
+            &evals[1..],
+            &proof
+        )
+        .is_err());
+        assert!(pcs.open_batch(&mut new_transcript()?, &[], &point).is_err());
+        let large_poly = DensePolynomial::<E::Fr>::rand(max_degree + 1, rng);
+        assert!(pcs.commit(&large_poly).is_err());
+        assert!(KzgPcs::new(&srs, max_degree + 1).is_err());
+
+        Ok(())
+    }
+}
@@ -7,26 +7,29 @@
 use core::ops::Neg;
 
 use super::structs::{
-    eval_merged_lookup_witness, eval_merged_table, Challenges, Oracles, PlookupEvaluations,
-    PlookupOracles, ProofEvaluations, ProvingKey,
+    eval_merged_lookup_witness, eval_merged_table, masked_quotient_polynomial_degree,
+    quotient_chunk_size, Challenges, Oracles, PcsProvingKey, PlookupEvaluations, PlookupOracles,
+    ProofEvaluations,
 };
 use crate::{
-    circuit::Arithmetization,
-    constants::{domain_size_ratio, GATE_WIDTH},
+    circuit::{gates::CustomGateSpec, Arithmetization},
+    constants::{domain_size_ratio, GATE_WIDTH, N_TURBO_PLONK_SELECTORS},
     errors::{PlonkError, SnarkError::*},
-    proof_system::structs::CommitKey,
+    profiling::{Operation, ProfileRecorder},
+    proof_system::{
+        msm::{ArkworksMsm, MsmBackend},
+        pcs::{commit_with_msm, PcsCommitter, PolynomialCommitmentScheme},
+    },
+    transcript::PlonkTranscript,
 };
-use ark_ec::PairingEngine;
-use ark_ff::{FftField, Field, One, Zero};
+use ark_ec::AffineCurve;
+use ark_ff::{FftField, Field, One, UniformRand, Zero};
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
     Radix2EvaluationDomain, UVPolynomial,
 };
-use ark_poly_commit::{
-    kzg10::{Commitment, Randomness, KZG10},
-    PCRandomness,
-};
 use ark_std::{
+    borrow::Cow,
     rand::{CryptoRng, RngCore},
     string::ToString,
     vec,
@@ -34,54 +37,287 @@ use ark_std::{
 };
 use rayon::prelude::*;
 
-type CommitmentsAndPolys<E> = (
-    Vec<Commitment<E>>,
-    Vec<DensePolynomial<<E as PairingEngine>::Fr>>,
+type CommitmentsAndPolys<PCS> = (
+    Vec<<PCS as PolynomialCommitmentScheme>::Commitment>,
+    Vec<DensePolynomial<<PCS as PolynomialCommitmentScheme>::Fr>>,
 );
 
+/// The wire witness polynomials of a circuit given to the prover, instead of
+/// being interpolated from its witness.
+pub(crate) enum WirePolys<PCS: PolynomialCommitmentScheme> {
+    /// The polynomials before masking.
+    Unmasked(Vec<DensePolynomial<PCS::Fr>>),
+    /// The masked polynomials along with their commitments, computed in an
+    /// earlier commitment phase.
+    Committed(CommitmentsAndPolys<PCS>),
+}
+
+/// A source of the scalars blinding the polynomials of the prover, which are
+/// sampled from an RNG unless given explicitly.
+pub(crate) trait Blinder<F> {
+    /// The next blinding scalar.
+    fn blinding_scalar(&mut self) -> F;
+
+    /// Whether the polynomials are masked at all. If not, no blinding scalar
+    /// is drawn.
+    fn is_hiding(&self) -> bool {
+        true
+    }
+}
+
+impl<F: UniformRand, R: CryptoRng + RngCore> Blinder<F> for R {
+    fn blinding_scalar(&mut self) -> F {
+        F::rand(self)
+    }
+}
+
+/// Explicit blinding scalars, consumed in order.
+#[cfg(any(test, feature = "test-rng"))]
+pub(crate) struct ExplicitBlinding<'b, F>(pub(crate) core::slice::Iter<'b, F>);
+
+#[cfg(any(test, feature = "test-rng"))]
+impl<'b, F: Copy> Blinder<F> for ExplicitBlinding<'b, F> {
+    fn blinding_scalar(&mut self) -> F {
+        // safe unwrap: the number of blinding scalars is checked in advance
+        *self.0.next().unwrap()
+    }
+}
+
+/// No blinding at all: the polynomials are left unmasked, so that the proof
+/// is not zero-knowledge.
+pub(crate) struct NoBlinding;
+
+impl<F: Zero> Blinder<F> for NoBlinding {
+    fn blinding_scalar(&mut self) -> F {
+        F::zero()
+    }
+
+    fn is_hiding(&self) -> bool {
+        false
+    }
+}
+
+/// The number of scalars blinding the polynomials of a proof for a circuit
+/// with `num_wire_types` wire types, with or without lookup: 2 per wire
+/// polynomial, 3 for the permutation product polynomial, and 3 for each of
+/// the 3 Plookup polynomials if any.
+#[cfg(any(test, feature = "test-rng"))]
+pub(crate) fn num_blinding_scalars(num_wire_types: usize, support_lookup: bool) -> usize {
+    let num_lookup_polys = if support_lookup { 3 } else { 0 };
+    2 * num_wire_types + 3 + 3 * num_lookup_polys
+}
+
+/// The evaluations of the polynomials of an instance that enter the
+/// quotient polynomial, on some coset. Those of the proving key polynomials
+/// may be borrowed from a precomputation.
+struct CosetEvals<'k, F: Clone> {
+    selectors: Cow<'k, [Vec<F>]>,
+    sigmas: Cow<'k, [Vec<F>]>,
+    wires: Vec<Vec<F>>,
+    prod_perm: Vec<F>,
+    pub_input: Vec<F>,
+    plookup: Option<PlookupCosetEvals<'k, F>>,
+}
+
+/// The evaluations of the Plookup polynomials that enter the quotient
+/// polynomial, on some coset.
+struct PlookupCosetEvals<'k, F: Clone> {
+    range_table: Cow<'k, [F]>,
+    key_table: Cow<'k, [F]>,
+    h: Vec<Vec<F>>,
+    prod_lookup: Vec<F>,
+}
+
+/// The evaluations of the polynomials of a proving key that enter the
+/// quotient polynomial, over the whole quotient coset. They don't depend on
+/// the witness, hence are computed once for the proofs of many witnesses
+/// w.r.t. the same proving key.
+pub(crate) struct KeyCosetEvals<F> {
+    selectors: Vec<Vec<F>>,
+    sigmas: Vec<Vec<F>>,
+    // the range and key tables, with lookup
+    tables: Option<(Vec<F>, Vec<F>)>,
+}
+
 /// A Plonk IOP prover.
-pub(crate) struct Prover<E: PairingEngine> {
-    domain: Radix2EvaluationDomain<E::Fr>,
-    quot_domain: GeneralEvaluationDomain<E::Fr>,
+pub(crate) struct Prover<'a, PCS: PolynomialCommitmentScheme> {
+    domain: Radix2EvaluationDomain<PCS::Fr>,
+    quot_domain: GeneralEvaluationDomain<PCS::Fr>,
+    profile: ProfileRecorder,
+    msm: &'a dyn MsmBackend<PCS::Curve>,
+    hiding: bool,
+    num_extra_openings: usize,
+    memory_budget: Option<usize>,
+    key_coset_evals: Option<&'a KeyCosetEvals<PCS::Fr>>,
 }
 
-impl<E: PairingEngine> Prover<E> {
+impl<'a, PCS: PolynomialCommitmentScheme> Prover<'a, PCS> {
     /// Construct a Plonk prover that uses a domain with size `domain_size` and
     /// quotient polynomial domain with a size that is larger than the degree of
     /// the quotient polynomial.
     /// * `num_wire_types` - number of wire types in the corresponding
     ///   constraint system.
     pub(crate) fn new(domain_size: usize, num_wire_types: usize) -> Result<Self, PlonkError> {
-        let domain = Radix2EvaluationDomain::<E::Fr>::new(domain_size)
+        let domain = Radix2EvaluationDomain::<PCS::Fr>::new(domain_size)
             .ok_or(PlonkError::DomainCreationError)?;
-        let quot_domain = GeneralEvaluationDomain::<E::Fr>::new(
+        let quot_domain = GeneralEvaluationDomain::<PCS::Fr>::new(
             domain_size * domain_size_ratio(domain_size, num_wire_types),
         )
         .ok_or(PlonkError::DomainCreationError)?;
         Ok(Self {
             domain,
             quot_domain,
+            profile: ProfileRecorder::default(),
+            msm: &ArkworksMsm,
+            hiding: true,
+            num_extra_openings: 0,
+            memory_budget: None,
+            key_coset_evals: None,
         })
     }
 
+    /// Record the durations of the operations of the prover in `profile`.
+    pub(crate) fn with_profile(mut self, profile: ProfileRecorder) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Compute the MSMs of the polynomial commitments with `msm`.
+    pub(crate) fn with_msm_backend(mut self, msm: &'a dyn MsmBackend<PCS::Curve>) -> Self {
+        self.msm = msm;
+        self
+    }
+
+    /// Mask the polynomials only if `hiding`. Without masking, the quotient
+    /// polynomial has a smaller degree than usual.
+    pub(crate) fn with_hiding(mut self, hiding: bool) -> Self {
+        self.hiding = hiding;
+        self
+    }
+
+    /// Mask the wire witness polynomials so that they stay hidden after
+    /// `num_extra_openings` openings besides the ones of the proof. The
+    /// quotient polynomial of the `num_wire_types` wire types grows by
+    /// `num_wire_types` coefficients per extra opening, and the quotient
+    /// domain accordingly.
+    pub(crate) fn with_extra_openings(
+        mut self,
+        num_wire_types: usize,
+        num_extra_openings: usize,
+    ) -> Result<Self, PlonkError> {
+        let domain_size = self.domain.size();
+        let quot_degree =
+            masked_quotient_polynomial_degree(domain_size, num_wire_types, num_extra_openings);
+        self.quot_domain =
+            GeneralEvaluationDomain::<PCS::Fr>::new(domain_size * (quot_degree / domain_size + 1))
+                .ok_or(PlonkError::DomainCreationError)?;
+        self.num_extra_openings = num_extra_openings;
+        Ok(self)
+    }
+
+    /// Evaluate the quotient polynomial by cosets of the evaluation domain,
+    /// with their evaluations taking about `memory_budget` bytes, if given.
+    pub(crate) fn with_memory_budget(mut self, memory_budget: Option<usize>) -> Self {
+        self.memory_budget = memory_budget;
+        self
+    }
+
+    /// Reuse the precomputed coset evaluations `key_coset_evals` of the
+    /// polynomials of the proving key, if given, instead of recomputing them.
+    /// They are only used when the quotient polynomial is evaluated over the
+    /// whole quotient coset at once, i.e. without memory budget.
+    /// Return error if they were computed over another quotient domain.
+    pub(crate) fn with_key_coset_evals(
+        mut self,
+        key_coset_evals: Option<&'a KeyCosetEvals<PCS::Fr>>,
+    ) -> Result<Self, PlonkError> {
+        if let Some(evals) = key_coset_evals {
+            if evals
+                .sigmas
+                .iter()
+                .any(|e| e.len() != self.quot_domain.size())
+            {
+                return Err(ParameterError(
+                    "precomputed coset evaluations of a different domain".to_string(),
+                )
+                .into());
+            }
+        }
+        self.key_coset_evals = key_coset_evals;
+        Ok(self)
+    }
+}
+
+impl<'a, 'c, PCS: PcsCommitter<'c>> Prover<'a, PCS> {
+    /// Compute the evaluations of the polynomials of `pk` over the quotient
+    /// coset, to be shared by the proofs w.r.t. `pk`, see
+    /// [`Self::with_key_coset_evals`].
+    pub(crate) fn compute_key_coset_evals(
+        &self,
+        pk: &PcsProvingKey<'c, PCS>,
+    ) -> KeyCosetEvals<PCS::Fr> {
+        let selectors = pk
+            .selectors
+            .par_iter()
+            .map(|poly| self.coset_fft(poly.coeffs()))
+            .collect();
+        let sigmas = pk
+            .sigmas
+            .par_iter()
+            .map(|poly| self.coset_fft(poly.coeffs()))
+            .collect();
+        let tables = pk.plookup_pk.as_ref().map(|plookup_pk| {
+            (
+                self.coset_fft(plookup_pk.range_table_poly.coeffs()),
+                self.coset_fft(plookup_pk.key_table_poly.coeffs()),
+            )
+        });
+        KeyCosetEvals {
+            selectors,
+            sigmas,
+            tables,
+        }
+    }
+
     /// Round 1:
     /// 1. Compute and commit wire witness polynomials.
     /// 2. Compute public input polynomial.
     /// Return the wire witness polynomials and their commitments,
     /// also return the public input polynomial.
-    pub(crate) fn run_1st_round<C: Arithmetization<E::Fr>, R: CryptoRng + RngCore>(
+    pub(crate) fn run_1st_round<C: Arithmetization<PCS::Fr>, R: Blinder<PCS::Fr>>(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &PCS::CommitKey,
         cs: &C,
-    ) -> Result<(CommitmentsAndPolys<E>, DensePolynomial<E::Fr>), PlonkError> {
-        let wire_polys: Vec<DensePolynomial<E::Fr>> = cs
-            .compute_wire_polynomials()?
+    ) -> Result<(CommitmentsAndPolys<PCS>, DensePolynomial<PCS::Fr>), PlonkError> {
+        let wire_polys = self
+            .profile
+            .record(Operation::Fft, || cs.compute_wire_polynomials())?;
+        self.run_1st_round_with_wire_polys(prng, ck, cs, wire_polys)
+    }
+
+    /// Round 1 with the (unmasked) wire witness polynomials `wire_polys`
+    /// precomputed by the caller.
+    pub(crate) fn run_1st_round_with_wire_polys<
+        C: Arithmetization<PCS::Fr>,
+        R: Blinder<PCS::Fr>,
+    >(
+        &self,
+        prng: &mut R,
+        ck: &PCS::CommitKey,
+        cs: &C,
+        wire_polys: Vec<DensePolynomial<PCS::Fr>>,
+    ) -> Result<(CommitmentsAndPolys<PCS>, DensePolynomial<PCS::Fr>), PlonkError> {
+        let wire_polys: Vec<DensePolynomial<PCS::Fr>> = wire_polys
             .into_iter()
-            .map(|poly| self.mask_polynomial(prng, poly, 1))
+            .map(|poly| self.mask_polynomial(prng, poly, self.num_extra_openings + 1))
             .collect();
-        let wires_poly_comms = Self::commit_polynomials(ck, &wire_polys)?;
-        let pub_input_poly = cs.compute_pub_input_polynomial()?;
+        let wires_poly_comms = self
+            .profile
+            .record(Operation::Msm, || self.commit_polynomials(ck, &wire_polys))?;
+        let pub_input_poly = self
+            .profile
+            .record(Operation::Fft, || cs.compute_pub_input_polynomial())?;
         Ok(((wires_poly_comms, wire_polys), pub_input_poly))
     }
 
@@ -91,53 +327,57 @@ impl<E: PairingEngine> Prover<E> {
     /// polynomials and their commitments, as well as the merged lookup table.
     /// `cs` is guaranteed to support lookup.
     #[allow(clippy::type_complexity)]
-    pub(crate) fn run_plookup_1st_round<C: Arithmetization<E::Fr>, R: CryptoRng + RngCore>(
+    pub(crate) fn run_plookup_1st_round<C: Arithmetization<PCS::Fr>, R: Blinder<PCS::Fr>>(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &PCS::CommitKey,
         cs: &C,
-        tau: E::Fr,
-    ) -> Result<(CommitmentsAndPolys<E>, Vec<E::Fr>, Vec<E::Fr>), PlonkError> {
+        tau: PCS::Fr,
+    ) -> Result<(CommitmentsAndPolys<PCS>, Vec<PCS::Fr>, Vec<PCS::Fr>), PlonkError> {
         let merged_lookup_table = cs.compute_merged_lookup_table(tau)?;
         let (sorted_vec, h_1_poly, h_2_poly) =
             cs.compute_lookup_sorted_vec_polynomials(tau, &merged_lookup_table)?;
         let h_1_poly = self.mask_polynomial(prng, h_1_poly, 2);
         let h_2_poly = self.mask_polynomial(prng, h_2_poly, 2);
         let h_polys = vec![h_1_poly, h_2_poly];
-        let h_poly_comms = Self::commit_polynomials(ck, &h_polys)?;
+        let h_poly_comms = self
+            .profile
+            .record(Operation::Msm, || self.commit_polynomials(ck, &h_polys))?;
         Ok(((h_poly_comms, h_polys), sorted_vec, merged_lookup_table))
     }
 
     /// Round 2: Compute and commit the permutation grand product polynomial.
     /// Return the grand product polynomial and its commitment.
-    pub(crate) fn run_2nd_round<C: Arithmetization<E::Fr>, R: CryptoRng + RngCore>(
+    pub(crate) fn run_2nd_round<C: Arithmetization<PCS::Fr>, R: Blinder<PCS::Fr>>(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &PCS::CommitKey,
         cs: &C,
-        challenges: &Challenges<E::Fr>,
-    ) -> Result<(Commitment<E>, DensePolynomial<E::Fr>), PlonkError> {
+        challenges: &Challenges<PCS::Fr>,
+    ) -> Result<(PCS::Commitment, DensePolynomial<PCS::Fr>), PlonkError> {
         let prod_perm_poly = self.mask_polynomial(
             prng,
             cs.compute_prod_permutation_polynomial(&challenges.beta, &challenges.gamma)?,
             2,
         );
-        let prod_perm_comm = Self::commit_polynomial(ck, &prod_perm_poly)?;
+        let prod_perm_comm = self.profile.record(Operation::Msm, || {
+            self.commit_polynomial(ck, &prod_perm_poly)
+        })?;
         Ok((prod_perm_comm, prod_perm_poly))
     }
 
     /// Round 2.5 (Plookup): Compute and commit the Plookup grand product
     /// polynomial. Return the grand product polynomial and its commitment.
     /// `cs` is guaranteed to support lookup
-    pub(crate) fn run_plookup_2nd_round<C: Arithmetization<E::Fr>, R: CryptoRng + RngCore>(
+    pub(crate) fn run_plookup_2nd_round<C: Arithmetization<PCS::Fr>, R: Blinder<PCS::Fr>>(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &PCS::CommitKey,
         cs: &C,
-        challenges: &Challenges<E::Fr>,
-        merged_lookup_table: Option<&Vec<E::Fr>>,
-        sorted_vec: Option<&Vec<E::Fr>>,
-    ) -> Result<(Commitment<E>, DensePolynomial<E::Fr>), PlonkError> {
+        challenges: &Challenges<PCS::Fr>,
+        merged_lookup_table: Option<&Vec<PCS::Fr>>,
+        sorted_vec: Option<&Vec<PCS::Fr>>,
+    ) -> Result<(PCS::Commitment, DensePolynomial<PCS::Fr>), PlonkError> {
         if sorted_vec.is_none() {
             return Err(
                 ParameterError("Run Plookup with empty sorted lookup vectors".to_string()).into(),
@@ -155,25 +395,32 @@ impl<E: PairingEngine> Prover<E> {
             )?,
             2,
         );
-        let prod_lookup_comm = Self::commit_polynomial(ck, &prod_lookup_poly)?;
+        let prod_lookup_comm = self.profile.record(Operation::Msm, || {
+            self.commit_polynomial(ck, &prod_lookup_poly)
+        })?;
         Ok((prod_lookup_comm, prod_lookup_poly))
     }
 
-    /// Round 3: Return the splitted quotient polynomials and their commitments.
-    /// Note that the first `num_wire_types`-1 splitted quotient polynomials
-    /// have degree `domain_size`+1.
+    /// Round 3: Return the `num_quot_chunks` splitted quotient polynomials and
+    /// their commitments. Note that with the default `num_wire_types` chunks,
+    /// the first `num_wire_types`-1 splitted quotient polynomials have degree
+    /// `domain_size`+1.
     pub(crate) fn run_3rd_round(
         &self,
-        ck: &CommitKey<E>,
-        pks: &[&ProvingKey<E>],
-        challenges: &Challenges<E::Fr>,
-        online_oracles: &[Oracles<E::Fr>],
+        ck: &PCS::CommitKey,
+        pks: &[&PcsProvingKey<'c, PCS>],
+        challenges: &Challenges<PCS::Fr>,
+        online_oracles: &[Oracles<PCS::Fr>],
         num_wire_types: usize,
-    ) -> Result<CommitmentsAndPolys<E>, PlonkError> {
+        num_quot_chunks: usize,
+    ) -> Result<CommitmentsAndPolys<PCS>, PlonkError> {
         let quot_poly =
             self.compute_quotient_polynomial(challenges, pks, online_oracles, num_wire_types)?;
-        let split_quot_polys = self.split_quotient_polynomial(&quot_poly, num_wire_types)?;
-        let split_quot_poly_comms = Self::commit_polynomials(ck, &split_quot_polys)?;
+        let split_quot_polys =
+            self.split_quotient_polynomial(&quot_poly, num_wire_types, num_quot_chunks)?;
+        let split_quot_poly_comms = self.profile.record(Operation::Msm, || {
+            self.commit_polynomials(ck, &split_quot_polys)
+        })?;
 
         Ok((split_quot_poly_comms, split_quot_polys))
     }
@@ -185,41 +432,43 @@ impl<E: PairingEngine> Prover<E> {
     /// Return evaluations of the Plonk proof.
     pub(crate) fn compute_evaluations(
         &self,
-        pk: &ProvingKey<E>,
-        challenges: &Challenges<E::Fr>,
-        online_oracles: &Oracles<E::Fr>,
+        pk: &PcsProvingKey<'c, PCS>,
+        challenges: &Challenges<PCS::Fr>,
+        online_oracles: &Oracles<PCS::Fr>,
         num_wire_types: usize,
-    ) -> ProofEvaluations<E::Fr> {
-        let wires_evals: Vec<E::Fr> = online_oracles
-            .wire_polys
-            .par_iter()
-            .map(|poly| poly.evaluate(&challenges.zeta))
-            .collect();
-        let wire_sigma_evals: Vec<E::Fr> = pk
-            .sigmas
-            .par_iter()
-            .take(num_wire_types - 1)
-            .map(|poly| poly.evaluate(&challenges.zeta))
-            .collect();
-        let perm_next_eval = online_oracles
-            .prod_perm_poly
-            .evaluate(&(challenges.zeta * self.domain.group_gen));
-
-        ProofEvaluations {
-            wires_evals,
-            wire_sigma_evals,
-            perm_next_eval,
-        }
+    ) -> ProofEvaluations<PCS::Fr> {
+        self.profile.record(Operation::PolyEval, || {
+            let wires_evals: Vec<PCS::Fr> = online_oracles
+                .wire_polys
+                .par_iter()
+                .map(|poly| poly.evaluate(&challenges.zeta))
+                .collect();
+            let wire_sigma_evals: Vec<PCS::Fr> = pk
+                .sigmas
+                .par_iter()
+                .take(num_wire_types - 1)
+                .map(|poly| poly.evaluate(&challenges.zeta))
+                .collect();
+            let perm_next_eval = online_oracles
+                .prod_perm_poly
+                .evaluate(&(challenges.zeta * self.domain.group_gen));
+
+            ProofEvaluations {
+                wires_evals,
+                wire_sigma_evals,
+                perm_next_eval,
+            }
+        })
     }
 
     /// Round 4.5 (Plookup): Compute and return evaluations of Plookup-related
     /// polynomials
     pub(crate) fn compute_plookup_evaluations(
         &self,
-        pk: &ProvingKey<E>,
-        challenges: &Challenges<E::Fr>,
-        online_oracles: &Oracles<E::Fr>,
-    ) -> Result<PlookupEvaluations<E::Fr>, PlonkError> {
+        pk: &PcsProvingKey<'c, PCS>,
+        challenges: &Challenges<PCS::Fr>,
+        online_oracles: &Oracles<PCS::Fr>,
+    ) -> Result<PlookupEvaluations<PCS::Fr>, PlonkError> {
         if pk.plookup_pk.is_none() {
             return Err(ParameterError(
                 "Evaluate Plookup polynomials without supporting lookup".to_string(),
@@ -234,52 +483,54 @@ impl<E: PairingEngine> Prover<E> {
             .into());
         }
 
-        let range_table_poly_ref = &pk.plookup_pk.as_ref().unwrap().range_table_poly;
-        let key_table_poly_ref = &pk.plookup_pk.as_ref().unwrap().key_table_poly;
-        let range_table_eval = range_table_poly_ref.evaluate(&challenges.zeta);
-        let key_table_eval = key_table_poly_ref.evaluate(&challenges.zeta);
-        let h_1_eval = online_oracles.plookup_oracles.h_polys[0].evaluate(&challenges.zeta);
-        let q_lookup_eval = pk.q_lookup_poly()?.evaluate(&challenges.zeta);
-
-        let zeta_mul_g = challenges.zeta * self.domain.group_gen;
-        let prod_next_eval = online_oracles
-            .plookup_oracles
-            .prod_lookup_poly
-            .evaluate(&zeta_mul_g);
-        let range_table_next_eval = range_table_poly_ref.evaluate(&zeta_mul_g);
-        let key_table_next_eval = key_table_poly_ref.evaluate(&zeta_mul_g);
-        let h_1_next_eval = online_oracles.plookup_oracles.h_polys[0].evaluate(&zeta_mul_g);
-        let h_2_next_eval = online_oracles.plookup_oracles.h_polys[1].evaluate(&zeta_mul_g);
-        let q_lookup_next_eval = pk.q_lookup_poly()?.evaluate(&zeta_mul_g);
-        let w_3_next_eval = online_oracles.wire_polys[3].evaluate(&zeta_mul_g);
-        let w_4_next_eval = online_oracles.wire_polys[4].evaluate(&zeta_mul_g);
-
-        Ok(PlookupEvaluations {
-            range_table_eval,
-            key_table_eval,
-            h_1_eval,
-            q_lookup_eval,
-            prod_next_eval,
-            range_table_next_eval,
-            key_table_next_eval,
-            h_1_next_eval,
-            h_2_next_eval,
-            q_lookup_next_eval,
-            w_3_next_eval,
-            w_4_next_eval,
+        self.profile.record(Operation::PolyEval, || {
+            let range_table_poly_ref = &pk.plookup_pk.as_ref().unwrap().range_table_poly;
+            let key_table_poly_ref = &pk.plookup_pk.as_ref().unwrap().key_table_poly;
+            let range_table_eval = range_table_poly_ref.evaluate(&challenges.zeta);
+            let key_table_eval = key_table_poly_ref.evaluate(&challenges.zeta);
+            let h_1_eval = online_oracles.plookup_oracles.h_polys[0].evaluate(&challenges.zeta);
+            let q_lookup_eval = pk.q_lookup_poly()?.evaluate(&challenges.zeta);
+
+            let zeta_mul_g = challenges.zeta * self.domain.group_gen;
+            let prod_next_eval = online_oracles
+                .plookup_oracles
+                .prod_lookup_poly
+                .evaluate(&zeta_mul_g);
+            let range_table_next_eval = range_table_poly_ref.evaluate(&zeta_mul_g);
+            let key_table_next_eval = key_table_poly_ref.evaluate(&zeta_mul_g);
+            let h_1_next_eval = online_oracles.plookup_oracles.h_polys[0].evaluate(&zeta_mul_g);
+            let h_2_next_eval = online_oracles.plookup_oracles.h_polys[1].evaluate(&zeta_mul_g);
+            let q_lookup_next_eval = pk.q_lookup_poly()?.evaluate(&zeta_mul_g);
+            let w_3_next_eval = online_oracles.wire_polys[3].evaluate(&zeta_mul_g);
+            let w_4_next_eval = online_oracles.wire_polys[4].evaluate(&zeta_mul_g);
+
+            Ok(PlookupEvaluations {
+                range_table_eval,
+                key_table_eval,
+                h_1_eval,
+                q_lookup_eval,
+                prod_next_eval,
+                range_table_next_eval,
+                key_table_next_eval,
+                h_1_next_eval,
+                h_2_next_eval,
+                q_lookup_next_eval,
+                w_3_next_eval,
+                w_4_next_eval,
+            })
         })
     }
 
     /// Compute linearization polynomial (excluding the quotient part)
     pub(crate) fn compute_non_quotient_component_for_lin_poly(
         &self,
-        alpha_base: E::Fr,
-        pk: &ProvingKey<E>,
-        challenges: &Challenges<E::Fr>,
-        online_oracles: &Oracles<E::Fr>,
-        poly_evals: &ProofEvaluations<E::Fr>,
-        plookup_evals: Option<&PlookupEvaluations<E::Fr>>,
-    ) -> Result<DensePolynomial<E::Fr>, PlonkError> {
+        alpha_base: PCS::Fr,
+        pk: &PcsProvingKey<'c, PCS>,
+        challenges: &Challenges<PCS::Fr>,
+        online_oracles: &Oracles<PCS::Fr>,
+        poly_evals: &ProofEvaluations<PCS::Fr>,
+        plookup_evals: Option<&PlookupEvaluations<PCS::Fr>>,
+    ) -> Result<DensePolynomial<PCS::Fr>, PlonkError> {
         let r_circ = Self::compute_lin_poly_circuit_contribution(pk, &poly_evals.wires_evals);
         let r_perm = Self::compute_lin_poly_copy_constraint_contribution(
             pk,
@@ -308,21 +559,23 @@ impl<E: PairingEngine> Prover<E> {
         Ok(lin_poly)
     }
 
-    // Compute the Quotient part of the linearization polynomial:
+    // Compute the Quotient part of the linearization polynomial, for split
+    // quotient polynomials of `chunk_size` coefficients (`n+2` by default):
     //
-    // -Z_H(x) * [t1(X) + x^{n+2} * t2(X) + ... + x^{(num_wire_types-1)*(n+2)} *
-    // t_{num_wire_types}(X)]
+    // -Z_H(x) * [t1(X) + x^{chunk_size} * t2(X) + ... +
+    // x^{(num_quot_chunks-1)*chunk_size} * t_{num_quot_chunks}(X)]
     pub(crate) fn compute_quotient_component_for_lin_poly(
         domain_size: usize,
-        zeta: E::Fr,
-        quot_polys: &[DensePolynomial<E::Fr>],
-    ) -> Result<DensePolynomial<E::Fr>, PlonkError> {
-        let vanish_eval = zeta.pow(&[domain_size as u64]) - E::Fr::one();
-        let zeta_to_n_plus_2 = (vanish_eval + E::Fr::one()) * zeta * zeta;
+        chunk_size: usize,
+        zeta: PCS::Fr,
+        quot_polys: &[DensePolynomial<PCS::Fr>],
+    ) -> Result<DensePolynomial<PCS::Fr>, PlonkError> {
+        let vanish_eval = zeta.pow(&[domain_size as u64]) - PCS::Fr::one();
+        let zeta_to_chunk_size = zeta.pow(&[chunk_size as u64]);
         let mut r_quot = quot_polys.first().ok_or(PlonkError::IndexError)?.clone();
-        let mut coeff = E::Fr::one();
+        let mut coeff = PCS::Fr::one();
         for poly in quot_polys.iter().skip(1) {
-            coeff *= zeta_to_n_plus_2;
+            coeff *= zeta_to_chunk_size;
             r_quot = r_quot + Self::mul_poly(poly, &coeff);
         }
         r_quot = Self::mul_poly(&r_quot, &vanish_eval.neg());
@@ -330,16 +583,22 @@ impl<E: PairingEngine> Prover<E> {
     }
 
     /// Compute (aggregated) polynomial opening proofs at point `zeta` and
-    /// `zeta * domain_generator`. TODO: Parallelize the computation.
-    pub(crate) fn compute_opening_proofs(
+    /// `zeta * domain_generator`, in this order, with the opening transcript
+    /// `transcript`. TODO: Parallelize the computation.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn compute_opening_proofs<T>(
         &self,
-        ck: &CommitKey<E>,
-        pks: &[&ProvingKey<E>],
-        zeta: &E::Fr,
-        v: &E::Fr,
-        online_oracles: &[Oracles<E::Fr>],
-        lin_poly: &DensePolynomial<E::Fr>,
-    ) -> Result<(Commitment<E>, Commitment<E>), PlonkError> {
+        ck: &PCS::CommitKey,
+        transcript: &mut T,
+        pks: &[&PcsProvingKey<'c, PCS>],
+        zeta: &PCS::Fr,
+        v: &PCS::Fr,
+        online_oracles: &[Oracles<PCS::Fr>],
+        lin_poly: &DensePolynomial<PCS::Fr>,
+    ) -> Result<(PCS::Proof, PCS::Proof), PlonkError>
+    where
+        T: PlonkTranscript<<PCS::Curve as AffineCurve>::BaseField>,
+    {
         if pks.is_empty() || pks.len() != online_oracles.len() {
             return Err(ParameterError(
                 "inconsistent pks/online oracles when computing opening proofs".to_string(),
@@ -366,7 +625,7 @@ impl<E: PairingEngine> Prover<E> {
         }
 
         let opening_proof =
-            Self::compute_batched_witness_polynomial_commitment(ck, &polys_ref, v, zeta)?;
+            self.compute_batched_opening_proof(ck, transcript, &polys_ref, v, zeta)?;
 
         // List the polynomials to be opened at point `zeta * w`.
         let mut polys_ref = vec![];
@@ -380,8 +639,9 @@ impl<E: PairingEngine> Prover<E> {
             }
         }
 
-        let shifted_opening_proof = Self::compute_batched_witness_polynomial_commitment(
+        let shifted_opening_proof = self.compute_batched_opening_proof(
             ck,
+            transcript,
             &polys_ref,
             v,
             &(self.domain.group_gen * zeta),
@@ -392,14 +652,14 @@ impl<E: PairingEngine> Prover<E> {
 }
 
 /// Private helper methods
-impl<E: PairingEngine> Prover<E> {
+impl<'a, 'c, PCS: PcsCommitter<'c>> Prover<'a, PCS> {
     /// Return the list of plookup polynomials to be opened at point `zeta`
     /// The order should be consistent with the verifier side.
     #[inline]
-    fn plookup_open_polys_ref<'a>(
-        oracles: &'a Oracles<E::Fr>,
-        pk: &'a ProvingKey<E>,
-    ) -> Result<Vec<&'a DensePolynomial<E::Fr>>, PlonkError> {
+    fn plookup_open_polys_ref<'b>(
+        oracles: &'b Oracles<PCS::Fr>,
+        pk: &'b PcsProvingKey<'c, PCS>,
+    ) -> Result<Vec<&'b DensePolynomial<PCS::Fr>>, PlonkError> {
         Ok(vec![
             &pk.plookup_pk.as_ref().unwrap().range_table_poly,
             &pk.plookup_pk.as_ref().unwrap().key_table_poly,
@@ -411,10 +671,10 @@ impl<E: PairingEngine> Prover<E> {
     /// Return the list of plookup polynomials to be opened at point `zeta * g`
     /// The order should be consistent with the verifier side.
     #[inline]
-    fn plookup_shifted_open_polys_ref<'a>(
-        oracles: &'a Oracles<E::Fr>,
-        pk: &'a ProvingKey<E>,
-    ) -> Result<Vec<&'a DensePolynomial<E::Fr>>, PlonkError> {
+    fn plookup_shifted_open_polys_ref<'b>(
+        oracles: &'b Oracles<PCS::Fr>,
+        pk: &'b PcsProvingKey<'c, PCS>,
+    ) -> Result<Vec<&'b DensePolynomial<PCS::Fr>>, PlonkError> {
         Ok(vec![
             &oracles.plookup_oracles.prod_lookup_poly,
             &pk.plookup_pk.as_ref().unwrap().range_table_poly,
@@ -428,73 +688,92 @@ impl<E: PairingEngine> Prover<E> {
     }
 
     /// Mask the polynomial so that it remains hidden after revealing
-    /// `hiding_bound` evaluations.
-    fn mask_polynomial<R: CryptoRng + RngCore>(
+    /// `hiding_bound` evaluations, unless the prover is not hiding.
+    fn mask_polynomial<R: Blinder<PCS::Fr>>(
         &self,
         prng: &mut R,
-        poly: DensePolynomial<E::Fr>,
+        poly: DensePolynomial<PCS::Fr>,
         hiding_bound: usize,
-    ) -> DensePolynomial<E::Fr> {
+    ) -> DensePolynomial<PCS::Fr> {
+        if !self.hiding {
+            return poly;
+        }
+        // the coefficients are drawn as in `DensePolynomial::rand`
+        let mask_coeffs = (0..=hiding_bound).map(|_| prng.blinding_scalar()).collect();
         let mask_poly =
-            DensePolynomial::rand(hiding_bound, prng).mul_by_vanishing_poly(self.domain);
+            DensePolynomial::from_coefficients_vec(mask_coeffs).mul_by_vanishing_poly(self.domain);
         mask_poly + poly
     }
 
-    /// Compute polynomial commitments.
+    /// Compute polynomial commitments, in the order of `polys`. The
+    /// commitments do not depend on the number of threads: the MSMs sum the
+    /// same group elements, in whichever order, and are returned in affine
+    /// form.
     fn commit_polynomials(
-        ck: &CommitKey<E>,
-        polys: &[DensePolynomial<E::Fr>],
-    ) -> Result<Vec<Commitment<E>>, PlonkError> {
+        &self,
+        ck: &PCS::CommitKey,
+        polys: &[DensePolynomial<PCS::Fr>],
+    ) -> Result<Vec<PCS::Commitment>, PlonkError> {
         let poly_comms = polys
             .par_iter()
-            .map(|poly| Self::commit_polynomial(ck, poly))
+            .map(|poly| self.commit_polynomial(ck, poly))
             .collect::<Result<Vec<_>, _>>()?;
         Ok(poly_comms)
     }
 
-    /// Commit a polynomial.
+    /// Commit a polynomial, with the MSM backend of the prover. The
+    /// commitment is not hiding.
     #[inline]
     fn commit_polynomial(
-        ck: &CommitKey<E>,
-        poly: &DensePolynomial<E::Fr>,
-    ) -> Result<Commitment<E>, PlonkError> {
-        let (poly_comm, _) = KZG10::commit(ck, poly, None, None).map_err(PlonkError::PcsError)?;
-        Ok(poly_comm)
+        &self,
+        ck: &PCS::CommitKey,
+        poly: &DensePolynomial<PCS::Fr>,
+    ) -> Result<PCS::Commitment, PlonkError> {
+        commit_with_msm::<PCS>(ck, poly, self.msm)
     }
 
     /// Return a batched opening proof given a list of polynomials `polys_ref`,
-    /// evaluation point `eval_point`, and randomized combiner `r`.
-    fn compute_batched_witness_polynomial_commitment(
-        ck: &CommitKey<E>,
-        polys_ref: &[&DensePolynomial<E::Fr>],
-        r: &E::Fr,
-        eval_point: &E::Fr,
-    ) -> Result<Commitment<E>, PlonkError> {
+    /// evaluation point `eval_point`, and randomized combiner `r`, opened
+    /// with `transcript`.
+    fn compute_batched_opening_proof<T>(
+        &self,
+        ck: &PCS::CommitKey,
+        transcript: &mut T,
+        polys_ref: &[&DensePolynomial<PCS::Fr>],
+        r: &PCS::Fr,
+        eval_point: &PCS::Fr,
+    ) -> Result<PCS::Proof, PlonkError>
+    where
+        T: PlonkTranscript<<PCS::Curve as AffineCurve>::BaseField>,
+    {
         // Compute the aggregated polynomial
         let (batch_poly, _) = polys_ref.iter().fold(
-            (DensePolynomial::zero(), E::Fr::one()),
+            (DensePolynomial::zero(), PCS::Fr::one()),
             |(acc, coeff), &poly| (acc + Self::mul_poly(poly, &coeff), coeff * r),
         );
 
-        // Compute opening witness polynomial and its commitment
-        let empty_rand = Randomness::<E::Fr, DensePolynomial<E::Fr>>::empty();
-        let (witness_poly, _) = KZG10::<E, DensePolynomial<E::Fr>>::compute_witness_polynomial(
-            &batch_poly,
-            *eval_point,
-            &empty_rand,
-        )?;
+        // Compute the opening proof of the aggregated polynomial
+        let (_, proof) = self.profile.record(Operation::Msm, || {
+            PCS::open(ck, transcript, &batch_poly, eval_point, self.msm)
+        })?;
+        Ok(proof)
+    }
 
-        Self::commit_polynomial(ck, &witness_poly)
+    /// Compute the coset evaluations of `coeffs` over the quotient domain.
+    #[inline]
+    fn coset_fft(&self, coeffs: &[PCS::Fr]) -> Vec<PCS::Fr> {
+        self.profile
+            .record(Operation::Fft, || self.quot_domain.coset_fft(coeffs))
     }
 
     /// Compute the quotient polynomial via (i)FFTs.
     fn compute_quotient_polynomial(
         &self,
-        challenges: &Challenges<E::Fr>,
-        pks: &[&ProvingKey<E>],
-        online_oracles: &[Oracles<E::Fr>],
+        challenges: &Challenges<PCS::Fr>,
+        pks: &[&PcsProvingKey<'c, PCS>],
+        online_oracles: &[Oracles<PCS::Fr>],
         num_wire_types: usize,
-    ) -> Result<DensePolynomial<E::Fr>, PlonkError> {
+    ) -> Result<DensePolynomial<PCS::Fr>, PlonkError> {
         if pks.is_empty() || pks.len() != online_oracles.len() {
             return Err(ParameterError(
                 "inconsistent pks/online oracles when computing quotient polys".to_string(),
@@ -506,140 +785,68 @@ impl<E: PairingEngine> Prover<E> {
         let m = self.quot_domain.size();
         let domain_size_ratio = m / n;
         // Compute 1/Z_H(w^i).
-        let z_h_inv: Vec<E::Fr> = (0..domain_size_ratio)
+        let z_h_inv: Vec<PCS::Fr> = (0..domain_size_ratio)
             .map(|i| {
-                ((E::Fr::multiplicative_generator() * self.quot_domain.element(i)).pow([n as u64])
-                    - E::Fr::one())
+                ((PCS::Fr::multiplicative_generator() * self.quot_domain.element(i))
+                    .pow([n as u64])
+                    - PCS::Fr::one())
                 .inverse()
                 .unwrap()
             })
             .collect();
 
         // Compute coset evaluations of the quotient polynomial.
-        let mut quot_poly_coset_evals_sum = vec![E::Fr::zero(); m];
-        let mut alpha_base = E::Fr::one();
+        let mut quot_poly_coset_evals_sum = vec![PCS::Fr::zero(); m];
+        let mut alpha_base = PCS::Fr::one();
         let alpha_3 = challenges.alpha.square() * challenges.alpha;
         let alpha_7 = alpha_3.square() * challenges.alpha;
         // enumerate proving instances
         for (oracles, pk) in online_oracles.iter().zip(pks.iter()) {
-            // lookup_flag = 1 if support Plookup argument.
-            let lookup_flag = pk.plookup_pk.is_some();
-
-            // Compute coset evaluations.
-            let selectors_coset_fft: Vec<Vec<E::Fr>> = pk
-                .selectors
-                .par_iter()
-                .map(|poly| self.quot_domain.coset_fft(poly.coeffs()))
-                .collect();
-            let sigmas_coset_fft: Vec<Vec<E::Fr>> = pk
-                .sigmas
-                .par_iter()
-                .map(|poly| self.quot_domain.coset_fft(poly.coeffs()))
-                .collect();
-
-            let wire_polys_coset_fft: Vec<Vec<E::Fr>> = oracles
-                .wire_polys
-                .par_iter()
-                .map(|poly| self.quot_domain.coset_fft(poly.coeffs()))
-                .collect();
-            // TODO: (binyi) we can also compute below in parallel with
-            // `wire_polys_coset_fft`.
-            let prod_perm_poly_coset_fft =
-                self.quot_domain.coset_fft(oracles.prod_perm_poly.coeffs());
-            let pub_input_poly_coset_fft =
-                self.quot_domain.coset_fft(oracles.pub_inp_poly.coeffs());
-
-            // Compute coset evaluations of Plookup online oracles.
-            let (
-                range_table_coset_fft,
-                key_table_coset_fft,
-                h_coset_ffts,
-                prod_lookup_poly_coset_fft,
-            ) = if lookup_flag {
-                let range_table_coset_fft = self
-                    .quot_domain
-                    .coset_fft(pk.plookup_pk.as_ref().unwrap().range_table_poly.coeffs()); // safe unwrap
-                let key_table_coset_fft = self
-                    .quot_domain
-                    .coset_fft(pk.plookup_pk.as_ref().unwrap().key_table_poly.coeffs()); // safe unwrap
-                let h_coset_ffts: Vec<Vec<E::Fr>> = oracles
-                    .plookup_oracles
-                    .h_polys
-                    .par_iter()
-                    .map(|poly| self.quot_domain.coset_fft(poly.coeffs()))
-                    .collect();
-                let prod_lookup_poly_coset_fft = self
-                    .quot_domain
-                    .coset_fft(oracles.plookup_oracles.prod_lookup_poly.coeffs());
-                (
-                    Some(range_table_coset_fft),
-                    Some(key_table_coset_fft),
-                    Some(h_coset_ffts),
-                    Some(prod_lookup_poly_coset_fft),
-                )
-            } else {
-                (None, None, None, None)
-            };
-
-            // Compute coset evaluations of the quotient polynomial.
-            let quot_poly_coset_evals: Vec<E::Fr> = (0..m)
-                .into_par_iter()
-                .map(|i| {
-                    let w: Vec<E::Fr> = (0..num_wire_types)
-                        .map(|j| wire_polys_coset_fft[j][i])
-                        .collect();
-                    let w_next: Vec<E::Fr> = (0..num_wire_types)
-                        .map(|j| wire_polys_coset_fft[j][(i + domain_size_ratio) % m])
-                        .collect();
-
-                    let t_circ = Self::compute_quotient_circuit_contribution(
-                        i,
-                        &w,
-                        &pub_input_poly_coset_fft[i],
-                        &selectors_coset_fft,
-                    );
-                    let (t_perm_1, t_perm_2) = Self::compute_quotient_copy_constraint_contribution(
-                        i,
-                        self.quot_domain.element(i) * E::Fr::multiplicative_generator(),
-                        pk,
-                        &w,
-                        &prod_perm_poly_coset_fft[i],
-                        &prod_perm_poly_coset_fft[(i + domain_size_ratio) % m],
+            match self.memory_budget {
+                Some(memory_budget)
+                    if self.quot_domain.element(domain_size_ratio) == self.domain.group_gen =>
+                {
+                    self.add_quotient_coset_evals_by_chunks(
+                        &mut quot_poly_coset_evals_sum,
+                        alpha_base,
+                        &z_h_inv,
                         challenges,
-                        &sigmas_coset_fft,
+                        pk,
+                        oracles,
+                        num_wire_types,
+                        memory_budget,
                     );
-                    let mut t1 = t_circ + t_perm_1;
-                    let mut t2 = t_perm_2;
-
-                    // add Plookup-related terms
-                    if lookup_flag {
-                        let (t_lookup_1, t_lookup_2) = self.compute_quotient_plookup_contribution(
-                            i,
-                            self.quot_domain.element(i) * E::Fr::multiplicative_generator(),
-                            pk,
-                            &w,
-                            &w_next,
-                            h_coset_ffts.as_ref().unwrap(),
-                            prod_lookup_poly_coset_fft.as_ref().unwrap(),
-                            range_table_coset_fft.as_ref().unwrap(),
-                            key_table_coset_fft.as_ref().unwrap(),
-                            selectors_coset_fft.last().unwrap(), // TODO: add a method to extract q_lookup_coset_fft
-                            challenges,
-                        );
-                        t1 += t_lookup_1;
-                        t2 += t_lookup_2;
+                },
+                _ => {
+                    let evals =
+                        self.compute_coset_evals(pk, oracles, self.key_coset_evals, |coeffs| {
+                            self.coset_fft(coeffs)
+                        });
+                    let quot_poly_coset_evals: Vec<PCS::Fr> = (0..m)
+                        .into_par_iter()
+                        .map(|i| {
+                            self.compute_quotient_coset_eval(
+                                i,
+                                (i + domain_size_ratio) % m,
+                                self.quot_domain.element(i) * PCS::Fr::multiplicative_generator(),
+                                z_h_inv[i % domain_size_ratio],
+                                challenges,
+                                pk,
+                                &evals,
+                                num_wire_types,
+                            )
+                        })
+                        .collect();
+                    for (a, b) in quot_poly_coset_evals_sum
+                        .iter_mut()
+                        .zip(quot_poly_coset_evals.iter())
+                    {
+                        *a += alpha_base * b;
                     }
-                    t1 * z_h_inv[i % domain_size_ratio] + t2
-                })
-                .collect();
-            for (a, b) in quot_poly_coset_evals_sum
-                .iter_mut()
-                .zip(quot_poly_coset_evals.iter())
-            {
-                *a += alpha_base * b;
+                },
             }
             // update the random combiner for aggregating multiple proving instances
-            if lookup_flag {
+            if pk.plookup_pk.is_some() {
                 alpha_base *= alpha_7;
             } else {
                 alpha_base *= alpha_3;
@@ -647,45 +854,248 @@ impl<E: PairingEngine> Prover<E> {
         }
         // Compute the coefficient form of the quotient polynomial
         Ok(DensePolynomial::from_coefficients_vec(
-            self.quot_domain.coset_ifft(&quot_poly_coset_evals_sum),
+            self.profile.record(Operation::Fft, || {
+                self.quot_domain.coset_ifft(&quot_poly_coset_evals_sum)
+            }),
         ))
     }
 
+    /// Add `alpha_base` times the evaluations of the quotient polynomial of
+    /// one instance over the quotient coset to `quot_poly_coset_evals_sum`,
+    /// evaluating the polynomials on one coset `c * H` of the evaluation
+    /// domain `H` at a time, with as many cosets in parallel as fit in
+    /// `memory_budget` bytes.
+    ///
+    /// The `i`-th point of the quotient coset is `c * g^k` for the `r`-th
+    /// coset, where `i = r + k * domain_size_ratio` and `g` generates `H`, and
+    /// its next point in the shifted evaluations is the `k+1`-th one.
+    #[allow(clippy::too_many_arguments)]
+    fn add_quotient_coset_evals_by_chunks(
+        &self,
+        quot_poly_coset_evals_sum: &mut [PCS::Fr],
+        alpha_base: PCS::Fr,
+        z_h_inv: &[PCS::Fr],
+        challenges: &Challenges<PCS::Fr>,
+        pk: &PcsProvingKey<'c, PCS>,
+        oracles: &Oracles<PCS::Fr>,
+        num_wire_types: usize,
+        memory_budget: usize,
+    ) {
+        let n = self.domain.size();
+        let domain_size_ratio = self.quot_domain.size() / n;
+        let num_polys = pk.selectors.len()
+            + pk.sigmas.len()
+            + oracles.wire_polys.len()
+            + 2
+            + if pk.plookup_pk.is_some() {
+                4 + oracles.plookup_oracles.h_polys.len()
+            } else {
+                0
+            };
+        let coset_bytes = num_polys * n * core::mem::size_of::<PCS::Fr>();
+        let num_cosets_in_flight = (memory_budget / coset_bytes).clamp(1, domain_size_ratio);
+
+        for first_coset in (0..domain_size_ratio).step_by(num_cosets_in_flight) {
+            let cosets = first_coset..(first_coset + num_cosets_in_flight).min(domain_size_ratio);
+            let cosets_quot_evals: Vec<Vec<PCS::Fr>> = cosets
+                .clone()
+                .into_par_iter()
+                .map(|r| {
+                    let shift = PCS::Fr::multiplicative_generator() * self.quot_domain.element(r);
+                    let evals = self.compute_coset_evals(pk, oracles, None, |coeffs| {
+                        self.coset_fft_on_domain(coeffs, shift)
+                    });
+                    (0..n)
+                        .into_par_iter()
+                        .map(|k| {
+                            self.compute_quotient_coset_eval(
+                                k,
+                                (k + 1) % n,
+                                shift * self.domain.element(k),
+                                z_h_inv[r],
+                                challenges,
+                                pk,
+                                &evals,
+                                num_wire_types,
+                            )
+                        })
+                        .collect()
+                })
+                .collect();
+            for (r, quot_evals) in cosets.zip(cosets_quot_evals.iter()) {
+                for (k, b) in quot_evals.iter().enumerate() {
+                    quot_poly_coset_evals_sum[r + k * domain_size_ratio] += alpha_base * b;
+                }
+            }
+        }
+    }
+
+    /// Compute the evaluations of `coeffs` on the coset `shift * H` of the
+    /// evaluation domain `H`, by folding the coefficients modulo
+    /// `X^|H| - shift^|H|` before an FFT over `H`.
+    fn coset_fft_on_domain(&self, coeffs: &[PCS::Fr], shift: PCS::Fr) -> Vec<PCS::Fr> {
+        self.profile.record(Operation::Fft, || {
+            let n = self.domain.size();
+            let mut folded = vec![PCS::Fr::zero(); n];
+            let mut shift_power = PCS::Fr::one();
+            for (i, coeff) in coeffs.iter().enumerate() {
+                folded[i % n] += shift_power * coeff;
+                shift_power *= shift;
+            }
+            self.domain.fft(&folded)
+        })
+    }
+
+    /// Compute the evaluations of the polynomials of an instance that enter
+    /// the quotient polynomial, with `coset_fft`. Those of the polynomials of
+    /// `pk` are borrowed from `key_evals` if given.
+    fn compute_coset_evals<'k, FFT>(
+        &self,
+        pk: &PcsProvingKey<'c, PCS>,
+        oracles: &Oracles<PCS::Fr>,
+        key_evals: Option<&'k KeyCosetEvals<PCS::Fr>>,
+        coset_fft: FFT,
+    ) -> CosetEvals<'k, PCS::Fr>
+    where
+        FFT: Fn(&[PCS::Fr]) -> Vec<PCS::Fr> + Sync,
+    {
+        let (selectors, sigmas) = match key_evals {
+            Some(key_evals) => (
+                Cow::Borrowed(key_evals.selectors.as_slice()),
+                Cow::Borrowed(key_evals.sigmas.as_slice()),
+            ),
+            None => (
+                Cow::Owned(
+                    pk.selectors
+                        .par_iter()
+                        .map(|poly| coset_fft(poly.coeffs()))
+                        .collect(),
+                ),
+                Cow::Owned(
+                    pk.sigmas
+                        .par_iter()
+                        .map(|poly| coset_fft(poly.coeffs()))
+                        .collect(),
+                ),
+            ),
+        };
+        let wires = oracles
+            .wire_polys
+            .par_iter()
+            .map(|poly| coset_fft(poly.coeffs()))
+            .collect();
+        // TODO: (binyi) we can also compute below in parallel with
+        // `wires`.
+        let prod_perm = coset_fft(oracles.prod_perm_poly.coeffs());
+        let pub_input = coset_fft(oracles.pub_inp_poly.coeffs());
+
+        // Compute coset evaluations of Plookup online oracles.
+        let plookup = pk.plookup_pk.as_ref().map(|plookup_pk| {
+            let (range_table, key_table) =
+                match key_evals.and_then(|key_evals| key_evals.tables.as_ref()) {
+                    Some((range_table, key_table)) => (
+                        Cow::Borrowed(range_table.as_slice()),
+                        Cow::Borrowed(key_table.as_slice()),
+                    ),
+                    None => (
+                        Cow::Owned(coset_fft(plookup_pk.range_table_poly.coeffs())),
+                        Cow::Owned(coset_fft(plookup_pk.key_table_poly.coeffs())),
+                    ),
+                };
+            PlookupCosetEvals {
+                range_table,
+                key_table,
+                h: oracles
+                    .plookup_oracles
+                    .h_polys
+                    .par_iter()
+                    .map(|poly| coset_fft(poly.coeffs()))
+                    .collect(),
+                prod_lookup: coset_fft(oracles.plookup_oracles.prod_lookup_poly.coeffs()),
+            }
+        });
+        CosetEvals {
+            selectors,
+            sigmas,
+            wires,
+            prod_perm,
+            pub_input,
+            plookup,
+        }
+    }
+
+    /// Compute the evaluation of the quotient polynomial of an instance at
+    /// `eval_point`, from the `i`-th entries of `evals`, where `i_next` is the
+    /// entry at `eval_point` times the generator of the evaluation domain.
+    #[allow(clippy::too_many_arguments)]
+    fn compute_quotient_coset_eval(
+        &self,
+        i: usize,
+        i_next: usize,
+        eval_point: PCS::Fr,
+        z_h_inv: PCS::Fr,
+        challenges: &Challenges<PCS::Fr>,
+        pk: &PcsProvingKey<'c, PCS>,
+        evals: &CosetEvals<PCS::Fr>,
+        num_wire_types: usize,
+    ) -> PCS::Fr {
+        let w: Vec<PCS::Fr> = (0..num_wire_types).map(|j| evals.wires[j][i]).collect();
+        let w_next: Vec<PCS::Fr> = (0..num_wire_types)
+            .map(|j| evals.wires[j][i_next])
+            .collect();
+
+        let t_circ = Self::compute_quotient_circuit_contribution(
+            i,
+            &w,
+            &evals.pub_input[i],
+            &evals.selectors,
+            &pk.vk.custom_gates,
+        );
+        let (t_perm_1, t_perm_2) = Self::compute_quotient_copy_constraint_contribution(
+            i,
+            eval_point,
+            pk,
+            &w,
+            &evals.prod_perm[i],
+            &evals.prod_perm[i_next],
+            challenges,
+            &evals.sigmas,
+        );
+        let mut t1 = t_circ + t_perm_1;
+        let mut t2 = t_perm_2;
+
+        // add Plookup-related terms
+        if let Some(plookup) = &evals.plookup {
+            let (t_lookup_1, t_lookup_2) = self.compute_quotient_plookup_contribution(
+                i,
+                i_next,
+                eval_point,
+                pk,
+                &w,
+                &w_next,
+                &plookup.h,
+                &plookup.prod_lookup,
+                &plookup.range_table,
+                &plookup.key_table,
+                evals.selectors.last().unwrap(), // TODO: add a method to extract q_lookup_coset_fft
+                challenges,
+            );
+            t1 += t_lookup_1;
+            t2 += t_lookup_2;
+        }
+        t1 * z_h_inv + t2
+    }
+
     // Compute the i-th coset evaluation of the circuit part of the quotient
     // polynomial.
     fn compute_quotient_circuit_contribution(
         i: usize,
-        w: &[E::Fr],
-        pi: &E::Fr,
-        selectors_coset_fft: &[Vec<E::Fr>],
-    ) -> E::Fr {
-        // Selectors
-        // The order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc
-        // TODO: (binyi) get the order from a function.
-        let q_lc: Vec<E::Fr> = (0..GATE_WIDTH).map(|j| selectors_coset_fft[j][i]).collect();
-        let q_mul: Vec<E::Fr> = (GATE_WIDTH..GATE_WIDTH + 2)
-            .map(|j| selectors_coset_fft[j][i])
-            .collect();
-        let q_hash: Vec<E::Fr> = (GATE_WIDTH + 2..2 * GATE_WIDTH + 2)
-            .map(|j| selectors_coset_fft[j][i])
-            .collect();
-        let q_o = selectors_coset_fft[2 * GATE_WIDTH + 2][i];
-        let q_c = selectors_coset_fft[2 * GATE_WIDTH + 3][i];
-        let q_ecc = selectors_coset_fft[2 * GATE_WIDTH + 4][i];
-
-        q_c + pi
-            + q_lc[0] * w[0]
-            + q_lc[1] * w[1]
-            + q_lc[2] * w[2]
-            + q_lc[3] * w[3]
-            + q_mul[0] * w[0] * w[1]
-            + q_mul[1] * w[2] * w[3]
-            + q_ecc * w[0] * w[1] * w[2] * w[3] * w[4]
-            + q_hash[0] * w[0].pow(&[5])
-            + q_hash[1] * w[1].pow(&[5])
-            + q_hash[2] * w[2].pow(&[5])
-            + q_hash[3] * w[3].pow(&[5])
-            - q_o * w[4]
+        w: &[PCS::Fr],
+        pi: &PCS::Fr,
+        selectors_coset_fft: &[Vec<PCS::Fr>],
+        custom_gates: &[CustomGateSpec<PCS::Fr>],
+    ) -> PCS::Fr {
+        eval_gate_identity(w, pi, |j| selectors_coset_fft[j][i], custom_gates)
     }
 
     /// Compute the i-th coset evaluation of the copy constraint part of the
@@ -698,14 +1108,14 @@ impl<E: PairingEngine> Prover<E> {
     #[allow(clippy::too_many_arguments)]
     fn compute_quotient_copy_constraint_contribution(
         i: usize,
-        eval_point: E::Fr,
-        pk: &ProvingKey<E>,
-        w: &[E::Fr],
-        z_x: &E::Fr,
-        z_xw: &E::Fr,
-        challenges: &Challenges<E::Fr>,
-        sigmas_coset_fft: &[Vec<E::Fr>],
-    ) -> (E::Fr, E::Fr) {
+        eval_point: PCS::Fr,
+        pk: &PcsProvingKey<'c, PCS>,
+        w: &[PCS::Fr],
+        z_x: &PCS::Fr,
+        z_xw: &PCS::Fr,
+        challenges: &Challenges<PCS::Fr>,
+        sigmas_coset_fft: &[Vec<PCS::Fr>],
+    ) -> (PCS::Fr, PCS::Fr) {
         let num_wire_types = w.len();
         let n = pk.domain_size();
 
@@ -716,33 +1126,33 @@ impl<E: PairingEngine> Prover<E> {
         // Delay the division of Z_H(X).
         //
         // Extended permutation values
-        let sigmas: Vec<E::Fr> = (0..num_wire_types)
+        let sigmas: Vec<PCS::Fr> = (0..num_wire_types)
             .map(|j| sigmas_coset_fft[j][i])
             .collect();
 
-        // Compute the 1st term.
-        let mut result_1 = challenges.alpha
-            * w.iter().enumerate().fold(*z_x, |acc, (j, &w)| {
-                acc * (w + pk.k()[j] * eval_point * challenges.beta + challenges.gamma)
-            });
-        // Minus the 2nd term.
-        result_1 -= challenges.alpha
-            * w.iter()
-                .zip(sigmas.iter())
-                .fold(*z_xw, |acc, (&w, &sigma)| {
-                    acc * (w + sigma * challenges.beta + challenges.gamma)
-                });
+        let result_1 = challenges.alpha
+            * eval_perm_identity(
+                w,
+                &sigmas,
+                pk.k(),
+                eval_point,
+                z_x,
+                z_xw,
+                &challenges.beta,
+                &challenges.gamma,
+            );
 
         // The check that z(x) = 1 at point 1.
         // (z(x)-1) * L1(x) * alpha^2 / Z_H(x) = (z(x)-1) * alpha^2 / (n * (x - 1))
-        let result_2 = challenges.alpha.square() * (*z_x - E::Fr::one())
-            / (E::Fr::from(n as u64) * (eval_point - E::Fr::one()));
+        let result_2 = challenges.alpha.square() * (*z_x - PCS::Fr::one())
+            / (PCS::Fr::from(n as u64) * (eval_point - PCS::Fr::one()));
 
         (result_1, result_2)
     }
 
     /// Compute the i-th coset evaluation of the lookup constraint part of the
     /// quotient polynomial.
+    /// `i_next`: the index of the coset evaluations at `eval_point * g`.
     /// `eval_point`: the evaluation point.
     /// `pk`: proving key.
     /// `lookup_w`: (merged) lookup witness coset evaluations at `eval_point`.
@@ -756,41 +1166,40 @@ impl<E: PairingEngine> Prover<E> {
     fn compute_quotient_plookup_contribution(
         &self,
         i: usize,
-        eval_point: E::Fr,
-        pk: &ProvingKey<E>,
-        w: &[E::Fr],
-        w_next: &[E::Fr],
-        h_coset_ffts: &[Vec<E::Fr>],
-        prod_lookup_coset_fft: &[E::Fr],
-        range_table_coset_fft: &[E::Fr],
-        key_table_coset_fft: &[E::Fr],
-        q_lookup_coset_fft: &[E::Fr],
-        challenges: &Challenges<E::Fr>,
-    ) -> (E::Fr, E::Fr) {
+        i_next: usize,
+        eval_point: PCS::Fr,
+        pk: &PcsProvingKey<'c, PCS>,
+        w: &[PCS::Fr],
+        w_next: &[PCS::Fr],
+        h_coset_ffts: &[Vec<PCS::Fr>],
+        prod_lookup_coset_fft: &[PCS::Fr],
+        range_table_coset_fft: &[PCS::Fr],
+        key_table_coset_fft: &[PCS::Fr],
+        q_lookup_coset_fft: &[PCS::Fr],
+        challenges: &Challenges<PCS::Fr>,
+    ) -> (PCS::Fr, PCS::Fr) {
         assert!(pk.plookup_pk.is_some());
         assert_eq!(h_coset_ffts.len(), 2);
 
         let n = pk.domain_size();
-        let m = self.quot_domain.size();
-        let domain_size_ratio = m / n;
-        let n_field = E::Fr::from(n as u64);
+        let n_field = PCS::Fr::from(n as u64);
         let lagrange_n_coeff =
             self.domain.group_gen_inv / (n_field * (eval_point - self.domain.group_gen_inv));
-        let lagrange_1_coeff = E::Fr::one() / (n_field * (eval_point - E::Fr::one()));
+        let lagrange_1_coeff = PCS::Fr::one() / (n_field * (eval_point - PCS::Fr::one()));
         let mut alpha_power = challenges.alpha * challenges.alpha * challenges.alpha;
 
         // extract polynomial evaluations
         let h_1_x = h_coset_ffts[0][i];
-        let h_1_xw = h_coset_ffts[0][(i + domain_size_ratio) % m];
+        let h_1_xw = h_coset_ffts[0][i_next];
         let h_2_x = h_coset_ffts[1][i];
-        let h_2_xw = h_coset_ffts[1][(i + domain_size_ratio) % m];
+        let h_2_xw = h_coset_ffts[1][i_next];
         let p_x = prod_lookup_coset_fft[i];
-        let p_xw = prod_lookup_coset_fft[(i + domain_size_ratio) % m];
+        let p_xw = prod_lookup_coset_fft[i_next];
         let range_table_x = range_table_coset_fft[i];
         let key_table_x = key_table_coset_fft[i];
-        let range_table_xw = range_table_coset_fft[(i + domain_size_ratio) % m];
-        let key_table_xw = key_table_coset_fft[(i + domain_size_ratio) % m];
-        let merged_table_x = eval_merged_table::<E>(
+        let range_table_xw = range_table_coset_fft[i_next];
+        let key_table_xw = key_table_coset_fft[i_next];
+        let merged_table_x = eval_merged_table(
             challenges.tau,
             range_table_x,
             key_table_x,
@@ -798,15 +1207,15 @@ impl<E: PairingEngine> Prover<E> {
             w[3],
             w[4],
         );
-        let merged_table_xw = eval_merged_table::<E>(
+        let merged_table_xw = eval_merged_table(
             challenges.tau,
             range_table_xw,
             key_table_xw,
-            q_lookup_coset_fft[(i + domain_size_ratio) % m],
+            q_lookup_coset_fft[i_next],
             w_next[3],
             w_next[4],
         );
-        let merged_lookup_x = eval_merged_lookup_witness::<E>(
+        let merged_lookup_x = eval_merged_lookup_witness(
             challenges.tau,
             w[5],
             w[0],
@@ -826,7 +1235,7 @@ impl<E: PairingEngine> Prover<E> {
         // The check that p(X) = 1 at point 1.
         //
         // Fp1(X)/Z_H(X) = (L1(X) * (p(X) - 1)) / Z_H(X) = (p(X) - 1) / (n * (X - 1))
-        let term_p_1 = (p_x - E::Fr::one()) * lagrange_1_coeff;
+        let term_p_1 = (p_x - PCS::Fr::one()) * lagrange_1_coeff;
         result_2 += alpha_power * term_p_1;
         alpha_power *= challenges.alpha;
 
@@ -834,7 +1243,7 @@ impl<E: PairingEngine> Prover<E> {
         //
         // Fp2(X)/Z_H(X) = (Ln(X) * (p(X) - 1)) / Z_H(X) = (p(X) - 1) * w^{n-1} / (n *
         // (X - w^{n-1}))
-        let term_p_2 = (p_x - E::Fr::one()) * lagrange_n_coeff;
+        let term_p_2 = (p_x - PCS::Fr::one()) * lagrange_n_coeff;
         result_2 += alpha_power * term_p_2;
         alpha_power *= challenges.alpha;
 
@@ -845,7 +1254,7 @@ impl<E: PairingEngine> Prover<E> {
         // [gamma*(1+beta) + merged_table(X) + beta * merged_table(Xw)]
         //        - (X - w^{n-1}) * p(Xw) * [gamma(1+beta) + h_1(X) + beta * h_1(Xw)] *
         //          [gamma(1+beta) + h_2(X) + beta * h_2(Xw)]
-        let beta_plus_one = E::Fr::one() + challenges.beta;
+        let beta_plus_one = PCS::Fr::one() + challenges.beta;
         let gamma_mul_beta_plus_one = beta_plus_one * challenges.gamma;
         let term_p_3 = (eval_point - self.domain.group_gen_inv)
             * (p_x
@@ -860,29 +1269,39 @@ impl<E: PairingEngine> Prover<E> {
         (result_1, result_2)
     }
 
-    /// Split the quotient polynomial into `num_wire_types` polynomials.
-    /// The first `num_wire_types`-1 polynomials have degree `domain_size`+1.
+    /// Split the quotient polynomial into `num_chunks` polynomials. The
+    /// first `num_chunks`-1 polynomials have the same number of coefficients,
+    /// i.e. degree `domain_size`+1 for the default `num_wire_types` chunks.
+    /// Without masking, the quotient polynomial may have a smaller degree, and
+    /// the last polynomials are zero-padded accordingly.
     fn split_quotient_polynomial(
         &self,
-        quot_poly: &DensePolynomial<E::Fr>,
+        quot_poly: &DensePolynomial<PCS::Fr>,
         num_wire_types: usize,
-    ) -> Result<Vec<DensePolynomial<E::Fr>>, PlonkError> {
-        let expected_degree = quotient_polynomial_degree(self.domain.size(), num_wire_types);
-        if quot_poly.degree() != expected_degree {
+        num_chunks: usize,
+    ) -> Result<Vec<DensePolynomial<PCS::Fr>>, PlonkError> {
+        let expected_degree = masked_quotient_polynomial_degree(
+            self.domain.size(),
+            num_wire_types,
+            self.num_extra_openings,
+        );
+        if quot_poly.degree() > expected_degree
+            || (self.hiding && quot_poly.degree() != expected_degree)
+        {
             return Err(WrongQuotientPolyDegree(quot_poly.degree(), expected_degree).into());
         }
-        let n = self.domain.size();
-        let split_quot_polys = (0..num_wire_types)
+        let chunk_size = quotient_chunk_size(self.domain.size(), num_wire_types, num_chunks);
+        let num_coeffs = quot_poly.coeffs.len();
+        let split_quot_polys = (0..num_chunks)
             .into_par_iter()
             .map(|i| {
-                let end = if i < num_wire_types - 1 {
-                    (i + 1) * (n + 2)
+                let end = if i < num_chunks - 1 {
+                    (i + 1) * chunk_size
                 } else {
-                    quot_poly.degree() + 1
+                    num_coeffs
                 };
-                // Degree-(n+1) polynomial has n + 2 coefficients.
-                DensePolynomial::<E::Fr>::from_coefficients_slice(
-                    &quot_poly.coeffs[i * (n + 2)..end],
+                DensePolynomial::<PCS::Fr>::from_coefficients_slice(
+                    &quot_poly.coeffs[(i * chunk_size).min(num_coeffs)..end.min(num_coeffs)],
                 )
             })
             .collect();
@@ -891,10 +1310,10 @@ impl<E: PairingEngine> Prover<E> {
 
     // Compute the circuit part of the linearization polynomial
     fn compute_lin_poly_circuit_contribution(
-        pk: &ProvingKey<E>,
-        w_evals: &[E::Fr],
-    ) -> DensePolynomial<E::Fr> {
-        // The selectors order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc
+        pk: &PcsProvingKey<'c, PCS>,
+        w_evals: &[PCS::Fr],
+    ) -> DensePolynomial<PCS::Fr> {
+        // The selectors order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc, [q_custom]
         // TODO: (binyi) get the order from a function.
         let q_lc = &pk.selectors[..GATE_WIDTH];
         let q_mul = &pk.selectors[GATE_WIDTH..GATE_WIDTH + 2];
@@ -921,17 +1340,26 @@ impl<E: PairingEngine> Prover<E> {
             )
             + Self::mul_poly(q_o, &(-w_evals[4]))
             + q_c.clone()
+            + pk.vk.custom_gates.iter().enumerate().fold(
+                DensePolynomial::zero(),
+                |acc, (k, spec)| {
+                    acc + Self::mul_poly(
+                        &pk.selectors[N_TURBO_PLONK_SELECTORS + k],
+                        &spec.evaluate(w_evals),
+                    )
+                },
+            )
     }
 
     // Compute the wire permutation part of the linearization polynomial
     fn compute_lin_poly_copy_constraint_contribution(
-        pk: &ProvingKey<E>,
-        challenges: &Challenges<E::Fr>,
-        poly_evals: &ProofEvaluations<E::Fr>,
-        prod_perm_poly: &DensePolynomial<E::Fr>,
-    ) -> DensePolynomial<E::Fr> {
-        let dividend = challenges.zeta.pow(&[pk.domain_size() as u64]) - E::Fr::one();
-        let divisor = E::Fr::from(pk.domain_size() as u32) * (challenges.zeta - E::Fr::one());
+        pk: &PcsProvingKey<'c, PCS>,
+        challenges: &Challenges<PCS::Fr>,
+        poly_evals: &ProofEvaluations<PCS::Fr>,
+        prod_perm_poly: &DensePolynomial<PCS::Fr>,
+    ) -> DensePolynomial<PCS::Fr> {
+        let dividend = challenges.zeta.pow(&[pk.domain_size() as u64]) - PCS::Fr::one();
+        let divisor = PCS::Fr::from(pk.domain_size() as u32) * (challenges.zeta - PCS::Fr::one());
         let lagrange_1_eval = dividend / divisor;
 
         // Compute the coefficient of z(X)
@@ -965,28 +1393,28 @@ impl<E: PairingEngine> Prover<E> {
     // Compute the Plookup part of the linearization polynomial
     fn compute_lin_poly_plookup_contribution(
         &self,
-        pk: &ProvingKey<E>,
-        challenges: &Challenges<E::Fr>,
-        w_evals: &[E::Fr],
-        plookup_evals: &PlookupEvaluations<E::Fr>,
-        oracles: &PlookupOracles<E::Fr>,
-    ) -> DensePolynomial<E::Fr> {
+        pk: &PcsProvingKey<'c, PCS>,
+        challenges: &Challenges<PCS::Fr>,
+        w_evals: &[PCS::Fr],
+        plookup_evals: &PlookupEvaluations<PCS::Fr>,
+        oracles: &PlookupOracles<PCS::Fr>,
+    ) -> DensePolynomial<PCS::Fr> {
         let alpha_2 = challenges.alpha.square();
         let alpha_4 = alpha_2.square();
         let alpha_5 = alpha_4 * challenges.alpha;
         let alpha_6 = alpha_4 * alpha_2;
         let n = pk.domain_size();
-        let one = E::Fr::one();
+        let one = PCS::Fr::one();
         let vanish_eval = challenges.zeta.pow(&[n as u64]) - one;
 
         // compute lagrange_1 and lagrange_n
-        let divisor = E::Fr::from(n as u32) * (challenges.zeta - one);
+        let divisor = PCS::Fr::from(n as u32) * (challenges.zeta - one);
         let lagrange_1_eval = vanish_eval / divisor;
-        let divisor = E::Fr::from(n as u32) * (challenges.zeta - self.domain.group_gen_inv);
+        let divisor = PCS::Fr::from(n as u32) * (challenges.zeta - self.domain.group_gen_inv);
         let lagrange_n_eval = vanish_eval * self.domain.group_gen_inv / divisor;
 
         // compute the coefficient for polynomial `prod_lookup_poly`
-        let merged_table_eval = eval_merged_table::<E>(
+        let merged_table_eval = eval_merged_table(
             challenges.tau,
             plookup_evals.range_table_eval,
             plookup_evals.key_table_eval,
@@ -994,7 +1422,7 @@ impl<E: PairingEngine> Prover<E> {
             w_evals[3],
             w_evals[4],
         );
-        let merged_table_next_eval = eval_merged_table::<E>(
+        let merged_table_next_eval = eval_merged_table(
             challenges.tau,
             plookup_evals.range_table_next_eval,
             plookup_evals.key_table_next_eval,
@@ -1002,7 +1430,7 @@ impl<E: PairingEngine> Prover<E> {
             plookup_evals.w_3_next_eval,
             plookup_evals.w_4_next_eval,
         );
-        let merged_lookup_eval = eval_merged_lookup_witness::<E>(
+        let merged_lookup_eval = eval_merged_lookup_witness(
             challenges.tau,
             w_evals[5],
             w_evals[0],
@@ -1037,25 +1465,89 @@ impl<E: PairingEngine> Prover<E> {
     }
 
     #[inline]
-    fn mul_poly(poly: &DensePolynomial<E::Fr>, coeff: &E::Fr) -> DensePolynomial<E::Fr> {
-        DensePolynomial::<E::Fr>::from_coefficients_vec(
+    fn mul_poly(poly: &DensePolynomial<PCS::Fr>, coeff: &PCS::Fr) -> DensePolynomial<PCS::Fr> {
+        DensePolynomial::<PCS::Fr>::from_coefficients_vec(
             poly.coeffs.par_iter().map(|c| *coeff * c).collect(),
         )
     }
 }
 
-#[inline]
-fn quotient_polynomial_degree(domain_size: usize, num_wire_types: usize) -> usize {
-    num_wire_types * (domain_size + 1) + 2
+/// Evaluate the TurboPlonk gate identity, which vanishes on the evaluation
+/// domain for a satisfied circuit, from the evaluations `w` of the wires, `pi`
+/// of the public input polynomial and `q(j)` of the `j`-th selector, at the
+/// same point.
+pub(crate) fn eval_gate_identity<F: Field>(
+    w: &[F],
+    pi: &F,
+    q: impl Fn(usize) -> F,
+    custom_gates: &[CustomGateSpec<F>],
+) -> F {
+    // Selectors
+    // The order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc, [q_custom]
+    // TODO: (binyi) get the order from a function.
+    let q_lc: Vec<F> = (0..GATE_WIDTH).map(&q).collect();
+    let q_mul: Vec<F> = (GATE_WIDTH..GATE_WIDTH + 2).map(&q).collect();
+    let q_hash: Vec<F> = (GATE_WIDTH + 2..2 * GATE_WIDTH + 2).map(&q).collect();
+    let q_o = q(2 * GATE_WIDTH + 2);
+    let q_c = q(2 * GATE_WIDTH + 3);
+    let q_ecc = q(2 * GATE_WIDTH + 4);
+
+    q_c + pi
+        + q_lc[0] * w[0]
+        + q_lc[1] * w[1]
+        + q_lc[2] * w[2]
+        + q_lc[3] * w[3]
+        + q_mul[0] * w[0] * w[1]
+        + q_mul[1] * w[2] * w[3]
+        + q_ecc * w[0] * w[1] * w[2] * w[3] * w[4]
+        + q_hash[0] * w[0].pow(&[5])
+        + q_hash[1] * w[1].pow(&[5])
+        + q_hash[2] * w[2].pow(&[5])
+        + q_hash[3] * w[3].pow(&[5])
+        - q_o * w[4]
+        + custom_gates
+            .iter()
+            .enumerate()
+            .fold(F::zero(), |acc, (k, spec)| {
+                acc + q(N_TURBO_PLONK_SELECTORS + k) * spec.evaluate(w)
+            })
+}
+
+/// Evaluate the permutation identity at `x`
+///   \prod_i [w_i(x) + beta * k_i * x + gamma] * z(x)
+/// - \prod_i [w_i(x) + beta * sigma_i(x) + gamma] * z(gx),
+/// which vanishes on the evaluation domain of generator `g` for a satisfied
+/// circuit and its permutation product polynomial `z`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn eval_perm_identity<F: Field>(
+    w: &[F],
+    sigmas: &[F],
+    k: &[F],
+    x: F,
+    z_x: &F,
+    z_xw: &F,
+    beta: &F,
+    gamma: &F,
+) -> F {
+    w.iter()
+        .zip(k.iter())
+        .fold(*z_x, |acc, (&w, k)| acc * (w + *k * x * beta + gamma))
+        - w.iter()
+            .zip(sigmas.iter())
+            .fold(*z_xw, |acc, (&w, &sigma)| acc * (w + sigma * beta + gamma))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::proof_system::{pcs::KzgPcs, structs::trim, PlonkKzgSnark, Snark};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
     use ark_bn254::Bn254;
     use ark_bw6_761::BW6_761;
+    use ark_ec::{PairingEngine, ProjectiveCurve};
+    use ark_ff::PrimeField;
+    use ark_serialize::CanonicalSerialize;
     use ark_std::test_rng;
 
     #[test]
@@ -1068,12 +1560,66 @@ mod test {
 
     fn test_split_quotient_polynomial_wrong_degree_helper<E: PairingEngine>(
     ) -> Result<(), PlonkError> {
-        let prover = Prover::<E>::new(4, GATE_WIDTH + 1)?;
+        let prover = Prover::<KzgPcs<E>>::new(4, GATE_WIDTH + 1)?;
         let rng = &mut test_rng();
         let bad_quot_poly = DensePolynomial::<E::Fr>::rand(25, rng);
         assert!(prover
-            .split_quotient_polynomial(&bad_quot_poly, GATE_WIDTH + 1)
+            .split_quotient_polynomial(&bad_quot_poly, GATE_WIDTH + 1, GATE_WIDTH + 1)
             .is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_commit_polynomials_serial_parallel() -> Result<(), PlonkError> {
+        test_commit_polynomials_serial_parallel_helper::<Bn254>()?;
+        test_commit_polynomials_serial_parallel_helper::<Bls12_377>()?;
+        test_commit_polynomials_serial_parallel_helper::<Bls12_381>()?;
+        test_commit_polynomials_serial_parallel_helper::<BW6_761>()
+    }
+
+    fn test_commit_polynomials_serial_parallel_helper<E: PairingEngine>() -> Result<(), PlonkError>
+    {
+        let rng = &mut test_rng();
+        let max_degree = 1024;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let (ck, _) = trim(&srs.0, max_degree);
+        let prover = Prover::<KzgPcs<E>>::new(4, GATE_WIDTH + 1)?;
+        let polys: Vec<_> = [0, 1, 7, 100, 513, max_degree]
+            .iter()
+            .map(|&degree| DensePolynomial::<E::Fr>::rand(degree, rng))
+            .collect();
+
+        let commit_with_threads = |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+                .install(|| prover.commit_polynomials(&ck, &polys))
+        };
+        let serial_comms = commit_with_threads(1)?;
+        let mut serial_bytes = Vec::new();
+        serial_comms.serialize(&mut serial_bytes)?;
+        for &num_threads in [2, 3, 8].iter() {
+            let parallel_comms = commit_with_threads(num_threads)?;
+            assert_eq!(parallel_comms, serial_comms);
+            let mut parallel_bytes = Vec::new();
+            parallel_comms.serialize(&mut parallel_bytes)?;
+            assert_eq!(parallel_bytes, serial_bytes);
+        }
+
+        // the commitments match a plain serial sum of the scaled powers of the
+        // generator
+        for (poly, comm) in polys.iter().zip(serial_comms.iter()) {
+            let naive_comm = poly
+                .coeffs
+                .iter()
+                .zip(ck.powers_of_g.iter())
+                .fold(E::G1Projective::zero(), |acc, (coeff, base)| {
+                    acc + base.mul(coeff.into_repr())
+                })
+                .into_affine();
+            assert_eq!(comm.0, naive_comm);
+        }
+        Ok(())
+    }
 }
@@ -5,80 +5,119 @@
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
 //! Instantiations of Plonk-based proof systems
+#[cfg(any(test, feature = "test-rng"))]
+use super::prover::{num_blinding_scalars, ExplicitBlinding};
 use super::{
-    prover::Prover,
+    msm::{ArkworksMsm, MsmBackend},
+    pcs::{KzgPcs, PcsCommitter, PolynomialCommitmentScheme},
+    prover::{Blinder, KeyCosetEvals, NoBlinding, Prover, WirePolys},
     structs::{
-        trim, BatchProof, Challenges, Oracles, PlookupProof, PlookupProvingKey,
-        PlookupVerifyingKey, Proof, ProvingKey, VerifyingKey,
+        check_srs_size, quotient_chunk_size, quotient_polynomial_degree, trim, trim_borrowed,
+        witness_openings_srs_size, BatchProof, BatchProofView, Challenges, CompressedProof,
+        Oracles, PcsBatchProof, PcsPlookupProof, PcsPlookupProvingKey, PcsPlookupVerifyingKey,
+        PcsProof, PcsProvingKey, PcsVerifyingKey, Proof, ProverState, ProvingKey,
+        VerificationReceipt, VerifierPrecompute, VerifyingKey, WitnessCommitments,
     },
-    verifier::Verifier,
+    verifier::{PcsInfo, Verifier},
     Snark,
 };
+#[cfg(feature = "std")]
+use crate::profiling::ProveProfile;
 use crate::{
     circuit::{customized::ecc::SWToTEConParam, Arithmetization},
-    constants::{compute_coset_representatives, EXTRA_TRANSCRIPT_MSG_LABEL},
-    errors::{PlonkError, SnarkError::ParameterError},
+    constants::{
+        compute_coset_representatives, BEACON_TRANSCRIPT_MSG_LABEL, EXTRA_TRANSCRIPT_MSG_LABEL,
+        GATE_WIDTH, SRS_CHUNK_SIZE, SRS_FILE_LITTLE_ENDIAN, SRS_FILE_MAGIC, SRS_FILE_VERSION,
+    },
+    errors::{
+        PlonkError,
+        SnarkError::{self, ParameterError},
+    },
+    profiling::{install_in_pool, Phase, ProfileRecorder},
     proof_system::structs::UniversalSrs,
     transcript::*,
 };
-use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
-use ark_ff::{Field, One};
-use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::{kzg10::KZG10, PCUniversalParams};
+use ark_ec::{
+    msm::FixedBaseMSM, short_weierstrass_jacobian::GroupAffine, AffineCurve, PairingEngine,
+    ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_poly_commit::{
+    kzg10::{UniversalParams, KZG10},
+    PCUniversalParams,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, Write};
 use ark_std::{
+    cmp,
+    collections::BTreeMap,
     format,
     marker::PhantomData,
-    rand::{CryptoRng, RngCore},
+    mem,
+    rand::{CryptoRng, RngCore, SeedableRng},
     string::ToString,
     vec,
     vec::Vec,
 };
 use jf_rescue::RescueParameter;
+use rand_chacha::ChaChaRng;
 use rayon::prelude::*;
+use sha3::{Digest, Keccak256};
+
+/// A Plonk instantiated with the polynomial commitment scheme `PCS`.
+///
+/// Only the proofs over [`KzgPcs`], i.e. of [`PlonkKzgSnark`], are
+/// zero-knowledge: over the schemes whose opening proofs leak the opened
+/// polynomials, such as [`IpaPcs`](super::pcs::IpaPcs), the proofs are
+/// computed by [`Self::prove_without_zero_knowledge`].
+pub struct PlonkPcsSnark<'a, PCS: PolynomialCommitmentScheme>(PhantomData<&'a PCS>);
 
 /// A Plonk instantiated with KZG PCS
-pub struct PlonkKzgSnark<'a, E: PairingEngine>(PhantomData<&'a E>);
+pub type PlonkKzgSnark<'a, E> = PlonkPcsSnark<'a, KzgPcs<E>>;
 
-impl<'a, E, F, P> PlonkKzgSnark<'a, E>
+impl<'a, PCS, F, P> PlonkPcsSnark<'a, PCS>
 where
-    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
-    F: RescueParameter + SWToTEConParam,
+    PCS: PcsCommitter<'a, Curve = GroupAffine<P>>,
     P: SWModelParameters<BaseField = F> + Clone,
 {
     #[allow(clippy::new_without_default)]
-    /// A new Plonk KZG SNARK
+    /// A new Plonk SNARK
     pub fn new() -> Self {
         Self(PhantomData)
     }
 
-    /// Generate the universal SRS for the argument system.
-    /// This setup is for trusted party to run, and mostly only used for
-    /// testing purpose. In practice, a MPC flavor of the setup will be carried
-    /// out to have higher assurance on the "toxic waste"/trapdoor being thrown
-    /// away to ensure soundness of the argument system.
-    pub fn universal_setup<R: RngCore>(
-        max_degree: usize,
-        rng: &mut R,
-    ) -> Result<UniversalSrs<E>, PlonkError> {
-        let srs = KZG10::<E, DensePolynomial<E::Fr>>::setup(max_degree, false, rng)?;
-        Ok(UniversalSrs(srs))
-    }
-
     // TODO: (alex) move back to Snark trait when `trait PolynomialCommitment` is
     // implemented for KZG10
     /// Input a circuit and the SRS, precompute the proving key and verification
     /// key.
-    pub fn preprocess<C: Arithmetization<E::Fr>>(
-        srs: &'a UniversalSrs<E>,
+    /// Return [`PlonkError::SrsTooSmall`] if `srs` doesn't support the degree
+    /// [`Arithmetization::srs_size`] of the circuit.
+    pub fn preprocess<C: Arithmetization<PCS::Fr>>(
+        srs: &'a PCS::Params,
         circuit: &C,
-    ) -> Result<(ProvingKey<'a, E>, VerifyingKey<E>), PlonkError> {
+    ) -> Result<(PcsProvingKey<'a, PCS>, PcsVerifyingKey<PCS>), PlonkError> {
         // Make sure the SRS can support the circuit (with hiding degree of 2 for zk)
+        let (commit_key, open_key) = PCS::trim(srs, circuit.srs_size()?)?;
+        let k = compute_coset_representatives(
+            circuit.num_wire_types(),
+            Some(circuit.eval_domain_size()?),
+        );
+        Self::preprocess_with_keys(commit_key, open_key, k, circuit, circuit.num_wire_types())
+    }
+
+    // Precompute the proving key and verification key of a circuit given the
+    // keys trimmed from the SRS to the circuit size and the coset
+    // representatives `k` of its domain, for proofs with `num_quot_chunks`
+    // split quotient polynomials.
+    fn preprocess_with_keys<C: Arithmetization<PCS::Fr>>(
+        commit_key: PCS::CommitKey,
+        open_key: PCS::OpenKey,
+        k: Vec<PCS::Fr>,
+        circuit: &C,
+        num_quot_chunks: usize,
+    ) -> Result<(PcsProvingKey<'a, PCS>, PcsVerifyingKey<PCS>), PlonkError> {
         let domain_size = circuit.eval_domain_size()?;
-        let srs_size = circuit.srs_size()?;
         let num_inputs = circuit.num_inputs();
-        if srs.0.max_degree() < circuit.srs_size()? {
-            return Err(PlonkError::IndexTooLarge);
-        }
         // 1. Compute selector and permutation polynomials.
         let selectors_polys = circuit.compute_selector_polynomials()?;
         let sigma_polys = circuit.compute_extended_permutation_polynomials()?;
@@ -87,7 +126,7 @@ where
         let plookup_pk = if circuit.support_lookup() {
             let range_table_poly = circuit.compute_range_table_polynomial()?;
             let key_table_poly = circuit.compute_key_table_polynomial()?;
-            Some(PlookupProvingKey {
+            Some(PcsPlookupProvingKey {
                 range_table_poly,
                 key_table_poly,
             })
@@ -96,59 +135,44 @@ where
         };
 
         // 2. Compute VerifyingKey
-        let (commit_key, open_key) = trim(&srs.0, srs_size);
         let selector_comms: Vec<_> = selectors_polys
             .par_iter()
-            .map(|poly| {
-                let (comm, _) = KZG10::commit(&commit_key, poly, None, None)?;
-                Ok(comm)
-            })
-            .collect::<Result<Vec<_>, PlonkError>>()?
-            .into_iter()
-            .collect();
+            .map(|poly| PCS::commit(&commit_key, poly))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
         let sigma_comms: Vec<_> = sigma_polys
             .par_iter()
-            .map(|poly| {
-                let (comm, _) = KZG10::commit(&commit_key, poly, None, None)?;
-                Ok(comm)
-            })
-            .collect::<Result<Vec<_>, PlonkError>>()?
-            .into_iter()
-            .collect();
+            .map(|poly| PCS::commit(&commit_key, poly))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
         // Compute Plookup verifying key if support lookup.
         let plookup_vk = match circuit.support_lookup() {
             false => None,
-            true => Some(PlookupVerifyingKey {
-                range_table_comm: KZG10::commit(
+            true => Some(PcsPlookupVerifyingKey {
+                range_table_comm: PCS::commit(
                     &commit_key,
                     &plookup_pk.as_ref().unwrap().range_table_poly,
-                    None,
-                    None,
-                )?
-                .0,
-                key_table_comm: KZG10::commit(
+                )?,
+                key_table_comm: PCS::commit(
                     &commit_key,
                     &plookup_pk.as_ref().unwrap().key_table_poly,
-                    None,
-                    None,
-                )?
-                .0,
+                )?,
             }),
         };
 
-        let vk = VerifyingKey {
+        let vk = PcsVerifyingKey {
             domain_size,
             num_inputs,
             selector_comms,
             sigma_comms,
-            k: compute_coset_representatives(circuit.num_wire_types(), Some(domain_size)),
+            k,
             open_key,
             plookup_vk,
             is_merged: false,
+            custom_gates: circuit.custom_gates().to_vec(),
+            num_quot_chunks,
         };
 
         // Compute ProvingKey (which includes the VerifyingKey)
-        let pk = ProvingKey {
+        let pk = PcsProvingKey {
             sigmas: sigma_polys,
             selectors: selectors_polys,
             commit_key,
@@ -159,95 +183,79 @@ where
         Ok((pk, vk))
     }
 
-    /// Generate an aggregated Plonk proof for multiple instances.
-    pub fn batch_prove<C, R, T>(
-        prng: &mut R,
-        circuits: &[&C],
-        prove_keys: &[&ProvingKey<'a, E>],
-    ) -> Result<BatchProof<E>, PlonkError>
-    where
-        C: Arithmetization<E::Fr>,
-        R: CryptoRng + RngCore,
-        T: PlonkTranscript<F>,
-    {
-        let (batch_proof, ..) =
-            Self::batch_prove_internal::<_, _, T>(prng, circuits, prove_keys, None)?;
-        Ok(batch_proof)
-    }
-
-    /// Verify a single aggregated Plonk proof.
-    pub fn verify_batch_proof<T>(
-        verify_keys: &[&VerifyingKey<E>],
-        public_inputs: &[&[E::Fr]],
-        batch_proof: &BatchProof<E>,
-    ) -> Result<(), PlonkError>
+    /// Compute a Plonk proof without masking the polynomials, which saves the
+    /// sampling and the arithmetic of the blinding polynomials, for circuits
+    /// whose witness is entirely public anyway.
+    ///
+    /// **Warning**: the proof is NOT zero-knowledge. The commitments and the
+    /// evaluations at the challenge points are those of the bare witness
+    /// polynomials, so the proof leaks information about the witness, and
+    /// identical inputs always yield identical proofs. Never use it for a
+    /// circuit with a secret witness.
+    ///
+    /// The proof has the same format and size as the one from
+    /// [`Snark::prove`]: blinding only adds multiples of the vanishing
+    /// polynomial, which leave the polynomial identities checked by the
+    /// verifier unchanged, so it is verified as usual, and the verifier need
+    /// not (and cannot) tell whether the prover blinded it.
+    ///
+    /// It is the only prover over the schemes whose opening proofs leak the
+    /// opened polynomials, such as [`IpaPcs`](super::pcs::IpaPcs), since the
+    /// proofs over them cannot be zero-knowledge anyway: the other provers are
+    /// only available over [`KzgPcs`]. Such proofs are checked with
+    /// [`Self::verify_unbatched`].
+    pub fn prove_without_zero_knowledge<C, T>(
+        circuit: &C,
+        prove_key: &PcsProvingKey<'a, PCS>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<PcsProof<PCS>, PlonkError>
     where
+        C: Arithmetization<PCS::Fr>,
         T: PlonkTranscript<F>,
     {
-        if verify_keys.is_empty() {
-            return Err(ParameterError("empty verification keys".to_string()).into());
-        }
-        let verifier = Verifier::new(verify_keys[0].domain_size)?;
-        let pcs_info =
-            verifier.prepare_pcs_info::<T>(verify_keys, public_inputs, batch_proof, &None)?;
-        if !Verifier::batch_verify_opening_proofs::<T>(
-            &verify_keys[0].open_key, // all open_key are the same
-            &[pcs_info],
-        )? {
-            return Err(PlonkError::WrongProof);
-        }
-        Ok(())
+        let (proof, ..) = Self::prove_internal::<_, _, T>(
+            &mut NoBlinding,
+            circuit,
+            prove_key,
+            None,
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+        Ok(proof)
     }
 
-    /// Batch verify multiple SNARK proofs (w.r.t. different verifying keys).
-    pub fn batch_verify<T>(
-        verify_keys: &[&VerifyingKey<E>],
-        public_inputs: &[&[E::Fr]],
-        proofs: &[&Proof<E>],
-        extra_transcript_init_msgs: &[Option<Vec<u8>>],
+    /// Verify a Plonk proof by checking its opening proofs at `zeta` and
+    /// `zeta * g` one after the other with
+    /// [`PolynomialCommitmentScheme::verify`], instead of folding them into a
+    /// single pairing check as [`Snark::verify`] does, hence over any
+    /// polynomial commitment scheme. Over [`KzgPcs`], it takes two pairing
+    /// checks instead of one.
+    pub fn verify_unbatched<T>(
+        verify_key: &PcsVerifyingKey<PCS>,
+        public_input: &[PCS::Fr],
+        proof: &PcsProof<PCS>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
     ) -> Result<(), PlonkError>
     where
         T: PlonkTranscript<F>,
     {
-        if public_inputs.len() != proofs.len()
-            || verify_keys.len() != proofs.len()
-            || extra_transcript_init_msgs.len() != proofs.len()
-        {
-            return Err(ParameterError(format!(
-                "verify_keys.len: {}, public_inputs.len: {}, proofs.len: {}, \
-                 extra_transcript_msg.len: {}",
-                verify_keys.len(),
-                public_inputs.len(),
-                proofs.len(),
-                extra_transcript_init_msgs.len()
-            ))
-            .into());
-        }
-        if verify_keys.is_empty() {
-            return Err(
-                ParameterError("the number of instances cannot be zero".to_string()).into(),
-            );
-        }
-
-        let pcs_infos = verify_keys
-            .par_iter()
-            .zip(proofs.par_iter())
-            .zip(public_inputs.par_iter())
-            .zip(extra_transcript_init_msgs.par_iter())
-            .map(|(((&vk, &proof), &pub_input), extra_msg)| {
-                let verifier = Verifier::new(vk.domain_size)?;
-                verifier.prepare_pcs_info::<T>(
-                    &[vk],
-                    &[pub_input],
-                    &(*proof).clone().into(),
-                    extra_msg,
-                )
-            })
-            .collect::<Result<Vec<_>, PlonkError>>()?;
-
-        if !Verifier::batch_verify_opening_proofs::<T>(
-            &verify_keys[0].open_key, // all open_key are the same
-            &pcs_infos,
+        let verifier = Verifier::<PCS>::new(verify_key.domain_size)?;
+        let batch_proof = BatchProofView::from(proof);
+        let (claims, challenges, _) = verifier.prepare_opening_claims_and_transcript::<T>(
+            &[verify_key],
+            &[public_input],
+            &batch_proof,
+            &extra_transcript_init_msg,
+        )?;
+        if !Verifier::<PCS>::verify_opening_proofs::<T>(
+            &verify_key.open_key,
+            &claims,
+            &batch_proof,
+            &challenges.v,
         )? {
             return Err(PlonkError::WrongProof);
         }
@@ -257,21 +265,40 @@ where
     /// An internal private API for ease of testing
     ///
     /// Batchly compute a Plonk proof for multiple instances. Return the batch
-    /// proof and the corresponding online polynomial oracles and
-    /// challenges. Refer to Sec 8.4 of https://eprint.iacr.org/2019/953.pdf
+    /// proof, the corresponding online polynomial oracles and
+    /// challenges, and the final transcript state. Refer to Sec 8.4 of https://eprint.iacr.org/2019/953.pdf
     ///
     /// `circuit` and `prove_key` has to be consistent (with the same evaluation
     /// domain etc.), otherwise return error.
-    #[allow(clippy::type_complexity)]
+    ///
+    /// The precomputed coset evaluations `key_coset_evals` of the proving key
+    /// can only be given for a single instance. The wire witness polynomials
+    /// are masked to stay hidden after `num_extra_openings` openings besides
+    /// the ones of the proof.
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
     fn batch_prove_internal<C, R, T>(
         prng: &mut R,
         circuits: &[&C],
-        prove_keys: &[&ProvingKey<'a, E>],
+        prove_keys: &[&PcsProvingKey<'a, PCS>],
+        mut wire_polys_vec: Option<Vec<WirePolys<PCS>>>,
         extra_transcript_init_msg: Option<Vec<u8>>,
-    ) -> Result<(BatchProof<E>, Vec<Oracles<E::Fr>>, Challenges<E::Fr>), PlonkError>
+        profile: ProfileRecorder,
+        msm: &dyn MsmBackend<PCS::Curve>,
+        memory_budget: Option<usize>,
+        key_coset_evals: Option<&KeyCosetEvals<PCS::Fr>>,
+        num_extra_openings: usize,
+    ) -> Result<
+        (
+            PcsBatchProof<PCS>,
+            Vec<Oracles<PCS::Fr>>,
+            Challenges<PCS::Fr>,
+            T,
+        ),
+        PlonkError,
+    >
     where
-        C: Arithmetization<E::Fr>,
-        R: CryptoRng + RngCore,
+        C: Arithmetization<PCS::Fr>,
+        R: Blinder<PCS::Fr>,
         T: PlonkTranscript<F>,
     {
         if circuits.is_empty() {
@@ -285,6 +312,12 @@ where
             ))
             .into());
         }
+        if key_coset_evals.is_some() && circuits.len() != 1 {
+            return Err(ParameterError(
+                "precomputed coset evaluations of a proving key for multiple instances".to_string(),
+            )
+            .into());
+        }
         let n = circuits[0].eval_domain_size()?;
         let num_wire_types = circuits[0].num_wire_types();
         for (circuit, pk) in circuits.iter().zip(prove_keys.iter()) {
@@ -321,36 +354,68 @@ where
             if circuit.num_wire_types() != num_wire_types {
                 return Err(ParameterError("inconsistent plonk circuit types".to_string()).into());
             }
+            if pk.vk.num_quot_chunks != prove_keys[0].vk.num_quot_chunks {
+                return Err(ParameterError(
+                    "inconsistent quotient polynomial splits of the proving keys".to_string(),
+                )
+                .into());
+            }
         }
 
+        let prove_phase = Phase::begin("prove");
         // Initialize transcript
         let mut transcript = T::new(b"PlonkProof");
         if let Some(msg) = extra_transcript_init_msg {
             transcript.append_message(EXTRA_TRANSCRIPT_MSG_LABEL, &msg)?;
         }
         for (pk, circuit) in prove_keys.iter().zip(circuits.iter()) {
-            transcript.append_vk_and_pub_input(&pk.vk, &circuit.public_input()?)?;
+            transcript.append_vk_and_pub_input::<PCS, P>(&pk.vk, &circuit.public_input()?)?;
         }
         // Initialize verifier challenges and online polynomial oracles.
         let mut challenges = Challenges::default();
         let mut online_oracles = vec![Oracles::default(); circuits.len()];
-        let prover = Prover::new(n, num_wire_types)?;
+        let prover = Prover::<PCS>::new(n, num_wire_types)?
+            .with_profile(profile)
+            .with_msm_backend(msm)
+            .with_hiding(prng.is_hiding())
+            .with_extra_openings(num_wire_types, num_extra_openings)?
+            .with_memory_budget(memory_budget)
+            .with_key_coset_evals(key_coset_evals)?;
 
         // Round 1
+        let phase = Phase::begin("round 1");
         let mut wires_poly_comms_vec = vec![];
         for i in 0..circuits.len() {
-            let ((wires_poly_comms, wire_polys), pi_poly) =
-                prover.run_1st_round(prng, &prove_keys[i].commit_key, circuits[i])?;
+            let ((wires_poly_comms, wire_polys), pi_poly) = match wire_polys_vec.as_mut() {
+                Some(wire_polys_vec) => {
+                    match mem::replace(&mut wire_polys_vec[i], WirePolys::Unmasked(vec![])) {
+                        WirePolys::Unmasked(wire_polys) => prover.run_1st_round_with_wire_polys(
+                            prng,
+                            &prove_keys[i].commit_key,
+                            circuits[i],
+                            wire_polys,
+                        )?,
+                        WirePolys::Committed(comms_and_polys) => {
+                            (comms_and_polys, circuits[i].compute_pub_input_polynomial()?)
+                        },
+                    }
+                },
+                None => prover.run_1st_round(prng, &prove_keys[i].commit_key, circuits[i])?,
+            };
             online_oracles[i].wire_polys = wire_polys;
             online_oracles[i].pub_inp_poly = pi_poly;
-            transcript.append_commitments(b"witness_poly_comms", &wires_poly_comms)?;
+            transcript
+                .append_pcs_commitments::<PCS, P>(b"witness_poly_comms", &wires_poly_comms)?;
             wires_poly_comms_vec.push(wires_poly_comms);
         }
 
+        phase.end();
+
         // Round 1.5
         // Plookup: compute and interpolate the sorted concatenation of the (merged)
         // lookup table and the (merged) witness values
-        challenges.tau = transcript.get_and_append_challenge::<E>(b"tau")?;
+        let phase = Phase::begin("round 1.5");
+        challenges.tau = transcript.get_and_append_field_challenge::<PCS::Fr>(b"tau")?;
         let mut h_poly_comms_vec = vec![];
         let mut sorted_vec_list = vec![];
         let mut merged_table_list = vec![];
@@ -364,7 +429,7 @@ where
                         challenges.tau,
                     )?;
                 online_oracles[i].plookup_oracles.h_polys = h_polys;
-                transcript.append_commitments(b"h_poly_comms", &h_poly_comms)?;
+                transcript.append_pcs_commitments::<PCS, P>(b"h_poly_comms", &h_poly_comms)?;
                 (Some(sorted_vec), Some(h_poly_comms), Some(merged_table))
             } else {
                 (None, None, None)
@@ -374,20 +439,26 @@ where
             merged_table_list.push(merged_table);
         }
 
+        phase.end();
+
         // Round 2
-        challenges.beta = transcript.get_and_append_challenge::<E>(b"beta")?;
-        challenges.gamma = transcript.get_and_append_challenge::<E>(b"gamma")?;
+        let phase = Phase::begin("round 2");
+        challenges.beta = transcript.get_and_append_field_challenge::<PCS::Fr>(b"beta")?;
+        challenges.gamma = transcript.get_and_append_field_challenge::<PCS::Fr>(b"gamma")?;
         let mut prod_perm_poly_comms_vec = vec![];
         for i in 0..circuits.len() {
             let (prod_perm_poly_comm, prod_perm_poly) =
                 prover.run_2nd_round(prng, &prove_keys[i].commit_key, circuits[i], &challenges)?;
             online_oracles[i].prod_perm_poly = prod_perm_poly;
-            transcript.append_commitment(b"perm_poly_comms", &prod_perm_poly_comm)?;
+            transcript.append_pcs_commitment::<PCS, P>(b"perm_poly_comms", &prod_perm_poly_comm)?;
             prod_perm_poly_comms_vec.push(prod_perm_poly_comm);
         }
 
+        phase.end();
+
         // Round 2.5
         // Plookup: compute Plookup product accumulation polynomial
+        let phase = Phase::begin("round 2.5");
         let mut prod_lookup_poly_comms_vec = vec![];
         for i in 0..circuits.len() {
             let prod_lookup_poly_comm = if circuits[i].support_lookup() {
@@ -400,7 +471,10 @@ where
                     sorted_vec_list[i].as_ref(),
                 )?;
                 online_oracles[i].plookup_oracles.prod_lookup_poly = prod_lookup_poly;
-                transcript.append_commitment(b"plookup_poly_comms", &prod_lookup_poly_comm)?;
+                transcript.append_pcs_commitment::<PCS, P>(
+                    b"plookup_poly_comms",
+                    &prod_lookup_poly_comm,
+                )?;
                 Some(prod_lookup_poly_comm)
             } else {
                 None
@@ -408,19 +482,26 @@ where
             prod_lookup_poly_comms_vec.push(prod_lookup_poly_comm);
         }
 
+        phase.end();
+
         // Round 3
-        challenges.alpha = transcript.get_and_append_challenge::<E>(b"alpha")?;
+        let phase = Phase::begin("round 3");
+        challenges.alpha = transcript.get_and_append_field_challenge::<PCS::Fr>(b"alpha")?;
         let (split_quot_poly_comms, split_quot_polys) = prover.run_3rd_round(
             &prove_keys[0].commit_key,
             prove_keys,
             &challenges,
             &online_oracles,
             num_wire_types,
+            prove_keys[0].vk.num_quot_chunks,
         )?;
-        transcript.append_commitments(b"quot_poly_comms", &split_quot_poly_comms)?;
+        transcript.append_pcs_commitments::<PCS, P>(b"quot_poly_comms", &split_quot_poly_comms)?;
+
+        phase.end();
 
         // Round 4
-        challenges.zeta = transcript.get_and_append_challenge::<E>(b"zeta")?;
+        let phase = Phase::begin("round 4");
+        challenges.zeta = transcript.get_and_append_field_challenge::<PCS::Fr>(b"zeta")?;
         let mut poly_evals_vec = vec![];
         for i in 0..circuits.len() {
             let poly_evals = prover.compute_evaluations(
@@ -429,7 +510,7 @@ where
                 &online_oracles[i],
                 num_wire_types,
             );
-            transcript.append_proof_evaluations::<E>(&poly_evals)?;
+            transcript.append_proof_evaluations::<PCS::Fr>(&poly_evals)?;
             poly_evals_vec.push(poly_evals);
         }
 
@@ -443,7 +524,7 @@ where
                     &challenges,
                     &online_oracles[i],
                 )?;
-                transcript.append_plookup_evaluations::<E>(&evals)?;
+                transcript.append_plookup_evaluations::<PCS::Fr>(&evals)?;
                 Some(evals)
             } else {
                 None
@@ -451,12 +532,13 @@ where
             plookup_evals_vec.push(plookup_evals);
         }
 
-        let mut lin_poly = Prover::<E>::compute_quotient_component_for_lin_poly(
+        let mut lin_poly = Prover::<PCS>::compute_quotient_component_for_lin_poly(
             n,
+            prove_keys[0].vk.quot_chunk_size(),
             challenges.zeta,
             &split_quot_polys,
         )?;
-        let mut alpha_base = E::Fr::one();
+        let mut alpha_base = PCS::Fr::one();
         let alpha_3 = challenges.alpha.square() * challenges.alpha;
         let alpha_7 = alpha_3.square() * challenges.alpha;
         for i in 0..circuits.len() {
@@ -478,10 +560,18 @@ where
             }
         }
 
+        phase.end();
+
         // Round 5
-        challenges.v = transcript.get_and_append_challenge::<E>(b"v")?;
+        let phase = Phase::begin("round 5");
+        challenges.v = transcript.get_and_append_field_challenge::<PCS::Fr>(b"v")?;
+        // the schemes with an interactive opening draw their challenges from a
+        // transcript of their own, bound to the proof by `v`
+        let mut opening_transcript = T::new(b"PlonkOpening");
+        opening_transcript.append_field_challenge(b"v", &challenges.v)?;
         let (opening_proof, shifted_opening_proof) = prover.compute_opening_proofs(
             &prove_keys[0].commit_key,
+            &mut opening_transcript,
             prove_keys,
             &challenges.zeta,
             &challenges.v,
@@ -493,7 +583,7 @@ where
         let mut plookup_proofs_vec = vec![];
         for i in 0..circuits.len() {
             let plookup_proof = if circuits[i].support_lookup() {
-                Some(PlookupProof {
+                Some(PcsPlookupProof {
                     h_poly_comms: h_poly_comms_vec[i].clone().unwrap(),
                     prod_lookup_poly_comm: prod_lookup_poly_comms_vec[i].unwrap(),
                     poly_evals: plookup_evals_vec[i].clone().unwrap(),
@@ -504,8 +594,16 @@ where
             plookup_proofs_vec.push(plookup_proof);
         }
 
+        // Bind the opening proofs to the transcript so that its final state
+        // matches the one reconstructed by the verifier.
+        transcript.append_pcs_proof::<PCS, P>(b"open_proof", &opening_proof)?;
+        transcript.append_pcs_proof::<PCS, P>(b"shifted_open_proof", &shifted_opening_proof)?;
+        challenges.u = transcript.get_and_append_field_challenge::<PCS::Fr>(b"u")?;
+        phase.end();
+        prove_phase.end();
+
         Ok((
-            BatchProof {
+            PcsBatchProof {
                 wires_poly_comms_vec,
                 prod_perm_poly_comms_vec,
                 poly_evals_vec,
@@ -516,33 +614,316 @@ where
             },
             online_oracles,
             challenges,
+            transcript,
+        ))
+    }
+
+    // Compute a Plonk proof, from the precomputed wire polynomials and coset
+    // evaluations of the proving key if any, with wire polynomials hidden
+    // after `num_extra_openings` more openings, and return it along with its
+    // online oracles, its challenges and the final transcript state.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_internal<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &PcsProvingKey<'a, PCS>,
+        wire_polys: Option<WirePolys<PCS>>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+        profile: ProfileRecorder,
+        msm: &dyn MsmBackend<PCS::Curve>,
+        memory_budget: Option<usize>,
+        key_coset_evals: Option<&KeyCosetEvals<PCS::Fr>>,
+        num_extra_openings: usize,
+    ) -> Result<(PcsProof<PCS>, Oracles<PCS::Fr>, Challenges<PCS::Fr>, T), PlonkError>
+    where
+        C: Arithmetization<PCS::Fr>,
+        R: Blinder<PCS::Fr>,
+        T: PlonkTranscript<F>,
+    {
+        let (batch_proof, mut online_oracles, challenges, transcript) =
+            Self::batch_prove_internal::<_, _, T>(
+                prng,
+                &[circuit],
+                &[prove_key],
+                wire_polys.map(|wire_polys| vec![wire_polys]),
+                extra_transcript_init_msg,
+                profile,
+                msm,
+                memory_budget,
+                key_coset_evals,
+                num_extra_openings,
+            )?;
+        Ok((
+            PcsProof {
+                wires_poly_comms: batch_proof.wires_poly_comms_vec[0].clone(),
+                prod_perm_poly_comm: batch_proof.prod_perm_poly_comms_vec[0],
+                split_quot_poly_comms: batch_proof.split_quot_poly_comms,
+                opening_proof: batch_proof.opening_proof,
+                shifted_opening_proof: batch_proof.shifted_opening_proof,
+                poly_evals: batch_proof.poly_evals_vec[0].clone(),
+                plookup_proof: batch_proof.plookup_proofs_vec[0].clone(),
+            },
+            online_oracles.remove(0),
+            challenges,
+            transcript,
         ))
     }
 }
 
-impl<'a, E, F, P> Snark<E> for PlonkKzgSnark<'a, E>
+impl<'a, E, F, P> PlonkKzgSnark<'a, E>
 where
     E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWModelParameters<BaseField = F> + Clone,
 {
-    type Proof = Proof<E>;
+    /// Generate the universal SRS for the argument system.
+    /// This setup is for trusted party to run, and mostly only used for
+    /// testing purpose. In practice, a MPC flavor of the setup will be carried
+    /// out to have higher assurance on the "toxic waste"/trapdoor being thrown
+    /// away to ensure soundness of the argument system.
+    pub fn universal_setup<R: RngCore>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<UniversalSrs<E>, PlonkError> {
+        let srs = KZG10::<E, DensePolynomial<E::Fr>>::setup(max_degree, false, rng)?;
+        Ok(UniversalSrs(srs))
+    }
 
-    type ProvingKey = ProvingKey<'a, E>;
+    /// Generate the same universal SRS as [`Self::universal_setup`] does for
+    /// the same `rng`, but write it to `writer` as it is computed rather than
+    /// holding it in memory, which only holds a chunk of powers at a time.
+    ///
+    /// The output starts with a header made of the magic bytes `JFSRS`, the
+    /// version of the format, a 32-byte id of the curve, an endianness flag
+    /// and `max_degree` as 8 bytes, all in little-endian. It is followed by
+    /// `h` and `beta * h`, then the pairs `(beta^i * g, beta^i * gamma_g)` for
+    /// `i` in [0, `max_degree` + 1], as uncompressed points.
+    /// Use [`Self::load_srs`] to read it back.
+    pub fn universal_setup_streaming<R: RngCore + CryptoRng, W: Write>(
+        max_degree: usize,
+        rng: &mut R,
+        mut writer: W,
+    ) -> Result<(), PlonkError> {
+        if max_degree < 1 {
+            return Err(ark_poly_commit::Error::DegreeIsZero.into());
+        }
+        // the randomness is drawn in the same order as in `KZG10::setup`
+        let beta = E::Fr::rand(rng);
+        let g = E::G1Projective::rand(rng);
+        let gamma_g = E::G1Projective::rand(rng);
+        let h = E::G2Projective::rand(rng).into_affine();
+
+        write_srs_header::<E, _>(&mut writer, max_degree)?;
+        h.serialize_uncompressed(&mut writer)?;
+        h.mul(beta)
+            .into_affine()
+            .serialize_uncompressed(&mut writer)?;
+
+        let num_powers = max_degree + 2;
+        let scalar_bits = E::Fr::size_in_bits();
+        let window_size = FixedBaseMSM::get_mul_window_size(cmp::min(num_powers, SRS_CHUNK_SIZE));
+        let g_table = FixedBaseMSM::get_window_table(scalar_bits, window_size, g);
+        let gamma_g_table = FixedBaseMSM::get_window_table(scalar_bits, window_size, gamma_g);
+        let mut power_of_beta = E::Fr::one();
+        let mut num_written = 0;
+        while num_written < num_powers {
+            let chunk_size = cmp::min(SRS_CHUNK_SIZE, num_powers - num_written);
+            let powers_of_beta: Vec<E::Fr> = (0..chunk_size)
+                .map(|_| {
+                    let power = power_of_beta;
+                    power_of_beta *= beta;
+                    power
+                })
+                .collect();
+            let powers_of_g = E::G1Projective::batch_normalization_into_affine(
+                &FixedBaseMSM::multi_scalar_mul::<E::G1Projective>(
+                    scalar_bits,
+                    window_size,
+                    &g_table,
+                    &powers_of_beta,
+                ),
+            );
+            let powers_of_gamma_g = E::G1Projective::batch_normalization_into_affine(
+                &FixedBaseMSM::multi_scalar_mul::<E::G1Projective>(
+                    scalar_bits,
+                    window_size,
+                    &gamma_g_table,
+                    &powers_of_beta,
+                ),
+            );
+            for (g_i, gamma_g_i) in powers_of_g.iter().zip(powers_of_gamma_g.iter()) {
+                g_i.serialize_uncompressed(&mut writer)?;
+                gamma_g_i.serialize_uncompressed(&mut writer)?;
+            }
+            num_written += chunk_size;
+        }
+        Ok(())
+    }
 
-    type VerifyingKey = VerifyingKey<E>;
+    /// Read the universal SRS written by [`Self::universal_setup_streaming`],
+    /// up to the degree `up_to_degree`. Only the needed prefix of `reader` is
+    /// read, and the SRS is the one [`Self::universal_setup`] would generate
+    /// for `up_to_degree` with the same randomness.
+    /// Return error if the header does not match the format or the curve, or
+    /// if `up_to_degree` is zero or exceeds the degree of the SRS.
+    pub fn load_srs<Rd: Read>(
+        mut reader: Rd,
+        up_to_degree: usize,
+    ) -> Result<UniversalSrs<E>, PlonkError> {
+        let max_degree = read_srs_header::<E, _>(&mut reader)?;
+        if up_to_degree < 1 || up_to_degree > max_degree {
+            return Err(ParameterError(format!(
+                "the degree {} should be in [1, {}]",
+                up_to_degree, max_degree
+            ))
+            .into());
+        }
+        let h = E::G2Affine::deserialize_uncompressed(&mut reader)?;
+        let beta_h = E::G2Affine::deserialize_uncompressed(&mut reader)?;
+        let mut powers_of_g = Vec::with_capacity(up_to_degree + 1);
+        let mut powers_of_gamma_g = BTreeMap::new();
+        for i in 0..up_to_degree + 2 {
+            let g_i = E::G1Affine::deserialize_uncompressed(&mut reader)?;
+            let gamma_g_i = E::G1Affine::deserialize_uncompressed(&mut reader)?;
+            // one more power of gamma_g than of g, as in `KZG10::setup`
+            if i <= up_to_degree {
+                powers_of_g.push(g_i);
+            }
+            powers_of_gamma_g.insert(i, gamma_g_i);
+        }
+        Ok(UniversalSrs(UniversalParams {
+            powers_of_g,
+            powers_of_gamma_g,
+            h,
+            beta_h,
+            neg_powers_of_h: BTreeMap::new(),
+            prepared_h: h.into(),
+            prepared_beta_h: beta_h.into(),
+        }))
+    }
 
-    /// Compute a Plonk proof.
-    /// Refer to Sec 8.4 of <https://eprint.iacr.org/2019/953.pdf>
+    /// Same as [`Self::preprocess`], but the proofs w.r.t. the keys split the
+    /// quotient polynomial into `num_quotient_chunks` polynomials instead of
+    /// one per wire type, which is the default of [`Self::preprocess`].
     ///
-    /// `circuit` and `prove_key` has to be consistent (with the same evaluation
-    /// domain etc.), otherwise return error.
-    fn prove<C, R, T>(
-        prng: &mut R,
+    /// Fewer chunks make smaller proofs, with one group element and one
+    /// committed polynomial less per chunk, but the chunks have a larger
+    /// degree and need a larger SRS. More chunks only lower the degree of the
+    /// chunks: the SRS still has to support the circuit size.
+    ///
+    /// Return error if `num_quotient_chunks` is zero or exceeds the number of
+    /// coefficients of the quotient polynomial, or if `srs` doesn't support
+    /// the chunks, with [`PlonkError::SrsTooSmall`] in the latter case.
+    pub fn preprocess_with_quotient_chunks<C: Arithmetization<E::Fr>>(
+        srs: &'a UniversalSrs<E>,
         circuit: &C,
-        prove_key: &Self::ProvingKey,
-        extra_transcript_init_msg: Option<Vec<u8>>,
-    ) -> Result<Self::Proof, PlonkError>
+        num_quotient_chunks: usize,
+    ) -> Result<(ProvingKey<'a, E>, VerifyingKey<E>), PlonkError> {
+        let domain_size = circuit.eval_domain_size()?;
+        let num_wire_types = circuit.num_wire_types();
+        let quot_degree = quotient_polynomial_degree(domain_size, num_wire_types);
+        if num_quotient_chunks == 0 || num_quotient_chunks > quot_degree + 1 {
+            return Err(ParameterError(format!(
+                "the number of quotient chunks {} is not in [1, {}]",
+                num_quotient_chunks,
+                quot_degree + 1
+            ))
+            .into());
+        }
+        let chunk_degree =
+            quotient_chunk_size(domain_size, num_wire_types, num_quotient_chunks) - 1;
+        let srs_size = circuit.srs_size()?.max(chunk_degree);
+        check_srs_size(srs, srs_size)?;
+        let (commit_key, open_key) = trim(&srs.0, srs_size);
+        let k = compute_coset_representatives(num_wire_types, Some(domain_size));
+        Self::preprocess_with_keys(commit_key, open_key, k, circuit, num_quotient_chunks)
+    }
+
+    /// Same as [`Self::preprocess`], but the proving key supports proofs from
+    /// [`Self::prove_with_witness_commitments`] whose wire witness
+    /// polynomials can be opened at up to `num_extra_openings` more points.
+    /// The verifying key is the one of [`Self::preprocess`].
+    /// Return [`PlonkError::SrsTooSmall`] if `srs` doesn't support the masked
+    /// polynomials of these proofs.
+    pub fn preprocess_with_witness_openings<C: Arithmetization<E::Fr>>(
+        srs: &'a UniversalSrs<E>,
+        circuit: &C,
+        num_extra_openings: usize,
+    ) -> Result<(ProvingKey<'a, E>, VerifyingKey<E>), PlonkError> {
+        let domain_size = circuit.eval_domain_size()?;
+        let num_wire_types = circuit.num_wire_types();
+        let srs_size = witness_openings_srs_size(
+            domain_size,
+            num_wire_types,
+            num_wire_types,
+            num_extra_openings,
+        );
+        check_srs_size(srs, srs_size)?;
+        let (commit_key, open_key) = trim(&srs.0, srs_size);
+        let k = compute_coset_representatives(num_wire_types, Some(domain_size));
+        Self::preprocess_with_keys(commit_key, open_key, k, circuit, num_wire_types)
+    }
+
+    /// Same as [`Self::preprocess`], but also check the consistency of the
+    /// keys with [`ProvingKey::check_consistency`], at the cost of committing
+    /// to the preprocessed polynomials twice.
+    pub fn preprocess_checked<C: Arithmetization<E::Fr>>(
+        srs: &'a UniversalSrs<E>,
+        circuit: &C,
+    ) -> Result<(ProvingKey<'a, E>, VerifyingKey<E>), PlonkError> {
+        let (pk, vk) = Self::preprocess(srs, circuit)?;
+        pk.check_consistency(&vk)?;
+        Ok((pk, vk))
+    }
+
+    /// Input circuits and the SRS, precompute the proving keys and verification
+    /// keys of all the circuits in parallel. The keys derived from the SRS are
+    /// computed once for all the circuits of the same size, and the proving
+    /// keys borrow the powers of `srs` instead of copying them. Return error
+    /// if any of the circuits is not finalized or is too large for the SRS.
+    pub fn batch_preprocess<C: Arithmetization<E::Fr> + Sync>(
+        srs: &'a UniversalSrs<E>,
+        circuits: &[&C],
+    ) -> Result<Vec<(ProvingKey<'a, E>, VerifyingKey<E>)>, PlonkError> {
+        let mut keys_by_size = BTreeMap::new();
+        let mut coset_reps_by_domain = BTreeMap::new();
+        for circuit in circuits.iter() {
+            let srs_size = circuit.srs_size()?;
+            check_srs_size(srs, srs_size)?;
+            keys_by_size
+                .entry(srs_size)
+                .or_insert_with(|| trim_borrowed(&srs.0, srs_size));
+            let domain_size = circuit.eval_domain_size()?;
+            let num_wire_types = circuit.num_wire_types();
+            coset_reps_by_domain
+                .entry((domain_size, num_wire_types))
+                .or_insert_with(|| {
+                    compute_coset_representatives(num_wire_types, Some(domain_size))
+                });
+        }
+        circuits
+            .par_iter()
+            .map(|circuit| {
+                let (commit_key, open_key) = &keys_by_size[&circuit.srs_size()?];
+                let k =
+                    &coset_reps_by_domain[&(circuit.eval_domain_size()?, circuit.num_wire_types())];
+                Self::preprocess_with_keys(
+                    commit_key.clone(),
+                    open_key.clone(),
+                    k.clone(),
+                    *circuit,
+                    circuit.num_wire_types(),
+                )
+            })
+            .collect()
+    }
+
+    /// Generate an aggregated Plonk proof for multiple instances.
+    pub fn batch_prove<C, R, T>(
+        prng: &mut R,
+        circuits: &[&C],
+        prove_keys: &[&ProvingKey<'a, E>],
+    ) -> Result<BatchProof<E>, PlonkError>
     where
         C: Arithmetization<E::Fr>,
         R: CryptoRng + RngCore,
@@ -550,468 +931,4577 @@ where
     {
         let (batch_proof, ..) = Self::batch_prove_internal::<_, _, T>(
             prng,
-            &[circuit],
-            &[prove_key],
-            extra_transcript_init_msg,
+            circuits,
+            prove_keys,
+            None,
+            None,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
         )?;
-        Ok(Proof {
-            wires_poly_comms: batch_proof.wires_poly_comms_vec[0].clone(),
-            prod_perm_poly_comm: batch_proof.prod_perm_poly_comms_vec[0],
-            split_quot_poly_comms: batch_proof.split_quot_poly_comms,
-            opening_proof: batch_proof.opening_proof,
-            shifted_opening_proof: batch_proof.shifted_opening_proof,
-            poly_evals: batch_proof.poly_evals_vec[0].clone(),
-            plookup_proof: batch_proof.plookup_proofs_vec[0].clone(),
-        })
+        Ok(batch_proof)
     }
 
-    fn verify<T>(
-        verify_key: &Self::VerifyingKey,
-        public_input: &[E::Fr],
-        proof: &Self::Proof,
+    /// Compute one Plonk proof for each of `circuits`, that share the same
+    /// proving key `prove_key`, e.g. the same circuit for many witnesses.
+    /// Unlike [`Self::batch_prove`], each proof is verified on its own.
+    ///
+    /// The evaluations of the polynomials of `prove_key` over the quotient
+    /// domain, which take most of the FFTs that don't depend on the witness,
+    /// are computed once for all the proofs, which are then computed in
+    /// parallel. Each proof is blinded by its own RNG seeded from `prng`.
+    /// The proofs are identical to the ones from [`Snark::prove`] given the
+    /// same per proof RNGs.
+    /// Return error if `circuits` is empty or if any circuit is
+    /// inconsistent with `prove_key`.
+    pub fn prove_many<C, R, T>(
+        prng: &mut R,
+        circuits: &[&C],
+        prove_key: &ProvingKey<'a, E>,
         extra_transcript_init_msg: Option<Vec<u8>>,
-    ) -> Result<(), PlonkError>
+    ) -> Result<Vec<Proof<E>>, PlonkError>
     where
+        C: Arithmetization<E::Fr> + Sync,
+        R: CryptoRng + RngCore,
         T: PlonkTranscript<F>,
     {
-        Self::batch_verify::<T>(
-            &[verify_key],
+        if circuits.is_empty() {
+            return Err(ParameterError("zero number of circuits".to_string()).into());
+        }
+        let prover =
+            Prover::<KzgPcs<E>>::new(prove_key.domain_size(), circuits[0].num_wire_types())?;
+        let key_coset_evals = prover.compute_key_coset_evals(prove_key);
+        let seeds: Vec<[u8; 32]> = circuits
+            .iter()
+            .map(|_| {
+                let mut seed = [0u8; 32];
+                prng.fill_bytes(&mut seed);
+                seed
+            })
+            .collect();
+        circuits
+            .par_iter()
+            .zip(seeds.into_par_iter())
+            .map(|(circuit, seed)| {
+                let (proof, ..) = Self::prove_internal::<_, _, T>(
+                    &mut ChaChaRng::from_seed(seed),
+                    *circuit,
+                    prove_key,
+                    None,
+                    extra_transcript_init_msg.clone(),
+                    ProfileRecorder::default(),
+                    &ArkworksMsm,
+                    None,
+                    Some(&key_coset_evals),
+                    0,
+                )?;
+                Ok(proof)
+            })
+            .collect()
+    }
+
+    /// Verify a single aggregated Plonk proof.
+    pub fn verify_batch_proof<T>(
+        verify_keys: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        batch_proof: &BatchProof<E>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if verify_keys.is_empty() {
+            return Err(ParameterError("empty verification keys".to_string()).into());
+        }
+        let verifier = Verifier::<KzgPcs<E>>::new(verify_keys[0].domain_size)?;
+        let pcs_info = verifier.prepare_pcs_info::<T>(
+            verify_keys,
+            public_inputs,
+            &batch_proof.into(),
+            &None,
+        )?;
+        if !Verifier::<KzgPcs<E>>::batch_verify_opening_proofs::<T>(
+            &verify_keys[0].open_key, // all open_key are the same
+            &[pcs_info],
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
+    }
+
+    /// Batch verify multiple SNARK proofs (w.r.t. different verifying keys).
+    ///
+    /// The opening checks of all the proofs are folded with a random linear
+    /// combination into a single pairing check.
+    pub fn batch_verify<T>(
+        verify_keys: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+        extra_transcript_init_msgs: &[Option<Vec<u8>>],
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let pcs_infos = Self::prepare_batch_pcs_infos::<T>(
+            verify_keys,
+            public_inputs,
+            proofs,
+            extra_transcript_init_msgs,
+        )?;
+        if !Verifier::<KzgPcs<E>>::batch_verify_opening_proofs::<T>(
+            &verify_keys[0].open_key, // all open_key are the same
+            &pcs_infos,
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
+    }
+
+    /// Batch verify multiple SNARK proofs (w.r.t. different verifying keys) as
+    /// `batch_verify` does, accumulating the opening proofs in parallel by
+    /// chunks of `chunk_size` proofs before a single final pairing check.
+    ///
+    /// The random challenge combining the proofs is derived from the whole
+    /// batch, so that the accumulated check, and hence the verdict, is the
+    /// same as the one of `batch_verify` for any `chunk_size`.
+    /// Return error if `chunk_size` is zero.
+    pub fn batch_verify_parallel<T>(
+        verify_keys: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+        extra_transcript_init_msgs: &[Option<Vec<u8>>],
+        chunk_size: usize,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if chunk_size == 0 {
+            return Err(ParameterError("the chunk size cannot be zero".to_string()).into());
+        }
+        let pcs_infos = Self::prepare_batch_pcs_infos::<T>(
+            verify_keys,
+            public_inputs,
+            proofs,
+            extra_transcript_init_msgs,
+        )?;
+        if !Verifier::<KzgPcs<E>>::batch_verify_opening_proofs_parallel::<T>(
+            &verify_keys[0].open_key, // all open_key are the same
+            &pcs_infos,
+            chunk_size,
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
+    }
+
+    /// Check the lengths of the inputs of a batch verification and compute
+    /// the opening proof info of each instance in parallel.
+    fn prepare_batch_pcs_infos<T>(
+        verify_keys: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+        extra_transcript_init_msgs: &[Option<Vec<u8>>],
+    ) -> Result<Vec<PcsInfo<E>>, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if public_inputs.len() != proofs.len()
+            || verify_keys.len() != proofs.len()
+            || extra_transcript_init_msgs.len() != proofs.len()
+        {
+            return Err(ParameterError(format!(
+                "verify_keys.len: {}, public_inputs.len: {}, proofs.len: {}, \
+                 extra_transcript_msg.len: {}",
+                verify_keys.len(),
+                public_inputs.len(),
+                proofs.len(),
+                extra_transcript_init_msgs.len()
+            ))
+            .into());
+        }
+        if verify_keys.is_empty() {
+            return Err(
+                ParameterError("the number of instances cannot be zero".to_string()).into(),
+            );
+        }
+
+        verify_keys
+            .par_iter()
+            .zip(proofs.par_iter())
+            .zip(public_inputs.par_iter())
+            .zip(extra_transcript_init_msgs.par_iter())
+            .map(|(((&vk, &proof), &pub_input), extra_msg)| {
+                let verifier = Verifier::<KzgPcs<E>>::new(vk.domain_size)?;
+                verifier.prepare_pcs_info::<T>(&[vk], &[pub_input], &proof.into(), extra_msg)
+            })
+            .collect()
+    }
+
+    /// Verify a SNARK proof w.r.t. the verifying key of `precompute`, reusing
+    /// its evaluation domain and public input Lagrange bases instead of
+    /// recomputing them as `verify` does. The verdict is the same as the one
+    /// of `verify`.
+    pub fn verify_with_precompute<T>(
+        precompute: &VerifierPrecompute<E>,
+        public_input: &[E::Fr],
+        proof: &Proof<E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let pcs_info = precompute.verifier.prepare_pcs_info::<T>(
+            &[&precompute.vk],
             &[public_input],
-            &[proof],
-            &[extra_transcript_init_msg],
+            &proof.into(),
+            &extra_transcript_init_msg,
+        )?;
+        if !Verifier::<KzgPcs<E>>::batch_verify_opening_proofs::<T>(
+            &precompute.vk.open_key,
+            &[pcs_info],
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
+    }
+
+    /// Batch verify multiple SNARK proofs read from a columnar buffer, without
+    /// building slices of references as `batch_verify` does. The `i`-th proof
+    /// is verified w.r.t. `verify_keys[i]`, `extra_transcript_init_msgs[i]`
+    /// and the public inputs made of the next `verify_keys[i].num_inputs`
+    /// elements of `public_inputs`, which concatenates the public inputs of
+    /// all the instances.
+    pub fn batch_verify_columnar<T>(
+        verify_keys: &[VerifyingKey<E>],
+        public_inputs: &[E::Fr],
+        proofs: &[Proof<E>],
+        extra_transcript_init_msgs: &[Option<Vec<u8>>],
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if verify_keys.len() != proofs.len() || extra_transcript_init_msgs.len() != proofs.len() {
+            return Err(ParameterError(format!(
+                "verify_keys.len: {}, proofs.len: {}, extra_transcript_msg.len: {}",
+                verify_keys.len(),
+                proofs.len(),
+                extra_transcript_init_msgs.len()
+            ))
+            .into());
+        }
+        if verify_keys.is_empty() {
+            return Err(
+                ParameterError("the number of instances cannot be zero".to_string()).into(),
+            );
+        }
+        // offsets of the public inputs of each instance in the buffer
+        let mut offsets = Vec::with_capacity(verify_keys.len() + 1);
+        offsets.push(0);
+        for vk in verify_keys.iter() {
+            offsets.push(offsets[offsets.len() - 1] + vk.num_inputs);
+        }
+        if offsets[verify_keys.len()] != public_inputs.len() {
+            return Err(ParameterError(format!(
+                "public_inputs.len: {}, total number of inputs of the verify keys: {}",
+                public_inputs.len(),
+                offsets[verify_keys.len()]
+            ))
+            .into());
+        }
+
+        let pcs_infos = (0..proofs.len())
+            .into_par_iter()
+            .map(|i| {
+                let vk = &verify_keys[i];
+                let verifier = Verifier::<KzgPcs<E>>::new(vk.domain_size)?;
+                verifier.prepare_pcs_info::<T>(
+                    &[vk],
+                    &[&public_inputs[offsets[i]..offsets[i + 1]]],
+                    &(&proofs[i]).into(),
+                    &extra_transcript_init_msgs[i],
+                )
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+
+        if !Verifier::<KzgPcs<E>>::batch_verify_opening_proofs::<T>(
+            &verify_keys[0].open_key, // all open_key are the same
+            &pcs_infos,
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
+    }
+
+    /// Compute a Plonk proof and return it along with the final transcript
+    /// state, so that the caller can keep absorbing messages and squeezing
+    /// further challenges bound to the proof.
+    ///
+    /// The returned transcript is identical to the one obtained by the
+    /// verifier from [`Self::verify_with_transcript`].
+    pub fn prove_with_transcript<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(Proof<E>, T), PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let (proof, _, _, transcript) = Self::prove_internal::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            None,
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+        Ok((proof, transcript))
+    }
+
+    /// Compute a Plonk proof and return it along with the durations of the
+    /// FFTs, MSMs and polynomial evaluations of this call, which are not
+    /// affected by other proving calls running concurrently.
+    #[cfg(feature = "std")]
+    pub fn prove_with_profile<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(Proof<E>, ProveProfile), PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let profile = ProfileRecorder::enabled();
+        let (proof, ..) = Self::prove_internal::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            None,
+            extra_transcript_init_msg,
+            profile.clone(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+        Ok((proof, profile.profile()))
+    }
+
+    /// Compute a Plonk proof with the multi-scalar multiplications of the
+    /// polynomial commitments computed by `msm`, e.g. on a GPU. The proof is
+    /// identical to the one from [`Snark::prove`] given the same `prng` state,
+    /// provided that `msm` is correct.
+    pub fn prove_with_msm_backend<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+        msm: &dyn MsmBackend<E::G1Affine>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let (proof, ..) = Self::prove_internal::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            None,
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            msm,
+            None,
+            None,
+            0,
+        )?;
+        Ok(proof)
+    }
+
+    /// Compute a Plonk proof with the explicit scalars `blinding` masking the
+    /// polynomials, instead of sampling them from an RNG, so that identical
+    /// inputs yield identical proofs, e.g. for test vectors and audits.
+    ///
+    /// **Warning**: the proof is zero-knowledge only if the blinding scalars
+    /// are uniformly random and never reused; reproducible blinding scalars
+    /// reveal information about the witness.
+    ///
+    /// Return error if the number of blinding scalars differs from the one
+    /// required by the Plonk type of `circuit`: 2 per wire type, 3 for the
+    /// permutation product and 9 for the Plookup polynomials of UltraPlonk
+    /// circuits, regardless of the domain size.
+    #[cfg(any(test, feature = "test-rng"))]
+    pub fn prove_deterministic<C, T>(
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        blinding: &[E::Fr],
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        T: PlonkTranscript<F>,
+    {
+        let expected = num_blinding_scalars(circuit.num_wire_types(), circuit.support_lookup());
+        if blinding.len() != expected {
+            return Err(ParameterError(format!(
+                "the number of blinding scalars {} != the expected number {}",
+                blinding.len(),
+                expected
+            ))
+            .into());
+        }
+        let (proof, ..) = Self::prove_internal::<_, _, T>(
+            &mut ExplicitBlinding(blinding.iter()),
+            circuit,
+            prove_key,
+            None,
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+        Ok(proof)
+    }
+
+    /// Compute a Plonk proof and return it along with the committed wire
+    /// witness polynomials, so that the caller can open each of them at up to
+    /// `num_extra_openings` more points later on with
+    /// [`WitnessCommitments::open_witness`].
+    ///
+    /// The wire polynomials are masked with polynomials of degree
+    /// `num_extra_openings + 1`, so that they stay hidden after these
+    /// openings, see [`WitnessCommitments`]. This raises the degree of the
+    /// wire and quotient polynomials: unless `num_extra_openings` is zero,
+    /// `prove_key` has to come from [`Self::preprocess_with_witness_openings`]
+    /// with at least as many openings. The proof is checked with
+    /// [`Snark::verify`] as usual.
+    /// Return [`PlonkError::SrsTooSmall`] if the committing key of
+    /// `prove_key` doesn't support the masked polynomials.
+    pub fn prove_with_witness_commitments<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        num_extra_openings: usize,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(Proof<E>, WitnessCommitments<'a, E>), PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let required = witness_openings_srs_size(
+            prove_key.domain_size(),
+            circuit.num_wire_types(),
+            prove_key.vk.num_quot_chunks,
+            num_extra_openings,
+        );
+        let provided = prove_key.commit_key.powers_of_g.len() - 1;
+        if provided < required {
+            return Err(PlonkError::SrsTooSmall { required, provided });
+        }
+        let (proof, oracles, challenges, _) = Self::prove_internal::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            None,
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            num_extra_openings,
+        )?;
+        let witness_comms = WitnessCommitments {
+            wire_polys: oracles.wire_polys,
+            wire_comms: proof.wires_poly_comms.clone(),
+            commit_key: prove_key.commit_key.clone(),
+            domain_size: prove_key.domain_size(),
+            zeta: challenges.zeta,
+            max_extra_openings: num_extra_openings,
+            extra_points: vec![vec![]; circuit.num_wire_types()],
+        };
+        Ok((proof, witness_comms))
+    }
+
+    /// Compute a Plonk proof from the wire witness polynomials `wire_polys`
+    /// precomputed by the caller, instead of deriving them from the witness of
+    /// `circuit`, which is still needed for the later rounds. The proof is
+    /// identical to the one from [`Snark::prove`] given the same `prng` state,
+    /// provided that `wire_polys` are those of `circuit` (before masking).
+    ///
+    /// Before committing, return error if the number of polynomials is not the
+    /// number of wire types of `circuit`, if one of them has a degree not
+    /// smaller than the domain size, or if one of them does not evaluate to
+    /// the wire values of `circuit` over the evaluation domain.
+    pub fn prove_with_witness_polys<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        wire_polys: Vec<DensePolynomial<E::Fr>>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        if wire_polys.len() != circuit.num_wire_types() {
+            return Err(ParameterError(format!(
+                "the number of wire polynomials {} != the number of wire types {}",
+                wire_polys.len(),
+                circuit.num_wire_types()
+            ))
+            .into());
+        }
+        let n = circuit.eval_domain_size()?;
+        if let Some(poly) = wire_polys.iter().find(|poly| poly.degree() >= n) {
+            return Err(ParameterError(format!(
+                "wire polynomial degree {} >= domain size {}",
+                poly.degree(),
+                n
+            ))
+            .into());
+        }
+        let domain =
+            Radix2EvaluationDomain::<E::Fr>::new(n).ok_or(PlonkError::DomainCreationError)?;
+        // the output wire values hold the public inputs at the public input
+        // gates, hence they are checked as well
+        let wire_evals = circuit.compute_wire_evaluations()?;
+        if let Some(i) = wire_polys
+            .par_iter()
+            .zip(wire_evals.par_iter())
+            .position_any(|(poly, evals)| domain.fft(&poly.coeffs) != *evals)
+        {
+            return Err(ParameterError(format!(
+                "the wire polynomial {} is inconsistent with the witness of the circuit",
+                i
+            ))
+            .into());
+        }
+
+        let (proof, ..) = Self::prove_internal::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            Some(WirePolys::Unmasked(wire_polys)),
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+        Ok(proof)
+    }
+
+    /// Commit to the wire witness polynomials of `circuit` before any
+    /// challenge is drawn, and return the state to finish the proof with
+    /// [`Self::finish_prove`], holding the commitments.
+    ///
+    /// The commitments can be published ahead of the proof, e.g. in
+    /// commit-reveal protocols: the finished proof contains them, and its
+    /// transcript absorbs them before deriving any challenge, hence the proof
+    /// is bound to them.
+    /// Return error if the domain sizes of `circuit` and `prove_key` differ.
+    pub fn commit_witness<'s, C, R>(
+        prng: &mut R,
+        circuit: &'s C,
+        prove_key: &'s ProvingKey<'a, E>,
+    ) -> Result<ProverState<'s, 'a, E, C>, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+    {
+        let n = circuit.eval_domain_size()?;
+        if prove_key.domain_size() != n {
+            return Err(ParameterError(format!(
+                "proving key domain size {} != expected domain size {}",
+                prove_key.domain_size(),
+                n
+            ))
+            .into());
+        }
+        // the same prover configuration as the round 1 of `prove_internal`
+        let prover = Prover::<KzgPcs<E>>::new(n, circuit.num_wire_types())?
+            .with_hiding(<R as Blinder<E::Fr>>::is_hiding(prng));
+        let ((wire_comms, wire_polys), _) =
+            prover.run_1st_round(prng, &prove_key.commit_key, circuit)?;
+        Ok(ProverState {
+            circuit,
+            prove_key,
+            wire_polys,
+            wire_comms,
+        })
+    }
+
+    /// Finish the proof committed by [`Self::commit_witness`] from its
+    /// `state`, sampling the remaining blinding scalars from `prng`. The
+    /// wire commitments of the proof are those of `state`, and it is checked
+    /// with [`Snark::verify`] as usual.
+    pub fn finish_prove<C, R, T>(
+        prng: &mut R,
+        state: ProverState<'_, 'a, E, C>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let ProverState {
+            circuit,
+            prove_key,
+            wire_polys,
+            wire_comms,
+        } = state;
+        let (proof, ..) = Self::prove_internal::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            Some(WirePolys::Committed((wire_comms, wire_polys))),
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+        Ok(proof)
+    }
+
+    /// Compute a Plonk proof with all internally-parallel sections executed
+    /// serially on a dedicated single-threaded pool. Meant for debugging:
+    /// the output is identical to [`Snark::prove`] given the same `prng`
+    /// state.
+    pub fn prove_single_threaded<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr> + Sync,
+        R: CryptoRng + RngCore + Send,
+        T: PlonkTranscript<F>,
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .map_err(|e| {
+                PlonkError::InvalidParameters(format!("failed to build thread pool: {}", e))
+            })?;
+        Self::prove_in_pool::<_, _, T>(&pool, prng, circuit, prove_key, extra_transcript_init_msg)
+    }
+
+    /// Compute a Plonk proof with all the parallel work, including the FFTs
+    /// and MSMs, scheduled on `pool` instead of the global rayon pool. The
+    /// output is identical to [`Snark::prove`] given the same `prng` state.
+    /// The phases of the proof are still recorded in the trace of the calling
+    /// thread.
+    pub fn prove_in_pool<C, R, T>(
+        pool: &rayon::ThreadPool,
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr> + Sync,
+        R: CryptoRng + RngCore + Send,
+        T: PlonkTranscript<F>,
+    {
+        install_in_pool(pool, || {
+            <Self as Snark<E>>::prove::<_, _, T>(
+                prng,
+                circuit,
+                prove_key,
+                extra_transcript_init_msg,
+            )
+        })
+    }
+
+    /// Compute a Plonk proof while bounding the memory of the quotient
+    /// polynomial computation, which dominates the memory of the prover.
+    ///
+    /// Instead of evaluating all the polynomials over the whole quotient
+    /// domain at once, the evaluations are computed for one coset of the
+    /// evaluation domain at a time, and dropped as soon as the quotient
+    /// polynomial is evaluated on this coset. `memory_budget` is a hint, in
+    /// bytes, of the memory that the evaluations may take: as many cosets as
+    /// fit in it are processed in parallel, and at least one. The proof is
+    /// identical to the one from [`Snark::prove`] given the same `prng`
+    /// state.
+    pub fn prove_low_memory<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+        memory_budget: usize,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let (proof, ..) = Self::prove_internal::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            None,
+            extra_transcript_init_msg,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            Some(memory_budget),
+            None,
+            0,
+        )?;
+        Ok(proof)
+    }
+
+    /// Verify a proof of a rolling accumulator transition, i.e. that the
+    /// proof binds to the update `prev_acc -> next_acc` rather than to the
+    /// raw inputs absorbed in between. The circuit is expected to expose
+    /// exactly `prev_acc` and `next_acc` as its public inputs, in this order
+    /// (see [`crate::circuit::customized::accumulator`]).
+    pub fn verify_accumulator_transition<T>(
+        verify_key: &VerifyingKey<E>,
+        prev_acc: E::Fr,
+        next_acc: E::Fr,
+        proof: &Proof<E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if verify_key.num_inputs != 2 {
+            return Err(ParameterError(format!(
+                "an accumulator transition has 2 public inputs, the verification key has {}",
+                verify_key.num_inputs
+            ))
+            .into());
+        }
+        <Self as Snark<E>>::verify::<T>(
+            verify_key,
+            &[prev_acc, next_acc],
+            proof,
+            extra_transcript_init_msg,
+        )
+    }
+
+    /// Verify a Plonk proof with a bounded amount of work. Before any
+    /// transcript or pairing computation, the lengths of the public input and
+    /// of the public input expected by `verify_key` are checked against
+    /// `max_public_inputs`, and `SnarkError::TooManyPublicInputs` is returned
+    /// if either exceeds it.
+    pub fn verify_bounded<T>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        proof: &Proof<E>,
+        max_public_inputs: usize,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let num_inputs = public_input.len().max(verify_key.num_inputs);
+        if num_inputs > max_public_inputs {
+            return Err(SnarkError::TooManyPublicInputs(num_inputs, max_public_inputs).into());
+        }
+        <Self as Snark<E>>::verify::<T>(verify_key, public_input, proof, extra_transcript_init_msg)
+    }
+
+    /// Compute a Plonk proof whose Fiat-Shamir challenges are bound to the
+    /// public randomness `beacon`, which the verifier has to provide to
+    /// [`Self::verify_with_beacon`].
+    ///
+    /// The beacon is absorbed first, before the verification key and the
+    /// public input, within the extra initial message of the transcript:
+    /// namely the label `randomness beacon`, the length of `beacon` as 8
+    /// little-endian bytes, `beacon` and then `extra_transcript_init_msg`
+    /// if any.
+    pub fn prove_with_beacon<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<'a, E>,
+        beacon: &[u8],
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        <Self as Snark<E>>::prove::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            Some(beacon_transcript_init_msg(
+                beacon,
+                extra_transcript_init_msg,
+            )),
+        )
+    }
+
+    /// Verify a Plonk proof generated by [`Self::prove_with_beacon`] with the
+    /// same `beacon`. A proof for another beacon, or for none, is rejected.
+    pub fn verify_with_beacon<T>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        proof: &Proof<E>,
+        beacon: &[u8],
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        <Self as Snark<E>>::verify::<T>(
+            verify_key,
+            public_input,
+            proof,
+            Some(beacon_transcript_init_msg(
+                beacon,
+                extra_transcript_init_msg,
+            )),
+        )
+    }
+
+    /// Verify a Plonk proof and, on success, return a
+    /// [`VerificationReceipt`] of the verification at the caller-supplied
+    /// `timestamp`.
+    pub fn verify_with_receipt<T>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        proof: &Proof<E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+        timestamp: u64,
+    ) -> Result<VerificationReceipt, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        <Self as Snark<E>>::verify::<T>(
+            verify_key,
+            public_input,
+            proof,
+            extra_transcript_init_msg,
+        )?;
+        VerificationReceipt::new(verify_key, public_input, timestamp)
+    }
+
+    /// Verify a Plonk proof and, on success, return the final transcript
+    /// state. It matches the transcript returned by
+    /// [`Self::prove_with_transcript`] for the same proof.
+    pub fn verify_with_transcript<T>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        proof: &Proof<E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<T, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let verifier = Verifier::<KzgPcs<E>>::new(verify_key.domain_size)?;
+        let (pcs_info, transcript) = verifier.prepare_pcs_info_and_transcript::<T>(
+            &[verify_key],
+            &[public_input],
+            &proof.into(),
+            &extra_transcript_init_msg,
+        )?;
+        if !Verifier::<KzgPcs<E>>::batch_verify_opening_proofs::<T>(
+            &verify_key.open_key,
+            &[pcs_info],
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(transcript)
+    }
+
+    /// Verify a [`CompressedProof`], after rebuilding the full proof with
+    /// [`CompressedProof::decompress`].
+    pub fn verify_compressed<T>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        compressed_proof: &CompressedProof<E>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let proof = compressed_proof.decompress::<T>(
+            verify_key,
+            public_input,
+            extra_transcript_init_msg.clone(),
+        )?;
+        <Self as Snark<E>>::verify::<T>(verify_key, public_input, &proof, extra_transcript_init_msg)
+    }
+
+    /// Verify a Plonk proof against the positions of `public_input` selected
+    /// by `mask` only, leaving the other positions to the prover.
+    ///
+    /// Plonk proofs do not commit to their public input, so the values of the
+    /// unmasked positions cannot be recovered from the proof: they are given
+    /// by `unmasked_input`, in order, as sent by the prover along with the
+    /// proof. `public_input` is authoritative for the masked positions and
+    /// `unmasked_input` for the unmasked ones; a verifier which does not know
+    /// an unmasked position leaves it to zero in `public_input`, and any
+    /// other value there must agree with `unmasked_input`. The full public
+    /// input is then absorbed into the transcript and evaluated in the public
+    /// input polynomial as in [`Snark::verify`].
+    ///
+    /// On success, the proof is thus sound for the full public input: the
+    /// masked positions hold the values expected by the verifier, and the
+    /// unmasked positions the values claimed by the prover, which cannot be
+    /// changed without invalidating the proof, but of which the verifier
+    /// learns nothing else. In particular, a proof only binds the masked
+    /// positions if the circuit constrains the unmasked ones by itself.
+    /// Return error if the lengths of `public_input` and `mask` differ, if
+    /// the length of `unmasked_input` is not the number of unmasked
+    /// positions, or if a non-zero entry of `public_input` at an unmasked
+    /// position differs from the corresponding value of `unmasked_input`.
+    pub fn verify_with_input_mask<T>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        mask: &[bool],
+        proof: &Proof<E>,
+        unmasked_input: &[E::Fr],
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let num_unmasked = mask.iter().filter(|&&masked| !masked).count();
+        if public_input.len() != mask.len() || unmasked_input.len() != num_unmasked {
+            return Err(ParameterError(format!(
+                "public_input.len: {}, mask.len: {}, unmasked_input.len: {}, unmasked positions: \
+                 {}",
+                public_input.len(),
+                mask.len(),
+                unmasked_input.len(),
+                num_unmasked
+            ))
+            .into());
+        }
+        let mut unmasked_iter = unmasked_input.iter();
+        let mut full_input = Vec::with_capacity(public_input.len());
+        for (i, (&input, &masked)) in public_input.iter().zip(mask.iter()).enumerate() {
+            if masked {
+                full_input.push(input);
+            } else {
+                // there are as many unmasked values as unmasked positions
+                let claimed = *unmasked_iter.next().unwrap();
+                if !input.is_zero() && input != claimed {
+                    return Err(ParameterError(format!(
+                        "public_input[{}] is unmasked and differs from the value claimed by the \
+                         prover",
+                        i
+                    ))
+                    .into());
+                }
+                full_input.push(claimed);
+            }
+        }
+        <Self as Snark<E>>::verify::<T>(verify_key, &full_input, proof, extra_transcript_init_msg)
+    }
+}
+
+// The extra initial transcript message binding `beacon`, followed by
+// `extra_transcript_init_msg`. The length prefix makes the encoding
+// unambiguous.
+fn beacon_transcript_init_msg(
+    beacon: &[u8],
+    extra_transcript_init_msg: Option<Vec<u8>>,
+) -> Vec<u8> {
+    let mut msg = BEACON_TRANSCRIPT_MSG_LABEL.to_vec();
+    msg.extend_from_slice(&(beacon.len() as u64).to_le_bytes());
+    msg.extend_from_slice(beacon);
+    if let Some(extra_msg) = extra_transcript_init_msg {
+        msg.extend_from_slice(&extra_msg);
+    }
+    msg
+}
+
+// The id of the curve of `E` in SRS files: the hash of the generators of G1
+// and G2.
+fn srs_curve_id<E: PairingEngine>() -> Result<[u8; 32], PlonkError> {
+    let mut bytes = Vec::new();
+    E::G1Affine::prime_subgroup_generator().serialize(&mut bytes)?;
+    E::G2Affine::prime_subgroup_generator().serialize(&mut bytes)?;
+    Ok(Keccak256::digest(&bytes).into())
+}
+
+// Write the header of an SRS file of degree `max_degree`.
+fn write_srs_header<E: PairingEngine, W: Write>(
+    writer: &mut W,
+    max_degree: usize,
+) -> Result<(), PlonkError> {
+    let mut header = SRS_FILE_MAGIC.to_vec();
+    header.push(SRS_FILE_VERSION);
+    header.extend_from_slice(&srs_curve_id::<E>()?);
+    header.push(SRS_FILE_LITTLE_ENDIAN);
+    header.extend_from_slice(&(max_degree as u64).to_le_bytes());
+    writer.write_all(&header)?;
+    Ok(())
+}
+
+// Read the header of an SRS file, and return the degree of the SRS.
+fn read_srs_header<E: PairingEngine, Rd: Read>(reader: &mut Rd) -> Result<usize, PlonkError> {
+    let magic_len = SRS_FILE_MAGIC.len();
+    let mut header = vec![0u8; magic_len + 1 + 32 + 1 + 8];
+    reader.read_exact(&mut header)?;
+    let (magic, rest) = header.split_at(magic_len);
+    let (version, rest) = (rest[0], &rest[1..]);
+    let (curve_id, rest) = rest.split_at(32);
+    let (endianness, max_degree) = (rest[0], &rest[1..]);
+    if magic != SRS_FILE_MAGIC {
+        return Err(ParameterError("not an SRS file".to_string()).into());
+    }
+    if version != SRS_FILE_VERSION {
+        return Err(ParameterError(format!(
+            "unsupported SRS file version {}, expected {}",
+            version, SRS_FILE_VERSION
+        ))
+        .into());
+    }
+    if curve_id != &srs_curve_id::<E>()?[..] {
+        return Err(ParameterError("the SRS file is for another curve".to_string()).into());
+    }
+    if endianness != SRS_FILE_LITTLE_ENDIAN {
+        return Err(
+            ParameterError(format!("unsupported SRS file endianness {}", endianness)).into(),
+        );
+    }
+    let mut degree_bytes = [0u8; 8];
+    degree_bytes.copy_from_slice(max_degree);
+    Ok(u64::from_le_bytes(degree_bytes) as usize)
+}
+
+impl<'a, E, F, P> Snark<E> for PlonkKzgSnark<'a, E>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + Clone,
+{
+    type Proof = Proof<E>;
+
+    type ProvingKey = ProvingKey<'a, E>;
+
+    type VerifyingKey = VerifyingKey<E>;
+
+    /// Compute a Plonk proof.
+    /// Refer to Sec 8.4 of <https://eprint.iacr.org/2019/953.pdf>
+    ///
+    /// `circuit` and `prove_key` has to be consistent (with the same evaluation
+    /// domain etc.), otherwise return error.
+    fn prove<C, R, T>(
+        prng: &mut R,
+        circuit: &C,
+        prove_key: &Self::ProvingKey,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Self::Proof, PlonkError>
+    where
+        C: Arithmetization<E::Fr>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let (proof, _) = Self::prove_with_transcript::<_, _, T>(
+            prng,
+            circuit,
+            prove_key,
+            extra_transcript_init_msg,
+        )?;
+        Ok(proof)
+    }
+
+    fn verify<T>(
+        verify_key: &Self::VerifyingKey,
+        public_input: &[E::Fr],
+        proof: &Self::Proof,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        Self::batch_verify::<T>(
+            &[verify_key],
+            &[public_input],
+            &[proof],
+            &[extra_transcript_init_msg],
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    #[cfg(feature = "std")]
+    use crate::profiling::ProveProfile;
+    use crate::{
+        circuit::{
+            customized::{accumulator::rolling_accumulate, ecc::SWToTEConParam},
+            gates::CustomGateSpec,
+            Arithmetization, Circuit, PlonkCircuit, Variable,
+        },
+        constants::{domain_size_ratio, GATE_WIDTH, N_TURBO_PLONK_SELECTORS, SRS_FILE_VERSION},
+        errors::PlonkError,
+        profiling::ProfileRecorder,
+        proof_system::{
+            msm::{ArkworksMsm, MsmBackend},
+            pcs::{IpaPcs, KzgPcs, PcsCommitter, PolynomialCommitmentScheme},
+            structs::{
+                eval_merged_lookup_witness, eval_merged_table, witness_openings_srs_size,
+                Challenges, CompressedProof, Oracles, PcsProof, PlookupVerifyingKey, Proof,
+                ProvingKey, UniversalSrs, VerificationReceipt, VerifierPrecompute, VerifyingKey,
+            },
+            verifier::Verifier,
+            PlonkKzgSnark, PlonkPcsSnark, Snark,
+        },
+        transcript::{
+            rescue::RescueTranscript, solidity::SolidityTranscript, standard::StandardTranscript,
+            PlonkTranscript,
+        },
+        MergeableCircuitType, PlonkType,
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bls12_381::{Bls12_381, Fq as Fq381};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_bw6_761::{Fq as Fq761, BW6_761};
+    use ark_ec::{
+        short_weierstrass_jacobian::GroupAffine, AffineCurve, PairingEngine, ProjectiveCurve,
+        SWModelParameters,
+    };
+    use ark_ff::{One, PrimeField, UniformRand, Zero};
+    use ark_poly::{
+        univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
+        Radix2EvaluationDomain, UVPolynomial,
+    };
+    use ark_poly_commit::kzg10::{Commitment, KZG10};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{
+        convert::TryInto,
+        format,
+        rand::{CryptoRng, RngCore, SeedableRng},
+        string::ToString,
+        test_rng, vec,
+        vec::Vec,
+    };
+    #[cfg(feature = "std")]
+    use core::time::Duration;
+    use core::{
+        ops::{Mul, Neg},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use jf_rescue::RescueParameter;
+    use rand_chacha::ChaChaRng;
+    #[cfg(feature = "std")]
+    use std::thread;
+
+    // Different `m`s lead to different circuits.
+    // Different `a0`s lead to different witness values.
+    // For UltraPlonk circuits, `a0` should be less than or equal to `m+1`
+    pub(crate) fn gen_circuit_for_test<F: PrimeField>(
+        m: usize,
+        a0: usize,
+        plonk_type: PlonkType,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let range_bit_len = 5;
+        let mut cs: PlonkCircuit<F> = match plonk_type {
+            PlonkType::TurboPlonk => PlonkCircuit::new_turbo_plonk(),
+            PlonkType::UltraPlonk => PlonkCircuit::new_ultra_plonk(range_bit_len),
+        };
+        // Create variables
+        let mut a = vec![];
+        for i in a0..(a0 + 4 * m) {
+            a.push(cs.create_variable(F::from(i as u64))?);
+        }
+        let b = vec![
+            cs.create_public_variable(F::from(m as u64 * 2))?,
+            cs.create_public_variable(F::from(a0 as u64 * 2 + m as u64 * 4 - 1))?,
+        ];
+        let c = cs.create_public_variable(
+            (cs.witness(b[1])? + cs.witness(a[0])?) * (cs.witness(b[1])? - cs.witness(a[0])?),
+        )?;
+
+        // Create gates:
+        // 1. a0 + ... + a_{4*m-1} = b0 * b1
+        // 2. (b1 + a0) * (b1 - a0) = c
+        // 3. b0 = 2 * m
+        let mut acc = cs.zero();
+        a.iter().for_each(|&elem| acc = cs.add(acc, elem).unwrap());
+        let b_mul = cs.mul(b[0], b[1])?;
+        cs.equal_gate(acc, b_mul)?;
+        let b1_plus_a0 = cs.add(b[1], a[0])?;
+        let b1_minus_a0 = cs.sub(b[1], a[0])?;
+        cs.mul_gate(b1_plus_a0, b1_minus_a0, c)?;
+        cs.constant_gate(b[0], F::from(m as u64 * 2))?;
+
+        if plonk_type == PlonkType::UltraPlonk {
+            // Create range gates
+            // 1. range_table = {0, 1, ..., 31}
+            // 2. a_i \in range_table for i = 0..m-1
+            // 3. b0 \in range_table
+            for &var in a.iter().take(m) {
+                cs.add_range_check_variable(var)?;
+            }
+            cs.add_range_check_variable(b[0])?;
+
+            // Create variable table lookup gates
+            // 1. table = [(a0, a2), (a1, a3), (b0, a0)]
+            let table_vars = [(a[0], a[2]), (a[1], a[3]), (b[0], a[0])];
+            // 2. lookup_witness = [(1, a0+1, a0+3), (2, 2m, a0)]
+            let key0 = cs.one();
+            let key1 = cs.create_variable(F::from(2u8))?;
+            let two_m = cs.create_public_variable(F::from(m as u64 * 2))?;
+            let a1 = cs.add_constant(a[0], &F::one())?;
+            let a3 = cs.add_constant(a[0], &F::from(3u8))?;
+            let lookup_vars = [(key0, a1, a3), (key1, two_m, a[0])];
+            cs.create_table_and_lookup_variables(&lookup_vars, &table_vars)?;
+        }
+
+        // Finalize the circuit.
+        cs.finalize_for_arithmetization()?;
+
+        Ok(cs)
+    }
+
+    #[test]
+    fn test_universal_setup_streaming() -> Result<(), PlonkError> {
+        test_universal_setup_streaming_helper::<Bn254, Fq254, _>()?;
+        test_universal_setup_streaming_helper::<Bls12_377, Fq377, _>()?;
+        test_universal_setup_streaming_helper::<Bls12_381, Fq381, _>()?;
+        test_universal_setup_streaming_helper::<BW6_761, Fq761, _>()
+    }
+
+    fn test_universal_setup_streaming_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let circuit = gen_circuit_for_test(3, 4, PlonkType::TurboPlonk)?;
+        let degree = circuit.srs_size()?;
+        let max_degree = degree + 5;
+        let mut bytes = Vec::new();
+        PlonkKzgSnark::<E>::universal_setup_streaming(max_degree, &mut test_rng(), &mut bytes)?;
+
+        // the streamed SRS is byte-identical to the in-memory one
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, &mut test_rng())?;
+        let loaded_srs = PlonkKzgSnark::<E>::load_srs(&bytes[..], max_degree)?;
+        assert_eq!(loaded_srs, srs);
+        let mut srs_bytes = Vec::new();
+        let mut loaded_srs_bytes = Vec::new();
+        srs.serialize(&mut srs_bytes)?;
+        loaded_srs.serialize(&mut loaded_srs_bytes)?;
+        assert_eq!(loaded_srs_bytes, srs_bytes);
+
+        // a prefix is the SRS of a lower degree, and only needs a prefix of the file
+        let srs = PlonkKzgSnark::<E>::universal_setup(degree, &mut test_rng())?;
+        let prefix_len = bytes.len() - 5 * 2 * E::G1Affine::default().uncompressed_size();
+        let loaded_srs = PlonkKzgSnark::<E>::load_srs(&bytes[..prefix_len], degree)?;
+        assert_eq!(loaded_srs, srs);
+        assert!(PlonkKzgSnark::<E>::load_srs(&bytes[..prefix_len - 1], degree).is_err());
+        assert!(PlonkKzgSnark::<E>::load_srs(&bytes[..bytes.len() - 1], max_degree).is_err());
+        // and works for proving and verifying
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&loaded_srs, &circuit)?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            None,
+        )?;
+        assert!(PlonkKzgSnark::<E>::verify::<StandardTranscript>(
+            &vk,
+            &circuit.public_input()?,
+            &proof,
+            None
+        )
+        .is_ok());
+
+        // bad path: wrong degrees
+        assert!(PlonkKzgSnark::<E>::load_srs(&bytes[..], max_degree + 1).is_err());
+        assert!(PlonkKzgSnark::<E>::load_srs(&bytes[..], 0).is_err());
+        assert!(
+            PlonkKzgSnark::<E>::universal_setup_streaming(0, &mut test_rng(), Vec::new()).is_err()
+        );
+
+        // bad path: wrong magic bytes, version, curve or endianness
+        for &(index, value) in [
+            (0, b'X'),
+            (5, SRS_FILE_VERSION + 1),
+            (6, bytes[6] ^ 1),
+            (38, 1),
+        ]
+        .iter()
+        {
+            let mut bad_bytes = bytes.clone();
+            bad_bytes[index] = value;
+            assert!(PlonkKzgSnark::<E>::load_srs(&bad_bytes[..], max_degree).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_srs_wrong_curve() -> Result<(), PlonkError> {
+        let mut bytes = Vec::new();
+        PlonkKzgSnark::<Bn254>::universal_setup_streaming(8, &mut test_rng(), &mut bytes)?;
+        assert!(PlonkKzgSnark::<Bn254>::load_srs(&bytes[..], 8).is_ok());
+        let err = PlonkKzgSnark::<Bls12_381>::load_srs(&bytes[..], 8).unwrap_err();
+        assert!(err.to_string().contains("another curve"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_preprocessing() -> Result<(), PlonkError> {
+        test_preprocessing_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
+        test_preprocessing_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
+        test_preprocessing_helper::<Bls12_377, Fq377, _>(PlonkType::TurboPlonk)?;
+        test_preprocessing_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)?;
+        test_preprocessing_helper::<Bls12_381, Fq381, _>(PlonkType::TurboPlonk)?;
+        test_preprocessing_helper::<Bls12_381, Fq381, _>(PlonkType::UltraPlonk)?;
+        test_preprocessing_helper::<BW6_761, Fq761, _>(PlonkType::TurboPlonk)?;
+        test_preprocessing_helper::<BW6_761, Fq761, _>(PlonkType::UltraPlonk)
+    }
+    fn test_preprocessing_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut ark_std::test_rng();
+        let circuit = gen_circuit_for_test(5, 6, plonk_type)?;
+        let domain_size = circuit.eval_domain_size()?;
+        let num_inputs = circuit.num_inputs();
+        let selectors = circuit.compute_selector_polynomials()?;
+        let sigmas = circuit.compute_extended_permutation_polynomials()?;
+
+        let max_degree = 64 + 2;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+
+        // check proving key
+        assert_eq!(pk.selectors, selectors);
+        assert_eq!(pk.sigmas, sigmas);
+        assert_eq!(pk.domain_size(), domain_size);
+        assert_eq!(pk.num_inputs(), num_inputs);
+        let num_wire_types = GATE_WIDTH
+            + 1
+            + match plonk_type {
+                PlonkType::TurboPlonk => 0,
+                PlonkType::UltraPlonk => 1,
+            };
+        assert_eq!(pk.sigmas.len(), num_wire_types);
+        // check plookup proving key
+        if plonk_type == PlonkType::UltraPlonk {
+            let range_table_poly = circuit.compute_range_table_polynomial()?;
+            assert_eq!(
+                pk.plookup_pk.as_ref().unwrap().range_table_poly,
+                range_table_poly
+            );
+
+            let key_table_poly = circuit.compute_key_table_polynomial()?;
+            assert_eq!(
+                pk.plookup_pk.as_ref().unwrap().key_table_poly,
+                key_table_poly
+            );
+        }
+
+        // check verifying key
+        assert_eq!(vk.domain_size, domain_size);
+        assert_eq!(vk.num_inputs, num_inputs);
+        assert_eq!(vk.selector_comms.len(), selectors.len());
+        assert_eq!(vk.sigma_comms.len(), sigmas.len());
+        assert_eq!(vk.sigma_comms.len(), num_wire_types);
+        selectors
+            .iter()
+            .zip(vk.selector_comms.iter())
+            .for_each(|(p, &p_comm)| {
+                let (expected_comm, _) = KZG10::commit(&pk.commit_key, p, None, None).unwrap();
+                assert_eq!(expected_comm, p_comm);
+            });
+        sigmas
+            .iter()
+            .zip(vk.sigma_comms.iter())
+            .for_each(|(p, &p_comm)| {
+                let (expected_comm, _) = KZG10::commit(&pk.commit_key, p, None, None).unwrap();
+                assert_eq!(expected_comm, p_comm);
+            });
+        // check plookup verification key
+        if plonk_type == PlonkType::UltraPlonk {
+            let (expected_comm, _) = KZG10::commit(
+                &pk.commit_key,
+                &pk.plookup_pk.as_ref().unwrap().range_table_poly,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(
+                expected_comm,
+                vk.plookup_vk.as_ref().unwrap().range_table_comm
+            );
+
+            let (expected_comm, _) = KZG10::commit(
+                &pk.commit_key,
+                &pk.plookup_pk.as_ref().unwrap().key_table_poly,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(
+                expected_comm,
+                vk.plookup_vk.as_ref().unwrap().key_table_comm
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preprocessing_imported_circuit() -> Result<(), PlonkError> {
+        test_preprocessing_imported_circuit_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
+        test_preprocessing_imported_circuit_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
+        test_preprocessing_imported_circuit_helper::<Bls12_377, Fq377, _>(PlonkType::TurboPlonk)?;
+        test_preprocessing_imported_circuit_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)
+    }
+
+    fn test_preprocessing_imported_circuit_helper<E, F, P>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let circuit = gen_circuit_for_test::<E::Fr>(5, 6, plonk_type)?;
+        let mut ir = vec![];
+        circuit.export_ir(&mut ir)?;
+        let imported = PlonkCircuit::<E::Fr>::import_ir(&ir[..])?;
+
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, &mut test_rng())?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let (imported_pk, imported_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &imported)?;
+        assert_eq!(imported_pk, pk);
+        assert_eq!(imported_vk, vk);
+
+        // proofs of the imported circuit match those of the original one
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            None,
+        )?;
+        let imported_proof = PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(
+            &mut test_rng(),
+            &imported,
+            &imported_pk,
+            None,
+        )?;
+        assert_eq!(imported_proof, proof);
+        let public_inputs = imported.public_input()?;
+        assert!(PlonkKzgSnark::<E>::verify::<StandardTranscript>(
+            &vk,
+            &public_inputs,
+            &imported_proof,
+            None
+        )
+        .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_preprocessing() -> Result<(), PlonkError> {
+        test_batch_preprocessing_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
+        test_batch_preprocessing_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
+        test_batch_preprocessing_helper::<Bls12_377, Fq377, _>(PlonkType::TurboPlonk)?;
+        test_batch_preprocessing_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)
+    }
+
+    fn test_batch_preprocessing_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut ark_std::test_rng();
+        // circuits of different sizes, some of which share the same size
+        let circuits = vec![
+            gen_circuit_for_test(3, 4, plonk_type)?,
+            gen_circuit_for_test(5, 6, plonk_type)?,
+            gen_circuit_for_test(4, 4, plonk_type)?,
+            gen_circuit_for_test(2, 3, plonk_type)?,
+        ];
+        let circuit_refs: Vec<&PlonkCircuit<E::Fr>> = circuits.iter().collect();
+
+        let max_degree = 64 + 2;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let keys = PlonkKzgSnark::<E>::batch_preprocess(&srs, &circuit_refs)?;
+        assert_eq!(keys.len(), circuits.len());
+        for (circuit, (pk, vk)) in circuits.iter().zip(keys.iter()) {
+            let (expected_pk, expected_vk) = PlonkKzgSnark::<E>::preprocess(&srs, circuit)?;
+            assert_eq!(pk, &expected_pk);
+            assert_eq!(vk, &expected_vk);
+            // the commit keys share the powers of the SRS
+            assert_eq!(
+                pk.commit_key.powers_of_g.as_ptr(),
+                srs.0.powers_of_g.as_ptr()
+            );
+        }
+        assert!(PlonkKzgSnark::<E>::batch_preprocess::<PlonkCircuit<E::Fr>>(&srs, &[])?.is_empty());
+
+        // bad path: the SRS is too small for one of the circuits
+        let small_srs = PlonkKzgSnark::<E>::universal_setup(circuits[0].srs_size()?, rng)?;
+        assert!(PlonkKzgSnark::<E>::batch_preprocess(&small_srs, &circuit_refs).is_err());
+        // bad path: a circuit is not finalized
+        let unfinalized: PlonkCircuit<E::Fr> = PlonkCircuit::new_turbo_plonk();
+        assert!(PlonkKzgSnark::<E>::batch_preprocess(&srs, &[&circuits[0], &unfinalized]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preprocess_checked() -> Result<(), PlonkError> {
+        test_preprocess_checked_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
+        test_preprocess_checked_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
+        test_preprocess_checked_helper::<Bls12_377, Fq377, _>(PlonkType::TurboPlonk)?;
+        test_preprocess_checked_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)
+    }
+
+    fn test_preprocess_checked_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut ark_std::test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let other_circuit = gen_circuit_for_test(4, 4, plonk_type)?;
+        let max_degree = 64 + 2;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess_checked(&srs, &circuit)?;
+        assert_eq!(
+            (pk.clone(), vk.clone()),
+            PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?
+        );
+        assert!(pk.check_consistency(&pk.vk).is_ok());
+
+        // keys of different circuits
+        let (other_pk, other_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &other_circuit)?;
+        assert!(pk.check_consistency(&other_vk).is_err());
+        assert!(other_pk.check_consistency(&vk).is_err());
+        // a tampered selector polynomial
+        let mut bad_pk = pk.clone();
+        bad_pk.selectors[0] =
+            &bad_pk.selectors[0] + &DensePolynomial::from_coefficients_vec(vec![E::Fr::one()]);
+        assert!(bad_pk.check_consistency(&vk).is_err());
+        // tampered sigma commitments
+        let mut bad_vk = vk.clone();
+        bad_vk.sigma_comms.swap(0, 1);
+        assert!(pk.check_consistency(&bad_vk).is_err());
+        bad_vk.sigma_comms.pop();
+        assert!(pk.check_consistency(&bad_vk).is_err());
+        // a wrong number of public inputs
+        let mut bad_vk = vk.clone();
+        bad_vk.num_inputs += 1;
+        assert!(pk.check_consistency(&bad_vk).is_err());
+        // lookup support mismatch
+        let mut bad_vk = vk.clone();
+        bad_vk.plookup_vk = match vk.plookup_vk {
+            Some(_) => None,
+            None => Some(PlookupVerifyingKey {
+                range_table_comm: Commitment::default(),
+                key_table_comm: Commitment::default(),
+            }),
+        };
+        assert!(pk.check_consistency(&bad_vk).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_proof_system() -> Result<(), PlonkError> {
+        // merlin transcripts
+        test_plonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_plonk_proof_system_helper::<BW6_761, Fq761, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_plonk_proof_system_helper::<BW6_761, Fq761, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+
+        // rescue transcripts
+        // currently only available for bls12-377
+        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::UltraPlonk,
+        )?;
+
+        // solidity-friendly keccak256 transcripts
+        // currently only needed for CAPE using bls12-381, and for EVM verifiers
+        // using bn254
+        test_plonk_proof_system_helper::<Bls12_381, Fq381, _, SolidityTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bn254, Fq254, _, SolidityTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_plonk_proof_system_helper::<Bn254, Fq254, _, SolidityTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        Ok(())
+    }
+
+    fn test_plonk_proof_system_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        // 1. Simulate universal setup
+        let rng = &mut test_rng();
+        let n = 64;
+        let max_degree = n + 2;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+
+        // 2. Create circuits
+        let circuits = (0..6)
+            .map(|i| {
+                let m = 2 + i / 3;
+                let a0 = 1 + i % 3;
+                gen_circuit_for_test(m, a0, plonk_type)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        // 3. Preprocessing
+        let (pk1, vk1) = PlonkKzgSnark::<E>::preprocess(&srs, &circuits[0])?;
+        let (pk2, vk2) = PlonkKzgSnark::<E>::preprocess(&srs, &circuits[3])?;
+        // 4. Proving
+        let mut proofs = vec![];
+        let mut extra_msgs = vec![];
+        for (i, cs) in circuits.iter().enumerate() {
+            let pk_ref = if i < 3 { &pk1 } else { &pk2 };
+            let extra_msg = if i % 2 == 0 {
+                None
+            } else {
+                Some(format!("extra message: {}", i).into_bytes())
+            };
+            proofs.push(
+                PlonkKzgSnark::<E>::prove::<_, _, T>(rng, cs, pk_ref, extra_msg.clone()).unwrap(),
+            );
+            extra_msgs.push(extra_msg);
+        }
+
+        // 5. Verification
+        let public_inputs: Vec<Vec<E::Fr>> = circuits
+            .iter()
+            .map(|cs| cs.public_input())
+            .collect::<Result<Vec<Vec<E::Fr>>, PlonkError>>()?;
+        for (i, proof) in proofs.iter().enumerate() {
+            let vk_ref = if i < 3 { &vk1 } else { &vk2 };
+            assert!(PlonkKzgSnark::<E>::verify::<T>(
+                vk_ref,
+                &public_inputs[i],
+                proof,
+                extra_msgs[i].clone(),
+            )
+            .is_ok());
+            // Inconsistent proof should fail the verification.
+            let mut bad_pub_input = public_inputs[i].clone();
+            bad_pub_input[0] = E::Fr::from(0u8);
+            assert!(PlonkKzgSnark::<E>::verify::<T>(
+                vk_ref,
+                &bad_pub_input,
+                proof,
+                extra_msgs[i].clone(),
+            )
+            .is_err());
+            // Incorrect extra transcript message should fail
+            assert!(PlonkKzgSnark::<E>::verify::<T>(
+                vk_ref,
+                &bad_pub_input,
+                proof,
+                Some("wrong message".to_string().into_bytes()),
+            )
+            .is_err());
+
+            // Incorrect proof [W_z] = 0, [W_z*g] = 0
+            // attack against some vulnerable implementation described in:
+            // https://cryptosubtlety.medium.com/00-8d4adcf4d255
+            let mut bad_proof = proof.clone();
+            bad_proof.opening_proof = Commitment::default();
+            bad_proof.shifted_opening_proof = Commitment::default();
+            assert!(PlonkKzgSnark::<E>::verify::<T>(
+                vk_ref,
+                &public_inputs[i],
+                &bad_proof,
+                extra_msgs[i].clone(),
+            )
+            .is_err());
+        }
+
+        // 6. Batch verification
+        let vks = vec![&vk1, &vk1, &vk1, &vk2, &vk2, &vk2];
+        let mut public_inputs_ref: Vec<&[E::Fr]> = public_inputs
+            .iter()
+            .map(|pub_input| &pub_input[..])
+            .collect();
+        let mut proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+        )
+        .is_ok());
+
+        // Inconsistent params
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks[..5],
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+        )
+        .is_err());
+
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref[..5],
+            &proofs_ref,
+            &extra_msgs,
+        )
+        .is_err());
+
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref[..5],
+            &extra_msgs,
+        )
+        .is_err());
+
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &vec![None; vks.len()],
+        )
+        .is_err());
+
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &vec![],
+        )
+        .is_err());
+
+        // Empty params
+        assert!(
+            PlonkKzgSnark::<E>::batch_verify::<T>(&vec![], &vec![], &vec![], &vec![],).is_err()
+        );
+
+        // Error paths
+        let tmp_pi_ref = public_inputs_ref[0];
+        public_inputs_ref[0] = public_inputs_ref[1];
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+        )
+        .is_err());
+        public_inputs_ref[0] = tmp_pi_ref;
+
+        proofs_ref[0] = proofs_ref[1];
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+        )
+        .is_err());
+
+        // 7. Columnar batch verification
+        let columnar_vks: Vec<VerifyingKey<E>> = vks.iter().map(|&vk| vk.clone()).collect();
+        let columnar_public_inputs: Vec<E::Fr> = public_inputs.concat();
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(
+            &columnar_vks,
+            &columnar_public_inputs,
+            &proofs,
+            &extra_msgs,
+        )
+        .is_ok());
+
+        // Inconsistent params
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(
+            &columnar_vks[..5],
+            &columnar_public_inputs,
+            &proofs,
+            &extra_msgs,
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(
+            &columnar_vks,
+            &columnar_public_inputs[1..],
+            &proofs,
+            &extra_msgs,
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(
+            &columnar_vks,
+            &columnar_public_inputs,
+            &proofs[..5],
+            &extra_msgs,
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(
+            &columnar_vks,
+            &columnar_public_inputs,
+            &proofs,
+            &vec![None; vks.len()],
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(&[], &[], &[], &[]).is_err());
+
+        // Error paths agree with `batch_verify`
+        let mut bad_public_inputs = columnar_public_inputs.clone();
+        bad_public_inputs[0] += E::Fr::one();
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(
+            &columnar_vks,
+            &bad_public_inputs,
+            &proofs,
+            &extra_msgs,
+        )
+        .is_err());
+        let mut bad_proofs = proofs.clone();
+        bad_proofs[0] = bad_proofs[1].clone();
+        assert!(PlonkKzgSnark::<E>::batch_verify_columnar::<T>(
+            &columnar_vks,
+            &columnar_public_inputs,
+            &bad_proofs,
+            &extra_msgs,
+        )
+        .is_err());
+
+        // 8. Parallel batch verification
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        let mut bad_proofs_ref = proofs_ref.clone();
+        bad_proofs_ref[4] = proofs_ref[5];
+        let pcs_infos = PlonkKzgSnark::<E>::prepare_batch_pcs_infos::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+        )?;
+        let aggregated =
+            Verifier::<KzgPcs<E>>::aggregate_opening_proofs::<T>(&vk1.open_key, &pcs_infos)?;
+        for &chunk_size in [1, 2, 4, 6, 7].iter() {
+            // the accumulated check doesn't depend on the chunking
+            assert_eq!(
+                Verifier::<KzgPcs<E>>::aggregate_opening_proofs_parallel::<T>(
+                    &vk1.open_key,
+                    &pcs_infos,
+                    chunk_size
+                )?,
+                aggregated
+            );
+            assert!(PlonkKzgSnark::<E>::batch_verify_parallel::<T>(
+                &vks,
+                &public_inputs_ref,
+                &proofs_ref,
+                &extra_msgs,
+                chunk_size,
+            )
+            .is_ok());
+            // a single invalid proof makes the batch fail as in `batch_verify`
+            assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+                &vks,
+                &public_inputs_ref,
+                &bad_proofs_ref,
+                &extra_msgs,
+            )
+            .is_err());
+            assert!(PlonkKzgSnark::<E>::batch_verify_parallel::<T>(
+                &vks,
+                &public_inputs_ref,
+                &bad_proofs_ref,
+                &extra_msgs,
+                chunk_size,
+            )
+            .is_err());
+        }
+
+        // Bad params
+        assert!(PlonkKzgSnark::<E>::batch_verify_parallel::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+            0,
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::batch_verify_parallel::<T>(
+            &vks[..5],
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+            2,
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::batch_verify_parallel::<T>(&[], &[], &[], &[], 2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transcript_continuation() -> Result<(), PlonkError> {
+        test_transcript_continuation_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_transcript_continuation_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_transcript_continuation_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_transcript_continuation_helper::<Bls12_381, Fq381, _, SolidityTranscript>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_transcript_continuation_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let max_degree = 80;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let extra_msg = Some(b"extra message".to_vec());
+        let pub_input = circuit.public_input()?;
+
+        let (proof, mut prover_transcript) = PlonkKzgSnark::<E>::prove_with_transcript::<_, _, T>(
+            rng,
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        let mut verifier_transcript = PlonkKzgSnark::<E>::verify_with_transcript::<T>(
+            &vk,
+            &pub_input,
+            &proof,
+            extra_msg.clone(),
+        )?;
+
+        // continue both transcripts with the same follow-up message
+        prover_transcript.append_message(b"next round", b"follow-up message")?;
+        verifier_transcript.append_message(b"next round", b"follow-up message")?;
+        let prover_challenge = prover_transcript.get_and_append_challenge::<E>(b"post")?;
+        let verifier_challenge = verifier_transcript.get_and_append_challenge::<E>(b"post")?;
+        assert_eq!(prover_challenge, verifier_challenge);
+
+        // a different follow-up message leads to a different challenge
+        let mut other_transcript = PlonkKzgSnark::<E>::verify_with_transcript::<T>(
+            &vk,
+            &pub_input,
+            &proof,
+            extra_msg.clone(),
+        )?;
+        other_transcript.append_message(b"next round", b"another message")?;
+        assert_ne!(
+            other_transcript.get_and_append_challenge::<E>(b"post")?,
+            prover_challenge
+        );
+
+        // a wrong public input is rejected rather than yielding a transcript
+        let mut bad_pub_input = pub_input.clone();
+        bad_pub_input[0] = E::Fr::from(0u8);
+        assert!(PlonkKzgSnark::<E>::verify_with_transcript::<T>(
+            &vk,
+            &bad_pub_input,
+            &proof,
+            extra_msg,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_single_threaded() -> Result<(), PlonkError> {
+        test_prove_single_threaded_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_prove_single_threaded_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_prove_single_threaded_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_prove_single_threaded_helper::<BW6_761, Fq761, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_prove_single_threaded_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let max_degree = 80;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let extra_msg = Some(b"extra message".to_vec());
+
+        let parallel_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        let serial_proof = PlonkKzgSnark::<E>::prove_single_threaded::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        assert_eq!(parallel_proof, serial_proof);
+        let mut parallel_bytes = Vec::new();
+        parallel_proof.serialize(&mut parallel_bytes)?;
+        let mut serial_bytes = Vec::new();
+        serial_proof.serialize(&mut serial_bytes)?;
+        assert_eq!(parallel_bytes, serial_bytes);
+
+        assert!(PlonkKzgSnark::<E>::verify::<T>(
+            &vk,
+            &circuit.public_input()?,
+            &serial_proof,
+            extra_msg
+        )
+        .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_low_memory() -> Result<(), PlonkError> {
+        test_prove_low_memory_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_prove_low_memory_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_prove_low_memory_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_prove_low_memory_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(120, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let extra_msg = Some(b"extra message".to_vec());
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+
+        // the polynomials evaluated on each coset, counted as the prover does:
+        // the selectors, the sigmas, the wires, the permutation product and
+        // the public input polynomial, and with lookup 4 more polynomials and
+        // the 2 sorted ones
+        let n = circuit.eval_domain_size()?;
+        let num_wire_types = circuit.num_wire_types();
+        let num_polys = pk.selectors.len()
+            + pk.sigmas.len()
+            + num_wire_types
+            + 2
+            + if pk.plookup_pk.is_some() { 4 + 2 } else { 0 };
+        let coset_bytes = num_polys * n * core::mem::size_of::<E::Fr>();
+        let num_cosets =
+            GeneralEvaluationDomain::<E::Fr>::new(n * domain_size_ratio(n, num_wire_types))
+                .unwrap()
+                .size()
+                / n;
+        assert!(num_cosets > 3);
+
+        // one coset at a time, a few cosets at a time, with a last chunk of
+        // fewer cosets or not, and all of them
+        for &memory_budget in [
+            0,
+            2 * coset_bytes,
+            3 * coset_bytes + coset_bytes / 2,
+            usize::MAX,
+        ]
+        .iter()
+        {
+            let low_memory_proof = PlonkKzgSnark::<E>::prove_low_memory::<_, _, T>(
+                &mut test_rng(),
+                &circuit,
+                &pk,
+                extra_msg.clone(),
+                memory_budget,
+            )?;
+            assert_eq!(low_memory_proof, proof);
+        }
+        assert!(
+            PlonkKzgSnark::<E>::verify::<T>(&vk, &circuit.public_input()?, &proof, extra_msg)
+                .is_ok()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_in_pool() -> Result<(), PlonkError> {
+        test_prove_in_pool_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_prove_in_pool_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(PlonkType::UltraPlonk)
+    }
+
+    fn test_prove_in_pool_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let extra_msg = Some(b"extra message".to_vec());
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+
+        let proof = PlonkKzgSnark::<E>::prove_in_pool::<_, _, T>(
+            &pool,
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        let global_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        assert_eq!(proof, global_proof);
+        assert!(
+            PlonkKzgSnark::<E>::verify::<T>(&vk, &circuit.public_input()?, &proof, extra_msg)
+                .is_ok()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_many() -> Result<(), PlonkError> {
+        for &plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk].iter() {
+            test_prove_many_helper::<Bn254, Fq254, _, StandardTranscript>(plonk_type)?;
+            test_prove_many_helper::<Bls12_377, Fq377, _, StandardTranscript>(plonk_type)?;
+            test_prove_many_helper::<Bls12_381, Fq381, _, StandardTranscript>(plonk_type)?;
+            test_prove_many_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(plonk_type)?;
+        }
+        Ok(())
+    }
+
+    fn test_prove_many_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        // the same circuit for 8 different witnesses
+        let circuits = (0..8)
+            .map(|a0| gen_circuit_for_test(3, a0, plonk_type))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let circuit_refs: Vec<&PlonkCircuit<E::Fr>> = circuits.iter().collect();
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuits[0].srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuits[0])?;
+        let extra_msg = Some(b"extra message".to_vec());
+
+        let proofs = PlonkKzgSnark::<E>::prove_many::<_, _, T>(
+            &mut test_rng(),
+            &circuit_refs,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        assert_eq!(proofs.len(), circuits.len());
+
+        // each proof verifies on its own, and only with its own public input
+        let mut seed_rng = test_rng();
+        for (i, (circuit, proof)) in circuits.iter().zip(proofs.iter()).enumerate() {
+            let pub_input = circuit.public_input()?;
+            assert!(
+                PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, proof, extra_msg.clone()).is_ok()
+            );
+            let other_pub_input = circuits[(i + 1) % circuits.len()].public_input()?;
+            assert!(PlonkKzgSnark::<E>::verify::<T>(
+                &vk,
+                &other_pub_input,
+                proof,
+                extra_msg.clone()
+            )
+            .is_err());
+
+            // the shared precomputation doesn't change the proof
+            let mut seed = [0u8; 32];
+            seed_rng.fill_bytes(&mut seed);
+            let expected_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(
+                &mut ChaChaRng::from_seed(seed),
+                circuit,
+                &pk,
+                extra_msg.clone(),
+            )?;
+            assert_eq!(*proof, expected_proof);
+        }
+
+        // bad path: no circuit
+        assert!(
+            PlonkKzgSnark::<E>::prove_many::<PlonkCircuit<E::Fr>, _, T>(rng, &[], &pk, None)
+                .is_err()
+        );
+
+        // bad path: a circuit inconsistent with the proving key
+        let other_circuit = gen_circuit_for_test(10, 3, plonk_type)?;
+        if other_circuit.eval_domain_size()? != circuits[0].eval_domain_size()? {
+            assert!(PlonkKzgSnark::<E>::prove_many::<_, _, T>(
+                rng,
+                &[&circuits[0], &other_circuit],
+                &pk,
+                None
+            )
+            .is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_without_zero_knowledge() -> Result<(), PlonkError> {
+        test_prove_without_zero_knowledge_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_prove_without_zero_knowledge_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_prove_without_zero_knowledge_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_prove_without_zero_knowledge_helper::<BW6_761, Fq761, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_prove_without_zero_knowledge_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let max_degree = 80;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let public_input = circuit.public_input()?;
+        let extra_msg = Some(b"extra message".to_vec());
+
+        let zk_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &circuit, &pk, extra_msg.clone())?;
+        let proof = PlonkKzgSnark::<E>::prove_without_zero_knowledge::<_, T>(
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        assert!(
+            PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &zk_proof, extra_msg.clone())
+                .is_ok()
+        );
+        assert!(
+            PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, extra_msg.clone()).is_ok()
+        );
+        // the opening proofs can also be checked one by one
+        assert!(PlonkKzgSnark::<E>::verify_unbatched::<T>(
+            &vk,
+            &public_input,
+            &zk_proof,
+            extra_msg.clone()
+        )
+        .is_ok());
+        assert!(PlonkKzgSnark::<E>::verify_unbatched::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            extra_msg.clone()
         )
+        .is_ok());
+
+        // same number of group and field elements with and without blinding
+        let mut zk_bytes = Vec::new();
+        zk_proof.serialize(&mut zk_bytes)?;
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes)?;
+        assert_eq!(zk_bytes.len(), bytes.len());
+        assert_ne!(zk_bytes, bytes);
+
+        // without blinding, the proof is determined by the witness
+        let same_proof = PlonkKzgSnark::<E>::prove_without_zero_knowledge::<_, T>(
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        assert_eq!(proof, same_proof);
+
+        // bad path: wrong public input
+        let mut bad_public_input = public_input.clone();
+        bad_public_input[0] += E::Fr::one();
+        assert!(
+            PlonkKzgSnark::<E>::verify::<T>(&vk, &bad_public_input, &proof, extra_msg).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pcs_snark() -> Result<(), PlonkError> {
+        // the same circuits are proven over both schemes
+        let circuit = gen_circuit_for_test::<ark_bn254::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        test_pcs_snark_helper::<KzgPcs<Bn254>, _, _, StandardTranscript>(&circuit)?;
+        test_pcs_snark_helper::<IpaPcs<ark_bn254::G1Affine>, _, _, StandardTranscript>(&circuit)?;
+
+        let circuit = gen_circuit_for_test::<ark_bls12_377::Fr>(5, 6, PlonkType::UltraPlonk)?;
+        test_pcs_snark_helper::<KzgPcs<Bls12_377>, _, _, RescueTranscript<_>>(&circuit)?;
+        test_pcs_snark_helper::<IpaPcs<ark_bls12_377::G1Affine>, _, _, RescueTranscript<_>>(
+            &circuit,
+        )
+    }
+
+    pub(crate) fn test_pcs_snark_helper<PCS, F, P, T>(
+        circuit: &PlonkCircuit<PCS::Fr>,
+    ) -> Result<(), PlonkError>
+    where
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>> + for<'a> PcsCommitter<'a>,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let params = PCS::setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkPcsSnark::<PCS>::preprocess(&params, circuit)?;
+        let public_input = circuit.public_input()?;
+        let extra_msg = Some(b"extra message".to_vec());
+
+        let proof = PlonkPcsSnark::<PCS>::prove_without_zero_knowledge::<_, T>(
+            circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        assert!(PlonkPcsSnark::<PCS>::verify_unbatched::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            extra_msg.clone()
+        )
+        .is_ok());
+
+        // serialization roundtrip
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes)?;
+        assert_eq!(PcsProof::<PCS>::deserialize(&bytes[..])?, proof);
+
+        // bad path: wrong public input
+        let mut bad_public_input = public_input.clone();
+        bad_public_input[0] += PCS::Fr::one();
+        assert!(PlonkPcsSnark::<PCS>::verify_unbatched::<T>(
+            &vk,
+            &bad_public_input,
+            &proof,
+            extra_msg.clone()
+        )
+        .is_err());
+
+        // bad path: wrong transcript message
+        assert!(
+            PlonkPcsSnark::<PCS>::verify_unbatched::<T>(&vk, &public_input, &proof, None).is_err()
+        );
+
+        // bad path: wrong evaluation, wrong opening proof, wrong commitment
+        let mut bad_proof = proof.clone();
+        bad_proof.poly_evals.wires_evals[0] += PCS::Fr::one();
+        assert!(PlonkPcsSnark::<PCS>::verify_unbatched::<T>(
+            &vk,
+            &public_input,
+            &bad_proof,
+            extra_msg.clone()
+        )
+        .is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.opening_proof = proof.shifted_opening_proof.clone();
+        assert!(PlonkPcsSnark::<PCS>::verify_unbatched::<T>(
+            &vk,
+            &public_input,
+            &bad_proof,
+            extra_msg.clone()
+        )
+        .is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.wires_poly_comms.swap(0, 1);
+        assert!(PlonkPcsSnark::<PCS>::verify_unbatched::<T>(
+            &vk,
+            &public_input,
+            &bad_proof,
+            extra_msg
+        )
+        .is_err());
+
+        // bad path: proof of another circuit with the same public input
+        let mut other_circuit = PlonkCircuit::<PCS::Fr>::new_turbo_plonk();
+        for input in public_input.iter() {
+            other_circuit.create_public_variable(*input)?;
+        }
+        other_circuit.finalize_for_arithmetization()?;
+        let (other_pk, _) = PlonkPcsSnark::<PCS>::preprocess(&params, &other_circuit)?;
+        assert!(PlonkPcsSnark::<PCS>::prove_without_zero_knowledge::<_, T>(
+            &other_circuit,
+            &pk,
+            None
+        )
+        .is_err());
+        let other_proof = PlonkPcsSnark::<PCS>::prove_without_zero_knowledge::<_, T>(
+            &other_circuit,
+            &other_pk,
+            None,
+        )?;
+        assert!(PlonkPcsSnark::<PCS>::verify_unbatched::<T>(
+            &vk,
+            &public_input,
+            &other_proof,
+            None
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_quotient_chunks() -> Result<(), PlonkError> {
+        test_quotient_chunks_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_quotient_chunks_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_quotient_chunks_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_quotient_chunks_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let n = circuit.eval_domain_size()?;
+        let num_wire_types = circuit.num_wire_types();
+        let max_degree = num_wire_types * (n + 1) + 2;
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let public_input = circuit.public_input()?;
+        let extra_msg = Some(b"extra message".to_vec());
+
+        // the default split has one chunk per wire type
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let (default_pk, default_vk) =
+            PlonkKzgSnark::<E>::preprocess_with_quotient_chunks(&srs, &circuit, num_wire_types)?;
+        assert_eq!(vk.num_quotient_chunks(), num_wire_types);
+        assert_eq!(default_vk, vk);
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            extra_msg.clone(),
+        )?;
+        let default_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &default_pk,
+            extra_msg.clone(),
+        )?;
+        assert_eq!(proof, default_proof);
+        let mut default_bytes = Vec::new();
+        default_proof.serialize(&mut default_bytes)?;
+
+        let mut proofs_and_vks = vec![];
+        for num_chunks in 2..=4 {
+            let (pk, vk) =
+                PlonkKzgSnark::<E>::preprocess_with_quotient_chunks(&srs, &circuit, num_chunks)?;
+            assert_eq!(vk.num_quotient_chunks(), num_chunks);
+            let proof =
+                PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &circuit, &pk, extra_msg.clone())?;
+            assert_eq!(proof.split_quot_poly_comms.len(), num_chunks);
+            assert!(
+                PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, extra_msg.clone())
+                    .is_ok()
+            );
+            // one group element less per chunk
+            let mut bytes = Vec::new();
+            proof.serialize(&mut bytes)?;
+            assert!(bytes.len() < default_bytes.len());
+
+            // the split quotient polynomials have the expected degrees
+            let degrees = vk.expected_poly_degrees();
+            assert_eq!(degrees.split_quot.len(), num_chunks);
+            assert_eq!(
+                degrees.split_quot.iter().map(|d| d + 1).sum::<usize>(),
+                max_degree + 1
+            );
+
+            // bad path: wrong public input
+            let mut bad_public_input = public_input.clone();
+            bad_public_input[0] += E::Fr::one();
+            assert!(PlonkKzgSnark::<E>::verify::<T>(
+                &vk,
+                &bad_public_input,
+                &proof,
+                extra_msg.clone()
+            )
+            .is_err());
+            proofs_and_vks.push((proof, vk));
+        }
+
+        // bad path: a proof verified against a key with another split
+        assert!(PlonkKzgSnark::<E>::verify::<T>(
+            &proofs_and_vks[1].1,
+            &public_input,
+            &proofs_and_vks[0].0,
+            extra_msg.clone()
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::verify::<T>(
+            &vk,
+            &public_input,
+            &proofs_and_vks[2].0,
+            extra_msg
+        )
+        .is_err());
+
+        // bad path: no chunk, too many chunks, or an SRS too small for the chunks
+        assert!(PlonkKzgSnark::<E>::preprocess_with_quotient_chunks(&srs, &circuit, 0).is_err());
+        assert!(PlonkKzgSnark::<E>::preprocess_with_quotient_chunks(
+            &srs,
+            &circuit,
+            max_degree + 2
+        )
+        .is_err());
+        let small_srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        assert!(PlonkKzgSnark::<E>::preprocess(&small_srs, &circuit).is_ok());
+        assert!(
+            PlonkKzgSnark::<E>::preprocess_with_quotient_chunks(&small_srs, &circuit, 2).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_srs_too_small() -> Result<(), PlonkError> {
+        for plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk] {
+            test_srs_too_small_helper::<Bn254>(plonk_type)?;
+            test_srs_too_small_helper::<Bls12_381>(plonk_type)?;
+        }
+        Ok(())
+    }
+
+    fn test_srs_too_small_helper<E: PairingEngine>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError> {
+        use crate::proof_system::structs::quotient_polynomial_degree;
+
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test::<E::Fr>(3, 4, plonk_type)?;
+        let required = circuit.srs_size()?;
+        let small_srs = PlonkKzgSnark::<E>::universal_setup(required - 1, rng)?;
+        let is_too_small = |res: Result<_, PlonkError>, expected: usize| {
+            matches!(
+                res,
+                Err(PlonkError::SrsTooSmall { required, provided })
+                    if required == expected && provided == expected - 1
+            )
+        };
+
+        assert!(is_too_small(
+            PlonkKzgSnark::<E>::preprocess(&small_srs, &circuit).map(|_| ()),
+            required
+        ));
+        assert!(is_too_small(
+            PlonkKzgSnark::<E>::batch_preprocess(&small_srs, &[&circuit]).map(|_| ()),
+            required
+        ));
+        assert!(is_too_small(
+            KzgPcs::new(&small_srs, required).map(|_| ()),
+            required
+        ));
+
+        // a single quotient chunk needs the whole quotient degree, which is
+        // larger with the lookup wires of UltraPlonk
+        let quot_degree =
+            quotient_polynomial_degree(circuit.eval_domain_size()?, circuit.num_wire_types());
+        let srs = PlonkKzgSnark::<E>::universal_setup(quot_degree - 1, rng)?;
+        assert!(is_too_small(
+            PlonkKzgSnark::<E>::preprocess_with_quotient_chunks(&srs, &circuit, 1).map(|_| ()),
+            quot_degree
+        ));
+        assert!(PlonkKzgSnark::<E>::preprocess(&srs, &circuit).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_loaded_witnesses() -> Result<(), PlonkError> {
+        test_prove_loaded_witnesses_helper::<Bn254, Fq254, _, StandardTranscript>()?;
+        test_prove_loaded_witnesses_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()
+    }
+
+    fn test_prove_loaded_witnesses_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        // (x + y) * y = z, with z public
+        let mut circuit = PlonkCircuit::<E::Fr>::new_turbo_plonk();
+        let x = circuit.create_variable(E::Fr::from(2u32))?;
+        let y = circuit.create_variable(E::Fr::from(3u32))?;
+        let sum = circuit.add(x, y)?;
+        let z = circuit.create_public_variable(E::Fr::from(15u32))?;
+        circuit.mul_gate(sum, y, z)?;
+        circuit.finalize_for_arithmetization()?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+
+        // prove for several witnesses computed outside of the circuit, the
+        // constant variables 0 and 1 included
+        let num_gates = circuit.num_gates();
+        for &(x_val, y_val) in [(2u32, 3u32), (5, 7), (0, 11)].iter() {
+            let mut values = vec![E::Fr::zero(); circuit.num_vars()];
+            values[1] = E::Fr::one();
+            values[x] = E::Fr::from(x_val);
+            values[y] = E::Fr::from(y_val);
+            values[sum] = E::Fr::from(x_val + y_val);
+            values[z] = E::Fr::from((x_val + y_val) * y_val);
+            circuit.load_witness(&values)?;
+            assert_eq!(circuit.num_gates(), num_gates);
+            assert_eq!(circuit.public_input()?, vec![values[z]]);
+            assert!(circuit.check_circuit_satisfiability(&[values[z]]).is_ok());
+
+            let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &circuit, &pk, None)?;
+            assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &[values[z]], &proof, None).is_ok());
+        }
+
+        // bad path: an unsatisfying assignment, or one of a wrong length
+        let mut values = vec![E::Fr::zero(); circuit.num_vars()];
+        values[1] = E::Fr::one();
+        values[x] = E::Fr::one();
+        circuit.load_witness(&values)?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[E::Fr::zero()])
+            .is_err());
+        assert!(circuit.load_witness(&values[1..]).is_err());
+        values.push(E::Fr::zero());
+        assert!(circuit.load_witness(&values).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_with_witness_polys() -> Result<(), PlonkError> {
+        test_prove_with_witness_polys_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_prove_with_witness_polys_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_prove_with_witness_polys_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_prove_with_witness_polys_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, &mut test_rng())?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let pub_input = circuit.public_input()?;
+        let wire_polys = circuit.compute_wire_polynomials()?;
+
+        // the polynomials of the normal path give an identical proof
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(&mut test_rng(), &circuit, &pk, None)?;
+        let proof_from_polys = PlonkKzgSnark::<E>::prove_with_witness_polys::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            wire_polys.clone(),
+            None,
+        )?;
+        assert_eq!(proof, proof_from_polys);
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &proof_from_polys, None).is_ok());
+
+        // a wrong number of polynomials
+        assert!(PlonkKzgSnark::<E>::prove_with_witness_polys::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            wire_polys[1..].to_vec(),
+            None,
+        )
+        .is_err());
+        // a polynomial of too large degree
+        let n = circuit.eval_domain_size()?;
+        let mut bad_polys = wire_polys.clone();
+        bad_polys[0] = &bad_polys[0]
+            + &DensePolynomial::from_coefficients_vec(vec![E::Fr::zero(), E::Fr::one()])
+                .mul_by_vanishing_poly(Radix2EvaluationDomain::<E::Fr>::new(n).unwrap());
+        assert!(PlonkKzgSnark::<E>::prove_with_witness_polys::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            bad_polys,
+            None,
+        )
+        .is_err());
+        // an output wire polynomial inconsistent with the public inputs
+        let mut bad_polys = wire_polys.clone();
+        bad_polys[GATE_WIDTH] =
+            &bad_polys[GATE_WIDTH] + &DensePolynomial::from_coefficients_vec(vec![E::Fr::one()]);
+        assert!(PlonkKzgSnark::<E>::prove_with_witness_polys::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            bad_polys,
+            None,
+        )
+        .is_err());
+        // an input wire polynomial inconsistent with the witness
+        let mut bad_polys = wire_polys;
+        bad_polys[0] = &bad_polys[0] + &DensePolynomial::from_coefficients_vec(vec![E::Fr::one()]);
+        assert!(PlonkKzgSnark::<E>::prove_with_witness_polys::<_, _, T>(
+            &mut test_rng(),
+            &circuit,
+            &pk,
+            bad_polys,
+            None,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_with_witness_commitments() -> Result<(), PlonkError> {
+        test_prove_with_witness_commitments_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_prove_with_witness_commitments_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_prove_with_witness_commitments_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_prove_with_witness_commitments_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let max_extra_openings = 3;
+        let srs = PlonkKzgSnark::<E>::universal_setup(
+            witness_openings_srs_size(
+                circuit.eval_domain_size()?,
+                circuit.num_wire_types(),
+                circuit.num_wire_types(),
+                max_extra_openings,
+            ),
+            rng,
+        )?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let pub_input = circuit.public_input()?;
+
+        // one extra opening
+        let (pk_1, vk_1) = PlonkKzgSnark::<E>::preprocess_with_witness_openings(&srs, &circuit, 1)?;
+        assert_eq!(vk, vk_1);
+        let (proof_with_comms, mut witness_comms) =
+            PlonkKzgSnark::<E>::prove_with_witness_commitments::<_, _, T>(
+                rng, &circuit, &pk_1, 1, None,
+            )?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &proof_with_comms, None).is_ok());
+        assert_eq!(
+            witness_comms.wire_comms(),
+            &proof_with_comms.wires_poly_comms[..]
+        );
+
+        // open the witness polynomials at a random point
+        let point = E::Fr::rand(rng);
+        let wire_comms = witness_comms.wire_comms().to_vec();
+        for (i, comm) in wire_comms.iter().enumerate() {
+            let (value, opening_proof) = witness_comms.open_witness(&point, i)?;
+            assert!(vk.verify_witness_opening(comm, &point, &value, &opening_proof)?);
+            assert!(!vk.verify_witness_opening(
+                comm,
+                &point,
+                &(value + E::Fr::one()),
+                &opening_proof
+            )?);
+            assert!(!vk.verify_witness_opening(
+                comm,
+                &(point + E::Fr::one()),
+                &value,
+                &opening_proof
+            )?);
+        }
+        // the same point can be opened again
+        let (value, opening_proof) = witness_comms.open_witness(&point, 0)?;
+        assert!(!vk.verify_witness_opening(&wire_comms[1], &point, &value, &opening_proof)?);
+
+        // at `zeta`, the openings are the ones of the proof
+        let zeta = witness_comms.zeta;
+        for (i, comm) in wire_comms.iter().enumerate() {
+            let (value, opening_proof) = witness_comms.open_witness(&zeta, i)?;
+            assert_eq!(value, proof_with_comms.poly_evals.wires_evals[i]);
+            assert!(vk.verify_witness_opening(comm, &zeta, &value, &opening_proof)?);
+        }
+
+        // bad path: a second point besides `zeta`
+        for i in 0..circuit.num_wire_types() {
+            assert!(witness_comms
+                .open_witness(&(point + E::Fr::one()), i)
+                .is_err());
+        }
+
+        // bad path: the points of the domain, which reveal the wire values
+        let domain = Radix2EvaluationDomain::<E::Fr>::new(circuit.eval_domain_size()?).unwrap();
+        for j in [0, 1, 5].iter() {
+            assert!(witness_comms.open_witness(&domain.element(*j), 0).is_err());
+        }
+
+        // bad path: a wire index out of range
+        assert!(witness_comms
+            .open_witness(&point, circuit.num_wire_types())
+            .is_err());
+
+        // without extra openings, the proof is the one of the normal path
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(&mut test_rng(), &circuit, &pk, None)?;
+        let (proof_with_comms, mut witness_comms) =
+            PlonkKzgSnark::<E>::prove_with_witness_commitments::<_, _, T>(
+                &mut test_rng(),
+                &circuit,
+                &pk,
+                0,
+                None,
+            )?;
+        assert_eq!(proof, proof_with_comms);
+        assert!(witness_comms.open_witness(&zeta, 0).is_ok());
+        assert!(witness_comms.open_witness(&point, 0).is_err());
+
+        // more extra openings need a larger proving key
+        let num_extra_openings = max_extra_openings;
+        assert!(
+            PlonkKzgSnark::<E>::prove_with_witness_commitments::<_, _, T>(
+                &mut test_rng(),
+                &circuit,
+                &pk_1,
+                num_extra_openings,
+                None,
+            )
+            .is_err()
+        );
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess_with_witness_openings(
+            &srs,
+            &circuit,
+            num_extra_openings,
+        )?;
+        let (proof_with_comms, mut witness_comms) =
+            PlonkKzgSnark::<E>::prove_with_witness_commitments::<_, _, T>(
+                rng,
+                &circuit,
+                &pk,
+                num_extra_openings,
+                None,
+            )?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &proof_with_comms, None).is_ok());
+        let points: Vec<_> = (0..num_extra_openings).map(|_| E::Fr::rand(rng)).collect();
+        for (i, comm) in witness_comms.wire_comms().to_vec().iter().enumerate() {
+            for point in points.iter() {
+                let (value, opening_proof) = witness_comms.open_witness(point, i)?;
+                assert!(vk.verify_witness_opening(comm, point, &value, &opening_proof)?);
+            }
+            assert!(witness_comms.open_witness(&points[0], i).is_ok());
+            assert!(witness_comms
+                .open_witness(&(points[0] + E::Fr::one()), i)
+                .is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_witness_then_finish_prove() -> Result<(), PlonkError> {
+        test_commit_witness_then_finish_prove_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_commit_witness_then_finish_prove_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_commit_witness_then_finish_prove_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_commit_witness_then_finish_prove_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let pub_input = circuit.public_input()?;
+
+        // committing then finishing consumes the randomness as the normal path
+        let prng = &mut test_rng();
+        let state = PlonkKzgSnark::<E>::commit_witness(prng, &circuit, &pk)?;
+        let wire_comms = state.wire_comms().to_vec();
+        let proof = PlonkKzgSnark::<E>::finish_prove::<_, _, T>(prng, state, None)?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &proof, None).is_ok());
+        assert_eq!(proof.wire_comms(), &wire_comms[..]);
+        assert_eq!(
+            proof,
+            PlonkKzgSnark::<E>::prove::<_, _, T>(&mut test_rng(), &circuit, &pk, None)?
+        );
+
+        // the commitments are bound to the proof: an independent commitment
+        // phase yields different ones
+        let other_state = PlonkKzgSnark::<E>::commit_witness(rng, &circuit, &pk)?;
+        assert_ne!(other_state.wire_comms(), &wire_comms[..]);
+
+        // bad path: a state whose commitments don't match its polynomials
+        let mut bad_state = PlonkKzgSnark::<E>::commit_witness(rng, &circuit, &pk)?;
+        bad_state.wire_comms = other_state.wire_comms().to_vec();
+        let bad_proof = PlonkKzgSnark::<E>::finish_prove::<_, _, T>(rng, bad_state, None)?;
+        assert_eq!(bad_proof.wire_comms(), other_state.wire_comms());
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &bad_proof, None).is_err());
+
+        // bad path: a proof finished with a different transcript initialization
+        let state = PlonkKzgSnark::<E>::commit_witness(rng, &circuit, &pk)?;
+        let proof = PlonkKzgSnark::<E>::finish_prove::<_, _, T>(rng, state, Some(vec![1]))?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &proof, None).is_err());
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &proof, Some(vec![1])).is_ok());
+
+        // bad path: mismatched domain sizes
+        let small_circuit = gen_circuit_for_test(1, 4, plonk_type)?;
+        if small_circuit.eval_domain_size()? != circuit.eval_domain_size()? {
+            assert!(PlonkKzgSnark::<E>::commit_witness(rng, &small_circuit, &pk).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_proof_size_bytes() -> Result<(), PlonkError> {
+        for &plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk].iter() {
+            test_proof_size_bytes_helper::<Bn254, Fq254, _, StandardTranscript>(plonk_type)?;
+            test_proof_size_bytes_helper::<Bls12_377, Fq377, _, StandardTranscript>(plonk_type)?;
+            test_proof_size_bytes_helper::<Bls12_381, Fq381, _, StandardTranscript>(plonk_type)?;
+            test_proof_size_bytes_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(plonk_type)?;
+        }
+        Ok(())
+    }
+
+    fn test_proof_size_bytes_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &circuit, &pk, None)?;
+        assert_eq!(vk.proof_size_bytes(), proof.serialized_size());
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes)?;
+        assert_eq!(vk.proof_size_bytes(), bytes.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampered_proofs() -> Result<(), PlonkError> {
+        for &plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk].iter() {
+            test_tampered_proofs_helper::<Bn254, Fq254, _, StandardTranscript>(plonk_type)?;
+            test_tampered_proofs_helper::<Bls12_377, Fq377, _, StandardTranscript>(plonk_type)?;
+            test_tampered_proofs_helper::<Bls12_381, Fq381, _, StandardTranscript>(plonk_type)?;
+            test_tampered_proofs_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(plonk_type)?;
+        }
+        Ok(())
+    }
+
+    fn test_tampered_proofs_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let pub_input = circuit.public_input()?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &circuit, &pk, None)?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &proof, None).is_ok());
+
+        // replacing a component by itself doesn't change the proof
+        let comm = proof.wire_comms()[0];
+        assert_eq!(proof.with_tampered_commitment(0, comm)?, proof);
+
+        // every tampered commitment is rejected
+        let num_comms = proof.num_commitments();
+        let generator = E::G1Affine::prime_subgroup_generator();
+        for idx in 0..num_comms {
+            let mut bad_proof = proof.with_tampered_commitment(idx, Commitment(generator))?;
+            if bad_proof == proof {
+                bad_proof = proof.with_tampered_commitment(idx, Commitment(generator.neg()))?;
+            }
+            assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &bad_proof, None).is_err());
+        }
+        assert!(proof
+            .with_tampered_commitment(num_comms, Commitment(generator))
+            .is_err());
+
+        // every tampered evaluation is rejected
+        let num_evals = proof.num_evaluations();
+        for idx in 0..num_evals {
+            let bad_proof = proof.with_tampered_evaluation(idx, E::Fr::rand(rng))?;
+            assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &pub_input, &bad_proof, None).is_err());
+        }
+        assert!(proof
+            .with_tampered_evaluation(num_evals, E::Fr::zero())
+            .is_err());
+
+        Ok(())
     }
-}
 
-#[cfg(test)]
-pub mod test {
-    use crate::{
-        circuit::{customized::ecc::SWToTEConParam, Arithmetization, Circuit, PlonkCircuit},
-        constants::GATE_WIDTH,
-        errors::PlonkError,
-        proof_system::{
-            structs::{
-                eval_merged_lookup_witness, eval_merged_table, Challenges, Oracles, Proof,
-                ProvingKey, UniversalSrs, VerifyingKey,
+    #[test]
+    fn test_commit_public_inputs() -> Result<(), PlonkError> {
+        for &plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk].iter() {
+            test_commit_public_inputs_helper::<Bn254, Fq254, _, StandardTranscript>(plonk_type)?;
+            test_commit_public_inputs_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+                plonk_type,
+            )?;
+            test_commit_public_inputs_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+                plonk_type,
+            )?;
+            test_commit_public_inputs_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+                plonk_type,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn test_commit_public_inputs_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let cs_a = gen_mergeable_circuit(6, 6, MergeableCircuitType::TypeA)?;
+        let cs_b = gen_mergeable_circuit(6, 6, MergeableCircuitType::TypeB)?;
+        let max_degree = ark_std::cmp::max(circuit.srs_size()?, cs_a.srs_size()?);
+        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        check_commit_public_inputs::<_, _, _, T>(rng, &srs, &circuit, &pk, &vk)?;
+
+        // merged circuits lay out their public inputs from both ends of the
+        // domain
+        if plonk_type == PlonkType::TurboPlonk {
+            let merged_circuit = cs_a.merge_mergeable_circuits(&cs_b)?;
+            let (pk_a, vk_a) = PlonkKzgSnark::<E>::preprocess(&srs, &cs_a)?;
+            let (pk_b, vk_b) = PlonkKzgSnark::<E>::preprocess(&srs, &cs_b)?;
+            check_commit_public_inputs::<_, _, _, T>(
+                rng,
+                &srs,
+                &merged_circuit,
+                &pk_a.merge(&pk_b)?,
+                &vk_a.merge(&vk_b)?,
+            )?;
+        }
+
+        // bad path: wrong number of public inputs
+        let pub_input = circuit.public_input()?;
+        assert!(pk.commit_public_inputs(&pub_input[1..]).is_err());
+        let precompute = VerifierPrecompute::new_with_public_input_commitments(&vk, &srs)?;
+        assert!(precompute.commit_public_inputs(&pub_input[1..]).is_err());
+        assert!(vk
+            .evaluate_public_input_poly(&pub_input[1..], &E::Fr::one())
+            .is_err());
+        // bad path: the SRS of another key
+        let other_srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
+        assert!(VerifierPrecompute::new_with_public_input_commitments(&vk, &other_srs).is_err());
+
+        Ok(())
+    }
+
+    fn check_commit_public_inputs<E, F, P, T>(
+        rng: &mut (impl CryptoRng + RngCore),
+        srs: &UniversalSrs<E>,
+        circuit: &PlonkCircuit<E::Fr>,
+        pk: &ProvingKey<E>,
+        vk: &VerifyingKey<E>,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let pub_input = circuit.public_input()?;
+        let (_, oracles, challenges, _) = PlonkKzgSnark::<E>::batch_prove_internal::<_, _, T>(
+            rng,
+            &[circuit],
+            &[pk],
+            None,
+            None,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+
+        // the commitment is to the public input polynomial of the prover
+        let pi_poly = &oracles[0].pub_inp_poly;
+        let (expected_comm, _) = KZG10::commit(&pk.commit_key, pi_poly, None, None)?;
+        assert_eq!(pk.commit_public_inputs(&pub_input)?, expected_comm);
+        // the verifier computes it from the precomputed commitments to the
+        // Lagrange bases, which are opt-in
+        let precompute = VerifierPrecompute::new_with_public_input_commitments(vk, srs)?;
+        assert_eq!(precompute.commit_public_inputs(&pub_input)?, expected_comm);
+        assert!(VerifierPrecompute::new(vk)?
+            .commit_public_inputs(&pub_input)
+            .is_err());
+
+        // the evaluation at `zeta` is the one of the verifier, and matches the
+        // committed polynomial anywhere
+        let zeta = challenges.zeta;
+        let verifier = Verifier::<KzgPcs<E>>::new(vk.domain_size)?;
+        let vanish_eval = verifier.domain.evaluate_vanishing_polynomial(zeta);
+        assert_eq!(
+            vk.evaluate_public_input_poly(&pub_input, &zeta)?,
+            verifier.evaluate_pi_poly(&pub_input, &zeta, &vanish_eval, vk.is_merged)?
+        );
+        assert_eq!(
+            vk.evaluate_public_input_poly(&pub_input, &zeta)?,
+            pi_poly.evaluate(&zeta)
+        );
+        let point = E::Fr::rand(rng);
+        assert_eq!(
+            vk.evaluate_public_input_poly(&pub_input, &point)?,
+            pi_poly.evaluate(&point)
+        );
+
+        // other public inputs have another commitment
+        let mut other_pub_input = pub_input.clone();
+        other_pub_input[0] += E::Fr::one();
+        assert_ne!(pk.commit_public_inputs(&other_pub_input)?, expected_comm);
+        assert_eq!(
+            precompute.commit_public_inputs(&other_pub_input)?,
+            pk.commit_public_inputs(&other_pub_input)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_proof() -> Result<(), PlonkError> {
+        for &plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk].iter() {
+            test_compressed_proof_helper::<Bn254, Fq254, _, StandardTranscript>(plonk_type)?;
+            test_compressed_proof_helper::<Bls12_377, Fq377, _, StandardTranscript>(plonk_type)?;
+            test_compressed_proof_helper::<Bls12_381, Fq381, _, StandardTranscript>(plonk_type)?;
+            test_compressed_proof_helper::<BW6_761, Fq761, _, StandardTranscript>(plonk_type)?;
+            test_compressed_proof_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(plonk_type)?;
+        }
+        Ok(())
+    }
+
+    fn test_compressed_proof_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let pub_input = circuit.public_input()?;
+        let extra_msg = Some(vec![1u8, 2, 3]);
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &circuit, &pk, extra_msg.clone())?;
+
+        // the compressed proof decompresses to the original proof, and verifies
+        let compressed_proof = proof.compress(&pk)?;
+        let decompressed = compressed_proof.decompress::<T>(&vk, &pub_input, extra_msg.clone())?;
+        assert_eq!(decompressed, proof);
+        assert!(PlonkKzgSnark::<E>::verify_compressed::<T>(
+            &vk,
+            &pub_input,
+            &compressed_proof,
+            extra_msg.clone()
+        )
+        .is_ok());
+
+        // the compressed proof is smaller iff it has lookup tables, and survives
+        // serialization
+        match plonk_type {
+            PlonkType::TurboPlonk => {
+                assert_eq!(compressed_proof.serialized_size(), proof.serialized_size())
             },
-            PlonkKzgSnark, Snark,
-        },
-        transcript::{
-            rescue::RescueTranscript, solidity::SolidityTranscript, standard::StandardTranscript,
-            PlonkTranscript,
-        },
-        MergeableCircuitType, PlonkType,
-    };
-    use ark_bls12_377::{Bls12_377, Fq as Fq377};
-    use ark_bls12_381::{Bls12_381, Fq as Fq381};
-    use ark_bn254::{Bn254, Fq as Fq254};
-    use ark_bw6_761::{Fq as Fq761, BW6_761};
-    use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
-    use ark_ff::{One, PrimeField, Zero};
-    use ark_poly::{
-        univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain,
-        UVPolynomial,
-    };
-    use ark_poly_commit::kzg10::{Commitment, KZG10};
-    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-    use ark_std::{
-        convert::TryInto,
-        format,
-        rand::{CryptoRng, RngCore},
-        string::ToString,
-        test_rng, vec,
-        vec::Vec,
-    };
-    use core::ops::{Mul, Neg};
-    use jf_rescue::RescueParameter;
+            PlonkType::UltraPlonk => {
+                // four table evaluations replaced by two table sizes
+                let scalar_size = E::Fr::zero().serialized_size();
+                assert_eq!(
+                    compressed_proof.serialized_size() + 4 * scalar_size,
+                    proof.serialized_size() + 2 * 0u64.serialized_size()
+                )
+            },
+        }
+        let mut bytes = Vec::new();
+        compressed_proof.serialize(&mut bytes)?;
+        assert_eq!(CompressedProof::deserialize(&bytes[..])?, compressed_proof);
+
+        // bad path: wrong public input or transcript message
+        let mut wrong_pub_input = pub_input.clone();
+        wrong_pub_input[0] += E::Fr::one();
+        assert!(PlonkKzgSnark::<E>::verify_compressed::<T>(
+            &vk,
+            &wrong_pub_input,
+            &compressed_proof,
+            extra_msg.clone()
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::verify_compressed::<T>(
+            &vk,
+            &pub_input,
+            &compressed_proof,
+            None
+        )
+        .is_err());
 
-    // Different `m`s lead to different circuits.
-    // Different `a0`s lead to different witness values.
-    // For UltraPlonk circuits, `a0` should be less than or equal to `m+1`
-    pub(crate) fn gen_circuit_for_test<F: PrimeField>(
-        m: usize,
-        a0: usize,
+        if plonk_type == PlonkType::UltraPlonk {
+            // bad path: wrong table sizes
+            let mut bad_proof = compressed_proof.clone();
+            bad_proof.plookup_proof.as_mut().unwrap().range_table_len += 1;
+            assert!(PlonkKzgSnark::<E>::verify_compressed::<T>(
+                &vk,
+                &pub_input,
+                &bad_proof,
+                extra_msg.clone()
+            )
+            .is_err());
+            let mut bad_proof = compressed_proof.clone();
+            bad_proof.plookup_proof.as_mut().unwrap().key_table_len = vk.domain_size as u64;
+            assert!(bad_proof
+                .decompress::<T>(&vk, &pub_input, extra_msg.clone())
+                .is_err());
+
+            // bad path: a proving key without lookup support
+            let turbo_circuit = gen_circuit_for_test(3, 4, PlonkType::TurboPlonk)?;
+            let (turbo_pk, _) = PlonkKzgSnark::<E>::preprocess(&srs, &turbo_circuit)?;
+            assert!(proof.compress(&turbo_pk).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expected_poly_degrees() -> Result<(), PlonkError> {
+        test_expected_poly_degrees_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_expected_poly_degrees_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )
+    }
+
+    fn test_expected_poly_degrees_helper<E, F, P, T>(
         plonk_type: PlonkType,
-    ) -> Result<PlonkCircuit<F>, PlonkError> {
-        let range_bit_len = 5;
-        let mut cs: PlonkCircuit<F> = match plonk_type {
-            PlonkType::TurboPlonk => PlonkCircuit::new_turbo_plonk(),
-            PlonkType::UltraPlonk => PlonkCircuit::new_ultra_plonk(range_bit_len),
-        };
-        // Create variables
-        let mut a = vec![];
-        for i in a0..(a0 + 4 * m) {
-            a.push(cs.create_variable(F::from(i as u64))?);
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let degrees = vk.expected_poly_degrees();
+        let n = vk.circuit_size();
+        assert_eq!(degrees.wire, n + 1);
+        assert_eq!(degrees.split_quot.len(), circuit.num_wire_types());
+
+        // the polynomials computed by the prover have the expected degrees
+        let (batch_proof, oracles, ..) = PlonkKzgSnark::<E>::batch_prove_internal::<_, _, T>(
+            rng,
+            &[&circuit],
+            &[&pk],
+            None,
+            None,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
+        assert_eq!(
+            batch_proof.split_quot_poly_comms.len(),
+            degrees.split_quot.len()
+        );
+        for poly in oracles[0].wire_polys.iter() {
+            assert_eq!(poly.degree(), degrees.wire);
         }
-        let b = vec![
-            cs.create_public_variable(F::from(m as u64 * 2))?,
-            cs.create_public_variable(F::from(a0 as u64 * 2 + m as u64 * 4 - 1))?,
+        assert_eq!(oracles[0].prod_perm_poly.degree(), degrees.prod_perm);
+        for poly in pk.selectors.iter() {
+            assert!(poly.degree() <= degrees.selector);
+        }
+        for poly in pk.sigmas.iter() {
+            assert!(poly.degree() <= degrees.sigma);
+        }
+        match degrees.plookup {
+            Some(plookup_degrees) => {
+                assert_eq!(plonk_type, PlonkType::UltraPlonk);
+                let plookup_oracles = &oracles[0].plookup_oracles;
+                for poly in plookup_oracles.h_polys.iter() {
+                    assert_eq!(poly.degree(), plookup_degrees.h);
+                }
+                assert_eq!(
+                    plookup_oracles.prod_lookup_poly.degree(),
+                    plookup_degrees.prod_lookup
+                );
+                let plookup_pk = pk.plookup_pk.as_ref().unwrap();
+                assert!(plookup_pk.range_table_poly.degree() <= plookup_degrees.table);
+                assert!(plookup_pk.key_table_poly.degree() <= plookup_degrees.table);
+            },
+            None => assert_eq!(plonk_type, PlonkType::TurboPlonk),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_accumulator_transition() -> Result<(), PlonkError> {
+        test_accumulator_transition_helper::<Bn254, Fq254, _, StandardTranscript>()?;
+        test_accumulator_transition_helper::<Bls12_377, Fq377, _, StandardTranscript>()?;
+        test_accumulator_transition_helper::<Bls12_381, Fq381, _, StandardTranscript>()?;
+        test_accumulator_transition_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()
+    }
+
+    fn test_accumulator_transition_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        E::Fr: RescueParameter,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        fn gen_transition_circuit<F: RescueParameter>(
+            prev_acc: F,
+            inputs: &[F],
+        ) -> Result<PlonkCircuit<F>, PlonkError> {
+            let mut cs: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let prev_acc_var = cs.create_public_variable(prev_acc)?;
+            let input_vars = inputs
+                .iter()
+                .map(|&x| cs.create_variable(x))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let next_acc_var = cs.rolling_accumulate(prev_acc_var, &input_vars)?;
+            cs.set_variable_public(next_acc_var)?;
+            cs.finalize_for_arithmetization()?;
+            Ok(cs)
+        }
+
+        let rng = &mut test_rng();
+        // chain three proofs through the accumulator
+        let mut accs = vec![E::Fr::zero()];
+        let mut circuits = vec![];
+        for i in 0..3u64 {
+            let inputs = vec![E::Fr::from(i), E::Fr::from(i + 10), E::Fr::from(i + 20)];
+            let prev_acc = accs[accs.len() - 1];
+            accs.push(rolling_accumulate(prev_acc, &inputs));
+            circuits.push(gen_transition_circuit(prev_acc, &inputs)?);
+        }
+
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuits[0].srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuits[0])?;
+        let proofs = circuits
+            .iter()
+            .map(|cs| PlonkKzgSnark::<E>::prove::<_, _, T>(rng, cs, &pk, None))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        for (i, proof) in proofs.iter().enumerate() {
+            assert!(PlonkKzgSnark::<E>::verify_accumulator_transition::<T>(
+                &vk,
+                accs[i],
+                accs[i + 1],
+                proof,
+                None
+            )
+            .is_ok());
+        }
+
+        // a broken link in the chain is rejected
+        assert!(PlonkKzgSnark::<E>::verify_accumulator_transition::<T>(
+            &vk, accs[0], accs[2], &proofs[1], None
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::verify_accumulator_transition::<T>(
+            &vk, accs[1], accs[3], &proofs[1], None
+        )
+        .is_err());
+        let forged_acc = accs[2] + E::Fr::one();
+        assert!(PlonkKzgSnark::<E>::verify_accumulator_transition::<T>(
+            &vk, accs[1], forged_acc, &proofs[1], None
+        )
+        .is_err());
+
+        // the verification key must describe an accumulator transition
+        let cs = gen_circuit_for_test::<E::Fr>(2, 3, PlonkType::TurboPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (_, bad_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        assert!(PlonkKzgSnark::<E>::verify_accumulator_transition::<T>(
+            &bad_vk, accs[0], accs[1], &proofs[0], None
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn test_prove_chrome_trace() -> Result<(), PlonkError> {
+        use crate::profiling::record_trace;
+
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, PlonkType::UltraPlonk)?;
+        let srs = PlonkKzgSnark::<Bls12_381>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, _) = PlonkKzgSnark::<Bls12_381>::preprocess(&srs, &circuit)?;
+        let (proof, trace) = record_trace(|| {
+            PlonkKzgSnark::<Bls12_381>::prove::<_, _, StandardTranscript>(rng, &circuit, &pk, None)
+        });
+        proof?;
+
+        let json: serde_json::Value = serde_json::from_str(&trace.to_json()).unwrap();
+        let events = json["traceEvents"].as_array().unwrap();
+        let expected_phases = [
+            "prove",
+            "round 1",
+            "round 1.5",
+            "round 2",
+            "round 2.5",
+            "round 3",
+            "round 4",
+            "round 5",
         ];
-        let c = cs.create_public_variable(
-            (cs.witness(b[1])? + cs.witness(a[0])?) * (cs.witness(b[1])? - cs.witness(a[0])?),
+        assert_eq!(events.len(), 2 * expected_phases.len());
+        for phase in expected_phases.iter() {
+            let phase_events: Vec<_> = events.iter().filter(|e| e["name"] == *phase).collect();
+            assert_eq!(phase_events.len(), 2);
+            assert_eq!(phase_events[0]["ph"], "B");
+            assert_eq!(phase_events[1]["ph"], "E");
+            assert!(phase_events[0]["ts"].as_u64() <= phase_events[1]["ts"].as_u64());
+            assert_eq!(phase_events[0]["tid"], phase_events[1]["tid"]);
+        }
+        assert_eq!(events[0]["name"], "prove");
+        assert_eq!(events[events.len() - 1]["name"], "prove");
+
+        // the phases run on a custom pool are recorded on the calling thread
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let (proof, pool_trace) = record_trace(|| {
+            PlonkKzgSnark::<Bls12_381>::prove_in_pool::<_, _, StandardTranscript>(
+                &pool, rng, &circuit, &pk, None,
+            )
+        });
+        proof?;
+        assert_eq!(pool_trace.events.len(), trace.events.len());
+        for (event, expected) in pool_trace.events.iter().zip(trace.events.iter()) {
+            assert_eq!((event.name, event.phase), (expected.name, expected.phase));
+        }
+
+        // nothing is recorded outside of `record_trace`
+        let (_, trace) = record_trace(|| ());
+        assert!(trace.events.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_prove_with_profile() -> Result<(), PlonkError> {
+        test_prove_with_profile_helper::<Bn254, Fq254, _>()?;
+        test_prove_with_profile_helper::<Bls12_377, Fq377, _>()
+    }
+
+    #[cfg(feature = "std")]
+    fn test_prove_with_profile_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        // Prove a circuit of `m` sized blocks and return the profile.
+        fn prove<E, F, P>(m: usize) -> Result<(bool, ProveProfile), PlonkError>
+        where
+            E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+            F: RescueParameter + SWToTEConParam,
+            P: SWModelParameters<BaseField = F> + Clone,
+        {
+            let rng = &mut test_rng();
+            let cs = gen_circuit_for_test::<E::Fr>(m, 4, PlonkType::TurboPlonk)?;
+            let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+            let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+            let (proof, profile) =
+                PlonkKzgSnark::<E>::prove_with_profile::<_, _, StandardTranscript>(
+                    rng, &cs, &pk, None,
+                )?;
+            let valid = PlonkKzgSnark::<E>::verify::<StandardTranscript>(
+                &vk,
+                &cs.public_input()?,
+                &proof,
+                None,
+            )
+            .is_ok();
+            Ok((valid, profile))
+        }
+        let total =
+            |profile: &ProveProfile| profile.fft_time + profile.msm_time + profile.poly_eval_time;
+
+        // the proof is the same as without profiling
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::UltraPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, _) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let (proof, profile) = PlonkKzgSnark::<E>::prove_with_profile::<_, _, StandardTranscript>(
+            &mut test_rng(),
+            &cs,
+            &pk,
+            None,
         )?;
+        let expected_proof =
+            PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(&mut test_rng(), &cs, &pk, None)?;
+        assert_eq!(proof, expected_proof);
+        assert!(profile.fft_time > Duration::ZERO);
+        assert!(profile.msm_time > Duration::ZERO);
+        assert!(profile.poly_eval_time > Duration::ZERO);
+
+        // two concurrent proofs of a small and a large circuit: the profile of
+        // the small one does not include the work of the large one
+        let small = thread::spawn(|| prove::<E, F, P>(2).unwrap());
+        let large = thread::spawn(|| prove::<E, F, P>(1024).unwrap());
+        let (small_valid, small_profile) = small.join().unwrap();
+        let (large_valid, large_profile) = large.join().unwrap();
+        assert!(small_valid && large_valid);
+        assert!(total(&small_profile) > Duration::ZERO);
+        assert!(total(&small_profile) < total(&large_profile));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_bounded() -> Result<(), PlonkError> {
+        test_verify_bounded_helper::<Bn254, Fq254, _, StandardTranscript>()?;
+        test_verify_bounded_helper::<Bls12_377, Fq377, _, StandardTranscript>()?;
+        test_verify_bounded_helper::<Bls12_381, Fq381, _, StandardTranscript>()?;
+        test_verify_bounded_helper::<BW6_761, Fq761, _, StandardTranscript>()?;
+        test_verify_bounded_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()
+    }
+
+    fn test_verify_bounded_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        let public_input = cs.public_input()?;
+        let num_inputs = public_input.len();
+
+        assert!(PlonkKzgSnark::<E>::verify_bounded::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            num_inputs,
+            None
+        )
+        .is_ok());
+        assert!(PlonkKzgSnark::<E>::verify_bounded::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            num_inputs + 1,
+            None
+        )
+        .is_ok());
+        // within the budget, a wrong input is still rejected by the full verification
+        let mut wrong_input = public_input.clone();
+        wrong_input[0] += E::Fr::one();
+        assert!(matches!(
+            PlonkKzgSnark::<E>::verify_bounded::<T>(&vk, &wrong_input, &proof, num_inputs, None),
+            Err(PlonkError::WrongProof)
+        ));
+
+        // over-budget inputs are rejected before running the verification, even
+        // though the proof is valid
+        assert!(matches!(
+            PlonkKzgSnark::<E>::verify_bounded::<T>(
+                &vk,
+                &public_input,
+                &proof,
+                num_inputs - 1,
+                None
+            ),
+            Err(PlonkError::SnarkError(SnarkError::TooManyPublicInputs(n, max)))
+                if n == num_inputs && max == num_inputs - 1
+        ));
+        // a maliciously long public input is rejected before its length is
+        // checked against the verification key
+        let long_input = vec![E::Fr::zero(); 1 << 16];
+        assert!(matches!(
+            PlonkKzgSnark::<E>::verify_bounded::<T>(&vk, &long_input, &proof, 1 << 10, None),
+            Err(PlonkError::SnarkError(SnarkError::TooManyPublicInputs(..)))
+        ));
+        // so is a verification key expecting more inputs than the budget
+        assert!(matches!(
+            PlonkKzgSnark::<E>::verify_bounded::<T>(&vk, &[], &proof, 0, None),
+            Err(PlonkError::SnarkError(SnarkError::TooManyPublicInputs(n, 0))) if n == num_inputs
+        ));
 
-        // Create gates:
-        // 1. a0 + ... + a_{4*m-1} = b0 * b1
-        // 2. (b1 + a0) * (b1 - a0) = c
-        // 3. b0 = 2 * m
-        let mut acc = cs.zero();
-        a.iter().for_each(|&elem| acc = cs.add(acc, elem).unwrap());
-        let b_mul = cs.mul(b[0], b[1])?;
-        cs.equal_gate(acc, b_mul)?;
-        let b1_plus_a0 = cs.add(b[1], a[0])?;
-        let b1_minus_a0 = cs.sub(b[1], a[0])?;
-        cs.mul_gate(b1_plus_a0, b1_minus_a0, c)?;
-        cs.constant_gate(b[0], F::from(m as u64 * 2))?;
+        Ok(())
+    }
 
-        if plonk_type == PlonkType::UltraPlonk {
-            // Create range gates
-            // 1. range_table = {0, 1, ..., 31}
-            // 2. a_i \in range_table for i = 0..m-1
-            // 3. b0 \in range_table
-            for &var in a.iter().take(m) {
-                cs.add_range_check_variable(var)?;
-            }
-            cs.add_range_check_variable(b[0])?;
+    #[test]
+    fn test_verify_with_input_mask() -> Result<(), PlonkError> {
+        test_verify_with_input_mask_helper::<Bn254, Fq254, _, StandardTranscript>()?;
+        test_verify_with_input_mask_helper::<Bls12_377, Fq377, _, StandardTranscript>()?;
+        test_verify_with_input_mask_helper::<Bls12_381, Fq381, _, StandardTranscript>()?;
+        test_verify_with_input_mask_helper::<BW6_761, Fq761, _, StandardTranscript>()?;
+        test_verify_with_input_mask_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()
+    }
 
-            // Create variable table lookup gates
-            // 1. table = [(a0, a2), (a1, a3), (b0, a0)]
-            let table_vars = [(a[0], a[2]), (a[1], a[3]), (b[0], a[0])];
-            // 2. lookup_witness = [(1, a0+1, a0+3), (2, 2m, a0)]
-            let key0 = cs.one();
-            let key1 = cs.create_variable(F::from(2u8))?;
-            let two_m = cs.create_public_variable(F::from(m as u64 * 2))?;
-            let a1 = cs.add_constant(a[0], &F::one())?;
-            let a3 = cs.add_constant(a[0], &F::from(3u8))?;
-            let lookup_vars = [(key0, a1, a3), (key1, two_m, a[0])];
-            cs.create_table_and_lookup_variables(&lookup_vars, &table_vars)?;
-        }
+    fn test_verify_with_input_mask_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        let public_input = cs.public_input()?;
+
+        // the first input is checked, the others are left to the prover
+        let mask = vec![true, false, false];
+        let unmasked_input = public_input[1..].to_vec();
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &public_input,
+            &mask,
+            &proof,
+            &unmasked_input,
+            None
+        )
+        .is_ok());
+        // the verifier need not know the unmasked inputs
+        let mut unknown_input = public_input.clone();
+        unknown_input[1] = E::Fr::zero();
+        unknown_input[2] = E::Fr::zero();
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &unknown_input,
+            &mask,
+            &proof,
+            &unmasked_input,
+            None
+        )
+        .is_ok());
+        // but an unmasked input it gives must agree with the prover
+        let mut flipped_input = public_input.clone();
+        flipped_input[2] += E::Fr::one();
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &flipped_input,
+            &mask,
+            &proof,
+            &unmasked_input,
+            None
+        )
+        .is_err());
+        // flipping a masked input does
+        let mut flipped_input = public_input.clone();
+        flipped_input[0] += E::Fr::one();
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &flipped_input,
+            &mask,
+            &proof,
+            &unmasked_input,
+            None
+        )
+        .is_err());
+        // the values claimed by the prover are bound to the proof
+        let mut flipped_unmasked_input = unmasked_input.clone();
+        flipped_unmasked_input[1] += E::Fr::one();
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &public_input,
+            &mask,
+            &proof,
+            &flipped_unmasked_input,
+            None
+        )
+        .is_err());
+        // a full mask is the plain verification
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &public_input,
+            &[true; 3],
+            &proof,
+            &[],
+            None
+        )
+        .is_ok());
 
-        // Finalize the circuit.
-        cs.finalize_for_arithmetization()?;
+        // wrong lengths
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &public_input,
+            &mask[..2],
+            &proof,
+            &unmasked_input[..1],
+            None
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::verify_with_input_mask::<T>(
+            &vk,
+            &public_input,
+            &mask,
+            &proof,
+            &unmasked_input[..1],
+            None
+        )
+        .is_err());
 
-        Ok(cs)
+        Ok(())
     }
 
     #[test]
-    fn test_preprocessing() -> Result<(), PlonkError> {
-        test_preprocessing_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
-        test_preprocessing_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
-        test_preprocessing_helper::<Bls12_377, Fq377, _>(PlonkType::TurboPlonk)?;
-        test_preprocessing_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)?;
-        test_preprocessing_helper::<Bls12_381, Fq381, _>(PlonkType::TurboPlonk)?;
-        test_preprocessing_helper::<Bls12_381, Fq381, _>(PlonkType::UltraPlonk)?;
-        test_preprocessing_helper::<BW6_761, Fq761, _>(PlonkType::TurboPlonk)?;
-        test_preprocessing_helper::<BW6_761, Fq761, _>(PlonkType::UltraPlonk)
+    fn test_dedup_constants() -> Result<(), PlonkError> {
+        test_dedup_constants_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_dedup_constants_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::UltraPlonk)?;
+        test_dedup_constants_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )
     }
-    fn test_preprocessing_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+
+    fn test_dedup_constants_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
     where
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         F: RescueParameter + SWToTEConParam,
         P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
     {
-        let rng = &mut ark_std::test_rng();
-        let circuit = gen_circuit_for_test(5, 6, plonk_type)?;
-        let domain_size = circuit.eval_domain_size()?;
-        let num_inputs = circuit.num_inputs();
-        let selectors = circuit.compute_selector_polynomials()?;
-        let sigmas = circuit.compute_extended_permutation_polynomials()?;
+        let rng = &mut test_rng();
+        let build = |dedup: bool| -> Result<PlonkCircuit<E::Fr>, PlonkError> {
+            let mut cs: PlonkCircuit<E::Fr> = match plonk_type {
+                PlonkType::TurboPlonk => PlonkCircuit::new_turbo_plonk(),
+                PlonkType::UltraPlonk => PlonkCircuit::new_ultra_plonk(4),
+            };
+            if dedup {
+                cs.dedup_constants();
+            }
+            let mut acc = cs.create_public_variable(E::Fr::from(3u32))?;
+            for i in 0..64u32 {
+                let constant = cs.create_constant_variable(E::Fr::from(i % 4))?;
+                acc = cs.mul_add(&[acc, cs.one(), constant, cs.one()], &[E::Fr::one(); 2])?;
+            }
+            cs.finalize_for_arithmetization()?;
+            Ok(cs)
+        };
+        let cs = build(false)?;
+        let dedup_cs = build(true)?;
+        assert!(dedup_cs.num_vars() < cs.num_vars());
+        assert!(dedup_cs.num_gates() < cs.num_gates());
+
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &dedup_cs)?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &dedup_cs, &pk, None)?;
+        let public_input = dedup_cs.public_input()?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_ok());
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &[E::Fr::from(4u32)], &proof, None).is_err());
 
-        let max_degree = 64 + 2;
-        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
-        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        Ok(())
+    }
 
-        // check proving key
-        assert_eq!(pk.selectors, selectors);
-        assert_eq!(pk.sigmas, sigmas);
-        assert_eq!(pk.domain_size(), domain_size);
-        assert_eq!(pk.num_inputs(), num_inputs);
-        let num_wire_types = GATE_WIDTH
-            + 1
-            + match plonk_type {
-                PlonkType::TurboPlonk => 0,
-                PlonkType::UltraPlonk => 1,
-            };
-        assert_eq!(pk.sigmas.len(), num_wire_types);
-        // check plookup proving key
-        if plonk_type == PlonkType::UltraPlonk {
-            let range_table_poly = circuit.compute_range_table_polynomial()?;
-            assert_eq!(
-                pk.plookup_pk.as_ref().unwrap().range_table_poly,
-                range_table_poly
-            );
+    #[test]
+    fn test_custom_gate() -> Result<(), PlonkError> {
+        test_custom_gate_helper::<Bn254, Fq254, _, StandardTranscript>()?;
+        test_custom_gate_helper::<Bn254, Fq254, _, SolidityTranscript>()?;
+        test_custom_gate_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()?;
+        test_custom_gate_helper::<Bls12_381, Fq381, _, StandardTranscript>()
+    }
 
-            let key_table_poly = circuit.compute_key_table_polynomial()?;
-            assert_eq!(
-                pk.plookup_pk.as_ref().unwrap().key_table_poly,
-                key_table_poly
-            );
-        }
+    fn test_custom_gate_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let one = E::Fr::one();
+        let build = |x_val: E::Fr| -> Result<PlonkCircuit<E::Fr>, PlonkError> {
+            let mut cs: PlonkCircuit<E::Fr> = PlonkCircuit::new_turbo_plonk();
+            // w_0^4 - w_4 = 0
+            let pow4 = cs.register_custom_gate(CustomGateSpec::new(&[
+                (one, [4, 0, 0, 0, 0]),
+                (-one, [0, 0, 0, 0, 1]),
+            ])?)?;
+            // w_0 * w_1 + 2 * w_2^5 - w_4 = 0
+            let mixed = cs.register_custom_gate(CustomGateSpec::new(&[
+                (one, [1, 1, 0, 0, 0]),
+                (E::Fr::from(2u32), [0, 0, 5, 0, 0]),
+                (-one, [0, 0, 0, 0, 1]),
+            ])?)?;
+            let zero = cs.zero();
+            let x = cs.create_variable(x_val)?;
+            let y = cs.create_public_variable(E::Fr::from(81u32))?;
+            cs.custom_gate(pow4, &[x, zero, zero, zero, y])?;
+            let x_plus_one = cs.add_constant(x, &one)?;
+            let z = cs.create_variable(E::Fr::from(12u32 + 2 * 243))?;
+            cs.custom_gate(mixed, &[x, x_plus_one, x, zero, z])?;
+            cs.finalize_for_arithmetization()?;
+            Ok(cs)
+        };
+        let cs = build(E::Fr::from(3u32))?;
+        assert!(cs
+            .check_circuit_satisfiability(&[E::Fr::from(81u32)])
+            .is_ok());
 
-        // check verifying key
-        assert_eq!(vk.domain_size, domain_size);
-        assert_eq!(vk.num_inputs, num_inputs);
-        assert_eq!(vk.selector_comms.len(), selectors.len());
-        assert_eq!(vk.sigma_comms.len(), sigmas.len());
-        assert_eq!(vk.sigma_comms.len(), num_wire_types);
-        selectors
-            .iter()
-            .zip(vk.selector_comms.iter())
-            .for_each(|(p, &p_comm)| {
-                let (expected_comm, _) = KZG10::commit(&pk.commit_key, p, None, None).unwrap();
-                assert_eq!(expected_comm, p_comm);
-            });
-        sigmas
-            .iter()
-            .zip(vk.sigma_comms.iter())
-            .for_each(|(p, &p_comm)| {
-                let (expected_comm, _) = KZG10::commit(&pk.commit_key, p, None, None).unwrap();
-                assert_eq!(expected_comm, p_comm);
-            });
-        // check plookup verification key
-        if plonk_type == PlonkType::UltraPlonk {
-            let (expected_comm, _) = KZG10::commit(
-                &pk.commit_key,
-                &pk.plookup_pk.as_ref().unwrap().range_table_poly,
-                None,
-                None,
-            )
-            .unwrap();
-            assert_eq!(
-                expected_comm,
-                vk.plookup_vk.as_ref().unwrap().range_table_comm
-            );
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        // the verifying key commits to the 2 custom selectors
+        assert_eq!(vk.selector_comms.len(), N_TURBO_PLONK_SELECTORS + 2);
+        assert_eq!(vk.custom_gates, cs.custom_gates().to_vec());
+        let mut vk_bytes = vec![];
+        vk.serialize(&mut vk_bytes)?;
+        assert_eq!(vk, VerifyingKey::deserialize(vk_bytes.as_slice())?);
+
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        let public_input = cs.public_input()?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_ok());
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &[E::Fr::from(80u32)], &proof, None).is_err());
+
+        // a witness violating the custom gates
+        let bad_cs = build(E::Fr::from(2u32))?;
+        assert!(bad_cs
+            .check_circuit_satisfiability(&[E::Fr::from(81u32)])
+            .is_err());
+        assert!(PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &bad_cs, &pk, None).is_err());
+
+        // a tampered custom gate in the verifying key
+        let mut bad_vk = vk.clone();
+        bad_vk.custom_gates[0].coeffs[0] = E::Fr::from(2u32);
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&bad_vk, &public_input, &proof, None).is_err());
+        // a verifying key dropping the custom gates
+        let mut bad_vk = vk.clone();
+        bad_vk.custom_gates.clear();
+        bad_vk.selector_comms.truncate(N_TURBO_PLONK_SELECTORS);
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&bad_vk, &public_input, &proof, None).is_err());
+
+        // keys with custom gates cannot be merged nor verified in circuits yet
+        assert!(vk.merge(&vk).is_err());
+        assert!(vk.verifier_circuit_ir().is_err());
 
-            let (expected_comm, _) = KZG10::commit(
-                &pk.commit_key,
-                &pk.plookup_pk.as_ref().unwrap().key_table_poly,
-                None,
-                None,
-            )
-            .unwrap();
-            assert_eq!(
-                expected_comm,
-                vk.plookup_vk.as_ref().unwrap().key_table_comm
-            );
-        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_circuits() -> Result<(), PlonkError> {
+        test_merge_circuits_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_merge_circuits_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::UltraPlonk)?;
+        test_merge_circuits_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_merge_circuits_helper::<Bls12_381, Fq381, _, StandardTranscript>(PlonkType::UltraPlonk)
+    }
+
+    fn test_merge_circuits_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let new_circuit = || -> PlonkCircuit<E::Fr> {
+            match plonk_type {
+                PlonkType::TurboPlonk => PlonkCircuit::new_turbo_plonk(),
+                PlonkType::UltraPlonk => PlonkCircuit::new_ultra_plonk(4),
+            }
+        };
+        // y = x^3 + 5, with x public
+        let build = |x_val: u32| -> Result<(PlonkCircuit<E::Fr>, Variable), PlonkError> {
+            let mut cs = new_circuit();
+            let x = cs.create_public_variable(E::Fr::from(x_val))?;
+            let x_square = cs.mul(x, x)?;
+            let x_cube = cs.mul(x_square, x)?;
+            let y = cs.add_constant(x_cube, &E::Fr::from(5u32))?;
+            Ok((cs, y))
+        };
+        // z = a * y, with z public, wiring y to the output of the other circuit
+        let mut other = new_circuit();
+        let a = other.create_variable(E::Fr::from(2u32))?;
+        let y = other.create_variable(E::Fr::from(32u32))?;
+        let z = other.mul(a, y)?;
+        other.set_variable_public(z)?;
+
+        let (mut cs, cs_y) = build(3)?;
+        let var_map = cs.merge(other.clone(), &[(cs_y, y)])?;
+        assert_eq!(cs.witness(var_map[z])?, E::Fr::from(64u32));
+        let public_input = cs.public_input()?;
+        assert_eq!(public_input, vec![E::Fr::from(3u32), E::Fr::from(64u32)]);
+        cs.finalize_for_arithmetization()?;
+
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_ok());
+        let bad_public_input = vec![E::Fr::from(3u32), E::Fr::from(96u32)];
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &bad_public_input, &proof, None).is_err());
+
+        // the same keys prove the merge of other witnesses, in which the shared
+        // wire carries the output of the first circuit
+        let (mut cs, cs_y) = build(2)?;
+        let var_map = cs.merge(other, &[(cs_y, y)])?;
+        let public_input = cs.public_input()?;
+        assert_eq!(public_input, vec![E::Fr::from(2u32), E::Fr::from(64u32)]);
+        assert!(cs.check_circuit_satisfiability(&public_input).is_err());
+        *cs.witness_mut(var_map[z]) = E::Fr::from(26u32);
+        let public_input = cs.public_input()?;
+        cs.finalize_for_arithmetization()?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_ok());
 
         Ok(())
     }
 
     #[test]
-    fn test_plonk_proof_system() -> Result<(), PlonkError> {
-        // merlin transcripts
-        test_plonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+    fn test_prove_with_msm_backend() -> Result<(), PlonkError> {
+        test_prove_with_msm_backend_helper::<Bn254, Fq254, _, StandardTranscript>(
             PlonkType::TurboPlonk,
         )?;
-        test_plonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+        test_prove_with_msm_backend_helper::<Bn254, Fq254, _, StandardTranscript>(
             PlonkType::UltraPlonk,
         )?;
-        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+        test_prove_with_msm_backend_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
             PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_prove_with_msm_backend_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        // a backend counting its invocations, and optionally computing wrong
+        // MSMs
+        #[derive(Default)]
+        struct MockMsm {
+            count: AtomicUsize,
+            faulty: bool,
+        }
+        impl<G: AffineCurve> MsmBackend<G> for MockMsm {
+            fn msm(&self, bases: &[G], scalars: &[G::ScalarField]) -> G::Projective {
+                self.count.fetch_add(1, Ordering::Relaxed);
+                let res = ArkworksMsm.msm(bases, scalars);
+                if self.faulty {
+                    res.double()
+                } else {
+                    res
+                }
+            }
+        }
+
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let public_input = cs.public_input()?;
+
+        let mock = MockMsm::default();
+        let proof = PlonkKzgSnark::<E>::prove_with_msm_backend::<_, _, T>(
+            &mut test_rng(),
+            &cs,
+            &pk,
+            None,
+            &mock,
         )?;
-        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, StandardTranscript>(
-            PlonkType::UltraPlonk,
-        )?;
-        test_plonk_proof_system_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+        // the wires, the permutation product, the split quotients and the 2
+        // opening proofs, and the 3 Plookup polynomials if any
+        let num_wire_types = cs.num_wire_types();
+        let expected_count = 2 * num_wire_types
+            + 3
+            + match plonk_type {
+                PlonkType::TurboPlonk => 0,
+                PlonkType::UltraPlonk => 3,
+            };
+        assert_eq!(mock.count.load(Ordering::Relaxed), expected_count);
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_ok());
+        // the default backend computes the same proof
+        let default_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(&mut test_rng(), &cs, &pk, None)?;
+        assert_eq!(proof, default_proof);
+
+        // a faulty backend leads to an invalid proof
+        let faulty = MockMsm {
+            faulty: true,
+            ..Default::default()
+        };
+        let bad_proof =
+            PlonkKzgSnark::<E>::prove_with_msm_backend::<_, _, T>(rng, &cs, &pk, None, &faulty)?;
+        assert!(faulty.count.load(Ordering::Relaxed) > 0);
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &bad_proof, None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_deterministic() -> Result<(), PlonkError> {
+        test_prove_deterministic_helper::<Bn254, Fq254, _, StandardTranscript>(
             PlonkType::TurboPlonk,
         )?;
-        test_plonk_proof_system_helper::<Bls12_381, Fq381, _, StandardTranscript>(
+        test_prove_deterministic_helper::<Bn254, Fq254, _, StandardTranscript>(
             PlonkType::UltraPlonk,
         )?;
-        test_plonk_proof_system_helper::<BW6_761, Fq761, _, StandardTranscript>(
-            PlonkType::TurboPlonk,
+        test_prove_deterministic_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )
+    }
+
+    fn test_prove_deterministic_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let public_input = cs.public_input()?;
+        let num_blinding = match plonk_type {
+            PlonkType::TurboPlonk => 13,
+            PlonkType::UltraPlonk => 24,
+        };
+        let blinding: Vec<E::Fr> = (0..num_blinding).map(|_| E::Fr::rand(rng)).collect();
+
+        let extra_msg = Some(b"extra message".to_vec());
+        let proof_1 = PlonkKzgSnark::<E>::prove_deterministic::<_, T>(
+            &cs,
+            &pk,
+            &blinding,
+            extra_msg.clone(),
+        )?;
+        let proof_2 = PlonkKzgSnark::<E>::prove_deterministic::<_, T>(
+            &cs,
+            &pk,
+            &blinding,
+            extra_msg.clone(),
         )?;
-        test_plonk_proof_system_helper::<BW6_761, Fq761, _, StandardTranscript>(
-            PlonkType::UltraPlonk,
+        let mut bytes_1 = Vec::new();
+        proof_1.serialize(&mut bytes_1)?;
+        let mut bytes_2 = Vec::new();
+        proof_2.serialize(&mut bytes_2)?;
+        assert_eq!(bytes_1, bytes_2);
+        assert!(
+            PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof_1, extra_msg.clone())
+                .is_ok()
+        );
+
+        // other blinding scalars yield another valid proof
+        let mut other_blinding = blinding.clone();
+        other_blinding[0] += E::Fr::one();
+        let proof_3 = PlonkKzgSnark::<E>::prove_deterministic::<_, T>(
+            &cs,
+            &pk,
+            &other_blinding,
+            extra_msg.clone(),
         )?;
+        assert_ne!(proof_3, proof_1);
+        assert!(
+            PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof_3, extra_msg.clone())
+                .is_ok()
+        );
 
-        // rescue transcripts
-        // currently only available for bls12-377
-        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+        // wrong numbers of blinding scalars
+        assert!(PlonkKzgSnark::<E>::prove_deterministic::<_, T>(
+            &cs,
+            &pk,
+            &blinding[1..],
+            extra_msg.clone(),
+        )
+        .is_err());
+        let mut more_blinding = blinding;
+        more_blinding.push(E::Fr::one());
+        assert!(PlonkKzgSnark::<E>::prove_deterministic::<_, T>(
+            &cs,
+            &pk,
+            &more_blinding,
+            extra_msg,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_with_precompute() -> Result<(), PlonkError> {
+        test_verify_with_precompute_helper::<Bn254, Fq254, _, StandardTranscript>(
             PlonkType::TurboPlonk,
         )?;
-        test_plonk_proof_system_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+        test_verify_with_precompute_helper::<Bn254, Fq254, _, StandardTranscript>(
             PlonkType::UltraPlonk,
         )?;
-
-        // solidity-friendly keccak256 transcripts
-        // currently only needed for CAPE using bls12-381
-        test_plonk_proof_system_helper::<Bls12_381, Fq381, _, SolidityTranscript>(
+        test_verify_with_precompute_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
             PlonkType::TurboPlonk,
         )?;
-        Ok(())
+        test_verify_with_precompute_helper::<Bls12_381, Fq381, _, SolidityTranscript>(
+            PlonkType::UltraPlonk,
+        )
     }
 
-    fn test_plonk_proof_system_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    fn test_verify_with_precompute_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
     where
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         F: RescueParameter + SWToTEConParam,
         P: SWModelParameters<BaseField = F> + Clone,
         T: PlonkTranscript<F>,
     {
-        // 1. Simulate universal setup
         let rng = &mut test_rng();
-        let n = 64;
-        let max_degree = n + 2;
-        let srs = PlonkKzgSnark::<E>::universal_setup(max_degree, rng)?;
-
-        // 2. Create circuits
-        let circuits = (0..6)
-            .map(|i| {
-                let m = 2 + i / 3;
-                let a0 = 1 + i % 3;
-                gen_circuit_for_test(m, a0, plonk_type)
-            })
-            .collect::<Result<Vec<_>, PlonkError>>()?;
-        // 3. Preprocessing
-        let (pk1, vk1) = PlonkKzgSnark::<E>::preprocess(&srs, &circuits[0])?;
-        let (pk2, vk2) = PlonkKzgSnark::<E>::preprocess(&srs, &circuits[3])?;
-        // 4. Proving
-        let mut proofs = vec![];
-        let mut extra_msgs = vec![];
-        for (i, cs) in circuits.iter().enumerate() {
-            let pk_ref = if i < 3 { &pk1 } else { &pk2 };
-            let extra_msg = if i % 2 == 0 {
-                None
-            } else {
-                Some(format!("extra message: {}", i).into_bytes())
-            };
-            proofs.push(
-                PlonkKzgSnark::<E>::prove::<_, _, T>(rng, cs, pk_ref, extra_msg.clone()).unwrap(),
-            );
-            extra_msgs.push(extra_msg);
-        }
-
-        // 5. Verification
-        let public_inputs: Vec<Vec<E::Fr>> = circuits
-            .iter()
-            .map(|cs| cs.public_input())
-            .collect::<Result<Vec<Vec<E::Fr>>, PlonkError>>()?;
-        for (i, proof) in proofs.iter().enumerate() {
-            let vk_ref = if i < 3 { &vk1 } else { &vk2 };
-            assert!(PlonkKzgSnark::<E>::verify::<T>(
-                vk_ref,
-                &public_inputs[i],
-                proof,
-                extra_msgs[i].clone(),
-            )
-            .is_ok());
-            // Inconsistent proof should fail the verification.
-            let mut bad_pub_input = public_inputs[i].clone();
-            bad_pub_input[0] = E::Fr::from(0u8);
-            assert!(PlonkKzgSnark::<E>::verify::<T>(
-                vk_ref,
-                &bad_pub_input,
-                proof,
-                extra_msgs[i].clone(),
-            )
-            .is_err());
-            // Incorrect extra transcript message should fail
-            assert!(PlonkKzgSnark::<E>::verify::<T>(
-                vk_ref,
-                &bad_pub_input,
-                proof,
-                Some("wrong message".to_string().into_bytes()),
-            )
-            .is_err());
-
-            // Incorrect proof [W_z] = 0, [W_z*g] = 0
-            // attack against some vulnerable implementation described in:
-            // https://cryptosubtlety.medium.com/00-8d4adcf4d255
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let public_input = cs.public_input()?;
+        let precompute = VerifierPrecompute::new(&vk)?;
+        assert_eq!(*precompute.verifying_key(), vk);
+
+        // the precompute is reused across proofs
+        for _ in 0..3 {
+            let extra_msg = Some(b"extra message".to_vec());
+            let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, extra_msg.clone())?;
+
+            let mut bad_public_input = public_input.clone();
+            bad_public_input[0] += E::Fr::one();
             let mut bad_proof = proof.clone();
-            bad_proof.opening_proof = Commitment::default();
-            bad_proof.shifted_opening_proof = Commitment::default();
-            assert!(PlonkKzgSnark::<E>::verify::<T>(
-                vk_ref,
-                &public_inputs[i],
-                &bad_proof,
-                extra_msgs[i].clone(),
+            bad_proof.poly_evals.wires_evals[0] += E::Fr::one();
+
+            for (pub_input, proof, extra_msg, valid) in [
+                (&public_input, &proof, &extra_msg, true),
+                (&bad_public_input, &proof, &extra_msg, false),
+                (&public_input, &bad_proof, &extra_msg, false),
+                (&public_input, &proof, &None, false),
+            ] {
+                let plain =
+                    PlonkKzgSnark::<E>::verify::<T>(&vk, pub_input, proof, extra_msg.clone());
+                let fast = PlonkKzgSnark::<E>::verify_with_precompute::<T>(
+                    &precompute,
+                    pub_input,
+                    proof,
+                    extra_msg.clone(),
+                );
+                assert_eq!(plain.is_ok(), valid);
+                assert_eq!(fast.is_ok(), valid);
+            }
+            // wrong number of public inputs
+            assert!(PlonkKzgSnark::<E>::verify_with_precompute::<T>(
+                &precompute,
+                &public_input[1..],
+                &proof,
+                extra_msg,
             )
             .is_err());
         }
+        Ok(())
+    }
 
-        // 6. Batch verification
-        let vks = vec![&vk1, &vk1, &vk1, &vk2, &vk2, &vk2];
-        let mut public_inputs_ref: Vec<&[E::Fr]> = public_inputs
-            .iter()
-            .map(|pub_input| &pub_input[..])
-            .collect();
-        let mut proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks,
-            &public_inputs_ref,
-            &proofs_ref,
-            &extra_msgs,
+    // Run with `cargo test --release --features std -- --ignored`.
+    #[test]
+    #[ignore]
+    #[cfg(feature = "std")]
+    fn test_verify_with_precompute_speedup() -> Result<(), PlonkError> {
+        const NUM_VERIFICATIONS: usize = 1000;
+        let rng = &mut test_rng();
+        let mut cs = PlonkCircuit::<<Bn254 as PairingEngine>::Fr>::new_turbo_plonk();
+        for i in 0..256u64 {
+            cs.create_public_variable(i.into())?;
+        }
+        cs.finalize_for_arithmetization()?;
+        let srs = PlonkKzgSnark::<Bn254>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<Bn254>::preprocess(&srs, &cs)?;
+        let public_input = cs.public_input()?;
+        let proof = PlonkKzgSnark::<Bn254>::prove::<_, _, StandardTranscript>(rng, &cs, &pk, None)?;
+
+        let start = std::time::Instant::now();
+        for _ in 0..NUM_VERIFICATIONS {
+            PlonkKzgSnark::<Bn254>::verify::<StandardTranscript>(&vk, &public_input, &proof, None)?;
+        }
+        let plain_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let precompute = VerifierPrecompute::new(&vk)?;
+        for _ in 0..NUM_VERIFICATIONS {
+            PlonkKzgSnark::<Bn254>::verify_with_precompute::<StandardTranscript>(
+                &precompute,
+                &public_input,
+                &proof,
+                None,
+            )?;
+        }
+        let precompute_time = start.elapsed();
+
+        assert!(
+            precompute_time < plain_time,
+            "{} verifications took {:?} with precompute, {:?} without",
+            NUM_VERIFICATIONS,
+            precompute_time,
+            plain_time
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_beacon_binding() -> Result<(), PlonkError> {
+        test_beacon_binding_helper::<Bn254, Fq254, _, StandardTranscript>()?;
+        test_beacon_binding_helper::<Bls12_377, Fq377, _, StandardTranscript>()?;
+        test_beacon_binding_helper::<Bls12_381, Fq381, _, StandardTranscript>()?;
+        test_beacon_binding_helper::<BW6_761, Fq761, _, StandardTranscript>()?;
+        test_beacon_binding_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()
+    }
+
+    fn test_beacon_binding_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let public_input = cs.public_input()?;
+        let beacon = b"beacon round 1234: 0x5eed";
+        let other_beacon = b"beacon round 1235: 0x5eed";
+
+        let proof = PlonkKzgSnark::<E>::prove_with_beacon::<_, _, T>(rng, &cs, &pk, beacon, None)?;
+        assert!(PlonkKzgSnark::<E>::verify_with_beacon::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            beacon,
+            None
         )
         .is_ok());
-
-        // Inconsistent params
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks[..5],
-            &public_inputs_ref,
-            &proofs_ref,
-            &extra_msgs,
+        // another beacon, or no beacon at all
+        assert!(PlonkKzgSnark::<E>::verify_with_beacon::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            other_beacon,
+            None
         )
         .is_err());
-
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks,
-            &public_inputs_ref[..5],
-            &proofs_ref,
-            &extra_msgs,
+        assert!(PlonkKzgSnark::<E>::verify_with_beacon::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            &beacon[..beacon.len() - 1],
+            None
         )
         .is_err());
-
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks,
-            &public_inputs_ref,
-            &proofs_ref[..5],
-            &extra_msgs,
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_err());
+        // a proof without beacon is rejected
+        let plain_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        assert!(PlonkKzgSnark::<E>::verify_with_beacon::<T>(
+            &vk,
+            &public_input,
+            &plain_proof,
+            beacon,
+            None
         )
         .is_err());
 
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks,
-            &public_inputs_ref,
-            &proofs_ref,
-            &vec![None; vks.len()],
+        // the beacon and the extra message are not interchangeable
+        let extra_msg = Some(b"extra".to_vec());
+        let proof = PlonkKzgSnark::<E>::prove_with_beacon::<_, _, T>(
+            rng,
+            &cs,
+            &pk,
+            beacon,
+            extra_msg.clone(),
+        )?;
+        assert!(PlonkKzgSnark::<E>::verify_with_beacon::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            beacon,
+            extra_msg
+        )
+        .is_ok());
+        assert!(PlonkKzgSnark::<E>::verify_with_beacon::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            beacon,
+            None
         )
         .is_err());
-
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks,
-            &public_inputs_ref,
-            &proofs_ref,
-            &vec![],
+        let mut merged_beacon = beacon.to_vec();
+        merged_beacon.extend_from_slice(b"extra");
+        assert!(PlonkKzgSnark::<E>::verify_with_beacon::<T>(
+            &vk,
+            &public_input,
+            &proof,
+            &merged_beacon,
+            None
         )
         .is_err());
 
-        // Empty params
-        assert!(
-            PlonkKzgSnark::<E>::batch_verify::<T>(&vec![], &vec![], &vec![], &vec![],).is_err()
-        );
+        Ok(())
+    }
 
-        // Error paths
-        let tmp_pi_ref = public_inputs_ref[0];
-        public_inputs_ref[0] = public_inputs_ref[1];
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks,
-            &public_inputs_ref,
-            &proofs_ref,
-            &extra_msgs,
+    #[test]
+    fn test_verification_receipt() -> Result<(), PlonkError> {
+        test_verification_receipt_helper::<Bn254, Fq254, _, StandardTranscript>()?;
+        test_verification_receipt_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()
+    }
+
+    fn test_verification_receipt_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let other_cs = gen_circuit_for_test::<E::Fr>(3, 5, PlonkType::TurboPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let public_input = cs.public_input()?;
+        let other_public_input = other_cs.public_input()?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        let other_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &other_cs, &pk, None)?;
+
+        // identical verifications, even of different proofs, give identical receipts
+        let receipt =
+            PlonkKzgSnark::<E>::verify_with_receipt::<T>(&vk, &public_input, &proof, None, 42)?;
+        let proof_2 = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        assert_eq!(
+            receipt,
+            PlonkKzgSnark::<E>::verify_with_receipt::<T>(&vk, &public_input, &proof_2, None, 42)?
+        );
+        assert_eq!(receipt.timestamp, 42);
+        assert_eq!(receipt, VerificationReceipt::new(&vk, &public_input, 42)?);
+
+        // different inputs, or timestamps
+        let other_receipt = PlonkKzgSnark::<E>::verify_with_receipt::<T>(
+            &vk,
+            &other_public_input,
+            &other_proof,
+            None,
+            42,
+        )?;
+        assert_eq!(receipt.vk_digest, other_receipt.vk_digest);
+        assert_ne!(receipt.input_digest, other_receipt.input_digest);
+        assert_ne!(receipt, VerificationReceipt::new(&vk, &public_input, 43)?);
+        // a different verifying key
+        let other_srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (_, other_vk) = PlonkKzgSnark::<E>::preprocess(&other_srs, &cs)?;
+        let other_receipt = VerificationReceipt::new(&other_vk, &public_input, 42)?;
+        assert_ne!(receipt.vk_digest, other_receipt.vk_digest);
+        assert_eq!(receipt.input_digest, other_receipt.input_digest);
+
+        // no receipt for a failed verification
+        assert!(PlonkKzgSnark::<E>::verify_with_receipt::<T>(
+            &vk,
+            &other_public_input,
+            &proof,
+            None,
+            42
         )
         .is_err());
-        public_inputs_ref[0] = tmp_pi_ref;
 
-        proofs_ref[0] = proofs_ref[1];
-        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
-            &vks,
-            &public_inputs_ref,
-            &proofs_ref,
-            &extra_msgs,
+        Ok(())
+    }
+
+    #[test]
+    fn test_interleaved_pub_inputs() -> Result<(), PlonkError> {
+        test_interleaved_pub_inputs_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_interleaved_pub_inputs_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_interleaved_pub_inputs_helper::<Bls12_381, Fq381, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
         )
-        .is_err());
+    }
+
+    fn test_interleaved_pub_inputs_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let mut cs: PlonkCircuit<E::Fr> = match plonk_type {
+            PlonkType::TurboPlonk => PlonkCircuit::new_turbo_plonk(),
+            PlonkType::UltraPlonk => PlonkCircuit::new_ultra_plonk(4),
+        };
+        // public inputs made public between the gates using them: an internal
+        // wire, a new variable, the first variable and the output
+        let a = cs.create_variable(E::Fr::from(3u32))?;
+        let b = cs.create_variable(E::Fr::from(4u32))?;
+        let c = cs.mul(a, b)?;
+        let d = cs.add(c, a)?;
+        cs.set_variable_public(c)?;
+        let e = cs.create_public_variable(E::Fr::from(7u32))?;
+        let f = cs.mul(d, e)?;
+        cs.set_variable_public(a)?;
+        let g = cs.add(f, b)?;
+        cs.set_variable_public(g)?;
+        cs.finalize_for_arithmetization()?;
+        let public_input = cs.public_input()?;
+        assert_eq!(
+            public_input,
+            [12u32, 7, 3, 109]
+                .iter()
+                .map(|&x| E::Fr::from(x))
+                .collect::<Vec<_>>()
+        );
+
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        assert_eq!(vk.num_inputs, 4);
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &cs, &pk, None)?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_ok());
+
+        // the public inputs are bound in the order in which they were made public
+        let mut swapped_input = public_input.clone();
+        swapped_input.swap(0, 1);
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &swapped_input, &proof, None).is_err());
+        let mut bad_input = public_input;
+        bad_input[3] += E::Fr::one();
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &bad_input, &proof, None).is_err());
 
         Ok(())
     }
@@ -1179,8 +5669,18 @@ pub mod test {
         let (pk, _) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
 
         // 4. Proving
-        let (_, oracles, challenges) =
-            PlonkKzgSnark::<E>::batch_prove_internal::<_, _, T>(rng, &[&circuit], &[&pk], None)?;
+        let (_, oracles, challenges, _) = PlonkKzgSnark::<E>::batch_prove_internal::<_, _, T>(
+            rng,
+            &[&circuit],
+            &[&pk],
+            None,
+            None,
+            ProfileRecorder::default(),
+            &ArkworksMsm,
+            None,
+            None,
+            0,
+        )?;
 
         // 5. Check that the targeted polynomials evaluate to zero on the vanishing set.
         check_plonk_prover_polynomials(plonk_type, &oracles[0], &pk, &challenges)?;
@@ -1360,7 +5860,7 @@ pub mod test {
         for i in 0..domain.size() - 1 {
             let point = domain.element(i);
             let next_point = point * domain.group_gen;
-            let merged_lookup_wire_eval = eval_merged_lookup_witness::<E>(
+            let merged_lookup_wire_eval = eval_merged_lookup_witness(
                 challenges.tau,
                 oracles.wire_polys[5].evaluate(&point),
                 oracles.wire_polys[0].evaluate(&point),
@@ -1370,7 +5870,7 @@ pub mod test {
             );
             let range_table_poly_ref = &pk.plookup_pk.as_ref().unwrap().range_table_poly;
             let key_table_poly_ref = &pk.plookup_pk.as_ref().unwrap().key_table_poly;
-            let merged_table_eval = eval_merged_table::<E>(
+            let merged_table_eval = eval_merged_table(
                 challenges.tau,
                 range_table_poly_ref.evaluate(&point),
                 key_table_poly_ref.evaluate(&point),
@@ -1378,7 +5878,7 @@ pub mod test {
                 oracles.wire_polys[3].evaluate(&point),
                 oracles.wire_polys[4].evaluate(&point),
             );
-            let merged_table_next_eval = eval_merged_table::<E>(
+            let merged_table_next_eval = eval_merged_table(
                 challenges.tau,
                 range_table_poly_ref.evaluate(&next_point),
                 key_table_poly_ref.evaluate(&next_point),
@@ -1493,6 +5993,43 @@ pub mod test {
         let de = Proof::<E>::deserialize(&ser_bytes[..])?;
         assert_eq!(de, proof);
 
+        // uncompressed keys
+        let mut pk_bytes = Vec::new();
+        pk.serialize_uncompressed(&mut pk_bytes)?;
+        let reloaded_pk = ProvingKey::<E>::deserialize_uncompressed(&pk_bytes[..])?;
+        assert_eq!(reloaded_pk, pk);
+        let mut vk_bytes = Vec::new();
+        vk.serialize_uncompressed(&mut vk_bytes)?;
+        let reloaded_vk = VerifyingKey::<E>::deserialize_uncompressed(&vk_bytes[..])?;
+        assert_eq!(reloaded_vk, vk);
+        // the compressed verifying key is the compact one
+        assert!(vk.serialized_size() < vk.uncompressed_size());
+        assert!(pk.serialized_size() < pk.uncompressed_size());
+
+        // the reloaded keys reproduce the same proofs, which verify
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(&mut test_rng(), &circuit, &pk, None)?;
+        let reloaded_proof =
+            PlonkKzgSnark::<E>::prove::<_, _, T>(&mut test_rng(), &circuit, &reloaded_pk, None)?;
+        assert_eq!(reloaded_proof, proof);
+        let public_input = circuit.public_input()?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(
+            &reloaded_vk,
+            &public_input,
+            &reloaded_proof,
+            None
+        )
+        .is_ok());
+        let mut vk_bytes = Vec::new();
+        vk.serialize(&mut vk_bytes)?;
+        let reloaded_vk = VerifyingKey::<E>::deserialize(&vk_bytes[..])?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&reloaded_vk, &public_input, &proof, None).is_ok());
+
+        // truncated keys
+        assert!(
+            ProvingKey::<E>::deserialize_uncompressed(&pk_bytes[..pk_bytes.len() - 1]).is_err()
+        );
+        assert!(VerifyingKey::<E>::deserialize(&vk_bytes[..vk_bytes.len() - 1]).is_err());
+
         Ok(())
     }
 
@@ -1567,7 +6104,7 @@ pub mod test {
         let circuits = type_a_circuits
             .iter()
             .zip(type_b_circuits.iter())
-            .map(|(cs_a, cs_b)| cs_a.merge(cs_b))
+            .map(|(cs_a, cs_b)| cs_a.merge_mergeable_circuits(cs_b))
             .collect::<Result<Vec<_>, PlonkError>>()?;
         let cs_ref: Vec<&PlonkCircuit<E::Fr>> = circuits.iter().collect();
 
@@ -14,22 +14,40 @@ use crate::{
                 plonk_verifier::{BatchProofVar, ProofEvaluationsVar},
             },
         },
+        gates::{CustomGateSpec, Gate},
         PlonkCircuit,
     },
-    constants::{compute_coset_representatives, GATE_WIDTH, N_TURBO_PLONK_SELECTORS},
+    constants::{
+        compute_coset_representatives, GATE_WIDTH, N_TURBO_PLONK_SELECTORS, PROOF_HEADER_MAGIC,
+        PROOF_HEADER_VERSION, VK_FINGERPRINT_DOMAIN_SEP,
+    },
     errors::{
         PlonkError,
         SnarkError::{self, ParameterError, SnarkLookupUnsupported},
     },
+    proof_system::{
+        pcs::{KzgPcs, PcsCommitter, PolynomialCommitmentScheme},
+        verifier::{PiLagrangeBases, Verifier},
+    },
+    transcript::PlonkTranscript,
 };
 use ark_ec::{
-    msm::VariableBaseMSM, short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters,
+    msm::VariableBaseMSM, short_weierstrass_jacobian::GroupAffine, AffineCurve, PairingEngine,
+    ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::{batch_inversion, FftField, Field, Fp2, Fp2Parameters, One, PrimeField, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain, UVPolynomial,
+};
+use ark_poly_commit::{
+    kzg10::{
+        Commitment, Powers, Proof as KzgProof, Randomness, UniversalParams, VerifierKey, KZG10,
+    },
+    PCRandomness, PCUniversalParams,
 };
-use ark_ff::{FftField, Field, Fp2, Fp2Parameters, PrimeField, Zero};
-use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::kzg10::{Commitment, Powers, UniversalParams, VerifierKey};
 use ark_serialize::*;
 use ark_std::{
+    boxed::Box,
     collections::HashMap,
     convert::{TryFrom, TryInto},
     format,
@@ -39,6 +57,8 @@ use ark_std::{
 };
 use jf_rescue::RescueParameter;
 use jf_utils::{field_switching, fq_to_fr, fr_to_fq, tagged_blob};
+use rayon::prelude::*;
+use sha3::{Digest, Keccak256};
 
 /// Universal Structured Reference String for PlonkKzgSnark
 #[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -49,6 +69,22 @@ impl<E: PairingEngine> UniversalSrs<E> {
     pub fn powers_of_g_ref(&self) -> &[E::G1Affine] {
         &self.0.powers_of_g
     }
+
+    /// The canonical (compressed) serialization of the G2 element `tau * h` of
+    /// the SRS, which can be compared against the value published by a setup
+    /// ceremony.
+    pub fn g2_tau_bytes(&self) -> Result<Vec<u8>, PlonkError> {
+        let mut bytes = Vec::new();
+        self.0.beta_h.serialize(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Check that the G2 element `tau * h` of the SRS equals the reference
+    /// element canonically serialized in `reference`. Return false if
+    /// `reference` is not a valid serialization.
+    pub fn matches_reference_g2(&self, reference: &[u8]) -> bool {
+        E::G2Affine::deserialize(reference).map_or(false, |g2| g2 == self.0.beta_h)
+    }
 }
 
 pub(crate) type CommitKey<'a, E> = Powers<'a, E>;
@@ -56,34 +92,43 @@ pub(crate) type CommitKey<'a, E> = Powers<'a, E>;
 /// Key for verifying PCS opening proof (alias to kzg10::VerifierKey).
 pub type OpenKey<E> = VerifierKey<E>;
 
-/// A Plonk SNARK proof.
+/// A Plonk SNARK proof over the polynomial commitment scheme `PCS`.
 #[tagged_blob("PROOF")]
-#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize, Derivative)]
-#[derivative(Hash(bound = "E:PairingEngine"))]
-pub struct Proof<E: PairingEngine> {
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = ""),
+    Clone(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = ""),
+    Hash(bound = "")
+)]
+pub struct PcsProof<PCS: PolynomialCommitmentScheme> {
     /// Wire witness polynomials commitments.
-    pub(crate) wires_poly_comms: Vec<Commitment<E>>,
+    pub(crate) wires_poly_comms: Vec<PCS::Commitment>,
 
     /// The polynomial commitment for the wire permutation argument.
-    pub(crate) prod_perm_poly_comm: Commitment<E>,
+    pub(crate) prod_perm_poly_comm: PCS::Commitment,
 
     /// Splitted quotient polynomial commitments.
-    pub(crate) split_quot_poly_comms: Vec<Commitment<E>>,
+    pub(crate) split_quot_poly_comms: Vec<PCS::Commitment>,
 
     /// (Aggregated) proof of evaluations at challenge point `zeta`.
-    pub(crate) opening_proof: Commitment<E>,
+    pub(crate) opening_proof: PCS::Proof,
 
     /// (Aggregated) proof of evaluation at challenge point `zeta * g` where `g`
     /// is the root of unity.
-    pub(crate) shifted_opening_proof: Commitment<E>,
+    pub(crate) shifted_opening_proof: PCS::Proof,
 
     /// Polynomial evaluations.
-    pub(crate) poly_evals: ProofEvaluations<E::Fr>,
+    pub(crate) poly_evals: ProofEvaluations<PCS::Fr>,
 
     /// The partial proof for Plookup argument
-    pub(crate) plookup_proof: Option<PlookupProof<E>>,
+    pub(crate) plookup_proof: Option<PcsPlookupProof<PCS>>,
 }
 
+/// A Plonk SNARK proof over the KZG polynomial commitment scheme.
+pub type Proof<E> = PcsProof<KzgPcs<E>>;
+
 impl<E, P> TryFrom<Vec<E::Fq>> for Proof<E>
 where
     E: PairingEngine<G1Affine = GroupAffine<P>>,
@@ -217,9 +262,304 @@ where
 }
 
 /// A Plookup argument proof.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = ""),
+    Clone(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = ""),
+    Hash(bound = "")
+)]
+pub struct PcsPlookupProof<PCS: PolynomialCommitmentScheme> {
+    /// The commitments for the polynomials that interpolate the sorted
+    /// concatenation of the lookup table and the witnesses in the lookup gates.
+    pub(crate) h_poly_comms: Vec<PCS::Commitment>,
+
+    /// The product accumulation polynomial commitment for the Plookup argument
+    pub(crate) prod_lookup_poly_comm: PCS::Commitment,
+
+    /// Polynomial evaluations.
+    pub(crate) poly_evals: PlookupEvaluations<PCS::Fr>,
+}
+
+/// A Plookup argument proof over the KZG polynomial commitment scheme.
+pub type PlookupProof<E> = PcsPlookupProof<KzgPcs<E>>;
+
+/// Options of the serialization of proofs by [`Proof::serialize_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SerializeConfig {
+    /// Whether the group elements are compressed.
+    pub compressed: bool,
+    /// Whether the serialization starts with a header made of the magic bytes
+    /// `JFPRF`, the version of the format and a byte of flags, whose lowest
+    /// bit tells whether the group elements are compressed.
+    pub with_header: bool,
+}
+
+impl<E: PairingEngine> Proof<E> {
+    /// The commitments to the wire witness polynomials.
+    pub fn wire_comms(&self) -> &[Commitment<E>] {
+        &self.wires_poly_comms
+    }
+
+    /// Serialize the proof to `writer` as specified by `config`.
+    pub fn serialize_with<W: Write>(
+        &self,
+        config: SerializeConfig,
+        mut writer: W,
+    ) -> Result<(), PlonkError> {
+        if config.with_header {
+            let mut header = PROOF_HEADER_MAGIC.to_vec();
+            header.push(PROOF_HEADER_VERSION);
+            header.push(config.compressed as u8);
+            writer.write_all(&header)?;
+        }
+        match config.compressed {
+            true => self.serialize(writer)?,
+            false => self.serialize_uncompressed(writer)?,
+        }
+        Ok(())
+    }
+
+    /// Deserialize a proof from `reader`, serialized by
+    /// [`Self::serialize_with`] with the same `config`.
+    /// Return error if the header does not match `config`.
+    pub fn deserialize_with<R: Read>(
+        config: SerializeConfig,
+        mut reader: R,
+    ) -> Result<Self, PlonkError> {
+        if config.with_header {
+            let mut header = vec![0u8; PROOF_HEADER_MAGIC.len() + 2];
+            reader.read_exact(&mut header)?;
+            let expected_flags = config.compressed as u8;
+            if header[..PROOF_HEADER_MAGIC.len()] != PROOF_HEADER_MAGIC[..]
+                || header[PROOF_HEADER_MAGIC.len()] != PROOF_HEADER_VERSION
+                || header[PROOF_HEADER_MAGIC.len() + 1] != expected_flags
+            {
+                return Err(SerializationError::InvalidData.into());
+            }
+        }
+        Ok(match config.compressed {
+            true => Self::deserialize(reader)?,
+            false => Self::deserialize_uncompressed(reader)?,
+        })
+    }
+
+    /// Drop the evaluations that the verifier can recompute, namely the
+    /// evaluations of the range and key tables of the Plookup argument, which
+    /// are replaced by the sizes of the tables read from the proving key `pk`
+    /// of the proof. A TurboPlonk proof has no such evaluations.
+    ///
+    /// These are the only evaluations that can be dropped: the linearization
+    /// already spares the evaluations of the quotient and linearization
+    /// polynomials, and each remaining evaluation only enters the opening
+    /// check through a group element, from which it cannot be recovered.
+    /// Rebuilding the table evaluations needs the challenge `zeta`, hence the
+    /// public input and the transcript message on top of the verifying key,
+    /// see [`CompressedProof::decompress`]. An UltraPlonk compressed proof is
+    /// smaller by four scalars minus the two `u64` table sizes.
+    /// Return error if the proof and `pk` disagree on the lookup support.
+    pub fn compress(&self, pk: &ProvingKey<E>) -> Result<CompressedProof<E>, PlonkError> {
+        let plookup_proof = match (&self.plookup_proof, &pk.plookup_pk) {
+            (None, None) => None,
+            (Some(plookup_proof), Some(plookup_pk)) => {
+                let domain = Radix2EvaluationDomain::<E::Fr>::new(pk.domain_size())
+                    .ok_or(PlonkError::DomainCreationError)?;
+                // the range table is [0, 1, ..., range_len - 1, 0, ..., 0]
+                let range_table = domain.fft(&plookup_pk.range_table_poly.coeffs);
+                let range_table_len = range_table
+                    .iter()
+                    .rposition(|val| !val.is_zero())
+                    .map_or(0, |i| i + 1);
+                // the key table is [0, ..., 0, 1, ..., key_len - 1, 0]
+                let key_table = domain.fft(&plookup_pk.key_table_poly.coeffs);
+                let key_table_len = key_table
+                    .iter()
+                    .position(|val| !val.is_zero())
+                    .map_or(0, |i| key_table.len() - i);
+                let evals = &plookup_proof.poly_evals;
+                Some(CompressedPlookupProof {
+                    h_poly_comms: plookup_proof.h_poly_comms.clone(),
+                    prod_lookup_poly_comm: plookup_proof.prod_lookup_poly_comm,
+                    range_table_len: range_table_len as u64,
+                    key_table_len: key_table_len as u64,
+                    h_1_eval: evals.h_1_eval,
+                    q_lookup_eval: evals.q_lookup_eval,
+                    prod_next_eval: evals.prod_next_eval,
+                    h_1_next_eval: evals.h_1_next_eval,
+                    h_2_next_eval: evals.h_2_next_eval,
+                    q_lookup_next_eval: evals.q_lookup_next_eval,
+                    w_3_next_eval: evals.w_3_next_eval,
+                    w_4_next_eval: evals.w_4_next_eval,
+                })
+            },
+            _ => {
+                return Err(ParameterError(
+                    "the proof and the proving key disagree on the lookup support".to_string(),
+                )
+                .into())
+            },
+        };
+        Ok(CompressedProof {
+            wires_poly_comms: self.wires_poly_comms.clone(),
+            prod_perm_poly_comm: self.prod_perm_poly_comm,
+            split_quot_poly_comms: self.split_quot_poly_comms.clone(),
+            opening_proof: self.opening_proof,
+            shifted_opening_proof: self.shifted_opening_proof,
+            poly_evals: self.poly_evals.clone(),
+            plookup_proof,
+        })
+    }
+}
+
+// Tampering with proofs, to check that the verifier rejects malformed ones.
+//
+// WARNING: those APIs are for testing purpose only, under the `test-helpers`
+// feature, and must not be used in production.
+#[cfg(any(test, feature = "test-helpers"))]
+impl<E: PairingEngine> Proof<E> {
+    /// The number of commitments in the proof, in the order of
+    /// [`Self::with_tampered_commitment`].
+    pub fn num_commitments(&self) -> usize {
+        self.wires_poly_comms.len()
+            + self.split_quot_poly_comms.len()
+            // the permutation product commitment and the two opening proofs
+            + 3
+            + self
+                .plookup_proof
+                .as_ref()
+                .map_or(0, |plookup_proof| plookup_proof.h_poly_comms.len() + 1)
+    }
+
+    /// The number of evaluations in the proof, in the order of
+    /// [`Self::with_tampered_evaluation`].
+    pub fn num_evaluations(&self) -> usize {
+        self.poly_evals.wires_evals.len()
+            + self.poly_evals.wire_sigma_evals.len()
+            + 1
+            + self.plookup_proof.as_ref().map_or(0, |plookup_proof| {
+                plookup_proof.poly_evals.evals_vec().len()
+                    + plookup_proof.poly_evals.next_evals_vec().len()
+            })
+    }
+
+    /// A copy of the proof whose `idx`-th commitment is replaced by `comm`.
+    /// The commitments are ordered as the wire polynomials commitments, the
+    /// permutation product commitment, the split quotient commitments, the
+    /// two opening proofs, then for UltraPlonk the sorted vector commitments
+    /// and the lookup product commitment.
+    /// Return error if `idx` is out of bound.
+    pub fn with_tampered_commitment(
+        &self,
+        idx: usize,
+        comm: Commitment<E>,
+    ) -> Result<Self, PlonkError> {
+        if idx >= self.num_commitments() {
+            return Err(ParameterError(format!(
+                "commitment index {} out of bound {}",
+                idx,
+                self.num_commitments()
+            ))
+            .into());
+        }
+        let mut proof = self.clone();
+        *proof.commitments_mut().swap_remove(idx) = comm;
+        Ok(proof)
+    }
+
+    /// A copy of the proof whose `idx`-th evaluation is replaced by `value`.
+    /// The evaluations are ordered as the wire evaluations, the sigma
+    /// evaluations, the permutation product evaluation at `zeta * g`, then
+    /// for UltraPlonk the Plookup evaluations at `zeta` and at `zeta * g`.
+    /// Return error if `idx` is out of bound.
+    pub fn with_tampered_evaluation(&self, idx: usize, value: E::Fr) -> Result<Self, PlonkError> {
+        if idx >= self.num_evaluations() {
+            return Err(ParameterError(format!(
+                "evaluation index {} out of bound {}",
+                idx,
+                self.num_evaluations()
+            ))
+            .into());
+        }
+        let mut proof = self.clone();
+        *proof.evaluations_mut().swap_remove(idx) = value;
+        Ok(proof)
+    }
+
+    // The commitments of the proof, in order.
+    fn commitments_mut(&mut self) -> Vec<&mut Commitment<E>> {
+        let mut comms: Vec<&mut Commitment<E>> = self.wires_poly_comms.iter_mut().collect();
+        comms.push(&mut self.prod_perm_poly_comm);
+        comms.extend(self.split_quot_poly_comms.iter_mut());
+        comms.push(&mut self.opening_proof);
+        comms.push(&mut self.shifted_opening_proof);
+        if let Some(plookup_proof) = self.plookup_proof.as_mut() {
+            comms.extend(plookup_proof.h_poly_comms.iter_mut());
+            comms.push(&mut plookup_proof.prod_lookup_poly_comm);
+        }
+        comms
+    }
+
+    // The evaluations of the proof, in order.
+    fn evaluations_mut(&mut self) -> Vec<&mut E::Fr> {
+        let poly_evals = &mut self.poly_evals;
+        let mut evals: Vec<&mut E::Fr> = poly_evals.wires_evals.iter_mut().collect();
+        evals.extend(poly_evals.wire_sigma_evals.iter_mut());
+        evals.push(&mut poly_evals.perm_next_eval);
+        if let Some(plookup_proof) = self.plookup_proof.as_mut() {
+            let plookup_evals = &mut plookup_proof.poly_evals;
+            evals.extend(vec![
+                &mut plookup_evals.range_table_eval,
+                &mut plookup_evals.key_table_eval,
+                &mut plookup_evals.h_1_eval,
+                &mut plookup_evals.q_lookup_eval,
+                &mut plookup_evals.prod_next_eval,
+                &mut plookup_evals.range_table_next_eval,
+                &mut plookup_evals.key_table_next_eval,
+                &mut plookup_evals.h_1_next_eval,
+                &mut plookup_evals.h_2_next_eval,
+                &mut plookup_evals.q_lookup_next_eval,
+                &mut plookup_evals.w_3_next_eval,
+                &mut plookup_evals.w_4_next_eval,
+            ]);
+        }
+        evals
+    }
+}
+
+/// A Plonk SNARK proof without the evaluations that the verifier can
+/// recompute, obtained by [`Proof::compress`].
 #[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize, Derivative)]
 #[derivative(Hash(bound = "E:PairingEngine"))]
-pub struct PlookupProof<E: PairingEngine> {
+pub struct CompressedProof<E: PairingEngine> {
+    /// Wire witness polynomials commitments.
+    pub(crate) wires_poly_comms: Vec<Commitment<E>>,
+
+    /// The polynomial commitment for the wire permutation argument.
+    pub(crate) prod_perm_poly_comm: Commitment<E>,
+
+    /// Splitted quotient polynomial commitments.
+    pub(crate) split_quot_poly_comms: Vec<Commitment<E>>,
+
+    /// (Aggregated) proof of evaluations at challenge point `zeta`.
+    pub(crate) opening_proof: Commitment<E>,
+
+    /// (Aggregated) proof of evaluation at challenge point `zeta * g` where `g`
+    /// is the root of unity.
+    pub(crate) shifted_opening_proof: Commitment<E>,
+
+    /// Polynomial evaluations.
+    pub(crate) poly_evals: ProofEvaluations<E::Fr>,
+
+    /// The partial proof for Plookup argument, without the table evaluations.
+    pub(crate) plookup_proof: Option<CompressedPlookupProof<E>>,
+}
+
+/// A Plookup argument proof without the evaluations of the range and key
+/// tables, which are replaced by the sizes of the tables.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(Hash(bound = "E:PairingEngine"))]
+pub struct CompressedPlookupProof<E: PairingEngine> {
     /// The commitments for the polynomials that interpolate the sorted
     /// concatenation of the lookup table and the witnesses in the lookup gates.
     pub(crate) h_poly_comms: Vec<Commitment<E>>,
@@ -227,39 +567,199 @@ pub struct PlookupProof<E: PairingEngine> {
     /// The product accumulation polynomial commitment for the Plookup argument
     pub(crate) prod_lookup_poly_comm: Commitment<E>,
 
-    /// Polynomial evaluations.
-    pub(crate) poly_evals: PlookupEvaluations<E::Fr>,
+    /// The size of the range table, whose entries 0, 1, ... are set at the
+    /// first points of the domain.
+    pub(crate) range_table_len: u64,
+
+    /// The size of the key table, whose entries 0, 1, ... are set at the
+    /// points of the domain right before the last one.
+    pub(crate) key_table_len: u64,
+
+    /// The first sorted vector polynomial evaluation at point `zeta`.
+    pub(crate) h_1_eval: E::Fr,
+
+    /// The lookup selector polynomial evaluation at point `zeta`.
+    pub(crate) q_lookup_eval: E::Fr,
+
+    /// Lookup product polynomial evaluation at point `zeta * g`.
+    pub(crate) prod_next_eval: E::Fr,
+
+    /// The first sorted vector polynomial evaluation at point `zeta * g`.
+    pub(crate) h_1_next_eval: E::Fr,
+
+    /// The second sorted vector polynomial evaluation at point `zeta * g`.
+    pub(crate) h_2_next_eval: E::Fr,
+
+    /// The lookup selector polynomial evaluation at point `zeta * g`.
+    pub(crate) q_lookup_next_eval: E::Fr,
+
+    /// The 4th witness polynomial evaluation at point `zeta * g`.
+    pub(crate) w_3_next_eval: E::Fr,
+
+    /// The 5th witness polynomial evaluation at point `zeta * g`.
+    pub(crate) w_4_next_eval: E::Fr,
+}
+
+impl<E: PairingEngine> CompressedProof<E> {
+    /// Rebuild the full proof with the evaluations of the range table at
+    /// `zeta` and `zeta * g` computed by `range_table_evals`, and those of the
+    /// key table computed by `key_table_evals`, both taking the table size.
+    pub(crate) fn to_proof<R, K>(
+        &self,
+        range_table_evals: R,
+        key_table_evals: K,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        R: FnOnce(usize) -> Result<(E::Fr, E::Fr), PlonkError>,
+        K: FnOnce(usize) -> Result<(E::Fr, E::Fr), PlonkError>,
+    {
+        let plookup_proof = match &self.plookup_proof {
+            None => None,
+            Some(proof) => {
+                let (range_table_eval, range_table_next_eval) =
+                    range_table_evals(proof.range_table_len as usize)?;
+                let (key_table_eval, key_table_next_eval) =
+                    key_table_evals(proof.key_table_len as usize)?;
+                Some(PlookupProof {
+                    h_poly_comms: proof.h_poly_comms.clone(),
+                    prod_lookup_poly_comm: proof.prod_lookup_poly_comm,
+                    poly_evals: PlookupEvaluations {
+                        range_table_eval,
+                        key_table_eval,
+                        h_1_eval: proof.h_1_eval,
+                        q_lookup_eval: proof.q_lookup_eval,
+                        prod_next_eval: proof.prod_next_eval,
+                        range_table_next_eval,
+                        key_table_next_eval,
+                        h_1_next_eval: proof.h_1_next_eval,
+                        h_2_next_eval: proof.h_2_next_eval,
+                        q_lookup_next_eval: proof.q_lookup_next_eval,
+                        w_3_next_eval: proof.w_3_next_eval,
+                        w_4_next_eval: proof.w_4_next_eval,
+                    },
+                })
+            },
+        };
+        Ok(Proof {
+            wires_poly_comms: self.wires_poly_comms.clone(),
+            prod_perm_poly_comm: self.prod_perm_poly_comm,
+            split_quot_poly_comms: self.split_quot_poly_comms.clone(),
+            opening_proof: self.opening_proof,
+            shifted_opening_proof: self.shifted_opening_proof,
+            poly_evals: self.poly_evals.clone(),
+            plookup_proof,
+        })
+    }
+}
+
+impl<E, F, P> CompressedProof<E>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + Clone,
+{
+    /// Rebuild the full proof obtained by [`Proof::compress`], recomputing the
+    /// evaluations of the range and key tables at the challenge points from
+    /// the sizes of the tables. The challenges depend on `public_input` and
+    /// `extra_transcript_init_msg`, which must be the ones of the proof.
+    /// Return error if the table sizes do not fit in the domain of
+    /// `verify_key`.
+    pub fn decompress<T>(
+        &self,
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        // the challenge `zeta` is derived before any evaluation enters the
+        // transcript, so placeholder table evaluations do not affect it
+        let placeholder = self.to_proof(
+            |_| Ok((E::Fr::zero(), E::Fr::zero())),
+            |_| Ok((E::Fr::zero(), E::Fr::zero())),
+        )?;
+        let challenges = Verifier::<KzgPcs<E>>::compute_challenges::<T>(
+            &[verify_key],
+            &[public_input],
+            &(&placeholder).into(),
+            &extra_transcript_init_msg,
+        )?;
+        let n = verify_key.domain_size;
+        let domain =
+            Radix2EvaluationDomain::<E::Fr>::new(n).ok_or(PlonkError::DomainCreationError)?;
+        let zeta = challenges.zeta;
+        let zeta_omega = zeta * domain.group_gen;
+        self.to_proof(
+            |range_len| {
+                if range_len > n {
+                    return Err(ParameterError(format!(
+                        "the range table size {} exceeds the domain size {}",
+                        range_len, n
+                    ))
+                    .into());
+                }
+                Ok((
+                    eval_progression_table(&domain, 0, range_len, zeta),
+                    eval_progression_table(&domain, 0, range_len, zeta_omega),
+                ))
+            },
+            |key_len| {
+                if key_len >= n {
+                    return Err(ParameterError(format!(
+                        "the key table size {} exceeds the domain size {} minus one",
+                        key_len, n
+                    ))
+                    .into());
+                }
+                let offset = n - 1 - key_len;
+                Ok((
+                    eval_progression_table(&domain, offset, key_len, zeta),
+                    eval_progression_table(&domain, offset, key_len, zeta_omega),
+                ))
+            },
+        )
+    }
 }
 
 /// An aggregated SNARK proof that batchly proving multiple instances.
 #[tagged_blob("BATCHPROOF")]
-#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize, Derivative)]
-#[derivative(Hash(bound = "E:PairingEngine"))]
-pub struct BatchProof<E: PairingEngine> {
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = ""),
+    Clone(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = ""),
+    Hash(bound = "")
+)]
+pub struct PcsBatchProof<PCS: PolynomialCommitmentScheme> {
     /// The list of wire witness polynomials commitments.
-    pub(crate) wires_poly_comms_vec: Vec<Vec<Commitment<E>>>,
+    pub(crate) wires_poly_comms_vec: Vec<Vec<PCS::Commitment>>,
 
     /// The list of polynomial commitment for the wire permutation argument.
-    pub(crate) prod_perm_poly_comms_vec: Vec<Commitment<E>>,
+    pub(crate) prod_perm_poly_comms_vec: Vec<PCS::Commitment>,
 
     /// The list of polynomial evaluations.
-    pub(crate) poly_evals_vec: Vec<ProofEvaluations<E::Fr>>,
+    pub(crate) poly_evals_vec: Vec<ProofEvaluations<PCS::Fr>>,
 
     /// The list of partial proofs for Plookup argument
-    pub(crate) plookup_proofs_vec: Vec<Option<PlookupProof<E>>>,
+    pub(crate) plookup_proofs_vec: Vec<Option<PcsPlookupProof<PCS>>>,
 
     /// Splitted quotient polynomial commitments.
-    pub(crate) split_quot_poly_comms: Vec<Commitment<E>>,
+    pub(crate) split_quot_poly_comms: Vec<PCS::Commitment>,
 
     /// (Aggregated) proof of evaluations at challenge point `zeta`.
-    pub(crate) opening_proof: Commitment<E>,
+    pub(crate) opening_proof: PCS::Proof,
 
     /// (Aggregated) proof of evaluation at challenge point `zeta * g` where `g`
     /// is the root of unity.
-    pub(crate) shifted_opening_proof: Commitment<E>,
+    pub(crate) shifted_opening_proof: PCS::Proof,
 }
 
-impl<E: PairingEngine> BatchProof<E> {
+/// An aggregated SNARK proof over the KZG polynomial commitment scheme.
+pub type BatchProof<E> = PcsBatchProof<KzgPcs<E>>;
+
+impl<PCS: PolynomialCommitmentScheme> PcsBatchProof<PCS> {
     /// The number of instances being proved in a batch proof.
     pub fn len(&self) -> usize {
         self.prod_perm_poly_comms_vec.len()
@@ -268,6 +768,9 @@ impl<E: PairingEngine> BatchProof<E> {
     pub fn is_empty(&self) -> bool {
         self.prod_perm_poly_comms_vec.is_empty()
     }
+}
+
+impl<E: PairingEngine> BatchProof<E> {
     /// Create a dummy batch proof over `n` TurboPlonk instances.
     pub fn dummy(n: usize) -> Self {
         let num_wire_types = GATE_WIDTH + 1;
@@ -283,8 +786,8 @@ impl<E: PairingEngine> BatchProof<E> {
     }
 }
 
-impl<E: PairingEngine> From<Proof<E>> for BatchProof<E> {
-    fn from(proof: Proof<E>) -> Self {
+impl<PCS: PolynomialCommitmentScheme> From<PcsProof<PCS>> for PcsBatchProof<PCS> {
+    fn from(proof: PcsProof<PCS>) -> Self {
         Self {
             wires_poly_comms_vec: vec![proof.wires_poly_comms],
             prod_perm_poly_comms_vec: vec![proof.prod_perm_poly_comm],
@@ -297,6 +800,69 @@ impl<E: PairingEngine> From<Proof<E>> for BatchProof<E> {
     }
 }
 
+/// A borrowed view of the parts of a batch proof, read by the verifier. A
+/// single proof is viewed as a batch of one instance, without copying it.
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), Copy(bound = ""))]
+pub(crate) struct BatchProofView<'p, PCS: PolynomialCommitmentScheme> {
+    /// The list of wire witness polynomials commitments.
+    pub(crate) wires_poly_comms_vec: &'p [Vec<PCS::Commitment>],
+
+    /// The list of polynomial commitment for the wire permutation argument.
+    pub(crate) prod_perm_poly_comms_vec: &'p [PCS::Commitment],
+
+    /// The list of polynomial evaluations.
+    pub(crate) poly_evals_vec: &'p [ProofEvaluations<PCS::Fr>],
+
+    /// The list of partial proofs for Plookup argument
+    pub(crate) plookup_proofs_vec: &'p [Option<PcsPlookupProof<PCS>>],
+
+    /// Splitted quotient polynomial commitments.
+    pub(crate) split_quot_poly_comms: &'p [PCS::Commitment],
+
+    /// (Aggregated) proof of evaluations at challenge point `zeta`.
+    pub(crate) opening_proof: &'p PCS::Proof,
+
+    /// (Aggregated) proof of evaluation at challenge point `zeta * g` where `g`
+    /// is the root of unity.
+    pub(crate) shifted_opening_proof: &'p PCS::Proof,
+}
+
+impl<'p, PCS: PolynomialCommitmentScheme> BatchProofView<'p, PCS> {
+    /// The number of instances being proved in the batch proof.
+    pub(crate) fn len(&self) -> usize {
+        self.prod_perm_poly_comms_vec.len()
+    }
+}
+
+impl<'p, PCS: PolynomialCommitmentScheme> From<&'p PcsBatchProof<PCS>> for BatchProofView<'p, PCS> {
+    fn from(batch_proof: &'p PcsBatchProof<PCS>) -> Self {
+        Self {
+            wires_poly_comms_vec: &batch_proof.wires_poly_comms_vec,
+            prod_perm_poly_comms_vec: &batch_proof.prod_perm_poly_comms_vec,
+            poly_evals_vec: &batch_proof.poly_evals_vec,
+            plookup_proofs_vec: &batch_proof.plookup_proofs_vec,
+            split_quot_poly_comms: &batch_proof.split_quot_poly_comms,
+            opening_proof: &batch_proof.opening_proof,
+            shifted_opening_proof: &batch_proof.shifted_opening_proof,
+        }
+    }
+}
+
+impl<'p, PCS: PolynomialCommitmentScheme> From<&'p PcsProof<PCS>> for BatchProofView<'p, PCS> {
+    fn from(proof: &'p PcsProof<PCS>) -> Self {
+        Self {
+            wires_poly_comms_vec: core::slice::from_ref(&proof.wires_poly_comms),
+            prod_perm_poly_comms_vec: core::slice::from_ref(&proof.prod_perm_poly_comm),
+            poly_evals_vec: core::slice::from_ref(&proof.poly_evals),
+            plookup_proofs_vec: core::slice::from_ref(&proof.plookup_proof),
+            split_quot_poly_comms: &proof.split_quot_poly_comms,
+            opening_proof: &proof.opening_proof,
+            shifted_opening_proof: &proof.shifted_opening_proof,
+        }
+    }
+}
+
 impl<T: PrimeField> ProofEvaluations<T> {
     /// create variables for the ProofEvaluations who's field
     /// is smaller than plonk circuit field.
@@ -470,7 +1036,7 @@ where
 }
 
 /// A struct that stores the polynomial evaluations in a Plookup argument proof.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
 pub struct PlookupEvaluations<F: Field> {
     /// Range table polynomial evaluation at point `zeta`.
     pub(crate) range_table_eval: F,
@@ -536,58 +1102,187 @@ impl<F: Field> PlookupEvaluations<F> {
 }
 
 /// Preprocessed prover parameters used to compute Plonk proofs for a certain
-/// circuit.
-#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct ProvingKey<'a, E: PairingEngine> {
+/// circuit over the polynomial commitment scheme `PCS`.
+///
+/// The key can be persisted with its compressed or uncompressed canonical
+/// serialization, and the reloaded key computes the same proofs.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), PartialEq(bound = ""))]
+pub struct PcsProvingKey<'a, PCS: PcsCommitter<'a>> {
     /// Extended permutation (sigma) polynomials.
-    pub(crate) sigmas: Vec<DensePolynomial<E::Fr>>,
+    pub(crate) sigmas: Vec<DensePolynomial<PCS::Fr>>,
 
     /// Selector polynomials.
-    pub(crate) selectors: Vec<DensePolynomial<E::Fr>>,
+    pub(crate) selectors: Vec<DensePolynomial<PCS::Fr>>,
 
-    // KZG PCS committing key.
-    pub(crate) commit_key: CommitKey<'a, E>,
+    // PCS committing key.
+    pub(crate) commit_key: PCS::CommitKey,
 
     /// The verifying key. It is used by prover to initialize transcripts.
-    pub vk: VerifyingKey<E>,
+    pub vk: PcsVerifyingKey<PCS>,
 
     /// Proving key for Plookup, None if not support lookup.
-    pub(crate) plookup_pk: Option<PlookupProvingKey<E>>,
+    pub(crate) plookup_pk: Option<PcsPlookupProvingKey<PCS>>,
 }
 
+/// Preprocessed prover parameters used to compute Plonk proofs for a certain
+/// circuit over the KZG polynomial commitment scheme.
+pub type ProvingKey<'a, E> = PcsProvingKey<'a, KzgPcs<E>>;
+
 /// Preprocessed prover parameters used to compute Plookup proofs for a certain
 /// circuit.
-#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct PlookupProvingKey<E: PairingEngine> {
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), PartialEq(bound = ""))]
+pub struct PcsPlookupProvingKey<PCS: PolynomialCommitmentScheme> {
     /// Range table polynomial.
-    pub(crate) range_table_poly: DensePolynomial<E::Fr>,
+    pub(crate) range_table_poly: DensePolynomial<PCS::Fr>,
 
     /// Key table polynomial.
-    pub(crate) key_table_poly: DensePolynomial<E::Fr>,
+    pub(crate) key_table_poly: DensePolynomial<PCS::Fr>,
 }
 
-impl<'a, E: PairingEngine> ProvingKey<'a, E> {
+/// Preprocessed prover parameters used to compute Plookup proofs over the KZG
+/// polynomial commitment scheme.
+pub type PlookupProvingKey<E> = PcsPlookupProvingKey<KzgPcs<E>>;
+
+impl<'a, PCS: PcsCommitter<'a>> PcsProvingKey<'a, PCS> {
     /// The size of the evaluation domain. Should be a power of two.
     pub(crate) fn domain_size(&self) -> usize {
         self.vk.domain_size
     }
     /// The number of public inputs.
-    #[allow(dead_code)]
     pub(crate) fn num_inputs(&self) -> usize {
         self.vk.num_inputs
     }
     /// The constants K0, ..., K4 that ensure wire subsets are disjoint.
-    pub(crate) fn k(&self) -> &[E::Fr] {
+    pub(crate) fn k(&self) -> &[PCS::Fr] {
         &self.vk.k
     }
 
     /// The lookup selector polynomial
-    pub(crate) fn q_lookup_poly(&self) -> Result<&DensePolynomial<E::Fr>, PlonkError> {
+    pub(crate) fn q_lookup_poly(&self) -> Result<&DensePolynomial<PCS::Fr>, PlonkError> {
         if self.plookup_pk.is_none() {
             return Err(SnarkLookupUnsupported.into());
         }
         Ok(self.selectors.last().unwrap())
     }
+}
+
+impl<'a, E: PairingEngine> ProvingKey<'a, E> {
+    /// The KZG commitment to the public input polynomial of the proofs of
+    /// `public_inputs` w.r.t. the key, i.e. the polynomial interpolating the
+    /// public inputs at their positions of the evaluation domain and zero
+    /// elsewhere, as computed by the prover. The commitment is not hiding.
+    ///
+    /// The proofs don't contain it: the verifier evaluates the polynomial
+    /// instead, see [`VerifyingKey::evaluate_public_input_poly`], against
+    /// which an opening of this commitment binds an external commitment to
+    /// the same public inputs. The verifier computes the same commitment from
+    /// a [`VerifierPrecompute`] with [`VerifierPrecompute::commit_public_inputs`].
+    /// Return error if the number of public inputs doesn't match the key.
+    pub fn commit_public_inputs(
+        &self,
+        public_inputs: &[E::Fr],
+    ) -> Result<Commitment<E>, PlonkError> {
+        if public_inputs.len() != self.num_inputs() {
+            return Err(ParameterError(format!(
+                "the number of public inputs {} != the number of public inputs of the key {}",
+                public_inputs.len(),
+                self.num_inputs()
+            ))
+            .into());
+        }
+        let domain = Radix2EvaluationDomain::<E::Fr>::new(self.domain_size())
+            .ok_or(PlonkError::DomainCreationError)?;
+        // the inputs of a merged circuit are laid out from both ends of the
+        // domain
+        let n = domain.size();
+        let len = match self.vk.is_merged {
+            false => public_inputs.len(),
+            true => public_inputs.len() / 2,
+        };
+        let mut evals = vec![E::Fr::zero(); n];
+        evals[..len].copy_from_slice(&public_inputs[..len]);
+        for (i, val) in public_inputs[len..].iter().enumerate() {
+            evals[n - i - 1] = *val;
+        }
+        let poly = DensePolynomial::from_coefficients_vec(domain.ifft(&evals));
+        Ok(KZG10::commit(&self.commit_key, &poly, None, None)?.0)
+    }
+
+    /// Check that the proving key matches the verifying key `vk`, i.e. the
+    /// polynomials of the proving key commit to the commitments of `vk`,
+    /// and both keys are for the same domain size and number of public
+    /// inputs. This commits to all the preprocessed polynomials again, and is
+    /// meant for debugging.
+    /// Return error describing the first mismatch found.
+    pub fn check_consistency(&self, vk: &VerifyingKey<E>) -> Result<(), PlonkError> {
+        if self.domain_size() != vk.domain_size || self.num_inputs() != vk.num_inputs {
+            return Err(ParameterError(format!(
+                "the proving key has domain size {} and {} public inputs, the verifying key {} and {}",
+                self.domain_size(),
+                self.num_inputs(),
+                vk.domain_size,
+                vk.num_inputs
+            ))
+            .into());
+        }
+        if self.vk.num_quot_chunks != vk.num_quot_chunks {
+            return Err(ParameterError(format!(
+                "the proving key splits the quotient polynomial into {} chunks, the verifying key {}",
+                self.vk.num_quot_chunks, vk.num_quot_chunks
+            ))
+            .into());
+        }
+        let commit = |poly: &DensePolynomial<E::Fr>| -> Result<Commitment<E>, PlonkError> {
+            Ok(KZG10::commit(&self.commit_key, poly, None, None)?.0)
+        };
+        let check_comms = |name: &str,
+                           polys: &[DensePolynomial<E::Fr>],
+                           comms: &[Commitment<E>]|
+         -> Result<(), PlonkError> {
+            if polys.len() != comms.len() {
+                return Err(ParameterError(format!(
+                    "the proving key has {} {} polynomials, the verifying key {} commitments",
+                    polys.len(),
+                    name,
+                    comms.len()
+                ))
+                .into());
+            }
+            for (i, (poly, comm)) in polys.iter().zip(comms.iter()).enumerate() {
+                if commit(poly)? != *comm {
+                    return Err(ParameterError(format!(
+                        "the {} polynomial {} of the proving key does not match the verifying key",
+                        name, i
+                    ))
+                    .into());
+                }
+            }
+            Ok(())
+        };
+        check_comms("selector", &self.selectors, &vk.selector_comms)?;
+        check_comms("sigma", &self.sigmas, &vk.sigma_comms)?;
+        match (self.plookup_pk.as_ref(), vk.plookup_vk.as_ref()) {
+            (None, None) => Ok(()),
+            (Some(plookup_pk), Some(plookup_vk)) => {
+                check_comms(
+                    "range table",
+                    &[plookup_pk.range_table_poly.clone()],
+                    &[plookup_vk.range_table_comm],
+                )?;
+                check_comms(
+                    "key table",
+                    &[plookup_pk.key_table_poly.clone()],
+                    &[plookup_vk.key_table_comm],
+                )
+            },
+            _ => Err(ParameterError(
+                "only one of the proving and verifying keys supports lookup".to_string(),
+            )
+            .into()),
+        }
+    }
 
     /// Merge with another TurboPlonk proving key to obtain a new TurboPlonk
     /// proving key. Return error if any of the following holds:
@@ -636,10 +1331,136 @@ impl<'a, E: PairingEngine> ProvingKey<'a, E> {
     }
 }
 
+/// The committed wire witness polynomials of a proof, which can be opened
+/// after proving. The polynomials are the masked ones committed to in the
+/// proof: at the i-th point of the evaluation domain, they evaluate to the
+/// wire values of the i-th gate.
+///
+/// Each wire polynomial is masked with a random multiple of degree `k + 1` of
+/// the vanishing polynomial of the evaluation domain, for the number `k` of
+/// extra openings requested when proving. Its evaluations at `zeta`, where the
+/// proof opens it, and at up to `k` other points outside of the evaluation
+/// domain are thus uniformly random and independent of the witness, and the
+/// openings are zero-knowledge. Openings at the points of the evaluation
+/// domain, which reveal the wire values, and at more than `k` points besides
+/// `zeta` are rejected. The polynomials can't be cloned, so that the limit
+/// holds.
+#[derive(Debug)]
+pub struct WitnessCommitments<'a, E: PairingEngine> {
+    /// The wire witness polynomials.
+    pub(crate) wire_polys: Vec<DensePolynomial<E::Fr>>,
+
+    /// Their commitments, as in the proof.
+    pub(crate) wire_comms: Vec<Commitment<E>>,
+
+    // KZG PCS committing key.
+    pub(crate) commit_key: CommitKey<'a, E>,
+
+    /// The size of the evaluation domain.
+    pub(crate) domain_size: usize,
+
+    /// The challenge `zeta` at which the proof opens the polynomials.
+    pub(crate) zeta: E::Fr,
+
+    /// The number of points other than `zeta` at which each polynomial can be
+    /// opened.
+    pub(crate) max_extra_openings: usize,
+
+    /// The points other than `zeta` at which each polynomial was opened.
+    pub(crate) extra_points: Vec<Vec<E::Fr>>,
+}
+
+impl<'a, E: PairingEngine> WitnessCommitments<'a, E> {
+    /// The commitments to the wire witness polynomials.
+    pub fn wire_comms(&self) -> &[Commitment<E>] {
+        &self.wire_comms
+    }
+
+    /// Open the `index`-th wire witness polynomial at `point`, and return its
+    /// evaluation along with the opening proof, to be checked with
+    /// [`VerifyingKey::verify_witness_opening`]. Opening again at a point
+    /// already opened reveals nothing more.
+    /// Return error if `index` is not smaller than the number of wire types,
+    /// if `point` is in the evaluation domain, or if the polynomial was
+    /// already opened at the maximal number of points other than `point` and
+    /// `zeta`.
+    pub fn open_witness(
+        &mut self,
+        point: &E::Fr,
+        index: usize,
+    ) -> Result<(E::Fr, Commitment<E>), PlonkError> {
+        let poly = self.wire_polys.get(index).ok_or_else(|| {
+            ParameterError(format!(
+                "wire index {} >= the number of wire types {}",
+                index,
+                self.wire_polys.len()
+            ))
+        })?;
+        if point.pow([self.domain_size as u64]).is_one() {
+            return Err(ParameterError(
+                "cannot open a wire polynomial in the evaluation domain".to_string(),
+            )
+            .into());
+        }
+        let extra_points = &mut self.extra_points[index];
+        if *point != self.zeta && !extra_points.contains(point) {
+            if extra_points.len() == self.max_extra_openings {
+                return Err(ParameterError(format!(
+                    "the {}-th wire polynomial was already opened at {} other points",
+                    index, self.max_extra_openings
+                ))
+                .into());
+            }
+            extra_points.push(*point);
+        }
+        let empty_rand = Randomness::<E::Fr, DensePolynomial<E::Fr>>::empty();
+        let (witness_poly, _) = KZG10::<E, DensePolynomial<E::Fr>>::compute_witness_polynomial(
+            poly,
+            *point,
+            &empty_rand,
+        )?;
+        let (opening_proof, _) = KZG10::commit(&self.commit_key, &witness_poly, None, None)?;
+        Ok((poly.evaluate(point), opening_proof))
+    }
+}
+
+/// The state of a proof between the commitment to its wire witness
+/// polynomials with `PlonkKzgSnark::commit_witness` and its completion with
+/// `PlonkKzgSnark::finish_prove`.
+///
+/// The state can't be cloned: finishing it twice would open the same masked
+/// wire polynomials at two challenges, leaking the witness.
+#[derive(Debug)]
+pub struct ProverState<'s, 'a, E: PairingEngine, C> {
+    /// The circuit being proved.
+    pub(crate) circuit: &'s C,
+
+    /// Its proving key.
+    pub(crate) prove_key: &'s ProvingKey<'a, E>,
+
+    /// The masked wire witness polynomials.
+    pub(crate) wire_polys: Vec<DensePolynomial<E::Fr>>,
+
+    /// Their commitments.
+    pub(crate) wire_comms: Vec<Commitment<E>>,
+}
+
+impl<'s, 'a, E: PairingEngine, C> ProverState<'s, 'a, E, C> {
+    /// The commitments to the wire witness polynomials, which the finished
+    /// proof contains.
+    pub fn wire_comms(&self) -> &[Commitment<E>] {
+        &self.wire_comms
+    }
+}
+
 /// Preprocessed verifier parameters used to verify Plonk proofs for a certain
-/// circuit.
-#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct VerifyingKey<E: PairingEngine> {
+/// circuit over the polynomial commitment scheme `PCS`.
+///
+/// The key can be persisted with its compressed or uncompressed canonical
+/// serialization, the compressed one being the most compact.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), PartialEq(bound = ""))]
+pub struct PcsVerifyingKey<PCS: PolynomialCommitmentScheme> {
     /// The size of the evaluation domain. Should be a power of two.
     pub(crate) domain_size: usize,
 
@@ -647,23 +1468,189 @@ pub struct VerifyingKey<E: PairingEngine> {
     pub(crate) num_inputs: usize,
 
     /// The permutation polynomial commitments. The commitments are not hiding.
-    pub(crate) sigma_comms: Vec<Commitment<E>>,
+    pub(crate) sigma_comms: Vec<PCS::Commitment>,
 
     /// The selector polynomial commitments. The commitments are not hiding.
-    pub(crate) selector_comms: Vec<Commitment<E>>,
+    pub(crate) selector_comms: Vec<PCS::Commitment>,
 
     /// The constants K0, ..., K_num_wire_types that ensure wire subsets are
     /// disjoint.
-    pub(crate) k: Vec<E::Fr>,
+    pub(crate) k: Vec<PCS::Fr>,
 
-    /// KZG PCS opening key.
-    pub open_key: OpenKey<E>,
+    /// PCS opening key.
+    pub open_key: PCS::OpenKey,
 
     /// A flag indicating whether the key is a merged key.
     pub(crate) is_merged: bool,
 
     /// Plookup verifying key, None if not support lookup.
-    pub(crate) plookup_vk: Option<PlookupVerifyingKey<E>>,
+    pub(crate) plookup_vk: Option<PcsPlookupVerifyingKey<PCS>>,
+
+    /// The custom gates of the circuit, whose selector commitments follow the
+    /// other selector commitments.
+    pub(crate) custom_gates: Vec<CustomGateSpec<PCS::Fr>>,
+
+    /// The number of polynomials the quotient polynomial is split into, by
+    /// default the number of wire types.
+    pub(crate) num_quot_chunks: usize,
+}
+
+/// Preprocessed verifier parameters used to verify Plonk proofs for a certain
+/// circuit over the KZG polynomial commitment scheme.
+pub type VerifyingKey<E> = PcsVerifyingKey<KzgPcs<E>>;
+
+/// The verifier data of a verifying key that does not depend on the proof:
+/// the evaluation domain and the Lagrange bases of the public inputs. It is
+/// computed once and reused by
+/// [`crate::proof_system::PlonkKzgSnark::verify_with_precompute`] for all the
+/// proofs w.r.t. the key.
+pub struct VerifierPrecompute<E: PairingEngine> {
+    pub(crate) vk: VerifyingKey<E>,
+    pub(crate) verifier: Verifier<KzgPcs<E>>,
+    /// The commitments to the Lagrange bases of the evaluation domain at the
+    /// positions of the public inputs, if computed. The commitments are not
+    /// hiding.
+    pub(crate) pi_lagrange_comms: Option<Vec<Commitment<E>>>,
+}
+
+impl<E: PairingEngine> VerifierPrecompute<E> {
+    /// Precompute the verifier data of `vk`.
+    pub fn new(vk: &VerifyingKey<E>) -> Result<Self, PlonkError> {
+        let domain = Radix2EvaluationDomain::<E::Fr>::new(vk.domain_size)
+            .ok_or(PlonkError::DomainCreationError)?;
+        let pi_bases = PiLagrangeBases::new(&domain, vk.num_inputs, vk.is_merged)?;
+        Ok(Self {
+            vk: vk.clone(),
+            verifier: Verifier {
+                domain,
+                pi_bases: Some(pi_bases),
+            },
+            pi_lagrange_comms: None,
+        })
+    }
+
+    /// Same as [`Self::new`], but also commit to the Lagrange bases of the
+    /// public inputs of `vk` with the powers of `srs`, for
+    /// [`Self::commit_public_inputs`]. This costs one MSM of the size of the
+    /// evaluation domain per public input.
+    /// Return error if `srs` is too small for the domain of `vk`, or if it is
+    /// not the SRS `vk` was preprocessed from.
+    pub fn new_with_public_input_commitments(
+        vk: &VerifyingKey<E>,
+        srs: &UniversalSrs<E>,
+    ) -> Result<Self, PlonkError> {
+        let mut precompute = Self::new(vk)?;
+        let n = vk.domain_size;
+        check_srs_size(srs, n - 1)?;
+        if srs.0.powers_of_g[0] != vk.open_key.g
+            || srs.0.h != vk.open_key.h
+            || srs.0.beta_h != vk.open_key.beta_h
+        {
+            return Err(
+                ParameterError("the SRS is not the one of the verifying key".to_string()).into(),
+            );
+        }
+        let domain = &precompute.verifier.domain;
+        let len = match vk.is_merged {
+            false => vk.num_inputs,
+            true => vk.num_inputs / 2,
+        };
+        let bases = &srs.0.powers_of_g[..n];
+        // L_i(X) = sum_j (g^{-i})^j X^j / n, for the i-th element g^i of the
+        // domain
+        let comms = (0..len)
+            .chain((0..vk.num_inputs - len).map(|i| n - i - 1))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|i| {
+                let point_inv = domain.element(n - i);
+                let mut coeff = domain.size_inv;
+                let scalars: Vec<_> = (0..n)
+                    .map(|_| {
+                        let scalar = coeff.into_repr();
+                        coeff *= point_inv;
+                        scalar
+                    })
+                    .collect();
+                Commitment(VariableBaseMSM::multi_scalar_mul(bases, &scalars).into_affine())
+            })
+            .collect();
+        precompute.pi_lagrange_comms = Some(comms);
+        Ok(precompute)
+    }
+
+    /// The commitment to the public input polynomial of the proofs of
+    /// `public_inputs` w.r.t. the key, computed from the commitments to the
+    /// Lagrange bases of the public inputs without the committing key. It is
+    /// the commitment of [`ProvingKey::commit_public_inputs`].
+    /// Return error if the number of public inputs doesn't match the key, or
+    /// if the precomputation is not from
+    /// [`Self::new_with_public_input_commitments`].
+    pub fn commit_public_inputs(
+        &self,
+        public_inputs: &[E::Fr],
+    ) -> Result<Commitment<E>, PlonkError> {
+        if public_inputs.len() != self.vk.num_inputs {
+            return Err(ParameterError(format!(
+                "the number of public inputs {} != the number of public inputs of the key {}",
+                public_inputs.len(),
+                self.vk.num_inputs
+            ))
+            .into());
+        }
+        let pi_lagrange_comms = self.pi_lagrange_comms.as_ref().ok_or_else(|| {
+            ParameterError(
+                "the Lagrange bases of the public inputs are not committed to".to_string(),
+            )
+        })?;
+        let bases: Vec<E::G1Affine> = pi_lagrange_comms.iter().map(|comm| comm.0).collect();
+        let scalars: Vec<_> = public_inputs
+            .iter()
+            .map(|input| input.into_repr())
+            .collect();
+        Ok(Commitment(
+            VariableBaseMSM::multi_scalar_mul(&bases, &scalars).into_affine(),
+        ))
+    }
+
+    /// The verifying key of the precomputed data.
+    pub fn verifying_key(&self) -> &VerifyingKey<E> {
+        &self.vk
+    }
+}
+
+/// A compact record of a successful verification, to be logged for auditing
+/// without the proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VerificationReceipt {
+    /// Keccak-256 digest of the canonical serialization of the verifying key.
+    pub vk_digest: [u8; 32],
+    /// Keccak-256 digest of the canonical serialization of the public input,
+    /// as a vector of field elements.
+    pub input_digest: [u8; 32],
+    /// Timestamp supplied by the caller.
+    pub timestamp: u64,
+}
+
+impl VerificationReceipt {
+    /// Create the receipt of the verification w.r.t. `verify_key` and
+    /// `public_input` at `timestamp`. It does not verify anything: auditors
+    /// can use it to recompute the digests of a logged receipt.
+    pub fn new<E: PairingEngine>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::Fr],
+        timestamp: u64,
+    ) -> Result<Self, PlonkError> {
+        let mut vk_bytes = Vec::new();
+        verify_key.serialize(&mut vk_bytes)?;
+        let mut input_bytes = Vec::new();
+        public_input.to_vec().serialize(&mut input_bytes)?;
+        Ok(Self {
+            vk_digest: Keccak256::digest(&vk_bytes).into(),
+            input_digest: Keccak256::digest(&input_bytes).into(),
+            timestamp,
+        })
+    }
 }
 
 impl<E, F, P1, P2> From<VerifyingKey<E>> for Vec<E::Fq>
@@ -677,6 +1664,12 @@ where
         if vk.plookup_vk.is_some() {
             panic!("Only support TurboPlonk VerifyingKey for now.");
         }
+        if !vk.custom_gates.is_empty() {
+            panic!("Do not support VerifyingKey with custom gates for now.");
+        }
+        if vk.num_quot_chunks != vk.sigma_comms.len() {
+            panic!("Only support the default quotient polynomial split for now.");
+        }
 
         [
             vec![E::Fq::from(vk.domain_size as u64)],
@@ -725,18 +1718,116 @@ where
     }
 }
 
+/// Expected degrees of the polynomials of the Plonk scheme for a circuit of a
+/// given size `n`, as returned by [`VerifyingKey::expected_poly_degrees`].
+///
+/// The online polynomials are masked by random multiples of the vanishing
+/// polynomial, hence reach their degrees with overwhelming probability; the
+/// preprocessed polynomials interpolate `n` evaluations and have degrees at
+/// most `n - 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolyDegrees {
+    /// Degree of each wire witness polynomial: `n + 1`.
+    pub wire: usize,
+    /// Degree of the permutation grand product polynomial: `n + 2`.
+    pub prod_perm: usize,
+    /// Degrees of the split quotient polynomials: `n + 1` for all of them but
+    /// the last one with the default split, which takes the remaining
+    /// coefficients of the quotient polynomial of degree
+    /// `num_wire_types * (n + 1) + 2`. With other splits, they are all of the
+    /// same degree but the last one too.
+    pub split_quot: Vec<usize>,
+    /// Maximal degree of the selector polynomials: `n - 1`.
+    pub selector: usize,
+    /// Maximal degree of the permutation polynomials: `n - 1`.
+    pub sigma: usize,
+    /// Degrees of the Plookup polynomials, None if not support lookup.
+    pub plookup: Option<PlookupPolyDegrees>,
+}
+
+/// Expected degrees of the Plookup polynomials for a circuit of size `n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlookupPolyDegrees {
+    /// Maximal degree of the range and key table polynomials: `n - 1`.
+    pub table: usize,
+    /// Degree of each polynomial of the sorted concatenation of the lookup
+    /// table and witnesses: `n + 2`.
+    pub h: usize,
+    /// Degree of the Plookup grand product polynomial: `n + 2`.
+    pub prod_lookup: usize,
+}
+
 /// Preprocessed verifier parameters used to verify Plookup proofs for a certain
 /// circuit.
-#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct PlookupVerifyingKey<E: PairingEngine> {
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), PartialEq(bound = ""))]
+pub struct PcsPlookupVerifyingKey<PCS: PolynomialCommitmentScheme> {
     /// Range table polynomial commitment. The commitment is not hiding.
-    pub(crate) range_table_comm: Commitment<E>,
+    pub(crate) range_table_comm: PCS::Commitment,
 
     /// Key table polynomial commitment. The commitment is not hiding.
-    pub(crate) key_table_comm: Commitment<E>,
+    pub(crate) key_table_comm: PCS::Commitment,
+}
+
+/// Preprocessed verifier parameters used to verify Plookup proofs over the KZG
+/// polynomial commitment scheme.
+pub type PlookupVerifyingKey<E> = PcsPlookupVerifyingKey<KzgPcs<E>>;
+
+impl<E, F, P> VerifyingKey<E>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + Clone,
+{
+    /// The evaluation at `point` of the public input polynomial of the
+    /// proofs of `public_inputs` w.r.t. the key, computed from the Lagrange
+    /// bases of the domain as the verifier does at the challenge `zeta`. It
+    /// is the evaluation of the polynomial committed by
+    /// [`ProvingKey::commit_public_inputs`].
+    /// Return error if the number of public inputs doesn't match the key.
+    pub fn evaluate_public_input_poly(
+        &self,
+        public_inputs: &[E::Fr],
+        point: &E::Fr,
+    ) -> Result<E::Fr, PlonkError> {
+        if public_inputs.len() != self.num_inputs {
+            return Err(ParameterError(format!(
+                "the number of public inputs {} != the number of public inputs of the key {}",
+                public_inputs.len(),
+                self.num_inputs
+            ))
+            .into());
+        }
+        let verifier = Verifier::<KzgPcs<E>>::new(self.domain_size)?;
+        let vanish_eval = verifier.domain.evaluate_vanishing_polynomial(*point);
+        verifier.evaluate_pi_poly(public_inputs, point, &vanish_eval, self.is_merged)
+    }
 }
 
 impl<E: PairingEngine> VerifyingKey<E> {
+    /// Check the opening proof `opening_proof` of the polynomial committed in
+    /// `comm` at `point`, from [`WitnessCommitments::open_witness`], against
+    /// the claimed evaluation `value`.
+    pub fn verify_witness_opening(
+        &self,
+        comm: &Commitment<E>,
+        point: &E::Fr,
+        value: &E::Fr,
+        opening_proof: &Commitment<E>,
+    ) -> Result<bool, PlonkError> {
+        let proof = KzgProof {
+            w: opening_proof.0,
+            random_v: None,
+        };
+        Ok(KZG10::<E, DensePolynomial<E::Fr>>::check(
+            &self.open_key,
+            comm,
+            *point,
+            *value,
+            &proof,
+        )?)
+    }
+
     /// Create a dummy TurboPlonk verification key for a circuit with
     /// `num_inputs` public inputs and domain size `domain_size`.
     pub fn dummy(num_inputs: usize, domain_size: usize) -> Self {
@@ -750,8 +1841,86 @@ impl<E: PairingEngine> VerifyingKey<E> {
             open_key: OpenKey::default(),
             is_merged: false,
             plookup_vk: None,
+            custom_gates: vec![],
+            num_quot_chunks: num_wire_types,
         }
     }
+    /// The number of gates of the circuit underlying the key, including the
+    /// padding gates, i.e. the size of the evaluation domain.
+    pub fn circuit_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// The number of polynomials the quotient polynomial is split into in the
+    /// proofs w.r.t. the key.
+    pub fn num_quotient_chunks(&self) -> usize {
+        self.num_quot_chunks
+    }
+
+    /// The size in bytes of the compressed serialization of the proofs
+    /// w.r.t. the key, which only depends on the shape of the circuit.
+    pub fn proof_size_bytes(&self) -> usize {
+        let num_wire_types = self.sigma_comms.len();
+        let proof = Proof::<E> {
+            wires_poly_comms: vec![Commitment::default(); num_wire_types],
+            prod_perm_poly_comm: Commitment::default(),
+            split_quot_poly_comms: vec![Commitment::default(); self.num_quot_chunks],
+            opening_proof: Commitment::default(),
+            shifted_opening_proof: Commitment::default(),
+            poly_evals: ProofEvaluations {
+                wires_evals: vec![E::Fr::zero(); num_wire_types],
+                wire_sigma_evals: vec![E::Fr::zero(); num_wire_types - 1],
+                perm_next_eval: E::Fr::zero(),
+            },
+            plookup_proof: self.plookup_vk.as_ref().map(|_| PlookupProof {
+                h_poly_comms: vec![Commitment::default(); 2],
+                prod_lookup_poly_comm: Commitment::default(),
+                poly_evals: PlookupEvaluations::default(),
+            }),
+        };
+        proof.serialized_size()
+    }
+
+    /// The Keccak-256 fingerprint of the key, to be compared with a pinned
+    /// value before verifying proofs w.r.t. the expected circuit only.
+    ///
+    /// The domain-separated digest binds all the components of the key,
+    /// including the opening key of the SRS, through their compressed
+    /// canonical serialization. It is thus the same on all machines and for
+    /// all the persisted forms of the key, and changes with the circuit
+    /// structure, but not with its witness.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut bytes = VK_FINGERPRINT_DOMAIN_SEP.to_vec();
+        // safe unwrap: serializing into a vector does not fail
+        self.serialize(&mut bytes).unwrap();
+        Keccak256::digest(&bytes).into()
+    }
+
+    /// The expected degrees of the polynomials of the circuit underlying the
+    /// key, as specified by the Plonk scheme. See [`PolyDegrees`].
+    pub fn expected_poly_degrees(&self) -> PolyDegrees {
+        let n = self.domain_size;
+        let num_wire_types = self.sigma_comms.len();
+        // the wire polynomials are masked with degree-1 multiples of the vanishing
+        // polynomial, the grand products with degree-2 ones
+        let quot_degree = quotient_polynomial_degree(n, num_wire_types);
+        let chunk_size = self.quot_chunk_size();
+        let mut split_quot = vec![chunk_size - 1; self.num_quot_chunks - 1];
+        split_quot.push(quot_degree - (self.num_quot_chunks - 1) * chunk_size);
+        PolyDegrees {
+            wire: n + 1,
+            prod_perm: n + 2,
+            split_quot,
+            selector: n - 1,
+            sigma: n - 1,
+            plookup: self.plookup_vk.as_ref().map(|_| PlookupPolyDegrees {
+                table: n - 1,
+                h: n + 2,
+                prod_lookup: n + 2,
+            }),
+        }
+    }
+
     /// Merge with another TurboPlonk verifying key to obtain a new TurboPlonk
     /// verifying key. Return error if any of the following holds:
     /// 1. the other verifying key has a different domain size;
@@ -778,6 +1947,18 @@ impl<E: PairingEngine> VerifyingKey<E> {
                 ParameterError("cannot merge UltraPlonk verifying keys".to_string()).into(),
             );
         }
+        if !self.custom_gates.is_empty() || !other_vk.custom_gates.is_empty() {
+            return Err(ParameterError(
+                "cannot merge verifying keys with custom gates".to_string(),
+            )
+            .into());
+        }
+        if self.num_quot_chunks != other_vk.num_quot_chunks {
+            return Err(ParameterError(
+                "cannot merge verifying keys with different quotient polynomial splits".to_string(),
+            )
+            .into());
+        }
         let sigma_comms: Vec<Commitment<E>> = self
             .sigma_comms
             .iter()
@@ -800,11 +1981,123 @@ impl<E: PairingEngine> VerifyingKey<E> {
             open_key: self.open_key.clone(),
             plookup_vk: None,
             is_merged: true,
+            custom_gates: vec![],
+            num_quot_chunks: self.num_quot_chunks,
         })
     }
 
+    /// Derive the verifying key of a TurboPlonk circuit extended with
+    /// `appended_gates` from the key of the original circuit with `num_gates`
+    /// gates, without preprocessing the extended circuit from scratch.
+    ///
+    /// The appended gates take the place of the first padding gates of the
+    /// original circuit, so only selector extensions are supported: the
+    /// appended gates are expected to have all their wires set to the zero
+    /// variable, as padding gates do, so that the permutation is unchanged.
+    /// Return error if the key is a merged or an UltraPlonk key, if
+    /// `num_gates` or the appended gates do not fit in the evaluation domain,
+    /// or if the SRS does not support the domain size.
+    pub fn extend_preprocess(
+        &self,
+        srs: &UniversalSrs<E>,
+        num_gates: usize,
+        appended_gates: &[Box<dyn Gate<E::Fr>>],
+    ) -> Result<Self, PlonkError> {
+        if self.is_merged {
+            return Err(ParameterError("cannot extend a merged key".to_string()).into());
+        }
+        if self.plookup_vk.is_some() {
+            return Err(SnarkLookupUnsupported.into());
+        }
+        if num_gates > self.domain_size {
+            return Err(ParameterError(format!(
+                "the number of gates {} exceeds the domain size {}",
+                num_gates, self.domain_size
+            ))
+            .into());
+        }
+        if appended_gates.len() > self.domain_size - num_gates {
+            return Err(ParameterError(format!(
+                "{} gates appended to {} gates exceed the domain size {}",
+                appended_gates.len(),
+                num_gates,
+                self.domain_size
+            ))
+            .into());
+        }
+        check_srs_size(srs, self.domain_size)?;
+        let (commit_key, _) = trim(&srs.0, self.domain_size);
+        let domain = Radix2EvaluationDomain::<E::Fr>::new(self.domain_size)
+            .ok_or(PlonkError::DomainCreationError)?;
+
+        // The selectors of padding gates are zero, hence the selector
+        // commitments grow by the commitments to the Lagrange basis
+        // polynomials of the appended rows, weighted by the new selectors.
+        let mut selector_comms: Vec<E::G1Projective> = self
+            .selector_comms
+            .iter()
+            .map(|comm| comm.0.into_projective())
+            .collect();
+        for (i, gate) in appended_gates.iter().enumerate() {
+            if let Some(id) = gate.q_custom() {
+                if id >= self.custom_gates.len() {
+                    return Err(ParameterError(format!(
+                        "the custom gate {} is not in the verifying key",
+                        id
+                    ))
+                    .into());
+                }
+            }
+            let mut selectors =
+                Vec::with_capacity(N_TURBO_PLONK_SELECTORS + self.custom_gates.len());
+            selectors.extend_from_slice(&gate.q_lc());
+            selectors.extend_from_slice(&gate.q_mul());
+            selectors.extend_from_slice(&gate.q_hash());
+            selectors.extend_from_slice(&[gate.q_o(), gate.q_c(), gate.q_ecc()]);
+            selectors.extend((0..self.custom_gates.len()).map(|id| {
+                if gate.q_custom() == Some(id) {
+                    E::Fr::one()
+                } else {
+                    E::Fr::zero()
+                }
+            }));
+            if selectors.iter().all(|s| s.is_zero()) {
+                continue;
+            }
+            let mut lagrange_evals = vec![E::Fr::zero(); self.domain_size];
+            lagrange_evals[num_gates + i] = E::Fr::one();
+            let lagrange_poly =
+                DensePolynomial::from_coefficients_vec(domain.ifft(&lagrange_evals));
+            let (lagrange_comm, _) = KZG10::commit(&commit_key, &lagrange_poly, None, None)?;
+            for (comm, selector) in selector_comms.iter_mut().zip(selectors.iter()) {
+                *comm += lagrange_comm.0.mul(selector.into_repr());
+            }
+        }
+        let selector_comms = E::G1Projective::batch_normalization_into_affine(&selector_comms)
+            .into_iter()
+            .map(Commitment)
+            .collect();
+
+        Ok(Self {
+            selector_comms,
+            ..self.clone()
+        })
+    }
+}
+
+impl<PCS: PolynomialCommitmentScheme> PcsVerifyingKey<PCS> {
+    /// The number of coefficients of each split quotient polynomial but the
+    /// last one.
+    pub(crate) fn quot_chunk_size(&self) -> usize {
+        quotient_chunk_size(
+            self.domain_size,
+            self.sigma_comms.len(),
+            self.num_quot_chunks,
+        )
+    }
+
     /// The lookup selector polynomial commitment
-    pub(crate) fn q_lookup_comm(&self) -> Result<&Commitment<E>, PlonkError> {
+    pub(crate) fn q_lookup_comm(&self) -> Result<&PCS::Commitment, PlonkError> {
         if self.plookup_vk.is_none() {
             return Err(SnarkLookupUnsupported.into());
         }
@@ -841,31 +2134,39 @@ pub(crate) struct PlookupOracles<F: FftField> {
 }
 
 /// The vector representation of bases and corresponding scalars.
-#[derive(Debug)]
-pub(crate) struct ScalarsAndBases<E: PairingEngine> {
-    pub(crate) base_scalar_map: HashMap<E::G1Affine, E::Fr>,
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""))]
+pub(crate) struct ScalarsAndBases<PCS: PolynomialCommitmentScheme> {
+    pub(crate) base_scalar_map: HashMap<PCS::Curve, PCS::Fr>,
 }
 
-impl<E: PairingEngine> ScalarsAndBases<E> {
+impl<PCS: PolynomialCommitmentScheme> ScalarsAndBases<PCS> {
     pub(crate) fn new() -> Self {
         Self {
             base_scalar_map: HashMap::new(),
         }
     }
     /// Insert a base point and the corresponding scalar.
-    pub(crate) fn push(&mut self, scalar: E::Fr, base: E::G1Affine) {
-        let entry_scalar = self.base_scalar_map.entry(base).or_insert_with(E::Fr::zero);
+    pub(crate) fn push(&mut self, scalar: PCS::Fr, base: PCS::Curve) {
+        let entry_scalar = self
+            .base_scalar_map
+            .entry(base)
+            .or_insert_with(PCS::Fr::zero);
         *entry_scalar += scalar;
     }
+    /// Insert a commitment and the corresponding scalar.
+    pub(crate) fn push_commitment(&mut self, scalar: PCS::Fr, comm: &PCS::Commitment) {
+        self.push(scalar, PCS::commitment_to_point(comm));
+    }
     /// Add a list of scalars and bases into self, where each scalar is
     /// multiplied by a constant c.
-    pub(crate) fn merge(&mut self, c: E::Fr, scalars_and_bases: &Self) {
+    pub(crate) fn merge(&mut self, c: PCS::Fr, scalars_and_bases: &Self) {
         for (&base, scalar) in &scalars_and_bases.base_scalar_map {
             self.push(c * scalar, base);
         }
     }
     /// Compute the multi-scalar multiplication.
-    pub(crate) fn multi_scalar_mul(&self) -> E::G1Projective {
+    pub(crate) fn multi_scalar_mul(&self) -> <PCS::Curve as AffineCurve>::Projective {
         let mut bases = vec![];
         let mut scalars = vec![];
         for (&base, scalar) in &self.base_scalar_map {
@@ -874,6 +2175,73 @@ impl<E: PairingEngine> ScalarsAndBases<E> {
         }
         VariableBaseMSM::multi_scalar_mul(&bases, &scalars)
     }
+    /// The commitment computed by the multi-scalar multiplication.
+    pub(crate) fn commitment(&self) -> PCS::Commitment {
+        PCS::commitment_from_point(self.multi_scalar_mul().into_affine())
+    }
+}
+
+impl<E: PairingEngine> ScalarsAndBases<KzgPcs<E>> {
+    /// Compute the multi-scalar multiplication as a point of
+    /// `E::G1Projective`, which callers knowing `E::G1Affine` to be a
+    /// concrete curve cannot infer from [`Self::multi_scalar_mul`].
+    pub(crate) fn g1_multi_scalar_mul(&self) -> E::G1Projective {
+        self.multi_scalar_mul()
+    }
+}
+
+/// The degree of the quotient polynomial of a circuit with `num_wire_types`
+/// wire types over a domain of size `domain_size`.
+#[inline]
+pub(crate) fn quotient_polynomial_degree(domain_size: usize, num_wire_types: usize) -> usize {
+    num_wire_types * (domain_size + 1) + 2
+}
+
+/// The number of coefficients of each of the `num_chunks` polynomials the
+/// quotient polynomial is split into, but the last one which has the remaining
+/// coefficients. For `num_wire_types` chunks, it is `domain_size + 2`.
+#[inline]
+pub(crate) fn quotient_chunk_size(
+    domain_size: usize,
+    num_wire_types: usize,
+    num_chunks: usize,
+) -> usize {
+    (quotient_polynomial_degree(domain_size, num_wire_types) + num_chunks) / num_chunks
+}
+
+/// The degree of the quotient polynomial when the wire witness polynomials
+/// are masked to stay hidden after `num_extra_openings` openings besides the
+/// ones of the proof: each extra opening raises the degree of the wire
+/// polynomials by one.
+#[inline]
+pub(crate) fn masked_quotient_polynomial_degree(
+    domain_size: usize,
+    num_wire_types: usize,
+    num_extra_openings: usize,
+) -> usize {
+    quotient_polynomial_degree(domain_size, num_wire_types) + num_wire_types * num_extra_openings
+}
+
+/// The SRS degree needed to prove with `num_extra_openings` extra openings of
+/// the wire witness polynomials, for `num_chunks` quotient chunks. The chunks
+/// keep the size fixed by the verifying key, so that the last one takes the
+/// extra coefficients of the quotient polynomial.
+#[inline]
+pub(crate) fn witness_openings_srs_size(
+    domain_size: usize,
+    num_wire_types: usize,
+    num_chunks: usize,
+    num_extra_openings: usize,
+) -> usize {
+    let chunk_size = quotient_chunk_size(domain_size, num_wire_types, num_chunks);
+    let last_chunk_degree =
+        masked_quotient_polynomial_degree(domain_size, num_wire_types, num_extra_openings)
+            - (num_chunks - 1) * chunk_size;
+    // the permutation product polynomial has degree `domain_size + 2`
+    (domain_size + 2)
+        .max(domain_size + 1 + num_extra_openings)
+        .max(chunk_size - 1)
+        .max(last_chunk_degree)
 }
 
 /// Specializes the public parameters for a given maximum degree `d` for
@@ -882,19 +2250,43 @@ impl<E: PairingEngine> ScalarsAndBases<E> {
 /// should fork Arkwork's KZG10 library and make this method public.
 /// NOTE: This doesn't support hiding variant of KZG10 since Plonk don't need
 /// it, and `powers_of_gamma_g` is empty and `gamma_g` is dummy.
+/// Return [`PlonkError::SrsTooSmall`] if `srs` doesn't support polynomials of
+/// degree `required`.
+pub(crate) fn check_srs_size<E: PairingEngine>(
+    srs: &UniversalSrs<E>,
+    required: usize,
+) -> Result<(), PlonkError> {
+    let provided = srs.0.max_degree();
+    if provided < required {
+        return Err(PlonkError::SrsTooSmall { required, provided });
+    }
+    Ok(())
+}
+
 pub(crate) fn trim<E: PairingEngine>(
+    pp: &UniversalParams<E>,
+    supported_degree: usize,
+) -> (Powers<'static, E>, VerifierKey<E>) {
+    let (powers, vk) = trim_borrowed(pp, supported_degree);
+    let powers = Powers {
+        powers_of_g: ark_std::borrow::Cow::Owned(powers.powers_of_g.into_owned()),
+        powers_of_gamma_g: ark_std::borrow::Cow::Owned(vec![]), // not used
+    };
+    (powers, vk)
+}
+
+// Same as `trim`, but the powers borrow the ones of `pp` instead of copying
+// them, so that the keys trimmed from the same SRS share them.
+pub(crate) fn trim_borrowed<E: PairingEngine>(
     pp: &UniversalParams<E>,
     mut supported_degree: usize,
-) -> (Powers<E>, VerifierKey<E>) {
+) -> (Powers<'_, E>, VerifierKey<E>) {
     if supported_degree == 1 {
         supported_degree += 1;
     }
-    let powers_of_g = pp.powers_of_g[..=supported_degree].to_vec();
-    let powers_of_gamma_g = vec![]; // not used
-
     let powers = Powers {
-        powers_of_g: ark_std::borrow::Cow::Owned(powers_of_g),
-        powers_of_gamma_g: ark_std::borrow::Cow::Owned(powers_of_gamma_g),
+        powers_of_g: ark_std::borrow::Cow::Borrowed(&pp.powers_of_g[..=supported_degree]),
+        powers_of_gamma_g: ark_std::borrow::Cow::Owned(vec![]), // not used
     };
     let vk = VerifierKey {
         g: pp.powers_of_g[0],
@@ -909,35 +2301,80 @@ pub(crate) fn trim<E: PairingEngine>(
 
 // Utility function for computing merged table evaluations.
 #[inline]
-pub(crate) fn eval_merged_table<E: PairingEngine>(
-    tau: E::Fr,
-    range_eval: E::Fr,
-    key_eval: E::Fr,
-    q_lookup_eval: E::Fr,
-    w3_eval: E::Fr,
-    w4_eval: E::Fr,
-) -> E::Fr {
+pub(crate) fn eval_merged_table<F: Field>(
+    tau: F,
+    range_eval: F,
+    key_eval: F,
+    q_lookup_eval: F,
+    w3_eval: F,
+    w4_eval: F,
+) -> F {
     range_eval + q_lookup_eval * tau * (key_eval + tau * (w3_eval + tau * w4_eval))
 }
 
+// Utility function for evaluating at `point` the polynomial that takes the
+// values 0, 1, ..., `len - 1` at the `len` consecutive elements of `domain`
+// from the `offset`-th one, and zero at the other elements of `domain`.
+pub(crate) fn eval_progression_table<F: FftField>(
+    domain: &Radix2EvaluationDomain<F>,
+    offset: usize,
+    len: usize,
+    point: F,
+) -> F {
+    let mut elems = Vec::with_capacity(len);
+    let mut elem = domain.element(offset);
+    for _ in 0..len {
+        elems.push(elem);
+        elem *= domain.group_gen;
+    }
+    let vanishing_eval = domain.evaluate_vanishing_polynomial(point);
+    if vanishing_eval.is_zero() {
+        return match elems.iter().position(|&elem| elem == point) {
+            Some(j) => F::from(j as u64),
+            None => F::zero(),
+        };
+    }
+    // the Lagrange basis polynomials are L_i(X) = g^i * (X^n - 1) / (n * (X - g^i))
+    let mut denominators: Vec<F> = elems.iter().map(|&elem| point - elem).collect();
+    batch_inversion(&mut denominators);
+    let sum = elems
+        .iter()
+        .zip(denominators.iter())
+        .enumerate()
+        .fold(F::zero(), |acc, (j, (&elem, &denominator))| {
+            acc + F::from(j as u64) * elem * denominator
+        });
+    sum * vanishing_eval * domain.size_inv
+}
+
 // Utility function for computing merged lookup witness evaluations.
 #[inline]
-pub(crate) fn eval_merged_lookup_witness<E: PairingEngine>(
-    tau: E::Fr,
-    w_range_eval: E::Fr,
-    w_0_eval: E::Fr,
-    w_1_eval: E::Fr,
-    w_2_eval: E::Fr,
-    q_lookup_eval: E::Fr,
-) -> E::Fr {
+pub(crate) fn eval_merged_lookup_witness<F: Field>(
+    tau: F,
+    w_range_eval: F,
+    w_0_eval: F,
+    w_1_eval: F,
+    w_2_eval: F,
+    q_lookup_eval: F,
+) -> F {
     w_range_eval + q_lookup_eval * tau * (w_0_eval + tau * (w_1_eval + tau * w_2_eval))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        circuit::{
+            gates::{AdditionGate, BoolGate, ConstantGate, MultiplicationGate},
+            Arithmetization, Circuit,
+        },
+        proof_system::{snark::test::gen_circuit_for_test, PlonkKzgSnark, Snark},
+        transcript::StandardTranscript,
+        PlonkType,
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
     use ark_bn254::{g1::Parameters, Bn254, Fq};
-    use ark_ec::AffineCurve;
+    use ark_std::test_rng;
 
     #[test]
     fn test_group_to_field() {
@@ -948,4 +2385,304 @@ mod test {
         let f2: Vec<Fq> = group2_to_fields::<Bn254, _, _>(g2);
         assert_eq!(f2.len(), 4);
     }
+
+    #[test]
+    fn test_serialize_with() -> Result<(), PlonkError> {
+        test_serialize_with_helper::<Bn254, Fq, _>(PlonkType::TurboPlonk)?;
+        test_serialize_with_helper::<Bn254, Fq, _>(PlonkType::UltraPlonk)?;
+        test_serialize_with_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)
+    }
+
+    fn test_serialize_with_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(rng, &circuit, &pk, None)?;
+        let pub_input = circuit.public_input()?;
+
+        let configs: Vec<SerializeConfig> =
+            [(false, false), (false, true), (true, false), (true, true)]
+                .iter()
+                .map(|&(compressed, with_header)| SerializeConfig {
+                    compressed,
+                    with_header,
+                })
+                .collect();
+        for &config in configs.iter() {
+            let mut bytes = Vec::new();
+            proof.serialize_with(config, &mut bytes)?;
+            let deserialized = Proof::<E>::deserialize_with(config, &bytes[..])?;
+            assert_eq!(deserialized, proof);
+            assert!(PlonkKzgSnark::<E>::verify::<StandardTranscript>(
+                &vk,
+                &pub_input,
+                &deserialized,
+                None
+            )
+            .is_ok());
+
+            // the compressed serialization without header is the canonical one
+            let mut canonical_bytes = Vec::new();
+            match config.compressed {
+                true => proof.serialize(&mut canonical_bytes)?,
+                false => proof.serialize_uncompressed(&mut canonical_bytes)?,
+            }
+            let header_len = config.with_header as usize * (PROOF_HEADER_MAGIC.len() + 2);
+            assert_eq!(bytes[header_len..], canonical_bytes[..]);
+
+            // bad path: a mismatched config, or a corrupted header
+            for &other_config in configs.iter().filter(|&&c| c != config) {
+                assert!(Proof::<E>::deserialize_with(other_config, &bytes[..])
+                    .map_or(true, |other_proof| other_proof != proof));
+            }
+            if config.with_header {
+                for i in 0..header_len {
+                    let mut bad_bytes = bytes.clone();
+                    bad_bytes[i] ^= 1;
+                    assert!(Proof::<E>::deserialize_with(config, &bad_bytes[..]).is_err());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_g2_tau_bytes() -> Result<(), PlonkError> {
+        test_g2_tau_bytes_helper::<Bn254>()?;
+        test_g2_tau_bytes_helper::<Bls12_377>()
+    }
+
+    fn test_g2_tau_bytes_helper<E: PairingEngine>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let srs = PlonkKzgSnark::<E>::universal_setup(16, rng)?;
+        let bytes = srs.g2_tau_bytes()?;
+        assert_eq!(E::G2Affine::deserialize(&bytes[..])?, srs.0.beta_h);
+        assert!(srs.matches_reference_g2(&bytes));
+
+        // an SRS with the known tau = 2
+        let mut known_srs = srs.clone();
+        known_srs.0.beta_h = srs.0.h.mul(2u64).into_affine();
+        let mut known_bytes = Vec::new();
+        srs.0
+            .h
+            .into_projective()
+            .double()
+            .into_affine()
+            .serialize(&mut known_bytes)?;
+        assert_eq!(known_srs.g2_tau_bytes()?, known_bytes);
+        assert!(known_srs.matches_reference_g2(&known_bytes));
+        assert!(!srs.matches_reference_g2(&known_bytes));
+
+        // another SRS, or invalid bytes
+        let other_srs = PlonkKzgSnark::<E>::universal_setup(16, rng)?;
+        assert!(!other_srs.matches_reference_g2(&bytes));
+        assert!(!srs.matches_reference_g2(&bytes[1..]));
+        assert!(!srs.matches_reference_g2(&[]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expected_poly_degrees() {
+        // TurboPlonk: 5 wire types, quotient of degree 5 * 1025 + 2 = 5127
+        let vk = VerifyingKey::<Bn254>::dummy(2, 1024);
+        let degrees = vk.expected_poly_degrees();
+        assert_eq!(degrees.wire, 1025);
+        assert_eq!(degrees.prod_perm, 1026);
+        assert_eq!(degrees.split_quot, vec![1025, 1025, 1025, 1025, 1023]);
+        assert_eq!(degrees.selector, 1023);
+        assert_eq!(degrees.sigma, 1023);
+        assert!(degrees.plookup.is_none());
+        // the split quotient polynomials have as many coefficients as the quotient
+        assert_eq!(
+            degrees.split_quot.iter().map(|d| d + 1).sum::<usize>(),
+            5 * 1025 + 3
+        );
+    }
+
+    #[test]
+    fn test_vk_circuit_size() -> Result<(), PlonkError> {
+        test_vk_circuit_size_helper::<Bn254>(PlonkType::TurboPlonk)?;
+        test_vk_circuit_size_helper::<Bn254>(PlonkType::UltraPlonk)?;
+        test_vk_circuit_size_helper::<Bls12_377>(PlonkType::TurboPlonk)?;
+        test_vk_circuit_size_helper::<Bls12_377>(PlonkType::UltraPlonk)
+    }
+
+    fn test_vk_circuit_size_helper<E: PairingEngine>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (_, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        // the finalized circuit is padded to the domain size
+        assert_eq!(vk.circuit_size(), cs.num_gates());
+        assert_eq!(vk.circuit_size(), cs.eval_domain_size()?);
+        if plonk_type == PlonkType::TurboPlonk {
+            assert_eq!(vk.merge(&vk)?.circuit_size(), cs.num_gates());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_vk_fingerprint() -> Result<(), PlonkError> {
+        test_vk_fingerprint_helper::<Bn254>()?;
+        test_vk_fingerprint_helper::<Bls12_377>()
+    }
+
+    fn test_vk_fingerprint_helper<E: PairingEngine>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        // a * b + a, or a + b + a if `add`, with a public
+        let build = |a_val: u32, add: bool| -> Result<PlonkCircuit<E::Fr>, PlonkError> {
+            let mut cs: PlonkCircuit<E::Fr> = PlonkCircuit::new_turbo_plonk();
+            let a = cs.create_public_variable(E::Fr::from(a_val))?;
+            let b = cs.create_variable(E::Fr::from(4u32))?;
+            let c = match add {
+                true => cs.add(a, b)?,
+                false => cs.mul(a, b)?,
+            };
+            cs.add(a, c)?;
+            cs.finalize_for_arithmetization()?;
+            Ok(cs)
+        };
+        let cs = build(3, false)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (_, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let fingerprint = vk.fingerprint();
+
+        // the same circuit structure, with another witness
+        let (_, other_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &build(5, false)?)?;
+        assert_eq!(other_vk.fingerprint(), fingerprint);
+        // reloaded keys
+        let mut bytes = Vec::new();
+        vk.serialize(&mut bytes)?;
+        assert_eq!(
+            VerifyingKey::<E>::deserialize(&bytes[..])?.fingerprint(),
+            fingerprint
+        );
+        let mut bytes = Vec::new();
+        vk.serialize_uncompressed(&mut bytes)?;
+        assert_eq!(
+            VerifyingKey::<E>::deserialize_uncompressed(&bytes[..])?.fingerprint(),
+            fingerprint
+        );
+
+        // other circuit structures, number of inputs, or SRS
+        let (_, add_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &build(3, true)?)?;
+        assert_ne!(add_vk.fingerprint(), fingerprint);
+        let mut more_inputs_vk = vk.clone();
+        more_inputs_vk.num_inputs += 1;
+        assert_ne!(more_inputs_vk.fingerprint(), fingerprint);
+        let other_srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (_, other_srs_vk) = PlonkKzgSnark::<E>::preprocess(&other_srs, &cs)?;
+        assert_ne!(other_srs_vk.fingerprint(), fingerprint);
+        let ultra_cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::UltraPlonk)?;
+        let turbo_cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let srs_size = ultra_cs.srs_size()?.max(turbo_cs.srs_size()?);
+        let srs = PlonkKzgSnark::<E>::universal_setup(srs_size, rng)?;
+        let (_, ultra_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &ultra_cs)?;
+        let (_, turbo_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &turbo_cs)?;
+        assert_ne!(ultra_vk.fingerprint(), turbo_vk.fingerprint());
+        // a tampered key
+        let mut bad_vk = vk.clone();
+        bad_vk.selector_comms[0] = bad_vk.selector_comms[1];
+        assert_ne!(bad_vk.fingerprint(), fingerprint);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend_preprocess() -> Result<(), PlonkError> {
+        test_extend_preprocess_helper::<Bn254>()?;
+        test_extend_preprocess_helper::<Bls12_377>()
+    }
+
+    fn test_extend_preprocess_helper<E: PairingEngine>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let mut cs: PlonkCircuit<E::Fr> = PlonkCircuit::new_turbo_plonk();
+        let a = cs.create_public_variable(E::Fr::from(3u32))?;
+        let b = cs.create_variable(E::Fr::from(4u32))?;
+        let c = cs.mul(a, b)?;
+        cs.add(a, c)?;
+        let num_gates = cs.num_gates();
+        let appended_gates: Vec<Box<dyn Gate<E::Fr>>> = vec![
+            Box::new(AdditionGate),
+            Box::new(MultiplicationGate),
+            Box::new(BoolGate),
+            Box::new(ConstantGate(E::Fr::zero())),
+        ];
+        let mut extended_cs = cs.clone();
+        let zero = extended_cs.zero();
+        for gate in appended_gates.iter() {
+            extended_cs.insert_gate(&[zero; GATE_WIDTH + 1], gate.clone())?;
+        }
+        cs.finalize_for_arithmetization()?;
+        extended_cs.finalize_for_arithmetization()?;
+        assert_eq!(cs.eval_domain_size()?, extended_cs.eval_domain_size()?);
+
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, rng)?;
+        let (_, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+        let (extended_pk, extended_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &extended_cs)?;
+        let incremental_vk = vk.extend_preprocess(&srs, num_gates, &appended_gates)?;
+        assert_eq!(incremental_vk, extended_vk);
+        assert_eq!(vk.extend_preprocess(&srs, num_gates, &[])?, vk);
+
+        // proofs for the extended circuit verify against the derived key
+        let public_inputs = extended_cs.public_input()?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(
+            rng,
+            &extended_cs,
+            &extended_pk,
+            None,
+        )?;
+        assert!(PlonkKzgSnark::<E>::verify::<StandardTranscript>(
+            &incremental_vk,
+            &public_inputs,
+            &proof,
+            None
+        )
+        .is_ok());
+
+        // too many appended gates
+        let padding_len = cs.eval_domain_size()? - num_gates;
+        let too_many_gates: Vec<Box<dyn Gate<E::Fr>>> = (0..padding_len + 1)
+            .map(|_| Box::new(AdditionGate) as Box<dyn Gate<E::Fr>>)
+            .collect();
+        assert!(vk
+            .extend_preprocess(&srs, num_gates, &too_many_gates)
+            .is_err());
+        // the number of gates of the original circuit exceeds the domain
+        let domain_size = cs.eval_domain_size()?;
+        assert!(vk.extend_preprocess(&srs, domain_size + 1, &[]).is_err());
+        assert!(vk
+            .extend_preprocess(&srs, usize::MAX, &appended_gates)
+            .is_err());
+        // the SRS doesn't support the domain size
+        let small_srs = PlonkKzgSnark::<E>::universal_setup(domain_size / 2, rng)?;
+        assert!(vk
+            .extend_preprocess(&small_srs, num_gates, &appended_gates)
+            .is_err());
+        // merged and UltraPlonk keys
+        let merged_vk = vk.merge(&vk)?;
+        assert!(merged_vk
+            .extend_preprocess(&srs, num_gates, &appended_gates)
+            .is_err());
+        let mut ultra_cs: PlonkCircuit<E::Fr> = PlonkCircuit::new_ultra_plonk(4);
+        let num_ultra_gates = ultra_cs.num_gates();
+        ultra_cs.finalize_for_arithmetization()?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(ultra_cs.srs_size()?, rng)?;
+        let (_, ultra_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &ultra_cs)?;
+        assert!(ultra_vk
+            .extend_preprocess(&srs, num_ultra_gates, &appended_gates)
+            .is_err());
+
+        Ok(())
+    }
 }
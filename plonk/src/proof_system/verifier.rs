@@ -4,8 +4,12 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
-use super::structs::{
-    BatchProof, Challenges, PlookupProof, ProofEvaluations, ScalarsAndBases, VerifyingKey,
+use super::{
+    pcs::{KzgPcs, PolynomialCommitmentScheme},
+    structs::{
+        BatchProofView, Challenges, PcsPlookupProof, PcsVerifyingKey, ProofEvaluations,
+        ScalarsAndBases, VerifyingKey,
+    },
 };
 use crate::{
     circuit::customized::ecc::SWToTEConParam,
@@ -15,13 +19,14 @@ use crate::{
     transcript::*,
 };
 use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
-use ark_ff::{Field, One, Zero};
+use ark_ff::{batch_inversion, FftField, Field, One, Zero};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use ark_poly_commit::kzg10::Commitment;
-use ark_std::{format, vec, vec::Vec};
+use ark_std::{format, string::ToString, vec, vec::Vec};
 use core::ops::Neg;
 use jf_rescue::RescueParameter;
 use jf_utils::multi_pairing;
+use rayon::prelude::*;
 
 /// (Aggregated) polynomial commitment evaluation info.
 /// * `u` - a random combiner that was used to combine evaluations at point
@@ -40,36 +45,127 @@ pub(crate) struct PcsInfo<E: PairingEngine> {
     pub(crate) eval_point: E::Fr,
     pub(crate) next_eval_point: E::Fr,
     pub(crate) eval: E::Fr,
-    pub(crate) comm_scalars_and_bases: ScalarsAndBases<E>,
+    pub(crate) comm_scalars_and_bases: ScalarsAndBases<KzgPcs<E>>,
     pub(crate) opening_proof: Commitment<E>,
     pub(crate) shifted_opening_proof: Commitment<E>,
 }
 
-pub(crate) struct Verifier<E: PairingEngine> {
-    pub(crate) domain: Radix2EvaluationDomain<E::Fr>,
+/// The evaluations claimed by the two opening proofs of a (batch) proof.
+/// * `eval_point` - the point `zeta`.
+/// * `comm_scalars_and_bases` - the scalars-and-bases form of the aggregated
+///   commitment to the polynomials opened at `zeta`.
+/// * `eval` - the aggregated evaluation of the polynomials at `zeta`.
+/// * `next_eval_point` - the shifted point `zeta * g`.
+/// * `shifted_comm_scalars_and_bases` - the scalars-and-bases form of the
+///   aggregated commitment to the polynomials opened at `zeta * g`.
+/// * `shifted_eval` - the aggregated evaluation of the polynomials at `zeta *
+///   g`.
+#[derive(Debug)]
+pub(crate) struct OpeningClaims<PCS: PolynomialCommitmentScheme> {
+    pub(crate) eval_point: PCS::Fr,
+    pub(crate) comm_scalars_and_bases: ScalarsAndBases<PCS>,
+    pub(crate) eval: PCS::Fr,
+    pub(crate) next_eval_point: PCS::Fr,
+    pub(crate) shifted_comm_scalars_and_bases: ScalarsAndBases<PCS>,
+    pub(crate) shifted_eval: PCS::Fr,
+}
+
+/// An aggregated polynomial commitment, in the scalars-and-bases form, along
+/// with the random combiners of the evaluations of the aggregated
+/// polynomials, in the order of [`Verifier::aggregate_evaluations`].
+type CommitmentAndCombiners<PCS> = (
+    ScalarsAndBases<PCS>,
+    Vec<<PCS as PolynomialCommitmentScheme>::Fr>,
+);
+
+pub(crate) struct Verifier<PCS: PolynomialCommitmentScheme> {
+    pub(crate) domain: Radix2EvaluationDomain<PCS::Fr>,
+    /// The Lagrange bases of the public inputs of a verifying key, set by
+    /// [`super::structs::VerifierPrecompute`].
+    pub(crate) pi_bases: Option<PiLagrangeBases<PCS::Fr>>,
 }
 
-impl<E, F, P> Verifier<E>
+/// The public input positions `g^i` of a verifying key over a domain `H` of
+/// size `n` with generator `g`, and the constants `v_i = g^i / n` of their
+/// Lagrange polynomials `L_{i,H}(z) = Z_H(z) * v_i / (z - g^i)`.
+#[derive(Debug, Clone)]
+pub(crate) struct PiLagrangeBases<F: Field> {
+    num_inputs: usize,
+    is_merged: bool,
+    points: Vec<F>,
+    coeffs: Vec<F>,
+}
+
+impl<F: FftField> PiLagrangeBases<F> {
+    /// Compute the bases of `num_inputs` public inputs, laid out as by
+    /// `Verifier::evaluate_pi_poly` for (merged) circuits.
+    pub(crate) fn new(
+        domain: &Radix2EvaluationDomain<F>,
+        num_inputs: usize,
+        is_merged: bool,
+    ) -> Result<Self, PlonkError> {
+        let n = domain.size();
+        let len = match is_merged {
+            false => num_inputs,
+            true => num_inputs / 2,
+        };
+        let points: Vec<F> = (0..len)
+            .map(|i| domain.element(i))
+            .chain((0..num_inputs - len).map(|i| domain.element(n - i - 1)))
+            .collect();
+        let n_inv = F::from(n as u32)
+            .inverse()
+            .ok_or(PlonkError::DivisionError)?;
+        let coeffs = points.iter().map(|point| n_inv * point).collect();
+        Ok(Self {
+            num_inputs,
+            is_merged,
+            points,
+            coeffs,
+        })
+    }
+
+    /// Evaluate at `z` the polynomial interpolating `pub_input` at the
+    /// positions of the bases, given the evaluation `vanish_eval` of the
+    /// vanishing polynomial at `z`, which should not be zero.
+    pub(crate) fn evaluate(&self, pub_input: &[F], z: &F, vanish_eval: &F) -> F {
+        let mut denominators: Vec<F> = self.points.iter().map(|point| *z - point).collect();
+        batch_inversion(&mut denominators);
+        let result = pub_input
+            .iter()
+            .zip(self.coeffs.iter().zip(denominators.iter()))
+            .fold(F::zero(), |acc, (val, (coeff, denominator))| {
+                acc + *coeff * denominator * val
+            });
+        result * vanish_eval
+    }
+}
+
+impl<PCS, F, P> Verifier<PCS>
 where
-    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
-    F: RescueParameter + SWToTEConParam,
+    PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
     P: SWModelParameters<BaseField = F> + Clone,
 {
     /// Construct a Plonk verifier that uses a domain with size `domain_size`.
     pub(crate) fn new(domain_size: usize) -> Result<Self, PlonkError> {
-        let domain = Radix2EvaluationDomain::<E::Fr>::new(domain_size)
+        let domain = Radix2EvaluationDomain::<PCS::Fr>::new(domain_size)
             .ok_or(PlonkError::DomainCreationError)?;
-        Ok(Self { domain })
+        Ok(Self {
+            domain,
+            pi_bases: None,
+        })
     }
 
-    /// Prepare the (aggregated) polynomial commitment evaluation information.
-    pub(crate) fn prepare_pcs_info<T>(
+    /// Prepare the evaluations claimed by the opening proofs, and return them
+    /// along with the challenges and the final transcript state.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn prepare_opening_claims_and_transcript<T>(
         &self,
-        verify_keys: &[&VerifyingKey<E>],
-        public_inputs: &[&[E::Fr]],
-        batch_proof: &BatchProof<E>,
+        verify_keys: &[&PcsVerifyingKey<PCS>],
+        public_inputs: &[&[PCS::Fr]],
+        batch_proof: &BatchProofView<PCS>,
         extra_transcript_init_msg: &Option<Vec<u8>>,
-    ) -> Result<PcsInfo<E>, PlonkError>
+    ) -> Result<(OpeningClaims<PCS>, Challenges<PCS::Fr>, T), PlonkError>
     where
         T: PlonkTranscript<F>,
     {
@@ -108,10 +204,25 @@ where
                 ))
                 .into());
             }
+            if vk.num_quot_chunks != verify_keys[0].num_quot_chunks {
+                return Err(ParameterError(format!(
+                    "the quotient polynomial split of the {}-th verification key is different from the first one",
+                    i
+                ))
+                .into());
+            }
+        }
+        if batch_proof.split_quot_poly_comms.len() != verify_keys[0].num_quot_chunks {
+            return Err(ParameterError(format!(
+                "the number of split quotient polynomial commitments {} != the expected number {}",
+                batch_proof.split_quot_poly_comms.len(),
+                verify_keys[0].num_quot_chunks
+            ))
+            .into());
         }
 
         // compute challenges and evaluations
-        let challenges = Self::compute_challenges::<T>(
+        let (challenges, transcript) = Self::compute_challenges_and_transcript::<T>(
             verify_keys,
             public_inputs,
             batch_proof,
@@ -126,7 +237,7 @@ where
         let alpha_6 = alpha_4 * alpha_2;
         let alpha_7 = alpha_3 * alpha_4;
         let alpha_powers = vec![alpha_2, alpha_3, alpha_4, alpha_5, alpha_6];
-        let mut alpha_bases = vec![E::Fr::one()];
+        let mut alpha_bases = vec![PCS::Fr::one()];
 
         let mut tmp = if verify_keys[0].plookup_vk.is_some() {
             alpha_7
@@ -157,111 +268,106 @@ where
             &alpha_bases,
         )?;
 
-        // build the (aggregated) polynomial commitment/evaluation instance
-        let (comm_scalars_and_bases, buffer_v_and_uv_basis) = self.aggregate_poly_commitments(
-            verify_keys,
-            &challenges,
-            &vanish_eval,
-            &lagrange_1_eval,
-            &lagrange_n_eval,
-            batch_proof,
-            &alpha_powers,
-            &alpha_bases,
-        )?;
+        // build the aggregated polynomial commitment/evaluation instances of
+        // either point
+        let ((comm_scalars_and_bases, v_basis), (shifted_comm_scalars_and_bases, shifted_v_basis)) =
+            self.aggregate_poly_commitments_by_point(
+                verify_keys,
+                &challenges,
+                &vanish_eval,
+                &lagrange_1_eval,
+                &lagrange_n_eval,
+                batch_proof,
+                &alpha_powers,
+                &alpha_bases,
+            )?;
         let eval = Self::aggregate_evaluations(
             &lin_poly_constant,
             &batch_proof.poly_evals_vec,
             &batch_proof.plookup_proofs_vec,
-            &buffer_v_and_uv_basis,
+            &v_basis,
+        )?;
+        let shifted_eval = Self::aggregate_evaluations(
+            &PCS::Fr::zero(),
+            &batch_proof.poly_evals_vec,
+            &batch_proof.plookup_proofs_vec,
+            &shifted_v_basis,
         )?;
 
-        Ok(PcsInfo {
-            u: challenges.u,
+        let claims = OpeningClaims {
             eval_point: challenges.zeta,
-            next_eval_point: challenges.zeta * self.domain.group_gen,
             comm_scalars_and_bases,
             eval,
-            opening_proof: batch_proof.opening_proof,
-            shifted_opening_proof: batch_proof.shifted_opening_proof,
-        })
+            next_eval_point: challenges.zeta * self.domain.group_gen,
+            shifted_comm_scalars_and_bases,
+            shifted_eval,
+        };
+        Ok((claims, challenges, transcript))
     }
 
-    /// Batchly verify multiple (aggregated) PCS opening proofs.
-    ///
-    /// We need to verify that
-    /// - `e(Ai, [x]2) = e(Bi, [1]2) for i \in {0, .., m-1}`, where
-    /// - `Ai = [open_proof_i] + u_i * [shifted_open_proof_i]` and
-    /// - `Bi = eval_point_i * [open_proof_i] + u_i * next_eval_point_i *
-    ///   [shifted_open_proof_i] + comm_i - eval_i * [1]1`.
-    /// By Schwartz-Zippel lemma, it's equivalent to check that for a random r:
-    /// - `e(A0 + ... + r^{m-1} * Am, [x]2) = e(B0 + ... + r^{m-1} * Bm, [1]2)`.
-    pub(crate) fn batch_verify_opening_proofs<T>(
-        open_key: &OpenKey<E>,
-        pcs_infos: &[PcsInfo<E>],
+    /// Check the opening proofs of `batch_proof` against the evaluations
+    /// `claims` with `PCS::verify`, first at `zeta` then at `zeta * g`, with
+    /// an opening transcript that binds the challenge `v` of the proof.
+    pub(crate) fn verify_opening_proofs<T>(
+        open_key: &PCS::OpenKey,
+        claims: &OpeningClaims<PCS>,
+        batch_proof: &BatchProofView<PCS>,
+        v: &PCS::Fr,
     ) -> Result<bool, PlonkError>
     where
         T: PlonkTranscript<F>,
     {
-        // Compute a pseudorandom challenge from the instances
-        let r = if pcs_infos.len() == 1 {
-            // No need to use `r` when there is only a single proof.
-            E::Fr::one()
-        } else {
-            let mut transcript = T::new(b"batch verify");
-            // r := hash(u1||u2||...||u_m), where u_i is the hash output of the i-th Plonk
-            // protocol transcript. This approach is more secure as `r` depends not only
-            // on the proofs, but also the list of public inputs and verifying keys.
-            for pcs_info in pcs_infos {
-                transcript.append_challenge::<E>(b"u", &pcs_info.u)?;
-            }
-            transcript.get_and_append_challenge::<E>(b"r")?
-        };
-
-        // Compute A := A0 + r * A1 + ... + r^{m-1} * Am
-        let mut inners = ScalarsAndBases::<E>::new();
-        let mut r_base = E::Fr::one();
-        for pcs_info in pcs_infos.iter() {
-            inners.push(r_base, pcs_info.opening_proof.0);
-            inners.push(r_base * pcs_info.u, pcs_info.shifted_opening_proof.0);
-            r_base *= r;
-        }
-        let inner = inners.multi_scalar_mul();
-        // Add (A, [x]2) to the product pairing list
-        let mut g1_elems: Vec<<E as PairingEngine>::G1Affine> = vec![inner.into()];
-        let mut g2_elems = vec![open_key.beta_h];
-
-        // Compute B := B0 + r * B1 + ... + r^{m-1} * Bm
-        let mut inners = ScalarsAndBases::new();
-        let mut r_base = E::Fr::one();
-        let mut sum_evals = E::Fr::zero();
-        for pcs_info in pcs_infos.iter() {
-            inners.merge(r_base, &pcs_info.comm_scalars_and_bases);
-            inners.push(r_base * pcs_info.eval_point, pcs_info.opening_proof.0);
-            inners.push(
-                r_base * pcs_info.u * pcs_info.next_eval_point,
-                pcs_info.shifted_opening_proof.0,
-            );
-            sum_evals += r_base * pcs_info.eval;
-            r_base *= r;
+        let mut transcript = T::new(b"PlonkOpening");
+        transcript.append_field_challenge(b"v", v)?;
+        if !PCS::verify(
+            open_key,
+            &mut transcript,
+            &claims.comm_scalars_and_bases.commitment(),
+            &claims.eval_point,
+            &claims.eval,
+            batch_proof.opening_proof,
+        )? {
+            return Ok(false);
         }
-        inners.push(-sum_evals, open_key.g);
-        let inner = inners.multi_scalar_mul();
-        // Add (-B, [1]2) to the product pairing list
-        g1_elems.push(-inner.into());
-        g2_elems.push(open_key.h);
-        // Check e(A, [x]2) ?= e(B, [1]2)
-        Ok(multi_pairing::<E>(&g1_elems, &g2_elems) == E::Fqk::one())
+        PCS::verify(
+            open_key,
+            &mut transcript,
+            &claims.shifted_comm_scalars_and_bases.commitment(),
+            &claims.next_eval_point,
+            &claims.shifted_eval,
+            batch_proof.shifted_opening_proof,
+        )
     }
 
     /// Compute verifier challenges `tau`, `beta`, `gamma`, `alpha`, `zeta`,
     /// 'v', 'u'.
     #[inline]
     pub(crate) fn compute_challenges<T>(
-        verify_keys: &[&VerifyingKey<E>],
-        public_inputs: &[&[E::Fr]],
-        batch_proof: &BatchProof<E>,
+        verify_keys: &[&PcsVerifyingKey<PCS>],
+        public_inputs: &[&[PCS::Fr]],
+        batch_proof: &BatchProofView<PCS>,
+        extra_transcript_init_msg: &Option<Vec<u8>>,
+    ) -> Result<Challenges<PCS::Fr>, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let (challenges, _) = Self::compute_challenges_and_transcript::<T>(
+            verify_keys,
+            public_inputs,
+            batch_proof,
+            extra_transcript_init_msg,
+        )?;
+        Ok(challenges)
+    }
+
+    /// Compute verifier challenges and return them along with the final
+    /// transcript state.
+    pub(crate) fn compute_challenges_and_transcript<T>(
+        verify_keys: &[&PcsVerifyingKey<PCS>],
+        public_inputs: &[&[PCS::Fr]],
+        batch_proof: &BatchProofView<PCS>,
         extra_transcript_init_msg: &Option<Vec<u8>>,
-    ) -> Result<Challenges<E::Fr>, PlonkError>
+    ) -> Result<(Challenges<PCS::Fr>, T), PlonkError>
     where
         T: PlonkTranscript<F>,
     {
@@ -279,56 +385,66 @@ where
             transcript.append_message(EXTRA_TRANSCRIPT_MSG_LABEL, msg)?;
         }
         for (&vk, &pi) in verify_keys.iter().zip(public_inputs.iter()) {
-            transcript.append_vk_and_pub_input(vk, pi)?;
+            transcript.append_vk_and_pub_input::<PCS, P>(vk, pi)?;
         }
         for wires_poly_comms in batch_proof.wires_poly_comms_vec.iter() {
-            transcript.append_commitments(b"witness_poly_comms", wires_poly_comms)?;
+            transcript.append_pcs_commitments::<PCS, P>(b"witness_poly_comms", wires_poly_comms)?;
         }
-        let tau = transcript.get_and_append_challenge::<E>(b"tau")?;
+        let tau = transcript.get_and_append_field_challenge::<PCS::Fr>(b"tau")?;
 
         for plookup_proof in batch_proof.plookup_proofs_vec.iter() {
             if let Some(proof_lkup) = plookup_proof.as_ref() {
-                transcript.append_commitments(b"h_poly_comms", &proof_lkup.h_poly_comms)?;
+                transcript
+                    .append_pcs_commitments::<PCS, P>(b"h_poly_comms", &proof_lkup.h_poly_comms)?;
             }
         }
 
-        let beta = transcript.get_and_append_challenge::<E>(b"beta")?;
-        let gamma = transcript.get_and_append_challenge::<E>(b"gamma")?;
+        let beta = transcript.get_and_append_field_challenge::<PCS::Fr>(b"beta")?;
+        let gamma = transcript.get_and_append_field_challenge::<PCS::Fr>(b"gamma")?;
         for prod_perm_poly_comm in batch_proof.prod_perm_poly_comms_vec.iter() {
-            transcript.append_commitment(b"perm_poly_comms", prod_perm_poly_comm)?;
+            transcript.append_pcs_commitment::<PCS, P>(b"perm_poly_comms", prod_perm_poly_comm)?;
         }
         for plookup_proof in batch_proof.plookup_proofs_vec.iter() {
             if let Some(proof_lkup) = plookup_proof.as_ref() {
-                transcript
-                    .append_commitment(b"plookup_poly_comms", &proof_lkup.prod_lookup_poly_comm)?;
+                transcript.append_pcs_commitment::<PCS, P>(
+                    b"plookup_poly_comms",
+                    &proof_lkup.prod_lookup_poly_comm,
+                )?;
             }
         }
 
-        let alpha = transcript.get_and_append_challenge::<E>(b"alpha")?;
-        transcript.append_commitments(b"quot_poly_comms", &batch_proof.split_quot_poly_comms)?;
-        let zeta = transcript.get_and_append_challenge::<E>(b"zeta")?;
+        let alpha = transcript.get_and_append_field_challenge::<PCS::Fr>(b"alpha")?;
+        transcript.append_pcs_commitments::<PCS, P>(
+            b"quot_poly_comms",
+            batch_proof.split_quot_poly_comms,
+        )?;
+        let zeta = transcript.get_and_append_field_challenge::<PCS::Fr>(b"zeta")?;
         for poly_evals in batch_proof.poly_evals_vec.iter() {
-            transcript.append_proof_evaluations::<E>(poly_evals)?;
+            transcript.append_proof_evaluations(poly_evals)?;
         }
         for plookup_proof in batch_proof.plookup_proofs_vec.iter() {
             if let Some(proof_lkup) = plookup_proof.as_ref() {
-                transcript.append_plookup_evaluations::<E>(&proof_lkup.poly_evals)?;
+                transcript.append_plookup_evaluations(&proof_lkup.poly_evals)?;
             }
         }
 
-        let v = transcript.get_and_append_challenge::<E>(b"v")?;
-        transcript.append_commitment(b"open_proof", &batch_proof.opening_proof)?;
-        transcript.append_commitment(b"shifted_open_proof", &batch_proof.shifted_opening_proof)?;
-        let u = transcript.get_and_append_challenge::<E>(b"u")?;
-        Ok(Challenges {
-            tau,
-            alpha,
-            beta,
-            gamma,
-            zeta,
-            v,
-            u,
-        })
+        let v = transcript.get_and_append_field_challenge::<PCS::Fr>(b"v")?;
+        transcript.append_pcs_proof::<PCS, P>(b"open_proof", batch_proof.opening_proof)?;
+        transcript
+            .append_pcs_proof::<PCS, P>(b"shifted_open_proof", batch_proof.shifted_opening_proof)?;
+        let u = transcript.get_and_append_field_challenge::<PCS::Fr>(b"u")?;
+        Ok((
+            Challenges {
+                tau,
+                alpha,
+                beta,
+                gamma,
+                zeta,
+                v,
+                u,
+            },
+            transcript,
+        ))
     }
 
     /// Compute the constant term of the linearization polynomial:
@@ -350,16 +466,16 @@ where
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn compute_lin_poly_constant_term(
         &self,
-        challenges: &Challenges<E::Fr>,
-        verify_keys: &[&VerifyingKey<E>],
-        public_inputs: &[&[E::Fr]],
-        batch_proof: &BatchProof<E>,
-        vanish_eval: &E::Fr,
-        lagrange_1_eval: &E::Fr,
-        lagrange_n_eval: &E::Fr,
-        alpha_powers: &[E::Fr],
-        alpha_bases: &[E::Fr],
-    ) -> Result<E::Fr, PlonkError> {
+        challenges: &Challenges<PCS::Fr>,
+        verify_keys: &[&PcsVerifyingKey<PCS>],
+        public_inputs: &[&[PCS::Fr]],
+        batch_proof: &BatchProofView<PCS>,
+        vanish_eval: &PCS::Fr,
+        lagrange_1_eval: &PCS::Fr,
+        lagrange_n_eval: &PCS::Fr,
+        alpha_powers: &[PCS::Fr],
+        alpha_bases: &[PCS::Fr],
+    ) -> Result<PCS::Fr, PlonkError> {
         if verify_keys.len() != batch_proof.len()
             || verify_keys.len() != public_inputs.len()
             || verify_keys.len() != alpha_bases.len()
@@ -374,7 +490,7 @@ where
             .into());
         }
 
-        let mut result = E::Fr::zero();
+        let mut result = PCS::Fr::zero();
         for (poly_evals, (plookup_proof, (&pi, (&vk, &current_alpha_bases)))) in
             batch_proof.poly_evals_vec.iter().zip(
                 batch_proof.plookup_proofs_vec.iter().zip(
@@ -403,7 +519,7 @@ where
             );
 
             if let Some(proof_lk) = plookup_proof {
-                let gamma_mul_beta_plus_one = challenges.gamma * (E::Fr::one() + challenges.beta);
+                let gamma_mul_beta_plus_one = challenges.gamma * (PCS::Fr::one() + challenges.beta);
                 let evals = &proof_lk.poly_evals;
 
                 let plookup_constant = *lagrange_n_eval
@@ -431,15 +547,53 @@ where
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn aggregate_poly_commitments(
         &self,
-        vks: &[&VerifyingKey<E>],
-        challenges: &Challenges<E::Fr>,
-        vanish_eval: &E::Fr,
-        lagrange_1_eval: &E::Fr,
-        lagrange_n_eval: &E::Fr,
-        batch_proof: &BatchProof<E>,
-        alpha_powers: &[E::Fr],
-        alpha_bases: &[E::Fr],
-    ) -> Result<(ScalarsAndBases<E>, Vec<E::Fr>), PlonkError> {
+        vks: &[&PcsVerifyingKey<PCS>],
+        challenges: &Challenges<PCS::Fr>,
+        vanish_eval: &PCS::Fr,
+        lagrange_1_eval: &PCS::Fr,
+        lagrange_n_eval: &PCS::Fr,
+        batch_proof: &BatchProofView<PCS>,
+        alpha_powers: &[PCS::Fr],
+        alpha_bases: &[PCS::Fr],
+    ) -> Result<CommitmentAndCombiners<PCS>, PlonkError> {
+        let ((mut scalars_and_bases, v_basis), (shifted_scalars_and_bases, shifted_v_basis)) = self
+            .aggregate_poly_commitments_by_point(
+                vks,
+                challenges,
+                vanish_eval,
+                lagrange_1_eval,
+                lagrange_n_eval,
+                batch_proof,
+                alpha_powers,
+                alpha_bases,
+            )?;
+        // the polynomials evaluated at point `zeta * g` are combined by `u`
+        scalars_and_bases.merge(challenges.u, &shifted_scalars_and_bases);
+        let buffer_v_and_uv_basis = v_basis
+            .iter()
+            .zip(shifted_v_basis.iter())
+            .map(|(v_base, shifted_v_base)| *v_base + challenges.u * shifted_v_base)
+            .collect();
+        Ok((scalars_and_bases, buffer_v_and_uv_basis))
+    }
+
+    /// Aggregate the polynomial commitments opened at point `zeta` and at
+    /// point `zeta * g` into two commitments, each with the powers of `v`
+    /// that combine their evaluations, zero for the evaluations at the other
+    /// point. The commitment at `zeta` includes the linearization polynomial,
+    /// see [`Self::linearization_scalars_and_bases`].
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn aggregate_poly_commitments_by_point(
+        &self,
+        vks: &[&PcsVerifyingKey<PCS>],
+        challenges: &Challenges<PCS::Fr>,
+        vanish_eval: &PCS::Fr,
+        lagrange_1_eval: &PCS::Fr,
+        lagrange_n_eval: &PCS::Fr,
+        batch_proof: &BatchProofView<PCS>,
+        alpha_powers: &[PCS::Fr],
+        alpha_bases: &[PCS::Fr],
+    ) -> Result<(CommitmentAndCombiners<PCS>, CommitmentAndCombiners<PCS>), PlonkError> {
         if vks.len() != batch_proof.len() {
             return Err(ParameterError(format!(
                 "the number of verification keys {} != the number of instances {}",
@@ -460,45 +614,40 @@ where
             alpha_powers,
             alpha_bases,
         )?;
+        let mut shifted_scalars_and_bases = ScalarsAndBases::new();
 
         // the random combiner term for the polynomials evaluated at point `zeta`
         let mut v_base = challenges.v;
         // the random combiner term for the polynomials evaluated at point `zeta * g`
-        let mut uv_base = challenges.u;
+        let mut shifted_v_base = PCS::Fr::one();
 
-        // return buffer for aggregate_evaluations computation
-        let mut buffer_v_and_uv_basis = vec![];
+        // return buffers for aggregate_evaluations computation
+        let mut v_basis = vec![];
+        let mut shifted_v_basis = vec![];
 
         for (i, vk) in vks.iter().enumerate() {
             // Add poly commitments to be evaluated at point `zeta`.
             // Add wire witness polynomial commitments.
-            for &poly_comm in batch_proof.wires_poly_comms_vec[i].iter() {
-                buffer_v_and_uv_basis.push(v_base);
-                Self::add_poly_comm(
-                    &mut scalars_and_bases,
-                    &mut v_base,
-                    poly_comm.0,
-                    challenges.v,
-                );
+            for poly_comm in batch_proof.wires_poly_comms_vec[i].iter() {
+                v_basis.push(v_base);
+                shifted_v_basis.push(PCS::Fr::zero());
+                Self::add_poly_comm(&mut scalars_and_bases, &mut v_base, poly_comm, challenges.v);
             }
             // Add wire sigma polynomial commitments. The last sigma commitment is excluded.
             let num_wire_types = batch_proof.wires_poly_comms_vec[i].len();
-            for &poly_comm in vk.sigma_comms.iter().take(num_wire_types - 1) {
-                buffer_v_and_uv_basis.push(v_base);
-                Self::add_poly_comm(
-                    &mut scalars_and_bases,
-                    &mut v_base,
-                    poly_comm.0,
-                    challenges.v,
-                );
+            for poly_comm in vk.sigma_comms.iter().take(num_wire_types - 1) {
+                v_basis.push(v_base);
+                shifted_v_basis.push(PCS::Fr::zero());
+                Self::add_poly_comm(&mut scalars_and_bases, &mut v_base, poly_comm, challenges.v);
             }
 
             // Add poly commitments to be evaluated at point `zeta * g`.
-            buffer_v_and_uv_basis.push(uv_base);
+            v_basis.push(PCS::Fr::zero());
+            shifted_v_basis.push(shifted_v_base);
             Self::add_poly_comm(
-                &mut scalars_and_bases,
-                &mut uv_base,
-                batch_proof.prod_perm_poly_comms_vec[i].0,
+                &mut shifted_scalars_and_bases,
+                &mut shifted_v_base,
+                &batch_proof.prod_perm_poly_comms_vec[i],
                 challenges.v,
             );
 
@@ -506,9 +655,10 @@ where
             if let Some(proof_lkup) = batch_proof.plookup_proofs_vec[i].as_ref() {
                 // add commitments to be evaluated at point `zeta`
                 let plookup_comms = Self::plookup_open_poly_comms(proof_lkup, vk)?;
-                for &comm in plookup_comms.iter() {
-                    buffer_v_and_uv_basis.push(v_base);
-                    Self::add_poly_comm(&mut scalars_and_bases, &mut v_base, comm.0, challenges.v);
+                for comm in plookup_comms.iter() {
+                    v_basis.push(v_base);
+                    shifted_v_basis.push(PCS::Fr::zero());
+                    Self::add_poly_comm(&mut scalars_and_bases, &mut v_base, comm, challenges.v);
                 }
 
                 // add commitments to be evaluated at point `zeta * g`
@@ -517,14 +667,23 @@ where
                     vk,
                     &batch_proof.wires_poly_comms_vec[i],
                 )?;
-                for &comm in plookup_shifted_comms.iter() {
-                    buffer_v_and_uv_basis.push(uv_base);
-                    Self::add_poly_comm(&mut scalars_and_bases, &mut uv_base, comm.0, challenges.v);
+                for comm in plookup_shifted_comms.iter() {
+                    v_basis.push(PCS::Fr::zero());
+                    shifted_v_basis.push(shifted_v_base);
+                    Self::add_poly_comm(
+                        &mut shifted_scalars_and_bases,
+                        &mut shifted_v_base,
+                        comm,
+                        challenges.v,
+                    );
                 }
             }
         }
 
-        Ok((scalars_and_bases, buffer_v_and_uv_basis))
+        Ok((
+            (scalars_and_bases, v_basis),
+            (shifted_scalars_and_bases, shifted_v_basis),
+        ))
     }
 
     /// Compute the bases and scalars in the batched polynomial commitment,
@@ -533,15 +692,15 @@ where
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn linearization_scalars_and_bases(
         &self,
-        vks: &[&VerifyingKey<E>],
-        challenges: &Challenges<E::Fr>,
-        vanish_eval: &E::Fr,
-        lagrange_1_eval: &E::Fr,
-        lagrange_n_eval: &E::Fr,
-        batch_proof: &BatchProof<E>,
-        alpha_powers: &[E::Fr],
-        alpha_bases: &[E::Fr],
-    ) -> Result<ScalarsAndBases<E>, PlonkError> {
+        vks: &[&PcsVerifyingKey<PCS>],
+        challenges: &Challenges<PCS::Fr>,
+        vanish_eval: &PCS::Fr,
+        lagrange_1_eval: &PCS::Fr,
+        lagrange_n_eval: &PCS::Fr,
+        batch_proof: &BatchProofView<PCS>,
+        alpha_powers: &[PCS::Fr],
+        alpha_bases: &[PCS::Fr],
+    ) -> Result<ScalarsAndBases<PCS>, PlonkError> {
         if vks.len() != batch_proof.len() || alpha_bases.len() != vks.len() {
             return Err(ParameterError(format!(
                 "the number of verification keys {} != the number of instances {} or alpha bases {}",
@@ -553,7 +712,7 @@ where
         }
 
         // compute constants that are being reused
-        let beta_plus_one = E::Fr::one() + challenges.beta;
+        let beta_plus_one = PCS::Fr::one() + challenges.beta;
         let gamma_mul_beta_plus_one = beta_plus_one * challenges.gamma;
 
         let mut scalars_and_bases = ScalarsAndBases::new();
@@ -576,7 +735,7 @@ where
                 });
             coeff *= current_alpha_bases;
             // Add permutation product polynomial commitment.
-            scalars_and_bases.push(coeff, batch_proof.prod_perm_poly_comms_vec[i].0);
+            scalars_and_bases.push_commitment(coeff, &batch_proof.prod_perm_poly_comms_vec[i]);
 
             // Compute coefficient for the last wire sigma polynomial commitment.
             let num_wire_types = batch_proof.wires_poly_comms_vec[i].len();
@@ -595,16 +754,14 @@ where
                 )
                 * current_alpha_bases;
             // Add output wire sigma polynomial commitment.
-            scalars_and_bases.push(
-                -coeff,
-                vk.sigma_comms.last().ok_or(PlonkError::IndexError)?.0,
-            );
+            scalars_and_bases
+                .push_commitment(-coeff, vk.sigma_comms.last().ok_or(PlonkError::IndexError)?);
 
             // Add selector polynomial commitments.
             // Compute coefficients for selector polynomial commitments.
-            // The order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc
+            // The order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc, [q_custom]
             // TODO(binyi): get the order from a function.
-            let mut q_scalars = vec![E::Fr::zero(); 2 * GATE_WIDTH + 5];
+            let mut q_scalars = vec![PCS::Fr::zero(); 2 * GATE_WIDTH + 5];
             q_scalars[0] = w_evals[0];
             q_scalars[1] = w_evals[1];
             q_scalars[2] = w_evals[2];
@@ -616,16 +773,17 @@ where
             q_scalars[8] = w_evals[2].pow([5]);
             q_scalars[9] = w_evals[3].pow([5]);
             q_scalars[10] = -w_evals[4];
-            q_scalars[11] = E::Fr::one();
+            q_scalars[11] = PCS::Fr::one();
             q_scalars[12] = w_evals[0] * w_evals[1] * w_evals[2] * w_evals[3] * w_evals[4];
+            q_scalars.extend(vk.custom_gates.iter().map(|spec| spec.evaluate(w_evals)));
             for (&s, poly) in q_scalars.iter().zip(vk.selector_comms.iter()) {
-                scalars_and_bases.push(s * current_alpha_bases, poly.0);
+                scalars_and_bases.push_commitment(s * current_alpha_bases, poly);
             }
 
             // Add Plookup related commitments
             if let Some(lookup_proof) = batch_proof.plookup_proofs_vec[i].as_ref() {
                 let lookup_evals = &lookup_proof.poly_evals;
-                let merged_lookup_x = eval_merged_lookup_witness::<E>(
+                let merged_lookup_x = eval_merged_lookup_witness(
                     challenges.tau,
                     w_evals[5],
                     w_evals[0],
@@ -633,7 +791,7 @@ where
                     w_evals[2],
                     lookup_evals.q_lookup_eval,
                 );
-                let merged_table_x = eval_merged_table::<E>(
+                let merged_table_x = eval_merged_table(
                     challenges.tau,
                     lookup_evals.range_table_eval,
                     lookup_evals.key_table_eval,
@@ -641,7 +799,7 @@ where
                     w_evals[3],
                     w_evals[4],
                 );
-                let merged_table_xw = eval_merged_table::<E>(
+                let merged_table_xw = eval_merged_table(
                     challenges.tau,
                     lookup_evals.range_table_next_eval,
                     lookup_evals.key_table_next_eval,
@@ -665,7 +823,7 @@ where
                             + merged_table_x
                             + challenges.beta * merged_table_xw);
                 coeff = current_alpha_bases * coeff;
-                scalars_and_bases.push(coeff, lookup_proof.prod_lookup_poly_comm.0);
+                scalars_and_bases.push_commitment(coeff, &lookup_proof.prod_lookup_poly_comm);
 
                 // coefficient for h2(X):
                 // coeff_lin_poly = alpha_base * alpha^6 * (w^{n-1} - x)
@@ -678,24 +836,23 @@ where
                     * (gamma_mul_beta_plus_one
                         + lookup_evals.h_1_eval
                         + challenges.beta * lookup_evals.h_1_next_eval);
-                scalars_and_bases.push(coeff, lookup_proof.h_poly_comms[1].0);
+                scalars_and_bases.push_commitment(coeff, &lookup_proof.h_poly_comms[1]);
             }
         }
 
         // Add splitted quotient commitments
-        let zeta_to_n_plus_2 = (E::Fr::one() + vanish_eval) * challenges.zeta * challenges.zeta;
+        let zeta_to_chunk_size = challenges.zeta.pow(&[vks[0].quot_chunk_size() as u64]);
         let mut coeff = vanish_eval.neg();
-        scalars_and_bases.push(
+        scalars_and_bases.push_commitment(
             coeff,
             batch_proof
                 .split_quot_poly_comms
                 .first()
-                .ok_or(PlonkError::IndexError)?
-                .0,
+                .ok_or(PlonkError::IndexError)?,
         );
         for poly in batch_proof.split_quot_poly_comms.iter().skip(1) {
-            coeff *= zeta_to_n_plus_2;
-            scalars_and_bases.push(coeff, poly.0);
+            coeff *= zeta_to_chunk_size;
+            scalars_and_bases.push_commitment(coeff, poly);
         }
 
         Ok(scalars_and_bases)
@@ -705,14 +862,14 @@ where
     /// batch opening.
     /// The returned value is the scalar in `[E]1` described in Sec 8.4, step 11 of https://eprint.iacr.org/2019/953.pdf
     pub(crate) fn aggregate_evaluations(
-        lin_poly_constant: &E::Fr,
-        poly_evals_vec: &[ProofEvaluations<E::Fr>],
-        plookup_proofs_vec: &[Option<PlookupProof<E>>],
-        buffer_v_and_uv_basis: &[E::Fr],
-    ) -> Result<E::Fr, PlonkError> {
+        lin_poly_constant: &PCS::Fr,
+        poly_evals_vec: &[ProofEvaluations<PCS::Fr>],
+        plookup_proofs_vec: &[Option<PcsPlookupProof<PCS>>],
+        buffer_v_and_uv_basis: &[PCS::Fr],
+    ) -> Result<PCS::Fr, PlonkError> {
         assert_eq!(poly_evals_vec.len(), plookup_proofs_vec.len());
 
-        let mut result: E::Fr = lin_poly_constant.neg();
+        let mut result: PCS::Fr = lin_poly_constant.neg();
         let mut v_and_uv_basis = buffer_v_and_uv_basis.iter();
 
         for (poly_evals, plookup_proof) in poly_evals_vec.iter().zip(plookup_proofs_vec.iter()) {
@@ -782,46 +939,271 @@ where
     }
 }
 
-/// Private helper methods
-impl<E, F, P> Verifier<E>
+impl<E, F, P> Verifier<KzgPcs<E>>
 where
     E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWModelParameters<BaseField = F> + Clone,
+{
+    /// Prepare the (aggregated) polynomial commitment evaluation information.
+    pub(crate) fn prepare_pcs_info<T>(
+        &self,
+        verify_keys: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        batch_proof: &BatchProofView<KzgPcs<E>>,
+        extra_transcript_init_msg: &Option<Vec<u8>>,
+    ) -> Result<PcsInfo<E>, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let (pcs_info, _) = self.prepare_pcs_info_and_transcript::<T>(
+            verify_keys,
+            public_inputs,
+            batch_proof,
+            extra_transcript_init_msg,
+        )?;
+        Ok(pcs_info)
+    }
+
+    /// Prepare the (aggregated) polynomial commitment evaluation information
+    /// and return it along with the final transcript state. The claims at
+    /// `zeta * g` are combined with those at `zeta` by the challenge `u`.
+    pub(crate) fn prepare_pcs_info_and_transcript<T>(
+        &self,
+        verify_keys: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        batch_proof: &BatchProofView<KzgPcs<E>>,
+        extra_transcript_init_msg: &Option<Vec<u8>>,
+    ) -> Result<(PcsInfo<E>, T), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let (claims, challenges, transcript) = self.prepare_opening_claims_and_transcript::<T>(
+            verify_keys,
+            public_inputs,
+            batch_proof,
+            extra_transcript_init_msg,
+        )?;
+        let mut comm_scalars_and_bases = claims.comm_scalars_and_bases;
+        comm_scalars_and_bases.merge(challenges.u, &claims.shifted_comm_scalars_and_bases);
+
+        let pcs_info = PcsInfo {
+            u: challenges.u,
+            eval_point: claims.eval_point,
+            next_eval_point: claims.next_eval_point,
+            comm_scalars_and_bases,
+            eval: claims.eval + challenges.u * claims.shifted_eval,
+            opening_proof: *batch_proof.opening_proof,
+            shifted_opening_proof: *batch_proof.shifted_opening_proof,
+        };
+        Ok((pcs_info, transcript))
+    }
+
+    /// Batchly verify multiple (aggregated) PCS opening proofs.
+    ///
+    /// We need to verify that
+    /// - `e(Ai, [x]2) = e(Bi, [1]2) for i \in {0, .., m-1}`, where
+    /// - `Ai = [open_proof_i] + u_i * [shifted_open_proof_i]` and
+    /// - `Bi = eval_point_i * [open_proof_i] + u_i * next_eval_point_i *
+    ///   [shifted_open_proof_i] + comm_i - eval_i * [1]1`.
+    /// By Schwartz-Zippel lemma, it's equivalent to check that for a random r:
+    /// - `e(A0 + ... + r^{m-1} * Am, [x]2) = e(B0 + ... + r^{m-1} * Bm, [1]2)`.
+    pub(crate) fn batch_verify_opening_proofs<T>(
+        open_key: &OpenKey<E>,
+        pcs_infos: &[PcsInfo<E>],
+    ) -> Result<bool, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let (inner, outer) = Self::aggregate_opening_proofs::<T>(open_key, pcs_infos)?;
+        Ok(Self::verify_aggregated_opening_proofs(
+            open_key, &inner, &outer,
+        ))
+    }
+
+    /// Batchly verify multiple (aggregated) PCS opening proofs as
+    /// [`Self::batch_verify_opening_proofs`] does, accumulating the chunks of
+    /// `chunk_size` opening proofs in parallel.
+    pub(crate) fn batch_verify_opening_proofs_parallel<T>(
+        open_key: &OpenKey<E>,
+        pcs_infos: &[PcsInfo<E>],
+        chunk_size: usize,
+    ) -> Result<bool, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let (inner, outer) =
+            Self::aggregate_opening_proofs_parallel::<T>(open_key, pcs_infos, chunk_size)?;
+        Ok(Self::verify_aggregated_opening_proofs(
+            open_key, &inner, &outer,
+        ))
+    }
+
+    /// Compute the points `A` and `B` of the batched pairing check of
+    /// [`Self::batch_verify_opening_proofs`].
+    pub(crate) fn aggregate_opening_proofs<T>(
+        open_key: &OpenKey<E>,
+        pcs_infos: &[PcsInfo<E>],
+    ) -> Result<(E::G1Affine, E::G1Affine), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let r = Self::batch_challenge::<T>(pcs_infos)?;
+        let (inner, outer) = Self::aggregate_chunk(open_key, pcs_infos, r, E::Fr::one());
+        Ok((inner.into(), outer.into()))
+    }
+
+    /// Compute the points `A` and `B` of [`Self::aggregate_opening_proofs`]
+    /// by accumulating the chunks of `chunk_size` opening proofs in parallel.
+    /// The challenge `r` is derived from all the opening proofs, so that the
+    /// points don't depend on `chunk_size`.
+    /// Return error if `chunk_size` is zero.
+    pub(crate) fn aggregate_opening_proofs_parallel<T>(
+        open_key: &OpenKey<E>,
+        pcs_infos: &[PcsInfo<E>],
+        chunk_size: usize,
+    ) -> Result<(E::G1Affine, E::G1Affine), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if chunk_size == 0 {
+            return Err(ParameterError("the chunk size cannot be zero".to_string()).into());
+        }
+        let r = Self::batch_challenge::<T>(pcs_infos)?;
+
+        // the i-th chunk starts at the power r^{i * chunk_size}
+        let r_chunk = r.pow([chunk_size as u64]);
+        let mut r_starts = Vec::with_capacity((pcs_infos.len() + chunk_size - 1) / chunk_size);
+        let mut r_start = E::Fr::one();
+        for _ in pcs_infos.chunks(chunk_size) {
+            r_starts.push(r_start);
+            r_start *= r_chunk;
+        }
+
+        let (inner, outer) = pcs_infos
+            .par_chunks(chunk_size)
+            .zip(r_starts.par_iter())
+            .map(|(chunk, &r_start)| Self::aggregate_chunk(open_key, chunk, r, r_start))
+            .reduce(
+                || (E::G1Projective::zero(), E::G1Projective::zero()),
+                |(inner_a, outer_a), (inner_b, outer_b)| (inner_a + inner_b, outer_a + outer_b),
+            );
+        Ok((inner.into(), outer.into()))
+    }
+
+    /// Compute a pseudorandom challenge `r` from the instances.
+    fn batch_challenge<T>(pcs_infos: &[PcsInfo<E>]) -> Result<E::Fr, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if pcs_infos.len() == 1 {
+            // No need to use `r` when there is only a single proof.
+            return Ok(E::Fr::one());
+        }
+        let mut transcript = T::new(b"batch verify");
+        // r := hash(u1||u2||...||u_m), where u_i is the hash output of the i-th Plonk
+        // protocol transcript. This approach is more secure as `r` depends not only
+        // on the proofs, but also the list of public inputs and verifying keys.
+        for pcs_info in pcs_infos {
+            transcript.append_challenge::<E>(b"u", &pcs_info.u)?;
+        }
+        transcript.get_and_append_challenge::<E>(b"r")
+    }
+
+    /// Compute the terms of `A` and `B` of the opening proofs `pcs_infos`,
+    /// whose first one is combined with the power `r_start` of `r`.
+    fn aggregate_chunk(
+        open_key: &OpenKey<E>,
+        pcs_infos: &[PcsInfo<E>],
+        r: E::Fr,
+        r_start: E::Fr,
+    ) -> (E::G1Projective, E::G1Projective) {
+        // Compute A := A0 + r * A1 + ... + r^{m-1} * Am
+        let mut inners = ScalarsAndBases::<KzgPcs<E>>::new();
+        let mut r_base = r_start;
+        for pcs_info in pcs_infos.iter() {
+            inners.push(r_base, pcs_info.opening_proof.0);
+            inners.push(r_base * pcs_info.u, pcs_info.shifted_opening_proof.0);
+            r_base *= r;
+        }
+        let inner = inners.g1_multi_scalar_mul();
+
+        // Compute B := B0 + r * B1 + ... + r^{m-1} * Bm
+        let mut inners = ScalarsAndBases::<KzgPcs<E>>::new();
+        let mut r_base = r_start;
+        let mut sum_evals = E::Fr::zero();
+        for pcs_info in pcs_infos.iter() {
+            inners.merge(r_base, &pcs_info.comm_scalars_and_bases);
+            inners.push(r_base * pcs_info.eval_point, pcs_info.opening_proof.0);
+            inners.push(
+                r_base * pcs_info.u * pcs_info.next_eval_point,
+                pcs_info.shifted_opening_proof.0,
+            );
+            sum_evals += r_base * pcs_info.eval;
+            r_base *= r;
+        }
+        inners.push(-sum_evals, open_key.g);
+        let outer = inners.g1_multi_scalar_mul();
+        (inner, outer)
+    }
+
+    /// Check that `e(A, [x]2) = e(B, [1]2)`.
+    pub(crate) fn verify_aggregated_opening_proofs(
+        open_key: &OpenKey<E>,
+        inner: &E::G1Affine,
+        outer: &E::G1Affine,
+    ) -> bool {
+        // Add (A, [x]2) and (-B, [1]2) to the product pairing list
+        let g1_elems: Vec<<E as PairingEngine>::G1Affine> = vec![*inner, -*outer];
+        let g2_elems = vec![open_key.beta_h, open_key.h];
+        multi_pairing::<E>(&g1_elems, &g2_elems) == E::Fqk::one()
+    }
+}
+
+/// Private helper methods
+impl<PCS, F, P> Verifier<PCS>
+where
+    PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
+    P: SWModelParameters<BaseField = F> + Clone,
 {
     /// Merge a polynomial commitment into the aggregated polynomial commitment
     /// (in the ScalarAndBases form), update the random combiner afterward.
     #[inline]
     fn add_poly_comm(
-        scalar_and_bases: &mut ScalarsAndBases<E>,
-        random_combiner: &mut E::Fr,
-        comm: E::G1Affine,
-        r: E::Fr,
+        scalar_and_bases: &mut ScalarsAndBases<PCS>,
+        random_combiner: &mut PCS::Fr,
+        comm: &PCS::Commitment,
+        r: PCS::Fr,
     ) {
-        scalar_and_bases.push(*random_combiner, comm);
+        scalar_and_bases.push_commitment(*random_combiner, comm);
         *random_combiner *= r;
     }
 
     /// Add a polynomial commitment evaluation value to the aggregated
     /// polynomial evaluation, update the random combiner afterward.
     #[inline]
-    fn add_pcs_eval(result: &mut E::Fr, random_combiner: &E::Fr, eval: E::Fr) {
+    fn add_pcs_eval(result: &mut PCS::Fr, random_combiner: &PCS::Fr, eval: PCS::Fr) {
         *result += eval * (*random_combiner);
     }
 
     /// Evaluate vanishing polynomial at point `zeta`
     #[inline]
-    fn evaluate_vanishing_poly(&self, zeta: &E::Fr) -> E::Fr {
+    fn evaluate_vanishing_poly(&self, zeta: &PCS::Fr) -> PCS::Fr {
         self.domain.evaluate_vanishing_polynomial(*zeta)
     }
 
     /// Evaluate the first and the last lagrange polynomial at point `zeta`
     /// given the vanishing polynomial evaluation `vanish_eval`.
     #[inline]
-    fn evaluate_lagrange_1_and_n(&self, zeta: &E::Fr, vanish_eval: &E::Fr) -> (E::Fr, E::Fr) {
-        let divisor = E::Fr::from(self.domain.size() as u32) * (*zeta - E::Fr::one());
+    fn evaluate_lagrange_1_and_n(
+        &self,
+        zeta: &PCS::Fr,
+        vanish_eval: &PCS::Fr,
+    ) -> (PCS::Fr, PCS::Fr) {
+        let divisor = PCS::Fr::from(self.domain.size() as u32) * (*zeta - PCS::Fr::one());
         let lagrange_1_eval = *vanish_eval / divisor;
-        let divisor = E::Fr::from(self.domain.size() as u32) * (*zeta - self.domain.group_gen_inv);
+        let divisor =
+            PCS::Fr::from(self.domain.size() as u32) * (*zeta - self.domain.group_gen_inv);
         let lagrange_n_eval = *vanish_eval * self.domain.group_gen_inv / divisor;
         (lagrange_1_eval, lagrange_n_eval)
     }
@@ -830,9 +1212,9 @@ where
     /// Return the list of polynomial commitments to be opened at point `zeta`.
     /// The order should be consistent with the prover side.
     fn plookup_open_poly_comms(
-        proof: &PlookupProof<E>,
-        vk: &VerifyingKey<E>,
-    ) -> Result<Vec<Commitment<E>>, PlonkError> {
+        proof: &PcsPlookupProof<PCS>,
+        vk: &PcsVerifyingKey<PCS>,
+    ) -> Result<Vec<PCS::Commitment>, PlonkError> {
         Ok(vec![
             vk.plookup_vk.as_ref().unwrap().range_table_comm,
             vk.plookup_vk.as_ref().unwrap().key_table_comm,
@@ -845,10 +1227,10 @@ where
     /// Return the list of polynomial commitments to be opened at point `zeta *
     /// g`. The order should be consistent with the prover side.
     fn plookup_shifted_open_poly_comms(
-        proof: &PlookupProof<E>,
-        vk: &VerifyingKey<E>,
-        wires_poly_comms: &[Commitment<E>],
-    ) -> Result<Vec<Commitment<E>>, PlonkError> {
+        proof: &PcsPlookupProof<PCS>,
+        vk: &PcsVerifyingKey<PCS>,
+        wires_poly_comms: &[PCS::Commitment],
+    ) -> Result<Vec<PCS::Commitment>, PlonkError> {
         Ok(vec![
             proof.prod_lookup_poly_comm,
             vk.plookup_vk.as_ref().unwrap().range_table_comm,
@@ -877,28 +1259,35 @@ where
     /// \sum_{i=0..l/2} L_{i,H}(z) * pub_input[i] + \sum_{i=0..l/2} L_{n-i,H}(z)
     /// * pub_input[l/2+i]
     ///
-    /// TODO: reuse the lagrange values
-    fn evaluate_pi_poly(
+    /// When the Lagrange bases of the public inputs are precomputed, the
+    /// denominators `z - g^i` are inverted at once.
+    pub(crate) fn evaluate_pi_poly(
         &self,
-        pub_input: &[E::Fr],
-        z: &E::Fr,
-        vanish_eval: &E::Fr,
+        pub_input: &[PCS::Fr],
+        z: &PCS::Fr,
+        vanish_eval: &PCS::Fr,
         circuit_is_merged: bool,
-    ) -> Result<E::Fr, PlonkError> {
+    ) -> Result<PCS::Fr, PlonkError> {
         // If z is a root of the vanishing polynomial, directly return zero.
         if vanish_eval.is_zero() {
-            return Ok(E::Fr::zero());
+            return Ok(PCS::Fr::zero());
+        }
+        if let Some(bases) = self.pi_bases.as_ref().filter(|bases| {
+            bases.num_inputs == pub_input.len() && bases.is_merged == circuit_is_merged
+        }) {
+            return Ok(bases.evaluate(pub_input, z, vanish_eval));
         }
+
         let len = match circuit_is_merged {
             false => pub_input.len(),
             true => pub_input.len() / 2,
         };
 
-        let vanish_eval_div_n = E::Fr::from(self.domain.size() as u32)
+        let vanish_eval_div_n = PCS::Fr::from(self.domain.size() as u32)
             .inverse()
             .ok_or(PlonkError::DivisionError)?
             * (*vanish_eval);
-        let mut result = E::Fr::zero();
+        let mut result = PCS::Fr::zero();
         for (i, val) in pub_input.iter().take(len).enumerate() {
             let lagrange_i =
                 vanish_eval_div_n * self.domain.element(i) / (*z - self.domain.element(i));
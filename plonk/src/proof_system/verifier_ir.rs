@@ -0,0 +1,493 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Symbolic description of the Plonk verifier, to be lowered into the
+//! constraints of another proof system, e.g. for recursion.
+//!
+//! [`VerifyingKey::verifier_circuit_ir`] describes the verification of a
+//! single TurboPlonk proof, once the challenges have been derived from the
+//! transcript, as a list of field operations over the challenges, the public
+//! inputs and the polynomial evaluations of the proof. The operations compute
+//! the scalars of the two multi-scalar multiplications of the final pairing
+//! check, which is kept abstract: a backend lowers group and pairing
+//! operations as it sees fit.
+//!
+//! The challenges are inputs of the IR: deriving them from the transcript is
+//! left to the backend, whose hash is specific to the target proof system.
+//! UltraPlonk keys are rejected.
+
+use crate::{
+    constants::GATE_WIDTH,
+    errors::{PlonkError, SnarkError::ParameterError},
+    proof_system::structs::VerifyingKey,
+};
+use ark_ec::PairingEngine;
+use ark_ff::{Field, One, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_std::{format, vec, vec::Vec};
+
+/// A value of the IR, namely the index of the operation computing it.
+pub type IrVar = usize;
+
+/// An input of the verifier computation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IrInput {
+    /// The challenge `beta`.
+    Beta,
+    /// The challenge `gamma`.
+    Gamma,
+    /// The challenge `alpha`.
+    Alpha,
+    /// The challenge `zeta`.
+    Zeta,
+    /// The challenge `v`.
+    V,
+    /// The challenge `u`.
+    U,
+    /// The i-th public input.
+    PublicInput(usize),
+    /// The evaluation of the i-th wire witness polynomial at `zeta`.
+    WireEval(usize),
+    /// The evaluation of the i-th permutation polynomial at `zeta`.
+    SigmaEval(usize),
+    /// The evaluation of the permutation product polynomial at `zeta * g`.
+    PermNextEval,
+}
+
+/// A field operation of the IR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IrOp<F: Field> {
+    /// An input.
+    Input(IrInput),
+    /// A constant.
+    Constant(F),
+    /// The sum of two values.
+    Add(IrVar, IrVar),
+    /// The difference of two values.
+    Sub(IrVar, IrVar),
+    /// The product of two values.
+    Mul(IrVar, IrVar),
+    /// The inverse of a value, which should be non-zero.
+    Inverse(IrVar),
+}
+
+/// A group element of the final pairing check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IrBase {
+    /// The commitment of the i-th wire witness polynomial, from the proof.
+    WireComm(usize),
+    /// The commitment of the permutation product polynomial, from the proof.
+    ProdPermComm,
+    /// The commitment of the i-th split quotient polynomial, from the proof.
+    SplitQuotComm(usize),
+    /// The opening proof at `zeta`, from the proof.
+    OpeningProof,
+    /// The opening proof at `zeta * g`, from the proof.
+    ShiftedOpeningProof,
+    /// The commitment of the i-th permutation polynomial, from the key.
+    SigmaComm(usize),
+    /// The commitment of the i-th selector polynomial, from the key.
+    SelectorComm(usize),
+    /// The generator `[1]1` of the opening key.
+    G1Generator,
+}
+
+/// The verifier computation as a list of field operations, whose results
+/// are the scalars of the final pairing check
+/// `e(sum(lhs), [x]2) = e(sum(rhs), [1]2)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierCircuitIr<F: Field> {
+    /// The operations, each one using values of previous ones only.
+    pub ops: Vec<IrOp<F>>,
+    /// The terms (scalar, base) of the left-hand side of the pairing check,
+    /// paired with `[x]2`.
+    pub lhs: Vec<(IrVar, IrBase)>,
+    /// The terms (scalar, base) of the right-hand side of the pairing check,
+    /// paired with `[1]2`.
+    pub rhs: Vec<(IrVar, IrBase)>,
+}
+
+impl<F: Field> VerifierCircuitIr<F> {
+    /// Interpret the operations over concrete values, where `inputs` returns
+    /// the value of each input, and return the values of all operations.
+    /// Return error if an input is missing or if a zero value is inverted.
+    pub fn evaluate<I>(&self, inputs: I) -> Result<Vec<F>, PlonkError>
+    where
+        I: Fn(IrInput) -> Option<F>,
+    {
+        let mut values: Vec<F> = Vec::with_capacity(self.ops.len());
+        for op in self.ops.iter() {
+            let value = match *op {
+                IrOp::Input(input) => inputs(input)
+                    .ok_or_else(|| ParameterError(format!("missing value of {:?}", input)))?,
+                IrOp::Constant(c) => c,
+                IrOp::Add(a, b) => values[a] + values[b],
+                IrOp::Sub(a, b) => values[a] - values[b],
+                IrOp::Mul(a, b) => values[a] * values[b],
+                IrOp::Inverse(a) => values[a].inverse().ok_or(PlonkError::DivisionError)?,
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    fn push(&mut self, op: IrOp<F>) -> IrVar {
+        self.ops.push(op);
+        self.ops.len() - 1
+    }
+
+    fn input(&mut self, input: IrInput) -> IrVar {
+        self.push(IrOp::Input(input))
+    }
+
+    fn constant(&mut self, c: F) -> IrVar {
+        self.push(IrOp::Constant(c))
+    }
+
+    fn add(&mut self, a: IrVar, b: IrVar) -> IrVar {
+        self.push(IrOp::Add(a, b))
+    }
+
+    fn sub(&mut self, a: IrVar, b: IrVar) -> IrVar {
+        self.push(IrOp::Sub(a, b))
+    }
+
+    fn mul(&mut self, a: IrVar, b: IrVar) -> IrVar {
+        self.push(IrOp::Mul(a, b))
+    }
+
+    fn inverse(&mut self, a: IrVar) -> IrVar {
+        self.push(IrOp::Inverse(a))
+    }
+
+    fn neg(&mut self, a: IrVar) -> IrVar {
+        let zero = self.constant(F::zero());
+        self.sub(zero, a)
+    }
+
+    fn pow(&mut self, a: IrVar, exp: usize) -> IrVar {
+        (1..exp).fold(a, |acc, _| self.mul(acc, a))
+    }
+}
+
+impl<E: PairingEngine> VerifyingKey<E> {
+    /// Describe the verification of a proof for the key, once its
+    /// challenges are computed, as a [`VerifierCircuitIr`]. The computation
+    /// matches the verification of [`crate::proof_system::PlonkKzgSnark`].
+    /// Return error for UltraPlonk, merged keys, keys with custom gates or
+    /// with a non-default quotient polynomial split, which are not supported
+    /// yet.
+    pub fn verifier_circuit_ir(&self) -> Result<VerifierCircuitIr<E::Fr>, PlonkError> {
+        if self.plookup_vk.is_some() || self.is_merged || !self.custom_gates.is_empty() {
+            return Err(ParameterError(
+                "only TurboPlonk keys of non-merged circuits without custom gates are supported"
+                    .into(),
+            )
+            .into());
+        }
+        if self.num_quot_chunks != self.sigma_comms.len() {
+            return Err(ParameterError(
+                "only the default quotient polynomial split is supported".into(),
+            )
+            .into());
+        }
+        let domain = Radix2EvaluationDomain::<E::Fr>::new(self.domain_size)
+            .ok_or(PlonkError::DomainCreationError)?;
+        let n = domain.size();
+        let num_wire_types = GATE_WIDTH + 1;
+        let mut ir = VerifierCircuitIr {
+            ops: vec![],
+            lhs: vec![],
+            rhs: vec![],
+        };
+
+        let beta = ir.input(IrInput::Beta);
+        let gamma = ir.input(IrInput::Gamma);
+        let alpha = ir.input(IrInput::Alpha);
+        let zeta = ir.input(IrInput::Zeta);
+        let v = ir.input(IrInput::V);
+        let u = ir.input(IrInput::U);
+        let w_evals: Vec<IrVar> = (0..num_wire_types)
+            .map(|i| ir.input(IrInput::WireEval(i)))
+            .collect();
+        let sigma_evals: Vec<IrVar> = (0..num_wire_types - 1)
+            .map(|i| ir.input(IrInput::SigmaEval(i)))
+            .collect();
+        let perm_next_eval = ir.input(IrInput::PermNextEval);
+        let one = ir.constant(E::Fr::one());
+        let alpha_2 = ir.mul(alpha, alpha);
+
+        // vanishing polynomial zeta^n - 1, with n a power of two
+        let zeta_to_n = (0..n.trailing_zeros()).fold(zeta, |acc, _| ir.mul(acc, acc));
+        let vanish_eval = ir.sub(zeta_to_n, one);
+        // L1(zeta) = Z_H(zeta) / (n * (zeta - 1))
+        let n_var = ir.constant(E::Fr::from(n as u64));
+        let zeta_minus_one = ir.sub(zeta, one);
+        let divisor = ir.mul(n_var, zeta_minus_one);
+        let divisor_inv = ir.inverse(divisor);
+        let lagrange_1_eval = ir.mul(vanish_eval, divisor_inv);
+
+        // PI(zeta) = \sum_i Z_H(zeta) * g^i / (n * (zeta - g^i)) * pub_input[i]
+        let n_inv = domain.size_as_field_element().inverse().unwrap();
+        let mut pi_eval = ir.constant(E::Fr::zero());
+        for i in 0..self.num_inputs {
+            let pub_input = ir.input(IrInput::PublicInput(i));
+            let g_i = domain.element(i);
+            let g_i_var = ir.constant(g_i);
+            let g_i_div_n = ir.constant(g_i * n_inv);
+            let zeta_minus_g_i = ir.sub(zeta, g_i_var);
+            let inv = ir.inverse(zeta_minus_g_i);
+            let lagrange_i = ir.mul(vanish_eval, g_i_div_n);
+            let lagrange_i = ir.mul(lagrange_i, inv);
+            let term = ir.mul(lagrange_i, pub_input);
+            pi_eval = ir.add(pi_eval, term);
+        }
+
+        // the constant term of the linearization polynomial:
+        // r_0 = PI - L1 * alpha^2 - alpha * z(zeta * g) * (w_{m} + gamma)
+        //       * \prod_i=1..m-1 (w_i + beta * sigma_i + gamma)
+        let last_w_plus_gamma = ir.add(w_evals[num_wire_types - 1], gamma);
+        let mut perm_term = ir.mul(alpha, perm_next_eval);
+        perm_term = ir.mul(perm_term, last_w_plus_gamma);
+        let mut sigma_product = alpha;
+        sigma_product = ir.mul(sigma_product, beta);
+        sigma_product = ir.mul(sigma_product, perm_next_eval);
+        for (&w_eval, &sigma_eval) in w_evals.iter().zip(sigma_evals.iter()) {
+            let beta_sigma = ir.mul(beta, sigma_eval);
+            let factor = ir.add(beta_sigma, gamma);
+            let factor = ir.add(factor, w_eval);
+            perm_term = ir.mul(perm_term, factor);
+            sigma_product = ir.mul(sigma_product, factor);
+        }
+        let l1_alpha_2 = ir.mul(lagrange_1_eval, alpha_2);
+        let lin_poly_constant = ir.sub(pi_eval, l1_alpha_2);
+        let lin_poly_constant = ir.sub(lin_poly_constant, perm_term);
+
+        // the linearization polynomial commitment [D]1
+        let mut comm_terms = vec![];
+        // permutation product: L1 * alpha^2 + alpha * \prod_i (beta * k_i * zeta +
+        // gamma + w_i)
+        let mut prod_perm_coeff = alpha;
+        for (&w_eval, &k) in w_evals.iter().zip(self.k.iter()) {
+            let beta_k = ir.constant(k);
+            let beta_k = ir.mul(beta, beta_k);
+            let beta_k_zeta = ir.mul(beta_k, zeta);
+            let factor = ir.add(beta_k_zeta, gamma);
+            let factor = ir.add(factor, w_eval);
+            prod_perm_coeff = ir.mul(prod_perm_coeff, factor);
+        }
+        let prod_perm_coeff = ir.add(prod_perm_coeff, l1_alpha_2);
+        comm_terms.push((prod_perm_coeff, IrBase::ProdPermComm));
+        // last permutation polynomial
+        let sigma_coeff = ir.neg(sigma_product);
+        comm_terms.push((sigma_coeff, IrBase::SigmaComm(num_wire_types - 1)));
+        // selectors, in the order q_lc, q_mul, q_hash, q_o, q_c, q_ecc
+        let mut q_scalars = w_evals[..GATE_WIDTH].to_vec();
+        q_scalars.push(ir.mul(w_evals[0], w_evals[1]));
+        q_scalars.push(ir.mul(w_evals[2], w_evals[3]));
+        for &w_eval in w_evals[..GATE_WIDTH].iter() {
+            q_scalars.push(ir.pow(w_eval, 5));
+        }
+        q_scalars.push(ir.neg(w_evals[GATE_WIDTH]));
+        q_scalars.push(one);
+        let w_product = w_evals[1..]
+            .iter()
+            .fold(w_evals[0], |acc, &w| ir.mul(acc, w));
+        q_scalars.push(w_product);
+        for (i, &scalar) in q_scalars.iter().enumerate().take(self.selector_comms.len()) {
+            comm_terms.push((scalar, IrBase::SelectorComm(i)));
+        }
+        // split quotient polynomials: -Z_H(zeta) * zeta^{(n+2) * i}
+        let zeta_2 = ir.mul(zeta, zeta);
+        let zeta_to_n_plus_2 = ir.mul(zeta_to_n, zeta_2);
+        let mut quot_coeff = ir.neg(vanish_eval);
+        for i in 0..num_wire_types {
+            if i > 0 {
+                quot_coeff = ir.mul(quot_coeff, zeta_to_n_plus_2);
+            }
+            comm_terms.push((quot_coeff, IrBase::SplitQuotComm(i)));
+        }
+
+        // batch the opened polynomials with powers of `v`, and with `u` for the
+        // permutation product opened at `zeta * g`; the aggregated evaluation is
+        // [E]1 = -r_0 + \sum_i v^i * eval_i + u * z(zeta * g)
+        let mut eval = ir.neg(lin_poly_constant);
+        let mut v_base = v;
+        let opened = w_evals
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| (e, IrBase::WireComm(i)))
+            .chain(
+                sigma_evals
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &e)| (e, IrBase::SigmaComm(i))),
+            )
+            .collect::<Vec<_>>();
+        for (i, &(poly_eval, base)) in opened.iter().enumerate() {
+            if i > 0 {
+                v_base = ir.mul(v_base, v);
+            }
+            comm_terms.push((v_base, base));
+            let term = ir.mul(v_base, poly_eval);
+            eval = ir.add(eval, term);
+        }
+        comm_terms.push((u, IrBase::ProdPermComm));
+        let term = ir.mul(u, perm_next_eval);
+        eval = ir.add(eval, term);
+
+        // e(open + u * shifted_open, [x]2) = e(zeta * open + u * zeta * g *
+        // shifted_open + [D]1 - [E]1, [1]2)
+        ir.lhs = vec![
+            (one, IrBase::OpeningProof),
+            (u, IrBase::ShiftedOpeningProof),
+        ];
+        let g = ir.constant(domain.group_gen);
+        let next_zeta = ir.mul(zeta, g);
+        let u_next_zeta = ir.mul(u, next_zeta);
+        let neg_eval = ir.neg(eval);
+        ir.rhs = comm_terms;
+        ir.rhs.push((zeta, IrBase::OpeningProof));
+        ir.rhs.push((u_next_zeta, IrBase::ShiftedOpeningProof));
+        ir.rhs.push((neg_eval, IrBase::G1Generator));
+        Ok(ir)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::{customized::ecc::SWToTEConParam, Arithmetization, Circuit},
+        proof_system::{
+            pcs::KzgPcs, snark::test::gen_circuit_for_test, structs::Proof, verifier::Verifier,
+            PlonkKzgSnark, Snark,
+        },
+        transcript::standard::StandardTranscript,
+        PlonkType,
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_ec::{
+        short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve, SWModelParameters,
+    };
+    use ark_ff::PrimeField;
+    use ark_std::test_rng;
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_verifier_circuit_ir() -> Result<(), PlonkError> {
+        test_verifier_circuit_ir_helper::<Bn254, Fq254, _>()?;
+        test_verifier_circuit_ir_helper::<Bls12_377, Fq377, _>()
+    }
+
+    fn test_verifier_circuit_ir_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut test_rng();
+        let circuit = gen_circuit_for_test(3, 4, PlonkType::TurboPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(rng, &circuit, &pk, None)?;
+        let public_inputs = circuit.public_input()?;
+        let ir = vk.verifier_circuit_ir()?;
+
+        // the interpreted IR accepts the valid proof
+        assert!(interpret_ir(&ir, &vk, &public_inputs, &proof)?);
+        // and rejects wrong public inputs
+        let mut bad_public_inputs = public_inputs.clone();
+        bad_public_inputs[0] += E::Fr::one();
+        assert!(!interpret_ir(&ir, &vk, &bad_public_inputs, &proof)?);
+        // or a wrong proof
+        let mut bad_proof = proof.clone();
+        bad_proof.poly_evals.wires_evals[0] += E::Fr::one();
+        assert!(!interpret_ir(&ir, &vk, &public_inputs, &bad_proof)?);
+        assert!(PlonkKzgSnark::<E>::verify::<StandardTranscript>(
+            &vk,
+            &public_inputs,
+            &proof,
+            None
+        )
+        .is_ok());
+
+        // the operations only use previous values
+        for (i, op) in ir.ops.iter().enumerate() {
+            match *op {
+                IrOp::Add(a, b) | IrOp::Sub(a, b) | IrOp::Mul(a, b) => assert!(a < i && b < i),
+                IrOp::Inverse(a) => assert!(a < i),
+                _ => (),
+            }
+        }
+        // missing inputs
+        assert!(ir.evaluate(|_| None).is_err());
+
+        // UltraPlonk keys are not supported
+        let circuit = gen_circuit_for_test(3, 4, PlonkType::UltraPlonk)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (_, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        assert!(vk.verifier_circuit_ir().is_err());
+
+        Ok(())
+    }
+
+    // Evaluate the IR on the challenges and evaluations of `proof`, and run the
+    // pairing check.
+    fn interpret_ir<E, F, P>(
+        ir: &VerifierCircuitIr<E::Fr>,
+        vk: &VerifyingKey<E>,
+        public_inputs: &[E::Fr],
+        proof: &Proof<E>,
+    ) -> Result<bool, PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let challenges = Verifier::<KzgPcs<E>>::compute_challenges::<StandardTranscript>(
+            &[vk],
+            &[public_inputs],
+            &proof.into(),
+            &None,
+        )?;
+        let evals = &proof.poly_evals;
+        let values = ir.evaluate(|input| match input {
+            IrInput::Beta => Some(challenges.beta),
+            IrInput::Gamma => Some(challenges.gamma),
+            IrInput::Alpha => Some(challenges.alpha),
+            IrInput::Zeta => Some(challenges.zeta),
+            IrInput::V => Some(challenges.v),
+            IrInput::U => Some(challenges.u),
+            IrInput::PublicInput(i) => public_inputs.get(i).copied(),
+            IrInput::WireEval(i) => evals.wires_evals.get(i).copied(),
+            IrInput::SigmaEval(i) => evals.wire_sigma_evals.get(i).copied(),
+            IrInput::PermNextEval => Some(evals.perm_next_eval),
+        })?;
+
+        let base = |b: IrBase| match b {
+            IrBase::WireComm(i) => proof.wires_poly_comms[i].0,
+            IrBase::ProdPermComm => proof.prod_perm_poly_comm.0,
+            IrBase::SplitQuotComm(i) => proof.split_quot_poly_comms[i].0,
+            IrBase::OpeningProof => proof.opening_proof.0,
+            IrBase::ShiftedOpeningProof => proof.shifted_opening_proof.0,
+            IrBase::SigmaComm(i) => vk.sigma_comms[i].0,
+            IrBase::SelectorComm(i) => vk.selector_comms[i].0,
+            IrBase::G1Generator => vk.open_key.g,
+        };
+        let msm = |terms: &[(IrVar, IrBase)]| {
+            terms
+                .iter()
+                .fold(E::G1Projective::zero(), |acc, &(scalar, b)| {
+                    acc + base(b).mul(values[scalar].into_repr())
+                })
+                .into_affine()
+        };
+        Ok(E::pairing(msm(&ir.lhs), vk.open_key.beta_h) == E::pairing(msm(&ir.rhs), vk.open_key.h))
+    }
+}
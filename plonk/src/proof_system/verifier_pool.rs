@@ -0,0 +1,329 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A pool verifying Plonk proofs in the background.
+//!
+//! Proofs submitted to a [`VerifierPool`] are collected by a worker thread
+//! during a time window, then verified at once with
+//! [`PlonkKzgSnark::batch_verify`]. Each submission returns a
+//! [`VerdictFuture`] resolving to the verdict of its own proof: when the batch
+//! is rejected, its proofs are verified one by one to find the invalid ones.
+
+use super::{
+    structs::{Proof, VerifyingKey},
+    PlonkKzgSnark,
+};
+use crate::{
+    circuit::customized::ecc::SWToTEConParam,
+    errors::{PlonkError, SnarkError::ParameterError},
+    transcript::PlonkTranscript,
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
+use ark_std::{marker::PhantomData, string::ToString, vec::Vec};
+use jf_rescue::RescueParameter;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// A pool of verification work, batching the proofs submitted within a time
+/// window of each other. The worker thread stops once the pool is dropped,
+/// after verifying the pending submissions.
+pub struct VerifierPool<E: PairingEngine, T> {
+    sender: Mutex<Option<Sender<Submission<E>>>>,
+    worker: Option<JoinHandle<()>>,
+    _transcript: PhantomData<fn() -> T>,
+}
+
+/// A future resolving to the verdict of a proof submitted to a
+/// [`VerifierPool`]: `Ok(())` if the proof is valid, an error otherwise.
+pub struct VerdictFuture {
+    state: Arc<Mutex<VerdictState>>,
+}
+
+// The verdict of a submission, shared between the worker and the future.
+#[derive(Default)]
+struct VerdictState {
+    verdict: Option<Result<(), PlonkError>>,
+    waker: Option<Waker>,
+}
+
+// A proof to verify, with its verifying key and public input.
+struct Submission<E: PairingEngine> {
+    verify_key: VerifyingKey<E>,
+    public_input: Vec<E::Fr>,
+    proof: Proof<E>,
+    state: Arc<Mutex<VerdictState>>,
+}
+
+impl<E, F, P, T> VerifierPool<E, T>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + Clone,
+    T: PlonkTranscript<F> + 'static,
+{
+    /// Start a pool whose batches gather the submissions received within
+    /// `window` of the first one, up to `max_batch_size` submissions.
+    /// Return error if `max_batch_size` is zero.
+    pub fn new(window: Duration, max_batch_size: usize) -> Result<Self, PlonkError> {
+        if max_batch_size == 0 {
+            return Err(ParameterError("the maximum batch size cannot be zero".to_string()).into());
+        }
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || Self::run(receiver, window, max_batch_size));
+        Ok(Self {
+            sender: Mutex::new(Some(sender)),
+            worker: Some(worker),
+            _transcript: PhantomData,
+        })
+    }
+
+    /// Submit `proof` for verification against `verify_key` and
+    /// `public_input`, without blocking.
+    pub fn submit(
+        &self,
+        verify_key: VerifyingKey<E>,
+        public_input: Vec<E::Fr>,
+        proof: Proof<E>,
+    ) -> VerdictFuture {
+        let state = Arc::new(Mutex::new(VerdictState::default()));
+        let submission = Submission {
+            verify_key,
+            public_input,
+            proof,
+            state: state.clone(),
+        };
+        // the worker only stops once the sender is dropped
+        let sent = self
+            .sender
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(false, |sender| sender.send(submission).is_ok());
+        if !sent {
+            resolve(
+                &state,
+                Err(ParameterError("the verifier pool is stopped".to_string()).into()),
+            );
+        }
+        VerdictFuture { state }
+    }
+
+    // Collect the submissions into batches and verify them, until the pool is
+    // dropped.
+    fn run(receiver: Receiver<Submission<E>>, window: Duration, max_batch_size: usize) {
+        while let Ok(first) = receiver.recv() {
+            let deadline = Instant::now() + window;
+            let mut batch = vec![first];
+            while batch.len() < max_batch_size {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match receiver.recv_timeout(timeout) {
+                    Ok(submission) => batch.push(submission),
+                    Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            Self::verify_batch(&batch);
+        }
+    }
+
+    // Verify a batch at once, falling back to verifying its proofs one by one
+    // if it is rejected.
+    fn verify_batch(batch: &[Submission<E>]) {
+        let verify_keys: Vec<&VerifyingKey<E>> = batch.iter().map(|s| &s.verify_key).collect();
+        let public_inputs: Vec<&[E::Fr]> =
+            batch.iter().map(|s| s.public_input.as_slice()).collect();
+        let proofs: Vec<&Proof<E>> = batch.iter().map(|s| &s.proof).collect();
+        let extra_msgs = vec![None; batch.len()];
+        if PlonkKzgSnark::<E>::batch_verify::<T>(&verify_keys, &public_inputs, &proofs, &extra_msgs)
+            .is_ok()
+        {
+            for submission in batch.iter() {
+                resolve(&submission.state, Ok(()));
+            }
+            return;
+        }
+        for submission in batch.iter() {
+            let verdict = PlonkKzgSnark::<E>::batch_verify::<T>(
+                &[&submission.verify_key],
+                &[&submission.public_input],
+                &[&submission.proof],
+                &[None],
+            );
+            resolve(&submission.state, verdict);
+        }
+    }
+}
+
+impl<E: PairingEngine, T> Drop for VerifierPool<E, T> {
+    fn drop(&mut self) {
+        // closing the channel stops the worker after the pending submissions
+        if let Ok(sender) = self.sender.get_mut() {
+            *sender = None;
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Future for VerdictFuture {
+    type Output = Result<(), PlonkError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.verdict.take() {
+            Some(verdict) => Poll::Ready(verdict),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+// Set the verdict of a submission and wake up its future.
+fn resolve(state: &Mutex<VerdictState>, verdict: Result<(), PlonkError>) {
+    let mut state = state.lock().unwrap();
+    state.verdict = Some(verdict);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::{Arithmetization, Circuit},
+        proof_system::{snark::test::gen_circuit_for_test, Snark},
+        transcript::StandardTranscript,
+        PlonkType,
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_ff::One;
+    use ark_std::test_rng;
+    use std::task::Wake;
+
+    // Wake up a thread blocked on a future.
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    // Block the current thread until `future` resolves.
+    fn block_on<O>(mut future: impl Future<Output = O> + Unpin) -> O {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match Pin::new(&mut future).poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_verifier_pool() -> Result<(), PlonkError> {
+        test_verifier_pool_helper::<Bn254, Fq254, _>()?;
+        test_verifier_pool_helper::<Bls12_377, Fq377, _>()
+    }
+
+    fn test_verifier_pool_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut test_rng();
+        let circuits = (0..4)
+            .map(|i| {
+                let plonk_type = match i % 2 {
+                    0 => PlonkType::TurboPlonk,
+                    _ => PlonkType::UltraPlonk,
+                };
+                gen_circuit_for_test(i + 3, i + 4, plonk_type)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let srs_size = circuits
+            .iter()
+            .map(|circuit| circuit.srs_size())
+            .collect::<Result<Vec<_>, PlonkError>>()?
+            .into_iter()
+            .max()
+            .unwrap();
+        let srs = PlonkKzgSnark::<E>::universal_setup(srs_size, rng)?;
+        let mut instances = Vec::new();
+        for circuit in circuits.iter() {
+            let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, circuit)?;
+            let proof =
+                PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(rng, circuit, &pk, None)?;
+            instances.push((vk, circuit.public_input()?, proof));
+        }
+
+        // all proofs in one batch, or each in its own batch
+        for &(window, max_batch_size) in [(Duration::from_secs(1), 8), (Duration::ZERO, 1)].iter() {
+            let pool = VerifierPool::<E, StandardTranscript>::new(window, max_batch_size)?;
+            // a mix of valid and invalid submissions, with a wrong public input
+            // for every third one
+            let futures: Vec<_> = (0..8)
+                .map(|i| {
+                    let (vk, public_input, proof) = instances[i % instances.len()].clone();
+                    let mut public_input = public_input;
+                    if i % 3 == 0 {
+                        public_input[0] += E::Fr::one();
+                    }
+                    pool.submit(vk, public_input, proof)
+                })
+                .collect();
+            for (i, future) in futures.into_iter().enumerate() {
+                assert_eq!(block_on(future).is_ok(), i % 3 != 0, "submission {}", i);
+            }
+        }
+
+        // concurrent submissions from several threads
+        let pool = Arc::new(VerifierPool::<E, StandardTranscript>::new(
+            Duration::from_millis(50),
+            16,
+        )?);
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let pool = pool.clone();
+                let (vk, public_input, proof) = instances[i].clone();
+                thread::spawn(move || {
+                    let valid = pool.submit(vk.clone(), public_input.clone(), proof.clone());
+                    let invalid = pool.submit(vk, public_input, swap_openings(proof));
+                    (block_on(valid).is_ok(), block_on(invalid).is_ok())
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), (true, false));
+        }
+
+        // wrong parameters
+        assert!(VerifierPool::<E, StandardTranscript>::new(Duration::ZERO, 0).is_err());
+
+        Ok(())
+    }
+
+    // An invalid proof, with its openings swapped.
+    fn swap_openings<E: PairingEngine>(mut proof: Proof<E>) -> Proof<E> {
+        ark_std::mem::swap(&mut proof.opening_proof, &mut proof.shifted_opening_proof);
+        proof
+    }
+}
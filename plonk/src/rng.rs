@@ -0,0 +1,151 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Deterministic randomness for reproducible proofs in tests.
+//! It is exposed under the `test-rng` feature.
+//! The randomness is predictable, hence proofs generated with it are NOT
+//! zero-knowledge: it should not be used for other purposes.
+
+use crate::{circuit::Arithmetization, errors::PlonkError};
+use ark_ff::FftField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{rand::SeedableRng, vec::Vec};
+use rand_chacha::ChaChaRng;
+use sha3::{Digest, Keccak256};
+
+const DETERMINISTIC_RNG_LABEL: &[u8] = b"jf-plonk deterministic rng";
+
+/// Return an RNG seeded with a hash of the digest of `circuit`, namely its
+/// selector and permutation polynomials, and of `public_inputs`. Proving the
+/// same circuit for the same public inputs with such RNGs produces identical
+/// proofs.
+///
+/// `circuit` has to be finalized, otherwise return error.
+pub fn deterministic_rng_for<F, C>(
+    circuit: &C,
+    public_inputs: &[F],
+) -> Result<ChaChaRng, PlonkError>
+where
+    F: FftField,
+    C: Arithmetization<F>,
+{
+    let mut circuit_digest = Vec::new();
+    for poly in circuit
+        .compute_selector_polynomials()?
+        .iter()
+        .chain(circuit.compute_extended_permutation_polynomials()?.iter())
+    {
+        poly.coeffs.serialize(&mut circuit_digest)?;
+    }
+    let mut inputs_bytes = Vec::new();
+    public_inputs.to_vec().serialize(&mut inputs_bytes)?;
+
+    let mut hasher = Keccak256::new();
+    hasher.update(DETERMINISTIC_RNG_LABEL);
+    hasher.update(Keccak256::digest(&circuit_digest));
+    hasher.update(&inputs_bytes);
+    Ok(ChaChaRng::from_seed(hasher.finalize().into()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::{customized::ecc::SWToTEConParam, Circuit, PlonkCircuit},
+        proof_system::{snark::test::gen_circuit_for_test, PlonkKzgSnark, Snark},
+        transcript::StandardTranscript,
+        PlonkType,
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
+    use ark_ff::One;
+    use ark_std::{rand::RngCore, test_rng};
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_deterministic_rng_for() -> Result<(), PlonkError> {
+        test_deterministic_rng_for_helper::<Bn254>()?;
+        test_deterministic_rng_for_helper::<Bls12_377>()
+    }
+
+    fn test_deterministic_rng_for_helper<E: PairingEngine>() -> Result<(), PlonkError> {
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let public_inputs = cs.public_input()?;
+
+        // same circuit and inputs, same randomness
+        let mut rng = deterministic_rng_for(&cs, &public_inputs)?;
+        let mut rng_2 = deterministic_rng_for(&cs, &public_inputs)?;
+        assert_eq!(rng.next_u64(), rng_2.next_u64());
+
+        // different inputs or circuit, different randomness
+        let mut other_inputs = public_inputs.clone();
+        other_inputs[0] += E::Fr::one();
+        let mut rng = deterministic_rng_for(&cs, &public_inputs)?;
+        assert_ne!(
+            rng.next_u64(),
+            deterministic_rng_for(&cs, &other_inputs)?.next_u64()
+        );
+        let other_cs = gen_circuit_for_test::<E::Fr>(3, 5, PlonkType::TurboPlonk)?;
+        let mut rng = deterministic_rng_for(&cs, &public_inputs)?;
+        assert_ne!(
+            rng.next_u64(),
+            deterministic_rng_for(&other_cs, &public_inputs)?.next_u64()
+        );
+
+        // unfinalized circuit
+        let unfinalized: PlonkCircuit<E::Fr> = PlonkCircuit::new_turbo_plonk();
+        assert!(deterministic_rng_for(&unfinalized, &[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deterministic_proofs() -> Result<(), PlonkError> {
+        test_deterministic_proofs_helper::<Bn254, Fq254, _>()?;
+        test_deterministic_proofs_helper::<Bls12_377, Fq377, _>()
+    }
+
+    fn test_deterministic_proofs_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let cs = gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::TurboPlonk)?;
+        let public_inputs = cs.public_input()?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(cs.srs_size()?, &mut test_rng())?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &cs)?;
+
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(
+            &mut deterministic_rng_for(&cs, &public_inputs)?,
+            &cs,
+            &pk,
+            None,
+        )?;
+        let proof_2 = PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(
+            &mut deterministic_rng_for(&cs, &public_inputs)?,
+            &cs,
+            &pk,
+            None,
+        )?;
+        assert_eq!(proof, proof_2);
+        assert!(PlonkKzgSnark::<E>::verify::<StandardTranscript>(
+            &vk,
+            &public_inputs,
+            &proof,
+            None
+        )
+        .is_ok());
+
+        // fresh randomness gives a different proof of the same statement
+        let proof_3 =
+            PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(&mut test_rng(), &cs, &pk, None)?;
+        assert_ne!(proof, proof_3);
+
+        Ok(())
+    }
+}
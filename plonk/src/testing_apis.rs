@@ -13,6 +13,7 @@ use crate::{
     circuit::customized::ecc::SWToTEConParam,
     errors::PlonkError,
     proof_system::{
+        pcs::KzgPcs,
         structs::{self, BatchProof, PlookupProof, ProofEvaluations, VerifyingKey},
         verifier,
     },
@@ -71,15 +72,15 @@ pub struct ScalarsAndBases<E: PairingEngine> {
     pub base_scalar_map: HashMap<E::G1Affine, E::Fr>,
 }
 
-impl<E: PairingEngine> From<structs::ScalarsAndBases<E>> for ScalarsAndBases<E> {
-    fn from(other: structs::ScalarsAndBases<E>) -> Self {
+impl<E: PairingEngine> From<structs::ScalarsAndBases<KzgPcs<E>>> for ScalarsAndBases<E> {
+    fn from(other: structs::ScalarsAndBases<KzgPcs<E>>) -> Self {
         Self {
             base_scalar_map: other.base_scalar_map,
         }
     }
 }
 
-impl<E: PairingEngine> From<ScalarsAndBases<E>> for structs::ScalarsAndBases<E> {
+impl<E: PairingEngine> From<ScalarsAndBases<E>> for structs::ScalarsAndBases<KzgPcs<E>> {
     fn from(other: ScalarsAndBases<E>) -> Self {
         Self {
             base_scalar_map: other.base_scalar_map,
@@ -90,7 +91,7 @@ impl<E: PairingEngine> From<ScalarsAndBases<E>> for structs::ScalarsAndBases<E>
 impl<E: PairingEngine> ScalarsAndBases<E> {
     /// Compute the multi-scalar multiplication.
     pub fn multi_scalar_mul(&self) -> E::G1Projective {
-        let tmp: structs::ScalarsAndBases<E> = self.clone().into();
+        let tmp: structs::ScalarsAndBases<KzgPcs<E>> = self.clone().into();
         tmp.multi_scalar_mul()
     }
 }
@@ -148,16 +149,17 @@ pub struct Verifier<E: PairingEngine> {
     pub(crate) domain: Radix2EvaluationDomain<E::Fr>,
 }
 
-impl<E: PairingEngine> From<Verifier<E>> for verifier::Verifier<E> {
+impl<E: PairingEngine> From<Verifier<E>> for verifier::Verifier<KzgPcs<E>> {
     fn from(other: Verifier<E>) -> Self {
         Self {
             domain: other.domain,
+            pi_bases: None,
         }
     }
 }
 
-impl<E: PairingEngine> From<verifier::Verifier<E>> for Verifier<E> {
-    fn from(other: verifier::Verifier<E>) -> Self {
+impl<E: PairingEngine> From<verifier::Verifier<KzgPcs<E>>> for Verifier<E> {
+    fn from(other: verifier::Verifier<KzgPcs<E>>) -> Self {
         Self {
             domain: other.domain,
         }
@@ -172,7 +174,7 @@ where
 {
     /// Construct a Plonk verifier that uses a domain with size `domain_size`.
     pub fn new(domain_size: usize) -> Result<Self, PlonkError> {
-        Ok(verifier::Verifier::new(domain_size)?.into())
+        Ok(verifier::Verifier::<KzgPcs<E>>::new(domain_size)?.into())
     }
     /// Prepare the (aggregated) polynomial commitment evaluation information.
     pub fn prepare_pcs_info<T>(
@@ -185,12 +187,12 @@ where
     where
         T: PlonkTranscript<F>,
     {
-        let tmp: verifier::Verifier<E> = (*self).clone().into();
+        let tmp: verifier::Verifier<KzgPcs<E>> = (*self).clone().into();
         Ok(tmp
             .prepare_pcs_info::<T>(
                 verify_keys,
                 public_inputs,
-                batch_proof,
+                &batch_proof.into(),
                 extra_transcript_init_msg,
             )?
             .into())
@@ -208,10 +210,10 @@ where
     where
         T: PlonkTranscript<F>,
     {
-        Ok(verifier::Verifier::compute_challenges::<T>(
+        Ok(verifier::Verifier::<KzgPcs<E>>::compute_challenges::<T>(
             verify_keys,
             public_inputs,
-            batch_proof,
+            &batch_proof.into(),
             extra_transcript_init_msg,
         )?
         .into())
@@ -246,14 +248,14 @@ where
         alpha_powers: &[E::Fr],
         alpha_bases: &[E::Fr],
     ) -> Result<E::Fr, PlonkError> {
-        let tmp: verifier::Verifier<E> = (*self).clone().into();
+        let tmp: verifier::Verifier<KzgPcs<E>> = (*self).clone().into();
         let challenges: structs::Challenges<E::Fr> = (*challenges).into();
         Ok(tmp
             .compute_lin_poly_constant_term(
                 &challenges,
                 verify_keys,
                 public_inputs,
-                batch_proof,
+                &batch_proof.into(),
                 vanish_eval,
                 lagrange_1_eval,
                 lagrange_n_eval,
@@ -279,7 +281,7 @@ where
         alpha_powers: &[E::Fr],
         alpha_bases: &[E::Fr],
     ) -> Result<(ScalarsAndBases<E>, Vec<E::Fr>), PlonkError> {
-        let tmp: verifier::Verifier<E> = (*self).clone().into();
+        let tmp: verifier::Verifier<KzgPcs<E>> = (*self).clone().into();
         let challenges: structs::Challenges<E::Fr> = (*challenges).into();
         let res = tmp.aggregate_poly_commitments(
             vks,
@@ -287,7 +289,7 @@ where
             vanish_eval,
             lagrange_1_eval,
             lagrange_n_eval,
-            batch_proof,
+            &batch_proof.into(),
             alpha_powers,
             alpha_bases,
         )?;
@@ -309,7 +311,7 @@ where
         alpha_powers: &[E::Fr],
         alpha_bases: &[E::Fr],
     ) -> Result<ScalarsAndBases<E>, PlonkError> {
-        let tmp: verifier::Verifier<E> = (*self).clone().into();
+        let tmp: verifier::Verifier<KzgPcs<E>> = (*self).clone().into();
         let challenges: structs::Challenges<E::Fr> = (*challenges).into();
         Ok(tmp
             .linearization_scalars_and_bases(
@@ -318,7 +320,7 @@ where
                 vanish_eval,
                 lagrange_1_eval,
                 lagrange_n_eval,
-                batch_proof,
+                &batch_proof.into(),
                 alpha_powers,
                 alpha_bases,
             )?
@@ -334,7 +336,7 @@ where
         plookup_proofs_vec: &[Option<PlookupProof<E>>],
         buffer_v_and_uv_basis: &[E::Fr],
     ) -> Result<E::Fr, PlonkError> {
-        verifier::Verifier::<E>::aggregate_evaluations(
+        verifier::Verifier::<KzgPcs<E>>::aggregate_evaluations(
             lin_poly_constant,
             poly_evals_vec,
             plookup_proofs_vec,
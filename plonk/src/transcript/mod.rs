@@ -13,11 +13,14 @@ pub(crate) mod standard;
 
 pub use rescue::RescueTranscript;
 pub use solidity::SolidityTranscript;
-pub use standard::StandardTranscript;
+pub use standard::{MerlinTranscript, StandardTranscript};
 
 use crate::{
     errors::PlonkError,
-    proof_system::structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+    proof_system::{
+        pcs::PolynomialCommitmentScheme,
+        structs::{PcsVerifyingKey, PlookupEvaluations, ProofEvaluations},
+    },
 };
 use ark_ec::{
     short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters as SWParam,
@@ -41,19 +44,19 @@ pub trait PlonkTranscript<F> {
     fn new(label: &'static [u8]) -> Self;
 
     /// Append the verification key and the public input to the transcript.
-    fn append_vk_and_pub_input<E, P>(
+    fn append_vk_and_pub_input<PCS, P>(
         &mut self,
-        vk: &VerifyingKey<E>,
-        pub_input: &[E::Fr],
+        vk: &PcsVerifyingKey<PCS>,
+        pub_input: &[PCS::Fr],
     ) -> Result<(), PlonkError>
     where
-        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
         P: SWParam<BaseField = F> + Clone,
     {
         <Self as PlonkTranscript<F>>::append_message(
             self,
             b"field size in bits",
-            E::Fr::size_in_bits().to_le_bytes().as_ref(),
+            PCS::Fr::size_in_bits().to_le_bytes().as_ref(),
         )?;
         <Self as PlonkTranscript<F>>::append_message(
             self,
@@ -88,6 +91,13 @@ pub trait PlonkTranscript<F> {
                 &to_bytes!(sigma_comms)?,
             )?;
         }
+        for custom_gate in vk.custom_gates.iter() {
+            <Self as PlonkTranscript<F>>::append_message(
+                self,
+                b"custom gates",
+                &to_bytes!(custom_gate)?,
+            )?;
+        }
 
         for input in pub_input.iter() {
             <Self as PlonkTranscript<F>>::append_message(
@@ -129,7 +139,71 @@ pub trait PlonkTranscript<F> {
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         P: SWParam<BaseField = F> + Clone,
     {
-        <Self as PlonkTranscript<F>>::append_message(self, label, &to_bytes!(comm)?)
+        self.append_curve_point(label, &comm.0)
+    }
+
+    /// Append a point of a short Weierstrass curve over `F` to the transcript,
+    /// e.g. a commitment or a point of an opening proof.
+    fn append_curve_point<P>(
+        &mut self,
+        label: &'static [u8],
+        point: &GroupAffine<P>,
+    ) -> Result<(), PlonkError>
+    where
+        P: SWParam<BaseField = F> + Clone,
+    {
+        <Self as PlonkTranscript<F>>::append_message(self, label, &to_bytes!(point)?)
+    }
+
+    /// Same as [`Self::append_commitments`], for the commitments of any
+    /// polynomial commitment scheme over a curve with base field `F`.
+    fn append_pcs_commitments<PCS, P>(
+        &mut self,
+        label: &'static [u8],
+        comms: &[PCS::Commitment],
+    ) -> Result<(), PlonkError>
+    where
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        for comm in comms.iter() {
+            self.append_pcs_commitment::<PCS, P>(label, comm)?;
+        }
+        Ok(())
+    }
+
+    /// Same as [`Self::append_commitment`], for a commitment of any
+    /// polynomial commitment scheme over a curve with base field `F`.
+    fn append_pcs_commitment<PCS, P>(
+        &mut self,
+        label: &'static [u8],
+        comm: &PCS::Commitment,
+    ) -> Result<(), PlonkError>
+    where
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        self.append_curve_point(label, &PCS::commitment_to_point(comm))
+    }
+
+    /// Append an opening proof of a polynomial commitment scheme to the
+    /// transcript: its points, then its scalars.
+    fn append_pcs_proof<PCS, P>(
+        &mut self,
+        label: &'static [u8],
+        proof: &PCS::Proof,
+    ) -> Result<(), PlonkError>
+    where
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        for point in PCS::proof_points(proof).iter() {
+            self.append_curve_point(label, point)?;
+        }
+        for scalar in PCS::proof_scalars(proof).iter() {
+            self.append_field_challenge(label, scalar)?;
+        }
+        Ok(())
     }
 
     /// Append a challenge to the transcript.
@@ -141,13 +215,23 @@ pub trait PlonkTranscript<F> {
     where
         E: PairingEngine<Fq = F>,
     {
+        self.append_field_challenge::<E::Fr>(label, challenge)
+    }
+
+    /// Same as [`Self::append_challenge`], for a challenge in any prime field
+    /// `S`, e.g. the scalar field of a curve without pairing.
+    fn append_field_challenge<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+        challenge: &S,
+    ) -> Result<(), PlonkError> {
         <Self as PlonkTranscript<F>>::append_message(self, label, &to_bytes!(challenge)?)
     }
 
     /// Append a proof evaluation to the transcript.
-    fn append_proof_evaluations<E: PairingEngine>(
+    fn append_proof_evaluations<S: PrimeField>(
         &mut self,
-        evals: &ProofEvaluations<E::Fr>,
+        evals: &ProofEvaluations<S>,
     ) -> Result<(), PlonkError> {
         for w_eval in &evals.wires_evals {
             <Self as PlonkTranscript<F>>::append_message(self, b"wire_evals", &to_bytes!(w_eval)?)?;
@@ -167,9 +251,9 @@ pub trait PlonkTranscript<F> {
     }
 
     /// Append the plookup evaluation to the transcript.
-    fn append_plookup_evaluations<E: PairingEngine>(
+    fn append_plookup_evaluations<S: PrimeField>(
         &mut self,
-        evals: &PlookupEvaluations<E::Fr>,
+        evals: &PlookupEvaluations<S>,
     ) -> Result<(), PlonkError> {
         <Self as PlonkTranscript<F>>::append_message(
             self,
@@ -207,5 +291,15 @@ pub trait PlonkTranscript<F> {
     /// and then append it to the transcript.
     fn get_and_append_challenge<E>(&mut self, label: &'static [u8]) -> Result<E::Fr, PlonkError>
     where
-        E: PairingEngine;
+        E: PairingEngine,
+    {
+        self.get_and_append_field_challenge::<E::Fr>(label)
+    }
+
+    /// Same as [`Self::get_and_append_challenge`], for a challenge in any
+    /// prime field `S`.
+    fn get_and_append_field_challenge<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+    ) -> Result<S, PlonkError>;
 }
@@ -9,15 +9,16 @@ use super::PlonkTranscript;
 use crate::{
     circuit::customized::ecc::{Point, SWToTEConParam},
     errors::PlonkError,
-    proof_system::structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+    proof_system::{
+        pcs::PolynomialCommitmentScheme,
+        structs::{PcsVerifyingKey, PlookupEvaluations, ProofEvaluations},
+    },
 };
-use ark_ec::{
-    short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters as SWParam,
-};
-use ark_poly_commit::kzg10::Commitment;
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters as SWParam};
+use ark_ff::PrimeField;
 use ark_std::vec::Vec;
 use jf_rescue::{Permutation as RescueHash, RescueParameter, STATE_SIZE};
-use jf_utils::{bytes_to_field_elements, field_switching, fq_to_fr_with_mask};
+use jf_utils::{bytes_to_field_elements, field_switching, fq_to_fr_with_mask, to_bytes};
 
 /// Transcript with rescue hash function.
 ///
@@ -52,13 +53,13 @@ where
         }
     }
 
-    fn append_vk_and_pub_input<E, P>(
+    fn append_vk_and_pub_input<PCS, P>(
         &mut self,
-        vk: &VerifyingKey<E>,
-        pub_input: &[E::Fr],
+        vk: &PcsVerifyingKey<PCS>,
+        pub_input: &[PCS::Fr],
     ) -> Result<(), PlonkError>
     where
-        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
         P: SWParam<BaseField = F> + Clone,
     {
         // to enable a more efficient verifier circuit, we remove
@@ -71,17 +72,22 @@ where
         // selector commitments
         for com in vk.selector_comms.iter() {
             // convert the SW form commitments into TE form
-            let te_point: Point<F> = (&com.0).into();
+            let te_point: Point<F> = (&PCS::commitment_to_point(com)).into();
             self.transcript.push(te_point.get_x());
             self.transcript.push(te_point.get_y());
         }
         // sigma commitments
         for com in vk.sigma_comms.iter() {
             // convert the SW form commitments into TE form
-            let te_point: Point<F> = (&com.0).into();
+            let te_point: Point<F> = (&PCS::commitment_to_point(com)).into();
             self.transcript.push(te_point.get_x());
             self.transcript.push(te_point.get_y());
         }
+        // custom gates
+        for custom_gate in vk.custom_gates.iter() {
+            self.transcript
+                .append(&mut bytes_to_field_elements(&to_bytes!(custom_gate)?));
+        }
         // public input
         for e in pub_input {
             self.transcript.push(field_switching(e))
@@ -100,20 +106,19 @@ where
         Ok(())
     }
 
-    /// Append a single commitment to the transcript. `_label` is omitted for
+    /// Append a single point to the transcript. `_label` is omitted for
     /// efficiency.
-    fn append_commitment<E, P>(
+    fn append_curve_point<P>(
         &mut self,
         _label: &'static [u8],
-        comm: &Commitment<E>,
+        point: &GroupAffine<P>,
     ) -> Result<(), PlonkError>
     where
-        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         P: SWParam<BaseField = F> + Clone,
     {
-        // convert the SW form commitments into TE form
-        let te_point: Point<F> = (&comm.0).into();
-        // push the x and y coordinate of comm (in twisted
+        // convert the SW form point into TE form
+        let te_point: Point<F> = point.into();
+        // push the x and y coordinate of the point (in twisted
         // edwards form) to the transcript
 
         self.transcript.push(te_point.get_x());
@@ -123,21 +128,18 @@ where
 
     /// Append a challenge to the transcript. `_label` is omitted for
     /// efficiency.
-    fn append_challenge<E>(
+    fn append_field_challenge<S: PrimeField>(
         &mut self,
         _label: &'static [u8],
-        challenge: &E::Fr,
-    ) -> Result<(), PlonkError>
-    where
-        E: PairingEngine<Fq = F>,
-    {
+        challenge: &S,
+    ) -> Result<(), PlonkError> {
         self.transcript.push(field_switching(challenge));
         Ok(())
     }
 
-    fn append_proof_evaluations<E: PairingEngine>(
+    fn append_proof_evaluations<S: PrimeField>(
         &mut self,
-        evals: &ProofEvaluations<E::Fr>,
+        evals: &ProofEvaluations<S>,
     ) -> Result<(), PlonkError> {
         for e in &evals.wires_evals {
             self.transcript.push(field_switching(e))
@@ -149,9 +151,9 @@ where
         Ok(())
     }
 
-    fn append_plookup_evaluations<E: PairingEngine>(
+    fn append_plookup_evaluations<S: PrimeField>(
         &mut self,
-        evals: &PlookupEvaluations<E::Fr>,
+        evals: &PlookupEvaluations<S>,
     ) -> Result<(), PlonkError> {
         for eval in evals.evals_vec().iter() {
             self.transcript.push(field_switching(eval));
@@ -165,10 +167,10 @@ where
     /// Generate the challenge for the current transcript,
     /// and then append it to the transcript. `_label` is omitted for
     /// efficiency.
-    fn get_and_append_challenge<E>(&mut self, _label: &'static [u8]) -> Result<E::Fr, PlonkError>
-    where
-        E: PairingEngine,
-    {
+    fn get_and_append_field_challenge<S: PrimeField>(
+        &mut self,
+        _label: &'static [u8],
+    ) -> Result<S, PlonkError> {
         // 1. state: [F: STATE_SIZE] = hash(state|transcript)
         // 2. challenge = state[0] in Fr
         // 3. transcript = Vec::new()
@@ -177,7 +179,7 @@ where
 
         let input = [self.state.as_ref(), self.transcript.as_ref()].concat();
         let tmp = hasher.sponge_with_padding(&input, STATE_SIZE);
-        let challenge = fq_to_fr_with_mask::<F, E::Fr>(&tmp[0]);
+        let challenge = fq_to_fr_with_mask::<F, S>(&tmp[0]);
         self.state.copy_from_slice(&tmp);
         self.transcript = Vec::new();
         self.transcript.push(field_switching(&challenge));
@@ -6,34 +6,87 @@
 
 //! This module implements solidity transcript.
 use super::PlonkTranscript;
-use crate::{constants::KECCAK256_STATE_SIZE, errors::PlonkError};
-use ark_ec::PairingEngine;
-use ark_ff::PrimeField;
-use ark_std::vec::Vec;
+use crate::{
+    constants::KECCAK256_STATE_SIZE,
+    errors::PlonkError,
+    proof_system::{
+        pcs::PolynomialCommitmentScheme,
+        structs::{PcsVerifyingKey, PlookupEvaluations, ProofEvaluations},
+    },
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters as SWParam};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_std::{vec, vec::Vec};
+use jf_utils::to_bytes;
 use sha3::{Digest, Keccak256};
 
+/// The size in bytes of an EVM word.
+const EVM_WORD_SIZE: usize = 32;
+
 /// Transcript with `keccak256` hash function.
 ///
 /// It is almost identical to `RescueTranscript` except using Solidity's
 /// `keccak256` for Solidity-friendly protocols.
 ///
+/// The transcript elements are absorbed as an EVM verifier would encode them:
+/// integers and field elements as big-endian 32-byte words (or the least
+/// number of words for fields larger than 256 bits), and commitments as the
+/// words of their x then y coordinates, uncompressed, with the point at
+/// infinity as (0, 0).
+///
 /// It is currently implemented simply as
-/// - an append only vector of field elements
+/// - an append only vector of bytes
 /// - a state that is initialized with 0
 ///
 /// We keep appending new elements to the transcript vector,
 /// and when a challenge is to be generated,
 /// we reset the state with the fresh challenge.
 ///
-/// 1. state: \[F: STATE_SIZE\] = hash(state|transcript)
-/// 2. challenge = state\[0\]
-/// 3. transcript = vec!\[challenge\]
+/// 1. state: \[u8: 64\] = keccak256(state|transcript|0) ||
+///    keccak256(state|transcript|1)
+/// 2. challenge = the first 32 bytes of the state, as a big-endian integer
+///    reduced modulo the scalar field
 pub struct SolidityTranscript {
     transcript: Vec<u8>,
     state: [u8; KECCAK256_STATE_SIZE], // 64 bytes state size
 }
 
-impl<F> PlonkTranscript<F> for SolidityTranscript {
+impl SolidityTranscript {
+    // Append an integer as a word.
+    fn append_integer(&mut self, val: usize) {
+        let mut word = vec![0u8; EVM_WORD_SIZE - 8];
+        word.extend_from_slice(&(val as u64).to_be_bytes());
+        self.transcript.extend_from_slice(&word);
+    }
+
+    // Append a field element as big-endian words.
+    fn append_field_elem<F: PrimeField>(&mut self, elem: &F) {
+        let bytes = elem.into_repr().to_bytes_be();
+        let padded_len = (bytes.len() + EVM_WORD_SIZE - 1) / EVM_WORD_SIZE * EVM_WORD_SIZE;
+        self.transcript
+            .extend_from_slice(&vec![0u8; padded_len - bytes.len()]);
+        self.transcript.extend_from_slice(&bytes);
+    }
+
+    // Append a point as its uncompressed coordinates.
+    fn append_point<P: SWParam>(&mut self, point: &GroupAffine<P>)
+    where
+        P::BaseField: PrimeField,
+    {
+        match point.infinity {
+            true => {
+                self.append_field_elem(&P::BaseField::zero());
+                self.append_field_elem(&P::BaseField::zero());
+            },
+            false => {
+                self.append_field_elem(&point.x);
+                self.append_field_elem(&point.y);
+            },
+        }
+    }
+}
+
+impl<F: PrimeField> PlonkTranscript<F> for SolidityTranscript {
     /// Create a new plonk transcript. `label` is omitted for efficiency.
     fn new(_label: &'static [u8]) -> Self {
         SolidityTranscript {
@@ -42,6 +95,33 @@ impl<F> PlonkTranscript<F> for SolidityTranscript {
         }
     }
 
+    fn append_vk_and_pub_input<PCS, P>(
+        &mut self,
+        vk: &PcsVerifyingKey<PCS>,
+        pub_input: &[PCS::Fr],
+    ) -> Result<(), PlonkError>
+    where
+        PCS: PolynomialCommitmentScheme<Curve = GroupAffine<P>>,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        self.append_integer(PCS::Fr::size_in_bits());
+        self.append_integer(vk.domain_size);
+        self.append_integer(vk.num_inputs);
+        for ki in vk.k.iter() {
+            self.append_field_elem(ki);
+        }
+        for com in vk.selector_comms.iter().chain(vk.sigma_comms.iter()) {
+            self.append_point(&PCS::commitment_to_point(com));
+        }
+        for custom_gate in vk.custom_gates.iter() {
+            self.transcript.extend_from_slice(&to_bytes!(custom_gate)?);
+        }
+        for input in pub_input.iter() {
+            self.append_field_elem(input);
+        }
+        Ok(())
+    }
+
     /// Append the message to the transcript. `_label` is omitted for
     /// efficiency.
     fn append_message(&mut self, _label: &'static [u8], msg: &[u8]) -> Result<(), PlonkError> {
@@ -50,13 +130,66 @@ impl<F> PlonkTranscript<F> for SolidityTranscript {
         Ok(())
     }
 
-    /// Generate the challenge for the current transcript,
-    /// and then append it to the transcript. `_label` is omitted for
+    /// Append a single point to the transcript. `_label` is omitted for
     /// efficiency.
-    fn get_and_append_challenge<E>(&mut self, _label: &'static [u8]) -> Result<E::Fr, PlonkError>
+    fn append_curve_point<P>(
+        &mut self,
+        _label: &'static [u8],
+        point: &GroupAffine<P>,
+    ) -> Result<(), PlonkError>
     where
-        E: PairingEngine,
+        P: SWParam<BaseField = F> + Clone,
     {
+        self.append_point(point);
+        Ok(())
+    }
+
+    /// Append a challenge to the transcript. `_label` is omitted for
+    /// efficiency.
+    fn append_field_challenge<S: PrimeField>(
+        &mut self,
+        _label: &'static [u8],
+        challenge: &S,
+    ) -> Result<(), PlonkError> {
+        self.append_field_elem(challenge);
+        Ok(())
+    }
+
+    fn append_proof_evaluations<S: PrimeField>(
+        &mut self,
+        evals: &ProofEvaluations<S>,
+    ) -> Result<(), PlonkError> {
+        for eval in evals
+            .wires_evals
+            .iter()
+            .chain(evals.wire_sigma_evals.iter())
+        {
+            self.append_field_elem(eval);
+        }
+        self.append_field_elem(&evals.perm_next_eval);
+        Ok(())
+    }
+
+    fn append_plookup_evaluations<S: PrimeField>(
+        &mut self,
+        evals: &PlookupEvaluations<S>,
+    ) -> Result<(), PlonkError> {
+        for eval in evals.evals_vec().iter() {
+            self.append_field_elem(eval);
+        }
+        for next_eval in evals.next_evals_vec().iter() {
+            self.append_field_elem(next_eval);
+        }
+        Ok(())
+    }
+
+    /// Generate the challenge for the current transcript,
+    /// and then append it to the transcript. `_label` is omitted for
+    /// efficiency.
+    fn get_and_append_field_challenge<S: PrimeField>(
+        &mut self,
+        _label: &'static [u8],
+    ) -> Result<S, PlonkError> {
         // 1. state = keccak256(state|transcript|0) || keccak256(state|transcript|1)
         let input0 = [self.state.as_ref(), self.transcript.as_ref(), &[0u8]].concat();
         let input1 = [self.state.as_ref(), self.transcript.as_ref(), &[1u8]].concat();
@@ -71,8 +204,9 @@ impl<F> PlonkTranscript<F> for SolidityTranscript {
 
         self.state.copy_from_slice(&[buf0, buf1].concat());
 
-        // 2. challenge: sample field from random bytes.
-        let challenge = E::Fr::from_le_bytes_mod_order(&self.state[..48]);
+        // 2. challenge: `uint256(keccak256(state|transcript|0)) % r`, as in
+        // Solidity.
+        let challenge = S::from_be_bytes_mod_order(&self.state[..EVM_WORD_SIZE]);
         Ok(challenge)
     }
 }
@@ -94,3 +228,53 @@ fn test_solidity_keccak() {
             .unwrap()
     );
 }
+
+#[test]
+fn test_solidity_transcript_fixture() {
+    use ark_bn254::{Bn254, Fr};
+    use ark_ec::{AffineCurve, PairingEngine};
+    use ark_poly_commit::kzg10::Commitment;
+    use hex::FromHex;
+
+    // the generator (1, 2) of bn254, the challenge 3 and a raw message, which are
+    // absorbed as the 32-byte words 1, 2 and 3 followed by the raw bytes
+    let mut transcript = <SolidityTranscript as PlonkTranscript<ark_bn254::Fq>>::new(b"test");
+    let generator =
+        Commitment::<Bn254>(<Bn254 as PairingEngine>::G1Affine::prime_subgroup_generator());
+    <SolidityTranscript as PlonkTranscript<ark_bn254::Fq>>::append_commitment(
+        &mut transcript,
+        b"comm",
+        &generator,
+    )
+    .unwrap();
+    <SolidityTranscript as PlonkTranscript<ark_bn254::Fq>>::append_challenge::<Bn254>(
+        &mut transcript,
+        b"challenge",
+        &Fr::from(3u64),
+    )
+    .unwrap();
+    <SolidityTranscript as PlonkTranscript<ark_bn254::Fq>>::append_message(
+        &mut transcript,
+        b"msg",
+        &[0xab, 0xcd],
+    )
+    .unwrap();
+
+    // expected values computed as `uint256(keccak256(state|transcript|0)) % r`
+    for expected in [
+        "11bd386b50e3734c0282d152204b6771751308b2da9e397e40b2644a8622dbad",
+        "1ff6133e1a0702a78021ea3911f14817be7f61e8b0dc6d9149544207ef55af1b",
+    ]
+    .iter()
+    {
+        let challenge =
+            <SolidityTranscript as PlonkTranscript<ark_bn254::Fq>>::get_and_append_challenge::<
+                Bn254,
+            >(&mut transcript, b"challenge")
+            .unwrap();
+        assert_eq!(
+            challenge.into_repr().to_bytes_be(),
+            <[u8; 32]>::from_hex(expected).unwrap().to_vec()
+        );
+    }
+}
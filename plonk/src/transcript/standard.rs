@@ -7,7 +7,6 @@
 //! This module is a wrapper of the Merlin transcript.
 use super::PlonkTranscript;
 use crate::errors::PlonkError;
-use ark_ec::PairingEngine;
 use ark_ff::PrimeField;
 use jf_utils::to_bytes;
 use merlin::Transcript;
@@ -15,6 +14,12 @@ use merlin::Transcript;
 /// A wrapper of `merlin::Transcript`.
 pub struct StandardTranscript(Transcript);
 
+/// A transcript backed by the Merlin STROBE framework, for interop with other
+/// Merlin-based provers. Every element is absorbed with its own domain
+/// separation label, and challenges are obtained by the wide reduction of 64
+/// squeezed bytes into the scalar field.
+pub type MerlinTranscript = StandardTranscript;
+
 impl<F> PlonkTranscript<F> for StandardTranscript {
     /// create a new plonk transcript
     fn new(label: &'static [u8]) -> Self {
@@ -30,14 +35,87 @@ impl<F> PlonkTranscript<F> for StandardTranscript {
 
     // generate the challenge for the current transcript
     // and append it to the transcript
-    fn get_and_append_challenge<E>(&mut self, label: &'static [u8]) -> Result<E::Fr, PlonkError>
-    where
-        E: PairingEngine,
-    {
+    fn get_and_append_field_challenge<S: PrimeField>(
+        &mut self,
+        label: &'static [u8],
+    ) -> Result<S, PlonkError> {
         let mut buf = [0u8; 64];
         self.0.challenge_bytes(label, &mut buf);
-        let challenge = E::Fr::from_le_bytes_mod_order(&buf);
+        let challenge = S::from_le_bytes_mod_order(&buf);
         self.0.append_message(label, &to_bytes!(&challenge)?);
         Ok(challenge)
     }
 }
+
+#[test]
+fn test_merlin_transcript_labels() {
+    use ark_bls12_377::{Bls12_377, Fq, Fr};
+
+    // the challenge depends on the protocol label, on the labels of the
+    // absorbed messages and on the challenge label
+    let challenge = |protocol: &'static [u8], msg_label: &'static [u8], label: &'static [u8]| {
+        let mut transcript = <MerlinTranscript as PlonkTranscript<Fq>>::new(protocol);
+        <MerlinTranscript as PlonkTranscript<Fq>>::append_message(
+            &mut transcript,
+            msg_label,
+            b"message",
+        )
+        .unwrap();
+        <MerlinTranscript as PlonkTranscript<Fq>>::get_and_append_challenge::<Bls12_377>(
+            &mut transcript,
+            label,
+        )
+        .unwrap()
+    };
+    let expected: Fr = challenge(b"protocol", b"msg", b"challenge");
+    assert_eq!(challenge(b"protocol", b"msg", b"challenge"), expected);
+    assert_ne!(challenge(b"other protocol", b"msg", b"challenge"), expected);
+    assert_ne!(challenge(b"protocol", b"other msg", b"challenge"), expected);
+    assert_ne!(challenge(b"protocol", b"msg", b"other challenge"), expected);
+}
+
+#[test]
+fn test_merlin_transcript_prove_and_verify() -> Result<(), PlonkError> {
+    use crate::{
+        circuit::Arithmetization,
+        proof_system::{snark::test::gen_circuit_for_test, PlonkKzgSnark, Snark},
+        PlonkType,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_ec::PairingEngine;
+    use ark_ff::One;
+    use ark_std::test_rng;
+
+    let rng = &mut test_rng();
+    for &plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk].iter() {
+        let cs = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<Bls12_377>::universal_setup(cs.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<Bls12_377>::preprocess(&srs, &cs)?;
+        let proof =
+            PlonkKzgSnark::<Bls12_377>::prove::<_, _, MerlinTranscript>(rng, &cs, &pk, None)?;
+        let mut public_input = cs.public_input()?;
+        assert!(PlonkKzgSnark::<Bls12_377>::verify::<MerlinTranscript>(
+            &vk,
+            &public_input,
+            &proof,
+            None
+        )
+        .is_ok());
+        assert!(PlonkKzgSnark::<Bls12_377>::verify::<MerlinTranscript>(
+            &vk,
+            &public_input,
+            &proof,
+            Some(b"another protocol".to_vec())
+        )
+        .is_err());
+        public_input[0] += <Bls12_377 as PairingEngine>::Fr::one();
+        assert!(PlonkKzgSnark::<Bls12_377>::verify::<MerlinTranscript>(
+            &vk,
+            &public_input,
+            &proof,
+            None
+        )
+        .is_err());
+    }
+    Ok(())
+}
@@ -7,7 +7,7 @@
 //! Circuit implementation of the commitment scheme.
 
 use crate::utils::pad_with;
-use ark_std::vec;
+use ark_std::{format, vec};
 use jf_plonk::{
     circuit::{customized::rescue::RescueGadget, Circuit, PlonkCircuit, Variable},
     errors::PlonkError,
@@ -24,6 +24,19 @@ pub trait CommitmentGadget {
     /// The underlying the commitment instance is bound to a specific length.
     /// Hence input length must match it.
     fn commit(&mut self, input: &[Variable], blinding: Variable) -> Result<Variable, PlonkError>;
+
+    /// Constrain `value` to be the opening of `commitment` with blinding
+    /// `nonce` (for a commitment instance of input length 1), and to lie in
+    /// the range [`min`, `max`].
+    /// Return error if `min > max` or if variables are invalid.
+    fn enforce_commit_reveal(
+        &mut self,
+        commitment: Variable,
+        value: Variable,
+        nonce: Variable,
+        min: u64,
+        max: u64,
+    ) -> Result<(), PlonkError>;
 }
 
 impl<F> CommitmentGadget for PlonkCircuit<F>
@@ -36,6 +49,33 @@ where
         pad_with(&mut msg, RATE, self.zero());
         Ok(self.rescue_sponge_no_padding(&msg, 1)?[0])
     }
+
+    fn enforce_commit_reveal(
+        &mut self,
+        commitment: Variable,
+        value: Variable,
+        nonce: Variable,
+        min: u64,
+        max: u64,
+    ) -> Result<(), PlonkError> {
+        if min > max {
+            return Err(PlonkError::InvalidParameters(format!(
+                "empty range: min {} > max {}",
+                min, max
+            )));
+        }
+        let expected_commitment = self.commit(&[value], nonce)?;
+        self.equal_gate(expected_commitment, commitment)?;
+
+        // both value - min and max - value are in [0, 2^bit_len), where
+        // max - min < 2^bit_len, iff min <= value <= max
+        let bit_len = ark_std::cmp::max(1, 64 - (max - min).leading_zeros() as usize);
+        let above_min = self.add_constant(value, &-F::from(min))?;
+        let max_var = self.create_constant_variable(F::from(max))?;
+        let below_max = self.sub(max_var, value)?;
+        self.range_gate(above_min, bit_len)?;
+        self.range_gate(below_max, bit_len)
+    }
 }
 
 #[cfg(test)]
@@ -83,6 +123,74 @@ mod tests {
             assert!(circuit.check_circuit_satisfiability(&[]).is_err());
         };
     }
+    macro_rules! test_commit_reveal_circuit {
+        ($base_field:tt) => {
+            let mut prng = ark_std::test_rng();
+            let commitment_instance = Commitment::new(1);
+            let build_circuit = |committed: u64, revealed: u64, nonce: $base_field| {
+                let mut circuit: PlonkCircuit<$base_field> = PlonkCircuit::new_turbo_plonk();
+                let commitment = commitment_instance
+                    .commit(&[$base_field::from(committed)], &nonce)
+                    .unwrap();
+                let commitment_var = circuit.create_variable(commitment).unwrap();
+                let value_var = circuit
+                    .create_variable($base_field::from(revealed))
+                    .unwrap();
+                let nonce_var = circuit.create_variable(nonce).unwrap();
+                circuit
+                    .enforce_commit_reveal(commitment_var, value_var, nonce_var, 1, 6)
+                    .unwrap();
+                circuit
+            };
+
+            // valid reveals
+            for value in 1..=6 {
+                let circuit = build_circuit(value, value, $base_field::rand(&mut prng));
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            }
+            // wrong value
+            let circuit = build_circuit(3, 4, $base_field::rand(&mut prng));
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            // out-of-range values with valid openings
+            for &value in [0, 7, 8, u64::MAX].iter() {
+                let circuit = build_circuit(value, value, $base_field::rand(&mut prng));
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+
+            // wrong nonce
+            let mut circuit: PlonkCircuit<$base_field> = PlonkCircuit::new_turbo_plonk();
+            let nonce = $base_field::rand(&mut prng);
+            let commitment = commitment_instance
+                .commit(&[$base_field::from(2u64)], &nonce)
+                .unwrap();
+            let commitment_var = circuit.create_variable(commitment).unwrap();
+            let value_var = circuit.create_variable($base_field::from(2u64)).unwrap();
+            let nonce_var = circuit.create_variable(nonce).unwrap();
+            circuit
+                .enforce_commit_reveal(commitment_var, value_var, nonce_var, 1, 6)
+                .unwrap();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            *circuit.witness_mut(nonce_var) = $base_field::from(1_u32);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // wrong parameters
+            assert!(circuit
+                .enforce_commit_reveal(commitment_var, value_var, nonce_var, 6, 1)
+                .is_err());
+            assert!(circuit
+                .enforce_commit_reveal(commitment_var, circuit.num_vars(), nonce_var, 1, 6)
+                .is_err());
+        };
+    }
+    #[test]
+    fn test_commit_reveal_circuit() {
+        test_commit_reveal_circuit!(FqEd254);
+        test_commit_reveal_circuit!(FqEd377);
+        test_commit_reveal_circuit!(FqEd381);
+        test_commit_reveal_circuit!(FqEd381b);
+        test_commit_reveal_circuit!(Fq377);
+    }
+
     #[test]
     fn test_commit_circuit() {
         test_commit_circuit!(FqEd254);
@@ -8,10 +8,12 @@
 
 //! Circuit implementation of a Merkle tree.
 
-use crate::merkle_tree::{AccMemberWitness, MerklePath, MerkleTree, NodePos, NodeValue};
+use crate::merkle_tree::{
+    AccMemberWitness, MerkleMultiproof, MerklePath, MerkleTree, NodePos, NodeValue,
+};
 use ark_ec::TEModelParameters as Parameters;
 use ark_ff::PrimeField;
-use ark_std::{vec, vec::Vec};
+use ark_std::{format, string::ToString, vec, vec::Vec};
 use jf_plonk::{
     circuit::{customized::rescue::RescueGadget, Circuit, PlonkCircuit, Variable},
     errors::PlonkError,
@@ -91,6 +93,13 @@ pub struct MerklePathVars {
     pub nodes: Vec<MerkleNodeVars>,
 }
 
+#[derive(Debug)]
+/// Circuit variables for a Merkle multiproof
+pub struct MerkleMultiproofVars {
+    pub height: u8,
+    pub nodes: Vec<Variable>,
+}
+
 /// Circuit variables for an accumulated element
 pub struct AccElemVars {
     pub uid: Variable,
@@ -171,6 +180,49 @@ pub trait MerkleTreeGadget<F: PrimeField> {
         elem: AccElemVars,
         path_vars: &MerklePathVars,
     ) -> Result<Variable, PlonkError>;
+    /// Adds the variables of the nodes of a Merkle multiproof.
+    /// * `multiproof` - nodes shared by the authentication paths of several
+    ///   leaves
+    /// * `returns` - variables corresponding to the multiproof
+    fn add_merkle_multiproof_variable(
+        &mut self,
+        multiproof: &MerkleMultiproof<F>,
+    ) -> Result<MerkleMultiproofVars, PlonkError>;
+
+    /// Enforces that the elements `leaves` are at the positions `uids` of the
+    /// Merkle tree of root `root`, each internal node lying on several
+    /// authentication paths being hashed only once.
+    /// * `root` - variable corresponding to the root value of the Merkle tree
+    /// * `leaves` - variables corresponding to the element values (e.g.:
+    ///   record commitments)
+    /// * `uids` - positions of the leaves, in strictly increasing order
+    /// * `shared_nodes` - variables corresponding to the Merkle multiproof
+    /// * `returns` - error if `uids` is empty, not strictly increasing, of a
+    ///   different length than `leaves` or out of the tree, or if the number
+    ///   of nodes of `shared_nodes` does not match the positions.
+    fn enforce_merkle_multiproof(
+        &mut self,
+        root: Variable,
+        leaves: &[Variable],
+        uids: &[u64],
+        shared_nodes: &MerkleMultiproofVars,
+    ) -> Result<(), PlonkError>;
+
+    /// Enforces that the element `leaf` is in the Merkle accumulator of root
+    /// `root`, at the position given by the authentication path, as proven
+    /// by a [`crate::merkle_tree::MembershipProof`].
+    /// * `leaf` - variable corresponding to the element value
+    /// * `path_vars` - variables corresponding to the path of the membership
+    ///   proof, see [`Self::add_merkle_path_variable`]
+    /// * `root` - variable corresponding to the root value of the accumulator
+    /// * `returns` - variable corresponding to the position of the leaf,
+    ///   derived from the path
+    fn enforce_membership(
+        &mut self,
+        leaf: Variable,
+        path_vars: &MerklePathVars,
+        root: Variable,
+    ) -> Result<Variable, PlonkError>;
 }
 
 impl<F> MerkleTreeGadget<F> for PlonkCircuit<F>
@@ -210,6 +262,128 @@ where
         }
         Ok(cur_label)
     }
+
+    fn add_merkle_multiproof_variable(
+        &mut self,
+        multiproof: &MerkleMultiproof<F>,
+    ) -> Result<MerkleMultiproofVars, PlonkError> {
+        let nodes = multiproof
+            .nodes
+            .iter()
+            .map(|node| self.create_variable(node.0))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        Ok(MerkleMultiproofVars {
+            height: multiproof.height,
+            nodes,
+        })
+    }
+
+    fn enforce_merkle_multiproof(
+        &mut self,
+        root: Variable,
+        leaves: &[Variable],
+        uids: &[u64],
+        shared_nodes: &MerkleMultiproofVars,
+    ) -> Result<(), PlonkError> {
+        if uids.is_empty() || uids.len() != leaves.len() {
+            return Err(PlonkError::InvalidParameters(format!(
+                "{} leaves for {} positions",
+                leaves.len(),
+                uids.len()
+            )));
+        }
+        if uids.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(PlonkError::InvalidParameters(
+                "leaf positions are not strictly increasing".to_string(),
+            ));
+        }
+        if let Some(capacity) = 3u64.checked_pow(shared_nodes.height as u32) {
+            if uids[uids.len() - 1] >= capacity {
+                return Err(PlonkError::InvalidParameters(format!(
+                    "leaf position {} is out of a tree of height {}",
+                    uids[uids.len() - 1],
+                    shared_nodes.height
+                )));
+            }
+        }
+        self.check_var_bound(root)?;
+        self.check_vars_bound(leaves)?;
+        self.check_vars_bound(&shared_nodes.nodes)?;
+
+        // the labels of the nodes on the paths of the current level, with their
+        // positions
+        let zero_var = self.zero();
+        let mut known = Vec::with_capacity(uids.len());
+        for (&uid, &leaf) in uids.iter().zip(leaves.iter()) {
+            // leaf label = H(0, uid, arc)
+            let uid_var = self.create_constant_variable(F::from(uid))?;
+            let label = self.rescue_sponge_no_padding(&[zero_var, uid_var, leaf], 1)?[0];
+            known.push((uid, label));
+        }
+        let mut shared_nodes_iter = shared_nodes.nodes.iter();
+        for _ in 0..shared_nodes.height {
+            let mut parents = vec![];
+            let mut i = 0;
+            while i < known.len() {
+                let parent = known[i].0 / 3;
+                let mut input_labels = [zero_var; 3];
+                for (child, label) in (3 * parent..3 * parent + 3).zip(input_labels.iter_mut()) {
+                    if i < known.len() && known[i].0 == child {
+                        *label = known[i].1;
+                        i += 1;
+                    } else {
+                        *label = *shared_nodes_iter.next().ok_or_else(|| {
+                            PlonkError::InvalidParameters(
+                                "too few nodes in the multiproof".to_string(),
+                            )
+                        })?;
+                    }
+                }
+                // check that the left child's label is non-zero
+                self.non_zero_gate(input_labels[0])?;
+                parents.push((parent, self.rescue_sponge_no_padding(&input_labels, 1)?[0]));
+            }
+            known = parents;
+        }
+        if shared_nodes_iter.next().is_some() {
+            return Err(PlonkError::InvalidParameters(
+                "too many nodes in the multiproof".to_string(),
+            ));
+        }
+        self.equal_gate(known[0].1, root)
+    }
+
+    fn enforce_membership(
+        &mut self,
+        leaf: Variable,
+        path_vars: &MerklePathVars,
+        root: Variable,
+    ) -> Result<Variable, PlonkError> {
+        self.check_var_bound(leaf)?;
+        self.check_var_bound(root)?;
+
+        // uid = \sum_i 3^i * pos_i where pos_i = 1 - is_left_child + is_right_child
+        // is the position of the i-th node of the path from the leaf
+        let one = F::one();
+        let zero_var = self.zero();
+        let mut uid = zero_var;
+        let mut offset = F::zero();
+        let mut power = one;
+        for node in path_vars.nodes.iter() {
+            uid = self.lc(
+                &[uid, node.is_right_child, node.is_left_child, zero_var],
+                &[one, power, power.neg(), F::zero()],
+            )?;
+            offset += power;
+            power *= F::from(3u32);
+        }
+        let uid = self.add_constant(uid, &offset)?;
+
+        let elem = AccElemVars { uid, elem: leaf };
+        let computed_root = self.compute_merkle_root(elem, path_vars)?;
+        self.equal_gate(computed_root, root)?;
+        Ok(uid)
+    }
 }
 
 impl<F> MerkleTreeHelperGadget<F> for PlonkCircuit<F>
@@ -292,10 +466,13 @@ pub fn gen_merkle_path_for_test<F: RescueParameter>(uid: u64, comm: F) -> (AccMe
 mod test {
     use crate::{
         circuit::merkle_tree::{
-            gen_merkle_path_for_test, AccElemVars, MerkleNodeBooleanEncoding,
+            gen_merkle_path_for_test, AccElemVars, MerkleMultiproofVars, MerkleNodeBooleanEncoding,
             MerklePathBooleanEncoding, MerkleTreeGadget, MerkleTreeHelperGadget,
         },
-        merkle_tree::{hash, MerklePath, MerklePathNode, NodePos, NodeValue},
+        merkle_tree::{
+            hash, AccMemberWitness, MerkleAccumulator, MerklePath, MerklePathNode, MerkleTree,
+            NodePos, NodeValue,
+        },
     };
     use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
@@ -521,4 +698,196 @@ mod test {
         // Circuit does not verify because a left node value is 0
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
     }
+
+    #[test]
+    fn test_merkle_multiproof() {
+        test_merkle_multiproof_helper::<FqEd254>();
+        test_merkle_multiproof_helper::<FqEd377>();
+        test_merkle_multiproof_helper::<FqEd381>();
+        test_merkle_multiproof_helper::<FqEd381b>();
+        test_merkle_multiproof_helper::<Fq377>();
+    }
+
+    fn test_merkle_multiproof_helper<F: RescueParameter>() {
+        let mut mt = MerkleTree::new(3).unwrap();
+        for i in 0..20u32 {
+            mt.push(F::from(i + 100));
+        }
+        let root = mt.commitment().root_value.to_scalar();
+        let uids = [0u64, 1, 5, 6, 19];
+
+        //// Happy path
+        let (mut circuit, root_var, leaf_vars) = build_multiproof_circuit(&mt, &uids, root);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a single tampered leaf
+        *circuit.witness_mut(leaf_vars[2]) = F::from(999u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(leaf_vars[2]) = F::from(105u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // a wrong root
+        *circuit.witness_mut(root_var) = F::zero();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // a multiproof of other leaves
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let root_var = circuit.create_variable(root).unwrap();
+        let leaf_vars: Vec<Variable> = [100u32, 101, 105, 106, 119]
+            .iter()
+            .map(|&leaf| circuit.create_variable(F::from(leaf)).unwrap())
+            .collect();
+        let multiproof = mt.get_multiproof(&[0, 1, 5, 7, 19]).unwrap();
+        let shared_nodes = circuit.add_merkle_multiproof_variable(&multiproof).unwrap();
+        circuit
+            .enforce_merkle_multiproof(root_var, &leaf_vars, &uids, &shared_nodes)
+            .unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        //// The multiproof uses fewer hashes than separate paths
+        let mut separate_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let root_var = separate_circuit.create_variable(root).unwrap();
+        for &uid in uids.iter() {
+            let (elem, witness) = AccMemberWitness::lookup_from_tree(&mt, uid)
+                .expect_ok()
+                .unwrap();
+            let elem = AccElemVars {
+                uid: separate_circuit.create_variable(F::from(uid)).unwrap(),
+                elem: separate_circuit.create_variable(elem).unwrap(),
+            };
+            let path_vars = separate_circuit
+                .add_merkle_path_variable(&witness.merkle_path)
+                .unwrap();
+            let computed_root = separate_circuit
+                .compute_merkle_root(elem, &path_vars)
+                .unwrap();
+            separate_circuit
+                .equal_gate(computed_root, root_var)
+                .unwrap();
+        }
+        assert!(separate_circuit.check_circuit_satisfiability(&[]).is_ok());
+        let (circuit, ..) = build_multiproof_circuit(&mt, &uids, root);
+        assert!(circuit.num_gates() < separate_circuit.num_gates());
+
+        //// Bad parameters
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let root_var = circuit.create_variable(root).unwrap();
+        let leaf_vars: Vec<Variable> = [100u32, 101]
+            .iter()
+            .map(|&leaf| circuit.create_variable(F::from(leaf)).unwrap())
+            .collect();
+        let multiproof = mt.get_multiproof(&[0, 1]).unwrap();
+        let shared_nodes = circuit.add_merkle_multiproof_variable(&multiproof).unwrap();
+        assert!(circuit
+            .enforce_merkle_multiproof(root_var, &leaf_vars, &[1, 0], &shared_nodes)
+            .is_err());
+        assert!(circuit
+            .enforce_merkle_multiproof(root_var, &leaf_vars, &[0], &shared_nodes)
+            .is_err());
+        assert!(circuit
+            .enforce_merkle_multiproof(root_var, &[], &[], &shared_nodes)
+            .is_err());
+        assert!(circuit
+            .enforce_merkle_multiproof(root_var, &leaf_vars, &[0, 27], &shared_nodes)
+            .is_err());
+        // too few or too many nodes
+        let too_few_nodes = MerkleMultiproofVars {
+            height: 3,
+            nodes: shared_nodes.nodes[1..].to_vec(),
+        };
+        assert!(circuit
+            .enforce_merkle_multiproof(root_var, &leaf_vars, &[0, 1], &too_few_nodes)
+            .is_err());
+        let mut too_many_nodes = MerkleMultiproofVars {
+            height: 3,
+            nodes: shared_nodes.nodes.clone(),
+        };
+        too_many_nodes.nodes.push(root_var);
+        assert!(circuit
+            .enforce_merkle_multiproof(root_var, &leaf_vars, &[0, 1], &too_many_nodes)
+            .is_err());
+        // out of bound variables
+        assert!(circuit
+            .enforce_merkle_multiproof(circuit.num_vars(), &leaf_vars, &[0, 1], &shared_nodes)
+            .is_err());
+    }
+
+    #[test]
+    fn test_enforce_membership() {
+        test_enforce_membership_helper::<FqEd254>();
+        test_enforce_membership_helper::<FqEd377>();
+        test_enforce_membership_helper::<FqEd381>();
+        test_enforce_membership_helper::<FqEd381b>();
+        test_enforce_membership_helper::<Fq377>();
+    }
+
+    fn test_enforce_membership_helper<F: RescueParameter>() {
+        let mut acc = MerkleAccumulator::<F>::new(3).unwrap();
+        for i in 0..10u32 {
+            acc.insert(F::from(i + 100)).unwrap();
+        }
+        let root = acc.root().to_scalar();
+
+        for &uid in [0u64, 4, 9].iter() {
+            let proof = acc.prove_membership(uid).unwrap();
+            assert!(proof.verify(&acc.root()).is_ok());
+
+            //// Happy path
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let leaf_var = circuit.create_variable(proof.leaf.0).unwrap();
+            let root_var = circuit.create_variable(root).unwrap();
+            let path_vars = circuit.add_merkle_path_variable(&proof.path).unwrap();
+            let uid_var = circuit
+                .enforce_membership(leaf_var, &path_vars, root_var)
+                .unwrap();
+            assert_eq!(circuit.witness(uid_var).unwrap(), F::from(uid));
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong leaf or root
+            *circuit.witness_mut(leaf_var) = F::from(999u32);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(leaf_var) = proof.leaf.0;
+            *circuit.witness_mut(root_var) = F::zero();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            //// Bad path: the path of another leaf
+            let other_proof = acc.prove_membership((uid + 1) % 10).unwrap();
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let leaf_var = circuit.create_variable(proof.leaf.0).unwrap();
+            let root_var = circuit.create_variable(root).unwrap();
+            let path_vars = circuit.add_merkle_path_variable(&other_proof.path).unwrap();
+            circuit
+                .enforce_membership(leaf_var, &path_vars, root_var)
+                .unwrap();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            //// Bad parameters: out of bound variables
+            assert!(circuit
+                .enforce_membership(circuit.num_vars(), &path_vars, root_var)
+                .is_err());
+            assert!(circuit
+                .enforce_membership(leaf_var, &path_vars, circuit.num_vars())
+                .is_err());
+        }
+    }
+
+    fn build_multiproof_circuit<F: RescueParameter>(
+        mt: &MerkleTree<F>,
+        uids: &[u64],
+        root: F,
+    ) -> (PlonkCircuit<F>, Variable, Vec<Variable>) {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let root_var = circuit.create_variable(root).unwrap();
+        let leaf_vars: Vec<Variable> = uids
+            .iter()
+            .map(|&uid| {
+                let leaf = mt.get_leaf(uid).expect_ok().unwrap().1.leaf.0;
+                circuit.create_variable(leaf).unwrap()
+            })
+            .collect();
+        let multiproof = mt.get_multiproof(uids).unwrap();
+        let shared_nodes = circuit.add_merkle_multiproof_variable(&multiproof).unwrap();
+        circuit
+            .enforce_merkle_multiproof(root_var, &leaf_vars, uids, &shared_nodes)
+            .unwrap();
+        (circuit, root_var, leaf_vars)
+    }
 }
@@ -8,5 +8,7 @@
 pub mod commitment;
 pub mod elgamal;
 pub mod merkle_tree;
+pub mod pedersen;
 pub mod prf;
 pub mod schnorr_dsa;
+pub mod shuffle;
@@ -0,0 +1,144 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the Pedersen commitment scheme.
+
+use crate::pedersen::PedersenCommitment;
+use ark_ec::{ModelParameters, TEModelParameters as Parameters};
+use ark_ff::PrimeField;
+use ark_std::format;
+use jf_plonk::{
+    circuit::{customized::ecc::PointVariable, Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use jf_rescue::RescueParameter;
+
+/// Circuit implementation of the Pedersen commitment scheme.
+pub trait PedersenCommitmentGadget<F, P>
+where
+    F: RescueParameter,
+    P: Parameters<BaseField = F> + Clone,
+{
+    /// Commitment function, with the generators of the commitment instance
+    /// `pedersen`: the output point equals [`PedersenCommitment::commit`] of
+    /// the values of the variables.
+    /// * `msg` - message variables, constrained to have at most
+    ///   [`PedersenCommitment::msg_bit_len`] bits,
+    /// * `blinding` - blinding variable, constrained to have at most the bit
+    ///   length of the order of the prime subgroup,
+    /// * `returns` the variables of the commitment point.
+    /// Return error if the length of `msg` doesn't match the instance, or if
+    /// some variable is invalid or out of range.
+    fn pedersen_commit(
+        &mut self,
+        pedersen: &PedersenCommitment<P>,
+        msg: &[Variable],
+        blinding: Variable,
+    ) -> Result<PointVariable, PlonkError>;
+}
+
+impl<F, P> PedersenCommitmentGadget<F, P> for PlonkCircuit<F>
+where
+    F: RescueParameter,
+    P: Parameters<BaseField = F> + Clone,
+{
+    fn pedersen_commit(
+        &mut self,
+        pedersen: &PedersenCommitment<P>,
+        msg: &[Variable],
+        blinding: Variable,
+    ) -> Result<PointVariable, PlonkError> {
+        if msg.len() != pedersen.msg_len() {
+            return Err(PlonkError::InvalidParameters(format!(
+                "the message length {} != the instance's message length {}",
+                msg.len(),
+                pedersen.msg_len()
+            )));
+        }
+        let blinding_bits_le = self.unpack(
+            blinding,
+            <P as ModelParameters>::ScalarField::size_in_bits(),
+        )?;
+        let mut comm =
+            self.fixed_base_binary_scalar_mul(&blinding_bits_le, &pedersen.blinding_generator)?;
+        for (&elem, generator) in msg.iter().zip(pedersen.msg_generators.iter()) {
+            let elem_bits_le = self.unpack(elem, PedersenCommitment::<P>::msg_bit_len())?;
+            let term = self.fixed_base_binary_scalar_mul(&elem_bits_le, generator)?;
+            comm = self.ecc_add::<P>(&comm, &term)?;
+        }
+        Ok(comm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ed_on_bls12_377::EdwardsParameters as Param377;
+    use ark_ed_on_bls12_381::EdwardsParameters as Param381;
+    use ark_ed_on_bls12_381_bandersnatch::EdwardsParameters as Param381b;
+    use ark_ed_on_bn254::EdwardsParameters as Param254;
+    use ark_ff::One;
+    use ark_std::{vec::Vec, UniformRand};
+    use jf_plonk::circuit::customized::ecc::Point;
+
+    #[test]
+    fn test_pedersen_commit_circuit() -> Result<(), PlonkError> {
+        test_pedersen_commit_circuit_helper::<_, Param377>()?;
+        test_pedersen_commit_circuit_helper::<_, Param381>()?;
+        test_pedersen_commit_circuit_helper::<_, Param381b>()?;
+        test_pedersen_commit_circuit_helper::<_, Param254>()
+    }
+
+    fn test_pedersen_commit_circuit_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: RescueParameter,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let pedersen = PedersenCommitment::<P>::new(3);
+        let max_elem =
+            F::from(2u64).pow([PedersenCommitment::<P>::msg_bit_len() as u64]) - F::one();
+        let msg = [F::from(u64::rand(&mut rng)), F::from(0u64), max_elem];
+        let blinding = PedersenCommitment::<P>::rand_blinding(&mut rng);
+        let expected = Point::from(pedersen.commit(&msg, &blinding).unwrap());
+
+        // the circuit output equals the native commitment
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let msg_vars = msg
+            .iter()
+            .map(|&elem| circuit.create_variable(elem))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let blinding_var = circuit.create_variable(blinding)?;
+        let comm_var = circuit.pedersen_commit(&pedersen, &msg_vars, blinding_var)?;
+        assert_eq!(circuit.point_witness(&comm_var)?, expected);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // another opening doesn't satisfy the circuit
+        *circuit.witness_mut(msg_vars[0]) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(msg_vars[0]) -= F::one();
+        *circuit.witness_mut(blinding_var) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // bad inputs: wrong message length, out of range message element or
+        // blinding
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let one = circuit.one();
+        assert!(circuit
+            .pedersen_commit(&pedersen, &[one, one], one)
+            .is_err());
+        let large_elem = circuit.create_variable(max_elem + F::one())?;
+        assert!(circuit
+            .pedersen_commit(&pedersen, &[one, one, large_elem], one)
+            .is_err());
+        let large_blinding = circuit.create_variable(-F::one())?;
+        assert!(circuit
+            .pedersen_commit(&pedersen, &[one, one, one], large_blinding)
+            .is_err());
+
+        Ok(())
+    }
+}
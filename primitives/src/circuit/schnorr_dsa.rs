@@ -59,11 +59,15 @@ where
         sig: &SignatureVar,
     ) -> Result<(), PlonkError>;
 
-    /// Obtain the result bit of a signature verification.
+    /// Obtain the result bit of a signature verification. The challenge is
+    /// recomputed with the in-circuit Rescue sponge as the native signer
+    /// does, domain separation included.
     /// * `vk` - signature verification key variable.
     /// * `msg` - message variables that have been signed.
     /// * `sig` - signature variable.
-    /// * `returns` - a bool variable indicating whether the signature is valid.
+    /// * `returns` - a bool variable indicating whether the signature is valid,
+    ///   constrained to be 0 or 1 so that callers can conditionally enforce the
+    ///   validity, e.g. with `PlonkCircuit::logic_or_gate`.
     fn is_valid_signature(
         &mut self,
         vk: &VerKeyVar,
@@ -206,6 +210,7 @@ mod tests {
         circuit::{Circuit, PlonkCircuit, Variable},
         errors::PlonkError,
     };
+    use jf_rescue::Permutation;
 
     #[test]
     fn test_dsa_circuit() -> Result<(), PlonkError> {
@@ -272,6 +277,66 @@ mod tests {
         *bad_circuit.witness_mut(bit) = F::one();
         assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
 
+        // Conditional enforcement: the signature must be valid unless skipped
+        for (sig, skip, expected) in [
+            (&sig, false, true),
+            (&sig, true, true),
+            (&sig_bad, true, true),
+            (&sig_bad, false, false),
+        ]
+        .iter()
+        {
+            let (mut circuit, bit) = build_is_valid_signature_circuit(vk, &msg, sig)?;
+            let skip = circuit.create_bool_variable(*skip)?;
+            circuit.logic_or_gate(bit, skip)?;
+            assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), *expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_challenge_bits() -> Result<(), PlonkError> {
+        test_challenge_bits_helper::<_, Param377>()?;
+        test_challenge_bits_helper::<_, Param381>()?;
+        test_challenge_bits_helper::<_, Param381b>()?;
+        test_challenge_bits_helper::<_, Param254>()
+    }
+
+    // The in-circuit challenge matches the native one, domain separation
+    // included.
+    fn test_challenge_bits_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: RescueParameter,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let keypair = KeyPair::<P>::generate(&mut rng);
+        let vk = keypair.ver_key_ref();
+        let msg: Vec<F> = (0..7).map(|i| F::from(i as u64)).collect();
+        let sig = keypair.sign(&msg);
+        let expected = vk.challenge(&Permutation::default(), &sig.R, &msg);
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let vk_var = circuit.create_signature_vk_variable(vk)?;
+        let sig_var = circuit.create_signature_variable(&sig)?;
+        let msg_var: Vec<Variable> = msg
+            .iter()
+            .map(|m| circuit.create_variable(*m))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let c_bits_le = SignatureHelperGadget::<F, P>::challenge_bits(
+            &mut circuit,
+            &vk_var,
+            &sig_var.R,
+            &msg_var,
+        )?;
+        assert_eq!(c_bits_le.len(), challenge_bit_len::<F>());
+        let mut challenge = F::zero();
+        for &bit in c_bits_le.iter().rev() {
+            challenge = challenge.double() + circuit.witness(bit)?;
+        }
+        assert_eq!(challenge, fr_to_fq::<F, P>(&expected));
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
         Ok(())
     }
 
@@ -0,0 +1,255 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of a committed shuffle.
+
+use crate::circuit::commitment::CommitmentGadget;
+use ark_std::{format, string::ToString, vec::Vec};
+use jf_plonk::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use jf_rescue::RescueParameter;
+
+/// Circuit implementation of a shuffle bound to a commitment.
+pub trait ShuffleGadget {
+    /// Constrain `shuffled` to be a permutation of `original`, namely
+    /// `shuffled[i] = original[permutation[i]]`, and `commitment` to be the
+    /// Rescue commitment, with blinding `blinding`, to the permutation, i.e.
+    /// to the indices `permutation[0], ..., permutation[n - 1]` (for a
+    /// commitment instance of input length `n`).
+    /// * `permutation` - the witness permutation of `0..n`
+    /// * `returns` - error if the decks are empty or of different
+    ///   lengths, if `permutation` has a different length or an index out of
+    ///   range, or if variables are invalid. The circuit is not satisfied if
+    ///   `permutation` is not a permutation.
+    ///
+    /// Cost: O(n^2) constraints.
+    fn enforce_shuffle(
+        &mut self,
+        original: &[Variable],
+        shuffled: &[Variable],
+        commitment: Variable,
+        permutation: &[usize],
+        blinding: Variable,
+    ) -> Result<(), PlonkError>;
+}
+
+impl<F> ShuffleGadget for PlonkCircuit<F>
+where
+    F: RescueParameter,
+{
+    fn enforce_shuffle(
+        &mut self,
+        original: &[Variable],
+        shuffled: &[Variable],
+        commitment: Variable,
+        permutation: &[usize],
+        blinding: Variable,
+    ) -> Result<(), PlonkError> {
+        let n = original.len();
+        if n == 0 || shuffled.len() != n || permutation.len() != n {
+            return Err(PlonkError::InvalidParameters(format!(
+                "decks of {} and {} cards, permutation of length {}",
+                n,
+                shuffled.len(),
+                permutation.len()
+            )));
+        }
+        if permutation.iter().any(|&index| index >= n) {
+            return Err(PlonkError::InvalidParameters(
+                "permutation index out of range".to_string(),
+            ));
+        }
+        self.check_vars_bound(original)?;
+        self.check_vars_bound(shuffled)?;
+        self.check_var_bound(commitment)?;
+        self.check_var_bound(blinding)?;
+
+        // `selectors[i][j]` is one iff `permutation[i] = j`: it is a boolean
+        // matrix with exactly one one per row and per column.
+        let selectors = permutation
+            .iter()
+            .map(|&index| {
+                (0..n)
+                    .map(|j| self.create_bool_variable(index == j))
+                    .collect::<Result<Vec<_>, PlonkError>>()
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        for row in selectors.iter() {
+            let row_sum = self.sum(row)?;
+            self.constant_gate(row_sum, F::one())?;
+        }
+        for j in 0..n {
+            let column: Vec<Variable> = selectors.iter().map(|row| row[j]).collect();
+            let column_sum = self.sum(&column)?;
+            self.constant_gate(column_sum, F::one())?;
+        }
+
+        let zero = self.zero();
+        let mut index_vars = Vec::with_capacity(n);
+        for (row, &card) in selectors.iter().zip(shuffled.iter()) {
+            // the selected card: sum_j selectors[i][j] * original[j]
+            let mut products = Vec::with_capacity((n + 1) / 2);
+            for (sels, cards) in row.chunks(2).zip(original.chunks(2)) {
+                products.push(if sels.len() == 2 {
+                    self.mul_add(
+                        &[sels[0], cards[0], sels[1], cards[1]],
+                        &[F::one(), F::one()],
+                    )?
+                } else {
+                    self.mul(sels[0], cards[0])?
+                });
+            }
+            let selected = self.sum(&products)?;
+            self.equal_gate(selected, card)?;
+
+            // the permutation index: sum_j j * selectors[i][j]
+            let mut index = zero;
+            for (k, sels) in row.chunks(3).enumerate() {
+                let mut wires = [index, zero, zero, zero];
+                let mut coeffs = [F::one(), F::zero(), F::zero(), F::zero()];
+                for (l, &sel) in sels.iter().enumerate() {
+                    wires[l + 1] = sel;
+                    coeffs[l + 1] = F::from((3 * k + l) as u64);
+                }
+                index = self.lc(&wires, &coeffs)?;
+            }
+            index_vars.push(index);
+        }
+
+        let expected_commitment = self.commit(&index_vars, blinding)?;
+        self.equal_gate(expected_commitment, commitment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{circuit::shuffle::ShuffleGadget, commitment::Commitment};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bls12_381_bandersnatch::Fq as FqEd381b;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::UniformRand;
+    use ark_std::vec::Vec;
+    use jf_plonk::circuit::{Circuit, PlonkCircuit, Variable};
+
+    macro_rules! test_shuffle_circuit {
+        ($base_field:tt) => {
+            let mut prng = ark_std::test_rng();
+            let deck: Vec<u64> = (1..=10).collect();
+            let permutation = [3usize, 7, 0, 9, 1, 2, 8, 5, 4, 6];
+            let blinding = $base_field::rand(&mut prng);
+            let commitment_instance = Commitment::new(deck.len());
+            let permutation_fields: Vec<$base_field> = permutation
+                .iter()
+                .map(|&index| $base_field::from(index as u64))
+                .collect();
+            let commitment = commitment_instance
+                .commit(&permutation_fields, &blinding)
+                .unwrap();
+            let build_circuit = |shuffled: &[u64],
+                                 permutation: &[usize],
+                                 commitment: $base_field|
+             -> (PlonkCircuit<$base_field>, Vec<Variable>) {
+                let mut circuit: PlonkCircuit<$base_field> = PlonkCircuit::new_turbo_plonk();
+                let mut create_vars = |vals: &[u64]| {
+                    vals.iter()
+                        .map(|&val| circuit.create_variable($base_field::from(val)).unwrap())
+                        .collect::<Vec<_>>()
+                };
+                let original_vars = create_vars(&deck);
+                let shuffled_vars = create_vars(shuffled);
+                let commitment_var = circuit.create_variable(commitment).unwrap();
+                let blinding_var = circuit.create_variable(blinding).unwrap();
+                circuit
+                    .enforce_shuffle(
+                        &original_vars,
+                        &shuffled_vars,
+                        commitment_var,
+                        permutation,
+                        blinding_var,
+                    )
+                    .unwrap();
+                (circuit, shuffled_vars)
+            };
+            let shuffle = |permutation: &[usize]| -> Vec<u64> {
+                permutation.iter().map(|&index| deck[index]).collect()
+            };
+
+            // a valid shuffle
+            let shuffled = shuffle(&permutation);
+            let (circuit, _) = build_circuit(&shuffled, &permutation, commitment);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            // the identity shuffle
+            let identity: Vec<usize> = (0..deck.len()).collect();
+            let identity_commitment = commitment_instance
+                .commit(
+                    &identity
+                        .iter()
+                        .map(|&index| $base_field::from(index as u64))
+                        .collect::<Vec<_>>(),
+                    &blinding,
+                )
+                .unwrap();
+            let (circuit, _) = build_circuit(&deck, &identity, identity_commitment);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a shuffle dropping a card, duplicating another one
+            let mut bad_permutation = permutation;
+            bad_permutation[4] = bad_permutation[5];
+            let (circuit, _) =
+                build_circuit(&shuffle(&bad_permutation), &bad_permutation, commitment);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            let mut dropped = shuffled.clone();
+            dropped[4] = dropped[5];
+            let (circuit, _) = build_circuit(&dropped, &permutation, commitment);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            // a valid shuffle not matching the commitment
+            let (circuit, _) = build_circuit(&deck, &identity, commitment);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // if mess up the wire value, should fail
+            let (mut circuit, shuffled_vars) = build_circuit(&shuffled, &permutation, commitment);
+            *circuit.witness_mut(shuffled_vars[0]) = $base_field::from(11u64);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // wrong parameters
+            let mut circuit: PlonkCircuit<$base_field> = PlonkCircuit::new_turbo_plonk();
+            let zero = circuit.zero();
+            let one = circuit.one();
+            assert!(circuit.enforce_shuffle(&[], &[], zero, &[], zero).is_err());
+            assert!(circuit
+                .enforce_shuffle(&[zero, one], &[one], zero, &[1, 0], zero)
+                .is_err());
+            assert!(circuit
+                .enforce_shuffle(&[zero, one], &[one, zero], zero, &[1], zero)
+                .is_err());
+            assert!(circuit
+                .enforce_shuffle(&[zero, one], &[one, zero], zero, &[1, 2], zero)
+                .is_err());
+            assert!(circuit
+                .enforce_shuffle(
+                    &[zero, one],
+                    &[one, zero],
+                    circuit.num_vars(),
+                    &[1, 0],
+                    zero
+                )
+                .is_err());
+        };
+    }
+
+    #[test]
+    fn test_shuffle_circuit() {
+        test_shuffle_circuit!(FqEd254);
+        test_shuffle_circuit!(FqEd377);
+        test_shuffle_circuit!(FqEd381);
+        test_shuffle_circuit!(FqEd381b);
+        test_shuffle_circuit!(Fq377);
+    }
+}
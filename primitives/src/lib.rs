@@ -23,7 +23,8 @@ pub mod constants;
 pub mod elgamal;
 pub mod errors;
 pub mod merkle_tree;
+pub mod pedersen;
 pub mod prf;
 pub mod schnorr_dsa;
-
-pub(crate) mod utils;
+pub mod signed_proof;
+pub mod utils;
@@ -19,7 +19,8 @@ use ark_ff::{BigInteger, Field, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
 use ark_std::{
     boxed::Box,
-    mem,
+    collections::BTreeMap,
+    format, mem,
     rand::{
         distributions::{Distribution, Standard},
         Rng,
@@ -857,6 +858,32 @@ where
     }
 }
 
+/// A membership proof of several leaves of a merkle tree, sharing the nodes
+/// common to their paths.
+///
+/// It lists the children of the internal nodes on some path that are not
+/// themselves on a path, i.e. that cannot be computed from the leaves, level
+/// by level from the leaves up, and from left to right within a level.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    CanonicalSerialize,
+    CanonicalDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(bound = "")]
+pub struct MerkleMultiproof<F: PrimeField> {
+    /// Height of the tree.
+    pub height: u8,
+    /// The nodes that cannot be computed from the leaves.
+    pub nodes: Vec<NodeValue<F>>,
+}
+
 /// A wrapper of the merkle membership proof.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -1054,6 +1081,56 @@ where
         }
     }
 
+    /// Returns the membership proof of the leaves at positions `uids`, given in
+    /// strictly increasing order, sharing the nodes common to their paths.
+    /// Returns `None` if `uids` is empty or not strictly increasing, or if a
+    /// leaf is empty, invalid or forgotten.
+    pub fn get_multiproof(&self, uids: &[u64]) -> Option<MerkleMultiproof<F>> {
+        if uids.is_empty() || uids.windows(2).any(|w| w[0] >= w[1]) {
+            return None;
+        }
+        // the siblings of the nodes on the paths, indexed by level and position
+        let mut siblings = BTreeMap::new();
+        for &uid in uids.iter() {
+            let proof = self.get_leaf(uid).expect_ok().ok()?.1;
+            let mut pos = uid;
+            for (level, node) in proof.path.nodes.iter().enumerate() {
+                let first = pos - pos % 3;
+                let sibling_pos = match node.pos {
+                    NodePos::Left => [first + 1, first + 2],
+                    NodePos::Middle => [first, first + 2],
+                    NodePos::Right => [first, first + 1],
+                };
+                siblings.insert((level, sibling_pos[0]), node.sibling1);
+                siblings.insert((level, sibling_pos[1]), node.sibling2);
+                pos /= 3;
+            }
+        }
+
+        let mut nodes = vec![];
+        let mut known = uids.to_vec();
+        for level in 0..self.height as usize {
+            let mut parents = vec![];
+            let mut i = 0;
+            while i < known.len() {
+                let parent = known[i] / 3;
+                for child in 3 * parent..3 * parent + 3 {
+                    if i < known.len() && known[i] == child {
+                        i += 1;
+                    } else {
+                        nodes.push(*siblings.get(&(level, child))?);
+                    }
+                }
+                parents.push(parent);
+            }
+            known = parents;
+        }
+        Some(MerkleMultiproof {
+            height: self.height,
+            nodes,
+        })
+    }
+
     /// Trim the leaf at position `i` from memory, if present.
     /// Will not trim if position `i` is the last inserted leaf position.
     /// Return is identical to result if `get_leaf(pos)` were called before this
@@ -1480,6 +1557,122 @@ impl<F: RescueParameter> AccMemberWitness<F> {
     }
 }
 
+/// An append-only accumulator of the leaves of a Merkle tree of a fixed
+/// height, with membership proofs for the accumulated leaves.
+///
+/// The subtrees without any leaf yet take the canonical
+/// [`NodeValue::empty_node_value`], so that the root only depends on the
+/// accumulated leaves, and the proofs of the leaves inserted so far remain
+/// valid w.r.t. the root at the time they are produced.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MerkleAccumulator<F: PrimeField> {
+    tree: MerkleTree<F>,
+}
+
+impl<F: RescueParameter> MerkleAccumulator<F> {
+    /// Create an empty accumulator backed by a tree of height `height`.
+    /// Returns `None` if the capacity of the tree overflows a u64.
+    pub fn new(height: u8) -> Option<Self> {
+        Some(Self {
+            tree: MerkleTree::new(height)?,
+        })
+    }
+
+    /// The root of the tree of the accumulated leaves.
+    pub fn root(&self) -> NodeValue<F> {
+        self.tree.commitment().root_value
+    }
+
+    /// The number of accumulated leaves.
+    pub fn num_leaves(&self) -> u64 {
+        self.tree.num_leaves()
+    }
+
+    /// The height of the underlying tree.
+    pub fn height(&self) -> u8 {
+        self.tree.height()
+    }
+
+    /// Insert `leaf` at the leftmost available slot, and return its index
+    /// along with the updated root.
+    /// Returns an error if the tree is full.
+    pub fn insert(&mut self, leaf: F) -> Result<(u64, NodeValue<F>), PrimitivesError> {
+        let index = self.tree.num_leaves();
+        if index >= self.tree.capacity {
+            return Err(PrimitivesError::ParameterError(format!(
+                "the accumulator of capacity {} is full",
+                self.tree.capacity
+            )));
+        }
+        self.tree.push(leaf);
+        Ok((index, self.root()))
+    }
+
+    /// The proof of membership of the leaf at `index` w.r.t. the current root.
+    /// Returns an error if no leaf was inserted at `index`.
+    pub fn prove_membership(&self, index: u64) -> Result<MembershipProof<F>, PrimitivesError> {
+        if index >= self.tree.num_leaves() {
+            return Err(PrimitivesError::ParameterError(format!(
+                "no leaf at index {}, only {} leaves were inserted",
+                index,
+                self.tree.num_leaves()
+            )));
+        }
+        let (_, MerkleLeafProof { leaf, path }) = self.tree.get_leaf(index).expect_ok()?;
+        Ok(MembershipProof { index, leaf, path })
+    }
+}
+
+/// The proof of membership of a leaf in a [`MerkleAccumulator`].
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    CanonicalSerialize,
+    CanonicalDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(bound = "")]
+pub struct MembershipProof<F: PrimeField> {
+    /// The index of the leaf.
+    pub index: u64,
+    /// The leaf.
+    pub leaf: MerkleLeaf<F>,
+    /// The path from the leaf to the root.
+    pub path: MerklePath<F>,
+}
+
+impl<F: RescueParameter> MembershipProof<F> {
+    /// Verify the membership of the leaf at its index w.r.t. `root`.
+    /// Returns an error if the positions on the path are not those of the
+    /// index, or if the path does not lead to `root`.
+    pub fn verify(&self, root: &NodeValue<F>) -> Result<(), PrimitivesError> {
+        let mut index = self.index;
+        for node in self.path.nodes.iter() {
+            if usize::from(node.pos) as u64 != index % 3 {
+                return Err(PrimitivesError::VerificationError(
+                    "the path does not match the leaf index".to_string(),
+                ));
+            }
+            index /= 3;
+        }
+        if index != 0 {
+            return Err(PrimitivesError::VerificationError(
+                "the leaf index is out of the tree".to_string(),
+            ));
+        }
+        let proof = MerkleLeafProof::new(self.leaf.0, self.path.clone());
+        MerkleTree::check_proof(*root, self.index, &proof).map_err(|_| {
+            PrimitivesError::VerificationError("the path does not lead to the root".to_string())
+        })
+    }
+}
+
 #[cfg(test)]
 mod mt_tests {
 
@@ -1982,6 +2175,135 @@ mod mt_tests {
         MerkleTree::check_proof(mt.root.value(), 0, &proof).unwrap()
     }
 
+    #[test]
+    fn test_get_multiproof() {
+        test_get_multiproof_helper::<Fq254>();
+        test_get_multiproof_helper::<Fq377>();
+        test_get_multiproof_helper::<Fq381>();
+    }
+
+    fn test_get_multiproof_helper<F: RescueParameter>() {
+        let mut mt = MerkleTree::<F>::new(2).unwrap();
+        for i in 0..7u64 {
+            mt.push(F::from(i + 100));
+        }
+        // a single leaf: its path
+        let multiproof = mt.get_multiproof(&[4]).unwrap();
+        let path = mt.get_leaf(4).expect_ok().unwrap().1.path;
+        assert_eq!(multiproof.height, 2);
+        assert_eq!(
+            multiproof.nodes,
+            path.nodes
+                .iter()
+                .flat_map(|node| vec![node.sibling1, node.sibling2])
+                .collect::<Vec<_>>()
+        );
+        // sibling leaves share their parent: 3 nodes instead of 4
+        let multiproof = mt.get_multiproof(&[0, 1]).unwrap();
+        let path_0 = mt.get_leaf(0).expect_ok().unwrap().1.path;
+        assert_eq!(
+            multiproof.nodes,
+            vec![
+                path_0.nodes[0].sibling2,
+                path_0.nodes[1].sibling1,
+                path_0.nodes[1].sibling2
+            ]
+        );
+        // leaves in the same subtree of the root
+        assert_eq!(mt.get_multiproof(&[0, 3, 6]).unwrap().nodes.len(), 6);
+        assert_eq!(mt.get_multiproof(&[0, 1, 2]).unwrap().nodes.len(), 2);
+
+        // unsorted, duplicated, empty or forgotten leaves
+        assert!(mt.get_multiproof(&[]).is_none());
+        assert!(mt.get_multiproof(&[1, 0]).is_none());
+        assert!(mt.get_multiproof(&[1, 1]).is_none());
+        assert!(mt.get_multiproof(&[0, 7]).is_none());
+        assert!(mt.get_multiproof(&[0, 9]).is_none());
+        mt.forget(3);
+        assert!(mt.get_multiproof(&[0, 3]).is_none());
+        assert!(mt.get_multiproof(&[0, 4]).is_some());
+    }
+
+    #[test]
+    fn test_merkle_accumulator() {
+        test_merkle_accumulator_helper::<Fq254>();
+        test_merkle_accumulator_helper::<Fq377>();
+        test_merkle_accumulator_helper::<Fq381>();
+    }
+
+    fn test_merkle_accumulator_helper<F: RescueParameter>() {
+        let mut acc = MerkleAccumulator::<F>::new(3).unwrap();
+        assert_eq!(acc.root(), NodeValue::empty_node_value());
+
+        // the empty subtrees take the canonical empty node value
+        let (index, root) = acc.insert(F::from(100u64)).unwrap();
+        assert_eq!(index, 0);
+        let empty = NodeValue::empty_node_value();
+        let mut expected_root = hash(
+            &NodeValue::empty_node_value(),
+            &NodeValue::from(0),
+            &NodeValue::from_scalar(F::from(100u64)),
+        );
+        for _ in 0..3 {
+            expected_root = hash(&expected_root, &empty, &empty);
+        }
+        assert_eq!(root, expected_root);
+        let proof = acc.prove_membership(0).unwrap();
+        assert!(proof
+            .path
+            .nodes
+            .iter()
+            .all(|node| node.sibling1 == empty && node.sibling2 == empty));
+        assert!(proof.verify(&root).is_ok());
+
+        // the root is updated on each insertion, and matches the Merkle tree of
+        // the same leaves
+        let mut mt = MerkleTree::<F>::new(3).unwrap();
+        mt.push(F::from(100u64));
+        let mut roots = vec![root];
+        for i in 1..10u64 {
+            let (index, root) = acc.insert(F::from(100 + i)).unwrap();
+            mt.push(F::from(100 + i));
+            assert_eq!(index, i);
+            assert_eq!(acc.num_leaves(), i + 1);
+            assert_ne!(root, roots[roots.len() - 1]);
+            assert_eq!(root, mt.commitment().root_value);
+            roots.push(root);
+        }
+        let root = acc.root();
+        for i in 0..10u64 {
+            let proof = acc.prove_membership(i).unwrap();
+            assert_eq!(proof.leaf, MerkleLeaf(F::from(100 + i)));
+            assert!(proof.verify(&root).is_ok());
+            // the proof is w.r.t. the current root only
+            assert!(proof.verify(&roots[0]).is_err());
+        }
+
+        // bad path: wrong leaf, index or path
+        let proof = acc.prove_membership(4).unwrap();
+        let mut bad_proof = proof.clone();
+        bad_proof.leaf = MerkleLeaf(F::from(105u64));
+        assert!(bad_proof.verify(&root).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.index = 5;
+        assert!(bad_proof.verify(&root).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.index += 27;
+        assert!(bad_proof.verify(&root).is_err());
+        let mut bad_proof = proof;
+        bad_proof.path.nodes[1].sibling2 = NodeValue::from(1);
+        assert!(bad_proof.verify(&root).is_err());
+
+        // bad path: no such leaf, or a full accumulator
+        assert!(acc.prove_membership(10).is_err());
+        let mut acc = MerkleAccumulator::<F>::new(1).unwrap();
+        for i in 0..3u64 {
+            assert!(acc.insert(F::from(i)).is_ok());
+        }
+        assert!(acc.insert(F::from(3u64)).is_err());
+        assert_eq!(acc.num_leaves(), 3);
+    }
+
     #[test]
     fn test_tree_extension_attack() {
         test_tree_extension_attack_helper::<Fq254>();
@@ -0,0 +1,223 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implements the Pedersen commitment scheme over a twisted Edwards curve,
+//! whose base field is the scalar field of the proof system, so that the
+//! commitments can be opened in circuits.
+
+use crate::{errors::PrimitivesError, utils::hash_to_curve};
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine, AffineCurve, ProjectiveCurve,
+    TEModelParameters as Parameters,
+};
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+use ark_std::{format, rand::Rng, string::String, vec::Vec};
+use jf_rescue::RescueParameter;
+use jf_utils::{fq_to_fr, fr_to_fq};
+
+/// Domain separator of the generators hashed to the curve.
+const PEDERSEN_GENERATORS_DOMAIN_SEP: &[u8] = b"JF PEDERSEN GENERATORS";
+
+#[derive(Clone, Derivative)]
+#[derivative(Debug(bound = "P: Parameters"), PartialEq(bound = "P: Parameters"))]
+/// Pedersen commitment instance for messages of a fixed length (in base field
+/// elements). The commitment to `msg` with blinding `r` is
+/// `r * H + \sum_i msg_i * G_i`, where the generators `H` and `G_i` are
+/// hashed to the curve: the instances for the same length are identical,
+/// natively and in circuits, and none knows the discrete logarithms between
+/// the generators.
+pub struct PedersenCommitment<P>
+where
+    P: Parameters + Clone,
+{
+    pub(crate) blinding_generator: GroupAffine<P>,
+    pub(crate) msg_generators: Vec<GroupAffine<P>>,
+}
+
+impl<F, P> PedersenCommitment<P>
+where
+    F: RescueParameter,
+    P: Parameters<BaseField = F> + Clone,
+{
+    /// Create a new commitment instance for messages of length `msg_len`.
+    /// The `i`-th generator, the blinding one being the 0-th, is the hash to
+    /// the curve of `i` in little-endian bytes, see [`hash_to_curve`].
+    pub fn new(msg_len: usize) -> Self {
+        let blinding_generator = hash_to_curve(PEDERSEN_GENERATORS_DOMAIN_SEP, &0u64.to_le_bytes());
+        let msg_generators = (1..=msg_len as u64)
+            .map(|i| hash_to_curve(PEDERSEN_GENERATORS_DOMAIN_SEP, &i.to_le_bytes()))
+            .collect();
+        Self {
+            blinding_generator,
+            msg_generators,
+        }
+    }
+
+    /// The length of the committed messages.
+    pub fn msg_len(&self) -> usize {
+        self.msg_generators.len()
+    }
+
+    /// The maximal bit length of the message elements, one less than the bit
+    /// length of the order of the prime subgroup, so that distinct messages
+    /// are distinct scalars and the commitment is binding.
+    pub fn msg_bit_len() -> usize {
+        P::ScalarField::size_in_bits() - 1
+    }
+
+    /// Sample a uniformly random blinding scalar, as a base field element.
+    pub fn rand_blinding<R: Rng>(rng: &mut R) -> F {
+        fr_to_fq::<F, P>(&P::ScalarField::rand(rng))
+    }
+
+    /// Commits to `msg` using blinding `blinding`.
+    /// Return Err(PrimitivesError::ParameterError) if the length of `msg` is
+    /// not `self.msg_len()`, if some message element has more than
+    /// `Self::msg_bit_len()` bits, or if `blinding` has more bits than the
+    /// order of the prime subgroup.
+    pub fn commit(&self, msg: &[F], blinding: &F) -> Result<GroupAffine<P>, PrimitivesError> {
+        if msg.len() != self.msg_len() {
+            return Err(PrimitivesError::ParameterError(format!(
+                "Pedersen commitment error: the message length ({}) must match \
+                instance's message length ({})",
+                msg.len(),
+                self.msg_len(),
+            )));
+        }
+        if let Some(elem) = msg
+            .iter()
+            .find(|elem| elem.into_repr().num_bits() as usize > Self::msg_bit_len())
+        {
+            return Err(PrimitivesError::ParameterError(format!(
+                "Pedersen commitment error: message element {} has more than {} bits",
+                elem,
+                Self::msg_bit_len(),
+            )));
+        }
+        if blinding.into_repr().num_bits() as usize > P::ScalarField::size_in_bits() {
+            return Err(PrimitivesError::ParameterError(
+                "Pedersen commitment error: the blinding is larger than the subgroup order".into(),
+            ));
+        }
+
+        let mut comm = self
+            .blinding_generator
+            .mul(fq_to_fr::<F, P>(blinding).into_repr());
+        for (generator, elem) in self.msg_generators.iter().zip(msg.iter()) {
+            comm += generator.mul(fq_to_fr::<F, P>(elem).into_repr());
+        }
+        Ok(comm.into_affine())
+    }
+
+    /// Verifies `commitment` against `msg` and `blinding`.
+    /// Returns Ok(()) on success. Otherwise, returns
+    /// PrimitivesError::ParameterError if `msg` or `blinding` are invalid
+    /// inputs to [`Self::commit`], and PrimitivesError::VerificationError if
+    /// commitment is not valid.
+    pub fn verify(
+        &self,
+        msg: &[F],
+        blinding: &F,
+        commitment: &GroupAffine<P>,
+    ) -> Result<(), PrimitivesError> {
+        if self.commit(msg, blinding)? == *commitment {
+            Ok(())
+        } else {
+            Err(PrimitivesError::VerificationError(String::from(
+                "Pedersen commitment verification failed",
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ed_on_bls12_377::EdwardsParameters as Param377;
+    use ark_ed_on_bls12_381::EdwardsParameters as Param381;
+    use ark_ed_on_bls12_381_bandersnatch::EdwardsParameters as Param381b;
+    use ark_ed_on_bn254::EdwardsParameters as Param254;
+    use ark_ff::One;
+
+    #[test]
+    fn test_pedersen_commitment() {
+        test_pedersen_commitment_helper::<_, Param377>();
+        test_pedersen_commitment_helper::<_, Param381>();
+        test_pedersen_commitment_helper::<_, Param381b>();
+        test_pedersen_commitment_helper::<_, Param254>();
+    }
+
+    fn test_pedersen_commitment_helper<F, P>()
+    where
+        F: RescueParameter,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let pedersen = PedersenCommitment::<P>::new(3);
+        let msg = [F::from(1u64), F::from(2u64), F::from(3u64)];
+        let blinding = PedersenCommitment::<P>::rand_blinding(&mut rng);
+        let comm = pedersen.commit(&msg, &blinding).unwrap();
+        assert!(pedersen.verify(&msg, &blinding, &comm).is_ok());
+
+        // the generators are deterministic, independent of the length, in the
+        // prime subgroup and distinct
+        assert_eq!(PedersenCommitment::<P>::new(3), pedersen);
+        let longer = PedersenCommitment::<P>::new(5);
+        assert_eq!(longer.blinding_generator, pedersen.blinding_generator);
+        assert_eq!(longer.msg_generators[..3], pedersen.msg_generators[..]);
+        assert_eq!(
+            pedersen.blinding_generator,
+            hash_to_curve::<F, P>(PEDERSEN_GENERATORS_DOMAIN_SEP, &0u64.to_le_bytes())
+        );
+        assert_eq!(
+            pedersen.msg_generators[2],
+            hash_to_curve::<F, P>(PEDERSEN_GENERATORS_DOMAIN_SEP, &3u64.to_le_bytes())
+        );
+        let mut generators = longer.msg_generators.clone();
+        generators.push(longer.blinding_generator);
+        for (i, g) in generators.iter().enumerate() {
+            assert!(g.is_on_curve());
+            assert!(g.is_in_correct_subgroup_assuming_on_curve());
+            assert!(generators[..i].iter().all(|h| h != g));
+        }
+
+        // binding: another opening fails
+        let bad_msg = [F::from(1u64), F::from(2u64), F::from(4u64)];
+        assert!(pedersen.verify(&bad_msg, &blinding, &comm).is_err());
+        assert!(pedersen
+            .verify(&msg, &(blinding + F::one()), &comm)
+            .is_err());
+        // hiding: a fresh blinding gives another commitment
+        let other_blinding = PedersenCommitment::<P>::rand_blinding(&mut rng);
+        assert_ne!(pedersen.commit(&msg, &other_blinding).unwrap(), comm);
+        // the commitment is additively homomorphic
+        let sum_msg = [F::from(2u64), F::from(4u64), F::from(7u64)];
+        let sum_comm = pedersen
+            .commit(
+                &sum_msg,
+                &fr_to_fq::<F, P>(
+                    &(fq_to_fr::<F, P>(&blinding) + fq_to_fr::<F, P>(&other_blinding)),
+                ),
+            )
+            .unwrap();
+        let other_comm = pedersen.commit(&bad_msg, &other_blinding).unwrap();
+        assert_eq!(
+            sum_comm,
+            (comm.into_projective() + other_comm.into_projective()).into_affine()
+        );
+
+        // bad inputs
+        assert!(pedersen.commit(&msg[..2], &blinding).is_err());
+        let large_elem = F::from(2u64).pow([PedersenCommitment::<P>::msg_bit_len() as u64]);
+        assert!(pedersen
+            .commit(&[F::one(), F::one(), large_elem], &blinding)
+            .is_err());
+        assert!(pedersen
+            .commit(&[F::one(), F::one(), large_elem - F::one()], &blinding)
+            .is_ok());
+        assert!(pedersen.commit(&msg, &-F::one()).is_err());
+    }
+}
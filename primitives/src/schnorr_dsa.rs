@@ -311,7 +311,7 @@ where
     P: Parameters<BaseField = F> + Clone,
 {
     #[allow(non_snake_case)]
-    fn challenge(
+    pub(crate) fn challenge(
         &self,
         hash: &Permutation<F>,
         R: &GroupProjective<P>,
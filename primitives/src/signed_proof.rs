@@ -0,0 +1,194 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Plonk proofs signed by their submitters with a Schnorr signature.
+
+use crate::{
+    errors::PrimitivesError,
+    schnorr_dsa::{KeyPair, Signature, VerKey},
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters,
+    TEModelParameters as Parameters,
+};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{format, vec, vec::Vec};
+use jf_plonk::{
+    circuit::customized::ecc::SWToTEConParam,
+    proof_system::{
+        structs::{Proof, VerifyingKey},
+        PlonkKzgSnark, Snark,
+    },
+    transcript::PlonkTranscript,
+};
+use jf_rescue::RescueParameter;
+use jf_utils::bytes_to_field_elements;
+
+/// The message signed by the submitter of `proof`: the byte length of the
+/// canonical serialization of the proof, followed by the serialization packed
+/// into field elements.
+pub fn proof_message<E, F>(proof: &Proof<E>) -> Result<Vec<F>, PrimitivesError>
+where
+    E: PairingEngine,
+    F: PrimeField,
+{
+    let mut bytes = Vec::new();
+    proof
+        .serialize(&mut bytes)
+        .map_err(|e| PrimitivesError::InternalError(format!("{}", e)))?;
+    let mut msg = vec![F::from(bytes.len() as u64)];
+    msg.extend(bytes_to_field_elements::<_, F>(bytes));
+    Ok(msg)
+}
+
+/// Sign `proof` (see [`proof_message`]) as its submitter.
+pub fn sign_proof<E, P>(
+    keypair: &KeyPair<P>,
+    proof: &Proof<E>,
+) -> Result<Signature<P>, PrimitivesError>
+where
+    E: PairingEngine,
+    P: Parameters + Clone,
+    P::BaseField: RescueParameter,
+{
+    Ok(keypair.sign(&proof_message::<E, P::BaseField>(proof)?))
+}
+
+/// Verify that `proof` is signed by `submitter` with `signature`, then verify
+/// the proof against `vk` and `public_input`. The signature, which is cheaper
+/// to check, is verified first.
+/// Return a verification error telling whether the signature or the proof is
+/// invalid.
+pub fn verify_signed<E, F, P, S, T>(
+    vk: &VerifyingKey<E>,
+    public_input: &[E::Fr],
+    proof: &Proof<E>,
+    signature: &Signature<S>,
+    submitter: &VerKey<S>,
+) -> Result<(), PrimitivesError>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + Clone,
+    S: Parameters + Clone,
+    S::BaseField: RescueParameter,
+    T: PlonkTranscript<F>,
+{
+    submitter
+        .verify(&proof_message::<E, S::BaseField>(proof)?, signature)
+        .map_err(|e| {
+            PrimitivesError::VerificationError(format!("invalid submitter signature: {}", e))
+        })?;
+    PlonkKzgSnark::<E>::verify::<T>(vk, public_input, proof, None)
+        .map_err(|e| PrimitivesError::VerificationError(format!("invalid proof: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fq as Fq381};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_ed_on_bls12_381::EdwardsParameters as Param381;
+    use ark_ed_on_bn254::EdwardsParameters as Param254;
+    use ark_std::{string::ToString, UniformRand};
+    use jf_plonk::{
+        circuit::{Arithmetization, Circuit, PlonkCircuit},
+        transcript::StandardTranscript,
+    };
+
+    #[test]
+    fn test_verify_signed() {
+        test_verify_signed_helper::<Bn254, Fq254, _, Param254>();
+        test_verify_signed_helper::<Bls12_381, Fq381, _, Param381>();
+    }
+
+    fn test_verify_signed_helper<E, F, P, S>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        S: Parameters + Clone,
+        S::BaseField: RescueParameter,
+    {
+        let rng = &mut ark_std::test_rng();
+        // a * b = c, with c public
+        let mut circuit: PlonkCircuit<E::Fr> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(E::Fr::from(3u32)).unwrap();
+        let b = circuit.create_variable(E::Fr::from(4u32)).unwrap();
+        let c = circuit.create_public_variable(E::Fr::from(12u32)).unwrap();
+        circuit.mul_gate(a, b, c).unwrap();
+        circuit.finalize_for_arithmetization().unwrap();
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size().unwrap(), rng).unwrap();
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit).unwrap();
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(rng, &circuit, &pk, None)
+            .unwrap();
+        let public_input = [E::Fr::from(12u32)];
+
+        // a valid signed proof
+        let submitter: KeyPair<S> = KeyPair::generate(rng);
+        let signature = sign_proof(&submitter, &proof).unwrap();
+        assert!(verify_signed::<_, _, _, _, StandardTranscript>(
+            &vk,
+            &public_input,
+            &proof,
+            &signature,
+            submitter.ver_key_ref()
+        )
+        .is_ok());
+
+        // a bad signature: by another submitter, or over another proof
+        let other_submitter: KeyPair<S> = KeyPair::generate(rng);
+        let err = verify_signed::<_, _, _, _, StandardTranscript>(
+            &vk,
+            &public_input,
+            &proof,
+            &signature,
+            other_submitter.ver_key_ref(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("invalid submitter signature"));
+        let other_proof =
+            PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(rng, &circuit, &pk, None)
+                .unwrap();
+        let other_signature = sign_proof(&submitter, &other_proof).unwrap();
+        assert!(verify_signed::<_, _, _, _, StandardTranscript>(
+            &vk,
+            &public_input,
+            &proof,
+            &other_signature,
+            submitter.ver_key_ref()
+        )
+        .is_err());
+        let bad_signature: Signature<S> = KeyPair::generate(rng).sign(&[S::BaseField::rand(rng)]);
+        assert!(verify_signed::<_, _, _, _, StandardTranscript>(
+            &vk,
+            &public_input,
+            &proof,
+            &bad_signature,
+            submitter.ver_key_ref()
+        )
+        .is_err());
+
+        // a valid signature over an invalid proof
+        let bad_public_input = [E::Fr::from(13u32)];
+        let err = verify_signed::<_, _, _, _, StandardTranscript>(
+            &vk,
+            &bad_public_input,
+            &proof,
+            &signature,
+            submitter.ver_key_ref(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("invalid proof"));
+
+        // the message depends on the whole proof
+        assert_ne!(
+            proof_message::<E, S::BaseField>(&proof).unwrap(),
+            proof_message::<E, S::BaseField>(&other_proof).unwrap()
+        );
+    }
+}
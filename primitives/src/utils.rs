@@ -4,11 +4,26 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
+//! Utilities of the primitives, including hashing bytes to the field and to
+//! the embedded curve with Rescue.
+
 use crate::{elgamal, schnorr_dsa};
-use ark_ec::{ProjectiveCurve, TEModelParameters as Parameters};
-use ark_ff::PrimeField;
-use ark_std::vec::Vec;
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine, AffineCurve, ProjectiveCurve,
+    TEModelParameters as Parameters,
+};
+use ark_ff::{PrimeField, Zero};
+use ark_std::{vec, vec::Vec};
 use jf_plonk::circuit::Variable;
+use jf_rescue::{rescue_sponge, RescueParameter};
+use jf_utils::bytes_to_field_elements;
+
+/// Domain separator of [`hash_to_field`], prepended to the domain of the
+/// caller.
+const HASH_TO_FIELD_DOMAIN_SEP: &[u8] = b"JF RESCUE HASH TO FIELD";
+/// Domain separator of [`hash_to_curve`], prepended to the domain of the
+/// caller.
+const HASH_TO_CURVE_DOMAIN_SEP: &[u8] = b"JF RESCUE HASH TO CURVE";
 
 impl<F, P> From<&schnorr_dsa::VerKey<P>> for (F, F)
 where
@@ -41,3 +56,224 @@ pub(crate) fn pad_with(vec: &mut Vec<Variable>, multiple: usize, var: Variable)
     };
     vec.resize(new_len, var);
 }
+
+/// The field elements absorbed by [`hash_to_field`] for `domain` and `msg`,
+/// which a circuit hashes with the Rescue sponge gadget to get the same
+/// output: the length then the bytes of the separated domain, then the
+/// length then the bytes of `msg`. The bytes are packed by chunks of
+/// `F::size_in_bits() / 8` bytes, in little-endian, so that no chunk is
+/// reduced modulo the field order.
+pub fn hash_to_field_input<F: PrimeField>(domain: &[u8], msg: &[u8]) -> Vec<F> {
+    encode_with_domain(HASH_TO_FIELD_DOMAIN_SEP, domain, msg)
+}
+
+/// Hash `msg` to a field element with the Rescue sponge, under `domain`, so
+/// that the hashes in different domains are independent. The output equals
+/// the Rescue sponge hash of [`hash_to_field_input`], natively and in
+/// circuits.
+pub fn hash_to_field<F: RescueParameter>(domain: &[u8], msg: &[u8]) -> F {
+    rescue_sponge(&hash_to_field_input::<F>(domain, msg))
+}
+
+/// Hash `msg` to a point of the prime order subgroup of the embedded curve
+/// with the Rescue sponge, under `domain`. None knows the discrete logarithm
+/// of the output w.r.t. any other point, e.g. to derive generators.
+///
+/// The point is found by try-and-increment: the hash of `msg` and of an
+/// increasing counter is the x-coordinate of a curve point for about half
+/// of the counters, whose multiple by the cofactor is the output, unless it
+/// is the neutral point. The running time thus depends on `msg`, which is
+/// meant to be public.
+pub fn hash_to_curve<F, P>(domain: &[u8], msg: &[u8]) -> GroupAffine<P>
+where
+    F: RescueParameter,
+    P: Parameters<BaseField = F> + Clone,
+{
+    let seed = rescue_sponge(&encode_with_domain::<F>(
+        HASH_TO_CURVE_DOMAIN_SEP,
+        domain,
+        msg,
+    ));
+    let mut counter = 0u64;
+    loop {
+        let x = rescue_sponge(&[seed, F::from(counter)]);
+        if let Some(point) = GroupAffine::<P>::get_point_from_x(x, false) {
+            let point = point.mul_by_cofactor();
+            if !point.is_zero() {
+                return point;
+            }
+        }
+        counter += 1;
+    }
+}
+
+// Encode `domain_sep || domain` and `msg` as field elements, each prefixed by
+// its length so that the boundary between the domain and the message is not
+// ambiguous.
+fn encode_with_domain<F: PrimeField>(domain_sep: &[u8], domain: &[u8], msg: &[u8]) -> Vec<F> {
+    let full_domain = [domain_sep, domain].concat();
+    let mut elems = vec![F::from(full_domain.len() as u64)];
+    elems.extend(bytes_to_field_elements::<_, F>(&full_domain));
+    elems.push(F::from(msg.len() as u64));
+    elems.extend(bytes_to_field_elements::<_, F>(msg));
+    elems
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ed_on_bls12_377::EdwardsParameters as Param377;
+    use ark_ed_on_bls12_381::EdwardsParameters as Param381;
+    use ark_ed_on_bls12_381_bandersnatch::EdwardsParameters as Param381b;
+    use ark_ed_on_bn254::{EdwardsParameters as Param254, Fq as Fq254};
+    use ark_ff::{field_new, Field, FpParameters, One};
+    use jf_rescue::Permutation;
+
+    #[test]
+    fn test_hash_to_field() {
+        test_hash_to_field_helper::<_, Param377>();
+        test_hash_to_field_helper::<_, Param381>();
+        test_hash_to_field_helper::<_, Param381b>();
+        test_hash_to_field_helper::<_, Param254>();
+    }
+
+    fn test_hash_to_field_helper<F, P>()
+    where
+        F: RescueParameter,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        // known vectors of the encoding: the base fields of the embedded
+        // curves take chunks of 31 bytes
+        let full_domain_len = HASH_TO_FIELD_DOMAIN_SEP.len() + 4;
+        let input = hash_to_field_input::<F>(b"test", &[1, 2, 3]);
+        assert_eq!(input.len(), 4);
+        assert_eq!(input[0], F::from(full_domain_len as u64));
+        assert_eq!(input[2..], [F::from(3u64), F::from(0x030201u64)]);
+        let input = hash_to_field_input::<F>(b"test", &[0xff; 32]);
+        assert_eq!(
+            input[2..],
+            [
+                F::from(32u64),
+                F::from(2u64).pow([248]) - F::one(),
+                F::from(0xffu64)
+            ]
+        );
+        let input = hash_to_field_input::<F>(b"", b"");
+        assert_eq!(input.len(), 3);
+        assert_eq!(input[2], F::zero());
+
+        // the hash is the Rescue sponge hash of the encoding
+        let hash = hash_to_field::<F>(b"test", &[1, 2, 3]);
+        let mut padded_input = hash_to_field_input::<F>(b"test", &[1, 2, 3]);
+        padded_input.extend([F::one(), F::zero()]);
+        assert_eq!(
+            hash,
+            Permutation::default()
+                .sponge_no_padding(&padded_input, 1)
+                .unwrap()[0]
+        );
+        assert_eq!(hash, hash_to_field::<F>(b"test", &[1, 2, 3]));
+
+        // domain separation, including the boundary between domain and message
+        assert_ne!(hash, hash_to_field::<F>(b"test2", &[1, 2, 3]));
+        assert_ne!(hash, hash_to_field::<F>(b"test", &[1, 2, 4]));
+        assert_ne!(
+            hash_to_field::<F>(b"ab", b"c"),
+            hash_to_field::<F>(b"a", b"bc")
+        );
+        assert_ne!(
+            hash_to_field::<F>(b"test", &[1, 2, 3]),
+            hash_to_field::<F>(b"test", &[1, 2, 3, 0])
+        );
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        test_hash_to_curve_helper::<_, Param377>();
+        test_hash_to_curve_helper::<_, Param381>();
+        test_hash_to_curve_helper::<_, Param381b>();
+        test_hash_to_curve_helper::<_, Param254>();
+    }
+
+    fn test_hash_to_curve_helper<F, P>()
+    where
+        F: RescueParameter,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut points = Vec::new();
+        for msg in [&b""[..], &b"a"[..], &b"b"[..], &[0u8; 100][..]].iter() {
+            let point = hash_to_curve::<F, P>(b"test", msg);
+            // the point is in the prime order subgroup: it is killed by the
+            // order of the subgroup, and isn't the neutral point
+            assert!(point.is_on_curve());
+            assert!(point.is_in_correct_subgroup_assuming_on_curve());
+            assert!(point
+                .mul(<P::ScalarField as PrimeField>::Params::MODULUS)
+                .is_zero());
+            assert!(!point.is_zero());
+            assert_eq!(point, hash_to_curve::<F, P>(b"test", msg));
+            points.push(point);
+        }
+        for (i, point) in points.iter().enumerate() {
+            assert!(points[..i].iter().all(|other| other != point));
+        }
+        assert_ne!(
+            hash_to_curve::<F, P>(b"test", b"a"),
+            hash_to_curve::<F, P>(b"other", b"a")
+        );
+        // the curve hash is separated from the field hash
+        assert_ne!(
+            hash_to_curve::<F, P>(b"test", b"a").x,
+            hash_to_field::<F>(b"test", b"a")
+        );
+    }
+
+    // The outputs are pinned, so that a change of the encoding, of the
+    // sponge or of the search of the curve point is caught.
+    #[test]
+    fn test_hash_vectors() {
+        assert_eq!(
+            hash_to_field::<Fq254>(b"test", &[1, 2, 3]),
+            field_new!(
+                Fq254,
+                "5130921464174380500821084292979612687670939467464158353766454486566948688975"
+            )
+        );
+        assert_eq!(
+            hash_to_field::<Fq254>(b"", b""),
+            field_new!(
+                Fq254,
+                "20057054818383191570108024897846149871121082008618770343212342591323370264155"
+            )
+        );
+
+        // found with the counter 4
+        assert_eq!(
+            hash_to_curve::<_, Param254>(b"test", b""),
+            GroupAffine::<Param254>::new(
+                field_new!(
+                    Fq254,
+                    "9544015581792940766331328772577605851455912001669104131603208469379508443261"
+                ),
+                field_new!(
+                    Fq254,
+                    "8734867394968442537864445809332952864512846003550542925809810747360106244585"
+                ),
+            )
+        );
+        // found with the counter 1
+        assert_eq!(
+            hash_to_curve::<_, Param254>(b"test", b"a"),
+            GroupAffine::<Param254>::new(
+                field_new!(
+                    Fq254,
+                    "3592350557580914397710032103371078200545448287519860609199835702395863058020"
+                ),
+                field_new!(
+                    Fq254,
+                    "14257270874225279506517180367130614261483558498519292717696996865543039906695"
+                ),
+            )
+        );
+    }
+}
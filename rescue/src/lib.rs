@@ -487,6 +487,13 @@ impl<F: RescueParameter> Permutation<F> {
     }
 }
 
+/// Sponge hash of an input of any length into a single field element, with
+/// the default Rescue permutation: the input is padded with a One then with
+/// zeros up to a multiple of RATE before being absorbed.
+pub fn rescue_sponge<F: RescueParameter>(input: &[F]) -> F {
+    Permutation::default().sponge_with_padding(input, 1)[0]
+}
+
 impl<F: RescueParameter> Permutation<F> {
     /// Pseudorandom function for Bls12_381 scalar field. It allows unrestricted
     /// variable length input and number of output elements